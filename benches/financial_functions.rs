@@ -1,7 +1,11 @@
 use criterion::{black_box, criterion_group, criterion_main, Criterion, BenchmarkId, Throughput};
 use datafusion::prelude::*;
 use datafusion::execution::context::SessionContext;
+use datafusion::arrow::array::{ArrayRef, Float64Array, Int64Array};
+use datafusion::logical_expr::WindowUDFImpl;
+use datafusion_functions_financial::functions::{macd::MacdIndicator, rsi::RelativeStrengthIndex};
 use datafusion_functions_financial::register_financial_functions;
+use std::sync::Arc;
 use std::time::{Duration, Instant};
 
 async fn benchmark_function(function_name: &str, size: usize, window: usize) -> datafusion::error::Result<f64> {
@@ -152,6 +156,39 @@ fn criterion_benchmark(c: &mut Criterion) {
     }
 
     combined_group.finish();
+
+    // Evaluator throughput without SQL planning or data loading
+    let mut kernel_group = c.benchmark_group("kernels");
+    kernel_group.sample_size(20);
+
+    for &size in [100_000, 1_000_000].iter() {
+        kernel_group.throughput(Throughput::Elements(size as u64));
+
+        let mut price = 100.0;
+        let prices: ArrayRef = Arc::new(Float64Array::from(
+            (0..size)
+                .map(|i| {
+                    price += ((i as f64 * 0.1).sin() + (i as f64 * 0.05).cos()) * 2.0;
+                    price
+                })
+                .collect::<Vec<_>>(),
+        ));
+        let periods: ArrayRef = Arc::new(Int64Array::from(vec![14; size]));
+
+        let rsi_args = vec![Arc::clone(&prices), periods];
+        kernel_group.bench_with_input(BenchmarkId::new("rsi_14", size), &rsi_args, |b, args| {
+            let rsi = RelativeStrengthIndex::new();
+            b.iter(|| rsi.partition_evaluator().unwrap().evaluate_all(black_box(args), size).unwrap());
+        });
+
+        let macd_args = vec![prices];
+        kernel_group.bench_with_input(BenchmarkId::new("macd", size), &macd_args, |b, args| {
+            let macd = MacdIndicator::new();
+            b.iter(|| macd.partition_evaluator().unwrap().evaluate_all(black_box(args), size).unwrap());
+        });
+    }
+
+    kernel_group.finish();
 }
 
 criterion_group!(benches, criterion_benchmark);
@@ -38,7 +38,7 @@ async fn main() -> datafusion::error::Result<()> {
         AssetClass::Crypto,
         PolygonDataType::DayAggs,
         test_date,
-        None
+        &[]
     ).await {
         Ok(df) => {
             println!("✅ Successfully loaded local crypto data!");
@@ -21,7 +21,7 @@ async fn main() -> datafusion::error::Result<()> {
                 AssetClass::Stocks, 
                 PolygonDataType::MinuteAggs, 
                 test_date, 
-                Some("AAPL")
+                &["AAPL"]
             ).await {
                 Ok(df) => {
                     println!("✅ Successfully loaded AAPL minute data");
@@ -53,7 +53,7 @@ async fn main() -> datafusion::error::Result<()> {
                 AssetClass::Crypto,
                 PolygonDataType::DayAggs,
                 NaiveDate::from_ymd_opt(2013, 11, 1).unwrap(), // Known available date
-                None
+                &[]
             ).await {
                 Ok(df) => {
                     println!("✅ Successfully loaded crypto data");
@@ -81,7 +81,7 @@ async fn main() -> datafusion::error::Result<()> {
             
             // 3. Options Data
             println!("\n📋 Loading Options data...");
-            match client.load_data(AssetClass::Options, PolygonDataType::DayAggs, test_date, Some("AAPL")).await {
+            match client.load_data(AssetClass::Options, PolygonDataType::DayAggs, test_date, &["AAPL"]).await {
                 Ok(df) => {
                     println!("✅ Successfully loaded AAPL options data");
                     df.clone().limit(0, Some(3))?.show().await?;
@@ -91,7 +91,7 @@ async fn main() -> datafusion::error::Result<()> {
             
             // 4. Forex Data
             println!("\n💱 Loading Forex data...");
-            match client.load_data(AssetClass::Forex, PolygonDataType::MinuteAggs, test_date, Some("EUR/USD")).await {
+            match client.load_data(AssetClass::Forex, PolygonDataType::MinuteAggs, test_date, &["EUR/USD"]).await {
                 Ok(df) => {
                     println!("✅ Successfully loaded EUR/USD forex data");
                     df.clone().limit(0, Some(3))?.show().await?;
@@ -101,7 +101,7 @@ async fn main() -> datafusion::error::Result<()> {
             
             // 5. Futures Data
             println!("\n🌾 Loading Futures data...");
-            match client.load_data(AssetClass::Futures, PolygonDataType::DayAggs, test_date, Some("ES")).await {
+            match client.load_data(AssetClass::Futures, PolygonDataType::DayAggs, test_date, &["ES"]).await {
                 Ok(df) => {
                     println!("✅ Successfully loaded ES futures data");
                     df.clone().limit(0, Some(3))?.show().await?;
@@ -111,7 +111,7 @@ async fn main() -> datafusion::error::Result<()> {
             
             // 6. Indices Data
             println!("\n📊 Loading Indices data...");
-            match client.load_data(AssetClass::Indices, PolygonDataType::DayAggs, test_date, Some("SPY")).await {
+            match client.load_data(AssetClass::Indices, PolygonDataType::DayAggs, test_date, &["SPY"]).await {
                 Ok(df) => {
                     println!("✅ Successfully loaded SPY index data");
                     df.clone().limit(0, Some(3))?.show().await?;
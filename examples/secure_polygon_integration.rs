@@ -14,9 +14,9 @@ async fn main() -> datafusion::error::Result<()> {
             println!("\n🔍 Discovering available S3 structure...");
             
             // Discover the root structure
-            match client.list_available_files("").await {
+            match client.list_directories("").await {
                 Ok(files) => {
-                    println!("📁 Root files/directories:");
+                    println!("📁 Root directories:");
                     for file in files.iter().take(10) {
                         println!("   {}", file);
                     }
@@ -15,7 +15,7 @@ async fn main() -> datafusion::error::Result<()> {
         AssetClass::Crypto,
         PolygonDataType::DayAggs,
         test_date,
-        None
+        &[]
     ).await {
         Ok(df) => {
             println!("✅ Successfully loaded data for validation");
@@ -74,7 +74,7 @@ async fn main() -> datafusion::error::Result<()> {
     println!("   ✅ Value range validation");
     println!("   ✅ Logic consistency checks");
     println!("   ✅ Timestamp gap detection");
-    println!("   ✅ Weekend data filtering (for day aggregates)");
+    println!("   ✅ Non-trading day filtering (for day aggregates)");
     println!("   ✅ Comprehensive reporting");
 
     Ok(())
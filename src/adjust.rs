@@ -0,0 +1,300 @@
+//! Split and dividend adjustment of OHLCV rows
+//!
+//! Flat files carry raw traded prices, so a 4-for-1 split shows up as a 75%
+//! overnight drop and every indicator spanning it is wrong. [`adjust_prices`]
+//! back-adjusts history: the latest prices are unchanged and every row before
+//! a corporate action is scaled so the series is continuous across it.
+
+use std::collections::{BTreeMap, HashMap};
+
+use chrono::{Datelike, NaiveDate};
+use datafusion::arrow::array::{Array, AsArray};
+use datafusion::arrow::compute::cast;
+use datafusion::arrow::datatypes::{DataType, Float64Type, Int64Type, TimeUnit};
+use datafusion::dataframe::DataFrame;
+use datafusion::error::Result;
+use datafusion::functions_aggregate::first_last::last_value_udaf;
+use datafusion::logical_expr::{cast as cast_expr, when, Expr, ExprFunctionExt};
+use datafusion::prelude::{col, lit};
+use serde::{Deserialize, Serialize};
+
+use crate::bars::OhlcvColumns;
+
+/// Nanoseconds per day
+const DAY_NANOS: i64 = 86_400_000_000_000;
+
+/// Shift from UTC to the US exchange date
+///
+/// Four hours back maps every US equity session (04:00 to 20:00 ET) and
+/// midnight-stamped daily bars onto their New York date, in both standard
+/// and daylight time.
+const EXCHANGE_DATE_OFFSET_NANOS: i64 = 4 * 3_600_000_000_000;
+
+/// Days from 0001-01-01 to 1970-01-01
+const UNIX_EPOCH_DAYS_FROM_CE: i64 = 719_163;
+
+/// A stock split: `split_from` old shares became `split_to` new ones
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Split {
+    pub ticker: String,
+    /// First day trading at the new share count
+    pub execution_date: NaiveDate,
+    pub split_from: f64,
+    pub split_to: f64,
+}
+
+/// A cash dividend of `cash_amount` per share
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Dividend {
+    pub ticker: String,
+    /// First day trading without the dividend
+    pub ex_dividend_date: NaiveDate,
+    pub cash_amount: f64,
+}
+
+/// Splits and dividends used to adjust prices
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct CorporateActions {
+    pub splits: Vec<Split>,
+    pub dividends: Vec<Dividend>,
+}
+
+/// Which corporate actions [`adjust_prices`] accounts for
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AdjustmentMode {
+    /// Scale prices and volumes across splits only
+    SplitOnly,
+    /// Also reinvest dividends: prices before each ex-date are scaled by
+    /// `1 - dividend / previous close`, so returns include the payout
+    TotalReturn,
+}
+
+/// One action's effect on the rows dated before it
+#[derive(Debug, Clone, Copy)]
+struct Factor {
+    day: i64,
+    price: f64,
+    volume: f64,
+}
+
+/// Back-adjust the OHLCV rows of `df` for `actions`, using Polygon column names
+pub async fn adjust_prices(df: DataFrame, actions: &CorporateActions, mode: AdjustmentMode) -> Result<DataFrame> {
+    adjust_prices_with_columns(df, actions, mode, &OhlcvColumns::default()).await
+}
+
+/// Back-adjust the OHLCV rows of `df` for `actions`
+///
+/// Open, high, low, close and VWAP (when `columns.vwap` is set) are
+/// multiplied by the adjustment factor of their date, and volume is divided
+/// by the split part of it, as Float64. Rows are dated on the US exchange
+/// calendar. Without a `columns.ticker` column, every action applies to
+/// every row. Dividends without a close before their ex-date in `df` are
+/// ignored.
+pub async fn adjust_prices_with_columns(
+    df: DataFrame,
+    actions: &CorporateActions,
+    mode: AdjustmentMode,
+    columns: &OhlcvColumns,
+) -> Result<DataFrame> {
+    let day = exchange_day(columns);
+
+    let mut factors: BTreeMap<Option<String>, Vec<Factor>> = BTreeMap::new();
+    let key = |ticker: &str| columns.ticker.as_ref().map(|_| ticker.to_string());
+    for split in actions.splits.iter().filter(|s| s.split_from > 0.0 && s.split_to > 0.0) {
+        let ratio = split.split_from / split.split_to;
+        factors.entry(key(&split.ticker)).or_default().push(Factor {
+            day: epoch_days(split.execution_date),
+            price: ratio,
+            volume: 1.0 / ratio,
+        });
+    }
+    if mode == AdjustmentMode::TotalReturn && !actions.dividends.is_empty() {
+        let closes = daily_closes(df.clone(), &day, columns).await?;
+        for dividend in &actions.dividends {
+            let ex_day = epoch_days(dividend.ex_dividend_date);
+            let previous_close = closes
+                .get(&key(&dividend.ticker))
+                .and_then(|closes| closes.range(..ex_day).next_back())
+                .map(|(_, close)| *close);
+            let Some(close) = previous_close.filter(|close| *close > dividend.cash_amount) else {
+                continue;
+            };
+            factors.entry(key(&dividend.ticker)).or_default().push(Factor {
+                day: ex_day,
+                price: 1.0 - dividend.cash_amount / close,
+                volume: 1.0,
+            });
+        }
+    }
+
+    let price_factor = factor_expr(&factors, &day, columns, |f| f.price)?;
+    let volume_factor = factor_expr(&factors, &day, columns, |f| f.volume)?;
+
+    let mut prices = vec![&columns.open, &columns.high, &columns.low, &columns.close];
+    prices.extend(&columns.vwap);
+
+    let output = df
+        .schema()
+        .columns()
+        .into_iter()
+        .map(|column| {
+            let name = column.name.clone();
+            let value = Expr::Column(column);
+            if prices.contains(&&name) {
+                (cast_expr(value, DataType::Float64) * price_factor.clone()).alias(name)
+            } else if name == columns.volume {
+                (cast_expr(value, DataType::Float64) * volume_factor.clone()).alias(name)
+            } else {
+                value
+            }
+        })
+        .collect::<Vec<_>>();
+
+    df.select(output)
+}
+
+/// Days since the Unix epoch of `date`
+fn epoch_days(date: NaiveDate) -> i64 {
+    date.num_days_from_ce() as i64 - UNIX_EPOCH_DAYS_FROM_CE
+}
+
+/// US exchange date of each row, as days since the Unix epoch
+fn exchange_day(columns: &OhlcvColumns) -> Expr {
+    let nanos = cast_expr(
+        cast_expr(col(columns.timestamp.as_str()), DataType::Timestamp(TimeUnit::Nanosecond, None)),
+        DataType::Int64,
+    );
+    (nanos - lit(EXCHANGE_DATE_OFFSET_NANOS)) / lit(DAY_NANOS)
+}
+
+/// Cumulative factor of every action dated after each row, 1.0 for rows
+/// after the last action
+fn factor_expr(
+    factors: &BTreeMap<Option<String>, Vec<Factor>>,
+    day: &Expr,
+    columns: &OhlcvColumns,
+    value: impl Fn(&Factor) -> f64,
+) -> Result<Expr> {
+    let mut branches = Vec::new();
+    for (ticker, actions) in factors {
+        let mut actions = actions.clone();
+        actions.sort_by_key(|f| f.day);
+
+        // Rows before the k-th action take the product of factors k..n
+        let mut cumulative = actions.iter().map(&value).collect::<Vec<_>>();
+        for k in (0..cumulative.len().saturating_sub(1)).rev() {
+            cumulative[k] *= cumulative[k + 1];
+        }
+
+        for (action, factor) in actions.iter().zip(cumulative) {
+            let mut condition = day.clone().lt(lit(action.day));
+            if let (Some(column), Some(ticker)) = (&columns.ticker, ticker) {
+                condition = col(column.as_str()).eq(lit(ticker.as_str())).and(condition);
+            }
+            branches.push((condition, lit(factor)));
+        }
+    }
+
+    let mut branches = branches.into_iter();
+    let Some((condition, factor)) = branches.next() else {
+        return Ok(lit(1.0));
+    };
+    let mut case = when(condition, factor);
+    for (condition, factor) in branches {
+        case = case.when(condition, factor);
+    }
+    case.otherwise(lit(1.0))
+}
+
+/// Last raw close of each exchange date, by ticker
+async fn daily_closes(
+    df: DataFrame,
+    day: &Expr,
+    columns: &OhlcvColumns,
+) -> Result<HashMap<Option<String>, BTreeMap<i64, f64>>> {
+    let mut group_by = vec![day.clone().alias("__day")];
+    if let Some(ticker) = &columns.ticker {
+        group_by.push(cast_expr(col(ticker.as_str()), DataType::Utf8).alias("__ticker"));
+    }
+    let close = last_value_udaf()
+        .call(vec![cast_expr(col(columns.close.as_str()), DataType::Float64)])
+        .order_by(vec![col(columns.timestamp.as_str()).sort(true, false)])
+        .build()?
+        .alias("__close");
+
+    let mut closes: HashMap<Option<String>, BTreeMap<i64, f64>> = HashMap::new();
+    for batch in df.aggregate(group_by, vec![close])?.collect().await? {
+        let days = cast(batch.column(0), &DataType::Int64)?;
+        let days = days.as_primitive::<Int64Type>();
+        let values = batch.column(batch.num_columns() - 1).as_primitive::<Float64Type>();
+        let tickers = columns.ticker.as_ref().map(|_| batch.column(1).as_string::<i32>());
+
+        for row in 0..batch.num_rows() {
+            if days.is_null(row) || values.is_null(row) {
+                continue;
+            }
+            let ticker = tickers.filter(|t| t.is_valid(row)).map(|t| t.value(row).to_string());
+            closes.entry(ticker).or_default().insert(days.value(row), values.value(row));
+        }
+    }
+    Ok(closes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use datafusion::execution::context::SessionContext;
+
+    #[tokio::test]
+    async fn test_adjust_prices() -> Result<()> {
+        let ctx = SessionContext::new();
+        // Daily bars stamped at midnight New York time (05:00 UTC in January)
+        let day = DAY_NANOS;
+        let df = ctx
+            .sql(&format!("SELECT ticker, CAST(d * {} + 18000000000000 AS BIGINT) AS window_start,
+                    open, high, low, close, volume
+                FROM (VALUES
+                    ('AAPL', 19723, 200.0, 202.0, 198.0, 200.0, 1000), ('AAPL', 19724, 201.0, 204.0, 200.0, 202.0, 1100),
+                    ('AAPL', 19725, 101.0, 103.0, 100.0, 102.0, 2400), ('AAPL', 19726, 101.0, 102.0, 100.0, 101.0, 2000),
+                    ('MSFT', 19723, 300.0, 301.0, 299.0, 300.0, 500), ('MSFT', 19726, 301.0, 302.0, 300.0, 301.0, 600)
+                ) AS t(ticker, d, open, high, low, close, volume)", day))
+            .await?;
+
+        // 2-for-1 split on 2024-01-03, $2 dividend going ex on 2024-01-04
+        let actions = CorporateActions {
+            splits: vec![Split {
+                ticker: "AAPL".to_string(),
+                execution_date: NaiveDate::from_ymd_opt(2024, 1, 3).unwrap(),
+                split_from: 1.0,
+                split_to: 2.0,
+            }],
+            dividends: vec![Dividend {
+                ticker: "AAPL".to_string(),
+                ex_dividend_date: NaiveDate::from_ymd_opt(2024, 1, 4).unwrap(),
+                cash_amount: 2.0,
+            }],
+        };
+
+        for mode in [AdjustmentMode::SplitOnly, AdjustmentMode::TotalReturn] {
+            let result = adjust_prices(df.clone(), &actions, mode)
+                .await?
+                .sort(vec![col("ticker").sort(true, false), col("window_start").sort(true, false)])?
+                .collect()
+                .await?;
+
+            println!("Adjust Prices Test Results ({:?}):", mode);
+            datafusion::arrow::util::pretty::print_batches(&result)?;
+
+            // The first AAPL close is halved by the split, and further scaled
+            // by 1 - 2 / 102 for the dividend
+            let closes = result[0].column_by_name("close").unwrap().as_primitive::<Float64Type>();
+            let expected = match mode {
+                AdjustmentMode::SplitOnly => 100.0,
+                AdjustmentMode::TotalReturn => 100.0 * (1.0 - 2.0 / 102.0),
+            };
+            assert!((closes.value(0) - expected).abs() < 1e-9);
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,487 @@
+//! Alpaca Markets stock data
+//!
+//! [`AlpacaClient`] loads historical bars, trades and quotes from Alpaca's
+//! market data REST API and implements [`MarketDataSource`], so the
+//! indicators, signals and transforms built around Polygon flat files can be
+//! used with an Alpaca account instead. Rows get Polygon's column names;
+//! exchanges stay Alpaca's one-letter codes and conditions its condition
+//! letters. Prices are raw, unadjusted for splits and dividends, like the
+//! flat files.
+
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use chrono::{DateTime, NaiveDate};
+use datafusion::arrow::array::{ArrayRef, Float64Array, Int64Array, RecordBatch, StringArray};
+use datafusion::arrow::datatypes::{DataType, Field, Schema};
+use datafusion::dataframe::DataFrame;
+use datafusion::error::{DataFusionError, Result};
+use datafusion::execution::context::SessionContext;
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+
+use crate::polygon::{with_retries, RetryConfig};
+use crate::source::{BarTimeframe, MarketDataSource};
+
+/// Base URL of the Alpaca market data API
+pub const ALPACA_DATA_URL: &str = "https://data.alpaca.markets";
+
+/// Largest page the historical endpoints return
+const PAGE_LIMIT: usize = 10_000;
+
+/// Which Alpaca data feed to read
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AlpacaFeed {
+    /// IEX only, available on free accounts
+    Iex,
+    /// Every US exchange, on paid subscriptions
+    Sip,
+}
+
+impl AlpacaFeed {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AlpacaFeed::Iex => "iex",
+            AlpacaFeed::Sip => "sip",
+        }
+    }
+}
+
+/// Credentials and options for the Alpaca market data API
+#[derive(Debug, Clone)]
+pub struct AlpacaConfig {
+    pub key_id: String,
+    pub secret_key: String,
+    pub base_url: String,
+    pub feed: AlpacaFeed,
+    pub retry: RetryConfig,
+}
+
+impl AlpacaConfig {
+    /// Read the SIP feed with `key_id` and `secret_key`
+    pub fn new(key_id: impl Into<String>, secret_key: impl Into<String>) -> Self {
+        Self {
+            key_id: key_id.into(),
+            secret_key: secret_key.into(),
+            base_url: ALPACA_DATA_URL.to_string(),
+            feed: AlpacaFeed::Sip,
+            retry: RetryConfig::default(),
+        }
+    }
+
+    /// Read the credentials from `APCA_API_KEY_ID` and `APCA_API_SECRET_KEY`
+    pub fn from_env() -> Result<Self> {
+        dotenv::dotenv().ok();
+        let var = |name: &str| {
+            std::env::var(name)
+                .map_err(|_| DataFusionError::Configuration(format!("{} not found in environment", name)))
+        };
+        Ok(Self::new(var("APCA_API_KEY_ID")?, var("APCA_API_SECRET_KEY")?))
+    }
+
+    pub fn with_feed(mut self, feed: AlpacaFeed) -> Self {
+        self.feed = feed;
+        self
+    }
+
+    pub fn with_retry(mut self, retry: RetryConfig) -> Self {
+        self.retry = retry;
+        self
+    }
+}
+
+/// One page of a historical endpoint: rows by symbol
+#[derive(Debug, Deserialize)]
+struct Page<T> {
+    #[serde(alias = "bars", alias = "trades", alias = "quotes", default = "Option::default")]
+    rows: Option<BTreeMap<String, Vec<T>>>,
+    next_page_token: Option<String>,
+}
+
+impl<T> Page<T> {
+    /// Append the page's rows to `rows`, returning the token of the next page
+    fn append_to(self, rows: &mut BTreeMap<String, Vec<T>>) -> Option<String> {
+        for (symbol, mut symbol_rows) in self.rows.unwrap_or_default() {
+            rows.entry(symbol).or_default().append(&mut symbol_rows);
+        }
+        self.next_page_token
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct AlpacaBar {
+    t: String,
+    o: f64,
+    h: f64,
+    l: f64,
+    c: f64,
+    v: i64,
+    n: Option<i64>,
+    vw: Option<f64>,
+}
+
+#[derive(Debug, Deserialize)]
+struct AlpacaTrade {
+    t: String,
+    x: String,
+    p: f64,
+    s: i64,
+    #[serde(default)]
+    c: Vec<String>,
+    i: Option<i64>,
+    z: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct AlpacaQuote {
+    t: String,
+    bx: String,
+    bp: f64,
+    bs: i64,
+    ax: String,
+    ap: f64,
+    #[serde(rename = "as")]
+    ask_size: i64,
+    #[serde(default)]
+    c: Vec<String>,
+    z: Option<String>,
+}
+
+/// Alpaca historical stock data client
+#[derive(Clone)]
+pub struct AlpacaClient {
+    config: AlpacaConfig,
+    ctx: SessionContext,
+    http: reqwest::Client,
+}
+
+impl AlpacaClient {
+    pub fn new(config: AlpacaConfig) -> Self {
+        Self::with_context(SessionContext::new(), config)
+    }
+
+    /// Create a client that loads into a caller-owned `ctx`
+    pub fn with_context(ctx: SessionContext, config: AlpacaConfig) -> Self {
+        Self {
+            config,
+            ctx,
+            http: reqwest::Client::new(),
+        }
+    }
+
+    /// Get the session context for custom queries
+    pub fn session_context(&self) -> &SessionContext {
+        &self.ctx
+    }
+
+    /// GET every page of `/v2/stocks/{endpoint}` for `symbols` from `start`
+    /// to the end of `end` (UTC)
+    async fn get_all<T: DeserializeOwned>(
+        &self,
+        endpoint: &str,
+        symbols: &[&str],
+        start: NaiveDate,
+        end: NaiveDate,
+        extra: &[(&str, &str)],
+    ) -> Result<BTreeMap<String, Vec<T>>> {
+        let url = format!("{}/v2/stocks/{}", self.config.base_url, endpoint);
+        let mut query = vec![
+            ("symbols", symbols.join(",")),
+            ("start", format!("{}T00:00:00Z", start)),
+            ("end", format!("{}T23:59:59Z", end)),
+            ("feed", self.config.feed.as_str().to_string()),
+            ("limit", PAGE_LIMIT.to_string()),
+        ];
+        query.extend(extra.iter().map(|(key, value)| (*key, value.to_string())));
+
+        let mut rows: BTreeMap<String, Vec<T>> = BTreeMap::new();
+        loop {
+            let (url, query) = (&url, &query);
+            let page: Page<T> = with_retries(&self.config.retry, || async move {
+                self.http
+                    .get(url)
+                    .header("APCA-API-KEY-ID", &self.config.key_id)
+                    .header("APCA-API-SECRET-KEY", &self.config.secret_key)
+                    .query(query)
+                    .send()
+                    .await
+                    .and_then(|response| response.error_for_status())
+                    .map_err(external)?
+                    .json::<Page<T>>()
+                    .await
+                    .map_err(external)
+            })
+            .await?;
+
+            let Some(token) = page.append_to(&mut rows) else {
+                break;
+            };
+            query.retain(|(key, _)| *key != "page_token");
+            query.push(("page_token", token));
+        }
+        Ok(rows)
+    }
+}
+
+impl std::fmt::Debug for AlpacaClient {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AlpacaClient")
+            .field("base_url", &self.config.base_url)
+            .field("feed", &self.config.feed)
+            .finish_non_exhaustive()
+    }
+}
+
+#[async_trait]
+impl MarketDataSource for AlpacaClient {
+    /// Bars also have Alpaca's `vwap`
+    async fn load_bars(
+        &self,
+        symbols: &[&str],
+        timeframe: BarTimeframe,
+        start: NaiveDate,
+        end: NaiveDate,
+    ) -> Result<DataFrame> {
+        let timeframe = match timeframe {
+            BarTimeframe::Minute => "1Min",
+            BarTimeframe::Day => "1Day",
+        };
+        let bars = self
+            .get_all::<AlpacaBar>("bars", symbols, start, end, &[("timeframe", timeframe), ("adjustment", "raw")])
+            .await?;
+        self.ctx.read_batch(bars_batch(&bars)?)
+    }
+
+    /// Trades also have Alpaca's trade `id` and `tape`
+    async fn load_trades(&self, symbols: &[&str], start: NaiveDate, end: NaiveDate) -> Result<DataFrame> {
+        let trades = self.get_all::<AlpacaTrade>("trades", symbols, start, end, &[]).await?;
+        self.ctx.read_batch(trades_batch(&trades)?)
+    }
+
+    /// Quotes also have Alpaca's `conditions` and `tape`
+    async fn load_quotes(&self, symbols: &[&str], start: NaiveDate, end: NaiveDate) -> Result<DataFrame> {
+        let quotes = self.get_all::<AlpacaQuote>("quotes", symbols, start, end, &[]).await?;
+        self.ctx.read_batch(quotes_batch(&quotes)?)
+    }
+}
+
+/// Bars with Polygon's aggregate columns, plus `vwap`
+fn bars_batch(bars: &BTreeMap<String, Vec<AlpacaBar>>) -> Result<RecordBatch> {
+    let rows = bars
+        .iter()
+        .flat_map(|(symbol, bars)| bars.iter().map(move |bar| (symbol.as_str(), bar)))
+        .collect::<Vec<_>>();
+
+    let prices = |price: fn(&AlpacaBar) -> f64| Arc::new(rows.iter().map(|(_, b)| price(b)).collect::<Float64Array>()) as ArrayRef;
+    let columns = vec![
+        Arc::new(rows.iter().map(|(symbol, _)| Some(*symbol)).collect::<StringArray>()) as ArrayRef,
+        Arc::new(rows.iter().map(|(_, b)| b.v).collect::<Int64Array>()),
+        prices(|b| b.o),
+        prices(|b| b.c),
+        prices(|b| b.h),
+        prices(|b| b.l),
+        Arc::new(rows.iter().map(|(_, b)| epoch_nanos(&b.t)).collect::<Result<Int64Array>>()?),
+        Arc::new(rows.iter().map(|(_, b)| b.n).collect::<Int64Array>()),
+        Arc::new(rows.iter().map(|(_, b)| b.vw).collect::<Float64Array>()),
+    ];
+    let schema = Schema::new(vec![
+        Field::new("ticker", DataType::Utf8, false),
+        Field::new("volume", DataType::Int64, false),
+        Field::new("open", DataType::Float64, false),
+        Field::new("close", DataType::Float64, false),
+        Field::new("high", DataType::Float64, false),
+        Field::new("low", DataType::Float64, false),
+        Field::new("window_start", DataType::Int64, false),
+        Field::new("transactions", DataType::Int64, true),
+        Field::new("vwap", DataType::Float64, true),
+    ]);
+    Ok(RecordBatch::try_new(Arc::new(schema), columns)?)
+}
+
+/// Trades with Polygon's trade columns, plus `id` and `tape`
+fn trades_batch(trades: &BTreeMap<String, Vec<AlpacaTrade>>) -> Result<RecordBatch> {
+    let rows = trades
+        .iter()
+        .flat_map(|(symbol, trades)| trades.iter().map(move |trade| (symbol.as_str(), trade)))
+        .collect::<Vec<_>>();
+
+    let columns = vec![
+        Arc::new(rows.iter().map(|(symbol, _)| Some(*symbol)).collect::<StringArray>()) as ArrayRef,
+        Arc::new(rows.iter().map(|(_, t)| epoch_nanos(&t.t)).collect::<Result<Int64Array>>()?),
+        Arc::new(rows.iter().map(|(_, t)| t.p).collect::<Float64Array>()),
+        Arc::new(rows.iter().map(|(_, t)| t.s).collect::<Int64Array>()),
+        Arc::new(rows.iter().map(|(_, t)| Some(t.x.as_str())).collect::<StringArray>()),
+        Arc::new(rows.iter().map(|(_, t)| Some(t.c.join(","))).collect::<StringArray>()),
+        Arc::new(rows.iter().map(|(_, t)| t.i).collect::<Int64Array>()),
+        Arc::new(rows.iter().map(|(_, t)| t.z.as_deref()).collect::<StringArray>()),
+    ];
+    let schema = Schema::new(vec![
+        Field::new("ticker", DataType::Utf8, false),
+        Field::new("sip_timestamp", DataType::Int64, false),
+        Field::new("price", DataType::Float64, false),
+        Field::new("size", DataType::Int64, false),
+        Field::new("exchange", DataType::Utf8, false),
+        Field::new("conditions", DataType::Utf8, false),
+        Field::new("id", DataType::Int64, true),
+        Field::new("tape", DataType::Utf8, true),
+    ]);
+    Ok(RecordBatch::try_new(Arc::new(schema), columns)?)
+}
+
+/// Quotes with Polygon's quote columns, plus `conditions` and `tape`
+fn quotes_batch(quotes: &BTreeMap<String, Vec<AlpacaQuote>>) -> Result<RecordBatch> {
+    let rows = quotes
+        .iter()
+        .flat_map(|(symbol, quotes)| quotes.iter().map(move |quote| (symbol.as_str(), quote)))
+        .collect::<Vec<_>>();
+
+    let columns = vec![
+        Arc::new(rows.iter().map(|(symbol, _)| Some(*symbol)).collect::<StringArray>()) as ArrayRef,
+        Arc::new(rows.iter().map(|(_, q)| epoch_nanos(&q.t)).collect::<Result<Int64Array>>()?),
+        Arc::new(rows.iter().map(|(_, q)| q.bp).collect::<Float64Array>()),
+        Arc::new(rows.iter().map(|(_, q)| q.bs).collect::<Int64Array>()),
+        Arc::new(rows.iter().map(|(_, q)| Some(q.bx.as_str())).collect::<StringArray>()),
+        Arc::new(rows.iter().map(|(_, q)| q.ap).collect::<Float64Array>()),
+        Arc::new(rows.iter().map(|(_, q)| q.ask_size).collect::<Int64Array>()),
+        Arc::new(rows.iter().map(|(_, q)| Some(q.ax.as_str())).collect::<StringArray>()),
+        Arc::new(rows.iter().map(|(_, q)| Some(q.c.join(","))).collect::<StringArray>()),
+        Arc::new(rows.iter().map(|(_, q)| q.z.as_deref()).collect::<StringArray>()),
+    ];
+    let schema = Schema::new(vec![
+        Field::new("ticker", DataType::Utf8, false),
+        Field::new("sip_timestamp", DataType::Int64, false),
+        Field::new("bid_price", DataType::Float64, false),
+        Field::new("bid_size", DataType::Int64, false),
+        Field::new("bid_exchange", DataType::Utf8, false),
+        Field::new("ask_price", DataType::Float64, false),
+        Field::new("ask_size", DataType::Int64, false),
+        Field::new("ask_exchange", DataType::Utf8, false),
+        Field::new("conditions", DataType::Utf8, false),
+        Field::new("tape", DataType::Utf8, true),
+    ]);
+    Ok(RecordBatch::try_new(Arc::new(schema), columns)?)
+}
+
+/// Epoch nanoseconds of an RFC 3339 timestamp such as `2024-01-02T14:30:00.123456789Z`
+fn epoch_nanos(timestamp: &str) -> Result<Option<i64>> {
+    let parsed = DateTime::parse_from_rfc3339(timestamp)
+        .map_err(|e| DataFusionError::Execution(format!("Invalid Alpaca timestamp '{}': {}", timestamp, e)))?;
+    Ok(parsed.timestamp_nanos_opt())
+}
+
+fn external(error: reqwest::Error) -> DataFusionError {
+    DataFusionError::External(Box::new(error))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use datafusion::arrow::array::{Array, AsArray};
+    use datafusion::arrow::datatypes::{Float64Type, Int64Type};
+
+    fn names(batch: &RecordBatch) -> Vec<String> {
+        batch.schema().fields().iter().map(|f| f.name().clone()).collect()
+    }
+
+    #[test]
+    fn test_bar_pages() -> Result<()> {
+        let pages = [
+            r#"{"bars":{"AAPL":[{"t":"2024-01-02T14:30:00Z","o":187.15,"h":188.44,"l":183.89,"c":185.64,"v":82488700,"n":1009074,"vw":185.9}]},
+                "next_page_token":"QUFQTHwy"}"#,
+            r#"{"bars":{"AAPL":[{"t":"2024-01-03T14:30:00Z","o":184.22,"h":185.88,"l":183.43,"c":184.25,"v":58414500,"n":656956,"vw":184.5}],
+                "MSFT":[{"t":"2024-01-02T14:30:00Z","o":373.86,"h":375.9,"l":366.77,"c":370.87,"v":25258600}]},
+                "next_page_token":null}"#,
+        ];
+
+        // Pages are followed while they carry a token, and a symbol's rows
+        // split across pages are joined
+        let mut bars = BTreeMap::new();
+        let first: Page<AlpacaBar> = serde_json::from_str(pages[0]).map_err(|e| DataFusionError::External(Box::new(e)))?;
+        assert_eq!(first.append_to(&mut bars).as_deref(), Some("QUFQTHwy"));
+        let last: Page<AlpacaBar> = serde_json::from_str(pages[1]).map_err(|e| DataFusionError::External(Box::new(e)))?;
+        assert_eq!(last.append_to(&mut bars), None);
+        assert_eq!(bars["AAPL"].len(), 2);
+
+        let batch = bars_batch(&bars)?;
+        assert_eq!(
+            names(&batch),
+            ["ticker", "volume", "open", "close", "high", "low", "window_start", "transactions", "vwap"]
+        );
+        let tickers = batch.column(0).as_string::<i32>().iter().flatten().collect::<Vec<_>>();
+        assert_eq!(tickers, ["AAPL", "AAPL", "MSFT"]);
+        assert_eq!(batch.column(1).as_primitive::<Int64Type>().values().to_vec(), [82488700, 58414500, 25258600]);
+        assert_eq!(batch.column(3).as_primitive::<Float64Type>().values().to_vec(), [185.64, 184.25, 370.87]);
+        assert_eq!(batch.column(6).as_primitive::<Int64Type>().value(0), 1_704_205_800_000_000_000);
+
+        // Fields Alpaca leaves out are null
+        assert!(batch.column(7).is_null(2) && batch.column(8).is_null(2));
+        assert_eq!(batch.column(8).as_primitive::<Float64Type>().value(0), 185.9);
+        Ok(())
+    }
+
+    #[test]
+    fn test_empty_page() -> Result<()> {
+        let page: Page<AlpacaBar> = serde_json::from_str(r#"{"bars":null,"next_page_token":null}"#)
+            .map_err(|e| DataFusionError::External(Box::new(e)))?;
+        let mut bars = BTreeMap::new();
+        assert_eq!(page.append_to(&mut bars), None);
+        assert_eq!(bars_batch(&bars)?.num_rows(), 0);
+        Ok(())
+    }
+
+    #[test]
+    fn test_trades_and_quotes() -> Result<()> {
+        let trades: Page<AlpacaTrade> = serde_json::from_str(
+            r#"{"trades":{"AAPL":[
+                {"t":"2024-01-02T14:30:00.123456789Z","x":"V","p":187.15,"s":100,"c":["@","I"],"i":52983525029461,"z":"C"},
+                {"t":"2024-01-02T14:30:01Z","x":"Q","p":187.2,"s":5}
+            ]},"next_page_token":null}"#,
+        )
+        .map_err(|e| DataFusionError::External(Box::new(e)))?;
+        let mut rows = BTreeMap::new();
+        trades.append_to(&mut rows);
+        let batch = trades_batch(&rows)?;
+
+        assert_eq!(
+            names(&batch),
+            ["ticker", "sip_timestamp", "price", "size", "exchange", "conditions", "id", "tape"]
+        );
+        assert_eq!(batch.column(1).as_primitive::<Int64Type>().value(0), 1_704_205_800_123_456_789);
+        assert_eq!(batch.column(4).as_string::<i32>().value(1), "Q");
+        // Conditions are joined as in the flat files; a trade without any has an empty list
+        assert_eq!(batch.column(5).as_string::<i32>().value(0), "@,I");
+        assert_eq!(batch.column(5).as_string::<i32>().value(1), "");
+        assert_eq!(batch.column(6).as_primitive::<Int64Type>().value(0), 52983525029461);
+        assert!(batch.column(6).is_null(1) && batch.column(7).is_null(1));
+
+        let quotes: Page<AlpacaQuote> = serde_json::from_str(
+            r#"{"quotes":{"AAPL":[
+                {"t":"2024-01-02T14:30:00.5Z","bx":"V","bp":187.1,"bs":2,"ax":"Q","ap":187.2,"as":3,"c":["R"],"z":"C"}
+            ]},"next_page_token":null}"#,
+        )
+        .map_err(|e| DataFusionError::External(Box::new(e)))?;
+        let mut rows = BTreeMap::new();
+        quotes.append_to(&mut rows);
+        let batch = quotes_batch(&rows)?;
+
+        assert_eq!(
+            names(&batch),
+            [
+                "ticker", "sip_timestamp", "bid_price", "bid_size", "bid_exchange",
+                "ask_price", "ask_size", "ask_exchange", "conditions", "tape",
+            ]
+        );
+        assert_eq!(batch.column(1).as_primitive::<Int64Type>().value(0), 1_704_205_800_500_000_000);
+        assert_eq!(batch.column(6).as_primitive::<Int64Type>().value(0), 3);
+        assert_eq!(batch.column(7).as_string::<i32>().value(0), "Q");
+        assert_eq!(batch.column(8).as_string::<i32>().value(0), "R");
+        Ok(())
+    }
+
+    #[test]
+    fn test_epoch_nanos() {
+        assert_eq!(epoch_nanos("2024-01-02T09:30:00-05:00").unwrap(), Some(1_704_205_800_000_000_000));
+        assert!(epoch_nanos("2024-01-02 14:30").is_err());
+    }
+}
@@ -0,0 +1,460 @@
+//! Alternative bar types built from time-based OHLC rows
+//!
+//! Renko bricks, range bars and resampled bars don't map one-to-one onto
+//! input rows, so they are exposed as `DataFrame` transforms rather than
+//! window functions.
+
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use datafusion::arrow::array::{Array, ArrayRef, AsArray, Float64Array, Int64Array, Int8Array, RecordBatch, StringArray};
+use datafusion::arrow::compute::cast;
+use datafusion::arrow::datatypes::{DataType, Field, Float64Type, Int64Type, Schema, TimeUnit};
+use datafusion::dataframe::DataFrame;
+use datafusion::error::{DataFusionError, Result};
+use datafusion::execution::context::SessionContext;
+use datafusion::functions::expr_fn::{date_bin, nullif};
+use datafusion::functions_aggregate::expr_fn::{max, min, sum};
+use datafusion::functions_aggregate::first_last::{first_value_udaf, last_value_udaf};
+use datafusion::logical_expr::{cast as cast_expr, Expr, ExprFunctionExt};
+use datafusion::prelude::{col, lit};
+use datafusion::scalar::ScalarValue;
+
+use crate::functions::atr::true_range;
+
+/// Column names used to read OHLC rows
+#[derive(Debug, Clone)]
+pub struct BarColumns {
+    /// Optional grouping column; bars never span two groups
+    pub ticker: Option<String>,
+    /// Epoch nanoseconds or timestamp column used for ordering
+    pub timestamp: String,
+    pub high: String,
+    pub low: String,
+    pub close: String,
+}
+
+impl Default for BarColumns {
+    /// Polygon flat file aggregate column names
+    fn default() -> Self {
+        Self {
+            ticker: Some("ticker".to_string()),
+            timestamp: "window_start".to_string(),
+            high: "high".to_string(),
+            low: "low".to_string(),
+            close: "close".to_string(),
+        }
+    }
+}
+
+/// How large each Renko brick is
+#[derive(Debug, Clone, Copy)]
+pub enum BrickSize {
+    /// Fixed price increment
+    Fixed(f64),
+    /// Wilder ATR over the given period, measured at the end of each ticker's series
+    Atr(usize),
+}
+
+/// One ticker's rows in time order
+#[derive(Debug, Default)]
+struct Series {
+    timestamps: Vec<i64>,
+    highs: Vec<f64>,
+    lows: Vec<f64>,
+    closes: Vec<f64>,
+}
+
+/// Output bar before it is written to a record batch
+#[derive(Debug)]
+struct Bar {
+    ticker: Option<String>,
+    timestamp: i64,
+    open: f64,
+    high: f64,
+    low: f64,
+    close: f64,
+    direction: i8,
+}
+
+/// Convert OHLC rows into Renko bricks built from closing prices
+///
+/// A new brick forms when the close moves one brick beyond the last brick in
+/// the trend direction, or two bricks against it. Output columns are
+/// `ticker` (when grouped), `timestamp`, `open`, `high`, `low`, `close` and
+/// `direction` (1 up, -1 down); `timestamp` is the row that completed the brick.
+pub async fn renko_bars(
+    ctx: &SessionContext,
+    df: DataFrame,
+    columns: &BarColumns,
+    brick_size: BrickSize,
+) -> Result<DataFrame> {
+    let mut bars = Vec::new();
+    for (ticker, series) in collect_series(df, columns).await? {
+        let size = match brick_size {
+            BrickSize::Fixed(size) => Some(size),
+            BrickSize::Atr(period) => wilder_atr(&series, period),
+        };
+        let Some(size) = size.filter(|s| *s > 0.0) else {
+            continue;
+        };
+        renko_series(ticker, &series, size, &mut bars);
+    }
+    bars_to_dataframe(ctx, columns, bars)
+}
+
+/// Convert OHLC rows into range bars spanning `range` in price
+///
+/// Rows are replayed as high/low/close ticks. A bar closes once its high-low
+/// span reaches `range`, at the boundary price, and the next bar opens there.
+pub async fn range_bars(
+    ctx: &SessionContext,
+    df: DataFrame,
+    columns: &BarColumns,
+    range: f64,
+) -> Result<DataFrame> {
+    if range <= 0.0 {
+        return Err(DataFusionError::Execution(format!(
+            "Range bar size must be positive, got {}",
+            range
+        )));
+    }
+
+    let mut bars = Vec::new();
+    for (ticker, series) in collect_series(df, columns).await? {
+        range_series(ticker, &series, range, &mut bars);
+    }
+    bars_to_dataframe(ctx, columns, bars)
+}
+
+/// Column names used to read OHLCV rows
+#[derive(Debug, Clone)]
+pub struct OhlcvColumns {
+    /// Optional grouping column; bars never span two groups
+    pub ticker: Option<String>,
+    /// Epoch nanoseconds or timestamp column marking the start of each row
+    pub timestamp: String,
+    pub open: String,
+    pub high: String,
+    pub low: String,
+    pub close: String,
+    pub volume: String,
+    /// Optional per-row VWAP; without it each row's typical price is used
+    pub vwap: Option<String>,
+}
+
+impl Default for OhlcvColumns {
+    /// Polygon flat file aggregate column names
+    fn default() -> Self {
+        Self {
+            ticker: Some("ticker".to_string()),
+            timestamp: "window_start".to_string(),
+            open: "open".to_string(),
+            high: "high".to_string(),
+            low: "low".to_string(),
+            close: "close".to_string(),
+            volume: "volume".to_string(),
+            vwap: None,
+        }
+    }
+}
+
+/// Downsample OHLCV rows into bars `interval` long, e.g. minute aggregates
+/// into 5-minute, hourly or daily bars
+///
+/// Bars are aligned to the Unix epoch (so daily bars are UTC days) and keep
+/// the input column names: the first open, highest high, lowest low, last
+/// close and total volume of each interval, plus a `vwap` column weighting
+/// each row's VWAP (or typical price (H + L + C) / 3 if `columns.vwap` is
+/// unset) by its volume. The timestamp is the interval start, in the input
+/// column's type. Intervals without rows produce no bar.
+pub fn resample_ohlcv(df: DataFrame, interval: Duration, columns: &OhlcvColumns) -> Result<DataFrame> {
+    let stride = i64::try_from(interval.as_nanos())
+        .ok()
+        .filter(|nanos| *nanos > 0)
+        .ok_or_else(|| {
+            DataFusionError::Execution(format!("Resample interval must be positive, got {:?}", interval))
+        })?;
+    let timestamp_type = df
+        .schema()
+        .field_with_unqualified_name(&columns.timestamp)?
+        .data_type()
+        .clone();
+
+    let float = |name: &str| cast_expr(col(name), DataType::Float64);
+    let by_time = || vec![col(columns.timestamp.as_str()).sort(true, false)];
+    let price = match &columns.vwap {
+        Some(vwap) => float(vwap),
+        None => (float(&columns.high) + float(&columns.low) + float(&columns.close)) / lit(3.0),
+    };
+
+    let bucket = date_bin(
+        lit(ScalarValue::new_interval_mdn(0, 0, stride)),
+        cast_expr(col(columns.timestamp.as_str()), DataType::Timestamp(TimeUnit::Nanosecond, None)),
+        lit(ScalarValue::TimestampNanosecond(Some(0), None)),
+    );
+
+    let mut group_by = Vec::new();
+    if let Some(ticker) = &columns.ticker {
+        group_by.push(col(ticker.as_str()));
+    }
+    group_by.push(bucket.alias(&columns.timestamp));
+
+    let aggregates = vec![
+        first_value_udaf()
+            .call(vec![float(&columns.open)])
+            .order_by(by_time())
+            .build()?
+            .alias(&columns.open),
+        max(float(&columns.high)).alias(&columns.high),
+        min(float(&columns.low)).alias(&columns.low),
+        last_value_udaf()
+            .call(vec![float(&columns.close)])
+            .order_by(by_time())
+            .build()?
+            .alias(&columns.close),
+        sum(col(columns.volume.as_str())).alias(&columns.volume),
+        sum(price * float(&columns.volume)).alias("__price_volume"),
+        sum(float(&columns.volume)).alias("__volume"),
+    ];
+
+    let mut output: Vec<Expr> = Vec::new();
+    if let Some(ticker) = &columns.ticker {
+        output.push(col(ticker.as_str()));
+    }
+    output.push(cast_expr(col(columns.timestamp.as_str()), timestamp_type).alias(&columns.timestamp));
+    for name in [&columns.open, &columns.high, &columns.low, &columns.close, &columns.volume] {
+        output.push(col(name.as_str()));
+    }
+    // VWAP = Σ(price × volume) / Σ(volume), null for bars without volume
+    output.push((col("__price_volume") / nullif(col("__volume"), lit(0.0))).alias("vwap"));
+
+    let mut sort = Vec::new();
+    if let Some(ticker) = &columns.ticker {
+        sort.push(col(ticker.as_str()).sort(true, false));
+    }
+    sort.push(col(columns.timestamp.as_str()).sort(true, false));
+
+    df.aggregate(group_by, aggregates)?.select(output)?.sort(sort)
+}
+
+fn renko_series(ticker: Option<String>, series: &Series, size: f64, bars: &mut Vec<Bar>) {
+    let Some(first) = series.closes.first() else {
+        return;
+    };
+    // Current brick spans [bottom, top]; the first close seeds a zero-height brick
+    let (mut bottom, mut top) = (*first, *first);
+
+    for (ts, close) in series.timestamps.iter().zip(&series.closes) {
+        while *close >= top + size {
+            bars.push(Bar {
+                ticker: ticker.clone(),
+                timestamp: *ts,
+                open: top,
+                high: top + size,
+                low: top,
+                close: top + size,
+                direction: 1,
+            });
+            bottom = top;
+            top += size;
+        }
+        while *close <= bottom - size {
+            bars.push(Bar {
+                ticker: ticker.clone(),
+                timestamp: *ts,
+                open: bottom,
+                high: bottom,
+                low: bottom - size,
+                close: bottom - size,
+                direction: -1,
+            });
+            top = bottom;
+            bottom -= size;
+        }
+    }
+}
+
+fn range_series(ticker: Option<String>, series: &Series, range: f64, bars: &mut Vec<Bar>) {
+    let mut current: Option<(f64, f64, f64)> = None;
+
+    for i in 0..series.timestamps.len() {
+        for price in [series.highs[i], series.lows[i], series.closes[i]] {
+            let (open, high, low) = match current {
+                Some((open, high, low)) => (open, high.max(price), low.min(price)),
+                None => (price, price, price),
+            };
+
+            if high - low < range {
+                current = Some((open, high, low));
+                continue;
+            }
+
+            // Close at the boundary in the direction of the breakout
+            let (close, high, low, direction) = if price >= high {
+                (low + range, low + range, low, 1)
+            } else {
+                (high - range, high, high - range, -1)
+            };
+            bars.push(Bar {
+                ticker: ticker.clone(),
+                timestamp: series.timestamps[i],
+                open,
+                high,
+                low,
+                close,
+                direction,
+            });
+            current = Some((close, close, close));
+        }
+    }
+}
+
+/// Final Wilder-smoothed ATR of a series
+fn wilder_atr(series: &Series, period: usize) -> Option<f64> {
+    if period == 0 || series.closes.len() < period {
+        return None;
+    }
+    let mut prev_close = None;
+    let mut atr: Option<f64> = None;
+    for (i, ((high, low), close)) in series.highs.iter().zip(&series.lows).zip(&series.closes).enumerate() {
+        let tr = true_range(*high, *low, prev_close);
+        prev_close = Some(*close);
+        atr = if i + 1 < period {
+            Some(atr.unwrap_or(0.0) + tr)
+        } else if i + 1 == period {
+            Some((atr.unwrap_or(0.0) + tr) / period as f64)
+        } else {
+            atr.map(|a| (a * (period - 1) as f64 + tr) / period as f64)
+        };
+    }
+    atr
+}
+
+/// Sort, collect and split rows into per-ticker series
+async fn collect_series(df: DataFrame, columns: &BarColumns) -> Result<BTreeMap<Option<String>, Series>> {
+    let mut sort = Vec::new();
+    if let Some(ticker) = &columns.ticker {
+        sort.push(col(ticker.as_str()).sort(true, false));
+    }
+    sort.push(col(columns.timestamp.as_str()).sort(true, false));
+    let batches = df.sort(sort)?.collect().await?;
+
+    let mut series: BTreeMap<Option<String>, Series> = BTreeMap::new();
+    for batch in &batches {
+        let tickers = match &columns.ticker {
+            Some(name) => Some(cast(column(batch, name)?, &DataType::Utf8)?),
+            None => None,
+        };
+        let timestamps = cast(column(batch, &columns.timestamp)?, &DataType::Int64)?;
+        let timestamps = timestamps.as_primitive::<Int64Type>();
+        let highs = cast(column(batch, &columns.high)?, &DataType::Float64)?;
+        let lows = cast(column(batch, &columns.low)?, &DataType::Float64)?;
+        let closes = cast(column(batch, &columns.close)?, &DataType::Float64)?;
+        let (highs, lows, closes) = (
+            highs.as_primitive::<Float64Type>(),
+            lows.as_primitive::<Float64Type>(),
+            closes.as_primitive::<Float64Type>(),
+        );
+
+        for row in 0..batch.num_rows() {
+            if timestamps.is_null(row) || highs.is_null(row) || lows.is_null(row) || closes.is_null(row) {
+                continue;
+            }
+            let ticker = tickers
+                .as_ref()
+                .map(|t| t.as_string::<i32>())
+                .filter(|t| t.is_valid(row))
+                .map(|t| t.value(row).to_string());
+
+            let entry = series.entry(ticker).or_default();
+            entry.timestamps.push(timestamps.value(row));
+            entry.highs.push(highs.value(row));
+            entry.lows.push(lows.value(row));
+            entry.closes.push(closes.value(row));
+        }
+    }
+
+    Ok(series)
+}
+
+fn column<'a>(batch: &'a RecordBatch, name: &str) -> Result<&'a ArrayRef> {
+    batch
+        .column_by_name(name)
+        .ok_or_else(|| DataFusionError::Plan(format!("Column '{}' not found", name)))
+}
+
+fn bars_to_dataframe(ctx: &SessionContext, columns: &BarColumns, bars: Vec<Bar>) -> Result<DataFrame> {
+    let mut fields = Vec::new();
+    let mut arrays: Vec<ArrayRef> = Vec::new();
+
+    if let Some(ticker) = &columns.ticker {
+        fields.push(Field::new(ticker, DataType::Utf8, true));
+        arrays.push(Arc::new(bars.iter().map(|b| b.ticker.clone()).collect::<StringArray>()));
+    }
+    fields.push(Field::new("timestamp", DataType::Int64, false));
+    arrays.push(Arc::new(bars.iter().map(|b| b.timestamp).collect::<Int64Array>()));
+    for name in ["open", "high", "low", "close"] {
+        fields.push(Field::new(name, DataType::Float64, false));
+    }
+    arrays.push(Arc::new(bars.iter().map(|b| b.open).collect::<Float64Array>()));
+    arrays.push(Arc::new(bars.iter().map(|b| b.high).collect::<Float64Array>()));
+    arrays.push(Arc::new(bars.iter().map(|b| b.low).collect::<Float64Array>()));
+    arrays.push(Arc::new(bars.iter().map(|b| b.close).collect::<Float64Array>()));
+    fields.push(Field::new("direction", DataType::Int8, false));
+    arrays.push(Arc::new(bars.iter().map(|b| b.direction).collect::<Int8Array>()));
+
+    let batch = RecordBatch::try_new(Arc::new(Schema::new(fields)), arrays)?;
+    ctx.read_batch(batch)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_renko_and_range_bars() -> Result<()> {
+        let ctx = SessionContext::new();
+        let df = ctx
+            .sql("SELECT * FROM (VALUES
+                ('AAPL', 1, 101.0, 99.0, 100.0), ('AAPL', 2, 102.5, 100.0, 102.2), ('AAPL', 3, 104.8, 102.0, 104.5),
+                ('AAPL', 4, 104.6, 101.0, 101.2), ('AAPL', 5, 101.5, 97.5, 98.0), ('AAPL', 6, 99.5, 97.0, 99.0)
+            ) AS t(ticker, window_start, high, low, close)")
+            .await?;
+
+        let renko = renko_bars(&ctx, df.clone(), &BarColumns::default(), BrickSize::Fixed(2.0)).await?;
+        println!("Renko Test Results:");
+        datafusion::arrow::util::pretty::print_batches(&renko.collect().await?)?;
+
+        let range = range_bars(&ctx, df, &BarColumns::default(), 3.0).await?;
+        println!("Range Bar Test Results:");
+        datafusion::arrow::util::pretty::print_batches(&range.collect().await?)?;
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_resample_ohlcv() -> Result<()> {
+        let ctx = SessionContext::new();
+        let minute = 60_000_000_000_i64;
+        let df = ctx
+            .sql(&format!("SELECT ticker, CAST(window_start * {} AS BIGINT) AS window_start, open, high, low, close, volume FROM (VALUES
+                ('AAPL', 0, 100.0, 101.0, 99.5, 100.5, 1000), ('AAPL', 1, 100.5, 102.0, 100.0, 101.5, 1500),
+                ('AAPL', 2, 101.5, 101.8, 100.8, 101.0, 800), ('AAPL', 5, 101.0, 101.2, 99.0, 99.5, 2000),
+                ('AAPL', 7, 99.5, 100.5, 99.4, 100.2, 1200), ('MSFT', 3, 300.0, 301.0, 299.0, 300.5, 500)
+            ) AS t(ticker, window_start, open, high, low, close, volume)", minute))
+            .await?;
+
+        // Minute rows into 5-minute bars
+        let bars = resample_ohlcv(df, Duration::from_secs(300), &OhlcvColumns::default())?;
+        let result = bars.collect().await?;
+
+        println!("Resample OHLCV Test Results:");
+        datafusion::arrow::util::pretty::print_batches(&result)?;
+
+        assert_eq!(result.iter().map(|b| b.num_rows()).sum::<usize>(), 3);
+
+        Ok(())
+    }
+}
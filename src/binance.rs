@@ -0,0 +1,318 @@
+//! Binance spot klines
+//!
+//! [`BinanceClient`] loads OHLCV klines from Binance's public REST API into
+//! DataFrames with the columns and types of Polygon's crypto aggregates
+//! flat files, so the indicator functions and bar transforms work on them
+//! unchanged. Tickers are Binance symbols such as `BTCUSDT`. No API key is
+//! needed.
+//!
+//! Binance has no historical quotes and its trades endpoint only covers
+//! recent data, so its [`MarketDataSource`] implementation loads bars only.
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use chrono::{Days, NaiveDate};
+use datafusion::arrow::array::{ArrayRef, Float64Array, Int64Array, RecordBatch, StringArray};
+use datafusion::arrow::datatypes::{DataType, Field, Schema};
+use datafusion::dataframe::DataFrame;
+use datafusion::error::{DataFusionError, Result};
+use datafusion::execution::context::SessionContext;
+
+use crate::polygon::{with_retries, RetryConfig};
+use crate::source::{BarTimeframe, MarketDataSource};
+
+/// Base URL of the Binance spot API
+pub const BINANCE_API_URL: &str = "https://api.binance.com";
+
+/// Most klines one request returns
+const PAGE_LIMIT: usize = 1000;
+
+/// Length of each kline
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KlineInterval {
+    OneMinute,
+    FiveMinutes,
+    FifteenMinutes,
+    OneHour,
+    FourHours,
+    OneDay,
+}
+
+impl KlineInterval {
+    /// Binance's name for the interval
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            KlineInterval::OneMinute => "1m",
+            KlineInterval::FiveMinutes => "5m",
+            KlineInterval::FifteenMinutes => "15m",
+            KlineInterval::OneHour => "1h",
+            KlineInterval::FourHours => "4h",
+            KlineInterval::OneDay => "1d",
+        }
+    }
+}
+
+impl From<BarTimeframe> for KlineInterval {
+    fn from(timeframe: BarTimeframe) -> Self {
+        match timeframe {
+            BarTimeframe::Minute => KlineInterval::OneMinute,
+            BarTimeframe::Day => KlineInterval::OneDay,
+        }
+    }
+}
+
+/// One kline as Binance sends it: open time, OHLCV, close time, quote
+/// volume, trade count, taker buy volumes and an unused field
+type RawKline = (
+    i64,
+    String,
+    String,
+    String,
+    String,
+    String,
+    i64,
+    String,
+    i64,
+    String,
+    String,
+    serde_json::Value,
+);
+
+/// Binance kline client
+#[derive(Clone)]
+pub struct BinanceClient {
+    base_url: String,
+    retry: RetryConfig,
+    ctx: SessionContext,
+    http: reqwest::Client,
+}
+
+impl Default for BinanceClient {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl BinanceClient {
+    pub fn new() -> Self {
+        Self::with_context(SessionContext::new())
+    }
+
+    /// Create a client that loads into a caller-owned `ctx`
+    pub fn with_context(ctx: SessionContext) -> Self {
+        Self {
+            base_url: BINANCE_API_URL.to_string(),
+            retry: RetryConfig::default(),
+            ctx,
+            http: reqwest::Client::new(),
+        }
+    }
+
+    /// Use another Binance-compatible API, e.g. `https://api.binance.us`
+    pub fn with_base_url(mut self, base_url: impl Into<String>) -> Self {
+        self.base_url = base_url.into();
+        self
+    }
+
+    pub fn with_retry(mut self, retry: RetryConfig) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    /// Get the session context for custom queries
+    pub fn session_context(&self) -> &SessionContext {
+        &self.ctx
+    }
+
+    /// Klines of `symbols` opening from `start` through the end of `end` (UTC)
+    ///
+    /// Columns are `ticker`, `volume`, `open`, `close`, `high`, `low`,
+    /// `window_start` (epoch nanoseconds) and `transactions`, plus
+    /// `quote_volume`.
+    pub async fn load_klines(
+        &self,
+        symbols: &[&str],
+        interval: KlineInterval,
+        start: NaiveDate,
+        end: NaiveDate,
+    ) -> Result<DataFrame> {
+        let start_ms = start.and_hms_opt(0, 0, 0).expect("midnight is valid").and_utc().timestamp_millis();
+        let end_ms = end
+            .checked_add_days(Days::new(1))
+            .and_then(|next| next.and_hms_opt(0, 0, 0))
+            .ok_or_else(|| DataFusionError::Execution(format!("Date out of range: {}", end)))?
+            .and_utc()
+            .timestamp_millis()
+            - 1;
+
+        let mut rows = Vec::new();
+        for symbol in symbols {
+            for kline in self.fetch_klines(symbol, interval, start_ms, end_ms).await? {
+                rows.push((*symbol, kline));
+            }
+        }
+
+        self.ctx.read_batch(klines_batch(&rows)?)
+    }
+
+    /// Every kline of `symbol` opening within `[start_ms, end_ms]`, a page at a time
+    async fn fetch_klines(
+        &self,
+        symbol: &str,
+        interval: KlineInterval,
+        start_ms: i64,
+        end_ms: i64,
+    ) -> Result<Vec<RawKline>> {
+        let url = format!("{}/api/v3/klines", self.base_url);
+        let mut klines: Vec<RawKline> = Vec::new();
+        let mut from_ms = start_ms;
+        while from_ms <= end_ms {
+            let query = [
+                ("symbol", symbol.to_string()),
+                ("interval", interval.as_str().to_string()),
+                ("startTime", from_ms.to_string()),
+                ("endTime", end_ms.to_string()),
+                ("limit", PAGE_LIMIT.to_string()),
+            ];
+            let (url, query) = (&url, &query);
+            let page: Vec<RawKline> = with_retries(&self.retry, || async move {
+                self.http
+                    .get(url)
+                    .query(query)
+                    .send()
+                    .await
+                    .and_then(|response| response.error_for_status())
+                    .map_err(external)?
+                    .json::<Vec<RawKline>>()
+                    .await
+                    .map_err(external)
+            })
+            .await?;
+
+            let Some(last) = page.last() else {
+                break;
+            };
+            from_ms = last.0 + 1;
+            let full = page.len() == PAGE_LIMIT;
+            klines.extend(page);
+            if !full {
+                break;
+            }
+        }
+        Ok(klines)
+    }
+}
+
+#[async_trait]
+impl MarketDataSource for BinanceClient {
+    async fn load_bars(
+        &self,
+        symbols: &[&str],
+        timeframe: BarTimeframe,
+        start: NaiveDate,
+        end: NaiveDate,
+    ) -> Result<DataFrame> {
+        self.load_klines(symbols, timeframe.into(), start, end).await
+    }
+
+    async fn load_trades(&self, _symbols: &[&str], _start: NaiveDate, _end: NaiveDate) -> Result<DataFrame> {
+        Err(DataFusionError::NotImplemented("Binance has no historical trades endpoint".to_string()))
+    }
+
+    async fn load_quotes(&self, _symbols: &[&str], _start: NaiveDate, _end: NaiveDate) -> Result<DataFrame> {
+        Err(DataFusionError::NotImplemented("Binance has no historical quotes".to_string()))
+    }
+}
+
+/// Klines of each symbol with Polygon's aggregate columns, plus `quote_volume`
+fn klines_batch(rows: &[(&str, RawKline)]) -> Result<RecordBatch> {
+    let prices = |field: fn(&RawKline) -> &str| {
+        rows.iter().map(|(_, k)| parse_decimal(field(k))).collect::<Result<Float64Array>>()
+    };
+    let columns = vec![
+        Arc::new(rows.iter().map(|(symbol, _)| Some(*symbol)).collect::<StringArray>()) as ArrayRef,
+        Arc::new(prices(|k| k.5.as_str())?),
+        Arc::new(prices(|k| k.1.as_str())?),
+        Arc::new(prices(|k| k.4.as_str())?),
+        Arc::new(prices(|k| k.2.as_str())?),
+        Arc::new(prices(|k| k.3.as_str())?),
+        Arc::new(rows.iter().map(|(_, k)| k.0 * 1_000_000).collect::<Int64Array>()),
+        Arc::new(rows.iter().map(|(_, k)| k.8).collect::<Int64Array>()),
+        Arc::new(prices(|k| k.7.as_str())?),
+    ];
+    let schema = Schema::new(vec![
+        Field::new("ticker", DataType::Utf8, false),
+        Field::new("volume", DataType::Float64, false),
+        Field::new("open", DataType::Float64, false),
+        Field::new("close", DataType::Float64, false),
+        Field::new("high", DataType::Float64, false),
+        Field::new("low", DataType::Float64, false),
+        Field::new("window_start", DataType::Int64, false),
+        Field::new("transactions", DataType::Int64, false),
+        Field::new("quote_volume", DataType::Float64, false),
+    ]);
+    Ok(RecordBatch::try_new(Arc::new(schema), columns)?)
+}
+
+fn parse_decimal(value: &str) -> Result<f64> {
+    value
+        .parse()
+        .map_err(|e| DataFusionError::Execution(format!("Invalid Binance decimal '{}': {}", value, e)))
+}
+
+fn external(error: reqwest::Error) -> DataFusionError {
+    DataFusionError::External(Box::new(error))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use datafusion::arrow::array::AsArray;
+    use datafusion::arrow::datatypes::{Float64Type, Int64Type};
+
+    /// Two one-minute BTCUSDT klines as `/api/v3/klines` returns them
+    const KLINES: &str = r#"[
+        [1704205800000, "42150.01000000", "42180.50000000", "42140.00000000", "42175.25000000", "12.34567000",
+         1704205859999, "520637.12345678", 1523, "6.10000000", "257261.00000000", "0"],
+        [1704205860000, "42175.25000000", "42190.00000000", "42160.10000000", "42161.00000000", "0.00100000",
+         1704205919999, "42.16100000", 1, "0.00000000", "0.00000000", "0"]
+    ]"#;
+
+    #[test]
+    fn test_klines_batch() -> Result<()> {
+        let klines: Vec<RawKline> = serde_json::from_str(KLINES).map_err(|e| DataFusionError::External(Box::new(e)))?;
+        let rows = klines.into_iter().map(|kline| ("BTCUSDT", kline)).collect::<Vec<_>>();
+        let batch = klines_batch(&rows)?;
+
+        let names = batch.schema().fields().iter().map(|f| f.name().clone()).collect::<Vec<_>>();
+        assert_eq!(
+            names,
+            ["ticker", "volume", "open", "close", "high", "low", "window_start", "transactions", "quote_volume"]
+        );
+        let column = |i: usize| batch.column(i).as_primitive::<Float64Type>().values().to_vec();
+        assert_eq!(column(1), [12.34567, 0.001]);
+        assert_eq!(column(2), [42150.01, 42175.25]);
+        assert_eq!(column(3), [42175.25, 42161.0]);
+        assert_eq!(column(4), [42180.5, 42190.0]);
+        assert_eq!(column(5), [42140.0, 42160.1]);
+        assert_eq!(column(8), [520637.12345678, 42.161]);
+
+        // Open times become epoch nanoseconds; close times are dropped
+        let window_start = batch.column(6).as_primitive::<Int64Type>().values().to_vec();
+        assert_eq!(window_start, [1_704_205_800_000_000_000, 1_704_205_860_000_000_000]);
+        assert_eq!(batch.column(7).as_primitive::<Int64Type>().values().to_vec(), [1523, 1]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_invalid_kline() {
+        let mut kline: RawKline = serde_json::from_str(
+            r#"[1704205800000, "1", "1", "1", "1", "1", 1704205859999, "1", 1, "0", "0", "0"]"#,
+        )
+        .unwrap();
+        kline.4 = "n/a".to_string();
+        let error = klines_batch(&[("BTCUSDT", kline)]).unwrap_err();
+        assert!(error.to_string().contains("Invalid Binance decimal 'n/a'"), "{}", error);
+    }
+}
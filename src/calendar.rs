@@ -0,0 +1,580 @@
+//! Exchange calendars: holidays, half days and regular session hours
+//!
+//! [`ExchangeCalendar::nyse`] follows the NYSE holiday rules (which NASDAQ
+//! shares), including observed weekend holidays and the unscheduled closures
+//! since 2001. The same calendar backs scalar functions for SQL:
+//!
+//! ```sql
+//! SELECT * FROM day_aggs WHERE is_trading_day(date);
+//! SELECT next_trading_day(DATE '2024-07-03'), session_close(DATE '2024-07-03');
+//! ```
+
+use std::any::Any;
+use std::sync::Arc;
+
+use chrono::{DateTime, Datelike, Duration, NaiveDate, NaiveTime, TimeZone, Utc, Weekday};
+use chrono_tz::Tz;
+use datafusion::arrow::array::{
+    Array, ArrayRef, AsArray, BooleanArray, Date32Array, StringArray, TimestampNanosecondArray,
+};
+use datafusion::arrow::compute::cast;
+use datafusion::arrow::datatypes::{DataType, Date32Type, Int64Type, TimeUnit};
+use datafusion::error::{DataFusionError, Result};
+use datafusion::execution::context::SessionContext;
+use datafusion::logical_expr::{ColumnarValue, ScalarUDF, ScalarUDFImpl, Signature, Volatility};
+
+/// Days from 0001-01-01 to 1970-01-01
+const UNIX_EPOCH_DAYS_FROM_CE: i32 = 719_163;
+
+/// Unscheduled full-day NYSE closures
+const NYSE_SPECIAL_CLOSURES: &[(i32, u32, u32)] = &[
+    (2001, 9, 11), // September 11 attacks
+    (2001, 9, 12),
+    (2001, 9, 13),
+    (2001, 9, 14),
+    (2004, 6, 11), // President Reagan's funeral
+    (2007, 1, 2),  // President Ford's funeral
+    (2012, 10, 29), // Hurricane Sandy
+    (2012, 10, 30),
+    (2018, 12, 5), // President G. H. W. Bush's funeral
+    (2025, 1, 9),  // President Carter's funeral
+];
+
+/// Regular trading hours of one day, in UTC
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TradingSession {
+    pub date: NaiveDate,
+    pub open: DateTime<Utc>,
+    pub close: DateTime<Utc>,
+    /// Whether the session closes early, e.g. the day after Thanksgiving
+    pub early_close: bool,
+}
+
+/// Part of the trading day an instant falls in
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MarketSession {
+    PreMarket,
+    Regular,
+    PostMarket,
+    Closed,
+}
+
+impl MarketSession {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            MarketSession::PreMarket => "pre",
+            MarketSession::Regular => "regular",
+            MarketSession::PostMarket => "post",
+            MarketSession::Closed => "closed",
+        }
+    }
+}
+
+/// Holidays and session hours of an exchange
+#[derive(Debug, Clone)]
+pub struct ExchangeCalendar {
+    name: &'static str,
+    timezone: Tz,
+    pre_open: NaiveTime,
+    open: NaiveTime,
+    close: NaiveTime,
+    early_close: NaiveTime,
+    post_close: NaiveTime,
+    early_post_close: NaiveTime,
+}
+
+impl ExchangeCalendar {
+    /// New York Stock Exchange: 09:30 to 16:00 ET (13:00 on half days),
+    /// with extended hours from 04:00 to 20:00 ET (17:00 on half days)
+    pub fn nyse() -> Self {
+        let time = |hour, minute| NaiveTime::from_hms_opt(hour, minute, 0).expect("valid time");
+        Self {
+            name: "NYSE",
+            timezone: chrono_tz::America::New_York,
+            pre_open: time(4, 0),
+            open: time(9, 30),
+            close: time(16, 0),
+            early_close: time(13, 0),
+            post_close: time(20, 0),
+            early_post_close: time(17, 0),
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        self.name
+    }
+
+    /// Exchange time zone
+    pub fn timezone(&self) -> Tz {
+        self.timezone
+    }
+
+    /// Exchange-local date of an instant
+    pub fn local_date(&self, instant: DateTime<Utc>) -> NaiveDate {
+        instant.with_timezone(&self.timezone).date_naive()
+    }
+
+    /// Full-day closures of `year`: holidays (on their observed dates) and
+    /// unscheduled closures
+    pub fn holidays(&self, year: i32) -> Vec<NaiveDate> {
+        let date = |month, day| NaiveDate::from_ymd_opt(year, month, day);
+        let nth = |month, weekday, n| NaiveDate::from_weekday_of_month_opt(year, month, weekday, n);
+
+        let mut holidays = vec![
+            // New Year's Day moves to Monday, but not back to the previous Friday
+            date(1, 1).map(|d| match d.weekday() {
+                Weekday::Sun => d + Duration::days(1),
+                _ => d,
+            }),
+            if year >= 1998 { nth(1, Weekday::Mon, 3) } else { None },
+            nth(2, Weekday::Mon, 3),
+            easter(year).map(|d| d - Duration::days(2)),
+            nth(5, Weekday::Mon, 5).or_else(|| nth(5, Weekday::Mon, 4)),
+            if year >= 2022 { date(6, 19).map(observed) } else { None },
+            date(7, 4).map(observed),
+            nth(9, Weekday::Mon, 1),
+            nth(11, Weekday::Thu, 4),
+            date(12, 25).map(observed),
+        ];
+        holidays.extend(
+            NYSE_SPECIAL_CLOSURES
+                .iter()
+                .filter(|(y, _, _)| *y == year)
+                .map(|&(y, m, d)| NaiveDate::from_ymd_opt(y, m, d)),
+        );
+
+        let mut holidays = holidays
+            .into_iter()
+            .flatten()
+            .filter(|d| d.year() == year && !is_weekend(*d))
+            .collect::<Vec<_>>();
+        holidays.sort();
+        holidays
+    }
+
+    /// Early-close days of `year`: July 3, the day after Thanksgiving and
+    /// Christmas Eve, when they are not themselves holidays
+    pub fn half_days(&self, year: i32) -> Vec<NaiveDate> {
+        let candidates = [
+            NaiveDate::from_ymd_opt(year, 7, 3),
+            NaiveDate::from_weekday_of_month_opt(year, 11, Weekday::Thu, 4).map(|d| d + Duration::days(1)),
+            NaiveDate::from_ymd_opt(year, 12, 24),
+        ];
+        // A Friday July 3 or December 24 is the observed holiday instead
+        candidates
+            .into_iter()
+            .flatten()
+            .filter(|d| self.is_trading_day(*d))
+            .collect()
+    }
+
+    /// Whether `date` is a full-day closure on a weekday
+    pub fn is_holiday(&self, date: NaiveDate) -> bool {
+        !is_weekend(date) && self.holidays(date.year()).contains(&date)
+    }
+
+    /// Whether the exchange closes early on `date`
+    pub fn is_half_day(&self, date: NaiveDate) -> bool {
+        self.half_days(date.year()).contains(&date)
+    }
+
+    /// Whether the exchange is open at all on `date`
+    pub fn is_trading_day(&self, date: NaiveDate) -> bool {
+        !is_weekend(date) && !self.is_holiday(date)
+    }
+
+    /// First trading day after `date`
+    pub fn next_trading_day(&self, date: NaiveDate) -> NaiveDate {
+        let mut day = date + Duration::days(1);
+        while !self.is_trading_day(day) {
+            day += Duration::days(1);
+        }
+        day
+    }
+
+    /// Last trading day before `date`
+    pub fn previous_trading_day(&self, date: NaiveDate) -> NaiveDate {
+        let mut day = date - Duration::days(1);
+        while !self.is_trading_day(day) {
+            day -= Duration::days(1);
+        }
+        day
+    }
+
+    /// Trading days from `start` to `end`, inclusive
+    pub fn trading_days(&self, start: NaiveDate, end: NaiveDate) -> Vec<NaiveDate> {
+        start
+            .iter_days()
+            .take_while(|day| *day <= end)
+            .filter(|day| self.is_trading_day(*day))
+            .collect()
+    }
+
+    /// Regular session of `date`, or `None` if the exchange is closed
+    pub fn session(&self, date: NaiveDate) -> Option<TradingSession> {
+        if !self.is_trading_day(date) {
+            return None;
+        }
+        let early_close = self.is_half_day(date);
+        let close = if early_close { self.early_close } else { self.close };
+        Some(TradingSession {
+            date,
+            open: self.to_utc(date, self.open)?,
+            close: self.to_utc(date, close)?,
+            early_close,
+        })
+    }
+
+    /// Whether the regular session is open at `instant`
+    pub fn is_open(&self, instant: DateTime<Utc>) -> bool {
+        self.session(self.local_date(instant))
+            .is_some_and(|session| session.open <= instant && instant < session.close)
+    }
+
+    /// Pre-market, regular, post-market or closed at `instant`
+    pub fn market_session(&self, instant: DateTime<Utc>) -> MarketSession {
+        let local = instant.with_timezone(&self.timezone);
+        let date = local.date_naive();
+        if !self.is_trading_day(date) {
+            return MarketSession::Closed;
+        }
+
+        let half_day = self.is_half_day(date);
+        let (close, post_close) = match half_day {
+            true => (self.early_close, self.early_post_close),
+            false => (self.close, self.post_close),
+        };
+        match local.time() {
+            t if t < self.pre_open => MarketSession::Closed,
+            t if t < self.open => MarketSession::PreMarket,
+            t if t < close => MarketSession::Regular,
+            t if t < post_close => MarketSession::PostMarket,
+            _ => MarketSession::Closed,
+        }
+    }
+
+    fn to_utc(&self, date: NaiveDate, time: NaiveTime) -> Option<DateTime<Utc>> {
+        self.timezone
+            .from_local_datetime(&date.and_time(time))
+            .single()
+            .map(|local| local.with_timezone(&Utc))
+    }
+}
+
+impl Default for ExchangeCalendar {
+    fn default() -> Self {
+        Self::nyse()
+    }
+}
+
+/// Whether the NYSE is open on `date`
+pub fn is_trading_day(date: NaiveDate) -> bool {
+    ExchangeCalendar::nyse().is_trading_day(date)
+}
+
+/// First NYSE trading day after `date`
+pub fn next_trading_day(date: NaiveDate) -> NaiveDate {
+    ExchangeCalendar::nyse().next_trading_day(date)
+}
+
+/// First NYSE trading day before `date`
+pub fn previous_trading_day(date: NaiveDate) -> NaiveDate {
+    ExchangeCalendar::nyse().previous_trading_day(date)
+}
+
+fn is_weekend(date: NaiveDate) -> bool {
+    matches!(date.weekday(), Weekday::Sat | Weekday::Sun)
+}
+
+/// Saturday holidays are observed on Friday, Sunday ones on Monday
+fn observed(date: NaiveDate) -> NaiveDate {
+    match date.weekday() {
+        Weekday::Sat => date - Duration::days(1),
+        Weekday::Sun => date + Duration::days(1),
+        _ => date,
+    }
+}
+
+/// Western Easter Sunday (anonymous Gregorian algorithm)
+fn easter(year: i32) -> Option<NaiveDate> {
+    let a = year % 19;
+    let (b, c) = (year / 100, year % 100);
+    let (d, e) = (b / 4, b % 4);
+    let f = (b + 8) / 25;
+    let g = (b - f + 1) / 3;
+    let h = (19 * a + b - d - g + 15) % 30;
+    let (i, k) = (c / 4, c % 4);
+    let l = (32 + 2 * e + 2 * i - h - k) % 7;
+    let m = (a + 11 * h + 22 * l) / 451;
+    let month = (h + l - 7 * m + 114) / 31;
+    let day = (h + l - 7 * m + 114) % 31 + 1;
+    NaiveDate::from_ymd_opt(year, month as u32, day as u32)
+}
+
+/// Which calendar question a scalar function answers
+#[derive(Debug, Clone, Copy)]
+enum CalendarKind {
+    IsTradingDay,
+    IsHalfDay,
+    NextTradingDay,
+    PreviousTradingDay,
+    SessionOpen,
+    SessionClose,
+    TradingDate,
+    MarketSession,
+}
+
+/// Scalar calendar functions taking a date, a timestamp or epoch
+/// nanoseconds; timestamps are read on the exchange-local date
+#[derive(Debug)]
+pub struct CalendarFunction {
+    name: String,
+    signature: Signature,
+    kind: CalendarKind,
+    calendar: ExchangeCalendar,
+}
+
+impl CalendarFunction {
+    /// `is_trading_day(date)`: whether the exchange is open
+    pub fn is_trading_day() -> Self {
+        Self::new("is_trading_day", CalendarKind::IsTradingDay)
+    }
+
+    /// `is_half_day(date)`: whether the exchange closes early
+    pub fn is_half_day() -> Self {
+        Self::new("is_half_day", CalendarKind::IsHalfDay)
+    }
+
+    /// `next_trading_day(date)`: first trading day after the date
+    pub fn next_trading_day() -> Self {
+        Self::new("next_trading_day", CalendarKind::NextTradingDay)
+    }
+
+    /// `previous_trading_day(date)`: last trading day before the date
+    pub fn previous_trading_day() -> Self {
+        Self::new("previous_trading_day", CalendarKind::PreviousTradingDay)
+    }
+
+    /// `session_open(date)`: regular session open in UTC, null when closed
+    pub fn session_open() -> Self {
+        Self::new("session_open", CalendarKind::SessionOpen)
+    }
+
+    /// `session_close(date)`: regular session close in UTC, null when closed
+    pub fn session_close() -> Self {
+        Self::new("session_close", CalendarKind::SessionClose)
+    }
+
+    /// `trading_date(ts)`: exchange-local date of a timestamp
+    pub fn trading_date() -> Self {
+        Self::new("trading_date", CalendarKind::TradingDate)
+    }
+
+    /// `market_session(ts)`: `'pre'`, `'regular'`, `'post'` or `'closed'`
+    pub fn market_session() -> Self {
+        Self::new("market_session", CalendarKind::MarketSession)
+    }
+
+    fn new(name: &str, kind: CalendarKind) -> Self {
+        Self {
+            name: name.to_string(),
+            signature: Signature::user_defined(Volatility::Immutable),
+            kind,
+            calendar: ExchangeCalendar::nyse(),
+        }
+    }
+
+    /// Use `calendar` instead of the NYSE's
+    pub fn with_calendar(mut self, calendar: ExchangeCalendar) -> Self {
+        self.calendar = calendar;
+        self
+    }
+
+    /// Exchange-local dates of a coerced date, timestamp or Int64 argument
+    fn dates(&self, array: &ArrayRef) -> Result<Vec<Option<NaiveDate>>> {
+        if let DataType::Date32 = array.data_type() {
+            return Ok(array
+                .as_primitive::<Date32Type>()
+                .iter()
+                .map(|days| days.and_then(|d| NaiveDate::from_num_days_from_ce_opt(d + UNIX_EPOCH_DAYS_FROM_CE)))
+                .collect());
+        }
+
+        let nanos = cast(array, &DataType::Int64)?;
+        Ok(nanos
+            .as_primitive::<Int64Type>()
+            .iter()
+            .map(|n| n.map(|n| self.calendar.local_date(DateTime::from_timestamp_nanos(n))))
+            .collect())
+    }
+}
+
+impl ScalarUDFImpl for CalendarFunction {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn signature(&self) -> &Signature {
+        &self.signature
+    }
+
+    fn coerce_types(&self, arg_types: &[DataType]) -> Result<Vec<DataType>> {
+        let coerced = match arg_types {
+            [DataType::Date32 | DataType::Date64 | DataType::Utf8 | DataType::LargeUtf8 | DataType::Null] => {
+                DataType::Date32
+            }
+            [DataType::Timestamp(_, tz)] => DataType::Timestamp(TimeUnit::Nanosecond, tz.clone()),
+            [t] if t.is_integer() => DataType::Int64,
+            _ => {
+                return Err(DataFusionError::Plan(format!(
+                    "{} expects one date, timestamp or Int64 epoch nanoseconds argument, got ({})",
+                    self.name,
+                    arg_types.iter().map(|t| t.to_string()).collect::<Vec<_>>().join(", ")
+                )))
+            }
+        };
+        Ok(vec![coerced])
+    }
+
+    fn return_type(&self, _arg_types: &[DataType]) -> Result<DataType> {
+        Ok(match self.kind {
+            CalendarKind::IsTradingDay | CalendarKind::IsHalfDay => DataType::Boolean,
+            CalendarKind::NextTradingDay | CalendarKind::PreviousTradingDay | CalendarKind::TradingDate => {
+                DataType::Date32
+            }
+            CalendarKind::MarketSession => DataType::Utf8,
+            CalendarKind::SessionOpen | CalendarKind::SessionClose => {
+                DataType::Timestamp(TimeUnit::Nanosecond, Some("UTC".into()))
+            }
+        })
+    }
+
+    fn invoke(&self, args: &[ColumnarValue]) -> Result<ColumnarValue> {
+        let values = ColumnarValue::values_to_arrays(args)?;
+        let calendar = &self.calendar;
+        let epoch_days = |d: NaiveDate| d.num_days_from_ce() - UNIX_EPOCH_DAYS_FROM_CE;
+
+        // Sessions depend on the time of day, not just the date
+        if let CalendarKind::MarketSession = self.kind {
+            let result = match values[0].data_type() {
+                DataType::Date32 => StringArray::from(vec![None::<&str>; values[0].len()]),
+                _ => cast(&values[0], &DataType::Int64)?
+                    .as_primitive::<Int64Type>()
+                    .iter()
+                    .map(|n| n.map(|n| calendar.market_session(DateTime::from_timestamp_nanos(n)).as_str()))
+                    .collect(),
+            };
+            return Ok(ColumnarValue::Array(Arc::new(result)));
+        }
+
+        let dates = self.dates(&values[0])?;
+
+        let result: ArrayRef = match self.kind {
+            CalendarKind::IsTradingDay => Arc::new(
+                dates.iter().map(|d| d.map(|d| calendar.is_trading_day(d))).collect::<BooleanArray>(),
+            ),
+            CalendarKind::IsHalfDay => Arc::new(
+                dates.iter().map(|d| d.map(|d| calendar.is_half_day(d))).collect::<BooleanArray>(),
+            ),
+            CalendarKind::NextTradingDay => Arc::new(
+                dates
+                    .iter()
+                    .map(|d| d.map(|d| epoch_days(calendar.next_trading_day(d))))
+                    .collect::<Date32Array>(),
+            ),
+            CalendarKind::TradingDate => Arc::new(
+                dates.iter().map(|d| d.map(epoch_days)).collect::<Date32Array>(),
+            ),
+            CalendarKind::MarketSession => unreachable!("handled above"),
+            CalendarKind::PreviousTradingDay => Arc::new(
+                dates
+                    .iter()
+                    .map(|d| d.map(|d| epoch_days(calendar.previous_trading_day(d))))
+                    .collect::<Date32Array>(),
+            ),
+            CalendarKind::SessionOpen | CalendarKind::SessionClose => {
+                let open = matches!(self.kind, CalendarKind::SessionOpen);
+                let instants = dates.iter().map(|d| {
+                    let session = calendar.session((*d)?)?;
+                    let instant = if open { session.open } else { session.close };
+                    instant.timestamp_nanos_opt()
+                });
+                Arc::new(instants.collect::<TimestampNanosecondArray>().with_timezone("UTC"))
+            }
+        };
+
+        Ok(ColumnarValue::Array(result))
+    }
+}
+
+/// Register `is_trading_day`, `is_half_day`, `next_trading_day`,
+/// `previous_trading_day`, `session_open`, `session_close`, `trading_date`
+/// and `market_session`
+pub fn register_calendar(ctx: &SessionContext) -> Result<()> {
+    for function in [
+        CalendarFunction::is_trading_day(),
+        CalendarFunction::is_half_day(),
+        CalendarFunction::next_trading_day(),
+        CalendarFunction::previous_trading_day(),
+        CalendarFunction::session_open(),
+        CalendarFunction::session_close(),
+        CalendarFunction::trading_date(),
+        CalendarFunction::market_session(),
+    ] {
+        ctx.register_udf(ScalarUDF::from(function));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_calendar() -> Result<()> {
+        let nyse = ExchangeCalendar::nyse();
+        let date = |m, d| NaiveDate::from_ymd_opt(2024, m, d).unwrap();
+
+        let holidays = [
+            date(1, 1), date(1, 15), date(2, 19), date(3, 29), date(5, 27), date(6, 19),
+            date(7, 4), date(9, 2), date(11, 28), date(12, 25),
+        ];
+        assert_eq!(nyse.holidays(2024), holidays);
+        assert_eq!(nyse.half_days(2024), [date(7, 3), date(11, 29), date(12, 24)]);
+        assert_eq!(next_trading_day(date(3, 28)), date(4, 1));
+
+        // 13:30 UTC is 09:30 in New York during daylight time, 08:30 in standard time
+        let at = |m, d| date(m, d).and_hms_opt(13, 30, 0).unwrap().and_utc();
+        assert_eq!(nyse.market_session(at(7, 3)), MarketSession::Regular);
+        assert_eq!(nyse.market_session(at(1, 3)), MarketSession::PreMarket);
+        assert_eq!(nyse.market_session(at(7, 4)), MarketSession::Closed);
+
+        let ctx = SessionContext::new();
+        register_calendar(&ctx)?;
+
+        // Test the calendar functions using SQL
+        let result = ctx
+            .sql("SELECT d,
+                    is_trading_day(d) AS trading,
+                    is_half_day(d) AS half_day,
+                    next_trading_day(d) AS next_day,
+                    previous_trading_day(d) AS previous_day,
+                    session_open(d) AS open,
+                    session_close(d) AS close
+                FROM (VALUES
+                    (DATE '2024-07-03'), (DATE '2024-07-04'), (DATE '2024-07-06'), (DATE '2024-11-04'), (DATE '2024-03-11')
+                ) AS t(d)")
+            .await?
+            .collect()
+            .await?;
+
+        println!("Calendar Test Results:");
+        datafusion::arrow::util::pretty::print_batches(&result)?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,387 @@
+//! Continuous futures contracts
+//!
+//! Each futures expiry is its own ticker, e.g. `ESH4` and `ESM4`, and is
+//! only actively traded for a few months, so an indicator over one
+//! contract's rows starts cold at every expiry. [`build_continuous`] stitches
+//! the contracts of one root into a single series: a [`RollRule`] picks the
+//! contract held each day, and a [`BackAdjust`] method shifts the prices
+//! before each roll so the switch doesn't show up as a price gap.
+
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+
+use chrono::{Datelike, Duration, NaiveDate, Weekday};
+use datafusion::arrow::array::{Array, ArrayRef, AsArray, Float64Array, Int64Array, RecordBatch, StringArray};
+use datafusion::arrow::compute::cast;
+use datafusion::arrow::datatypes::{DataType, Field, Float64Type, Int64Type, Schema, TimeUnit};
+use datafusion::common::JoinType;
+use datafusion::dataframe::DataFrame;
+use datafusion::error::{DataFusionError, Result};
+use datafusion::execution::context::SessionContext;
+use datafusion::functions::expr_fn::starts_with;
+use datafusion::functions_aggregate::expr_fn::sum;
+use datafusion::functions_aggregate::first_last::last_value_udaf;
+use datafusion::logical_expr::{cast as cast_expr, Expr, ExprFunctionExt};
+use datafusion::prelude::{col, lit};
+
+use crate::bars::OhlcvColumns;
+
+/// Futures month codes, January to December
+pub const MONTH_CODES: [char; 12] = ['F', 'G', 'H', 'J', 'K', 'M', 'N', 'Q', 'U', 'V', 'X', 'Z'];
+
+/// Nanoseconds per day
+const DAY_NANOS: i64 = 86_400_000_000_000;
+
+/// Shift from UTC that puts each futures session on its trade date
+///
+/// CME sessions open at 18:00 New York time for the next trade date and
+/// close by 17:00. Two hours forward maps the whole session, and daily bars
+/// stamped at midnight, onto the trade date in both standard and daylight
+/// time.
+const TRADE_DATE_OFFSET_NANOS: i64 = 2 * 3_600_000_000_000;
+
+/// One futures expiry, ordered by delivery month
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct FuturesContract {
+    pub root: String,
+    pub year: i32,
+    pub month: u32,
+}
+
+impl FuturesContract {
+    /// Parse a contract ticker of `root`, such as `ESM4`, `ESM24` or `ESM2024`
+    ///
+    /// A one-digit year is the first year ending in that digit from the year
+    /// before `as_of`, so `ESM4` seen in 2023 or 2024 is June 2024.
+    pub fn parse(ticker: &str, root: &str, as_of: NaiveDate) -> Option<Self> {
+        let rest = ticker.strip_prefix(root)?;
+        let mut chars = rest.chars();
+        let code = chars.next()?;
+        let month = MONTH_CODES.iter().position(|c| *c == code)? as u32 + 1;
+
+        let digits = chars.as_str();
+        if digits.is_empty() || !digits.chars().all(|c| c.is_ascii_digit()) {
+            return None;
+        }
+        let value: i32 = digits.parse().ok()?;
+        let year = match digits.len() {
+            1 => {
+                let first = as_of.year() - 1;
+                first + (value - first).rem_euclid(10)
+            }
+            2 => 2000 + value,
+            4 => value,
+            _ => return None,
+        };
+        Some(Self { root: root.to_string(), year, month })
+    }
+
+    /// Estimated last trading day: the third Friday of the delivery month,
+    /// as for equity index and currency futures
+    pub fn expiry(&self) -> Option<NaiveDate> {
+        NaiveDate::from_weekday_of_month_opt(self.year, self.month, Weekday::Fri, 3)
+    }
+}
+
+/// When the continuous series moves to the next contract
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RollRule {
+    /// Hold the contract with the most volume each trade date, never rolling
+    /// back to an earlier expiry
+    VolumeBased,
+    /// Roll this many calendar days before the held contract's estimated
+    /// expiry (see [`FuturesContract::expiry`])
+    DaysBeforeExpiry(u32),
+}
+
+/// How prices before a roll are adjusted
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BackAdjust {
+    /// Raw prices; each roll shows up as a gap
+    None,
+    /// Add the price difference between the contracts at each roll, which
+    /// keeps point moves but can take old prices below zero
+    Difference,
+    /// Multiply by the price ratio between the contracts at each roll, which
+    /// keeps percentage returns
+    Ratio,
+}
+
+/// Volume and last close of one contract on one trade date
+#[derive(Debug, Clone, Copy)]
+struct Session {
+    volume: f64,
+    close: Option<f64>,
+}
+
+/// Stitch the contracts of `root` into one series, using Polygon column names
+pub async fn build_continuous(
+    ctx: &SessionContext,
+    bars: DataFrame,
+    root: &str,
+    roll: RollRule,
+    adjust: BackAdjust,
+) -> Result<DataFrame> {
+    build_continuous_with_columns(ctx, bars, root, roll, adjust, &OhlcvColumns::default()).await
+}
+
+/// Stitch the contracts of `root` into one series
+///
+/// Rows of tickers that parse as `root` contracts are dated by trade date,
+/// and each date keeps the rows of the contract `roll` holds that day.
+/// Earlier rows are back-adjusted, so the latest prices are unchanged: open,
+/// high, low, close and VWAP (when `columns.vwap` is set) become Float64.
+/// The gap at a roll is measured between the two contracts' closes on the
+/// last date before it, or on the roll date when the new contract hadn't
+/// traded yet. The ticker column is replaced by `root`, and a `contract`
+/// column keeps the contract each row came from. Dates on which only
+/// contracts before the held one trade are dropped.
+pub async fn build_continuous_with_columns(
+    ctx: &SessionContext,
+    bars: DataFrame,
+    root: &str,
+    roll: RollRule,
+    adjust: BackAdjust,
+    columns: &OhlcvColumns,
+) -> Result<DataFrame> {
+    let ticker = columns
+        .ticker
+        .as_deref()
+        .ok_or_else(|| DataFusionError::Plan("Continuous contracts need a ticker column".to_string()))?;
+    let bars = bars
+        .filter(starts_with(cast_expr(col(ticker), DataType::Utf8), lit(root)))?
+        .with_column("__contract", cast_expr(col(ticker), DataType::Utf8))?
+        .with_column("__day", trade_day(columns))?;
+
+    let sessions = daily_sessions(bars.clone(), columns).await?;
+    let contracts = parse_contracts(&sessions, root);
+    let held = held_contracts(&sessions, &contracts, roll);
+    let adjustments = roll_adjustments(&sessions, &held, adjust);
+
+    let schema = Arc::new(Schema::new(vec![
+        Field::new("__held_contract", DataType::Utf8, false),
+        Field::new("__held_day", DataType::Int64, false),
+        Field::new("__adjustment", DataType::Float64, false),
+    ]));
+    let arrays: Vec<ArrayRef> = vec![
+        Arc::new(held.iter().map(|(_, contract)| Some(contract.as_str())).collect::<StringArray>()),
+        Arc::new(held.iter().map(|(day, _)| *day).collect::<Int64Array>()),
+        Arc::new(Float64Array::from(adjustments)),
+    ];
+    let held = ctx.read_batch(RecordBatch::try_new(schema, arrays)?)?;
+
+    let mut prices = vec![&columns.open, &columns.high, &columns.low, &columns.close];
+    prices.extend(&columns.vwap);
+    let mut output = Vec::new();
+    for field in bars.schema().fields() {
+        let name = field.name();
+        if name.starts_with("__") {
+            continue;
+        }
+        let value = col(name.as_str());
+        output.push(if name == ticker {
+            lit(root).alias(name)
+        } else if prices.contains(&name) {
+            let price = cast_expr(value, DataType::Float64);
+            match adjust {
+                BackAdjust::Ratio => (price * col("__adjustment")).alias(name),
+                BackAdjust::Difference => (price + col("__adjustment")).alias(name),
+                BackAdjust::None => price.alias(name),
+            }
+        } else {
+            value
+        });
+    }
+    output.push(col("__contract").alias("contract"));
+
+    bars.join(held, JoinType::Inner, &["__contract", "__day"], &["__held_contract", "__held_day"], None)?
+        .select(output)?
+        .sort(vec![col(columns.timestamp.as_str()).sort(true, false)])
+}
+
+/// Trade date of each row, as days since the Unix epoch
+fn trade_day(columns: &OhlcvColumns) -> Expr {
+    let nanos = cast_expr(
+        cast_expr(col(columns.timestamp.as_str()), DataType::Timestamp(TimeUnit::Nanosecond, None)),
+        DataType::Int64,
+    );
+    (nanos + lit(TRADE_DATE_OFFSET_NANOS)) / lit(DAY_NANOS)
+}
+
+/// Volume and last close of each contract, by trade date
+async fn daily_sessions(bars: DataFrame, columns: &OhlcvColumns) -> Result<BTreeMap<i64, HashMap<String, Session>>> {
+    let close = last_value_udaf()
+        .call(vec![cast_expr(col(columns.close.as_str()), DataType::Float64)])
+        .order_by(vec![col(columns.timestamp.as_str()).sort(true, false)])
+        .build()?
+        .alias("__close");
+    let volume = sum(cast_expr(col(columns.volume.as_str()), DataType::Float64)).alias("__volume");
+
+    let mut sessions: BTreeMap<i64, HashMap<String, Session>> = BTreeMap::new();
+    for batch in bars.aggregate(vec![col("__day"), col("__contract")], vec![volume, close])?.collect().await? {
+        let days = cast(batch.column(0), &DataType::Int64)?;
+        let days = days.as_primitive::<Int64Type>();
+        let contracts = batch.column(1).as_string::<i32>();
+        let volumes = batch.column(2).as_primitive::<Float64Type>();
+        let closes = batch.column(3).as_primitive::<Float64Type>();
+
+        for row in 0..batch.num_rows() {
+            if days.is_null(row) || contracts.is_null(row) {
+                continue;
+            }
+            let session = Session {
+                volume: if volumes.is_valid(row) { volumes.value(row) } else { 0.0 },
+                close: closes.is_valid(row).then(|| closes.value(row)),
+            };
+            sessions.entry(days.value(row)).or_default().insert(contracts.value(row).to_string(), session);
+        }
+    }
+    Ok(sessions)
+}
+
+/// Date of a trade day number
+fn day_date(day: i64) -> NaiveDate {
+    // NaiveDate's default is the Unix epoch
+    NaiveDate::default() + Duration::days(day)
+}
+
+/// Contract of each ticker of `root`, with one-digit years resolved from
+/// the ticker's first trade date
+fn parse_contracts(sessions: &BTreeMap<i64, HashMap<String, Session>>, root: &str) -> HashMap<String, FuturesContract> {
+    let mut contracts = HashMap::new();
+    for (day, tickers) in sessions {
+        for ticker in tickers.keys() {
+            if !contracts.contains_key(ticker) {
+                if let Some(contract) = FuturesContract::parse(ticker, root, day_date(*day)) {
+                    contracts.insert(ticker.clone(), contract);
+                }
+            }
+        }
+    }
+    contracts
+}
+
+/// The ticker held on each trade date, in date order
+fn held_contracts(
+    sessions: &BTreeMap<i64, HashMap<String, Session>>,
+    contracts: &HashMap<String, FuturesContract>,
+    roll: RollRule,
+) -> Vec<(i64, String)> {
+    let mut held: Vec<(i64, String)> = Vec::new();
+    let mut current: Option<&FuturesContract> = None;
+    for (day, tickers) in sessions {
+        // Contracts trading today that don't roll back to an earlier expiry
+        let candidates = tickers
+            .iter()
+            .filter_map(|(ticker, session)| Some((contracts.get(ticker)?, ticker, session)))
+            .filter(|(contract, _, _)| current.map_or(true, |current| *contract >= current))
+            .collect::<Vec<_>>();
+
+        let chosen = match roll {
+            RollRule::VolumeBased => candidates
+                .iter()
+                .max_by(|a, b| a.2.volume.total_cmp(&b.2.volume).then_with(|| b.0.cmp(a.0)))
+                .copied(),
+            RollRule::DaysBeforeExpiry(days) => {
+                let date = day_date(*day);
+                let before_roll = |contract: &FuturesContract| {
+                    contract.expiry().is_some_and(|expiry| expiry - Duration::days(days as i64) > date)
+                };
+                candidates
+                    .iter()
+                    .filter(|(contract, _, _)| before_roll(contract))
+                    .min_by(|a, b| a.0.cmp(b.0))
+                    .or_else(|| candidates.iter().max_by(|a, b| a.0.cmp(b.0)))
+                    .copied()
+            }
+        };
+        if let Some((contract, ticker, _)) = chosen {
+            current = Some(contract);
+            held.push((*day, ticker.clone()));
+        }
+    }
+    held
+}
+
+/// Cumulative adjustment of each held date: a multiplier for
+/// [`BackAdjust::Ratio`], an offset otherwise
+fn roll_adjustments(
+    sessions: &BTreeMap<i64, HashMap<String, Session>>,
+    held: &[(i64, String)],
+    adjust: BackAdjust,
+) -> Vec<f64> {
+    let neutral = if adjust == BackAdjust::Ratio { 1.0 } else { 0.0 };
+    let mut adjustments = vec![neutral; held.len()];
+    if adjust == BackAdjust::None {
+        return adjustments;
+    }
+
+    let close = |day: i64, ticker: &str| sessions.get(&day)?.get(ticker)?.close;
+    for i in (1..held.len()).rev() {
+        let mut gap = neutral;
+        let ((previous_day, old), (day, new)) = (&held[i - 1], &held[i]);
+        if old != new {
+            let closes = close(*previous_day, old)
+                .zip(close(*previous_day, new))
+                .or_else(|| close(*day, old).zip(close(*day, new)));
+            if let Some((old, new)) = closes {
+                match adjust {
+                    BackAdjust::Ratio if old > 0.0 => gap = new / old,
+                    BackAdjust::Difference => gap = new - old,
+                    _ => {}
+                }
+            }
+        }
+        adjustments[i - 1] = match adjust {
+            BackAdjust::Ratio => adjustments[i] * gap,
+            _ => adjustments[i] + gap,
+        };
+    }
+    adjustments
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_build_continuous() -> Result<()> {
+        let ctx = SessionContext::new();
+        // Daily bars from 2024-03-11, stamped at 05:00 UTC; volume moves from
+        // ESH4 to ESM4 on 2024-03-12
+        let df = ctx
+            .sql(&format!("SELECT ticker, CAST(d * {} + 18000000000000 AS BIGINT) AS window_start,
+                    open, high, low, close, volume
+                FROM (VALUES
+                    ('ESH4', 19793, 5100.0, 5110.0, 5090.0, 5100.0, 1500000),
+                    ('ESM4', 19793, 5150.0, 5160.0, 5140.0, 5150.0, 400000),
+                    ('ESH4', 19794, 5105.0, 5120.0, 5100.0, 5110.0, 900000),
+                    ('ESM4', 19794, 5155.0, 5170.0, 5150.0, 5161.0, 1200000),
+                    ('ESH4', 19795, 5110.0, 5115.0, 5100.0, 5112.0, 300000),
+                    ('ESM4', 19795, 5161.0, 5175.0, 5155.0, 5170.0, 1300000),
+                    ('NQM4', 19795, 18000.0, 18100.0, 17900.0, 18050.0, 500000)
+                ) AS t(ticker, d, open, high, low, close, volume)", DAY_NANOS))
+            .await?;
+
+        for adjust in [BackAdjust::Ratio, BackAdjust::Difference] {
+            let result = build_continuous(&ctx, df.clone(), "ES", RollRule::VolumeBased, adjust)
+                .await?
+                .collect()
+                .await?;
+
+            println!("Continuous Contract Test Results ({:?}):", adjust);
+            datafusion::arrow::util::pretty::print_batches(&result)?;
+
+            assert_eq!(result.iter().map(|b| b.num_rows()).sum::<usize>(), 3);
+            // The first close is ESH4's, shifted by the 2024-03-11 gap to ESM4
+            let closes = result[0].column_by_name("close").unwrap().as_primitive::<Float64Type>();
+            let expected = match adjust {
+                BackAdjust::Ratio => 5100.0 * 5150.0 / 5100.0,
+                _ => 5100.0 + 50.0,
+            };
+            assert!((closes.value(0) - expected).abs() < 1e-9);
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,946 @@
+//! Documentation metadata for the financial functions
+//!
+//! DataFusion 42 has no documentation hook on its UDF traits, so the
+//! descriptions live here, keyed by function name. [`documentation`] looks up
+//! one function, planning errors quote its syntax, and
+//! [`register_function_docs`] exposes the whole set as a `financial_functions`
+//! table for `SHOW`-style tooling:
+//!
+//! ```sql
+//! SELECT name, syntax FROM financial_functions WHERE kind = 'aggregate';
+//! ```
+
+use std::sync::Arc;
+
+use datafusion::arrow::array::{ArrayRef, StringArray};
+use datafusion::arrow::datatypes::{DataType, Field, Schema};
+use datafusion::arrow::record_batch::RecordBatch;
+use datafusion::datasource::MemTable;
+use datafusion::error::Result;
+use datafusion::execution::context::SessionContext;
+
+/// How a function is invoked in SQL
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FunctionKind {
+    Window,
+    Aggregate,
+    Scalar,
+}
+
+impl FunctionKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            FunctionKind::Window => "window",
+            FunctionKind::Aggregate => "aggregate",
+            FunctionKind::Scalar => "scalar",
+        }
+    }
+}
+
+/// Description, syntax and arguments of one function
+#[derive(Debug, Clone, Copy)]
+pub struct FunctionDoc {
+    pub name: &'static str,
+    pub kind: FunctionKind,
+    pub description: &'static str,
+    pub syntax: &'static str,
+    /// `(argument, description)` pairs, including defaults of optional ones
+    pub arguments: &'static [(&'static str, &'static str)],
+    pub formula: Option<&'static str>,
+}
+
+/// Look up the documentation of a financial function by name
+pub fn documentation(name: &str) -> Option<&'static FunctionDoc> {
+    FUNCTION_DOCS.iter().find(|doc| doc.name == name)
+}
+
+/// Documentation for every financial function
+pub fn all_documentation() -> &'static [FunctionDoc] {
+    FUNCTION_DOCS
+}
+
+/// Register a `financial_functions` table describing every function
+pub fn register_function_docs(ctx: &SessionContext) -> Result<()> {
+    let schema = Arc::new(Schema::new(vec![
+        Field::new("name", DataType::Utf8, false),
+        Field::new("kind", DataType::Utf8, false),
+        Field::new("description", DataType::Utf8, false),
+        Field::new("syntax", DataType::Utf8, false),
+        Field::new("arguments", DataType::Utf8, false),
+        Field::new("formula", DataType::Utf8, true),
+    ]));
+
+    let column = |value: fn(&FunctionDoc) -> Option<String>| -> ArrayRef {
+        Arc::new(FUNCTION_DOCS.iter().map(value).collect::<StringArray>())
+    };
+    let batch = RecordBatch::try_new(
+        schema.clone(),
+        vec![
+            column(|doc| Some(doc.name.to_string())),
+            column(|doc| Some(doc.kind.as_str().to_string())),
+            column(|doc| Some(doc.description.to_string())),
+            column(|doc| Some(doc.syntax.to_string())),
+            column(|doc| {
+                Some(
+                    doc.arguments
+                        .iter()
+                        .map(|(arg, desc)| format!("{}: {}", arg, desc))
+                        .collect::<Vec<_>>()
+                        .join("; "),
+                )
+            }),
+            column(|doc| doc.formula.map(str::to_string)),
+        ],
+    )?;
+
+    ctx.register_table("financial_functions", Arc::new(MemTable::try_new(schema, vec![vec![batch]])?))?;
+    Ok(())
+}
+
+use FunctionKind::{Aggregate, Scalar, Window};
+
+const VALUE: (&str, &str) = ("value", "Float64 price or value column");
+const HLC: (&str, &str) = ("high, low, close", "Float64 price columns");
+const HL: (&str, &str) = ("high, low", "Float64 high and low price columns");
+const OHLC: (&str, &str) = ("open, high, low, close", "Float64 price columns");
+const VOLUME: (&str, &str) = ("volume", "Float64 traded volume column");
+const PERIOD: (&str, &str) = ("period", "Int64 number of rows in the window");
+const RETURNS: (&str, &str) = ("returns", "Float64 periodic simple returns");
+const TIMESTAMP: (&str, &str) = ("ts", "Int64 epoch nanoseconds or Timestamp of the row");
+const OPTION_TICKER: (&str, &str) = ("ticker", "Utf8 OCC option symbol, e.g. 'O:AAPL240119C00150000'");
+const BS_CONTRACT: (&str, &str) = (
+    "option_type, spot, strike, years, rate",
+    "'call'/'put' (or 'C'/'P'), Float64 underlying price, strike, years to expiry and risk-free rate",
+);
+const DIVIDEND_YIELD: (&str, &str) = ("dividend_yield", "Float64 continuous dividend yield, default 0");
+const DATE: (&str, &str) = ("date", "Date, or Timestamp / Int64 epoch nanoseconds read on the New York date");
+
+static FUNCTION_DOCS: &[FunctionDoc] = &[
+    // Moving averages and momentum
+    FunctionDoc {
+        name: "sma",
+        kind: Window,
+        description: "Simple moving average over a fixed window",
+        syntax: "sma(value, window_size [, min_periods])",
+        arguments: &[
+            VALUE,
+            ("window_size", "Int64 number of periods"),
+            ("min_periods", "Int64 observations needed before emitting a value, default window_size"),
+        ],
+        formula: Some("SMA = Σ value / N"),
+    },
+    FunctionDoc {
+        name: "ema",
+        kind: Window,
+        description: "Exponential moving average",
+        syntax: "ema(value, window_size [, min_periods])",
+        arguments: &[
+            VALUE,
+            ("window_size", "Int64 periods for the smoothing factor α = 2 / (N + 1)"),
+            ("min_periods", "Int64 observations needed before emitting a value, default 1"),
+        ],
+        formula: Some("EMA = α × value + (1 - α) × previous_EMA"),
+    },
+    FunctionDoc {
+        name: "rsi",
+        kind: Window,
+        description: "Relative Strength Index momentum oscillator",
+        syntax: "rsi(value, window_size [, method])",
+        arguments: &[
+            VALUE,
+            ("window_size", "Int64 number of periods, typically 14"),
+            ("method", "Utf8 gain/loss smoothing: 'wilder' (default), 'ema' or 'sma'"),
+        ],
+        formula: Some("RSI = 100 - 100 / (1 + average gain / average loss)"),
+    },
+    FunctionDoc {
+        name: "macd",
+        kind: Window,
+        description: "MACD line, the difference of the 12 and 26 period EMAs",
+        syntax: "macd(value)",
+        arguments: &[VALUE],
+        formula: Some("MACD = EMA(12) - EMA(26)"),
+    },
+    FunctionDoc {
+        name: "macd_full",
+        kind: Window,
+        description: "MACD line, signal line and histogram as a struct",
+        syntax: "macd_full(value)",
+        arguments: &[VALUE],
+        formula: Some("Signal = EMA(9) of MACD, Histogram = MACD - Signal"),
+    },
+    FunctionDoc {
+        name: "kama",
+        kind: Window,
+        description: "Kaufman adaptive moving average",
+        syntax: "kama(value, er_period, fast, slow)",
+        arguments: &[
+            VALUE,
+            ("er_period", "Int64 efficiency ratio lookback, typically 10"),
+            ("fast, slow", "Int64 EMA periods bounding the smoothing constant, typically 2 and 30"),
+        ],
+        formula: Some("KAMA = previous_KAMA + SC × (value - previous_KAMA)"),
+    },
+    FunctionDoc {
+        name: "trix",
+        kind: Window,
+        description: "Rate of change of a triple-smoothed EMA",
+        syntax: "trix(value, period)",
+        arguments: &[VALUE, ("period", "Int64 period of each EMA, typically 15")],
+        formula: Some("TRIX = 100 × (EMA3 - previous_EMA3) / previous_EMA3"),
+    },
+    FunctionDoc {
+        name: "coppock",
+        kind: Window,
+        description: "Coppock curve long-term momentum indicator",
+        syntax: "coppock(value [, long_roc, short_roc, wma_period])",
+        arguments: &[VALUE, ("long_roc, short_roc, wma_period", "Int64 periods, default 14, 11 and 10")],
+        formula: Some("Coppock = WMA(ROC(long) + ROC(short))"),
+    },
+    FunctionDoc {
+        name: "kst",
+        kind: Window,
+        description: "Know Sure Thing momentum oscillator and signal line",
+        syntax: "kst(value [, roc1, roc2, roc3, roc4, sma1, sma2, sma3, sma4, signal])",
+        arguments: &[VALUE, ("roc*, sma*, signal", "Int64 periods, default 10/15/20/30, 10/10/10/15 and 9")],
+        formula: Some("KST = Σ j × SMA(ROC(roc_j), sma_j) for j = 1..4"),
+    },
+    FunctionDoc {
+        name: "stochastic",
+        kind: Window,
+        description: "Stochastic oscillator %K and %D",
+        syntax: "stochastic(high, low, close, k_period, d_period)",
+        arguments: &[
+            HLC,
+            ("k_period", "Int64 highest high / lowest low lookback, typically 14"),
+            ("d_period", "Int64 %D moving average period, typically 3"),
+        ],
+        formula: Some("%K = 100 × (close - LL) / (HH - LL), %D = SMA(%K, d_period)"),
+    },
+    FunctionDoc {
+        name: "rvi",
+        kind: Window,
+        description: "Relative Vigor Index and signal line",
+        syntax: "rvi(open, high, low, close, period)",
+        arguments: &[OHLC, ("period", "Int64 averaging period, typically 10")],
+        formula: Some("RVI = SMA(SWMA(close - open), N) / SMA(SWMA(high - low), N)"),
+    },
+    // Trend and volatility
+    FunctionDoc {
+        name: "bollinger",
+        kind: Window,
+        description: "Bollinger Bands around a simple moving average",
+        syntax: "bollinger(value, period, std_multiplier)",
+        arguments: &[
+            VALUE,
+            ("period", "Int64 moving average and deviation period, typically 20"),
+            ("std_multiplier", "Float64 band width in standard deviations, typically 2.0"),
+        ],
+        formula: Some("Middle = SMA(N), Upper/Lower = Middle ± k × σ(N)"),
+    },
+    FunctionDoc {
+        name: "atr",
+        kind: Window,
+        description: "Average True Range with Wilder's smoothing",
+        syntax: "atr(high, low, close, period)",
+        arguments: &[HLC, ("period", "Int64 smoothing period, typically 14")],
+        formula: Some("ATR = (previous_ATR × (N - 1) + TR) / N"),
+    },
+    FunctionDoc {
+        name: "adx",
+        kind: Window,
+        description: "Average Directional Index with +DI and -DI",
+        syntax: "adx(high, low, close, period)",
+        arguments: &[HLC, ("period", "Int64 Wilder smoothing period, typically 14")],
+        formula: Some("DX = 100 × |+DI - -DI| / (+DI + -DI), ADX = Wilder average of DX"),
+    },
+    FunctionDoc {
+        name: "cci",
+        kind: Window,
+        description: "Commodity Channel Index",
+        syntax: "cci(high, low, close, period)",
+        arguments: &[HLC, ("period", "Int64 number of periods, typically 20")],
+        formula: Some("CCI = (TP - SMA(TP)) / (0.015 × mean deviation)"),
+    },
+    FunctionDoc {
+        name: "ichimoku",
+        kind: Window,
+        description: "Ichimoku Kinko Hyo lines",
+        syntax: "ichimoku(high, low, close [, tenkan_period, kijun_period, senkou_b_period])",
+        arguments: &[HLC, ("tenkan_period, kijun_period, senkou_b_period", "Int64 lookbacks, default 9, 26 and 52")],
+        formula: Some("Tenkan/Kijun/Senkou B = (HH(N) + LL(N)) / 2, Senkou A = (Tenkan + Kijun) / 2"),
+    },
+    FunctionDoc {
+        name: "psar",
+        kind: Window,
+        description: "Parabolic stop-and-reverse",
+        syntax: "psar(high, low, acceleration, max_acceleration)",
+        arguments: &[
+            HL,
+            ("acceleration", "Float64 acceleration factor step, typically 0.02"),
+            ("max_acceleration", "Float64 acceleration factor cap, typically 0.2"),
+        ],
+        formula: Some("SAR = previous_SAR + AF × (EP - previous_SAR)"),
+    },
+    FunctionDoc {
+        name: "donchian",
+        kind: Window,
+        description: "Donchian channel of the rolling highest high and lowest low",
+        syntax: "donchian(high, low, period)",
+        arguments: &[HL, PERIOD],
+        formula: Some("Upper = HH(N), Lower = LL(N), Middle = (Upper + Lower) / 2"),
+    },
+    FunctionDoc {
+        name: "highest",
+        kind: Window,
+        description: "Rolling maximum",
+        syntax: "highest(value, period)",
+        arguments: &[VALUE, PERIOD],
+        formula: None,
+    },
+    FunctionDoc {
+        name: "lowest",
+        kind: Window,
+        description: "Rolling minimum",
+        syntax: "lowest(value, period)",
+        arguments: &[VALUE, PERIOD],
+        formula: None,
+    },
+    FunctionDoc {
+        name: "mass_index",
+        kind: Window,
+        description: "Mass Index range expansion indicator",
+        syntax: "mass_index(high, low, period)",
+        arguments: &[HL, ("period", "Int64 number of ratios to sum, typically 25")],
+        formula: Some("Σ EMA9(high - low) / EMA9(EMA9(high - low)) over N periods"),
+    },
+    FunctionDoc {
+        name: "ulcer_index",
+        kind: Window,
+        description: "Ulcer Index of drawdown depth and duration",
+        syntax: "ulcer_index(value, period)",
+        arguments: &[VALUE, ("period", "Int64 rolling high and averaging lookback, typically 14")],
+        formula: Some("UI = sqrt(Σ drawdown%² / N)"),
+    },
+    FunctionDoc {
+        name: "stddev_rolling",
+        kind: Window,
+        description: "Rolling sample standard deviation",
+        syntax: "stddev_rolling(value, period [, min_periods])",
+        arguments: &[VALUE, PERIOD, ("min_periods", "Int64 observations needed before emitting a value, default period")],
+        formula: Some("StdDev = sqrt(Σ(x - mean)² / (N - 1))"),
+    },
+    FunctionDoc {
+        name: "var_rolling",
+        kind: Window,
+        description: "Rolling sample variance",
+        syntax: "var_rolling(value, period [, min_periods])",
+        arguments: &[VALUE, PERIOD, ("min_periods", "Int64 observations needed before emitting a value, default period")],
+        formula: Some("Var = Σ(x - mean)² / (N - 1)"),
+    },
+    FunctionDoc {
+        name: "volatility",
+        kind: Window,
+        description: "Annualized rolling standard deviation of log returns",
+        syntax: "volatility(price, period, annualization_factor)",
+        arguments: &[
+            ("price", "Float64 price column"),
+            ("period", "Int64 number of returns in the window"),
+            ("annualization_factor", "Float64 periods per year, e.g. 252 for daily bars"),
+        ],
+        formula: Some("σ = stddev(ln(P_t / P_t-1)) × sqrt(annualization_factor)"),
+    },
+    FunctionDoc {
+        name: "ewma_vol",
+        kind: Window,
+        description: "RiskMetrics exponentially weighted volatility of returns",
+        syntax: "ewma_vol(returns [, lambda])",
+        arguments: &[RETURNS, ("lambda", "Float64 decay factor in [0, 1), default 0.94")],
+        formula: Some("σ²_t = λ × σ²_t-1 + (1 - λ) × r²_t"),
+    },
+    FunctionDoc {
+        name: "zscore",
+        kind: Window,
+        description: "Rolling z-score of the current value",
+        syntax: "zscore(value, period)",
+        arguments: &[VALUE, PERIOD],
+        formula: Some("z = (x - mean(N)) / stddev(N)"),
+    },
+    FunctionDoc {
+        name: "linreg",
+        kind: Window,
+        description: "Rolling linear regression slope, intercept and R² as a struct",
+        syntax: "linreg(value, period)",
+        arguments: &[VALUE, PERIOD],
+        formula: Some("slope = Sxy / Sxx, intercept = ȳ - slope × x̄, R² = Sxy² / (Sxx × Syy)"),
+    },
+    FunctionDoc {
+        name: "linreg_slope",
+        kind: Window,
+        description: "Rolling linear regression slope",
+        syntax: "linreg_slope(value, period)",
+        arguments: &[VALUE, PERIOD],
+        formula: Some("slope = Sxy / Sxx"),
+    },
+    FunctionDoc {
+        name: "linreg_intercept",
+        kind: Window,
+        description: "Rolling linear regression intercept",
+        syntax: "linreg_intercept(value, period)",
+        arguments: &[VALUE, PERIOD],
+        formula: Some("intercept = ȳ - slope × x̄"),
+    },
+    FunctionDoc {
+        name: "linreg_r2",
+        kind: Window,
+        description: "Rolling linear regression coefficient of determination",
+        syntax: "linreg_r2(value, period)",
+        arguments: &[VALUE, PERIOD],
+        formula: Some("R² = Sxy² / (Sxx × Syy)"),
+    },
+    // Volume
+    FunctionDoc {
+        name: "vwap",
+        kind: Window,
+        description: "Volume weighted average price, cumulative or rolling",
+        syntax: "vwap(price, volume [, period])",
+        arguments: &[("price", "Float64 price column"), VOLUME, ("period", "Int64 rolling window rows, cumulative if omitted")],
+        formula: Some("VWAP = Σ(price × volume) / Σ(volume)"),
+    },
+    FunctionDoc {
+        name: "anchored_vwap",
+        kind: Window,
+        description: "VWAP accumulated from an anchor timestamp",
+        syntax: "anchored_vwap(price, volume, ts, anchor_ts)",
+        arguments: &[
+            ("price", "Float64 price column"),
+            VOLUME,
+            TIMESTAMP,
+            ("anchor_ts", "Int64 epoch nanoseconds or Timestamp where accumulation starts"),
+        ],
+        formula: Some("Σ(price × volume) / Σ(volume) over rows with ts >= anchor_ts"),
+    },
+    FunctionDoc {
+        name: "twap",
+        kind: Window,
+        description: "Time weighted average price over a trailing time window",
+        syntax: "twap(price, ts, period)",
+        arguments: &[("price", "Float64 price column"), TIMESTAMP, ("period", "Int64 window length in nanoseconds")],
+        formula: Some("Σ(price × time held) / Σ(time held) over (ts - period, ts]"),
+    },
+    FunctionDoc {
+        name: "obv",
+        kind: Window,
+        description: "On-balance volume",
+        syntax: "obv(close, volume)",
+        arguments: &[("close", "Float64 closing price column"), VOLUME],
+        formula: Some("OBV = previous_OBV ± volume by close-to-close direction"),
+    },
+    FunctionDoc {
+        name: "cmf",
+        kind: Window,
+        description: "Chaikin money flow",
+        syntax: "cmf(high, low, close, volume, period)",
+        arguments: &[HLC, VOLUME, ("period", "Int64 number of periods, typically 20")],
+        formula: Some("CMF = Σ(MFM × volume) / Σ(volume)"),
+    },
+    FunctionDoc {
+        name: "chaikin_osc",
+        kind: Window,
+        description: "Chaikin oscillator of the accumulation/distribution line",
+        syntax: "chaikin_osc(high, low, close, volume)",
+        arguments: &[HLC, VOLUME],
+        formula: Some("Chaikin = EMA(3, ADL) - EMA(10, ADL)"),
+    },
+    FunctionDoc {
+        name: "adl",
+        kind: Window,
+        description: "Accumulation/distribution line",
+        syntax: "adl(high, low, close, volume)",
+        arguments: &[HLC, VOLUME],
+        formula: Some("ADL = previous_ADL + MFM × volume"),
+    },
+    FunctionDoc {
+        name: "force_index",
+        kind: Window,
+        description: "EMA-smoothed force index",
+        syntax: "force_index(close, volume, period)",
+        arguments: &[("close", "Float64 closing price column"), VOLUME, ("period", "Int64 EMA period, typically 13")],
+        formula: Some("Force Index = EMA((close - previous_close) × volume, N)"),
+    },
+    // Returns and risk
+    FunctionDoc {
+        name: "returns",
+        kind: Window,
+        description: "Simple period-over-period returns",
+        syntax: "returns(price [, lag])",
+        arguments: &[("price", "Float64 price column"), ("lag", "Int64 rows back to compare against, default 1")],
+        formula: Some("returns = P_t / P_t-lag - 1"),
+    },
+    FunctionDoc {
+        name: "log_returns",
+        kind: Window,
+        description: "Logarithmic period-over-period returns",
+        syntax: "log_returns(price [, lag])",
+        arguments: &[("price", "Float64 price column"), ("lag", "Int64 rows back to compare against, default 1")],
+        formula: Some("log_returns = ln(P_t / P_t-lag)"),
+    },
+    FunctionDoc {
+        name: "cum_return",
+        kind: Window,
+        description: "Cumulative return since the first price",
+        syntax: "cum_return(price)",
+        arguments: &[("price", "Float64 price column")],
+        formula: Some("cum_return = P_t / P_0 - 1"),
+    },
+    FunctionDoc {
+        name: "cum_return_from",
+        kind: Window,
+        description: "Cumulative return compounded from periodic returns",
+        syntax: "cum_return_from(returns)",
+        arguments: &[RETURNS],
+        formula: Some("cum_return_from = Π(1 + r_i) - 1"),
+    },
+    FunctionDoc {
+        name: "drawdown",
+        kind: Window,
+        description: "Running drawdown from the peak, as a fraction ≤ 0",
+        syntax: "drawdown(value)",
+        arguments: &[VALUE],
+        formula: Some("Drawdown = value / running_max(value) - 1"),
+    },
+    FunctionDoc {
+        name: "rolling_corr",
+        kind: Window,
+        description: "Rolling Pearson correlation of two series",
+        syntax: "rolling_corr(x, y, period)",
+        arguments: &[("x, y", "Float64 series"), PERIOD],
+        formula: Some("ρ = cov(x, y) / (σx × σy)"),
+    },
+    FunctionDoc {
+        name: "rolling_cov",
+        kind: Window,
+        description: "Rolling sample covariance of two series",
+        syntax: "rolling_cov(x, y, period)",
+        arguments: &[("x, y", "Float64 series"), PERIOD],
+        formula: Some("cov = Σ(x - x̄)(y - ȳ) / (N - 1)"),
+    },
+    FunctionDoc {
+        name: "rolling_beta",
+        kind: Window,
+        description: "Rolling CAPM beta against a benchmark",
+        syntax: "rolling_beta(asset_returns, benchmark_returns, period)",
+        arguments: &[("asset_returns, benchmark_returns", "Float64 periodic returns aligned by date"), PERIOD],
+        formula: Some("β = cov(asset, benchmark) / var(benchmark)"),
+    },
+    FunctionDoc {
+        name: "rolling_alpha",
+        kind: Window,
+        description: "Rolling CAPM alpha against a benchmark",
+        syntax: "rolling_alpha(asset_returns, benchmark_returns, period)",
+        arguments: &[("asset_returns, benchmark_returns", "Float64 periodic returns aligned by date"), PERIOD],
+        formula: Some("α = mean(asset) - β × mean(benchmark)"),
+    },
+    // Price structure
+    FunctionDoc {
+        name: "pivot_points",
+        kind: Window,
+        description: "Classic floor pivot, resistance and support levels from the prior bar",
+        syntax: "pivot_points(high, low, close)",
+        arguments: &[HLC],
+        formula: Some("P = (H + L + C) / 3, R1 = 2P - L, S1 = 2P - H"),
+    },
+    FunctionDoc {
+        name: "true_range",
+        kind: Window,
+        description: "True range of each bar",
+        syntax: "true_range(high, low, close)",
+        arguments: &[HLC],
+        formula: Some("TR = max(high - low, |high - prev_close|, |low - prev_close|)"),
+    },
+    FunctionDoc {
+        name: "heikin_ashi",
+        kind: Window,
+        description: "Heikin-Ashi smoothed candles",
+        syntax: "heikin_ashi(open, high, low, close)",
+        arguments: &[OHLC],
+        formula: Some("HA close = (O + H + L + C) / 4, HA open = (prev HA open + prev HA close) / 2"),
+    },
+    FunctionDoc {
+        name: "gap_pct",
+        kind: Window,
+        description: "Opening gap from the previous close, in percent",
+        syntax: "gap_pct(open, close)",
+        arguments: &[("open", "Float64 open price column"), ("close", "Float64 close price column")],
+        formula: Some("Gap % = 100 × (open - prev close) / prev close"),
+    },
+    FunctionDoc {
+        name: "is_gap",
+        kind: Window,
+        description: "Whether the opening gap exceeds a threshold",
+        syntax: "is_gap(open, close, threshold_pct)",
+        arguments: &[
+            ("open", "Float64 open price column"),
+            ("close", "Float64 close price column"),
+            ("threshold_pct", "Float64 minimum absolute gap in percent"),
+        ],
+        formula: None,
+    },
+    FunctionDoc {
+        name: "fractal",
+        kind: Window,
+        description: "Williams fractal up/down flags",
+        syntax: "fractal(high, low, lookback)",
+        arguments: &[HL, ("lookback", "Int64 bars required on each side, 2 for the 5-bar pattern")],
+        formula: None,
+    },
+    // Scalar price helpers
+    FunctionDoc {
+        name: "typical_price",
+        kind: Scalar,
+        description: "Typical price of a bar",
+        syntax: "typical_price(high, low, close)",
+        arguments: &[HLC],
+        formula: Some("(high + low + close) / 3"),
+    },
+    FunctionDoc {
+        name: "median_price",
+        kind: Scalar,
+        description: "Median price of a bar",
+        syntax: "median_price(high, low)",
+        arguments: &[HL],
+        formula: Some("(high + low) / 2"),
+    },
+    FunctionDoc {
+        name: "weighted_close",
+        kind: Scalar,
+        description: "Close-weighted price of a bar",
+        syntax: "weighted_close(high, low, close)",
+        arguments: &[HLC],
+        formula: Some("(high + low + 2 × close) / 4"),
+    },
+    // Exchange calendar
+    FunctionDoc {
+        name: "is_trading_day",
+        kind: Scalar,
+        description: "Whether the NYSE is open on a date",
+        syntax: "is_trading_day(date)",
+        arguments: &[DATE],
+        formula: None,
+    },
+    FunctionDoc {
+        name: "is_half_day",
+        kind: Scalar,
+        description: "Whether the NYSE closes early (13:00 ET) on a date",
+        syntax: "is_half_day(date)",
+        arguments: &[DATE],
+        formula: None,
+    },
+    FunctionDoc {
+        name: "next_trading_day",
+        kind: Scalar,
+        description: "First NYSE trading day after a date",
+        syntax: "next_trading_day(date)",
+        arguments: &[DATE],
+        formula: None,
+    },
+    FunctionDoc {
+        name: "previous_trading_day",
+        kind: Scalar,
+        description: "Last NYSE trading day before a date",
+        syntax: "previous_trading_day(date)",
+        arguments: &[DATE],
+        formula: None,
+    },
+    FunctionDoc {
+        name: "session_open",
+        kind: Scalar,
+        description: "Regular session open in UTC, null on closed days",
+        syntax: "session_open(date)",
+        arguments: &[DATE],
+        formula: None,
+    },
+    FunctionDoc {
+        name: "session_close",
+        kind: Scalar,
+        description: "Regular session close in UTC, null on closed days",
+        syntax: "session_close(date)",
+        arguments: &[DATE],
+        formula: None,
+    },
+    FunctionDoc {
+        name: "trading_date",
+        kind: Scalar,
+        description: "New York date of a timestamp",
+        syntax: "trading_date(ts)",
+        arguments: &[TIMESTAMP],
+        formula: None,
+    },
+    FunctionDoc {
+        name: "market_session",
+        kind: Scalar,
+        description: "'pre', 'regular', 'post' or 'closed' for the NYSE at a timestamp",
+        syntax: "market_session(ts)",
+        arguments: &[TIMESTAMP],
+        formula: None,
+    },
+    // Option symbols
+    FunctionDoc {
+        name: "option_underlying",
+        kind: Scalar,
+        description: "Underlying root of an OCC option symbol",
+        syntax: "option_underlying(ticker)",
+        arguments: &[OPTION_TICKER],
+        formula: None,
+    },
+    FunctionDoc {
+        name: "option_expiration",
+        kind: Scalar,
+        description: "Expiration date of an OCC option symbol",
+        syntax: "option_expiration(ticker)",
+        arguments: &[OPTION_TICKER],
+        formula: None,
+    },
+    FunctionDoc {
+        name: "option_strike",
+        kind: Scalar,
+        description: "Strike price of an OCC option symbol",
+        syntax: "option_strike(ticker)",
+        arguments: &[OPTION_TICKER],
+        formula: None,
+    },
+    FunctionDoc {
+        name: "option_type",
+        kind: Scalar,
+        description: "'call' or 'put' for an OCC option symbol",
+        syntax: "option_type(ticker)",
+        arguments: &[OPTION_TICKER],
+        formula: None,
+    },
+    // Black-Scholes
+    FunctionDoc {
+        name: "bs_price",
+        kind: Scalar,
+        description: "Black-Scholes price of a European option",
+        syntax: "bs_price(option_type, spot, strike, years, rate, volatility [, dividend_yield])",
+        arguments: &[BS_CONTRACT, ("volatility", "Float64 annualized volatility, e.g. 0.2"), DIVIDEND_YIELD],
+        formula: Some("Call = S·e^(-qT)·N(d1) - K·e^(-rT)·N(d2)"),
+    },
+    FunctionDoc {
+        name: "implied_volatility",
+        kind: Scalar,
+        description: "Black-Scholes volatility matching an option's market price, null if none does",
+        syntax: "implied_volatility(option_type, spot, strike, years, rate, price [, dividend_yield])",
+        arguments: &[BS_CONTRACT, ("price", "Float64 option market price"), DIVIDEND_YIELD],
+        formula: None,
+    },
+    FunctionDoc {
+        name: "bs_delta",
+        kind: Scalar,
+        description: "Black-Scholes delta: price change per unit of spot",
+        syntax: "bs_delta(option_type, spot, strike, years, rate, volatility [, dividend_yield])",
+        arguments: &[BS_CONTRACT, ("volatility", "Float64 annualized volatility, e.g. 0.2"), DIVIDEND_YIELD],
+        formula: None,
+    },
+    FunctionDoc {
+        name: "bs_gamma",
+        kind: Scalar,
+        description: "Black-Scholes gamma: delta change per unit of spot",
+        syntax: "bs_gamma(option_type, spot, strike, years, rate, volatility [, dividend_yield])",
+        arguments: &[BS_CONTRACT, ("volatility", "Float64 annualized volatility, e.g. 0.2"), DIVIDEND_YIELD],
+        formula: None,
+    },
+    FunctionDoc {
+        name: "bs_vega",
+        kind: Scalar,
+        description: "Black-Scholes vega: price change per percentage point of volatility",
+        syntax: "bs_vega(option_type, spot, strike, years, rate, volatility [, dividend_yield])",
+        arguments: &[BS_CONTRACT, ("volatility", "Float64 annualized volatility, e.g. 0.2"), DIVIDEND_YIELD],
+        formula: None,
+    },
+    FunctionDoc {
+        name: "bs_theta",
+        kind: Scalar,
+        description: "Black-Scholes theta: price change per calendar day",
+        syntax: "bs_theta(option_type, spot, strike, years, rate, volatility [, dividend_yield])",
+        arguments: &[BS_CONTRACT, ("volatility", "Float64 annualized volatility, e.g. 0.2"), DIVIDEND_YIELD],
+        formula: None,
+    },
+    FunctionDoc {
+        name: "bs_rho",
+        kind: Scalar,
+        description: "Black-Scholes rho: price change per percentage point of the rate",
+        syntax: "bs_rho(option_type, spot, strike, years, rate, volatility [, dividend_yield])",
+        arguments: &[BS_CONTRACT, ("volatility", "Float64 annualized volatility, e.g. 0.2"), DIVIDEND_YIELD],
+        formula: None,
+    },
+    // Aggregates
+    FunctionDoc {
+        name: "max_drawdown",
+        kind: Aggregate,
+        description: "Largest peak-to-trough decline, as a fraction ≤ 0",
+        syntax: "max_drawdown(value)",
+        arguments: &[VALUE],
+        formula: Some("Max Drawdown = min(value / running_max(value) - 1)"),
+    },
+    FunctionDoc {
+        name: "sharpe_ratio",
+        kind: Aggregate,
+        description: "Sharpe ratio of periodic returns",
+        syntax: "sharpe_ratio(returns [, risk_free])",
+        arguments: &[RETURNS, ("risk_free", "Float64 risk-free return per period, default 0")],
+        formula: Some("Sharpe = mean(r - rf) / σ(r - rf)"),
+    },
+    FunctionDoc {
+        name: "sortino_ratio",
+        kind: Aggregate,
+        description: "Sortino ratio of periodic returns",
+        syntax: "sortino_ratio(returns [, risk_free])",
+        arguments: &[RETURNS, ("risk_free", "Float64 risk-free return per period, default 0")],
+        formula: Some("Sortino = mean(r - rf) / downside σ"),
+    },
+    FunctionDoc {
+        name: "calmar_ratio",
+        kind: Aggregate,
+        description: "Calmar ratio of periodic returns",
+        syntax: "calmar_ratio(returns [, periods_per_year])",
+        arguments: &[RETURNS, ("periods_per_year", "Float64 annualization periods, default 252")],
+        formula: Some("Calmar = CAGR / |max drawdown|"),
+    },
+    FunctionDoc {
+        name: "ulcer_performance_index",
+        kind: Aggregate,
+        description: "Return per unit of Ulcer Index drawdown risk",
+        syntax: "ulcer_performance_index(value [, risk_free])",
+        arguments: &[VALUE, ("risk_free", "Float64 risk-free return in percent, default 0")],
+        formula: Some("UPI = (total return% - risk_free) / UI"),
+    },
+    FunctionDoc {
+        name: "volume_profile",
+        kind: Aggregate,
+        description: "Volume traded at each price level and the point of control",
+        syntax: "volume_profile(price, volume, bin_size)",
+        arguments: &[("price", "Float64 price column"), VOLUME, ("bin_size", "Float64 width of each price level")],
+        formula: None,
+    },
+    FunctionDoc {
+        name: "last_sma",
+        kind: Aggregate,
+        description: "Final sma value of each group",
+        syntax: "last_sma(value, window_size [, min_periods] ORDER BY ...)",
+        arguments: &[VALUE, ("window_size, min_periods", "as for sma")],
+        formula: None,
+    },
+    FunctionDoc {
+        name: "last_ema",
+        kind: Aggregate,
+        description: "Final ema value of each group",
+        syntax: "last_ema(value, window_size [, min_periods] ORDER BY ...)",
+        arguments: &[VALUE, ("window_size, min_periods", "as for ema")],
+        formula: None,
+    },
+    FunctionDoc {
+        name: "last_rsi",
+        kind: Aggregate,
+        description: "Final rsi value of each group",
+        syntax: "last_rsi(value, window_size [, method] ORDER BY ...)",
+        arguments: &[VALUE, ("window_size, method", "as for rsi")],
+        formula: None,
+    },
+    FunctionDoc {
+        name: "last_macd",
+        kind: Aggregate,
+        description: "Final macd value of each group",
+        syntax: "last_macd(value ORDER BY ...)",
+        arguments: &[VALUE],
+        formula: None,
+    },
+    FunctionDoc {
+        name: "last_bollinger",
+        kind: Aggregate,
+        description: "Final bollinger bands of each group",
+        syntax: "last_bollinger(value, period, std_multiplier ORDER BY ...)",
+        arguments: &[VALUE, ("period, std_multiplier", "as for bollinger")],
+        formula: None,
+    },
+    FunctionDoc {
+        name: "last_zscore",
+        kind: Aggregate,
+        description: "Final zscore value of each group",
+        syntax: "last_zscore(value, period ORDER BY ...)",
+        arguments: &[VALUE, PERIOD],
+        formula: None,
+    },
+    FunctionDoc {
+        name: "last_atr",
+        kind: Aggregate,
+        description: "Final atr value of each group",
+        syntax: "last_atr(high, low, close, period ORDER BY ...)",
+        arguments: &[HLC, ("period", "as for atr")],
+        formula: None,
+    },
+    FunctionDoc {
+        name: "last_cci",
+        kind: Aggregate,
+        description: "Final cci value of each group",
+        syntax: "last_cci(high, low, close, period ORDER BY ...)",
+        arguments: &[HLC, ("period", "as for cci")],
+        formula: None,
+    },
+    FunctionDoc {
+        name: "last_stochastic",
+        kind: Aggregate,
+        description: "Final stochastic %K and %D of each group",
+        syntax: "last_stochastic(high, low, close, k_period, d_period ORDER BY ...)",
+        arguments: &[HLC, ("k_period, d_period", "as for stochastic")],
+        formula: None,
+    },
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    #[tokio::test]
+    async fn test_function_docs() -> Result<()> {
+        let ctx = SessionContext::new();
+        let builtin = ctx.state();
+        let builtin_names = builtin
+            .window_functions()
+            .keys()
+            .chain(builtin.aggregate_functions().keys())
+            .chain(builtin.scalar_functions().keys())
+            .cloned()
+            .collect::<HashSet<_>>();
+
+        // Custom indicators come from users, so only the built-in ones are checked
+        crate::FinancialFunctions::all().without_custom_indicators().register(&ctx)?;
+        register_function_docs(&ctx)?;
+
+        // Every registered financial function is documented
+        let state = ctx.state();
+        let undocumented = state
+            .window_functions()
+            .keys()
+            .chain(state.aggregate_functions().keys())
+            .chain(state.scalar_functions().keys())
+            .filter(|name| !builtin_names.contains(*name))
+            .filter(|name| documentation(name).is_none())
+            .cloned()
+            .collect::<Vec<_>>();
+        assert!(undocumented.is_empty(), "Undocumented functions: {:?}", undocumented);
+
+        // Test the documentation table using SQL
+        let result = ctx
+            .sql("SELECT name, kind, syntax FROM financial_functions WHERE kind <> 'window' ORDER BY name")
+            .await?
+            .collect()
+            .await?;
+
+        println!("Function Docs Test Results:");
+        datafusion::arrow::util::pretty::print_batches(&result)?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,235 @@
+//! `Expr` builders for the financial functions
+//!
+//! Lets DataFrame users build indicator expressions without SQL strings:
+//!
+//! ```ignore
+//! use datafusion::logical_expr::ExprFunctionExt;
+//! use datafusion::prelude::{col, lit};
+//! use datafusion_functions_financial::expr::sma;
+//!
+//! let sma_20 = sma(col("close"), lit(20i64))
+//!     .partition_by(vec![col("ticker")])
+//!     .order_by(vec![col("window_start").sort(true, false)])
+//!     .build()?
+//!     .alias("sma_20");
+//! ```
+//!
+//! Window builders return a window `Expr` over the whole partition; refine it
+//! with `ExprFunctionExt`. They call the same UDFs SQL registers, with the
+//! multi-input indicators wrapped to accept an OHLCV struct, so a builder
+//! expression plans and coerces exactly like its SQL form. Builders cover each function's required arguments;
+//! call the UDF directly (`WindowUDF::from(..).call(args)`) for optional ones.
+
+use datafusion::logical_expr::{AggregateUDF, Expr, ScalarUDF, WindowUDF};
+
+use crate::functions::adl::AccumulationDistributionLine;
+use crate::functions::adx::AverageDirectionalIndex;
+use crate::functions::anchored_vwap::AnchoredVwap;
+use crate::functions::atr::AverageTrueRange;
+use crate::functions::beta::RollingCapm;
+use crate::functions::bollinger::BollingerBands;
+use crate::functions::cci::CommodityChannelIndex;
+use crate::functions::chaikin::{ChaikinMoneyFlow, ChaikinOscillator};
+use crate::functions::coppock::CoppockCurve;
+use crate::functions::correlation::RollingCorrelation;
+use crate::functions::cum_return::CumulativeReturn;
+use crate::functions::donchian::{DonchianChannels, RollingExtreme};
+use crate::functions::drawdown::{Drawdown, MaxDrawdown};
+use crate::functions::ema::ExponentialMovingAverage;
+use crate::functions::ewma_vol::EwmaVolatility;
+use crate::functions::force_index::ForceIndex;
+use crate::functions::fractal::Fractal;
+use crate::functions::gaps::GapDetector;
+use crate::functions::heikin_ashi::HeikinAshi;
+use crate::functions::ichimoku::Ichimoku;
+use crate::functions::kama::KaufmanAdaptiveMovingAverage;
+use crate::functions::kst::KnowSureThing;
+use crate::functions::last_indicators::LastIndicator;
+use crate::functions::linreg::LinearRegression;
+use crate::functions::macd::MacdIndicator;
+use crate::functions::mass_index::MassIndex;
+use crate::functions::obv::OnBalanceVolume;
+use crate::functions::ohlcv::{OhlcvArgs, CV, HL, HLC, HLCV, OC, OHLC};
+use crate::functions::pivot_points::PivotPoints;
+use crate::functions::price_transforms::{PriceTransform, TrueRange};
+use crate::functions::psar::ParabolicSar;
+use crate::functions::returns::PeriodReturns;
+use crate::functions::risk_ratios::RiskRatio;
+use crate::functions::rolling_stats::RollingDispersion;
+use crate::functions::rsi::RelativeStrengthIndex;
+use crate::functions::rvi::RelativeVigorIndex;
+use crate::functions::sma::SimpleMovingAverage;
+use crate::functions::stochastic::StochasticOscillator;
+use crate::functions::trix::Trix;
+use crate::functions::twap::TimeWeightedAveragePrice;
+use crate::functions::ulcer::{UlcerIndex, UlcerPerformanceIndex};
+use crate::functions::volatility::RealizedVolatility;
+use crate::functions::volume_profile::VolumeProfile;
+use crate::functions::vwap::VolumeWeightedAveragePrice;
+use crate::functions::zscore::RollingZScore;
+
+/// Define a builder that calls a window, aggregate or scalar UDF
+macro_rules! expr_fn {
+    ($udf_type:ident, $fn_name:ident, $udf:expr, [$($arg:ident),*]) => {
+        #[doc = concat!("`", stringify!($fn_name), "(", stringify!($($arg),*), ")`")]
+        pub fn $fn_name($($arg: Expr),*) -> Expr {
+            $udf_type::from($udf).call(vec![$($arg),*])
+        }
+    };
+}
+
+// Moving averages and momentum
+expr_fn!(WindowUDF, sma, SimpleMovingAverage::new(), [value, window_size]);
+expr_fn!(WindowUDF, ema, ExponentialMovingAverage::new(), [value, window_size]);
+expr_fn!(WindowUDF, rsi, RelativeStrengthIndex::new(), [value, window_size]);
+expr_fn!(WindowUDF, macd, MacdIndicator::new(), [value]);
+expr_fn!(WindowUDF, macd_full, MacdIndicator::full(), [value]);
+expr_fn!(WindowUDF, kama, KaufmanAdaptiveMovingAverage::new(), [value, er_period, fast, slow]);
+expr_fn!(WindowUDF, trix, Trix::new(), [value, period]);
+expr_fn!(WindowUDF, coppock, CoppockCurve::new(), [value]);
+expr_fn!(WindowUDF, kst, KnowSureThing::new(), [value]);
+expr_fn!(WindowUDF, stochastic, OhlcvArgs::new(StochasticOscillator::new(), HLC), [high, low, close, k_period, d_period]);
+expr_fn!(WindowUDF, rvi, OhlcvArgs::new(RelativeVigorIndex::new(), OHLC), [open, high, low, close, period]);
+
+// Trend and volatility
+expr_fn!(WindowUDF, bollinger, BollingerBands::new(), [value, period, std_multiplier]);
+expr_fn!(WindowUDF, atr, OhlcvArgs::new(AverageTrueRange::new(), HLC), [high, low, close, period]);
+expr_fn!(WindowUDF, adx, OhlcvArgs::new(AverageDirectionalIndex::new(), HLC), [high, low, close, period]);
+expr_fn!(WindowUDF, cci, OhlcvArgs::new(CommodityChannelIndex::new(), HLC), [high, low, close, period]);
+expr_fn!(WindowUDF, ichimoku, OhlcvArgs::new(Ichimoku::new(), HLC), [high, low, close]);
+expr_fn!(WindowUDF, psar, OhlcvArgs::new(ParabolicSar::new(), HL), [high, low, acceleration, max_acceleration]);
+expr_fn!(WindowUDF, donchian, OhlcvArgs::new(DonchianChannels::new(), HL), [high, low, period]);
+expr_fn!(WindowUDF, highest, RollingExtreme::highest(), [value, period]);
+expr_fn!(WindowUDF, lowest, RollingExtreme::lowest(), [value, period]);
+expr_fn!(WindowUDF, mass_index, OhlcvArgs::new(MassIndex::new(), HL), [high, low, period]);
+expr_fn!(WindowUDF, ulcer_index, UlcerIndex::new(), [value, period]);
+expr_fn!(WindowUDF, stddev_rolling, RollingDispersion::stddev(), [value, period]);
+expr_fn!(WindowUDF, var_rolling, RollingDispersion::variance(), [value, period]);
+expr_fn!(WindowUDF, volatility, RealizedVolatility::new(), [price, period, annualization_factor]);
+expr_fn!(WindowUDF, ewma_vol, EwmaVolatility::new(), [returns]);
+expr_fn!(WindowUDF, zscore, RollingZScore::new(), [value, period]);
+expr_fn!(WindowUDF, linreg, LinearRegression::new(), [value, period]);
+expr_fn!(WindowUDF, linreg_slope, LinearRegression::slope(), [value, period]);
+expr_fn!(WindowUDF, linreg_intercept, LinearRegression::intercept(), [value, period]);
+expr_fn!(WindowUDF, linreg_r2, LinearRegression::r_squared(), [value, period]);
+
+// Volume
+expr_fn!(WindowUDF, vwap, VolumeWeightedAveragePrice::new(), [price, volume]);
+expr_fn!(WindowUDF, anchored_vwap, AnchoredVwap::new(), [price, volume, ts, anchor_ts]);
+expr_fn!(WindowUDF, twap, TimeWeightedAveragePrice::new(), [price, ts, period]);
+expr_fn!(WindowUDF, obv, OhlcvArgs::new(OnBalanceVolume::new(), CV), [close, volume]);
+expr_fn!(WindowUDF, cmf, OhlcvArgs::new(ChaikinMoneyFlow::new(), HLCV), [high, low, close, volume, period]);
+expr_fn!(WindowUDF, chaikin_osc, OhlcvArgs::new(ChaikinOscillator::new(), HLCV), [high, low, close, volume]);
+expr_fn!(WindowUDF, adl, OhlcvArgs::new(AccumulationDistributionLine::new(), HLCV), [high, low, close, volume]);
+expr_fn!(WindowUDF, force_index, OhlcvArgs::new(ForceIndex::new(), CV), [close, volume, period]);
+
+// Returns and risk
+expr_fn!(WindowUDF, returns, PeriodReturns::simple(), [price]);
+expr_fn!(WindowUDF, log_returns, PeriodReturns::log(), [price]);
+expr_fn!(WindowUDF, cum_return, CumulativeReturn::from_prices(), [price]);
+expr_fn!(WindowUDF, cum_return_from, CumulativeReturn::from_returns(), [returns]);
+expr_fn!(WindowUDF, drawdown, Drawdown::new(), [value]);
+expr_fn!(WindowUDF, rolling_corr, RollingCorrelation::correlation(), [x, y, period]);
+expr_fn!(WindowUDF, rolling_cov, RollingCorrelation::covariance(), [x, y, period]);
+expr_fn!(WindowUDF, rolling_beta, RollingCapm::beta(), [asset_returns, benchmark_returns, period]);
+expr_fn!(WindowUDF, rolling_alpha, RollingCapm::alpha(), [asset_returns, benchmark_returns, period]);
+
+// Price structure
+expr_fn!(WindowUDF, pivot_points, OhlcvArgs::new(PivotPoints::new(), HLC), [high, low, close]);
+expr_fn!(WindowUDF, true_range, OhlcvArgs::new(TrueRange::new(), HLC), [high, low, close]);
+expr_fn!(WindowUDF, heikin_ashi, OhlcvArgs::new(HeikinAshi::new(), OHLC), [open, high, low, close]);
+expr_fn!(WindowUDF, gap_pct, OhlcvArgs::new(GapDetector::percent(), OC), [open, close]);
+expr_fn!(WindowUDF, is_gap, OhlcvArgs::new(GapDetector::flag(), OC), [open, close, threshold_pct]);
+expr_fn!(WindowUDF, fractal, OhlcvArgs::new(Fractal::new(), HL), [high, low, lookback]);
+
+// Scalar price helpers
+expr_fn!(ScalarUDF, typical_price, PriceTransform::typical(), [high, low, close]);
+expr_fn!(ScalarUDF, median_price, PriceTransform::median(), [high, low]);
+expr_fn!(ScalarUDF, weighted_close, PriceTransform::weighted_close(), [high, low, close]);
+
+// Aggregates
+expr_fn!(AggregateUDF, max_drawdown, MaxDrawdown::new(), [value]);
+expr_fn!(AggregateUDF, sharpe_ratio, RiskRatio::sharpe(), [returns]);
+expr_fn!(AggregateUDF, sortino_ratio, RiskRatio::sortino(), [returns]);
+expr_fn!(AggregateUDF, calmar_ratio, RiskRatio::calmar(), [returns]);
+expr_fn!(AggregateUDF, ulcer_performance_index, UlcerPerformanceIndex::new(), [value]);
+expr_fn!(AggregateUDF, volume_profile, VolumeProfile::new(), [price, volume, bin_size]);
+expr_fn!(AggregateUDF, last_sma, LastIndicator::sma(), [value, window_size]);
+expr_fn!(AggregateUDF, last_ema, LastIndicator::ema(), [value, window_size]);
+expr_fn!(AggregateUDF, last_rsi, LastIndicator::rsi(), [value, window_size]);
+expr_fn!(AggregateUDF, last_macd, LastIndicator::macd(), [value]);
+expr_fn!(AggregateUDF, last_bollinger, LastIndicator::bollinger(), [value, period, std_multiplier]);
+expr_fn!(AggregateUDF, last_zscore, LastIndicator::zscore(), [value, period]);
+expr_fn!(AggregateUDF, last_atr, LastIndicator::atr(), [high, low, close, period]);
+expr_fn!(AggregateUDF, last_cci, LastIndicator::cci(), [high, low, close, period]);
+expr_fn!(AggregateUDF, last_stochastic, LastIndicator::stochastic(), [high, low, close, k_period, d_period]);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use datafusion::error::Result;
+    use datafusion::execution::context::SessionContext;
+    use datafusion::logical_expr::expr::WindowFunction;
+    use datafusion::logical_expr::{ExprFunctionExt, WindowFunctionDefinition};
+    use datafusion::prelude::{col, lit};
+
+    #[tokio::test]
+    async fn test_expr_builders() -> Result<()> {
+        let ctx = SessionContext::new();
+        let df = ctx
+            .sql("SELECT * FROM (VALUES
+                ('AAPL', 1, 100.0), ('AAPL', 2, 101.5), ('AAPL', 3, 99.8), ('AAPL', 4, 102.2),
+                ('MSFT', 1, 300.0), ('MSFT', 2, 303.1), ('MSFT', 3, 301.4), ('MSFT', 4, 305.0)
+            ) AS t(ticker, day, close)")
+            .await?;
+
+        // Test window builders with partitioning and ordering via the DataFrame API
+        let result = df
+            .select(vec![
+                col("ticker"),
+                col("day"),
+                col("close"),
+                sma(col("close"), lit(2i64))
+                    .partition_by(vec![col("ticker")])
+                    .order_by(vec![col("day").sort(true, false)])
+                    .build()?
+                    .alias("sma_2"),
+                rsi(col("close"), lit(2i64))
+                    .partition_by(vec![col("ticker")])
+                    .order_by(vec![col("day").sort(true, false)])
+                    .build()?
+                    .alias("rsi_2"),
+            ])?
+            .sort(vec![col("ticker").sort(true, false), col("day").sort(true, false)])?
+            .collect()
+            .await?;
+
+        println!("Expr Builder Test Results:");
+        datafusion::arrow::util::pretty::print_batches(&result)?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_ohlcv_builders_match_sql() {
+        let ctx = SessionContext::new();
+        crate::register_financial_functions(&ctx).unwrap();
+
+        for expr in [
+            atr(col("high"), col("low"), col("close"), lit(14i64)),
+            obv(col("close"), col("volume")),
+            heikin_ashi(col("open"), col("high"), col("low"), col("close")),
+        ] {
+            let Expr::WindowFunction(WindowFunction {
+                fun: WindowFunctionDefinition::WindowUDF(udf),
+                ..
+            }) = expr
+            else {
+                panic!("expected a window UDF call");
+            };
+            let registered = ctx.udwf(udf.name()).unwrap();
+            assert!(udf.inner().as_any().is::<OhlcvArgs>(), "{}", udf.name());
+            assert!(registered.inner().as_any().is::<OhlcvArgs>(), "{}", udf.name());
+        }
+    }
+}
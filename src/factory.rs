@@ -0,0 +1,164 @@
+//! `CREATE FUNCTION` support for SQL-only deployments
+//!
+//! Installing [`FinancialFunctionFactory`] on a context lets SQL clients
+//! enable functions one statement at a time, without any Rust calls:
+//!
+//! ```sql
+//! CREATE FUNCTION sma() LANGUAGE financial;
+//! -- or under another name
+//! CREATE FUNCTION fin_rsi() LANGUAGE financial AS 'rsi';
+//! ```
+//!
+//! `DROP FUNCTION` removes them again as usual.
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use datafusion::error::{DataFusionError, Result};
+use datafusion::execution::context::{FunctionFactory, RegisterFunction, SessionContext, SessionState};
+use datafusion::logical_expr::{AggregateUDF, CreateFunction, Expr, ScalarUDF, WindowUDF};
+use datafusion::scalar::ScalarValue;
+
+use crate::registration::{FinancialFunctions, Renamed};
+
+/// `LANGUAGE` handled by [`FinancialFunctionFactory`]
+pub const LANGUAGE: &str = "financial";
+
+/// Resolves `CREATE FUNCTION <name>() LANGUAGE financial [AS '<function>']`
+///
+/// The optional body names the financial function to bind, so it can be
+/// exposed under a different name; without it `<name>` itself is looked up.
+/// Custom indicators registered through `register_indicator` are included.
+#[derive(Debug, Default)]
+pub struct FinancialFunctionFactory;
+
+impl FinancialFunctionFactory {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+#[async_trait]
+impl FunctionFactory for FinancialFunctionFactory {
+    async fn create(&self, _state: &SessionState, statement: CreateFunction) -> Result<RegisterFunction> {
+        let language = statement.params.language.as_ref().map(|ident| ident.value.as_str());
+        if !language.is_some_and(|language| language.eq_ignore_ascii_case(LANGUAGE)) {
+            return Err(DataFusionError::Plan(format!(
+                "CREATE FUNCTION {} must use LANGUAGE {}",
+                statement.name, LANGUAGE
+            )));
+        }
+
+        let target = match &statement.params.function_body {
+            None => statement.name.clone(),
+            Some(Expr::Literal(ScalarValue::Utf8(Some(target)))) => target.clone(),
+            Some(other) => {
+                return Err(DataFusionError::Plan(format!(
+                    "CREATE FUNCTION {} body must name a financial function as a string, got {}",
+                    statement.name, other
+                )))
+            }
+        };
+
+        financial_function(&statement.name, &target)
+    }
+}
+
+/// Look up the financial function `target` and expose it as `name`
+fn financial_function(name: &str, target: &str) -> Result<RegisterFunction> {
+    let builtin = SessionContext::new().state();
+    let scratch = SessionContext::new();
+    FinancialFunctions::all().register(&scratch)?;
+    let state = scratch.state();
+
+    if let Some(udf) = state
+        .window_functions()
+        .get(target)
+        .filter(|_| !builtin.window_functions().contains_key(target))
+    {
+        return Ok(RegisterFunction::Window(match name == target {
+            true => Arc::clone(udf),
+            false => Arc::new(WindowUDF::from(Renamed::named(name, Arc::clone(udf.inner())))),
+        }));
+    }
+    if let Some(udf) = state
+        .aggregate_functions()
+        .get(target)
+        .filter(|_| !builtin.aggregate_functions().contains_key(target))
+    {
+        return Ok(RegisterFunction::Aggregate(match name == target {
+            true => Arc::clone(udf),
+            false => Arc::new(AggregateUDF::from(Renamed::named(name, Arc::clone(udf.inner())))),
+        }));
+    }
+    if let Some(udf) = state
+        .scalar_functions()
+        .get(target)
+        .filter(|_| !builtin.scalar_functions().contains_key(target))
+    {
+        return Ok(RegisterFunction::Scalar(match name == target {
+            true => Arc::clone(udf),
+            false => Arc::new(ScalarUDF::from(Renamed::named(name, Arc::clone(udf.inner())))),
+        }));
+    }
+
+    Err(DataFusionError::Plan(format!("Unknown financial function '{}'", target)))
+}
+
+/// Install [`FinancialFunctionFactory`] on `ctx`
+pub fn with_financial_function_factory(ctx: SessionContext) -> SessionContext {
+    ctx.with_function_factory(Arc::new(FinancialFunctionFactory::new()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_create_function() -> Result<()> {
+        let ctx = with_financial_function_factory(SessionContext::new());
+
+        // Nothing is registered until the CREATE FUNCTION statements run
+        assert!(ctx.sql("SELECT sma(1.0, 3) OVER ()").await.is_err());
+
+        for statement in [
+            "CREATE FUNCTION sma() LANGUAGE financial",
+            "CREATE FUNCTION fin_rsi() LANGUAGE financial AS 'rsi'",
+            "CREATE FUNCTION typical_price() LANGUAGE financial",
+        ] {
+            ctx.sql(statement).await?.collect().await?;
+        }
+
+        let result = ctx
+            .sql("SELECT price,
+                    sma(price, 3) OVER () AS sma_3,
+                    fin_rsi(price, 3) OVER () AS rsi_3,
+                    typical_price(price + 1.0, price - 1.0, price) AS tp
+                FROM (VALUES (1.0), (2.0), (3.0), (2.5), (4.0), (5.0)) AS t(price)")
+            .await?
+            .collect()
+            .await?;
+
+        println!("CREATE FUNCTION Test Results:");
+        datafusion::arrow::util::pretty::print_batches(&result)?;
+
+        // Unknown functions, built-in functions and other languages are rejected
+        for statement in [
+            "CREATE FUNCTION not_an_indicator() LANGUAGE financial",
+            "CREATE FUNCTION row_number() LANGUAGE financial",
+            "CREATE FUNCTION ema() LANGUAGE python AS 'ema'",
+        ] {
+            assert!(ctx.sql(statement).await.is_err(), "{}", statement);
+        }
+
+        // Aliases keep the period checks of the function they bind
+        let err = ctx
+            .sql("SELECT fin_rsi(1.0, 0) OVER ()")
+            .await?
+            .into_optimized_plan()
+            .unwrap_err();
+        assert!(err.to_string().contains("fin_rsi: window_size"), "{}", err);
+
+        Ok(())
+    }
+}
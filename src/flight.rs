@@ -0,0 +1,305 @@
+//! Arrow Flight SQL server
+//!
+//! [`FlightSqlServer`] answers Flight SQL queries against a
+//! `SessionContext`, so Python (ADBC, `pyarrow.flight`), JDBC and BI clients
+//! can query registered tables remotely, raw or enriched with the financial
+//! functions, without exporting files. Register the functions and tables
+//! first.
+//!
+//! Ad hoc queries and table listing are supported. The server is read-only:
+//! DDL (`CREATE`, `DROP`), DML (`INSERT`, `COPY`) and statements such as
+//! `SET` are rejected, and prepared statements, updates and the other
+//! catalog commands return `Unimplemented`.
+//!
+//! **Anyone who can reach the address can query every registered table**
+//! unless a token is set with [`FlightSqlServer::with_bearer_token`], in
+//! which case requests must send `authorization: Bearer <token>`. The
+//! server speaks plain gRPC; put TLS in front of it, or bind to localhost,
+//! for anything beyond a trusted network.
+
+use std::net::SocketAddr;
+use std::pin::Pin;
+
+use arrow_flight::encode::FlightDataEncoderBuilder;
+use arrow_flight::error::FlightError;
+use arrow_flight::flight_service_server::{FlightService, FlightServiceServer};
+use arrow_flight::sql::server::FlightSqlService;
+use arrow_flight::sql::{CommandGetTables, CommandStatementQuery, ProstMessageExt, SqlInfo, TicketStatementQuery};
+use arrow_flight::{FlightData, FlightDescriptor, FlightEndpoint, FlightInfo, HandshakeRequest, HandshakeResponse, Ticket};
+use datafusion::dataframe::DataFrame;
+use datafusion::datasource::TableType;
+use datafusion::error::{DataFusionError, Result};
+use datafusion::execution::context::{SQLOptions, SessionContext};
+use futures::{stream, Stream, TryStreamExt};
+use prost::Message;
+use tonic::{Request, Response, Status, Streaming};
+
+/// Flight SQL service over a `SessionContext`
+#[derive(Clone)]
+pub struct FlightSqlServer {
+    ctx: SessionContext,
+    token: Option<String>,
+}
+
+impl FlightSqlServer {
+    /// Serve queries against `ctx`, to any client
+    pub fn new(ctx: SessionContext) -> Self {
+        Self { ctx, token: None }
+    }
+
+    /// Require every request to carry `authorization: Bearer <token>`
+    pub fn with_bearer_token(mut self, token: impl Into<String>) -> Self {
+        self.token = Some(token.into());
+        self
+    }
+
+    /// The tonic service, for adding to a server of the caller's own
+    pub fn into_service(self) -> FlightServiceServer<Self> {
+        FlightServiceServer::new(self)
+    }
+
+    /// Listen on `addr` until the server fails
+    pub async fn serve(self, addr: SocketAddr) -> Result<()> {
+        tonic::transport::Server::builder()
+            .add_service(self.into_service())
+            .serve(addr)
+            .await
+            .map_err(|e| DataFusionError::External(Box::new(e)))
+    }
+
+    /// Reject requests without the configured bearer token
+    fn authorize<T>(&self, request: &Request<T>) -> std::result::Result<(), Status> {
+        let Some(token) = &self.token else {
+            return Ok(());
+        };
+        let given = request
+            .metadata()
+            .get("authorization")
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Bearer "));
+        match given {
+            Some(given) if constant_time_eq(given.as_bytes(), token.as_bytes()) => Ok(()),
+            _ => Err(Status::unauthenticated("Missing or invalid bearer token")),
+        }
+    }
+
+    /// Plan a client's query, refusing anything that would change the context
+    async fn plan(&self, query: &str) -> std::result::Result<DataFrame, Status> {
+        let options = SQLOptions::new()
+            .with_allow_ddl(false)
+            .with_allow_dml(false)
+            .with_allow_statements(false);
+        self.ctx.sql_with_options(query, options).await.map_err(invalid_query)
+    }
+}
+
+type HandshakeStream = Pin<Box<dyn Stream<Item = std::result::Result<HandshakeResponse, Status>> + Send>>;
+
+type DoGetStream = Pin<Box<dyn Stream<Item = std::result::Result<FlightData, Status>> + Send + 'static>>;
+
+#[tonic::async_trait]
+impl FlightSqlService for FlightSqlServer {
+    type FlightService = FlightSqlServer;
+
+    /// Check the bearer token up front for clients that handshake, echoing
+    /// it back as the session token
+    async fn do_handshake(
+        &self,
+        request: Request<Streaming<HandshakeRequest>>,
+    ) -> std::result::Result<Response<HandshakeStream>, Status> {
+        self.authorize(&request)?;
+        let payload = self.token.clone().unwrap_or_default();
+        let response = HandshakeResponse {
+            protocol_version: 0,
+            payload: payload.clone().into(),
+        };
+        let mut response: Response<HandshakeStream> = Response::new(Box::pin(stream::iter(vec![Ok(response)])));
+        if self.token.is_some() {
+            let value = format!("Bearer {}", payload).parse().map_err(internal)?;
+            response.metadata_mut().insert("authorization", value);
+        }
+        Ok(response)
+    }
+
+    /// Plan the query for its schema; the ticket carries the SQL to run
+    async fn get_flight_info_statement(
+        &self,
+        query: CommandStatementQuery,
+        request: Request<FlightDescriptor>,
+    ) -> std::result::Result<Response<FlightInfo>, Status> {
+        self.authorize(&request)?;
+        let df = self.plan(&query.query).await?;
+        let schema = df.schema().as_arrow().clone();
+
+        let handle = TicketStatementQuery {
+            statement_handle: query.query.into_bytes().into(),
+        };
+        let ticket = Ticket::new(handle.as_any().encode_to_vec());
+        let info = FlightInfo::new()
+            .try_with_schema(&schema)
+            .map_err(internal)?
+            .with_endpoint(FlightEndpoint::new().with_ticket(ticket))
+            .with_descriptor(request.into_inner());
+        Ok(Response::new(info))
+    }
+
+    async fn do_get_statement(
+        &self,
+        ticket: TicketStatementQuery,
+        request: Request<Ticket>,
+    ) -> std::result::Result<Response<<Self as FlightService>::DoGetStream>, Status> {
+        self.authorize(&request)?;
+        // The ticket is client-supplied, so it is checked like a fresh query
+        let query = String::from_utf8(ticket.statement_handle.to_vec())
+            .map_err(|_| Status::invalid_argument("Statement handle is not UTF-8 SQL"))?;
+        let df = self.plan(&query).await?;
+        let schema = df.schema().inner().clone();
+        let batches = df
+            .execute_stream()
+            .await
+            .map_err(internal)?
+            .map_err(|e| FlightError::ExternalError(Box::new(e)));
+
+        let stream: DoGetStream = Box::pin(
+            FlightDataEncoderBuilder::new()
+                .with_schema(schema)
+                .build(batches)
+                .map_err(Status::from),
+        );
+        Ok(Response::new(stream))
+    }
+
+    async fn get_flight_info_tables(
+        &self,
+        query: CommandGetTables,
+        request: Request<FlightDescriptor>,
+    ) -> std::result::Result<Response<FlightInfo>, Status> {
+        self.authorize(&request)?;
+        let schema = query.clone().into_builder().schema();
+        let ticket = Ticket::new(query.as_any().encode_to_vec());
+        let info = FlightInfo::new()
+            .try_with_schema(&schema)
+            .map_err(internal)?
+            .with_endpoint(FlightEndpoint::new().with_ticket(ticket))
+            .with_descriptor(request.into_inner());
+        Ok(Response::new(info))
+    }
+
+    /// Every table of every catalog and schema; the builder applies the
+    /// command's filters
+    async fn do_get_tables(
+        &self,
+        query: CommandGetTables,
+        request: Request<Ticket>,
+    ) -> std::result::Result<Response<<Self as FlightService>::DoGetStream>, Status> {
+        self.authorize(&request)?;
+        let mut builder = query.into_builder();
+        for catalog_name in self.ctx.catalog_names() {
+            let Some(catalog) = self.ctx.catalog(&catalog_name) else {
+                continue;
+            };
+            for schema_name in catalog.schema_names() {
+                let Some(schema) = catalog.schema(&schema_name) else {
+                    continue;
+                };
+                for table_name in schema.table_names() {
+                    let Some(table) = schema.table(&table_name).await.map_err(internal)? else {
+                        continue;
+                    };
+                    let table_type = match table.table_type() {
+                        TableType::Base => "TABLE",
+                        TableType::View => "VIEW",
+                        TableType::Temporary => "LOCAL TEMPORARY",
+                    };
+                    builder
+                        .append(&catalog_name, &schema_name, &table_name, table_type, &table.schema())
+                        .map_err(internal)?;
+                }
+            }
+        }
+        let batch = builder.build().map_err(internal)?;
+
+        let stream: DoGetStream = Box::pin(
+            FlightDataEncoderBuilder::new()
+                .with_schema(batch.schema())
+                .build(stream::once(async { Ok(batch) }))
+                .map_err(Status::from),
+        );
+        Ok(Response::new(stream))
+    }
+
+    async fn register_sql_info(&self, _id: i32, _result: &SqlInfo) {}
+}
+
+fn invalid_query(error: DataFusionError) -> Status {
+    Status::invalid_argument(error.to_string())
+}
+
+fn internal(error: impl std::fmt::Display) -> Status {
+    Status::internal(error.to_string())
+}
+
+/// Compare secrets without leaking the length of the matching prefix
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |diff, (x, y)| diff | (x ^ y)) == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn statement(query: &str) -> (CommandStatementQuery, Request<FlightDescriptor>) {
+        let command = CommandStatementQuery {
+            query: query.to_string(),
+            transaction_id: None,
+        };
+        (command, Request::new(FlightDescriptor::new_cmd(Vec::new())))
+    }
+
+    #[tokio::test]
+    async fn test_flight_rejects_ddl_and_dml() -> Result<()> {
+        let server = FlightSqlServer::new(SessionContext::new());
+
+        let (query, request) = statement("SELECT 1 AS one");
+        assert!(server.get_flight_info_statement(query, request).await.is_ok());
+
+        for sql in [
+            "CREATE TABLE t AS VALUES (1)",
+            "COPY (VALUES (1)) TO '/tmp/flight_copy.csv'",
+            "SET datafusion.execution.batch_size = 1",
+        ] {
+            let (query, request) = statement(sql);
+            let status = server.get_flight_info_statement(query, request).await.unwrap_err();
+            assert_eq!(status.code(), tonic::Code::InvalidArgument, "{}", sql);
+        }
+
+        // A forged ticket goes through the same checks
+        let ticket = TicketStatementQuery {
+            statement_handle: "CREATE TABLE t AS VALUES (1)".as_bytes().to_vec().into(),
+        };
+        let status = server.do_get_statement(ticket, Request::new(Ticket::new(Vec::new()))).await.err();
+        assert_eq!(status.map(|s| s.code()), Some(tonic::Code::InvalidArgument));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_flight_bearer_token() -> Result<()> {
+        let server = FlightSqlServer::new(SessionContext::new()).with_bearer_token("secret");
+
+        let (query, request) = statement("SELECT 1 AS one");
+        let status = server.get_flight_info_statement(query, request).await.unwrap_err();
+        assert_eq!(status.code(), tonic::Code::Unauthenticated);
+
+        let (query, mut request) = statement("SELECT 1 AS one");
+        request.metadata_mut().insert("authorization", "Bearer wrong".parse().unwrap());
+        let status = server.get_flight_info_statement(query, request).await.unwrap_err();
+        assert_eq!(status.code(), tonic::Code::Unauthenticated);
+
+        let (query, mut request) = statement("SELECT 1 AS one");
+        request.metadata_mut().insert("authorization", "Bearer secret".parse().unwrap());
+        assert!(server.get_flight_info_statement(query, request).await.is_ok());
+
+        Ok(())
+    }
+}
@@ -0,0 +1,294 @@
+//! Currency pairs and cross rates
+//!
+//! Forex flat files key rows by Polygon tickers such as `C:EURUSD`: the
+//! base currency followed by the quote currency, priced in units of the
+//! quote currency per unit of the base. [`parse_forex_pair`] splits one
+//! ticker, [`with_pair_columns`] adds the two currencies as columns,
+//! [`invert_pairs`] turns `EURUSD` rows into `USDEUR` rows, and
+//! [`cross_rate`] synthesizes a pair such as `EURJPY` from two legs through
+//! a common currency.
+
+use std::any::Any;
+use std::sync::Arc;
+
+use datafusion::arrow::array::{ArrayRef, AsArray, StringArray};
+use datafusion::arrow::compute::cast;
+use datafusion::arrow::datatypes::DataType;
+use datafusion::common::JoinType;
+use datafusion::dataframe::DataFrame;
+use datafusion::error::{DataFusionError, Result};
+use datafusion::logical_expr::{cast as cast_expr, ColumnarValue, Expr, ScalarUDF, ScalarUDFImpl, Signature, Volatility};
+use datafusion::prelude::{col, lit};
+
+/// Polygon's prefix for forex tickers
+pub const POLYGON_FOREX_PREFIX: &str = "C:";
+
+/// Price columns of forex aggregates, scaled by cross rates
+const BAR_PRICES: [&str; 4] = ["open", "high", "low", "close"];
+
+/// A currency pair, quoted as units of `quote` per unit of `base`
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct CurrencyPair {
+    pub base: String,
+    pub quote: String,
+}
+
+impl CurrencyPair {
+    pub fn new(base: &str, quote: &str) -> Self {
+        Self {
+            base: base.to_ascii_uppercase(),
+            quote: quote.to_ascii_uppercase(),
+        }
+    }
+
+    /// Polygon ticker of the pair, e.g. `C:EURUSD`
+    pub fn ticker(&self) -> String {
+        format!("{}{}{}", POLYGON_FOREX_PREFIX, self.base, self.quote)
+    }
+
+    /// The pair quoted the other way round, e.g. `USDEUR` for `EURUSD`
+    pub fn inverse(&self) -> Self {
+        Self {
+            base: self.quote.clone(),
+            quote: self.base.clone(),
+        }
+    }
+}
+
+impl std::fmt::Display for CurrencyPair {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}/{}", self.base, self.quote)
+    }
+}
+
+/// Parse a currency pair: a Polygon ticker (`C:EURUSD`), a bare pair
+/// (`EURUSD`) or a separated pair as used by the WebSocket feed (`EUR/USD`,
+/// `EUR-USD`)
+///
+/// Currencies are three-letter ISO codes. Returns `None` for anything else.
+pub fn parse_forex_pair(symbol: &str) -> Option<CurrencyPair> {
+    let symbol = symbol.strip_prefix(POLYGON_FOREX_PREFIX).unwrap_or(symbol);
+    let (base, quote) = match symbol.len() {
+        6 => symbol.split_at(3),
+        7 if matches!(symbol.as_bytes()[3], b'/' | b'-') => (&symbol[..3], &symbol[4..]),
+        _ => return None,
+    };
+    let is_code = |code: &str| code.bytes().all(|b| b.is_ascii_alphabetic());
+    if !is_code(base) || !is_code(quote) {
+        return None;
+    }
+    Some(CurrencyPair::new(base, quote))
+}
+
+/// Which part of a pair a scalar function returns
+#[derive(Debug, Clone, Copy)]
+enum PairField {
+    Base,
+    Quote,
+    Inverse,
+}
+
+/// Base currency, quote currency or inverted ticker of a forex ticker; null
+/// for tickers that don't parse
+#[derive(Debug)]
+struct ForexPairFunction {
+    name: String,
+    signature: Signature,
+    field: PairField,
+}
+
+impl ForexPairFunction {
+    fn new(name: &str, field: PairField) -> Self {
+        Self {
+            name: name.to_string(),
+            signature: Signature::uniform(1, vec![DataType::Utf8], Volatility::Immutable),
+            field,
+        }
+    }
+}
+
+impl ScalarUDFImpl for ForexPairFunction {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn signature(&self) -> &Signature {
+        &self.signature
+    }
+
+    fn return_type(&self, _arg_types: &[DataType]) -> Result<DataType> {
+        Ok(DataType::Utf8)
+    }
+
+    fn invoke(&self, args: &[ColumnarValue]) -> Result<ColumnarValue> {
+        let values = ColumnarValue::values_to_arrays(args)?;
+        let tickers = cast(&values[0], &DataType::Utf8)?;
+        let result: ArrayRef = Arc::new(
+            tickers
+                .as_string::<i32>()
+                .iter()
+                .map(|ticker| {
+                    let pair = parse_forex_pair(ticker?)?;
+                    Some(match self.field {
+                        PairField::Base => pair.base,
+                        PairField::Quote => pair.quote,
+                        PairField::Inverse => pair.inverse().ticker(),
+                    })
+                })
+                .collect::<StringArray>(),
+        );
+        Ok(ColumnarValue::Array(result))
+    }
+}
+
+/// Add `base_currency` and `quote_currency` columns parsed from the forex
+/// tickers in `ticker`
+pub fn with_pair_columns(df: DataFrame, ticker: &str) -> Result<DataFrame> {
+    let udf = |name: &str, field: PairField| ScalarUDF::from(ForexPairFunction::new(name, field));
+    df.with_column("base_currency", udf("forex_base_currency", PairField::Base).call(vec![col(ticker)]))?
+        .with_column("quote_currency", udf("forex_quote_currency", PairField::Quote).call(vec![col(ticker)]))
+}
+
+/// Quote every row of `df` the other way round, e.g. `C:EURUSD` rows as
+/// `C:USDEUR`
+///
+/// Works on Polygon forex aggregates and quotes: `ticker` is inverted,
+/// `open` and `close` become their reciprocals, `high` and `low` the
+/// reciprocals of each other, and the bid becomes the reciprocal of the ask
+/// (`bid_price` and `bid_exchange` from `ask_price` and `ask_exchange`, and
+/// the reverse). Prices become Float64. Volume is left as is, in the
+/// original base currency.
+pub fn invert_pairs(df: DataFrame) -> Result<DataFrame> {
+    let reciprocal = |column: &str| lit(1.0) / cast_expr(col(column), DataType::Float64);
+    let inverse = ScalarUDF::from(ForexPairFunction::new("forex_inverse_ticker", PairField::Inverse));
+
+    let schema = df.schema().clone();
+    let has = |name: &str| schema.has_column_with_unqualified_name(name);
+    let output = schema
+        .fields()
+        .iter()
+        .map(|field| {
+            let name = field.name().as_str();
+            let swapped = |other: &str| if has(other) { Some(other) } else { None };
+            let value = match name {
+                "ticker" => inverse.call(vec![col(name)]),
+                "open" | "close" => reciprocal(name),
+                "high" => swapped("low").map_or_else(|| reciprocal(name), reciprocal),
+                "low" => swapped("high").map_or_else(|| reciprocal(name), reciprocal),
+                "bid_price" => swapped("ask_price").map_or_else(|| reciprocal(name), reciprocal),
+                "ask_price" => swapped("bid_price").map_or_else(|| reciprocal(name), reciprocal),
+                "bid_exchange" => col(swapped("ask_exchange").unwrap_or(name)),
+                "ask_exchange" => col(swapped("bid_exchange").unwrap_or(name)),
+                _ => return col(name),
+            };
+            value.alias(name)
+        })
+        .collect::<Vec<_>>();
+    df.select(output)
+}
+
+/// Synthesize `target` from forex aggregates of two pairs through `via`,
+/// e.g. `EURJPY` from `EURUSD` and `USDJPY` through `USD`
+///
+/// `df` holds Polygon forex aggregates of any number of pairs; each leg is
+/// taken from whichever way round it is quoted, inverting as needed. Bars
+/// of the two legs are matched on `window_start`, so a cross only has rows
+/// where both legs traded. `open` and `close` are exact products of the
+/// legs; `high` and `low` are the products of the legs' highs and lows, the
+/// widest range the cross could have had. Output columns are `ticker`,
+/// `window_start`, `open`, `high`, `low` and `close`.
+pub async fn cross_rate(df: DataFrame, target: &CurrencyPair, via: &str) -> Result<DataFrame> {
+    let via = via.to_ascii_uppercase();
+    let first = leg(df.clone(), &CurrencyPair::new(&target.base, &via), "__a").await?;
+    let second = leg(df, &CurrencyPair::new(&via, &target.quote), "__b").await?;
+
+    let mut output = vec![lit(target.ticker()).alias("ticker"), col("__a_window_start").alias("window_start")];
+    for price in BAR_PRICES {
+        output.push((col(format!("__a_{}", price)) * col(format!("__b_{}", price))).alias(price));
+    }
+    first
+        .join(second, JoinType::Inner, &["__a_window_start"], &["__b_window_start"], None)?
+        .select(output)?
+        .sort(vec![col("window_start").sort(true, false)])
+}
+
+/// Rows of `pair` from `df`, inverted if only the inverse pair is present,
+/// with `window_start` and the bar prices renamed to `{prefix}_{column}`
+async fn leg(df: DataFrame, pair: &CurrencyPair, prefix: &str) -> Result<DataFrame> {
+    let (direct, inverse) = (pair.ticker(), pair.inverse().ticker());
+    let ticker = || cast_expr(col("ticker"), DataType::Utf8);
+
+    let present = df
+        .clone()
+        .filter(ticker().in_list(vec![lit(direct.as_str()), lit(inverse.as_str())], false))?
+        .aggregate(vec![ticker().alias("ticker")], vec![])?
+        .collect()
+        .await?;
+    let present = present
+        .iter()
+        .flat_map(|batch| batch.column(0).as_string::<i32>().iter().flatten().map(str::to_string).collect::<Vec<_>>())
+        .collect::<Vec<_>>();
+
+    let rows = if present.contains(&direct) {
+        df.filter(ticker().eq(lit(direct)))?
+    } else if present.contains(&inverse) {
+        invert_pairs(df.filter(ticker().eq(lit(inverse)))?)?
+    } else {
+        return Err(DataFusionError::Plan(format!(
+            "No {} or {} rows to build a cross rate from",
+            direct, inverse
+        )));
+    };
+
+    let mut columns: Vec<Expr> = vec![col("window_start").alias(format!("{}_window_start", prefix))];
+    for price in BAR_PRICES {
+        columns.push(cast_expr(col(price), DataType::Float64).alias(format!("{}_{}", prefix, price)));
+    }
+    rows.select(columns)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use datafusion::arrow::datatypes::Float64Type;
+    use datafusion::execution::context::SessionContext;
+
+    #[tokio::test]
+    async fn test_forex_pairs() -> Result<()> {
+        let pair = parse_forex_pair("C:EURUSD").unwrap();
+        assert_eq!(pair, CurrencyPair::new("EUR", "USD"));
+        assert_eq!(pair.inverse().ticker(), "C:USDEUR");
+        assert_eq!(parse_forex_pair("GBP/JPY").unwrap().to_string(), "GBP/JPY");
+        assert!(parse_forex_pair("X:BTCUSD").is_none());
+
+        let ctx = SessionContext::new();
+        // EURUSD and JPYUSD minute bars; EURJPY goes through USD, inverting JPYUSD
+        let df = ctx
+            .sql("SELECT * FROM (VALUES
+                ('C:EURUSD', 1, 1.0850, 1.0860, 1.0845, 1.0855, 120.0),
+                ('C:EURUSD', 2, 1.0855, 1.0870, 1.0850, 1.0865, 95.0),
+                ('C:JPYUSD', 1, 0.0068, 0.0069, 0.0067, 0.0068, 300.0),
+                ('C:JPYUSD', 2, 0.0068, 0.0068, 0.0066, 0.0067, 280.0),
+                ('C:GBPUSD', 1, 1.2700, 1.2710, 1.2690, 1.2705, 80.0)
+            ) AS t(ticker, window_start, open, high, low, close, volume)")
+            .await?;
+
+        let result = with_pair_columns(invert_pairs(df.clone())?, "ticker")?.collect().await?;
+
+        println!("Forex Pair Test Results:");
+        datafusion::arrow::util::pretty::print_batches(&result)?;
+
+        let result = cross_rate(df, &CurrencyPair::new("EUR", "JPY"), "USD").await?.collect().await?;
+        datafusion::arrow::util::pretty::print_batches(&result)?;
+
+        assert_eq!(result.iter().map(|b| b.num_rows()).sum::<usize>(), 2);
+        let closes = result[0].column_by_name("close").unwrap().as_primitive::<Float64Type>();
+        assert!((closes.value(0) - 1.0855 / 0.0068).abs() < 1e-9);
+
+        Ok(())
+    }
+}
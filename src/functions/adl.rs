@@ -0,0 +1,147 @@
+use std::any::Any;
+use std::sync::Arc;
+
+use datafusion::arrow::array::{ArrayRef, Float64Array};
+use datafusion::arrow::datatypes::DataType;
+use datafusion::error::Result;
+use datafusion::execution::context::SessionContext;
+use datafusion::logical_expr::{Signature, TypeSignature, Volatility, WindowUDF, WindowUDFImpl, PartitionEvaluator};
+
+use super::chaikin::money_flow_volume;
+use super::ohlcv::{HLCV, OhlcvArgs};
+use super::utils::{check_arg_count, float64_arg, value_at};
+
+/// Accumulation/Distribution Line: cumulative money flow volume
+#[derive(Debug)]
+pub struct AccumulationDistributionLine {
+    name: String,
+    signature: Signature,
+}
+
+impl AccumulationDistributionLine {
+    pub fn new() -> Self {
+        Self {
+            name: "adl".to_string(),
+            signature: Signature::one_of(
+                vec![TypeSignature::Exact(vec![
+                    DataType::Float64,
+                    DataType::Float64,
+                    DataType::Float64,
+                    DataType::Float64,
+                ])],
+                Volatility::Immutable,
+            ),
+        }
+    }
+}
+
+impl WindowUDFImpl for AccumulationDistributionLine {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn signature(&self) -> &Signature {
+        &self.signature
+    }
+
+    fn return_type(&self, _arg_types: &[DataType]) -> Result<DataType> {
+        Ok(DataType::Float64)
+    }
+
+    fn partition_evaluator(&self) -> Result<Box<dyn PartitionEvaluator>> {
+        Ok(Box::new(AdlPartitionEvaluator::new()))
+    }
+}
+
+#[derive(Debug)]
+struct AdlPartitionEvaluator {
+    current_adl: f64,
+}
+
+impl AdlPartitionEvaluator {
+    fn new() -> Self {
+        Self { current_adl: 0.0 }
+    }
+}
+
+impl PartitionEvaluator for AdlPartitionEvaluator {
+    fn evaluate_all(
+        &mut self,
+        values: &[ArrayRef],
+        num_rows: usize,
+    ) -> Result<ArrayRef> {
+        check_arg_count(values, 4, "ADL function (high, low, close, volume)")?;
+
+        let high_array = float64_arg(values, 0, "High")?;
+        let low_array = float64_arg(values, 1, "Low")?;
+        let close_array = float64_arg(values, 2, "Close")?;
+        let volume_array = float64_arg(values, 3, "Volume")?;
+
+        let mut result = Vec::with_capacity(num_rows);
+        self.current_adl = 0.0;
+
+        for i in 0..num_rows {
+            let (Some(high), Some(low), Some(close), Some(volume)) = (
+                value_at(high_array, i),
+                value_at(low_array, i),
+                value_at(close_array, i),
+                value_at(volume_array, i),
+            ) else {
+                result.push(None);
+                continue;
+            };
+
+            // ADL = previous ADL + money flow volume
+            self.current_adl += money_flow_volume(high, low, close, volume);
+            result.push(Some(self.current_adl));
+        }
+
+        Ok(Arc::new(Float64Array::from(result)))
+    }
+
+    fn uses_window_frame(&self) -> bool {
+        false
+    }
+
+    fn include_rank(&self) -> bool {
+        false
+    }
+}
+
+pub fn register_adl(ctx: &SessionContext) -> Result<()> {
+    let adl_udf = WindowUDF::from(OhlcvArgs::new(AccumulationDistributionLine::new(), HLCV));
+    ctx.register_udwf(adl_udf);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use datafusion::execution::context::SessionContext;
+
+    #[tokio::test]
+    async fn test_adl() -> Result<()> {
+        let ctx = SessionContext::new();
+        register_adl(&ctx)?;
+
+        // Test ADL using SQL
+        let result = ctx
+            .sql("SELECT close, adl(high, low, close, volume) OVER () AS adl FROM (VALUES
+                (62.34, 61.37, 62.15, 7849.0), (62.05, 60.69, 60.81, 11692.0),
+                (62.27, 60.10, 60.45, 10575.0), (60.79, 58.61, 59.18, 13059.0),
+                (59.93, 58.71, 59.24, 20734.0), (61.75, 59.86, 60.20, 29630.0)
+            ) AS t(high, low, close, volume)")
+            .await?
+            .collect()
+            .await?;
+
+        println!("ADL Test Results:");
+        datafusion::arrow::util::pretty::print_batches(&result)?;
+
+        Ok(())
+    }
+}
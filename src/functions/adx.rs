@@ -0,0 +1,278 @@
+use std::any::Any;
+
+use datafusion::arrow::array::ArrayRef;
+use datafusion::arrow::datatypes::DataType;
+use datafusion::error::Result;
+use datafusion::execution::context::SessionContext;
+use datafusion::logical_expr::function::WindowFunctionSimplification;
+use datafusion::logical_expr::{Signature, TypeSignature, Volatility, WindowUDF, WindowUDFImpl, PartitionEvaluator};
+
+use super::atr::true_range;
+use super::ohlcv::{HLC, OhlcvArgs};
+use super::utils::{check_arg_count, float64_arg, float64_struct_array, float64_struct_type, period_arg, value_at};
+use crate::validation::{period_simplification, PeriodArg};
+
+const ADX_FIELDS: [&str; 3] = ["adx", "plus_di", "minus_di"];
+
+/// Average Directional Index with the +DI / -DI directional indicators
+#[derive(Debug)]
+pub struct AverageDirectionalIndex {
+    name: String,
+    signature: Signature,
+}
+
+impl AverageDirectionalIndex {
+    pub fn new() -> Self {
+        Self {
+            name: "adx".to_string(),
+            signature: Signature::one_of(
+                vec![TypeSignature::Exact(vec![
+                    DataType::Float64,
+                    DataType::Float64,
+                    DataType::Float64,
+                    DataType::Int64,
+                ])],
+                Volatility::Immutable,
+            ),
+        }
+    }
+}
+
+impl WindowUDFImpl for AverageDirectionalIndex {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn signature(&self) -> &Signature {
+        &self.signature
+    }
+
+    fn return_type(&self, _arg_types: &[DataType]) -> Result<DataType> {
+        Ok(float64_struct_type(&ADX_FIELDS))
+    }
+
+    fn partition_evaluator(&self) -> Result<Box<dyn PartitionEvaluator>> {
+        Ok(Box::new(AdxPartitionEvaluator::new()))
+    }
+
+    fn simplify(&self) -> Option<WindowFunctionSimplification> {
+        period_simplification(&self.name, vec![PeriodArg::rows(3, "period")])
+    }
+}
+
+#[derive(Debug)]
+struct AdxPartitionEvaluator {
+    prev_bar: Option<(f64, f64, f64)>,
+    bars_seen: usize,
+    smoothed_tr: f64,
+    smoothed_plus_dm: f64,
+    smoothed_minus_dm: f64,
+    dx_values: Vec<f64>,
+    current_adx: Option<f64>,
+}
+
+impl AdxPartitionEvaluator {
+    fn new() -> Self {
+        Self {
+            prev_bar: None,
+            bars_seen: 0,
+            smoothed_tr: 0.0,
+            smoothed_plus_dm: 0.0,
+            smoothed_minus_dm: 0.0,
+            dx_values: Vec::new(),
+            current_adx: None,
+        }
+    }
+
+    fn reset(&mut self) {
+        *self = Self::new();
+    }
+}
+
+impl PartitionEvaluator for AdxPartitionEvaluator {
+    fn evaluate_all(
+        &mut self,
+        values: &[ArrayRef],
+        num_rows: usize,
+    ) -> Result<ArrayRef> {
+        check_arg_count(values, 4, "ADX function (high, low, close, period)")?;
+
+        let high_array = float64_arg(values, 0, "High")?;
+        let low_array = float64_arg(values, 1, "Low")?;
+        let close_array = float64_arg(values, 2, "Close")?;
+        let period = period_arg(values, 3, "Period")?;
+        let n = period as f64;
+
+        let mut adx_result = Vec::with_capacity(num_rows);
+        let mut plus_di_result = Vec::with_capacity(num_rows);
+        let mut minus_di_result = Vec::with_capacity(num_rows);
+        self.reset();
+
+        for i in 0..num_rows {
+            let (Some(high), Some(low), Some(close)) = (
+                value_at(high_array, i),
+                value_at(low_array, i),
+                value_at(close_array, i),
+            ) else {
+                adx_result.push(None);
+                plus_di_result.push(None);
+                minus_di_result.push(None);
+                continue;
+            };
+
+            let Some((prev_high, prev_low, prev_close)) = self.prev_bar.replace((high, low, close)) else {
+                // Directional movement needs a previous bar
+                adx_result.push(None);
+                plus_di_result.push(None);
+                minus_di_result.push(None);
+                continue;
+            };
+
+            let up_move = high - prev_high;
+            let down_move = prev_low - low;
+            let plus_dm = if up_move > down_move && up_move > 0.0 { up_move } else { 0.0 };
+            let minus_dm = if down_move > up_move && down_move > 0.0 { down_move } else { 0.0 };
+            let tr = true_range(high, low, Some(prev_close));
+
+            self.bars_seen += 1;
+            if self.bars_seen <= period {
+                // Seed the Wilder sums with the first N raw values
+                self.smoothed_tr += tr;
+                self.smoothed_plus_dm += plus_dm;
+                self.smoothed_minus_dm += minus_dm;
+            } else {
+                // Wilder's smoothing: S = S - S / N + current
+                self.smoothed_tr = self.smoothed_tr - self.smoothed_tr / n + tr;
+                self.smoothed_plus_dm = self.smoothed_plus_dm - self.smoothed_plus_dm / n + plus_dm;
+                self.smoothed_minus_dm = self.smoothed_minus_dm - self.smoothed_minus_dm / n + minus_dm;
+            }
+
+            if self.bars_seen < period {
+                adx_result.push(None);
+                plus_di_result.push(None);
+                minus_di_result.push(None);
+                continue;
+            }
+
+            let (plus_di, minus_di) = if self.smoothed_tr > 0.0 {
+                (
+                    100.0 * self.smoothed_plus_dm / self.smoothed_tr,
+                    100.0 * self.smoothed_minus_dm / self.smoothed_tr,
+                )
+            } else {
+                (0.0, 0.0)
+            };
+            plus_di_result.push(Some(plus_di));
+            minus_di_result.push(Some(minus_di));
+
+            let di_sum = plus_di + minus_di;
+            let dx = if di_sum > 0.0 {
+                100.0 * (plus_di - minus_di).abs() / di_sum
+            } else {
+                0.0
+            };
+
+            self.current_adx = match self.current_adx {
+                Some(prev_adx) => Some((prev_adx * (n - 1.0) + dx) / n),
+                None => {
+                    self.dx_values.push(dx);
+                    if self.dx_values.len() == period {
+                        Some(self.dx_values.iter().sum::<f64>() / n)
+                    } else {
+                        None
+                    }
+                }
+            };
+            adx_result.push(self.current_adx);
+        }
+
+        Ok(float64_struct_array(&ADX_FIELDS, vec![adx_result, plus_di_result, minus_di_result]))
+    }
+
+    fn uses_window_frame(&self) -> bool {
+        false
+    }
+
+    fn include_rank(&self) -> bool {
+        false
+    }
+}
+
+pub fn register_adx(ctx: &SessionContext) -> Result<()> {
+    let adx_udf = WindowUDF::from(OhlcvArgs::new(AverageDirectionalIndex::new(), HLC));
+    ctx.register_udwf(adx_udf);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::functions::utils::{assert_close, float64_column};
+    use datafusion::execution::context::SessionContext;
+
+    #[tokio::test]
+    async fn test_adx() -> Result<()> {
+        let ctx = SessionContext::new();
+        register_adx(&ctx)?;
+
+        // Test ADX with a 3 period window using SQL
+        let result = ctx
+            .sql("SELECT close, adx(high, low, close, 3) OVER () AS adx_3 FROM (VALUES
+                (30.20, 29.41, 29.87), (30.28, 29.32, 30.24), (30.45, 29.96, 30.10),
+                (29.35, 28.74, 28.90), (29.35, 28.56, 28.92), (29.29, 28.41, 28.48),
+                (28.83, 28.08, 28.56), (28.73, 27.43, 27.56), (28.67, 27.66, 28.47),
+                (28.85, 27.83, 28.28), (28.64, 27.40, 27.49), (27.68, 27.09, 27.23)
+            ) AS t(high, low, close)")
+            .await?
+            .collect()
+            .await?;
+
+        println!("ADX Test Results:");
+        datafusion::arrow::util::pretty::print_batches(&result)?;
+
+        // Reference values from Wilder's definitions: the DIs start once three
+        // bars of directional movement are summed, the ADX once three DX values are
+        let result = ctx
+            .sql("SELECT adx_3['adx'] AS adx, adx_3['plus_di'] AS plus_di, adx_3['minus_di'] AS minus_di
+                FROM (SELECT adx(high, low, close, 3) OVER () AS adx_3 FROM (VALUES
+                    (30.20, 29.41, 29.87), (30.28, 29.32, 30.24), (30.45, 29.96, 30.10),
+                    (29.35, 28.74, 28.90), (29.35, 28.56, 28.92), (29.29, 28.41, 28.48),
+                    (28.83, 28.08, 28.56), (28.73, 27.43, 27.56), (28.67, 27.66, 28.47),
+                    (28.85, 27.83, 28.28), (28.64, 27.40, 27.49), (27.68, 27.09, 27.23)
+                ) AS t(high, low, close))")
+            .await?
+            .collect()
+            .await?;
+
+        let warm_up = |n: usize, values: &[f64]| {
+            std::iter::repeat(None).take(n).chain(values.iter().copied().map(Some)).collect::<Vec<_>>()
+        };
+        assert_close(
+            &float64_column(&result, "plus_di"),
+            &warm_up(3, &[
+                6.049822064057, 4.255319148936, 2.845188284519, 1.998530492285, 1.126760563380,
+                0.722894768315, 6.319375266828, 3.942206859052, 3.107826375079,
+            ]),
+        );
+        assert_close(
+            &float64_column(&result, "minus_di"),
+            &warm_up(3, &[
+                46.619217081851, 39.549436795995, 32.092050209205, 35.635562086701, 41.901408450704,
+                26.882649196710, 17.993098050377, 24.269262993192, 30.253355648737,
+            ]),
+        );
+        assert_close(
+            &float64_column(&result, "adx"),
+            &warm_up(5, &[
+                80.437010149585, 83.417723023061, 87.199376723503, 89.720479190464, 75.818774432567,
+                74.563334047494, 76.831758546988,
+            ]),
+        );
+
+        Ok(())
+    }
+}
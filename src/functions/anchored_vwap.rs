@@ -0,0 +1,192 @@
+use std::any::Any;
+use std::sync::Arc;
+
+use datafusion::arrow::array::{Array, ArrayRef, Float64Array};
+use datafusion::arrow::datatypes::DataType;
+use datafusion::error::Result;
+use datafusion::execution::context::SessionContext;
+use datafusion::logical_expr::{Signature, TypeSignature, Volatility, WindowUDF, WindowUDFImpl, PartitionEvaluator};
+
+use super::utils::{check_arg_count, float64_arg, timestamp_arg_types, timestamp_nanos_arg, value_at};
+
+/// VWAP accumulated from an anchor timestamp (earnings date, session open, ...)
+///
+/// Rows before the anchor are null. The anchor may be a literal or a column;
+/// accumulation restarts whenever its value changes.
+#[derive(Debug)]
+pub struct AnchoredVwap {
+    name: String,
+    signature: Signature,
+}
+
+impl AnchoredVwap {
+    pub fn new() -> Self {
+        let mut signatures = Vec::new();
+        for ts_type in timestamp_arg_types() {
+            for anchor_type in timestamp_arg_types() {
+                signatures.push(TypeSignature::Exact(vec![
+                    DataType::Float64,
+                    DataType::Float64,
+                    ts_type.clone(),
+                    anchor_type,
+                ]));
+            }
+        }
+
+        Self {
+            name: "anchored_vwap".to_string(),
+            signature: Signature::one_of(signatures, Volatility::Immutable),
+        }
+    }
+}
+
+impl WindowUDFImpl for AnchoredVwap {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn signature(&self) -> &Signature {
+        &self.signature
+    }
+
+    fn return_type(&self, _arg_types: &[DataType]) -> Result<DataType> {
+        Ok(DataType::Float64)
+    }
+
+    fn partition_evaluator(&self) -> Result<Box<dyn PartitionEvaluator>> {
+        Ok(Box::new(AnchoredVwapPartitionEvaluator::new()))
+    }
+}
+
+#[derive(Debug)]
+struct AnchoredVwapPartitionEvaluator {
+    anchor: Option<i64>,
+    price_volume_sum: f64,
+    volume_sum: f64,
+}
+
+impl AnchoredVwapPartitionEvaluator {
+    fn new() -> Self {
+        Self {
+            anchor: None,
+            price_volume_sum: 0.0,
+            volume_sum: 0.0,
+        }
+    }
+}
+
+impl PartitionEvaluator for AnchoredVwapPartitionEvaluator {
+    fn evaluate_all(
+        &mut self,
+        values: &[ArrayRef],
+        num_rows: usize,
+    ) -> Result<ArrayRef> {
+        check_arg_count(values, 4, "Anchored VWAP function (price, volume, ts, anchor_ts)")?;
+
+        let price_array = float64_arg(values, 0, "Price")?;
+        let volume_array = float64_arg(values, 1, "Volume")?;
+        let ts_array = timestamp_nanos_arg(values, 2, "Timestamp")?;
+        let anchor_array = timestamp_nanos_arg(values, 3, "Anchor timestamp")?;
+
+        let mut result = Vec::with_capacity(num_rows);
+        self.anchor = None;
+        self.price_volume_sum = 0.0;
+        self.volume_sum = 0.0;
+
+        for i in 0..num_rows {
+            if ts_array.is_null(i) || anchor_array.is_null(i) {
+                result.push(None);
+                continue;
+            }
+            let (ts, anchor) = (ts_array.value(i), anchor_array.value(i));
+
+            if self.anchor != Some(anchor) {
+                self.anchor = Some(anchor);
+                self.price_volume_sum = 0.0;
+                self.volume_sum = 0.0;
+            }
+
+            if ts < anchor {
+                result.push(None);
+                continue;
+            }
+
+            if let (Some(price), Some(volume)) = (value_at(price_array, i), value_at(volume_array, i)) {
+                self.price_volume_sum += price * volume;
+                self.volume_sum += volume;
+            }
+
+            // Anchored VWAP = Σ(price × volume) / Σ(volume) for ts >= anchor
+            if self.volume_sum > 0.0 {
+                result.push(Some(self.price_volume_sum / self.volume_sum));
+            } else {
+                result.push(None);
+            }
+        }
+
+        Ok(Arc::new(Float64Array::from(result)))
+    }
+
+    fn uses_window_frame(&self) -> bool {
+        false
+    }
+
+    fn include_rank(&self) -> bool {
+        false
+    }
+}
+
+pub fn register_anchored_vwap(ctx: &SessionContext) -> Result<()> {
+    let anchored_vwap_udf = WindowUDF::from(AnchoredVwap::new());
+    ctx.register_udwf(anchored_vwap_udf);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::functions::utils::{assert_close, float64_column};
+    use datafusion::execution::context::SessionContext;
+
+    #[tokio::test]
+    async fn test_anchored_vwap() -> Result<()> {
+        let ctx = SessionContext::new();
+        register_anchored_vwap(&ctx)?;
+
+        // Test VWAP anchored at the third bar using SQL
+        let result = ctx
+            .sql("SELECT ts, price, volume,
+                    anchored_vwap(price, volume, ts, 3000) OVER (ORDER BY ts) AS avwap
+                FROM (VALUES
+                (1000, 100.0, 1000.0), (2000, 101.0, 1500.0), (3000, 102.0, 800.0), (4000, 101.5, 1200.0),
+                (5000, 103.0, 2000.0), (6000, 102.5, 900.0), (7000, 104.0, 1700.0)
+            ) AS t(ts, price, volume)")
+            .await?
+            .collect()
+            .await?;
+
+        println!("Anchored VWAP Test Results:");
+        datafusion::arrow::util::pretty::print_batches(&result)?;
+
+        // Nothing before the anchor; from it on, Σ(price × volume) / Σ(volume),
+        // e.g. (81600 + 121800) / 2000 = 101.7 on the second anchored bar
+        assert_close(
+            &float64_column(&result, "avwap"),
+            &[
+                None,
+                None,
+                Some(102.0),
+                Some(101.7),
+                Some(102.35),
+                Some(501650.0 / 4900.0),
+                Some(678450.0 / 6600.0),
+            ],
+        );
+
+        Ok(())
+    }
+}
@@ -0,0 +1,202 @@
+use std::any::Any;
+use std::sync::Arc;
+
+use datafusion::arrow::array::{ArrayRef, Float64Array};
+use datafusion::arrow::datatypes::DataType;
+use datafusion::error::Result;
+use datafusion::execution::context::SessionContext;
+use datafusion::logical_expr::function::WindowFunctionSimplification;
+use datafusion::logical_expr::{Signature, TypeSignature, Volatility, WindowUDF, WindowUDFImpl, PartitionEvaluator};
+
+use super::ohlcv::{HLC, OhlcvArgs};
+use super::utils::{check_arg_count, float64_arg, period_arg, value_at};
+use crate::validation::{period_simplification, PeriodArg};
+
+/// Average True Range with Wilder's smoothing
+#[derive(Debug)]
+pub struct AverageTrueRange {
+    name: String,
+    signature: Signature,
+}
+
+impl AverageTrueRange {
+    pub fn new() -> Self {
+        Self {
+            name: "atr".to_string(),
+            signature: Signature::one_of(
+                vec![TypeSignature::Exact(vec![
+                    DataType::Float64,
+                    DataType::Float64,
+                    DataType::Float64,
+                    DataType::Int64,
+                ])],
+                Volatility::Immutable,
+            ),
+        }
+    }
+}
+
+impl WindowUDFImpl for AverageTrueRange {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn signature(&self) -> &Signature {
+        &self.signature
+    }
+
+    fn return_type(&self, _arg_types: &[DataType]) -> Result<DataType> {
+        Ok(DataType::Float64)
+    }
+
+    fn partition_evaluator(&self) -> Result<Box<dyn PartitionEvaluator>> {
+        Ok(Box::new(AtrPartitionEvaluator::new()))
+    }
+
+    fn simplify(&self) -> Option<WindowFunctionSimplification> {
+        period_simplification(&self.name, vec![PeriodArg::rows(3, "period")])
+    }
+}
+
+/// True range of a bar given the previous close, if any
+pub(crate) fn true_range(high: f64, low: f64, prev_close: Option<f64>) -> f64 {
+    match prev_close {
+        Some(prev) => (high - low).max((high - prev).abs()).max((low - prev).abs()),
+        None => high - low,
+    }
+}
+
+#[derive(Debug)]
+struct AtrPartitionEvaluator {
+    prev_close: Option<f64>,
+    true_ranges: Vec<f64>,
+    current_atr: Option<f64>,
+}
+
+impl AtrPartitionEvaluator {
+    fn new() -> Self {
+        Self {
+            prev_close: None,
+            true_ranges: Vec::new(),
+            current_atr: None,
+        }
+    }
+}
+
+impl PartitionEvaluator for AtrPartitionEvaluator {
+    fn evaluate_all(
+        &mut self,
+        values: &[ArrayRef],
+        num_rows: usize,
+    ) -> Result<ArrayRef> {
+        check_arg_count(values, 4, "ATR function (high, low, close, period)")?;
+
+        let high_array = float64_arg(values, 0, "High")?;
+        let low_array = float64_arg(values, 1, "Low")?;
+        let close_array = float64_arg(values, 2, "Close")?;
+        let period = period_arg(values, 3, "Period")?;
+
+        let mut result = Vec::with_capacity(num_rows);
+        self.prev_close = None;
+        self.true_ranges.clear();
+        self.current_atr = None;
+
+        for i in 0..num_rows {
+            let (Some(high), Some(low), Some(close)) = (
+                value_at(high_array, i),
+                value_at(low_array, i),
+                value_at(close_array, i),
+            ) else {
+                result.push(None);
+                continue;
+            };
+
+            let tr = true_range(high, low, self.prev_close);
+            self.prev_close = Some(close);
+
+            self.current_atr = match self.current_atr {
+                Some(prev_atr) => {
+                    // Wilder's smoothing: ATR = (previous ATR × (N - 1) + TR) / N
+                    Some((prev_atr * (period as f64 - 1.0) + tr) / period as f64)
+                }
+                None => {
+                    self.true_ranges.push(tr);
+                    if self.true_ranges.len() == period {
+                        // First ATR is the simple average of the first N true ranges
+                        Some(self.true_ranges.iter().sum::<f64>() / period as f64)
+                    } else {
+                        None
+                    }
+                }
+            };
+
+            result.push(self.current_atr);
+        }
+
+        Ok(Arc::new(Float64Array::from(result)))
+    }
+
+    fn uses_window_frame(&self) -> bool {
+        false
+    }
+
+    fn include_rank(&self) -> bool {
+        false
+    }
+}
+
+pub fn register_atr(ctx: &SessionContext) -> Result<()> {
+    let atr_udf = WindowUDF::from(OhlcvArgs::new(AverageTrueRange::new(), HLC));
+    ctx.register_udwf(atr_udf);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::functions::utils::{assert_close, float64_column};
+    use datafusion::execution::context::SessionContext;
+
+    #[tokio::test]
+    async fn test_atr() -> Result<()> {
+        let ctx = SessionContext::new();
+        register_atr(&ctx)?;
+
+        // Test ATR with a 3 period window using SQL
+        let result = ctx
+            .sql("SELECT high, low, close, atr(high, low, close, 3) OVER () AS atr_3 FROM (VALUES
+                (48.70, 47.79, 48.16), (48.72, 48.14, 48.61), (48.90, 48.39, 48.75),
+                (48.87, 48.37, 48.63), (48.82, 48.24, 48.74), (49.05, 48.64, 49.03),
+                (49.20, 48.94, 49.07), (49.35, 48.86, 49.32), (49.92, 49.50, 49.91)
+            ) AS t(high, low, close)")
+            .await?
+            .collect()
+            .await?;
+
+        println!("ATR Test Results:");
+        datafusion::arrow::util::pretty::print_batches(&result)?;
+
+        // The first ATR averages three true ranges (0.91, 0.58 and 0.51); after
+        // that each is Wilder-smoothed, e.g. (0.666667 × 2 + 0.50) / 3
+        assert_close(
+            &float64_column(&result, "atr_3"),
+            &[
+                None,
+                None,
+                Some(0.666666666667),
+                Some(0.611111111111),
+                Some(0.600740740741),
+                Some(0.537160493827),
+                Some(0.444773662551),
+                Some(0.459849108368),
+                Some(0.506566072245),
+            ],
+        );
+
+        Ok(())
+    }
+}
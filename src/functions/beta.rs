@@ -0,0 +1,188 @@
+use std::any::Any;
+use std::sync::Arc;
+
+use datafusion::arrow::array::{ArrayRef, Float64Array};
+use datafusion::arrow::datatypes::DataType;
+use datafusion::error::Result;
+use datafusion::execution::context::SessionContext;
+use datafusion::logical_expr::function::WindowFunctionSimplification;
+use datafusion::logical_expr::{Signature, TypeSignature, Volatility, WindowUDF, WindowUDFImpl, PartitionEvaluator};
+
+use super::correlation::paired_moments;
+use super::utils::{check_arg_count, float64_arg, period_arg, value_at};
+use crate::validation::{period_simplification, PeriodArg};
+
+/// Which CAPM exposure a rolling function reports
+#[derive(Debug, Clone, Copy)]
+enum CapmStatistic {
+    Beta,
+    Alpha,
+}
+
+/// Rolling CAPM beta / alpha of asset returns against benchmark returns
+#[derive(Debug)]
+pub struct RollingCapm {
+    name: String,
+    signature: Signature,
+    statistic: CapmStatistic,
+}
+
+impl RollingCapm {
+    /// `rolling_beta(asset_returns, benchmark_returns, period)`
+    pub fn beta() -> Self {
+        Self::new("rolling_beta", CapmStatistic::Beta)
+    }
+
+    /// `rolling_alpha(asset_returns, benchmark_returns, period)`
+    pub fn alpha() -> Self {
+        Self::new("rolling_alpha", CapmStatistic::Alpha)
+    }
+
+    fn new(name: &str, statistic: CapmStatistic) -> Self {
+        Self {
+            name: name.to_string(),
+            signature: Signature::one_of(
+                vec![TypeSignature::Exact(vec![DataType::Float64, DataType::Float64, DataType::Int64])],
+                Volatility::Immutable,
+            ),
+            statistic,
+        }
+    }
+}
+
+impl WindowUDFImpl for RollingCapm {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn signature(&self) -> &Signature {
+        &self.signature
+    }
+
+    fn return_type(&self, _arg_types: &[DataType]) -> Result<DataType> {
+        Ok(DataType::Float64)
+    }
+
+    fn partition_evaluator(&self) -> Result<Box<dyn PartitionEvaluator>> {
+        Ok(Box::new(RollingCapmPartitionEvaluator::new(self.statistic)))
+    }
+
+    fn simplify(&self) -> Option<WindowFunctionSimplification> {
+        period_simplification(&self.name, vec![PeriodArg::rows(2, "period")])
+    }
+}
+
+#[derive(Debug)]
+struct RollingCapmPartitionEvaluator {
+    statistic: CapmStatistic,
+    asset: Vec<f64>,
+    benchmark: Vec<f64>,
+}
+
+impl RollingCapmPartitionEvaluator {
+    fn new(statistic: CapmStatistic) -> Self {
+        Self {
+            statistic,
+            asset: Vec::new(),
+            benchmark: Vec::new(),
+        }
+    }
+}
+
+impl PartitionEvaluator for RollingCapmPartitionEvaluator {
+    fn evaluate_all(
+        &mut self,
+        values: &[ArrayRef],
+        num_rows: usize,
+    ) -> Result<ArrayRef> {
+        check_arg_count(values, 3, "Rolling beta/alpha function (asset_returns, benchmark_returns, period)")?;
+
+        let asset_array = float64_arg(values, 0, "Asset returns")?;
+        let benchmark_array = float64_arg(values, 1, "Benchmark returns")?;
+        let period = period_arg(values, 2, "Period")?;
+
+        let mut result = Vec::with_capacity(num_rows);
+        self.asset.clear();
+        self.benchmark.clear();
+
+        for i in 0..num_rows {
+            let (Some(asset), Some(benchmark)) = (value_at(asset_array, i), value_at(benchmark_array, i)) else {
+                result.push(None);
+                continue;
+            };
+
+            self.asset.push(asset);
+            self.benchmark.push(benchmark);
+
+            if self.asset.len() < period {
+                result.push(None);
+                continue;
+            }
+
+            let start_idx = self.asset.len() - period;
+            let moments = paired_moments(&self.asset[start_idx..], &self.benchmark[start_idx..]);
+            result.push(moments.and_then(|m| {
+                if m.variance_y <= 0.0 {
+                    return None;
+                }
+                // β = cov(asset, benchmark) / var(benchmark), α = mean(asset) - β × mean(benchmark)
+                let beta = m.covariance / m.variance_y;
+                Some(match self.statistic {
+                    CapmStatistic::Beta => beta,
+                    CapmStatistic::Alpha => m.mean_x - beta * m.mean_y,
+                })
+            }));
+        }
+
+        Ok(Arc::new(Float64Array::from(result)))
+    }
+
+    fn uses_window_frame(&self) -> bool {
+        false
+    }
+
+    fn include_rank(&self) -> bool {
+        false
+    }
+}
+
+/// Register `rolling_beta` and `rolling_alpha`
+pub fn register_beta(ctx: &SessionContext) -> Result<()> {
+    ctx.register_udwf(WindowUDF::from(RollingCapm::beta()));
+    ctx.register_udwf(WindowUDF::from(RollingCapm::alpha()));
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use datafusion::execution::context::SessionContext;
+
+    #[tokio::test]
+    async fn test_beta() -> Result<()> {
+        let ctx = SessionContext::new();
+        register_beta(&ctx)?;
+
+        // Test rolling beta and alpha with a 4 period window using SQL
+        let result = ctx
+            .sql("SELECT asset, bench,
+                    rolling_beta(asset, bench, 4) OVER () AS beta_4,
+                    rolling_alpha(asset, bench, 4) OVER () AS alpha_4
+                FROM (VALUES
+                (0.010, 0.008), (-0.004, -0.002), (0.015, 0.010), (-0.012, -0.009),
+                (0.007, 0.004), (0.003, 0.001), (-0.006, -0.005)
+            ) AS t(asset, bench)")
+            .await?
+            .collect()
+            .await?;
+
+        println!("Rolling Beta Test Results:");
+        datafusion::arrow::util::pretty::print_batches(&result)?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,159 @@
+use std::any::Any;
+
+use datafusion::arrow::array::ArrayRef;
+use datafusion::arrow::datatypes::DataType;
+use datafusion::error::Result;
+use datafusion::execution::context::SessionContext;
+use datafusion::logical_expr::function::WindowFunctionSimplification;
+use datafusion::logical_expr::{Signature, TypeSignature, Volatility, WindowUDF, WindowUDFImpl, PartitionEvaluator};
+
+use super::utils::{check_arg_count, f64_param_arg, float64_arg, float64_struct_array, float64_struct_type, period_arg, value_at, RollingMoments};
+use crate::validation::{period_simplification, PeriodArg};
+
+const BAND_FIELDS: [&str; 3] = ["upper", "middle", "lower"];
+
+/// Bollinger Bands: SMA(period) ± std_multiplier × rolling population standard deviation
+#[derive(Debug)]
+pub struct BollingerBands {
+    name: String,
+    signature: Signature,
+}
+
+impl BollingerBands {
+    pub fn new() -> Self {
+        Self {
+            name: "bollinger".to_string(),
+            signature: Signature::one_of(
+                vec![TypeSignature::Exact(vec![DataType::Float64, DataType::Int64, DataType::Float64])],
+                Volatility::Immutable,
+            ),
+        }
+    }
+}
+
+impl WindowUDFImpl for BollingerBands {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn signature(&self) -> &Signature {
+        &self.signature
+    }
+
+    fn return_type(&self, _arg_types: &[DataType]) -> Result<DataType> {
+        Ok(float64_struct_type(&BAND_FIELDS))
+    }
+
+    fn partition_evaluator(&self) -> Result<Box<dyn PartitionEvaluator>> {
+        Ok(Box::new(BollingerPartitionEvaluator::new()))
+    }
+
+    fn simplify(&self) -> Option<WindowFunctionSimplification> {
+        period_simplification(&self.name, vec![PeriodArg::rows(1, "period")])
+    }
+}
+
+#[derive(Debug)]
+struct BollingerPartitionEvaluator {
+    window: RollingMoments,
+}
+
+impl BollingerPartitionEvaluator {
+    fn new() -> Self {
+        Self {
+            window: RollingMoments::default(),
+        }
+    }
+}
+
+impl PartitionEvaluator for BollingerPartitionEvaluator {
+    fn evaluate_all(
+        &mut self,
+        values: &[ArrayRef],
+        num_rows: usize,
+    ) -> Result<ArrayRef> {
+        check_arg_count(values, 3, "bollinger(price, period, std_multiplier)")?;
+
+        let price_array = float64_arg(values, 0, "Price")?;
+        let period = period_arg(values, 1, "Period")?;
+        let multiplier = f64_param_arg(values, 2, "Standard deviation multiplier")?;
+
+        let mut upper = Vec::with_capacity(num_rows);
+        let mut middle = Vec::with_capacity(num_rows);
+        let mut lower = Vec::with_capacity(num_rows);
+        self.window.reset(period);
+
+        for i in 0..num_rows {
+            let Some(price) = value_at(price_array, i) else {
+                upper.push(None);
+                middle.push(None);
+                lower.push(None);
+                continue;
+            };
+
+            self.window.push(price);
+
+            if !self.window.is_full() {
+                upper.push(None);
+                middle.push(None);
+                lower.push(None);
+                continue;
+            }
+
+            let mean = self.window.mean();
+            let band_width = multiplier * self.window.population_variance().sqrt();
+
+            upper.push(Some(mean + band_width));
+            middle.push(Some(mean));
+            lower.push(Some(mean - band_width));
+        }
+
+        Ok(float64_struct_array(&BAND_FIELDS, vec![upper, middle, lower]))
+    }
+
+    fn uses_window_frame(&self) -> bool {
+        false
+    }
+
+    fn include_rank(&self) -> bool {
+        false
+    }
+}
+
+pub fn register_bollinger(ctx: &SessionContext) -> Result<()> {
+    let bollinger_udf = WindowUDF::from(BollingerBands::new());
+    ctx.register_udwf(bollinger_udf);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use datafusion::execution::context::SessionContext;
+
+    #[tokio::test]
+    async fn test_bollinger() -> Result<()> {
+        let ctx = SessionContext::new();
+        register_bollinger(&ctx)?;
+
+        // Test Bollinger Bands with a 5 period window and 2 standard deviations
+        let result = ctx
+            .sql("SELECT price, bollinger(price, 5, 2.0) OVER () AS bands FROM (VALUES
+                (20.0), (21.0), (22.0), (21.5), (23.0), (24.0), (22.5), (25.0), (26.0), (24.5)
+            ) AS t(price)")
+            .await?
+            .collect()
+            .await?;
+
+        println!("Bollinger Bands Test Results:");
+        datafusion::arrow::util::pretty::print_batches(&result)?;
+
+        assert_eq!(result.iter().map(|b| b.num_rows()).sum::<usize>(), 10);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,171 @@
+use std::any::Any;
+use std::sync::Arc;
+
+use datafusion::arrow::array::{ArrayRef, Float64Array};
+use datafusion::arrow::datatypes::DataType;
+use datafusion::error::Result;
+use datafusion::execution::context::SessionContext;
+use datafusion::logical_expr::function::WindowFunctionSimplification;
+use datafusion::logical_expr::{Signature, TypeSignature, Volatility, WindowUDF, WindowUDFImpl, PartitionEvaluator};
+
+use super::ohlcv::{HLC, OhlcvArgs};
+use super::utils::{check_arg_count, float64_arg, period_arg, value_at, RollingSum};
+use crate::validation::{period_simplification, PeriodArg};
+
+/// Lambert's constant so that roughly 70-80% of CCI values fall between -100 and +100
+const CCI_CONSTANT: f64 = 0.015;
+
+/// Commodity Channel Index
+#[derive(Debug)]
+pub struct CommodityChannelIndex {
+    name: String,
+    signature: Signature,
+}
+
+impl CommodityChannelIndex {
+    pub fn new() -> Self {
+        Self {
+            name: "cci".to_string(),
+            signature: Signature::one_of(
+                vec![TypeSignature::Exact(vec![
+                    DataType::Float64,
+                    DataType::Float64,
+                    DataType::Float64,
+                    DataType::Int64,
+                ])],
+                Volatility::Immutable,
+            ),
+        }
+    }
+}
+
+impl WindowUDFImpl for CommodityChannelIndex {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn signature(&self) -> &Signature {
+        &self.signature
+    }
+
+    fn return_type(&self, _arg_types: &[DataType]) -> Result<DataType> {
+        Ok(DataType::Float64)
+    }
+
+    fn partition_evaluator(&self) -> Result<Box<dyn PartitionEvaluator>> {
+        Ok(Box::new(CciPartitionEvaluator::new()))
+    }
+
+    fn simplify(&self) -> Option<WindowFunctionSimplification> {
+        period_simplification(&self.name, vec![PeriodArg::rows(3, "period")])
+    }
+}
+
+#[derive(Debug)]
+struct CciPartitionEvaluator {
+    typical_prices: RollingSum,
+}
+
+impl CciPartitionEvaluator {
+    fn new() -> Self {
+        Self {
+            typical_prices: RollingSum::default(),
+        }
+    }
+}
+
+impl PartitionEvaluator for CciPartitionEvaluator {
+    fn evaluate_all(
+        &mut self,
+        values: &[ArrayRef],
+        num_rows: usize,
+    ) -> Result<ArrayRef> {
+        check_arg_count(values, 4, "CCI function (high, low, close, period)")?;
+
+        let high_array = float64_arg(values, 0, "High")?;
+        let low_array = float64_arg(values, 1, "Low")?;
+        let close_array = float64_arg(values, 2, "Close")?;
+        let period = period_arg(values, 3, "Period")?;
+
+        let mut result = Vec::with_capacity(num_rows);
+        self.typical_prices.reset(period);
+
+        for i in 0..num_rows {
+            let (Some(high), Some(low), Some(close)) = (
+                value_at(high_array, i),
+                value_at(low_array, i),
+                value_at(close_array, i),
+            ) else {
+                result.push(None);
+                continue;
+            };
+
+            let typical_price = (high + low + close) / 3.0;
+            let Some(sum) = self.typical_prices.push(typical_price) else {
+                result.push(None);
+                continue;
+            };
+
+            // The mean is a running sum, but the mean absolute deviation has
+            // no running form and still walks the window
+            let mean = sum / period as f64;
+            let mean_deviation = self.typical_prices.values().map(|tp| (tp - mean).abs()).sum::<f64>() / period as f64;
+
+            // CCI = (TP - SMA(TP)) / (0.015 × mean deviation)
+            if mean_deviation > 0.0 {
+                result.push(Some((typical_price - mean) / (CCI_CONSTANT * mean_deviation)));
+            } else {
+                result.push(Some(0.0));
+            }
+        }
+
+        Ok(Arc::new(Float64Array::from(result)))
+    }
+
+    fn uses_window_frame(&self) -> bool {
+        false
+    }
+
+    fn include_rank(&self) -> bool {
+        false
+    }
+}
+
+pub fn register_cci(ctx: &SessionContext) -> Result<()> {
+    let cci_udf = WindowUDF::from(OhlcvArgs::new(CommodityChannelIndex::new(), HLC));
+    ctx.register_udwf(cci_udf);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use datafusion::execution::context::SessionContext;
+
+    #[tokio::test]
+    async fn test_cci() -> Result<()> {
+        let ctx = SessionContext::new();
+        register_cci(&ctx)?;
+
+        // Test CCI with a 5 period window using SQL
+        let result = ctx
+            .sql("SELECT close, cci(high, low, close, 5) OVER () AS cci_5 FROM (VALUES
+                (24.20, 23.85, 23.89), (24.07, 23.72, 23.95), (24.04, 23.64, 23.67),
+                (23.87, 23.37, 23.78), (23.67, 23.46, 23.50), (23.59, 23.18, 23.32),
+                (23.80, 23.40, 23.75), (23.80, 23.57, 23.79), (24.30, 24.05, 24.14),
+                (24.15, 23.77, 23.81)
+            ) AS t(high, low, close)")
+            .await?
+            .collect()
+            .await?;
+
+        println!("CCI Test Results:");
+        datafusion::arrow::util::pretty::print_batches(&result)?;
+
+        Ok(())
+    }
+}
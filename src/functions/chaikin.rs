@@ -0,0 +1,302 @@
+use std::any::Any;
+use std::sync::Arc;
+
+use datafusion::arrow::array::{ArrayRef, Float64Array};
+use datafusion::arrow::datatypes::DataType;
+use datafusion::error::Result;
+use datafusion::execution::context::SessionContext;
+use datafusion::logical_expr::function::WindowFunctionSimplification;
+use datafusion::logical_expr::{Signature, TypeSignature, Volatility, WindowUDF, WindowUDFImpl, PartitionEvaluator};
+
+use super::ohlcv::{HLCV, OhlcvArgs};
+use super::utils::{check_arg_count, float64_arg, period_arg, value_at, RollingSum};
+use crate::validation::{period_simplification, PeriodArg};
+
+/// Money flow volume of a bar: volume weighted by where the close sits in the high-low range
+pub(crate) fn money_flow_volume(high: f64, low: f64, close: f64, volume: f64) -> f64 {
+    let range = high - low;
+    if range <= 0.0 {
+        return 0.0;
+    }
+    // MFM = ((close - low) - (high - close)) / (high - low)
+    let multiplier = ((close - low) - (high - close)) / range;
+    multiplier * volume
+}
+
+/// Chaikin Money Flow over a rolling period
+#[derive(Debug)]
+pub struct ChaikinMoneyFlow {
+    name: String,
+    signature: Signature,
+}
+
+impl ChaikinMoneyFlow {
+    pub fn new() -> Self {
+        Self {
+            name: "cmf".to_string(),
+            signature: Signature::one_of(
+                vec![TypeSignature::Exact(vec![
+                    DataType::Float64,
+                    DataType::Float64,
+                    DataType::Float64,
+                    DataType::Float64,
+                    DataType::Int64,
+                ])],
+                Volatility::Immutable,
+            ),
+        }
+    }
+}
+
+impl WindowUDFImpl for ChaikinMoneyFlow {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn signature(&self) -> &Signature {
+        &self.signature
+    }
+
+    fn return_type(&self, _arg_types: &[DataType]) -> Result<DataType> {
+        Ok(DataType::Float64)
+    }
+
+    fn partition_evaluator(&self) -> Result<Box<dyn PartitionEvaluator>> {
+        Ok(Box::new(CmfPartitionEvaluator::new()))
+    }
+
+    fn simplify(&self) -> Option<WindowFunctionSimplification> {
+        period_simplification(&self.name, vec![PeriodArg::rows(4, "period")])
+    }
+}
+
+#[derive(Debug)]
+struct CmfPartitionEvaluator {
+    flow_volumes: RollingSum,
+    volumes: RollingSum,
+}
+
+impl CmfPartitionEvaluator {
+    fn new() -> Self {
+        Self {
+            flow_volumes: RollingSum::default(),
+            volumes: RollingSum::default(),
+        }
+    }
+}
+
+impl PartitionEvaluator for CmfPartitionEvaluator {
+    fn evaluate_all(
+        &mut self,
+        values: &[ArrayRef],
+        num_rows: usize,
+    ) -> Result<ArrayRef> {
+        check_arg_count(values, 5, "CMF function (high, low, close, volume, period)")?;
+
+        let high_array = float64_arg(values, 0, "High")?;
+        let low_array = float64_arg(values, 1, "Low")?;
+        let close_array = float64_arg(values, 2, "Close")?;
+        let volume_array = float64_arg(values, 3, "Volume")?;
+        let period = period_arg(values, 4, "Period")?;
+
+        let mut result = Vec::with_capacity(num_rows);
+        self.flow_volumes.reset(period);
+        self.volumes.reset(period);
+
+        for i in 0..num_rows {
+            let (Some(high), Some(low), Some(close), Some(volume)) = (
+                value_at(high_array, i),
+                value_at(low_array, i),
+                value_at(close_array, i),
+                value_at(volume_array, i),
+            ) else {
+                result.push(None);
+                continue;
+            };
+
+            let flow_sum = self.flow_volumes.push(money_flow_volume(high, low, close, volume));
+            let volume_sum = self.volumes.push(volume);
+
+            // CMF = Σ money flow volume / Σ volume over the period
+            match (flow_sum, volume_sum) {
+                (Some(flow_sum), Some(volume_sum)) if volume_sum > 0.0 => result.push(Some(flow_sum / volume_sum)),
+                _ => result.push(None),
+            }
+        }
+
+        Ok(Arc::new(Float64Array::from(result)))
+    }
+
+    fn uses_window_frame(&self) -> bool {
+        false
+    }
+
+    fn include_rank(&self) -> bool {
+        false
+    }
+}
+
+/// Chaikin Oscillator: EMA(3) - EMA(10) of the accumulation/distribution line
+#[derive(Debug)]
+pub struct ChaikinOscillator {
+    name: String,
+    signature: Signature,
+}
+
+impl ChaikinOscillator {
+    pub fn new() -> Self {
+        Self {
+            name: "chaikin_osc".to_string(),
+            signature: Signature::one_of(
+                vec![TypeSignature::Exact(vec![
+                    DataType::Float64,
+                    DataType::Float64,
+                    DataType::Float64,
+                    DataType::Float64,
+                ])],
+                Volatility::Immutable,
+            ),
+        }
+    }
+}
+
+impl WindowUDFImpl for ChaikinOscillator {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn signature(&self) -> &Signature {
+        &self.signature
+    }
+
+    fn return_type(&self, _arg_types: &[DataType]) -> Result<DataType> {
+        Ok(DataType::Float64)
+    }
+
+    fn partition_evaluator(&self) -> Result<Box<dyn PartitionEvaluator>> {
+        Ok(Box::new(ChaikinOscPartitionEvaluator::new()))
+    }
+}
+
+#[derive(Debug)]
+struct ChaikinOscPartitionEvaluator {
+    adl: f64,
+    fast_ema: Option<f64>,
+    slow_ema: Option<f64>,
+    alpha_fast: f64,
+    alpha_slow: f64,
+}
+
+impl ChaikinOscPartitionEvaluator {
+    fn new() -> Self {
+        Self {
+            adl: 0.0,
+            fast_ema: None,
+            slow_ema: None,
+            alpha_fast: 2.0 / 4.0,  // 2 / (3 + 1)
+            alpha_slow: 2.0 / 11.0, // 2 / (10 + 1)
+        }
+    }
+}
+
+impl PartitionEvaluator for ChaikinOscPartitionEvaluator {
+    fn evaluate_all(
+        &mut self,
+        values: &[ArrayRef],
+        num_rows: usize,
+    ) -> Result<ArrayRef> {
+        check_arg_count(values, 4, "Chaikin oscillator function (high, low, close, volume)")?;
+
+        let high_array = float64_arg(values, 0, "High")?;
+        let low_array = float64_arg(values, 1, "Low")?;
+        let close_array = float64_arg(values, 2, "Close")?;
+        let volume_array = float64_arg(values, 3, "Volume")?;
+
+        let mut result = Vec::with_capacity(num_rows);
+        *self = Self::new();
+
+        for i in 0..num_rows {
+            let (Some(high), Some(low), Some(close), Some(volume)) = (
+                value_at(high_array, i),
+                value_at(low_array, i),
+                value_at(close_array, i),
+                value_at(volume_array, i),
+            ) else {
+                result.push(None);
+                continue;
+            };
+
+            self.adl += money_flow_volume(high, low, close, volume);
+
+            let fast = match self.fast_ema {
+                None => self.adl,
+                Some(prev) => self.alpha_fast * self.adl + (1.0 - self.alpha_fast) * prev,
+            };
+            let slow = match self.slow_ema {
+                None => self.adl,
+                Some(prev) => self.alpha_slow * self.adl + (1.0 - self.alpha_slow) * prev,
+            };
+            self.fast_ema = Some(fast);
+            self.slow_ema = Some(slow);
+
+            result.push(Some(fast - slow));
+        }
+
+        Ok(Arc::new(Float64Array::from(result)))
+    }
+
+    fn uses_window_frame(&self) -> bool {
+        false
+    }
+
+    fn include_rank(&self) -> bool {
+        false
+    }
+}
+
+/// Register `cmf` and `chaikin_osc`
+pub fn register_chaikin(ctx: &SessionContext) -> Result<()> {
+    ctx.register_udwf(WindowUDF::from(OhlcvArgs::new(ChaikinMoneyFlow::new(), HLCV)));
+    ctx.register_udwf(WindowUDF::from(OhlcvArgs::new(ChaikinOscillator::new(), HLCV)));
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use datafusion::execution::context::SessionContext;
+
+    #[tokio::test]
+    async fn test_chaikin() -> Result<()> {
+        let ctx = SessionContext::new();
+        register_chaikin(&ctx)?;
+
+        // Test CMF(3) and the Chaikin oscillator using SQL
+        let result = ctx
+            .sql("SELECT close,
+                    cmf(high, low, close, volume, 3) OVER () AS cmf_3,
+                    chaikin_osc(high, low, close, volume) OVER () AS chaikin
+                FROM (VALUES
+                (62.34, 61.37, 62.15, 7849.0), (62.05, 60.69, 60.81, 11692.0),
+                (62.27, 60.10, 60.45, 10575.0), (60.79, 58.61, 59.18, 13059.0),
+                (59.93, 58.71, 59.24, 20734.0), (61.75, 59.86, 60.20, 29630.0),
+                (60.00, 57.97, 58.48, 17705.0), (59.00, 58.02, 58.24, 7259.0)
+            ) AS t(high, low, close, volume)")
+            .await?
+            .collect()
+            .await?;
+
+        println!("Chaikin Test Results:");
+        datafusion::arrow::util::pretty::print_batches(&result)?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,196 @@
+use std::any::Any;
+use std::sync::Arc;
+
+use datafusion::arrow::array::{ArrayRef, Float64Array};
+use datafusion::arrow::datatypes::DataType;
+use datafusion::error::{DataFusionError, Result};
+use datafusion::execution::context::SessionContext;
+use datafusion::logical_expr::function::WindowFunctionSimplification;
+use datafusion::logical_expr::{Signature, TypeSignature, Volatility, WindowUDF, WindowUDFImpl, PartitionEvaluator};
+
+use super::utils::{float64_arg, period_arg, rate_of_change, value_at};
+use crate::validation::{period_simplification, PeriodArg};
+
+const DEFAULT_LONG_ROC: usize = 14;
+const DEFAULT_SHORT_ROC: usize = 11;
+const DEFAULT_WMA_PERIOD: usize = 10;
+
+/// Coppock Curve: weighted moving average of the sum of a long and a short rate of change
+#[derive(Debug)]
+pub struct CoppockCurve {
+    name: String,
+    signature: Signature,
+}
+
+impl CoppockCurve {
+    pub fn new() -> Self {
+        Self {
+            name: "coppock".to_string(),
+            signature: Signature::one_of(
+                vec![
+                    TypeSignature::Exact(vec![DataType::Float64]),
+                    TypeSignature::Exact(vec![
+                        DataType::Float64,
+                        DataType::Int64,
+                        DataType::Int64,
+                        DataType::Int64,
+                    ]),
+                ],
+                Volatility::Immutable,
+            ),
+        }
+    }
+}
+
+impl WindowUDFImpl for CoppockCurve {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn signature(&self) -> &Signature {
+        &self.signature
+    }
+
+    fn return_type(&self, _arg_types: &[DataType]) -> Result<DataType> {
+        Ok(DataType::Float64)
+    }
+
+    fn partition_evaluator(&self) -> Result<Box<dyn PartitionEvaluator>> {
+        Ok(Box::new(CoppockPartitionEvaluator::new()))
+    }
+
+    fn simplify(&self) -> Option<WindowFunctionSimplification> {
+        period_simplification(
+            &self.name,
+            vec![
+                PeriodArg::rows(1, "long_roc"),
+                PeriodArg::rows(2, "short_roc"),
+                PeriodArg::rows(3, "wma_period"),
+            ],
+        )
+    }
+}
+
+/// Linearly weighted moving average of the last `period` values (newest weighted highest)
+pub(crate) fn weighted_moving_average(values: &[f64], period: usize) -> Option<f64> {
+    if values.len() < period {
+        return None;
+    }
+    let window = &values[values.len() - period..];
+    let weighted_sum: f64 = window
+        .iter()
+        .enumerate()
+        .map(|(i, v)| (i + 1) as f64 * v)
+        .sum();
+    let weight_total = (period * (period + 1)) as f64 / 2.0;
+    Some(weighted_sum / weight_total)
+}
+
+#[derive(Debug)]
+struct CoppockPartitionEvaluator {
+    values: Vec<f64>,
+    roc_sums: Vec<f64>,
+}
+
+impl CoppockPartitionEvaluator {
+    fn new() -> Self {
+        Self {
+            values: Vec::new(),
+            roc_sums: Vec::new(),
+        }
+    }
+}
+
+impl PartitionEvaluator for CoppockPartitionEvaluator {
+    fn evaluate_all(
+        &mut self,
+        values: &[ArrayRef],
+        num_rows: usize,
+    ) -> Result<ArrayRef> {
+        if values.len() != 1 && values.len() != 4 {
+            return Err(DataFusionError::Execution(
+                "Coppock function requires 1 or 4 arguments: value and optional long_roc, short_roc, wma_period".to_string(),
+            ));
+        }
+
+        let value_array = float64_arg(values, 0, "First argument")?;
+        let (long_roc, short_roc, wma_period) = if values.len() == 4 {
+            (
+                period_arg(values, 1, "Long ROC period")?,
+                period_arg(values, 2, "Short ROC period")?,
+                period_arg(values, 3, "WMA period")?,
+            )
+        } else {
+            (DEFAULT_LONG_ROC, DEFAULT_SHORT_ROC, DEFAULT_WMA_PERIOD)
+        };
+
+        let mut result = Vec::with_capacity(num_rows);
+        self.values.clear();
+        self.roc_sums.clear();
+
+        for i in 0..num_rows {
+            let Some(value) = value_at(value_array, i) else {
+                result.push(None);
+                continue;
+            };
+
+            self.values.push(value);
+
+            // Coppock = WMA(ROC(long) + ROC(short), wma_period)
+            match (rate_of_change(&self.values, long_roc), rate_of_change(&self.values, short_roc)) {
+                (Some(long), Some(short)) => {
+                    self.roc_sums.push(long + short);
+                    result.push(weighted_moving_average(&self.roc_sums, wma_period));
+                }
+                _ => result.push(None),
+            }
+        }
+
+        Ok(Arc::new(Float64Array::from(result)))
+    }
+
+    fn uses_window_frame(&self) -> bool {
+        false
+    }
+
+    fn include_rank(&self) -> bool {
+        false
+    }
+}
+
+pub fn register_coppock(ctx: &SessionContext) -> Result<()> {
+    let coppock_udf = WindowUDF::from(CoppockCurve::new());
+    ctx.register_udwf(coppock_udf);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use datafusion::execution::context::SessionContext;
+
+    #[tokio::test]
+    async fn test_coppock() -> Result<()> {
+        let ctx = SessionContext::new();
+        register_coppock(&ctx)?;
+
+        // Test Coppock with short 4/3/3 periods using SQL
+        let result = ctx
+            .sql("SELECT price, coppock(price, 4, 3, 3) OVER () AS coppock FROM (VALUES
+                (100.0), (102.0), (98.0), (105.0), (107.0), (103.0), (110.0), (108.0),
+                (112.0), (115.0), (113.0), (118.0)
+            ) AS t(price)")
+            .await?
+            .collect()
+            .await?;
+
+        println!("Coppock Test Results:");
+        datafusion::arrow::util::pretty::print_batches(&result)?;
+
+        Ok(())
+    }
+}
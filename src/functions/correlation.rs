@@ -0,0 +1,224 @@
+use std::any::Any;
+use std::sync::Arc;
+
+use datafusion::arrow::array::{ArrayRef, Float64Array};
+use datafusion::arrow::datatypes::DataType;
+use datafusion::error::Result;
+use datafusion::execution::context::SessionContext;
+use datafusion::logical_expr::function::WindowFunctionSimplification;
+use datafusion::logical_expr::{Signature, TypeSignature, Volatility, WindowUDF, WindowUDFImpl, PartitionEvaluator};
+
+use super::utils::{check_arg_count, float64_arg, period_arg, value_at};
+use crate::validation::{period_simplification, PeriodArg};
+
+/// Sample moments of two paired series
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct PairedMoments {
+    pub mean_x: f64,
+    pub mean_y: f64,
+    pub covariance: f64,
+    pub variance_x: f64,
+    pub variance_y: f64,
+}
+
+/// Sample (N - 1) means, covariance and variances of paired windows
+pub(crate) fn paired_moments(xs: &[f64], ys: &[f64]) -> Option<PairedMoments> {
+    let n = xs.len().min(ys.len());
+    if n < 2 {
+        return None;
+    }
+    let (xs, ys) = (&xs[..n], &ys[..n]);
+    let mean_x = xs.iter().sum::<f64>() / n as f64;
+    let mean_y = ys.iter().sum::<f64>() / n as f64;
+
+    let mut covariance = 0.0;
+    let mut variance_x = 0.0;
+    let mut variance_y = 0.0;
+    for (x, y) in xs.iter().zip(ys) {
+        let dx = x - mean_x;
+        let dy = y - mean_y;
+        covariance += dx * dy;
+        variance_x += dx * dx;
+        variance_y += dy * dy;
+    }
+    let denominator = (n - 1) as f64;
+
+    Some(PairedMoments {
+        mean_x,
+        mean_y,
+        covariance: covariance / denominator,
+        variance_x: variance_x / denominator,
+        variance_y: variance_y / denominator,
+    })
+}
+
+/// Which paired statistic a rolling function reports
+#[derive(Debug, Clone, Copy)]
+enum PairedStatistic {
+    Correlation,
+    Covariance,
+}
+
+/// Rolling Pearson correlation / sample covariance between two columns
+#[derive(Debug)]
+pub struct RollingCorrelation {
+    name: String,
+    signature: Signature,
+    statistic: PairedStatistic,
+}
+
+impl RollingCorrelation {
+    /// `rolling_corr(x, y, period)`
+    pub fn correlation() -> Self {
+        Self::new("rolling_corr", PairedStatistic::Correlation)
+    }
+
+    /// `rolling_cov(x, y, period)`
+    pub fn covariance() -> Self {
+        Self::new("rolling_cov", PairedStatistic::Covariance)
+    }
+
+    fn new(name: &str, statistic: PairedStatistic) -> Self {
+        Self {
+            name: name.to_string(),
+            signature: Signature::one_of(
+                vec![TypeSignature::Exact(vec![DataType::Float64, DataType::Float64, DataType::Int64])],
+                Volatility::Immutable,
+            ),
+            statistic,
+        }
+    }
+}
+
+impl WindowUDFImpl for RollingCorrelation {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn signature(&self) -> &Signature {
+        &self.signature
+    }
+
+    fn return_type(&self, _arg_types: &[DataType]) -> Result<DataType> {
+        Ok(DataType::Float64)
+    }
+
+    fn partition_evaluator(&self) -> Result<Box<dyn PartitionEvaluator>> {
+        Ok(Box::new(RollingCorrelationPartitionEvaluator::new(self.statistic)))
+    }
+
+    fn simplify(&self) -> Option<WindowFunctionSimplification> {
+        period_simplification(&self.name, vec![PeriodArg::rows(2, "period")])
+    }
+}
+
+#[derive(Debug)]
+struct RollingCorrelationPartitionEvaluator {
+    statistic: PairedStatistic,
+    xs: Vec<f64>,
+    ys: Vec<f64>,
+}
+
+impl RollingCorrelationPartitionEvaluator {
+    fn new(statistic: PairedStatistic) -> Self {
+        Self {
+            statistic,
+            xs: Vec::new(),
+            ys: Vec::new(),
+        }
+    }
+}
+
+impl PartitionEvaluator for RollingCorrelationPartitionEvaluator {
+    fn evaluate_all(
+        &mut self,
+        values: &[ArrayRef],
+        num_rows: usize,
+    ) -> Result<ArrayRef> {
+        check_arg_count(values, 3, "Rolling correlation function (x, y, period)")?;
+
+        let x_array = float64_arg(values, 0, "First argument")?;
+        let y_array = float64_arg(values, 1, "Second argument")?;
+        let period = period_arg(values, 2, "Period")?;
+
+        let mut result = Vec::with_capacity(num_rows);
+        self.xs.clear();
+        self.ys.clear();
+
+        for i in 0..num_rows {
+            let (Some(x), Some(y)) = (value_at(x_array, i), value_at(y_array, i)) else {
+                result.push(None);
+                continue;
+            };
+
+            self.xs.push(x);
+            self.ys.push(y);
+
+            if self.xs.len() < period {
+                result.push(None);
+                continue;
+            }
+
+            let start_idx = self.xs.len() - period;
+            let moments = paired_moments(&self.xs[start_idx..], &self.ys[start_idx..]);
+            result.push(moments.and_then(|m| match self.statistic {
+                PairedStatistic::Covariance => Some(m.covariance),
+                // ρ = cov(x, y) / (σx × σy)
+                PairedStatistic::Correlation => {
+                    let denominator = (m.variance_x * m.variance_y).sqrt();
+                    (denominator > 0.0).then(|| m.covariance / denominator)
+                }
+            }));
+        }
+
+        Ok(Arc::new(Float64Array::from(result)))
+    }
+
+    fn uses_window_frame(&self) -> bool {
+        false
+    }
+
+    fn include_rank(&self) -> bool {
+        false
+    }
+}
+
+/// Register `rolling_corr` and `rolling_cov`
+pub fn register_correlation(ctx: &SessionContext) -> Result<()> {
+    ctx.register_udwf(WindowUDF::from(RollingCorrelation::correlation()));
+    ctx.register_udwf(WindowUDF::from(RollingCorrelation::covariance()));
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use datafusion::execution::context::SessionContext;
+
+    #[tokio::test]
+    async fn test_correlation() -> Result<()> {
+        let ctx = SessionContext::new();
+        register_correlation(&ctx)?;
+
+        // Test rolling correlation and covariance with a 4 period window using SQL
+        let result = ctx
+            .sql("SELECT x, y,
+                    rolling_corr(x, y, 4) OVER () AS corr_4,
+                    rolling_cov(x, y, 4) OVER () AS cov_4
+                FROM (VALUES
+                (1.0, 2.1), (2.0, 3.9), (3.0, 6.2), (4.0, 8.1), (5.0, 9.8), (6.0, 11.0), (7.0, 15.2)
+            ) AS t(x, y)")
+            .await?
+            .collect()
+            .await?;
+
+        println!("Rolling Correlation Test Results:");
+        datafusion::arrow::util::pretty::print_batches(&result)?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,171 @@
+use std::any::Any;
+use std::sync::Arc;
+
+use datafusion::arrow::array::{ArrayRef, Float64Array};
+use datafusion::arrow::datatypes::DataType;
+use datafusion::error::Result;
+use datafusion::execution::context::SessionContext;
+use datafusion::logical_expr::{Signature, TypeSignature, Volatility, WindowUDF, WindowUDFImpl, PartitionEvaluator};
+
+use super::utils::{check_arg_count, float64_arg, value_at};
+
+/// What the input column of a cumulative return function holds
+#[derive(Debug, Clone, Copy)]
+enum CumulativeInput {
+    Prices,
+    Returns,
+}
+
+/// Compounded return since the start of the partition
+#[derive(Debug)]
+pub struct CumulativeReturn {
+    name: String,
+    signature: Signature,
+    input: CumulativeInput,
+}
+
+impl CumulativeReturn {
+    /// `cum_return(price)`: P_t / P_0 - 1
+    pub fn from_prices() -> Self {
+        Self::new("cum_return", CumulativeInput::Prices)
+    }
+
+    /// `cum_return_from(returns)`: Π(1 + r) - 1
+    pub fn from_returns() -> Self {
+        Self::new("cum_return_from", CumulativeInput::Returns)
+    }
+
+    fn new(name: &str, input: CumulativeInput) -> Self {
+        Self {
+            name: name.to_string(),
+            signature: Signature::one_of(
+                vec![TypeSignature::Exact(vec![DataType::Float64])],
+                Volatility::Immutable,
+            ),
+            input,
+        }
+    }
+}
+
+impl WindowUDFImpl for CumulativeReturn {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn signature(&self) -> &Signature {
+        &self.signature
+    }
+
+    fn return_type(&self, _arg_types: &[DataType]) -> Result<DataType> {
+        Ok(DataType::Float64)
+    }
+
+    fn partition_evaluator(&self) -> Result<Box<dyn PartitionEvaluator>> {
+        Ok(Box::new(CumulativeReturnPartitionEvaluator::new(self.input)))
+    }
+}
+
+#[derive(Debug)]
+struct CumulativeReturnPartitionEvaluator {
+    input: CumulativeInput,
+    first_price: Option<f64>,
+    growth: f64,
+}
+
+impl CumulativeReturnPartitionEvaluator {
+    fn new(input: CumulativeInput) -> Self {
+        Self {
+            input,
+            first_price: None,
+            growth: 1.0,
+        }
+    }
+}
+
+impl PartitionEvaluator for CumulativeReturnPartitionEvaluator {
+    fn evaluate_all(
+        &mut self,
+        values: &[ArrayRef],
+        num_rows: usize,
+    ) -> Result<ArrayRef> {
+        check_arg_count(values, 1, "Cumulative return function (value)")?;
+
+        let value_array = float64_arg(values, 0, "First argument")?;
+
+        let mut result = Vec::with_capacity(num_rows);
+        self.first_price = None;
+        self.growth = 1.0;
+
+        for i in 0..num_rows {
+            let Some(value) = value_at(value_array, i) else {
+                result.push(None);
+                continue;
+            };
+
+            match self.input {
+                CumulativeInput::Prices => {
+                    let first = *self.first_price.get_or_insert(value);
+                    if first != 0.0 {
+                        result.push(Some(value / first - 1.0));
+                    } else {
+                        result.push(None);
+                    }
+                }
+                CumulativeInput::Returns => {
+                    self.growth *= 1.0 + value;
+                    result.push(Some(self.growth - 1.0));
+                }
+            }
+        }
+
+        Ok(Arc::new(Float64Array::from(result)))
+    }
+
+    fn uses_window_frame(&self) -> bool {
+        false
+    }
+
+    fn include_rank(&self) -> bool {
+        false
+    }
+}
+
+/// Register `cum_return` and `cum_return_from`
+pub fn register_cum_return(ctx: &SessionContext) -> Result<()> {
+    ctx.register_udwf(WindowUDF::from(CumulativeReturn::from_prices()));
+    ctx.register_udwf(WindowUDF::from(CumulativeReturn::from_returns()));
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use datafusion::execution::context::SessionContext;
+
+    #[tokio::test]
+    async fn test_cum_return() -> Result<()> {
+        let ctx = SessionContext::new();
+        register_cum_return(&ctx)?;
+
+        // Test cumulative return from prices and from periodic returns using SQL
+        let result = ctx
+            .sql("SELECT price, ret,
+                    cum_return(price) OVER () AS cum_from_price,
+                    cum_return_from(ret) OVER () AS cum_from_returns
+                FROM (VALUES
+                (100.0, 0.0), (102.0, 0.02), (99.96, -0.02), (104.958, 0.05), (104.958, 0.0)
+            ) AS t(price, ret)")
+            .await?
+            .collect()
+            .await?;
+
+        println!("Cumulative Return Test Results:");
+        datafusion::arrow::util::pretty::print_batches(&result)?;
+
+        Ok(())
+    }
+}
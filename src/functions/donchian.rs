@@ -0,0 +1,297 @@
+use std::any::Any;
+use std::sync::Arc;
+
+use datafusion::arrow::array::{ArrayRef, Float64Array};
+use datafusion::arrow::datatypes::DataType;
+use datafusion::error::Result;
+use datafusion::execution::context::SessionContext;
+use datafusion::logical_expr::function::WindowFunctionSimplification;
+use datafusion::logical_expr::{Signature, TypeSignature, Volatility, WindowUDF, WindowUDFImpl, PartitionEvaluator};
+
+use super::ohlcv::{HL, OhlcvArgs};
+use super::utils::{check_arg_count, float64_arg, float64_struct_array, float64_struct_type, period_arg, value_at};
+use crate::validation::{period_simplification, PeriodArg};
+
+const DONCHIAN_FIELDS: [&str; 3] = ["upper", "middle", "lower"];
+
+/// Donchian Channels: highest high, lowest low and their midpoint over a period
+#[derive(Debug)]
+pub struct DonchianChannels {
+    name: String,
+    signature: Signature,
+}
+
+impl DonchianChannels {
+    pub fn new() -> Self {
+        Self {
+            name: "donchian".to_string(),
+            signature: Signature::one_of(
+                vec![TypeSignature::Exact(vec![DataType::Float64, DataType::Float64, DataType::Int64])],
+                Volatility::Immutable,
+            ),
+        }
+    }
+}
+
+impl WindowUDFImpl for DonchianChannels {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn signature(&self) -> &Signature {
+        &self.signature
+    }
+
+    fn return_type(&self, _arg_types: &[DataType]) -> Result<DataType> {
+        Ok(float64_struct_type(&DONCHIAN_FIELDS))
+    }
+
+    fn partition_evaluator(&self) -> Result<Box<dyn PartitionEvaluator>> {
+        Ok(Box::new(DonchianPartitionEvaluator::new()))
+    }
+
+    fn simplify(&self) -> Option<WindowFunctionSimplification> {
+        period_simplification(&self.name, vec![PeriodArg::rows(2, "period")])
+    }
+}
+
+#[derive(Debug)]
+struct DonchianPartitionEvaluator {
+    highs: Vec<f64>,
+    lows: Vec<f64>,
+}
+
+impl DonchianPartitionEvaluator {
+    fn new() -> Self {
+        Self {
+            highs: Vec::new(),
+            lows: Vec::new(),
+        }
+    }
+}
+
+impl PartitionEvaluator for DonchianPartitionEvaluator {
+    fn evaluate_all(
+        &mut self,
+        values: &[ArrayRef],
+        num_rows: usize,
+    ) -> Result<ArrayRef> {
+        check_arg_count(values, 3, "Donchian function (high, low, period)")?;
+
+        let high_array = float64_arg(values, 0, "High")?;
+        let low_array = float64_arg(values, 1, "Low")?;
+        let period = period_arg(values, 2, "Period")?;
+
+        let mut upper = Vec::with_capacity(num_rows);
+        let mut middle = Vec::with_capacity(num_rows);
+        let mut lower = Vec::with_capacity(num_rows);
+        self.highs.clear();
+        self.lows.clear();
+
+        for i in 0..num_rows {
+            let (Some(high), Some(low)) = (value_at(high_array, i), value_at(low_array, i)) else {
+                upper.push(None);
+                middle.push(None);
+                lower.push(None);
+                continue;
+            };
+
+            self.highs.push(high);
+            self.lows.push(low);
+
+            if self.highs.len() < period {
+                upper.push(None);
+                middle.push(None);
+                lower.push(None);
+                continue;
+            }
+
+            let start_idx = self.highs.len() - period;
+            let highest = self.highs[start_idx..].iter().cloned().fold(f64::MIN, f64::max);
+            let lowest = self.lows[start_idx..].iter().cloned().fold(f64::MAX, f64::min);
+
+            upper.push(Some(highest));
+            middle.push(Some((highest + lowest) / 2.0));
+            lower.push(Some(lowest));
+        }
+
+        Ok(float64_struct_array(&DONCHIAN_FIELDS, vec![upper, middle, lower]))
+    }
+
+    fn uses_window_frame(&self) -> bool {
+        false
+    }
+
+    fn include_rank(&self) -> bool {
+        false
+    }
+}
+
+/// Which extreme a rolling extrema function tracks
+#[derive(Debug, Clone, Copy)]
+enum Extreme {
+    Highest,
+    Lowest,
+}
+
+/// Rolling highest / lowest value over a fixed number of rows
+#[derive(Debug)]
+pub struct RollingExtreme {
+    name: String,
+    signature: Signature,
+    extreme: Extreme,
+}
+
+impl RollingExtreme {
+    /// `highest(value, period)`
+    pub fn highest() -> Self {
+        Self::new("highest", Extreme::Highest)
+    }
+
+    /// `lowest(value, period)`
+    pub fn lowest() -> Self {
+        Self::new("lowest", Extreme::Lowest)
+    }
+
+    fn new(name: &str, extreme: Extreme) -> Self {
+        Self {
+            name: name.to_string(),
+            signature: Signature::one_of(
+                vec![TypeSignature::Exact(vec![DataType::Float64, DataType::Int64])],
+                Volatility::Immutable,
+            ),
+            extreme,
+        }
+    }
+}
+
+impl WindowUDFImpl for RollingExtreme {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn signature(&self) -> &Signature {
+        &self.signature
+    }
+
+    fn return_type(&self, _arg_types: &[DataType]) -> Result<DataType> {
+        Ok(DataType::Float64)
+    }
+
+    fn partition_evaluator(&self) -> Result<Box<dyn PartitionEvaluator>> {
+        Ok(Box::new(RollingExtremePartitionEvaluator::new(self.extreme)))
+    }
+
+    fn simplify(&self) -> Option<WindowFunctionSimplification> {
+        period_simplification(&self.name, vec![PeriodArg::rows(1, "period")])
+    }
+}
+
+#[derive(Debug)]
+struct RollingExtremePartitionEvaluator {
+    extreme: Extreme,
+    values: Vec<f64>,
+}
+
+impl RollingExtremePartitionEvaluator {
+    fn new(extreme: Extreme) -> Self {
+        Self {
+            extreme,
+            values: Vec::new(),
+        }
+    }
+}
+
+impl PartitionEvaluator for RollingExtremePartitionEvaluator {
+    fn evaluate_all(
+        &mut self,
+        values: &[ArrayRef],
+        num_rows: usize,
+    ) -> Result<ArrayRef> {
+        check_arg_count(values, 2, "Rolling extreme function (value, period)")?;
+
+        let value_array = float64_arg(values, 0, "First argument")?;
+        let period = period_arg(values, 1, "Period")?;
+
+        let mut result = Vec::with_capacity(num_rows);
+        self.values.clear();
+
+        for i in 0..num_rows {
+            let Some(value) = value_at(value_array, i) else {
+                result.push(None);
+                continue;
+            };
+
+            self.values.push(value);
+
+            if self.values.len() < period {
+                result.push(None);
+                continue;
+            }
+
+            let window = self.values[self.values.len() - period..].iter().cloned();
+            let extreme = match self.extreme {
+                Extreme::Highest => window.fold(f64::MIN, f64::max),
+                Extreme::Lowest => window.fold(f64::MAX, f64::min),
+            };
+            result.push(Some(extreme));
+        }
+
+        Ok(Arc::new(Float64Array::from(result)))
+    }
+
+    fn uses_window_frame(&self) -> bool {
+        false
+    }
+
+    fn include_rank(&self) -> bool {
+        false
+    }
+}
+
+/// Register `donchian`, `highest` and `lowest`
+pub fn register_donchian(ctx: &SessionContext) -> Result<()> {
+    ctx.register_udwf(WindowUDF::from(OhlcvArgs::new(DonchianChannels::new(), HL)));
+    ctx.register_udwf(WindowUDF::from(RollingExtreme::highest()));
+    ctx.register_udwf(WindowUDF::from(RollingExtreme::lowest()));
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use datafusion::execution::context::SessionContext;
+
+    #[tokio::test]
+    async fn test_donchian() -> Result<()> {
+        let ctx = SessionContext::new();
+        register_donchian(&ctx)?;
+
+        // Test Donchian channels and rolling extremes with a 3 period window using SQL
+        let result = ctx
+            .sql("SELECT high, low,
+                    donchian(high, low, 3) OVER () AS channel,
+                    highest(high, 3) OVER () AS hh_3,
+                    lowest(low, 3) OVER () AS ll_3
+                FROM (VALUES
+                (101.0, 99.0), (103.0, 100.0), (104.0, 101.0), (102.0, 98.0),
+                (106.0, 99.0), (108.0, 104.0), (107.0, 103.0), (110.0, 105.0)
+            ) AS t(high, low)")
+            .await?
+            .collect()
+            .await?;
+
+        println!("Donchian Test Results:");
+        datafusion::arrow::util::pretty::print_batches(&result)?;
+
+        Ok(())
+    }
+}
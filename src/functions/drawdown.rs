@@ -0,0 +1,266 @@
+use std::any::Any;
+use std::sync::Arc;
+
+use datafusion::arrow::array::{ArrayRef, Float64Array};
+use datafusion::arrow::datatypes::{DataType, Field};
+use datafusion::error::{DataFusionError, Result};
+use datafusion::execution::context::SessionContext;
+use datafusion::logical_expr::function::{AccumulatorArgs, StateFieldsArgs};
+use datafusion::logical_expr::{
+    Accumulator, AggregateUDF, AggregateUDFImpl, PartitionEvaluator, Signature, TypeSignature, Volatility,
+    WindowUDF, WindowUDFImpl,
+};
+use datafusion::scalar::ScalarValue;
+
+use super::utils::{check_arg_count, float64_arg, value_at, OrderedSeries};
+
+/// Deepest peak-to-trough decline of a series as a fraction (<= 0)
+pub(crate) fn max_drawdown(values: &[f64]) -> Option<f64> {
+    let mut peak = f64::MIN;
+    let mut worst: Option<f64> = None;
+    for value in values {
+        peak = peak.max(*value);
+        if peak > 0.0 {
+            let drawdown = value / peak - 1.0;
+            worst = Some(worst.map_or(drawdown, |w| w.min(drawdown)));
+        }
+    }
+    worst
+}
+
+/// Running drawdown from the high-water mark as a fraction (<= 0)
+#[derive(Debug)]
+pub struct Drawdown {
+    name: String,
+    signature: Signature,
+}
+
+impl Drawdown {
+    pub fn new() -> Self {
+        Self {
+            name: "drawdown".to_string(),
+            signature: Signature::one_of(
+                vec![TypeSignature::Exact(vec![DataType::Float64])],
+                Volatility::Immutable,
+            ),
+        }
+    }
+}
+
+impl WindowUDFImpl for Drawdown {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn signature(&self) -> &Signature {
+        &self.signature
+    }
+
+    fn return_type(&self, _arg_types: &[DataType]) -> Result<DataType> {
+        Ok(DataType::Float64)
+    }
+
+    fn partition_evaluator(&self) -> Result<Box<dyn PartitionEvaluator>> {
+        Ok(Box::new(DrawdownPartitionEvaluator::new()))
+    }
+}
+
+#[derive(Debug)]
+struct DrawdownPartitionEvaluator {
+    high_water_mark: Option<f64>,
+}
+
+impl DrawdownPartitionEvaluator {
+    fn new() -> Self {
+        Self { high_water_mark: None }
+    }
+}
+
+impl PartitionEvaluator for DrawdownPartitionEvaluator {
+    fn evaluate_all(
+        &mut self,
+        values: &[ArrayRef],
+        num_rows: usize,
+    ) -> Result<ArrayRef> {
+        check_arg_count(values, 1, "Drawdown function (value)")?;
+
+        let value_array = float64_arg(values, 0, "First argument")?;
+
+        let mut result = Vec::with_capacity(num_rows);
+        self.high_water_mark = None;
+
+        for i in 0..num_rows {
+            let Some(value) = value_at(value_array, i) else {
+                result.push(None);
+                continue;
+            };
+
+            let peak = self.high_water_mark.map_or(value, |hwm| hwm.max(value));
+            self.high_water_mark = Some(peak);
+
+            // Drawdown = value / high-water mark - 1
+            if peak > 0.0 {
+                result.push(Some(value / peak - 1.0));
+            } else {
+                result.push(None);
+            }
+        }
+
+        Ok(Arc::new(Float64Array::from(result)))
+    }
+
+    fn uses_window_frame(&self) -> bool {
+        false
+    }
+
+    fn include_rank(&self) -> bool {
+        false
+    }
+}
+
+/// Maximum drawdown over all values in a group
+///
+/// Give the series order inside the call, e.g.
+/// `max_drawdown(close ORDER BY window_start)`; the keys are carried through
+/// partial aggregation so the result holds across partitions. Without
+/// `ORDER BY` values are taken in arrival order.
+#[derive(Debug)]
+pub struct MaxDrawdown {
+    name: String,
+    signature: Signature,
+}
+
+impl MaxDrawdown {
+    pub fn new() -> Self {
+        Self {
+            name: "max_drawdown".to_string(),
+            signature: Signature::one_of(
+                vec![TypeSignature::Exact(vec![DataType::Float64])],
+                Volatility::Immutable,
+            ),
+        }
+    }
+}
+
+impl AggregateUDFImpl for MaxDrawdown {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn signature(&self) -> &Signature {
+        &self.signature
+    }
+
+    fn return_type(&self, _arg_types: &[DataType]) -> Result<DataType> {
+        Ok(DataType::Float64)
+    }
+
+    fn accumulator(&self, acc_args: AccumulatorArgs) -> Result<Box<dyn Accumulator>> {
+        Ok(Box::new(MaxDrawdownAccumulator {
+            values: OrderedSeries::try_new(1, &acc_args)?,
+        }))
+    }
+
+    fn state_fields(&self, args: StateFieldsArgs) -> Result<Vec<Field>> {
+        Ok(OrderedSeries::state_fields(args.name, &["values"]))
+    }
+}
+
+#[derive(Debug)]
+struct MaxDrawdownAccumulator {
+    values: OrderedSeries,
+}
+
+impl Accumulator for MaxDrawdownAccumulator {
+    fn update_batch(&mut self, values: &[ArrayRef]) -> Result<()> {
+        if values.is_empty() {
+            return Err(DataFusionError::Execution("max_drawdown requires a value argument".to_string()));
+        }
+        // Any ORDER BY columns follow the value
+        self.values.update(&[float64_arg(values, 0, "First argument")?], &values[1..])
+    }
+
+    fn evaluate(&mut self) -> Result<ScalarValue> {
+        let values = self.values.sorted().swap_remove(0).into_iter().flatten().collect::<Vec<_>>();
+        Ok(ScalarValue::Float64(max_drawdown(&values)))
+    }
+
+    fn size(&self) -> usize {
+        std::mem::size_of_val(self) + self.values.heap_size()
+    }
+
+    fn state(&mut self) -> Result<Vec<ScalarValue>> {
+        Ok(self.values.state())
+    }
+
+    fn merge_batch(&mut self, states: &[ArrayRef]) -> Result<()> {
+        self.values.merge(states)
+    }
+}
+
+/// Register `drawdown` and `max_drawdown`
+pub fn register_drawdown(ctx: &SessionContext) -> Result<()> {
+    ctx.register_udwf(WindowUDF::from(Drawdown::new()));
+    ctx.register_udaf(AggregateUDF::from(MaxDrawdown::new()));
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use datafusion::arrow::array::AsArray;
+    use datafusion::arrow::datatypes::Float64Type;
+    use datafusion::execution::context::SessionContext;
+
+    #[tokio::test]
+    async fn test_drawdown() -> Result<()> {
+        let ctx = SessionContext::new();
+        register_drawdown(&ctx)?;
+
+        // Test running drawdown using SQL
+        let result = ctx
+            .sql("SELECT price, drawdown(price) OVER () AS dd FROM (VALUES
+                (100.0), (110.0), (99.0), (88.0), (105.0), (115.0), (103.5)
+            ) AS t(price)")
+            .await?
+            .collect()
+            .await?;
+
+        println!("Drawdown Test Results:");
+        datafusion::arrow::util::pretty::print_batches(&result)?;
+
+        // Test max drawdown aggregate using SQL
+        let result = ctx
+            .sql("SELECT max_drawdown(price) AS mdd FROM (VALUES
+                (100.0), (110.0), (99.0), (88.0), (105.0), (115.0), (103.5)
+            ) AS t(price)")
+            .await?
+            .collect()
+            .await?;
+
+        println!("Max Drawdown Test Results:");
+        datafusion::arrow::util::pretty::print_batches(&result)?;
+
+        // Test that ORDER BY inside the call, not row order, drives the aggregate
+        let result = ctx
+            .sql("SELECT max_drawdown(price ORDER BY day) AS mdd FROM (VALUES
+                (3, 88.0), (1, 100.0), (4, 105.0), (2, 110.0)
+            ) AS t(day, price)")
+            .await?
+            .collect()
+            .await?;
+
+        let mdd = result[0].column(0).as_primitive::<Float64Type>().value(0);
+        assert!((mdd + 0.2).abs() < 1e-12);
+
+        Ok(())
+    }
+}
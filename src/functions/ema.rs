@@ -1,11 +1,18 @@
 use std::any::Any;
+use std::ops::Range;
 use std::sync::Arc;
 
-use datafusion::arrow::array::{ArrayRef, Float64Array, Int64Array};
+use datafusion::arrow::array::{ArrayRef, Float64Array};
 use datafusion::arrow::datatypes::DataType;
 use datafusion::error::{DataFusionError, Result};
 use datafusion::execution::context::SessionContext;
-use datafusion::logical_expr::{Signature, TypeSignature, Volatility, WindowUDF, WindowUDFImpl, PartitionEvaluator};
+use datafusion::logical_expr::function::WindowFunctionSimplification;
+use datafusion::logical_expr::{Signature, Volatility, WindowUDF, WindowUDFImpl, PartitionEvaluator};
+use datafusion::scalar::ScalarValue;
+
+use super::kernels::{self, map_valid};
+use super::utils::{coerce_numeric_args, min_periods_arg, period_arg, value_at};
+use crate::validation::{period_simplification, PeriodArg};
 
 #[derive(Debug)]
 pub struct ExponentialMovingAverage {
@@ -17,10 +24,7 @@ impl ExponentialMovingAverage {
     pub fn new() -> Self {
         Self {
             name: "ema".to_string(),
-            signature: Signature::one_of(
-                vec![TypeSignature::Exact(vec![DataType::Float64, DataType::Int64])],
-                Volatility::Immutable,
-            ),
+            signature: Signature::user_defined(Volatility::Immutable),
         }
     }
 }
@@ -38,6 +42,17 @@ impl WindowUDFImpl for ExponentialMovingAverage {
         &self.signature
     }
 
+    fn coerce_types(&self, arg_types: &[DataType]) -> Result<Vec<DataType>> {
+        coerce_numeric_args(
+            &self.name,
+            arg_types,
+            &[
+                vec![DataType::Float64, DataType::Int64],
+                vec![DataType::Float64, DataType::Int64, DataType::Int64],
+            ],
+        )
+    }
+
     fn return_type(&self, _arg_types: &[DataType]) -> Result<DataType> {
         Ok(DataType::Float64)
     }
@@ -45,13 +60,23 @@ impl WindowUDFImpl for ExponentialMovingAverage {
     fn partition_evaluator(&self) -> Result<Box<dyn PartitionEvaluator>> {
         Ok(Box::new(EmaPartitionEvaluator::new()))
     }
+
+    fn simplify(&self) -> Option<WindowFunctionSimplification> {
+        period_simplification(
+            &self.name,
+            vec![PeriodArg::rows(1, "window_size"), PeriodArg::rows(2, "min_periods")],
+        )
+    }
 }
 
 #[derive(Debug)]
 struct EmaPartitionEvaluator {
     window_size: usize,
     alpha: f64,
+    min_periods: usize,
+    // Running state for row-at-a-time evaluation
     current_ema: Option<f64>,
+    count: usize,
 }
 
 impl EmaPartitionEvaluator {
@@ -59,9 +84,38 @@ impl EmaPartitionEvaluator {
         Self {
             window_size: 0,
             alpha: 0.0,
+            min_periods: 1,
             current_ema: None,
+            count: 0,
         }
     }
+
+    /// Read the constant arguments and derive the smoothing factor
+    fn configure(&mut self, values: &[ArrayRef]) -> Result<()> {
+        if values.len() != 2 && values.len() != 3 {
+            return Err(DataFusionError::Execution(
+                "EMA function requires 2 or 3 arguments: value, window_size and optional min_periods".to_string(),
+            ));
+        }
+
+        self.window_size = period_arg(values, 1, "Window size")?;
+
+        // EMA is seeded from the first value, so by default every row emits
+        self.min_periods = min_periods_arg(values, 2, self.window_size, 1)?;
+
+        // Calculate alpha (smoothing factor): 2 / (N + 1)
+        self.alpha = 2.0 / (self.window_size as f64 + 1.0);
+        Ok(())
+    }
+}
+
+fn value_array(values: &[ArrayRef]) -> Result<&Float64Array> {
+    values[0]
+        .as_any()
+        .downcast_ref::<Float64Array>()
+        .ok_or_else(|| {
+            DataFusionError::Execution("First argument must be Float64".to_string())
+        })
 }
 
 impl PartitionEvaluator for EmaPartitionEvaluator {
@@ -70,61 +124,44 @@ impl PartitionEvaluator for EmaPartitionEvaluator {
         values: &[ArrayRef],
         num_rows: usize,
     ) -> Result<ArrayRef> {
-        if values.len() != 2 {
-            return Err(DataFusionError::Execution(
-                "EMA function requires exactly 2 arguments: value and window_size".to_string(),
-            ));
-        }
+        self.configure(values)?;
+        let value_array = value_array(values)?;
 
-        let value_array = values[0]
-            .as_any()
-            .downcast_ref::<Float64Array>()
-            .ok_or_else(|| {
-                DataFusionError::Execution("First argument must be Float64".to_string())
-            })?;
-
-        let window_size_array = values[1]
-            .as_any()
-            .downcast_ref::<Int64Array>()
-            .ok_or_else(|| {
-                DataFusionError::Execution("Second argument must be Int64".to_string())
-            })?;
-
-        // Get window size from first non-null value
-        self.window_size = window_size_array
-            .iter()
-            .find_map(|x| x)
-            .ok_or_else(|| {
-                DataFusionError::Execution("Window size cannot be null".to_string())
-            })? as usize;
+        // EMA = alpha * current_value + (1 - alpha) * previous_ema, seeded with
+        // the first value; rows before min_periods values are null
+        let alpha = self.alpha;
+        let warmup = self.min_periods.saturating_sub(1);
+        let result = map_valid(value_array, |values, out| kernels::ema(values, alpha, out).max(warmup));
+        debug_assert_eq!(result.len(), num_rows);
 
-        // Calculate alpha (smoothing factor): 2 / (N + 1)
-        self.alpha = 2.0 / (self.window_size as f64 + 1.0);
+        Ok(Arc::new(result))
+    }
 
-        let mut result = Vec::with_capacity(num_rows);
-        self.current_ema = None;
-
-        for i in 0..num_rows {
-            if let Some(value) = value_array.value(i).into() {
-                match self.current_ema {
-                    None => {
-                        // First value becomes the initial EMA
-                        self.current_ema = Some(value);
-                        result.push(Some(value));
-                    }
-                    Some(prev_ema) => {
-                        // EMA = alpha * current_value + (1 - alpha) * previous_ema
-                        let new_ema = self.alpha * value + (1.0 - self.alpha) * prev_ema;
-                        self.current_ema = Some(new_ema);
-                        result.push(Some(new_ema));
-                    }
-                }
-            } else {
-                result.push(None);
-            }
+    /// Streaming evaluation: only the previous EMA is kept between rows
+    fn evaluate(&mut self, values: &[ArrayRef], range: &Range<usize>) -> Result<ScalarValue> {
+        if self.window_size == 0 {
+            self.configure(values)?;
         }
+        let Some(value) = value_at(value_array(values)?, range.start) else {
+            return Ok(ScalarValue::Float64(None));
+        };
+
+        let ema = match self.current_ema {
+            Some(prev) => self.alpha * value + (1.0 - self.alpha) * prev,
+            None => value,
+        };
+        self.current_ema = Some(ema);
+        self.count += 1;
+
+        Ok(ScalarValue::Float64((self.count >= self.min_periods).then_some(ema)))
+    }
+
+    fn supports_bounded_execution(&self) -> bool {
+        true
+    }
 
-        Ok(Arc::new(Float64Array::from(result)))
+    fn is_causal(&self) -> bool {
+        true
     }
 
     fn uses_window_frame(&self) -> bool {
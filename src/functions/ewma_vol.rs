@@ -0,0 +1,160 @@
+use std::any::Any;
+use std::sync::Arc;
+
+use datafusion::arrow::array::{ArrayRef, Float64Array};
+use datafusion::arrow::datatypes::DataType;
+use datafusion::error::{DataFusionError, Result};
+use datafusion::execution::context::SessionContext;
+use datafusion::logical_expr::{Signature, TypeSignature, Volatility, WindowUDF, WindowUDFImpl, PartitionEvaluator};
+
+use super::utils::{f64_param_arg, float64_arg, value_at};
+
+/// RiskMetrics decay factor for daily returns
+const DEFAULT_LAMBDA: f64 = 0.94;
+
+/// Exponentially weighted (RiskMetrics) volatility of periodic returns
+///
+/// Returns per-period volatility; scale by sqrt(periods per year) to annualize.
+#[derive(Debug)]
+pub struct EwmaVolatility {
+    name: String,
+    signature: Signature,
+}
+
+impl EwmaVolatility {
+    pub fn new() -> Self {
+        Self {
+            name: "ewma_vol".to_string(),
+            signature: Signature::one_of(
+                vec![
+                    TypeSignature::Exact(vec![DataType::Float64]),
+                    TypeSignature::Exact(vec![DataType::Float64, DataType::Float64]),
+                ],
+                Volatility::Immutable,
+            ),
+        }
+    }
+}
+
+impl WindowUDFImpl for EwmaVolatility {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn signature(&self) -> &Signature {
+        &self.signature
+    }
+
+    fn return_type(&self, _arg_types: &[DataType]) -> Result<DataType> {
+        Ok(DataType::Float64)
+    }
+
+    fn partition_evaluator(&self) -> Result<Box<dyn PartitionEvaluator>> {
+        Ok(Box::new(EwmaVolPartitionEvaluator::new()))
+    }
+}
+
+#[derive(Debug)]
+struct EwmaVolPartitionEvaluator {
+    variance: Option<f64>,
+}
+
+impl EwmaVolPartitionEvaluator {
+    fn new() -> Self {
+        Self { variance: None }
+    }
+}
+
+impl PartitionEvaluator for EwmaVolPartitionEvaluator {
+    fn evaluate_all(
+        &mut self,
+        values: &[ArrayRef],
+        num_rows: usize,
+    ) -> Result<ArrayRef> {
+        if values.is_empty() || values.len() > 2 {
+            return Err(DataFusionError::Execution(
+                "EWMA volatility function requires 1 or 2 arguments: returns and optional lambda".to_string(),
+            ));
+        }
+
+        let returns_array = float64_arg(values, 0, "Returns")?;
+        let lambda = if values.len() == 2 {
+            f64_param_arg(values, 1, "Lambda")?
+        } else {
+            DEFAULT_LAMBDA
+        };
+        if !(0.0..1.0).contains(&lambda) {
+            return Err(DataFusionError::Execution(format!(
+                "Lambda must be in [0, 1), got {}",
+                lambda
+            )));
+        }
+
+        let mut result = Vec::with_capacity(num_rows);
+        self.variance = None;
+
+        for i in 0..num_rows {
+            let Some(r) = value_at(returns_array, i) else {
+                result.push(self.variance.map(f64::sqrt));
+                continue;
+            };
+
+            // σ²_t = λ × σ²_t-1 + (1 - λ) × r²_t, seeded with the first r²
+            let variance = match self.variance {
+                Some(prev) => lambda * prev + (1.0 - lambda) * r * r,
+                None => r * r,
+            };
+            self.variance = Some(variance);
+            result.push(Some(variance.sqrt()));
+        }
+
+        Ok(Arc::new(Float64Array::from(result)))
+    }
+
+    fn uses_window_frame(&self) -> bool {
+        false
+    }
+
+    fn include_rank(&self) -> bool {
+        false
+    }
+}
+
+pub fn register_ewma_vol(ctx: &SessionContext) -> Result<()> {
+    let ewma_vol_udf = WindowUDF::from(EwmaVolatility::new());
+    ctx.register_udwf(ewma_vol_udf);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use datafusion::execution::context::SessionContext;
+
+    #[tokio::test]
+    async fn test_ewma_vol() -> Result<()> {
+        let ctx = SessionContext::new();
+        register_ewma_vol(&ctx)?;
+
+        // Test EWMA volatility with the default and an explicit lambda using SQL
+        let result = ctx
+            .sql("SELECT ret,
+                    ewma_vol(ret) OVER () AS vol_default,
+                    ewma_vol(ret, 0.97) OVER () AS vol_97
+                FROM (VALUES
+                (0.012), (-0.004), (0.007), (-0.011), (0.015), (0.003), (-0.021), (0.009)
+            ) AS t(ret)")
+            .await?
+            .collect()
+            .await?;
+
+        println!("EWMA Volatility Test Results:");
+        datafusion::arrow::util::pretty::print_batches(&result)?;
+
+        Ok(())
+    }
+}
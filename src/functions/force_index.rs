@@ -0,0 +1,156 @@
+use std::any::Any;
+use std::sync::Arc;
+
+use datafusion::arrow::array::{ArrayRef, Float64Array};
+use datafusion::arrow::datatypes::DataType;
+use datafusion::error::Result;
+use datafusion::execution::context::SessionContext;
+use datafusion::logical_expr::function::WindowFunctionSimplification;
+use datafusion::logical_expr::{Signature, TypeSignature, Volatility, WindowUDF, WindowUDFImpl, PartitionEvaluator};
+
+use super::ohlcv::{CV, OhlcvArgs};
+use super::utils::{check_arg_count, float64_arg, period_arg, value_at};
+use crate::validation::{period_simplification, PeriodArg};
+
+/// Elder's Force Index: EMA of price change × volume
+#[derive(Debug)]
+pub struct ForceIndex {
+    name: String,
+    signature: Signature,
+}
+
+impl ForceIndex {
+    pub fn new() -> Self {
+        Self {
+            name: "force_index".to_string(),
+            signature: Signature::one_of(
+                vec![TypeSignature::Exact(vec![DataType::Float64, DataType::Float64, DataType::Int64])],
+                Volatility::Immutable,
+            ),
+        }
+    }
+}
+
+impl WindowUDFImpl for ForceIndex {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn signature(&self) -> &Signature {
+        &self.signature
+    }
+
+    fn return_type(&self, _arg_types: &[DataType]) -> Result<DataType> {
+        Ok(DataType::Float64)
+    }
+
+    fn partition_evaluator(&self) -> Result<Box<dyn PartitionEvaluator>> {
+        Ok(Box::new(ForceIndexPartitionEvaluator::new()))
+    }
+
+    fn simplify(&self) -> Option<WindowFunctionSimplification> {
+        period_simplification(&self.name, vec![PeriodArg::rows(2, "period")])
+    }
+}
+
+#[derive(Debug)]
+struct ForceIndexPartitionEvaluator {
+    prev_close: Option<f64>,
+    current_ema: Option<f64>,
+}
+
+impl ForceIndexPartitionEvaluator {
+    fn new() -> Self {
+        Self {
+            prev_close: None,
+            current_ema: None,
+        }
+    }
+}
+
+impl PartitionEvaluator for ForceIndexPartitionEvaluator {
+    fn evaluate_all(
+        &mut self,
+        values: &[ArrayRef],
+        num_rows: usize,
+    ) -> Result<ArrayRef> {
+        check_arg_count(values, 3, "Force index function (close, volume, period)")?;
+
+        let close_array = float64_arg(values, 0, "Close")?;
+        let volume_array = float64_arg(values, 1, "Volume")?;
+        let period = period_arg(values, 2, "Period")?;
+        let alpha = 2.0 / (period as f64 + 1.0);
+
+        let mut result = Vec::with_capacity(num_rows);
+        self.prev_close = None;
+        self.current_ema = None;
+
+        for i in 0..num_rows {
+            let (Some(close), Some(volume)) = (value_at(close_array, i), value_at(volume_array, i)) else {
+                result.push(None);
+                continue;
+            };
+
+            let Some(prev_close) = self.prev_close.replace(close) else {
+                result.push(None);
+                continue;
+            };
+
+            // Raw force = (close - previous close) × volume
+            let force = (close - prev_close) * volume;
+            let smoothed = match self.current_ema {
+                None => force,
+                Some(prev_ema) => alpha * force + (1.0 - alpha) * prev_ema,
+            };
+            self.current_ema = Some(smoothed);
+            result.push(Some(smoothed));
+        }
+
+        Ok(Arc::new(Float64Array::from(result)))
+    }
+
+    fn uses_window_frame(&self) -> bool {
+        false
+    }
+
+    fn include_rank(&self) -> bool {
+        false
+    }
+}
+
+pub fn register_force_index(ctx: &SessionContext) -> Result<()> {
+    let force_index_udf = WindowUDF::from(OhlcvArgs::new(ForceIndex::new(), CV));
+    ctx.register_udwf(force_index_udf);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use datafusion::execution::context::SessionContext;
+
+    #[tokio::test]
+    async fn test_force_index() -> Result<()> {
+        let ctx = SessionContext::new();
+        register_force_index(&ctx)?;
+
+        // Test 3 period force index using SQL
+        let result = ctx
+            .sql("SELECT close, volume, force_index(close, volume, 3) OVER () AS fi_3 FROM (VALUES
+                (14.33, 0.0), (14.23, 45579.0), (13.98, 66285.0), (13.96, 51761.0),
+                (13.93, 69341.0), (13.84, 41631.0), (13.99, 73499.0), (14.31, 55427.0)
+            ) AS t(close, volume)")
+            .await?
+            .collect()
+            .await?;
+
+        println!("Force Index Test Results:");
+        datafusion::arrow::util::pretty::print_batches(&result)?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,174 @@
+use std::any::Any;
+use std::sync::Arc;
+
+use datafusion::arrow::array::{ArrayRef, BooleanArray, StructArray};
+use datafusion::arrow::datatypes::{DataType, Field, Fields};
+use datafusion::error::Result;
+use datafusion::execution::context::SessionContext;
+use datafusion::logical_expr::function::WindowFunctionSimplification;
+use datafusion::logical_expr::{Signature, TypeSignature, Volatility, WindowUDF, WindowUDFImpl, PartitionEvaluator};
+
+use super::ohlcv::{HL, OhlcvArgs};
+use super::utils::{check_arg_count, float64_arg, period_arg, value_at};
+use crate::validation::{period_simplification, PeriodArg};
+
+/// Bill Williams fractal pivots
+///
+/// A bar is an up (down) fractal when its high (low) is strictly above (below)
+/// the `lookback` bars on both sides. Confirmation needs future bars, so the
+/// first and last `lookback` rows of a partition are null.
+#[derive(Debug)]
+pub struct Fractal {
+    name: String,
+    signature: Signature,
+}
+
+impl Fractal {
+    pub fn new() -> Self {
+        Self {
+            name: "fractal".to_string(),
+            signature: Signature::one_of(
+                vec![TypeSignature::Exact(vec![DataType::Float64, DataType::Float64, DataType::Int64])],
+                Volatility::Immutable,
+            ),
+        }
+    }
+}
+
+fn fractal_fields() -> Fields {
+    Fields::from(vec![
+        Field::new("up", DataType::Boolean, true),
+        Field::new("down", DataType::Boolean, true),
+    ])
+}
+
+impl WindowUDFImpl for Fractal {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn signature(&self) -> &Signature {
+        &self.signature
+    }
+
+    fn return_type(&self, _arg_types: &[DataType]) -> Result<DataType> {
+        Ok(DataType::Struct(fractal_fields()))
+    }
+
+    fn partition_evaluator(&self) -> Result<Box<dyn PartitionEvaluator>> {
+        Ok(Box::new(FractalPartitionEvaluator::new()))
+    }
+
+    fn simplify(&self) -> Option<WindowFunctionSimplification> {
+        period_simplification(&self.name, vec![PeriodArg::rows(2, "lookback")])
+    }
+}
+
+#[derive(Debug)]
+struct FractalPartitionEvaluator;
+
+impl FractalPartitionEvaluator {
+    fn new() -> Self {
+        Self
+    }
+}
+
+impl PartitionEvaluator for FractalPartitionEvaluator {
+    fn evaluate_all(
+        &mut self,
+        values: &[ArrayRef],
+        num_rows: usize,
+    ) -> Result<ArrayRef> {
+        check_arg_count(values, 3, "Fractal function (high, low, lookback)")?;
+
+        let high_array = float64_arg(values, 0, "High")?;
+        let low_array = float64_arg(values, 1, "Low")?;
+        let lookback = period_arg(values, 2, "Lookback")?;
+
+        let highs = (0..num_rows).map(|i| value_at(high_array, i)).collect::<Vec<_>>();
+        let lows = (0..num_rows).map(|i| value_at(low_array, i)).collect::<Vec<_>>();
+
+        let mut up = Vec::with_capacity(num_rows);
+        let mut down = Vec::with_capacity(num_rows);
+
+        for i in 0..num_rows {
+            if i < lookback || i + lookback >= num_rows {
+                up.push(None);
+                down.push(None);
+                continue;
+            }
+
+            let neighbours = (i - lookback..=i + lookback).filter(|j| *j != i);
+            up.push(highs[i].and_then(|high| {
+                neighbours
+                    .clone()
+                    .map(|j| highs[j].map(|h| high > h))
+                    .collect::<Option<Vec<_>>>()
+                    .map(|above| above.into_iter().all(|x| x))
+            }));
+            down.push(lows[i].and_then(|low| {
+                neighbours
+                    .clone()
+                    .map(|j| lows[j].map(|l| low < l))
+                    .collect::<Option<Vec<_>>>()
+                    .map(|below| below.into_iter().all(|x| x))
+            }));
+        }
+
+        Ok(Arc::new(StructArray::new(
+            fractal_fields(),
+            vec![
+                Arc::new(BooleanArray::from(up)) as ArrayRef,
+                Arc::new(BooleanArray::from(down)) as ArrayRef,
+            ],
+            None,
+        )))
+    }
+
+    fn uses_window_frame(&self) -> bool {
+        false
+    }
+
+    fn include_rank(&self) -> bool {
+        false
+    }
+}
+
+pub fn register_fractal(ctx: &SessionContext) -> Result<()> {
+    let fractal_udf = WindowUDF::from(OhlcvArgs::new(Fractal::new(), HL));
+    ctx.register_udwf(fractal_udf);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use datafusion::execution::context::SessionContext;
+
+    #[tokio::test]
+    async fn test_fractal() -> Result<()> {
+        let ctx = SessionContext::new();
+        register_fractal(&ctx)?;
+
+        // Test 2-bar fractals using SQL
+        let result = ctx
+            .sql("SELECT high, low,
+                    fractal(high, low, 2) OVER () AS fractal
+                FROM (VALUES
+                (101.0, 99.0), (102.0, 100.0), (105.0, 101.0), (103.0, 100.5), (102.0, 98.0),
+                (101.5, 96.0), (102.5, 97.5), (104.0, 99.0), (103.0, 100.0)
+            ) AS t(high, low)")
+            .await?
+            .collect()
+            .await?;
+
+        println!("Fractal Test Results:");
+        datafusion::arrow::util::pretty::print_batches(&result)?;
+
+        Ok(())
+    }
+}
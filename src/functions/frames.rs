@@ -0,0 +1,282 @@
+//! Opt-in SQL window frame support for the financial window functions
+//!
+//! By default the evaluators ignore the window frame and compute each
+//! indicator over the whole ordered partition, which is what `OVER ()` and
+//! `OVER (PARTITION BY ... ORDER BY ...)` users expect. Wrapping a function
+//! with [`frame_aware`] instead evaluates it over the rows of each row's
+//! frame and reports the value at the frame's last row, so
+//! `ROWS BETWEEN 20 PRECEDING AND CURRENT ROW` bounds the history it sees.
+//!
+//! Functions registered through [`FinancialFunctions`](crate::FinancialFunctions)
+//! reject any other frame at planning time rather than silently ignore it.
+//! The frame-aware variants re-evaluate each row's whole frame, so a frame of
+//! `w` rows costs O(n·w) per partition instead of O(n).
+
+use std::any::Any;
+use std::collections::HashSet;
+use std::ops::Range;
+use std::sync::Arc;
+
+use datafusion::arrow::array::{Array, ArrayRef};
+use datafusion::arrow::datatypes::DataType;
+use datafusion::common::config::ConfigOptions;
+use datafusion::common::tree_node::{TreeNode, TreeNodeRecursion};
+use datafusion::error::{DataFusionError, Result};
+use datafusion::execution::context::SessionContext;
+use datafusion::logical_expr::expr::{WindowFunction, WindowFunctionDefinition};
+use datafusion::logical_expr::function::WindowFunctionSimplification;
+use datafusion::logical_expr::{
+    Expr, LogicalPlan, PartitionEvaluator, Signature, WindowFrame, WindowFrameBound, WindowUDF, WindowUDFImpl,
+};
+use datafusion::optimizer::AnalyzerRule;
+use datafusion::scalar::ScalarValue;
+
+/// Wraps a window function so it is evaluated per row over its SQL frame
+#[derive(Debug)]
+pub struct FrameAware {
+    inner: Arc<dyn WindowUDFImpl>,
+}
+
+impl FrameAware {
+    pub fn new(udf: &WindowUDF) -> Self {
+        Self {
+            inner: Arc::clone(udf.inner()),
+        }
+    }
+}
+
+impl WindowUDFImpl for FrameAware {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn name(&self) -> &str {
+        self.inner.name()
+    }
+
+    fn signature(&self) -> &Signature {
+        self.inner.signature()
+    }
+
+    fn coerce_types(&self, arg_types: &[DataType]) -> Result<Vec<DataType>> {
+        self.inner.coerce_types(arg_types)
+    }
+
+    fn return_type(&self, arg_types: &[DataType]) -> Result<DataType> {
+        self.inner.return_type(arg_types)
+    }
+
+    fn partition_evaluator(&self) -> Result<Box<dyn PartitionEvaluator>> {
+        Ok(Box::new(FrameAwarePartitionEvaluator {
+            udf: Arc::clone(&self.inner),
+            inner: self.inner.partition_evaluator()?,
+        }))
+    }
+
+    fn simplify(&self) -> Option<WindowFunctionSimplification> {
+        self.inner.simplify()
+    }
+}
+
+#[derive(Debug)]
+struct FrameAwarePartitionEvaluator {
+    udf: Arc<dyn WindowUDFImpl>,
+    inner: Box<dyn PartitionEvaluator>,
+}
+
+impl PartitionEvaluator for FrameAwarePartitionEvaluator {
+    fn evaluate(&mut self, values: &[ArrayRef], range: &Range<usize>) -> Result<ScalarValue> {
+        let frame = values
+            .iter()
+            .map(|array| array.slice(range.start, range.len()))
+            .collect::<Vec<_>>();
+
+        if range.is_empty() {
+            // Constant arguments (periods) are unreadable in an empty frame, so
+            // emit a null of the function's return type without evaluating it
+            let arg_types = values.iter().map(|array| array.data_type().clone()).collect::<Vec<_>>();
+            return ScalarValue::try_from(&self.udf.return_type(&arg_types)?);
+        }
+
+        let result = self.inner.evaluate_all(&frame, range.len())?;
+        ScalarValue::try_from_array(&result, range.len() - 1)
+    }
+
+    fn uses_window_frame(&self) -> bool {
+        true
+    }
+
+    fn include_rank(&self) -> bool {
+        false
+    }
+}
+
+/// Wrap a window function so it honours `ROWS` / `RANGE` frames
+pub fn frame_aware(udf: &WindowUDF) -> WindowUDF {
+    WindowUDF::from(FrameAware::new(udf))
+}
+
+/// Analyzer rule failing plans that give `functions` a frame they would ignore
+///
+/// Only the frames DataFusion assigns an `OVER` clause without one pass:
+/// from the partition start to the current row or the partition end.
+/// Frame-aware wrappers registered under the same names are let through.
+#[derive(Debug)]
+pub(crate) struct RejectIgnoredFrames {
+    functions: HashSet<String>,
+}
+
+impl RejectIgnoredFrames {
+    pub(crate) fn new(functions: HashSet<String>) -> Self {
+        Self { functions }
+    }
+
+    fn check(&self, function: &WindowFunction) -> Result<()> {
+        let WindowFunctionDefinition::WindowUDF(udf) = &function.fun else {
+            return Ok(());
+        };
+        if !self.functions.contains(udf.name())
+            || udf.inner().as_any().is::<FrameAware>()
+            || is_default_frame(&function.window_frame)
+        {
+            return Ok(());
+        }
+        Err(DataFusionError::Plan(format!(
+            "{} ignores window frames and computes over the whole partition, so '{}' would have no effect; \
+             drop the frame or register the frame-aware variants with register_frame_aware_functions",
+            udf.name(),
+            function.window_frame
+        )))
+    }
+}
+
+impl AnalyzerRule for RejectIgnoredFrames {
+    fn analyze(&self, plan: LogicalPlan, _config: &ConfigOptions) -> Result<LogicalPlan> {
+        plan.apply_with_subqueries(|node| {
+            if let LogicalPlan::Window(window) = node {
+                for expr in &window.window_expr {
+                    expr.apply(|expr| {
+                        if let Expr::WindowFunction(function) = expr {
+                            self.check(function)?;
+                        }
+                        Ok(TreeNodeRecursion::Continue)
+                    })?;
+                }
+            }
+            Ok(TreeNodeRecursion::Continue)
+        })?;
+        Ok(plan)
+    }
+
+    fn name(&self) -> &str {
+        "reject_ignored_frames"
+    }
+}
+
+/// Whether `frame` runs from the partition start to the current row or the
+/// partition end, as the frames of `OVER` clauses without one do
+fn is_default_frame(frame: &WindowFrame) -> bool {
+    let from_start = matches!(&frame.start_bound, WindowFrameBound::Preceding(value) if value.is_null());
+    let to_row_or_end = match &frame.end_bound {
+        WindowFrameBound::CurrentRow => true,
+        WindowFrameBound::Following(value) => value.is_null(),
+        WindowFrameBound::Preceding(_) => false,
+    };
+    from_start && to_row_or_end
+}
+
+/// Register every financial function, with the window functions frame-aware
+///
+/// Note that `OVER ()` then means the whole partition for every row, as with
+/// built-in SQL aggregates; add an explicit `ROWS BETWEEN ...` frame.
+pub fn register_frame_aware_functions(ctx: &SessionContext) -> Result<()> {
+    let defaults = SessionContext::new();
+    let builtin = defaults
+        .state()
+        .window_functions()
+        .keys()
+        .cloned()
+        .collect::<HashSet<_>>();
+
+    crate::register_financial_functions(&defaults)?;
+    crate::register_financial_functions(ctx)?;
+
+    let state = defaults.state();
+    for (name, udf) in state.window_functions() {
+        if !builtin.contains(name) {
+            ctx.register_udwf(frame_aware(udf));
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::functions::sma::SimpleMovingAverage;
+
+    #[tokio::test]
+    async fn test_frame_aware() -> Result<()> {
+        let ctx = SessionContext::new();
+        ctx.register_udwf(frame_aware(&WindowUDF::from(SimpleMovingAverage::new())));
+
+        // Test SMA limited to a 3 row frame using SQL
+        let result = ctx
+            .sql("SELECT price,
+                    sma(price, 2) OVER (ORDER BY day ROWS BETWEEN 2 PRECEDING AND CURRENT ROW) AS sma_framed
+                FROM (VALUES
+                (1, 1.0), (2, 2.0), (3, 3.0), (4, 4.0), (5, 5.0), (6, 6.0)
+            ) AS t(day, price)")
+            .await?
+            .collect()
+            .await?;
+
+        println!("Frame-Aware Test Results:");
+        datafusion::arrow::util::pretty::print_batches(&result)?;
+
+        // Test that empty frames give typed nulls
+        let result = ctx
+            .sql("SELECT sma(price, 1) OVER (ORDER BY day ROWS BETWEEN 3 PRECEDING AND 2 PRECEDING) AS sma_lagged
+                FROM (VALUES (1, 1.0), (2, 2.0), (3, 3.0), (4, 4.0)) AS t(day, price)")
+            .await?
+            .collect()
+            .await?;
+
+        let column = result[0].column(0);
+        assert_eq!(column.data_type(), &DataType::Float64);
+        assert!(column.is_null(0) && column.is_null(1));
+        assert!(column.is_valid(2));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_reject_ignored_frames() -> Result<()> {
+        let framed = "SELECT sma(price, 2) OVER (ORDER BY day ROWS BETWEEN 2 PRECEDING AND CURRENT ROW) AS sma
+            FROM (VALUES (1, 1.0), (2, 2.0), (3, 3.0)) AS t(day, price)";
+
+        let ctx = SessionContext::new();
+        crate::FinancialFunctions::new().with_sma().register(&ctx)?;
+
+        // An explicit bounded frame would be ignored, so planning fails
+        let error = ctx.sql(framed).await?.collect().await.unwrap_err();
+        assert!(error.to_string().contains("sma ignores window frames"), "{}", error);
+
+        // Frames DataFusion fills in for OVER clauses without one are fine
+        for over in ["()", "(ORDER BY day)", "(ORDER BY day ROWS UNBOUNDED PRECEDING)"] {
+            let sql = format!(
+                "SELECT sma(price, 2) OVER {} AS sma FROM (VALUES (1, 1.0), (2, 2.0)) AS t(day, price)",
+                over
+            );
+            ctx.sql(&sql).await?.collect().await?;
+        }
+
+        // The frame-aware variants registered over them honour the frame
+        let ctx = SessionContext::new();
+        register_frame_aware_functions(&ctx)?;
+        ctx.sql(framed).await?.collect().await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,192 @@
+use std::any::Any;
+use std::sync::Arc;
+
+use datafusion::arrow::array::{ArrayRef, BooleanArray, Float64Array};
+use datafusion::arrow::datatypes::DataType;
+use datafusion::error::Result;
+use datafusion::execution::context::SessionContext;
+use datafusion::logical_expr::{Signature, TypeSignature, Volatility, WindowUDF, WindowUDFImpl, PartitionEvaluator};
+
+use super::ohlcv::{OC, OhlcvArgs};
+use super::utils::{check_arg_count, f64_param_arg, float64_arg, value_at};
+
+/// Which gap output a function reports
+#[derive(Debug, Clone, Copy)]
+enum GapOutput {
+    Percent,
+    Flag,
+}
+
+/// Opening gap against the previous bar's close in the partition
+///
+/// Takes the current bar's close rather than a pre-computed LAG, so the
+/// previous close is carried in the evaluator.
+#[derive(Debug)]
+pub struct GapDetector {
+    name: String,
+    signature: Signature,
+    output: GapOutput,
+}
+
+impl GapDetector {
+    /// `gap_pct(open, close)`
+    pub fn percent() -> Self {
+        Self::new(
+            "gap_pct",
+            GapOutput::Percent,
+            vec![DataType::Float64, DataType::Float64],
+        )
+    }
+
+    /// `is_gap(open, close, threshold_pct)`
+    pub fn flag() -> Self {
+        Self::new(
+            "is_gap",
+            GapOutput::Flag,
+            vec![DataType::Float64, DataType::Float64, DataType::Float64],
+        )
+    }
+
+    fn new(name: &str, output: GapOutput, arg_types: Vec<DataType>) -> Self {
+        Self {
+            name: name.to_string(),
+            signature: Signature::one_of(vec![TypeSignature::Exact(arg_types)], Volatility::Immutable),
+            output,
+        }
+    }
+}
+
+impl WindowUDFImpl for GapDetector {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn signature(&self) -> &Signature {
+        &self.signature
+    }
+
+    fn return_type(&self, _arg_types: &[DataType]) -> Result<DataType> {
+        match self.output {
+            GapOutput::Percent => Ok(DataType::Float64),
+            GapOutput::Flag => Ok(DataType::Boolean),
+        }
+    }
+
+    fn partition_evaluator(&self) -> Result<Box<dyn PartitionEvaluator>> {
+        Ok(Box::new(GapPartitionEvaluator::new(self.output)))
+    }
+}
+
+#[derive(Debug)]
+struct GapPartitionEvaluator {
+    output: GapOutput,
+    prev_close: Option<f64>,
+}
+
+impl GapPartitionEvaluator {
+    fn new(output: GapOutput) -> Self {
+        Self {
+            output,
+            prev_close: None,
+        }
+    }
+}
+
+impl PartitionEvaluator for GapPartitionEvaluator {
+    fn evaluate_all(
+        &mut self,
+        values: &[ArrayRef],
+        num_rows: usize,
+    ) -> Result<ArrayRef> {
+        let threshold = match self.output {
+            GapOutput::Percent => {
+                check_arg_count(values, 2, "gap_pct(open, close)")?;
+                None
+            }
+            GapOutput::Flag => {
+                check_arg_count(values, 3, "is_gap(open, close, threshold_pct)")?;
+                Some(f64_param_arg(values, 2, "Threshold")?.abs())
+            }
+        };
+
+        let open_array = float64_arg(values, 0, "Open")?;
+        let close_array = float64_arg(values, 1, "Close")?;
+
+        let mut gaps = Vec::with_capacity(num_rows);
+        self.prev_close = None;
+
+        for i in 0..num_rows {
+            // Gap % = 100 × (open - prev close) / prev close
+            let gap = match (value_at(open_array, i), self.prev_close) {
+                (Some(open), Some(prev_close)) if prev_close != 0.0 => {
+                    Some(100.0 * (open - prev_close) / prev_close)
+                }
+                _ => None,
+            };
+            gaps.push(gap);
+
+            if let Some(close) = value_at(close_array, i) {
+                self.prev_close = Some(close);
+            }
+        }
+
+        Ok(match threshold {
+            Some(threshold) => Arc::new(
+                gaps.into_iter()
+                    .map(|gap| gap.map(|g| g.abs() >= threshold))
+                    .collect::<BooleanArray>(),
+            ),
+            None => Arc::new(Float64Array::from(gaps)),
+        })
+    }
+
+    fn uses_window_frame(&self) -> bool {
+        false
+    }
+
+    fn include_rank(&self) -> bool {
+        false
+    }
+}
+
+/// Register `gap_pct` and `is_gap`
+pub fn register_gaps(ctx: &SessionContext) -> Result<()> {
+    ctx.register_udwf(WindowUDF::from(OhlcvArgs::new(GapDetector::percent(), OC)));
+    ctx.register_udwf(WindowUDF::from(OhlcvArgs::new(GapDetector::flag(), OC)));
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use datafusion::execution::context::SessionContext;
+
+    #[tokio::test]
+    async fn test_gaps() -> Result<()> {
+        let ctx = SessionContext::new();
+        register_gaps(&ctx)?;
+
+        // Test per-ticker gap detection with a 1% threshold using SQL
+        let result = ctx
+            .sql("SELECT ticker, open, close,
+                    gap_pct(open, close) OVER (PARTITION BY ticker ORDER BY day) AS gap,
+                    is_gap(open, close, 1.0) OVER (PARTITION BY ticker ORDER BY day) AS gapped
+                FROM (VALUES
+                ('AAPL', 1, 100.0, 101.0), ('AAPL', 2, 103.0, 102.5), ('AAPL', 3, 102.4, 101.0),
+                ('MSFT', 1, 300.0, 305.0), ('MSFT', 2, 298.0, 299.0), ('MSFT', 3, 299.5, 301.0)
+            ) AS t(ticker, day, open, close)
+                ORDER BY ticker, day")
+            .await?
+            .collect()
+            .await?;
+
+        println!("Gap Detection Test Results:");
+        datafusion::arrow::util::pretty::print_batches(&result)?;
+
+        Ok(())
+    }
+}
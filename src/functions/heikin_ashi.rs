@@ -0,0 +1,160 @@
+use std::any::Any;
+
+use datafusion::arrow::array::ArrayRef;
+use datafusion::arrow::datatypes::DataType;
+use datafusion::error::Result;
+use datafusion::execution::context::SessionContext;
+use datafusion::logical_expr::{Signature, TypeSignature, Volatility, WindowUDF, WindowUDFImpl, PartitionEvaluator};
+
+use super::ohlcv::{OHLC, OhlcvArgs};
+use super::utils::{check_arg_count, float64_arg, float64_struct_array, float64_struct_type, value_at};
+
+const HEIKIN_ASHI_FIELDS: [&str; 4] = ["open", "high", "low", "close"];
+
+/// Heikin-Ashi candles, recursive on the prior HA open and close
+#[derive(Debug)]
+pub struct HeikinAshi {
+    name: String,
+    signature: Signature,
+}
+
+impl HeikinAshi {
+    pub fn new() -> Self {
+        Self {
+            name: "heikin_ashi".to_string(),
+            signature: Signature::one_of(
+                vec![TypeSignature::Exact(vec![
+                    DataType::Float64,
+                    DataType::Float64,
+                    DataType::Float64,
+                    DataType::Float64,
+                ])],
+                Volatility::Immutable,
+            ),
+        }
+    }
+}
+
+impl WindowUDFImpl for HeikinAshi {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn signature(&self) -> &Signature {
+        &self.signature
+    }
+
+    fn return_type(&self, _arg_types: &[DataType]) -> Result<DataType> {
+        Ok(float64_struct_type(&HEIKIN_ASHI_FIELDS))
+    }
+
+    fn partition_evaluator(&self) -> Result<Box<dyn PartitionEvaluator>> {
+        Ok(Box::new(HeikinAshiPartitionEvaluator::new()))
+    }
+}
+
+#[derive(Debug)]
+struct HeikinAshiPartitionEvaluator {
+    /// Previous Heikin-Ashi (open, close)
+    prev_ha: Option<(f64, f64)>,
+}
+
+impl HeikinAshiPartitionEvaluator {
+    fn new() -> Self {
+        Self { prev_ha: None }
+    }
+}
+
+impl PartitionEvaluator for HeikinAshiPartitionEvaluator {
+    fn evaluate_all(
+        &mut self,
+        values: &[ArrayRef],
+        num_rows: usize,
+    ) -> Result<ArrayRef> {
+        check_arg_count(values, 4, "Heikin-Ashi function (open, high, low, close)")?;
+
+        let open_array = float64_arg(values, 0, "Open")?;
+        let high_array = float64_arg(values, 1, "High")?;
+        let low_array = float64_arg(values, 2, "Low")?;
+        let close_array = float64_arg(values, 3, "Close")?;
+
+        let mut columns = vec![Vec::with_capacity(num_rows); HEIKIN_ASHI_FIELDS.len()];
+        self.prev_ha = None;
+
+        for i in 0..num_rows {
+            let (Some(open), Some(high), Some(low), Some(close)) = (
+                value_at(open_array, i),
+                value_at(high_array, i),
+                value_at(low_array, i),
+                value_at(close_array, i),
+            ) else {
+                columns.iter_mut().for_each(|column| column.push(None));
+                continue;
+            };
+
+            // HA close = (O + H + L + C) / 4
+            // HA open = (prev HA open + prev HA close) / 2, seeded with (O + C) / 2
+            let ha_close = (open + high + low + close) / 4.0;
+            let ha_open = match self.prev_ha {
+                Some((prev_open, prev_close)) => (prev_open + prev_close) / 2.0,
+                None => (open + close) / 2.0,
+            };
+            let ha_high = high.max(ha_open).max(ha_close);
+            let ha_low = low.min(ha_open).min(ha_close);
+            self.prev_ha = Some((ha_open, ha_close));
+
+            for (column, value) in columns.iter_mut().zip([ha_open, ha_high, ha_low, ha_close]) {
+                column.push(Some(value));
+            }
+        }
+
+        Ok(float64_struct_array(&HEIKIN_ASHI_FIELDS, columns))
+    }
+
+    fn uses_window_frame(&self) -> bool {
+        false
+    }
+
+    fn include_rank(&self) -> bool {
+        false
+    }
+}
+
+pub fn register_heikin_ashi(ctx: &SessionContext) -> Result<()> {
+    let heikin_ashi_udf = WindowUDF::from(OhlcvArgs::new(HeikinAshi::new(), OHLC));
+    ctx.register_udwf(heikin_ashi_udf);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use datafusion::execution::context::SessionContext;
+
+    #[tokio::test]
+    async fn test_heikin_ashi() -> Result<()> {
+        let ctx = SessionContext::new();
+        register_heikin_ashi(&ctx)?;
+
+        // Test Heikin-Ashi transform using SQL
+        let result = ctx
+            .sql("SELECT open, high, low, close,
+                    heikin_ashi(open, high, low, close) OVER () AS ha
+                FROM (VALUES
+                (100.0, 102.0, 99.0, 101.0), (101.0, 103.5, 100.5, 103.0),
+                (103.0, 104.0, 101.0, 101.5), (101.5, 102.5, 98.5, 99.0)
+            ) AS t(open, high, low, close)")
+            .await?
+            .collect()
+            .await?;
+
+        println!("Heikin-Ashi Test Results:");
+        datafusion::arrow::util::pretty::print_batches(&result)?;
+
+        Ok(())
+    }
+}
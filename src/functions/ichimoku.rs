@@ -0,0 +1,270 @@
+use std::any::Any;
+
+use datafusion::arrow::array::ArrayRef;
+use datafusion::arrow::datatypes::DataType;
+use datafusion::error::{DataFusionError, Result};
+use datafusion::execution::context::SessionContext;
+use datafusion::logical_expr::function::WindowFunctionSimplification;
+use datafusion::logical_expr::{Signature, TypeSignature, Volatility, WindowUDF, WindowUDFImpl, PartitionEvaluator};
+
+use super::ohlcv::{HLC, OhlcvArgs};
+use super::utils::{float64_arg, float64_struct_array, float64_struct_type, period_arg, value_at};
+use crate::validation::{period_simplification, PeriodArg};
+
+const ICHIMOKU_FIELDS: [&str; 5] = ["tenkan", "kijun", "senkou_a", "senkou_b", "chikou"];
+
+const DEFAULT_TENKAN_PERIOD: usize = 9;
+const DEFAULT_KIJUN_PERIOD: usize = 26;
+const DEFAULT_SENKOU_B_PERIOD: usize = 52;
+
+/// Ichimoku Kinko Hyo cloud components
+///
+/// The senkou spans are displaced forward and the chikou span backward by the
+/// kijun period, so each row reports the values plotted at that bar.
+#[derive(Debug)]
+pub struct Ichimoku {
+    name: String,
+    signature: Signature,
+}
+
+impl Ichimoku {
+    pub fn new() -> Self {
+        Self {
+            name: "ichimoku".to_string(),
+            signature: Signature::one_of(
+                vec![
+                    TypeSignature::Exact(vec![DataType::Float64, DataType::Float64, DataType::Float64]),
+                    TypeSignature::Exact(vec![
+                        DataType::Float64,
+                        DataType::Float64,
+                        DataType::Float64,
+                        DataType::Int64,
+                        DataType::Int64,
+                        DataType::Int64,
+                    ]),
+                ],
+                Volatility::Immutable,
+            ),
+        }
+    }
+}
+
+impl WindowUDFImpl for Ichimoku {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn signature(&self) -> &Signature {
+        &self.signature
+    }
+
+    fn return_type(&self, _arg_types: &[DataType]) -> Result<DataType> {
+        Ok(float64_struct_type(&ICHIMOKU_FIELDS))
+    }
+
+    fn partition_evaluator(&self) -> Result<Box<dyn PartitionEvaluator>> {
+        Ok(Box::new(IchimokuPartitionEvaluator::new()))
+    }
+
+    fn simplify(&self) -> Option<WindowFunctionSimplification> {
+        period_simplification(
+            &self.name,
+            vec![
+                PeriodArg::rows(3, "tenkan_period"),
+                PeriodArg::rows(4, "kijun_period"),
+                PeriodArg::rows(5, "senkou_b_period"),
+            ],
+        )
+    }
+}
+
+#[derive(Debug)]
+struct IchimokuPartitionEvaluator {
+    highs: Vec<f64>,
+    lows: Vec<f64>,
+}
+
+impl IchimokuPartitionEvaluator {
+    fn new() -> Self {
+        Self {
+            highs: Vec::new(),
+            lows: Vec::new(),
+        }
+    }
+
+    /// Midpoint of the highest high and lowest low over the last `period` bars
+    fn midpoint(&self, period: usize) -> Option<f64> {
+        if self.highs.len() < period {
+            return None;
+        }
+        let start_idx = self.highs.len() - period;
+        let highest = self.highs[start_idx..].iter().cloned().fold(f64::MIN, f64::max);
+        let lowest = self.lows[start_idx..].iter().cloned().fold(f64::MAX, f64::min);
+        Some((highest + lowest) / 2.0)
+    }
+}
+
+impl PartitionEvaluator for IchimokuPartitionEvaluator {
+    fn evaluate_all(
+        &mut self,
+        values: &[ArrayRef],
+        num_rows: usize,
+    ) -> Result<ArrayRef> {
+        if values.len() != 3 && values.len() != 6 {
+            return Err(DataFusionError::Execution(
+                "Ichimoku function requires 3 or 6 arguments: high, low, close and optional tenkan, kijun, senkou_b periods".to_string(),
+            ));
+        }
+
+        let high_array = float64_arg(values, 0, "High")?;
+        let low_array = float64_arg(values, 1, "Low")?;
+        let close_array = float64_arg(values, 2, "Close")?;
+        let (tenkan_period, kijun_period, senkou_b_period) = if values.len() == 6 {
+            (
+                period_arg(values, 3, "Tenkan period")?,
+                period_arg(values, 4, "Kijun period")?,
+                period_arg(values, 5, "Senkou B period")?,
+            )
+        } else {
+            (DEFAULT_TENKAN_PERIOD, DEFAULT_KIJUN_PERIOD, DEFAULT_SENKOU_B_PERIOD)
+        };
+        let displacement = kijun_period;
+
+        let mut tenkan = Vec::with_capacity(num_rows);
+        let mut kijun = Vec::with_capacity(num_rows);
+        let mut raw_senkou_a = Vec::with_capacity(num_rows);
+        let mut raw_senkou_b = Vec::with_capacity(num_rows);
+        self.highs.clear();
+        self.lows.clear();
+
+        for i in 0..num_rows {
+            let (Some(high), Some(low)) = (value_at(high_array, i), value_at(low_array, i)) else {
+                tenkan.push(None);
+                kijun.push(None);
+                raw_senkou_a.push(None);
+                raw_senkou_b.push(None);
+                continue;
+            };
+
+            self.highs.push(high);
+            self.lows.push(low);
+
+            let tenkan_value = self.midpoint(tenkan_period);
+            let kijun_value = self.midpoint(kijun_period);
+
+            tenkan.push(tenkan_value);
+            kijun.push(kijun_value);
+            raw_senkou_a.push(match (tenkan_value, kijun_value) {
+                (Some(t), Some(k)) => Some((t + k) / 2.0),
+                _ => None,
+            });
+            raw_senkou_b.push(self.midpoint(senkou_b_period));
+        }
+
+        // Senkou spans are plotted `displacement` bars ahead, chikou `displacement` bars behind
+        let senkou_a = (0..num_rows)
+            .map(|i| i.checked_sub(displacement).and_then(|src| raw_senkou_a[src]))
+            .collect::<Vec<_>>();
+        let senkou_b = (0..num_rows)
+            .map(|i| i.checked_sub(displacement).and_then(|src| raw_senkou_b[src]))
+            .collect::<Vec<_>>();
+        let chikou = (0..num_rows)
+            .map(|i| {
+                let src = i + displacement;
+                if src < num_rows {
+                    value_at(close_array, src)
+                } else {
+                    None
+                }
+            })
+            .collect::<Vec<_>>();
+
+        Ok(float64_struct_array(
+            &ICHIMOKU_FIELDS,
+            vec![tenkan, kijun, senkou_a, senkou_b, chikou],
+        ))
+    }
+
+    fn uses_window_frame(&self) -> bool {
+        false
+    }
+
+    fn include_rank(&self) -> bool {
+        false
+    }
+}
+
+pub fn register_ichimoku(ctx: &SessionContext) -> Result<()> {
+    let ichimoku_udf = WindowUDF::from(OhlcvArgs::new(Ichimoku::new(), HLC));
+    ctx.register_udwf(ichimoku_udf);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::functions::utils::{assert_close, float64_column};
+    use datafusion::execution::context::SessionContext;
+
+    #[tokio::test]
+    async fn test_ichimoku() -> Result<()> {
+        let ctx = SessionContext::new();
+        register_ichimoku(&ctx)?;
+
+        // Test Ichimoku with short 2/3/4 periods using SQL
+        let result = ctx
+            .sql("SELECT close, ichimoku(high, low, close, 2, 3, 4) OVER () AS cloud FROM (VALUES
+                (101.0, 99.0, 100.0), (103.0, 100.0, 102.0), (104.0, 101.0, 103.0),
+                (102.0, 98.0, 99.0), (106.0, 99.0, 105.0), (108.0, 104.0, 107.0),
+                (107.0, 103.0, 104.0), (110.0, 105.0, 109.0)
+            ) AS t(high, low, close)")
+            .await?
+            .collect()
+            .await?;
+
+        println!("Ichimoku Test Results:");
+        datafusion::arrow::util::pretty::print_batches(&result)?;
+
+        // Each line is the midpoint of its period's range; the senkou spans
+        // are shifted three (kijun) bars forward and chikou three bars back
+        let result = ctx
+            .sql("SELECT cloud['tenkan'] AS tenkan, cloud['kijun'] AS kijun, cloud['senkou_a'] AS senkou_a,
+                    cloud['senkou_b'] AS senkou_b, cloud['chikou'] AS chikou
+                FROM (SELECT ichimoku(high, low, close, 2, 3, 4) OVER () AS cloud FROM (VALUES
+                    (101.0, 99.0, 100.0), (103.0, 100.0, 102.0), (104.0, 101.0, 103.0),
+                    (102.0, 98.0, 99.0), (106.0, 99.0, 105.0), (108.0, 104.0, 107.0),
+                    (107.0, 103.0, 104.0), (110.0, 105.0, 109.0)
+                ) AS t(high, low, close))")
+            .await?
+            .collect()
+            .await?;
+        assert_close(
+            &float64_column(&result, "tenkan"),
+            &[None, Some(101.0), Some(102.0), Some(101.0), Some(102.0), Some(103.5), Some(105.5), Some(106.5)],
+        );
+        assert_close(
+            &float64_column(&result, "kijun"),
+            &[None, None, Some(101.5), Some(101.0), Some(102.0), Some(103.0), Some(103.5), Some(106.5)],
+        );
+        // (tenkan + kijun) / 2 of row 2, (102 + 101.5) / 2, lands on row 5
+        assert_close(
+            &float64_column(&result, "senkou_a"),
+            &[None, None, None, None, None, Some(101.75), Some(101.0), Some(102.0)],
+        );
+        // The first four-bar midpoint, (104 + 98) / 2 on row 3, lands on row 6
+        assert_close(
+            &float64_column(&result, "senkou_b"),
+            &[None, None, None, None, None, None, Some(101.0), Some(102.0)],
+        );
+        assert_close(
+            &float64_column(&result, "chikou"),
+            &[Some(99.0), Some(105.0), Some(107.0), Some(104.0), Some(109.0), None, None, None],
+        );
+
+        Ok(())
+    }
+}
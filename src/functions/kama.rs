@@ -0,0 +1,203 @@
+use std::any::Any;
+use std::sync::Arc;
+
+use datafusion::arrow::array::{ArrayRef, Float64Array};
+use datafusion::arrow::datatypes::DataType;
+use datafusion::error::Result;
+use datafusion::execution::context::SessionContext;
+use datafusion::logical_expr::function::WindowFunctionSimplification;
+use datafusion::logical_expr::{Signature, TypeSignature, Volatility, WindowUDF, WindowUDFImpl, PartitionEvaluator};
+
+use super::utils::{check_arg_count, float64_arg, period_arg, value_at};
+use crate::validation::{period_simplification, PeriodArg};
+
+/// Kaufman Adaptive Moving Average
+#[derive(Debug)]
+pub struct KaufmanAdaptiveMovingAverage {
+    name: String,
+    signature: Signature,
+}
+
+impl KaufmanAdaptiveMovingAverage {
+    pub fn new() -> Self {
+        Self {
+            name: "kama".to_string(),
+            signature: Signature::one_of(
+                vec![TypeSignature::Exact(vec![
+                    DataType::Float64,
+                    DataType::Int64,
+                    DataType::Int64,
+                    DataType::Int64,
+                ])],
+                Volatility::Immutable,
+            ),
+        }
+    }
+}
+
+impl WindowUDFImpl for KaufmanAdaptiveMovingAverage {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn signature(&self) -> &Signature {
+        &self.signature
+    }
+
+    fn return_type(&self, _arg_types: &[DataType]) -> Result<DataType> {
+        Ok(DataType::Float64)
+    }
+
+    fn partition_evaluator(&self) -> Result<Box<dyn PartitionEvaluator>> {
+        Ok(Box::new(KamaPartitionEvaluator::new()))
+    }
+
+    fn simplify(&self) -> Option<WindowFunctionSimplification> {
+        period_simplification(
+            &self.name,
+            vec![
+                PeriodArg::rows(1, "er_period"),
+                PeriodArg::rows(2, "fast"),
+                PeriodArg::rows(3, "slow"),
+            ],
+        )
+    }
+}
+
+#[derive(Debug)]
+struct KamaPartitionEvaluator {
+    values: Vec<f64>,
+    current_kama: Option<f64>,
+}
+
+impl KamaPartitionEvaluator {
+    fn new() -> Self {
+        Self {
+            values: Vec::new(),
+            current_kama: None,
+        }
+    }
+}
+
+impl PartitionEvaluator for KamaPartitionEvaluator {
+    fn evaluate_all(
+        &mut self,
+        values: &[ArrayRef],
+        num_rows: usize,
+    ) -> Result<ArrayRef> {
+        check_arg_count(values, 4, "KAMA function (value, er_period, fast, slow)")?;
+
+        let value_array = float64_arg(values, 0, "First argument")?;
+        let er_period = period_arg(values, 1, "Efficiency ratio period")?;
+        let fast = period_arg(values, 2, "Fast period")?;
+        let slow = period_arg(values, 3, "Slow period")?;
+
+        let fast_sc = 2.0 / (fast as f64 + 1.0);
+        let slow_sc = 2.0 / (slow as f64 + 1.0);
+
+        let mut result = Vec::with_capacity(num_rows);
+        self.values.clear();
+        self.current_kama = None;
+
+        for i in 0..num_rows {
+            let Some(value) = value_at(value_array, i) else {
+                result.push(None);
+                continue;
+            };
+
+            self.values.push(value);
+            let n = self.values.len();
+
+            if n <= er_period {
+                result.push(None);
+                continue;
+            }
+
+            let prev_kama = match self.current_kama {
+                Some(prev_kama) => prev_kama,
+                None => {
+                    // Seed from the previous bar's price, so the first KAMA moves
+                    // from it by one smoothing step
+                    self.values[n - 2]
+                }
+            };
+
+            // ER = |change over N| / Σ|bar-to-bar change|
+            let window = &self.values[n - er_period - 1..];
+            let change = (value - window[0]).abs();
+            let volatility: f64 = window.windows(2).map(|w| (w[1] - w[0]).abs()).sum();
+            let efficiency_ratio = if volatility > 0.0 { change / volatility } else { 0.0 };
+
+            // SC = (ER × (fast SC - slow SC) + slow SC)²
+            let smoothing = (efficiency_ratio * (fast_sc - slow_sc) + slow_sc).powi(2);
+            let kama = prev_kama + smoothing * (value - prev_kama);
+
+            self.current_kama = Some(kama);
+            result.push(Some(kama));
+        }
+
+        Ok(Arc::new(Float64Array::from(result)))
+    }
+
+    fn uses_window_frame(&self) -> bool {
+        false
+    }
+
+    fn include_rank(&self) -> bool {
+        false
+    }
+}
+
+pub fn register_kama(ctx: &SessionContext) -> Result<()> {
+    let kama_udf = WindowUDF::from(KaufmanAdaptiveMovingAverage::new());
+    ctx.register_udwf(kama_udf);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::functions::utils::{assert_close, float64_column};
+    use datafusion::execution::context::SessionContext;
+
+    #[tokio::test]
+    async fn test_kama() -> Result<()> {
+        let ctx = SessionContext::new();
+        register_kama(&ctx)?;
+
+        // Test KAMA(5, 2, 30) using SQL
+        let result = ctx
+            .sql("SELECT price, kama(price, 5, 2, 30) OVER () AS kama FROM (VALUES
+                (110.46), (109.80), (110.17), (109.82), (110.15), (109.31), (109.05), (107.94),
+                (107.76), (109.24), (109.40), (108.50), (107.96), (108.55), (108.85)
+            ) AS t(price)")
+            .await?
+            .collect()
+            .await?;
+
+        println!("KAMA Test Results:");
+        datafusion::arrow::util::pretty::print_batches(&result)?;
+
+        // Row 6: ER = 1.15 / 2.55, SC = (ER × (2/3 - 2/31) + 2/31)² ≈ 0.11295,
+        // seeded from 110.15 → 110.15 - 0.11295 × 0.84
+        let expected: Vec<Option<f64>> = std::iter::repeat(None)
+            .take(5)
+            .chain(
+                [
+                    110.05512546, 109.97935113, 109.40833053, 108.96166727, 108.97349088,
+                    108.97632419, 108.96546541, 108.96078734, 108.94530552, 108.94290129,
+                ]
+                .iter()
+                .copied()
+                .map(Some),
+            )
+            .collect();
+        assert_close(&float64_column(&result, "kama"), &expected);
+
+        Ok(())
+    }
+}
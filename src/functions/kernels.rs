@@ -0,0 +1,136 @@
+//! Slice-based compute kernels for the recursive indicators
+//!
+//! Each kernel reads a contiguous `&[f64]` of non-null inputs and writes a
+//! plain `&mut [f64]` of outputs, returning how many leading outputs are
+//! still warming up. No `Option` is built per row, the elementwise steps
+//! (price changes, gains, losses) are separate branch-free loops the compiler
+//! can vectorize, and [`map_valid`] turns the result into an Arrow array with
+//! a validity bitmap in one pass.
+
+use datafusion::arrow::array::{Array, Float64Array};
+use datafusion::arrow::buffer::{BooleanBuffer, NullBuffer};
+
+/// Run `kernel` over the non-null values of `input` and scatter its output
+/// back to their rows; null inputs and warm-up outputs become null
+pub(crate) fn map_valid(input: &Float64Array, kernel: impl FnOnce(&[f64], &mut [f64]) -> usize) -> Float64Array {
+    let num_rows = input.len();
+
+    if input.null_count() == 0 {
+        let mut out = vec![0.0; num_rows];
+        let warmup = kernel(input.values(), &mut out);
+        let validity = BooleanBuffer::collect_bool(num_rows, |i| i >= warmup);
+        return Float64Array::new(out.into(), Some(NullBuffer::new(validity)));
+    }
+
+    let valid = input.iter().flatten().collect::<Vec<_>>();
+    let mut compact = vec![0.0; valid.len()];
+    let warmup = kernel(&valid, &mut compact);
+
+    let mut out = vec![0.0; num_rows];
+    let mut validity = vec![false; num_rows];
+    let rows = (0..num_rows).filter(|&i| input.is_valid(i));
+    for (n, row) in rows.enumerate() {
+        out[row] = compact[n];
+        validity[row] = n >= warmup;
+    }
+    Float64Array::new(out.into(), Some(NullBuffer::from(validity)))
+}
+
+/// Exponential moving average seeded with the first value
+pub(crate) fn ema(values: &[f64], alpha: f64, out: &mut [f64]) -> usize {
+    let Some(&first) = values.first() else {
+        return 0;
+    };
+
+    let mut prev = first;
+    out[0] = first;
+    for (o, &v) in out[1..].iter_mut().zip(&values[1..]) {
+        prev = alpha * v + (1.0 - alpha) * prev;
+        *o = prev;
+    }
+    0
+}
+
+/// MACD line: EMA(12) - EMA(26), both seeded with the first value
+pub(crate) fn macd_line(values: &[f64], out: &mut [f64]) -> usize {
+    const ALPHA12: f64 = 2.0 / 13.0;
+    const ALPHA26: f64 = 2.0 / 27.0;
+
+    let Some(&first) = values.first() else {
+        return 0;
+    };
+
+    let (mut ema12, mut ema26) = (first, first);
+    for (o, &v) in out.iter_mut().zip(values) {
+        ema12 = ALPHA12 * v + (1.0 - ALPHA12) * ema12;
+        ema26 = ALPHA26 * v + (1.0 - ALPHA26) * ema26;
+        *o = ema12 - ema26;
+    }
+    0
+}
+
+/// MACD signal line: EMA(9) of the MACD line
+pub(crate) fn macd_signal(macd: &[f64], out: &mut [f64]) -> usize {
+    ema(macd, 2.0 / 10.0, out)
+}
+
+/// Split consecutive changes of `values` into gains and losses (both ≥ 0)
+fn gains_losses(values: &[f64]) -> (Vec<f64>, Vec<f64>) {
+    let changes = values.windows(2).map(|w| w[1] - w[0]);
+    let gains = changes.clone().map(|c| c.max(0.0)).collect();
+    let losses = changes.map(|c| (-c).max(0.0)).collect();
+    (gains, losses)
+}
+
+/// RSI from average (or summed) gains and losses
+pub(crate) fn rsi_value(avg_gain: f64, avg_loss: f64) -> f64 {
+    if avg_loss == 0.0 {
+        return 100.0;
+    }
+    100.0 - 100.0 / (1.0 + avg_gain / avg_loss)
+}
+
+/// RSI with averages seeded by an SMA of the first `period` changes and
+/// smoothed with `alpha` (1 / N for Wilder, 2 / (N + 1) for an EMA)
+pub(crate) fn rsi_smoothed(values: &[f64], period: usize, alpha: f64, out: &mut [f64]) -> usize {
+    if period == 0 || values.len() <= period {
+        return values.len();
+    }
+
+    let (gains, losses) = gains_losses(values);
+    let mut avg_gain = gains[..period].iter().sum::<f64>() / period as f64;
+    let mut avg_loss = losses[..period].iter().sum::<f64>() / period as f64;
+    out[period] = rsi_value(avg_gain, avg_loss);
+
+    for i in period..gains.len() {
+        avg_gain = avg_gain * (1.0 - alpha) + gains[i] * alpha;
+        avg_loss = avg_loss * (1.0 - alpha) + losses[i] * alpha;
+        out[i + 1] = rsi_value(avg_gain, avg_loss);
+    }
+    period
+}
+
+/// Cutler's RSI: plain rolling means of the last `period` gains and losses
+pub(crate) fn rsi_rolling(values: &[f64], period: usize, out: &mut [f64]) -> usize {
+    if period == 0 || values.len() <= period {
+        return values.len();
+    }
+
+    let (gains, losses) = gains_losses(values);
+    let mut gain_sum = gains[..period].iter().sum::<f64>();
+    let mut loss_sum = losses[..period].iter().sum::<f64>();
+    out[period] = rsi_value(gain_sum, loss_sum);
+
+    for i in period..gains.len() {
+        // Recompute the sums once per window so rounding error can't accumulate
+        if (i + 1) % period == 0 {
+            gain_sum = gains[i + 1 - period..=i].iter().sum();
+            loss_sum = losses[i + 1 - period..=i].iter().sum();
+        } else {
+            gain_sum += gains[i] - gains[i - period];
+            loss_sum += losses[i] - losses[i - period];
+        }
+        out[i + 1] = rsi_value(gain_sum, loss_sum);
+    }
+    period
+}
@@ -0,0 +1,213 @@
+use std::any::Any;
+
+use datafusion::arrow::array::ArrayRef;
+use datafusion::arrow::datatypes::DataType;
+use datafusion::error::{DataFusionError, Result};
+use datafusion::execution::context::SessionContext;
+use datafusion::logical_expr::function::WindowFunctionSimplification;
+use datafusion::logical_expr::{Signature, TypeSignature, Volatility, WindowUDF, WindowUDFImpl, PartitionEvaluator};
+
+use super::utils::{float64_arg, float64_struct_array, float64_struct_type, period_arg, rate_of_change, value_at};
+use crate::validation::{period_simplification, PeriodArg};
+
+const KST_FIELDS: [&str; 2] = ["kst", "signal"];
+
+const DEFAULT_ROC_PERIODS: [usize; 4] = [10, 15, 20, 30];
+const DEFAULT_SMA_PERIODS: [usize; 4] = [10, 10, 10, 15];
+const DEFAULT_SIGNAL_PERIOD: usize = 9;
+
+/// Know Sure Thing: weighted sum of four smoothed rates of change, with an SMA signal line
+#[derive(Debug)]
+pub struct KnowSureThing {
+    name: String,
+    signature: Signature,
+}
+
+impl KnowSureThing {
+    pub fn new() -> Self {
+        let mut custom_args = vec![DataType::Float64];
+        custom_args.extend(std::iter::repeat(DataType::Int64).take(9));
+
+        Self {
+            name: "kst".to_string(),
+            signature: Signature::one_of(
+                vec![
+                    TypeSignature::Exact(vec![DataType::Float64]),
+                    TypeSignature::Exact(custom_args),
+                ],
+                Volatility::Immutable,
+            ),
+        }
+    }
+}
+
+impl WindowUDFImpl for KnowSureThing {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn signature(&self) -> &Signature {
+        &self.signature
+    }
+
+    fn return_type(&self, _arg_types: &[DataType]) -> Result<DataType> {
+        Ok(float64_struct_type(&KST_FIELDS))
+    }
+
+    fn partition_evaluator(&self) -> Result<Box<dyn PartitionEvaluator>> {
+        Ok(Box::new(KstPartitionEvaluator::new()))
+    }
+
+    fn simplify(&self) -> Option<WindowFunctionSimplification> {
+        period_simplification(
+            &self.name,
+            vec![
+                PeriodArg::rows(1, "roc1"),
+                PeriodArg::rows(2, "roc2"),
+                PeriodArg::rows(3, "roc3"),
+                PeriodArg::rows(4, "roc4"),
+                PeriodArg::rows(5, "sma1"),
+                PeriodArg::rows(6, "sma2"),
+                PeriodArg::rows(7, "sma3"),
+                PeriodArg::rows(8, "sma4"),
+                PeriodArg::rows(9, "signal"),
+            ],
+        )
+    }
+}
+
+fn simple_average(values: &[f64], period: usize) -> Option<f64> {
+    if values.len() < period {
+        return None;
+    }
+    Some(values[values.len() - period..].iter().sum::<f64>() / period as f64)
+}
+
+#[derive(Debug)]
+struct KstPartitionEvaluator {
+    values: Vec<f64>,
+    rocs: [Vec<f64>; 4],
+    kst_values: Vec<f64>,
+}
+
+impl KstPartitionEvaluator {
+    fn new() -> Self {
+        Self {
+            values: Vec::new(),
+            rocs: Default::default(),
+            kst_values: Vec::new(),
+        }
+    }
+}
+
+impl PartitionEvaluator for KstPartitionEvaluator {
+    fn evaluate_all(
+        &mut self,
+        values: &[ArrayRef],
+        num_rows: usize,
+    ) -> Result<ArrayRef> {
+        if values.len() != 1 && values.len() != 10 {
+            return Err(DataFusionError::Execution(
+                "KST function requires 1 or 10 arguments: value and optional roc1..roc4, sma1..sma4, signal periods".to_string(),
+            ));
+        }
+
+        let value_array = float64_arg(values, 0, "First argument")?;
+        let (roc_periods, sma_periods, signal_period) = if values.len() == 10 {
+            let mut roc_periods = [0; 4];
+            let mut sma_periods = [0; 4];
+            for j in 0..4 {
+                roc_periods[j] = period_arg(values, 1 + j, "ROC period")?;
+                sma_periods[j] = period_arg(values, 5 + j, "ROC smoothing period")?;
+            }
+            (roc_periods, sma_periods, period_arg(values, 9, "Signal period")?)
+        } else {
+            (DEFAULT_ROC_PERIODS, DEFAULT_SMA_PERIODS, DEFAULT_SIGNAL_PERIOD)
+        };
+
+        let mut kst_result = Vec::with_capacity(num_rows);
+        let mut signal_result = Vec::with_capacity(num_rows);
+        *self = Self::new();
+
+        for i in 0..num_rows {
+            let Some(value) = value_at(value_array, i) else {
+                kst_result.push(None);
+                signal_result.push(None);
+                continue;
+            };
+
+            self.values.push(value);
+
+            // KST = Σ weight(j) × SMA(ROC(roc_j), sma_j) with weights 1, 2, 3, 4
+            let mut kst = Some(0.0);
+            for j in 0..4 {
+                if let Some(roc) = rate_of_change(&self.values, roc_periods[j]) {
+                    self.rocs[j].push(roc);
+                }
+                kst = match (kst, simple_average(&self.rocs[j], sma_periods[j])) {
+                    (Some(total), Some(smoothed)) => Some(total + (j + 1) as f64 * smoothed),
+                    _ => None,
+                };
+            }
+
+            match kst {
+                Some(kst) => {
+                    self.kst_values.push(kst);
+                    kst_result.push(Some(kst));
+                    signal_result.push(simple_average(&self.kst_values, signal_period));
+                }
+                None => {
+                    kst_result.push(None);
+                    signal_result.push(None);
+                }
+            }
+        }
+
+        Ok(float64_struct_array(&KST_FIELDS, vec![kst_result, signal_result]))
+    }
+
+    fn uses_window_frame(&self) -> bool {
+        false
+    }
+
+    fn include_rank(&self) -> bool {
+        false
+    }
+}
+
+pub fn register_kst(ctx: &SessionContext) -> Result<()> {
+    let kst_udf = WindowUDF::from(KnowSureThing::new());
+    ctx.register_udwf(kst_udf);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use datafusion::execution::context::SessionContext;
+
+    #[tokio::test]
+    async fn test_kst() -> Result<()> {
+        let ctx = SessionContext::new();
+        register_kst(&ctx)?;
+
+        // Test KST with short custom periods using SQL
+        let result = ctx
+            .sql("SELECT price, kst(price, 1, 2, 3, 4, 2, 2, 2, 2, 2) OVER () AS kst FROM (VALUES
+                (100.0), (102.0), (98.0), (105.0), (107.0), (103.0), (110.0), (108.0),
+                (112.0), (115.0), (113.0), (118.0)
+            ) AS t(price)")
+            .await?
+            .collect()
+            .await?;
+
+        println!("KST Test Results:");
+        datafusion::arrow::util::pretty::print_batches(&result)?;
+
+        Ok(())
+    }
+}
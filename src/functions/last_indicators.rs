@@ -0,0 +1,337 @@
+use std::any::Any;
+use std::sync::Arc;
+
+use datafusion::arrow::array::{Array, ArrayRef, Float64Array};
+use datafusion::arrow::datatypes::{DataType, Field};
+use datafusion::error::{DataFusionError, Result};
+use datafusion::execution::context::SessionContext;
+use datafusion::logical_expr::expr::{AggregateFunction, WindowFunction};
+use datafusion::logical_expr::function::{AccumulatorArgs, AggregateFunctionSimplification, StateFieldsArgs};
+use datafusion::logical_expr::utils::format_state_name;
+use datafusion::logical_expr::{Accumulator, AggregateUDF, AggregateUDFImpl, Expr, Signature, WindowUDF};
+use datafusion::scalar::ScalarValue;
+
+use super::atr::AverageTrueRange;
+use super::bollinger::BollingerBands;
+use super::cci::CommodityChannelIndex;
+use super::ema::ExponentialMovingAverage;
+use super::macd::MacdIndicator;
+use super::rsi::RelativeStrengthIndex;
+use super::sma::SimpleMovingAverage;
+use super::stochastic::StochasticOscillator;
+use super::utils::{float64_arg, OrderedSeries};
+use super::zscore::RollingZScore;
+use crate::registration::Renamed;
+
+/// Aggregate returning a window indicator's final value for each group
+///
+/// `last_rsi(close, 14)` equals the last row of `rsi(close, 14) OVER (...)`
+/// without computing and discarding the earlier rows. Give the series order
+/// inside the call, e.g. `last_rsi(close, 14 ORDER BY window_start)`; the
+/// keys are carried through partial aggregation, so the result holds across
+/// partitions. Without `ORDER BY` rows are taken in arrival order, which is
+/// arbitrary once the input has more than one partition. Null inputs are
+/// passed to the window function like any other row.
+#[derive(Debug)]
+pub struct LastIndicator {
+    name: String,
+    window: WindowUDF,
+    inputs: usize,
+}
+
+impl LastIndicator {
+    /// Wrap `window`, whose first `inputs` arguments are Float64 series and
+    /// whose remaining arguments are constant parameters
+    pub fn new(window: WindowUDF, inputs: usize) -> Self {
+        Self {
+            name: format!("last_{}", window.name()),
+            window,
+            inputs,
+        }
+    }
+
+    pub fn sma() -> Self {
+        Self::new(WindowUDF::from(SimpleMovingAverage::new()), 1)
+    }
+
+    pub fn ema() -> Self {
+        Self::new(WindowUDF::from(ExponentialMovingAverage::new()), 1)
+    }
+
+    pub fn rsi() -> Self {
+        Self::new(WindowUDF::from(RelativeStrengthIndex::new()), 1)
+    }
+
+    pub fn macd() -> Self {
+        Self::new(WindowUDF::from(MacdIndicator::new()), 1)
+    }
+
+    pub fn bollinger() -> Self {
+        Self::new(WindowUDF::from(BollingerBands::new()), 1)
+    }
+
+    pub fn zscore() -> Self {
+        Self::new(WindowUDF::from(RollingZScore::new()), 1)
+    }
+
+    pub fn atr() -> Self {
+        Self::new(WindowUDF::from(AverageTrueRange::new()), 3)
+    }
+
+    pub fn cci() -> Self {
+        Self::new(WindowUDF::from(CommodityChannelIndex::new()), 3)
+    }
+
+    pub fn stochastic() -> Self {
+        Self::new(WindowUDF::from(StochasticOscillator::new()), 3)
+    }
+}
+
+impl AggregateUDFImpl for LastIndicator {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn signature(&self) -> &Signature {
+        self.window.signature()
+    }
+
+    fn coerce_types(&self, arg_types: &[DataType]) -> Result<Vec<DataType>> {
+        self.window.inner().coerce_types(arg_types)
+    }
+
+    fn return_type(&self, arg_types: &[DataType]) -> Result<DataType> {
+        self.window.return_type(arg_types)
+    }
+
+    fn accumulator(&self, acc_args: AccumulatorArgs) -> Result<Box<dyn Accumulator>> {
+        let param_types = acc_args.exprs[self.inputs..]
+            .iter()
+            .map(|expr| expr.data_type(acc_args.schema))
+            .collect::<Result<Vec<_>>>()?;
+        Ok(Box::new(LastIndicatorAccumulator::new(
+            self.window.clone(),
+            OrderedSeries::try_new(self.inputs, &acc_args)?,
+            param_types,
+        )?))
+    }
+
+    fn state_fields(&self, args: StateFieldsArgs) -> Result<Vec<Field>> {
+        let inputs = (0..self.inputs).map(|i| format!("input_{}", i)).collect::<Vec<_>>();
+        let mut fields = OrderedSeries::state_fields(args.name, &inputs);
+        fields.extend(
+            args.input_types[self.inputs..]
+                .iter()
+                .enumerate()
+                .map(|(i, data_type)| Field::new(format_state_name(args.name, &format!("param_{}", i)), data_type.clone(), true)),
+        );
+        Ok(fields)
+    }
+
+    /// Check the periods as the window function would, under this
+    /// aggregate's name
+    fn simplify(&self) -> Option<AggregateFunctionSimplification> {
+        let check = self.window.simplify()?;
+        let window = Arc::clone(self.window.inner());
+        Some(Box::new(move |aggregate: AggregateFunction, info| {
+            let named = WindowUDF::from(Renamed::named(aggregate.func.name(), Arc::clone(&window)));
+            check(WindowFunction::new(Arc::new(named), aggregate.args.clone()), info)?;
+            Ok(Expr::AggregateFunction(aggregate))
+        }))
+    }
+}
+
+#[derive(Debug)]
+struct LastIndicatorAccumulator {
+    window: WindowUDF,
+    inputs: usize,
+    rows: OrderedSeries,
+    params: Vec<ScalarValue>,
+}
+
+impl LastIndicatorAccumulator {
+    fn new(window: WindowUDF, rows: OrderedSeries, param_types: Vec<DataType>) -> Result<Self> {
+        Ok(Self {
+            window,
+            inputs: rows.state_len() - 1,
+            rows,
+            params: param_types
+                .iter()
+                .map(ScalarValue::try_from)
+                .collect::<Result<Vec<_>>>()?,
+        })
+    }
+
+    /// Keep the first non-null value seen for each constant parameter
+    fn update_params(&mut self, values: &[ArrayRef]) -> Result<()> {
+        for (param, array) in self.params.iter_mut().zip(values) {
+            if param.is_null() {
+                if let Some(i) = (0..array.len()).find(|&i| array.is_valid(i)) {
+                    *param = ScalarValue::try_from_array(array, i)?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Accumulator for LastIndicatorAccumulator {
+    fn update_batch(&mut self, values: &[ArrayRef]) -> Result<()> {
+        let args = self.inputs + self.params.len();
+        if values.len() < args {
+            return Err(DataFusionError::Execution(format!(
+                "{} requires {} arguments",
+                self.window.name(),
+                args
+            )));
+        }
+
+        // Null inputs are kept, as the window function sees them, and any
+        // ORDER BY columns follow the arguments
+        let arrays = (0..self.inputs)
+            .map(|i| float64_arg(values, i, "Input"))
+            .collect::<Result<Vec<_>>>()?;
+        self.rows.update(&arrays, &values[args..])?;
+
+        self.update_params(&values[self.inputs..args])
+    }
+
+    fn evaluate(&mut self) -> Result<ScalarValue> {
+        let series = self.rows.sorted();
+        let num_rows = series[0].len();
+        let return_type = self.window.return_type(
+            &std::iter::repeat(DataType::Float64)
+                .take(self.inputs)
+                .chain(self.params.iter().map(|param| param.data_type()))
+                .collect::<Vec<_>>(),
+        )?;
+        if num_rows == 0 {
+            return ScalarValue::try_from(&return_type);
+        }
+
+        let mut args = series
+            .into_iter()
+            .map(|series| Arc::new(Float64Array::from(series)) as ArrayRef)
+            .collect::<Vec<_>>();
+        for param in &self.params {
+            args.push(param.to_array_of_size(num_rows)?);
+        }
+
+        let result = self
+            .window
+            .partition_evaluator_factory()?
+            .evaluate_all(&args, num_rows)?;
+        ScalarValue::try_from_array(&result, num_rows - 1)
+    }
+
+    fn size(&self) -> usize {
+        std::mem::size_of_val(self) + self.rows.heap_size() + self.params.iter().map(ScalarValue::size).sum::<usize>()
+    }
+
+    fn state(&mut self) -> Result<Vec<ScalarValue>> {
+        let mut state = self.rows.state();
+        state.extend(self.params.iter().cloned());
+        Ok(state)
+    }
+
+    fn merge_batch(&mut self, states: &[ArrayRef]) -> Result<()> {
+        self.rows.merge(states)?;
+        self.update_params(&states[self.rows.state_len()..])
+    }
+}
+
+/// Register `last_sma`, `last_ema`, `last_rsi`, `last_macd`, `last_bollinger`,
+/// `last_zscore`, `last_atr`, `last_cci` and `last_stochastic`
+pub fn register_last_indicators(ctx: &SessionContext) -> Result<()> {
+    ctx.register_udaf(AggregateUDF::from(LastIndicator::sma()));
+    ctx.register_udaf(AggregateUDF::from(LastIndicator::ema()));
+    ctx.register_udaf(AggregateUDF::from(LastIndicator::rsi()));
+    ctx.register_udaf(AggregateUDF::from(LastIndicator::macd()));
+    ctx.register_udaf(AggregateUDF::from(LastIndicator::bollinger()));
+    ctx.register_udaf(AggregateUDF::from(LastIndicator::zscore()));
+    ctx.register_udaf(AggregateUDF::from(LastIndicator::atr()));
+    ctx.register_udaf(AggregateUDF::from(LastIndicator::cci()));
+    ctx.register_udaf(AggregateUDF::from(LastIndicator::stochastic()));
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use datafusion::arrow::array::{AsArray, Int64Array, RecordBatch};
+    use datafusion::arrow::datatypes::{Float64Type, Schema};
+    use datafusion::datasource::MemTable;
+    use datafusion::execution::context::{SessionConfig, SessionContext};
+
+    #[tokio::test]
+    async fn test_last_indicators() -> Result<()> {
+        let ctx = SessionContext::new();
+        register_last_indicators(&ctx)?;
+
+        // Test one final indicator value per ticker using SQL
+        let result = ctx
+            .sql("SELECT ticker,
+                    last_sma(close, 3 ORDER BY day) AS sma_3,
+                    last_rsi(close, 3 ORDER BY day) AS rsi_3,
+                    last_atr(close + 1.0, close - 1.0, close, 3 ORDER BY day) AS atr_3
+                FROM (VALUES
+                ('AAPL', 1, 100.0), ('AAPL', 2, 101.5), ('AAPL', 3, 99.8), ('AAPL', 4, 102.2), ('AAPL', 5, 103.0),
+                ('MSFT', 1, 300.0), ('MSFT', 2, 303.1), ('MSFT', 3, 301.4), ('MSFT', 4, 305.0), ('MSFT', 5, 304.2)
+            ) AS t(ticker, day, close)
+            GROUP BY ticker
+            ORDER BY ticker")
+            .await?
+            .collect()
+            .await?;
+
+        println!("Last Indicator Test Results:");
+        datafusion::arrow::util::pretty::print_batches(&result)?;
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_last_indicators_across_partitions() -> Result<()> {
+        let ctx = SessionContext::new_with_config(SessionConfig::new().with_target_partitions(4));
+        register_last_indicators(&ctx)?;
+
+        // The later days sit in the first partition, so arrival order disagrees with ORDER BY
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("day", DataType::Int64, false),
+            Field::new("close", DataType::Float64, false),
+        ]));
+        let batch = |days: Vec<i64>, closes: Vec<f64>| {
+            RecordBatch::try_new(
+                schema.clone(),
+                vec![Arc::new(Int64Array::from(days)), Arc::new(Float64Array::from(closes))],
+            )
+        };
+        let table = MemTable::try_new(
+            schema.clone(),
+            vec![
+                vec![batch(vec![3, 4], vec![30.0, 40.0])?],
+                vec![batch(vec![1, 2], vec![10.0, 20.0])?],
+            ],
+        )?;
+        ctx.register_table("prices", Arc::new(table))?;
+
+        let result = ctx
+            .sql("SELECT last_sma(close, 2 ORDER BY day) AS asc_sma, last_sma(close, 2 ORDER BY day DESC) AS desc_sma
+                FROM prices")
+            .await?
+            .collect()
+            .await?;
+
+        println!("Last Indicator Partition Test Results:");
+        datafusion::arrow::util::pretty::print_batches(&result)?;
+
+        assert_eq!(result[0].column(0).as_primitive::<Float64Type>().value(0), 35.0);
+        assert_eq!(result[0].column(1).as_primitive::<Float64Type>().value(0), 15.0);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,289 @@
+use std::any::Any;
+use std::sync::Arc;
+
+use datafusion::arrow::array::{ArrayRef, Float64Array};
+use datafusion::arrow::datatypes::DataType;
+use datafusion::error::Result;
+use datafusion::execution::context::SessionContext;
+use datafusion::logical_expr::function::WindowFunctionSimplification;
+use datafusion::logical_expr::{Signature, TypeSignature, Volatility, WindowUDF, WindowUDFImpl, PartitionEvaluator};
+
+use super::utils::{check_arg_count, float64_arg, float64_struct_array, float64_struct_type, period_arg, value_at, RollingMoments};
+use crate::validation::{period_simplification, PeriodArg};
+
+const LINREG_FIELDS: [&str; 3] = ["slope", "intercept", "r2"];
+
+/// Which part of the least-squares fit a function returns
+#[derive(Debug, Clone, Copy)]
+enum LinregOutput {
+    All,
+    Slope,
+    Intercept,
+    RSquared,
+}
+
+/// Rolling least-squares line fitted against bar index
+///
+/// The oldest bar in the window is x = 0 and the current bar is x = N - 1.
+#[derive(Debug)]
+pub struct LinearRegression {
+    name: String,
+    signature: Signature,
+    output: LinregOutput,
+}
+
+impl LinearRegression {
+    /// `linreg(value, period)` returning `{slope, intercept, r2}`
+    pub fn new() -> Self {
+        Self::with_output("linreg", LinregOutput::All)
+    }
+
+    /// `linreg_slope(value, period)`
+    pub fn slope() -> Self {
+        Self::with_output("linreg_slope", LinregOutput::Slope)
+    }
+
+    /// `linreg_intercept(value, period)`
+    pub fn intercept() -> Self {
+        Self::with_output("linreg_intercept", LinregOutput::Intercept)
+    }
+
+    /// `linreg_r2(value, period)`
+    pub fn r_squared() -> Self {
+        Self::with_output("linreg_r2", LinregOutput::RSquared)
+    }
+
+    fn with_output(name: &str, output: LinregOutput) -> Self {
+        Self {
+            name: name.to_string(),
+            signature: Signature::one_of(
+                vec![TypeSignature::Exact(vec![DataType::Float64, DataType::Int64])],
+                Volatility::Immutable,
+            ),
+            output,
+        }
+    }
+}
+
+impl WindowUDFImpl for LinearRegression {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn signature(&self) -> &Signature {
+        &self.signature
+    }
+
+    fn return_type(&self, _arg_types: &[DataType]) -> Result<DataType> {
+        match self.output {
+            LinregOutput::All => Ok(float64_struct_type(&LINREG_FIELDS)),
+            _ => Ok(DataType::Float64),
+        }
+    }
+
+    fn partition_evaluator(&self) -> Result<Box<dyn PartitionEvaluator>> {
+        Ok(Box::new(LinregPartitionEvaluator::new(self.output)))
+    }
+
+    fn simplify(&self) -> Option<WindowFunctionSimplification> {
+        period_simplification(&self.name, vec![PeriodArg::rows(1, "period")])
+    }
+}
+
+/// Trailing window regressed against 0..N, with the sums the fit needs kept
+/// up to date in O(1) per row
+#[derive(Debug, Default)]
+struct RollingFit {
+    window: RollingMoments,
+    /// Σ i × yᵢ, with i counted from the oldest value in the window
+    weighted_sum: f64,
+    evictions: usize,
+}
+
+impl RollingFit {
+    fn reset(&mut self, period: usize) {
+        *self = Self {
+            window: RollingMoments::new(period),
+            ..Self::default()
+        };
+    }
+
+    fn push(&mut self, value: f64) {
+        let len = self.window.len();
+        let sum = self.window.mean() * len as f64;
+        match self.window.push(value) {
+            None => self.weighted_sum += len as f64 * value,
+            Some(evicted) => {
+                // Every remaining value moves down one index as the oldest leaves
+                self.weighted_sum += (len - 1) as f64 * value - (sum - evicted);
+
+                // Recompute periodically, as RollingSum does, to bound drift
+                self.evictions += 1;
+                if self.evictions >= len {
+                    self.weighted_sum = self.window.values().enumerate().map(|(i, y)| i as f64 * y).sum();
+                    self.evictions = 0;
+                }
+            }
+        }
+    }
+
+    fn is_full(&self) -> bool {
+        self.window.is_full()
+    }
+
+    /// Least-squares slope, intercept and R² of the window
+    fn fit(&self) -> Option<(f64, f64, f64)> {
+        if self.window.len() < 2 {
+            return None;
+        }
+        let n = self.window.len() as f64;
+        let mean_x = (n - 1.0) / 2.0;
+        let mean_y = self.window.mean();
+
+        // Sxy = Σ i × yᵢ - N × x̄ × ȳ, Sxx = N × (N² - 1) / 12 for x = 0..N
+        let sxy = self.weighted_sum - n * mean_x * mean_y;
+        let sxx = n * (n * n - 1.0) / 12.0;
+        let syy = self.window.sum_squares();
+
+        // slope = Sxy / Sxx, intercept = ȳ - slope × x̄, R² = Sxy² / (Sxx × Syy)
+        let slope = sxy / sxx;
+        let intercept = mean_y - slope * mean_x;
+        let r_squared = if syy > 0.0 { (sxy * sxy) / (sxx * syy) } else { 1.0 };
+        Some((slope, intercept, r_squared))
+    }
+}
+
+#[derive(Debug)]
+struct LinregPartitionEvaluator {
+    output: LinregOutput,
+    window: RollingFit,
+}
+
+impl LinregPartitionEvaluator {
+    fn new(output: LinregOutput) -> Self {
+        Self {
+            output,
+            window: RollingFit::default(),
+        }
+    }
+}
+
+impl PartitionEvaluator for LinregPartitionEvaluator {
+    fn evaluate_all(
+        &mut self,
+        values: &[ArrayRef],
+        num_rows: usize,
+    ) -> Result<ArrayRef> {
+        check_arg_count(values, 2, "Linear regression function (value, period)")?;
+
+        let value_array = float64_arg(values, 0, "First argument")?;
+        let period = period_arg(values, 1, "Period")?;
+
+        let mut slopes = Vec::with_capacity(num_rows);
+        let mut intercepts = Vec::with_capacity(num_rows);
+        let mut r_squared = Vec::with_capacity(num_rows);
+        self.window.reset(period);
+
+        for i in 0..num_rows {
+            let fitted = value_at(value_array, i).and_then(|value| {
+                self.window.push(value);
+                if !self.window.is_full() {
+                    return None;
+                }
+                self.window.fit()
+            });
+
+            slopes.push(fitted.map(|f| f.0));
+            intercepts.push(fitted.map(|f| f.1));
+            r_squared.push(fitted.map(|f| f.2));
+        }
+
+        Ok(match self.output {
+            LinregOutput::All => float64_struct_array(&LINREG_FIELDS, vec![slopes, intercepts, r_squared]),
+            LinregOutput::Slope => Arc::new(Float64Array::from(slopes)),
+            LinregOutput::Intercept => Arc::new(Float64Array::from(intercepts)),
+            LinregOutput::RSquared => Arc::new(Float64Array::from(r_squared)),
+        })
+    }
+
+    fn uses_window_frame(&self) -> bool {
+        false
+    }
+
+    fn include_rank(&self) -> bool {
+        false
+    }
+}
+
+/// Register `linreg`, `linreg_slope`, `linreg_intercept` and `linreg_r2`
+pub fn register_linreg(ctx: &SessionContext) -> Result<()> {
+    ctx.register_udwf(WindowUDF::from(LinearRegression::new()));
+    ctx.register_udwf(WindowUDF::from(LinearRegression::slope()));
+    ctx.register_udwf(WindowUDF::from(LinearRegression::intercept()));
+    ctx.register_udwf(WindowUDF::from(LinearRegression::r_squared()));
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use datafusion::arrow::array::AsArray;
+    use datafusion::arrow::datatypes::Float64Type;
+    use datafusion::execution::context::SessionContext;
+
+    #[tokio::test]
+    async fn test_linreg() -> Result<()> {
+        let ctx = SessionContext::new();
+        register_linreg(&ctx)?;
+
+        // Test rolling regression with a 4 period window using SQL
+        let result = ctx
+            .sql("SELECT price,
+                    linreg(price, 4) OVER () AS fit,
+                    linreg_slope(price, 4) OVER () AS slope,
+                    linreg_r2(price, 4) OVER () AS r2
+                FROM (VALUES
+                (100.0), (101.0), (103.0), (102.5), (105.0), (106.5), (106.0), (108.0)
+            ) AS t(price)")
+            .await?
+            .collect()
+            .await?;
+
+        println!("Linear Regression Test Results:");
+        datafusion::arrow::util::pretty::print_batches(&result)?;
+
+        // The running sums should match a direct fit of each window frame
+        let result = ctx
+            .sql("SELECT linreg_slope(p, 5) OVER (ORDER BY i) AS slope,
+                    regr_slope(p, x) OVER (ORDER BY i ROWS BETWEEN 4 PRECEDING AND CURRENT ROW) AS framed_slope,
+                    linreg_r2(p, 5) OVER (ORDER BY i) AS r2,
+                    regr_r2(p, x) OVER (ORDER BY i ROWS BETWEEN 4 PRECEDING AND CURRENT ROW) AS framed_r2
+                FROM (SELECT i, CAST(i AS DOUBLE) AS x, 100.0 + 0.1 * i + 3.0 * sin(CAST(i AS DOUBLE)) AS p
+                      FROM (SELECT unnest(range(0, 200)) AS i))
+                ORDER BY i")
+            .await?
+            .collect()
+            .await?;
+
+        let mut compared = 0;
+        for batch in &result {
+            let column = |i: usize| batch.column(i).as_primitive::<Float64Type>();
+            let fits = column(0).iter().zip(column(1).iter()).zip(column(2).iter().zip(column(3).iter()));
+            for ((slope, framed_slope), (r2, framed_r2)) in fits {
+                let (Some(slope), Some(r2)) = (slope, r2) else {
+                    continue;
+                };
+                assert!((slope - framed_slope.unwrap()).abs() < 1e-9, "{} vs {:?}", slope, framed_slope);
+                assert!((r2 - framed_r2.unwrap()).abs() < 1e-9, "{} vs {:?}", r2, framed_r2);
+                compared += 1;
+            }
+        }
+        assert_eq!(compared, 196);
+
+        Ok(())
+    }
+}
@@ -1,26 +1,50 @@
 use std::any::Any;
+use std::ops::Range;
 use std::sync::Arc;
 
-use datafusion::arrow::array::{ArrayRef, Float64Array};
-use datafusion::arrow::datatypes::DataType;
+use datafusion::arrow::array::{ArrayRef, Float64Array, StructArray};
+use datafusion::arrow::compute::kernels::numeric::sub;
+use datafusion::arrow::datatypes::{DataType, Field};
 use datafusion::error::{DataFusionError, Result};
 use datafusion::execution::context::SessionContext;
-use datafusion::logical_expr::{Signature, TypeSignature, Volatility, WindowUDF, WindowUDFImpl, PartitionEvaluator};
+use datafusion::logical_expr::{Signature, Volatility, WindowUDF, WindowUDFImpl, PartitionEvaluator};
+use datafusion::scalar::ScalarValue;
+
+use super::kernels::{self, map_valid};
+use super::utils::{coerce_numeric_args, float64_struct_array, float64_struct_type, value_at};
+
+const MACD_FIELDS: [&str; 3] = ["macd", "signal", "histogram"];
+
+/// Which MACD series a function returns
+#[derive(Debug, Clone, Copy)]
+enum MacdOutput {
+    Line,
+    Full,
+}
 
 #[derive(Debug)]
 pub struct MacdIndicator {
     name: String,
     signature: Signature,
+    output: MacdOutput,
 }
 
 impl MacdIndicator {
+    /// `macd(value)` returning the MACD line only
     pub fn new() -> Self {
+        Self::with_output("macd", MacdOutput::Line)
+    }
+
+    /// `macd_full(value)` returning `{macd, signal, histogram}`
+    pub fn full() -> Self {
+        Self::with_output("macd_full", MacdOutput::Full)
+    }
+
+    fn with_output(name: &str, output: MacdOutput) -> Self {
         Self {
-            name: "macd".to_string(),
-            signature: Signature::one_of(
-                vec![TypeSignature::Exact(vec![DataType::Float64])],
-                Volatility::Immutable,
-            ),
+            name: name.to_string(),
+            signature: Signature::user_defined(Volatility::Immutable),
+            output,
         }
     }
 }
@@ -38,52 +62,78 @@ impl WindowUDFImpl for MacdIndicator {
         &self.signature
     }
 
+    fn coerce_types(&self, arg_types: &[DataType]) -> Result<Vec<DataType>> {
+        coerce_numeric_args(&self.name, arg_types, &[vec![DataType::Float64]])
+    }
+
     fn return_type(&self, _arg_types: &[DataType]) -> Result<DataType> {
-        Ok(DataType::Float64)
+        match self.output {
+            MacdOutput::Line => Ok(DataType::Float64),
+            MacdOutput::Full => Ok(float64_struct_type(&MACD_FIELDS)),
+        }
     }
 
     fn partition_evaluator(&self) -> Result<Box<dyn PartitionEvaluator>> {
-        Ok(Box::new(MacdPartitionEvaluator::new()))
+        Ok(Box::new(MacdPartitionEvaluator::new(self.output)))
     }
 }
 
 #[derive(Debug)]
 struct MacdPartitionEvaluator {
-    ema12: Option<f64>,
-    ema26: Option<f64>,
-    alpha12: f64,
-    alpha26: f64,
+    output: MacdOutput,
+    // EMA12, EMA26 and signal EMA9 for row-at-a-time evaluation
+    emas: Option<(f64, f64, f64)>,
 }
 
 impl MacdPartitionEvaluator {
-    fn new() -> Self {
-        Self {
-            ema12: None,
-            ema26: None,
-            alpha12: 2.0 / 13.0, // 2 / (12 + 1)
-            alpha26: 2.0 / 27.0, // 2 / (26 + 1)
-        }
+    fn new(output: MacdOutput) -> Self {
+        Self { output, emas: None }
     }
 
-    fn update_ema(&mut self, value: f64) -> Option<f64> {
-        // Update EMA12
-        self.ema12 = match self.ema12 {
-            None => Some(value),
-            Some(prev_ema) => Some(self.alpha12 * value + (1.0 - self.alpha12) * prev_ema),
+    /// Add the next value and return the MACD line and signal
+    fn push(&mut self, value: f64) -> (f64, f64) {
+        let (ema12, ema26, signal) = match self.emas {
+            Some((ema12, ema26, signal)) => {
+                let (alpha12, alpha26, alpha9) = (2.0 / 13.0, 2.0 / 27.0, 2.0 / 10.0);
+                let ema12 = alpha12 * value + (1.0 - alpha12) * ema12;
+                let ema26 = alpha26 * value + (1.0 - alpha26) * ema26;
+                (ema12, ema26, alpha9 * (ema12 - ema26) + (1.0 - alpha9) * signal)
+            }
+            // Both EMAs start at the first value, so the line and signal start at zero
+            None => (value, value, 0.0),
         };
+        self.emas = Some((ema12, ema26, signal));
+        (ema12 - ema26, signal)
+    }
 
-        // Update EMA26
-        self.ema26 = match self.ema26 {
-            None => Some(value),
-            Some(prev_ema) => Some(self.alpha26 * value + (1.0 - self.alpha26) * prev_ema),
-        };
+    fn evaluate_full(&mut self, value_array: &Float64Array) -> Result<ArrayRef> {
+        // MACD = EMA12 - EMA26, Signal = EMA9 of MACD, Histogram = MACD - Signal
+        let macd_line = map_valid(value_array, kernels::macd_line);
+        let signal_line = map_valid(&macd_line, kernels::macd_signal);
+        let histogram = sub(&macd_line, &signal_line)?;
+
+        let columns = MACD_FIELDS
+            .iter()
+            .zip([Arc::new(macd_line) as ArrayRef, Arc::new(signal_line), histogram])
+            .map(|(name, column)| (Arc::new(Field::new(*name, DataType::Float64, true)), column))
+            .collect::<Vec<_>>();
+        Ok(Arc::new(StructArray::from(columns)))
+    }
+}
 
-        // Calculate MACD (EMA12 - EMA26)
-        match (self.ema12, self.ema26) {
-            (Some(ema12), Some(ema26)) => Some(ema12 - ema26),
-            _ => None,
-        }
+fn value_array(values: &[ArrayRef]) -> Result<&Float64Array> {
+    if values.len() != 1 {
+        return Err(DataFusionError::Execution(
+            "MACD function requires exactly 1 argument: value".to_string(),
+        ));
     }
+
+    values[0]
+        .as_any()
+        .downcast_ref::<Float64Array>()
+        .ok_or_else(|| {
+            DataFusionError::Execution("Argument must be Float64".to_string())
+        })
 }
 
 impl PartitionEvaluator for MacdPartitionEvaluator {
@@ -92,31 +142,42 @@ impl PartitionEvaluator for MacdPartitionEvaluator {
         values: &[ArrayRef],
         num_rows: usize,
     ) -> Result<ArrayRef> {
-        if values.len() != 1 {
-            return Err(DataFusionError::Execution(
-                "MACD function requires exactly 1 argument: value".to_string(),
-            ));
+        let value_array = value_array(values)?;
+
+        if let MacdOutput::Full = self.output {
+            return self.evaluate_full(value_array);
         }
 
-        let value_array = values[0]
-            .as_any()
-            .downcast_ref::<Float64Array>()
-            .ok_or_else(|| {
-                DataFusionError::Execution("Argument must be Float64".to_string())
-            })?;
-
-        let mut result = Vec::with_capacity(num_rows);
-
-        for i in 0..num_rows {
-            if let Some(value) = value_array.value(i).into() {
-                let macd = self.update_ema(value);
-                result.push(macd);
-            } else {
-                result.push(None);
+        let result = map_valid(value_array, kernels::macd_line);
+        debug_assert_eq!(result.len(), num_rows);
+
+        Ok(Arc::new(result))
+    }
+
+    /// Streaming evaluation: only the three running EMAs are kept between rows
+    fn evaluate(&mut self, values: &[ArrayRef], range: &Range<usize>) -> Result<ScalarValue> {
+        let value = value_at(value_array(values)?, range.start);
+        let (macd, signal) = match value.map(|value| self.push(value)) {
+            Some((macd, signal)) => (Some(macd), Some(signal)),
+            None => (None, None),
+        };
+
+        match self.output {
+            MacdOutput::Line => Ok(ScalarValue::Float64(macd)),
+            MacdOutput::Full => {
+                let histogram = macd.zip(signal).map(|(macd, signal)| macd - signal);
+                let row = float64_struct_array(&MACD_FIELDS, vec![vec![macd], vec![signal], vec![histogram]]);
+                ScalarValue::try_from_array(&row, 0)
             }
         }
+    }
+
+    fn supports_bounded_execution(&self) -> bool {
+        true
+    }
 
-        Ok(Arc::new(Float64Array::from(result)))
+    fn is_causal(&self) -> bool {
+        true
     }
 
     fn uses_window_frame(&self) -> bool {
@@ -128,9 +189,11 @@ impl PartitionEvaluator for MacdPartitionEvaluator {
     }
 }
 
+/// Register `macd` and `macd_full`
 pub fn register_macd(ctx: &SessionContext) -> Result<()> {
     let macd_udf = WindowUDF::from(MacdIndicator::new());
     ctx.register_udwf(macd_udf);
+    ctx.register_udwf(WindowUDF::from(MacdIndicator::full()));
     Ok(())
 }
 
@@ -159,6 +222,19 @@ mod tests {
         println!("MACD Test Results:");
         datafusion::arrow::util::pretty::print_batches(&result)?;
 
+        // Test MACD with signal line and histogram using SQL
+        let result = ctx
+            .sql("SELECT price, macd_full(price) OVER () AS macd FROM (VALUES 
+                (100.0), (102.0), (98.0), (105.0), (107.0), (103.0), (110.0), (108.0),
+                (112.0), (115.0), (113.0), (118.0), (120.0), (116.0), (122.0), (119.0)
+            ) AS t(price)")
+            .await?
+            .collect()
+            .await?;
+
+        println!("MACD Full Test Results:");
+        datafusion::arrow::util::pretty::print_batches(&result)?;
+
         Ok(())
     }
 }
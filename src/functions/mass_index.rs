@@ -0,0 +1,163 @@
+use std::any::Any;
+use std::sync::Arc;
+
+use datafusion::arrow::array::{ArrayRef, Float64Array};
+use datafusion::arrow::datatypes::DataType;
+use datafusion::error::Result;
+use datafusion::execution::context::SessionContext;
+use datafusion::logical_expr::function::WindowFunctionSimplification;
+use datafusion::logical_expr::{Signature, TypeSignature, Volatility, WindowUDF, WindowUDFImpl, PartitionEvaluator};
+
+use super::ohlcv::{HL, OhlcvArgs};
+use super::utils::{check_arg_count, float64_arg, period_arg, value_at, RollingSum};
+use crate::validation::{period_simplification, PeriodArg};
+
+/// EMA period applied to the high-low range
+const RANGE_EMA_PERIOD: f64 = 9.0;
+
+/// Mass Index: sum of the single/double EMA ratio of the high-low range
+#[derive(Debug)]
+pub struct MassIndex {
+    name: String,
+    signature: Signature,
+}
+
+impl MassIndex {
+    pub fn new() -> Self {
+        Self {
+            name: "mass_index".to_string(),
+            signature: Signature::one_of(
+                vec![TypeSignature::Exact(vec![DataType::Float64, DataType::Float64, DataType::Int64])],
+                Volatility::Immutable,
+            ),
+        }
+    }
+}
+
+impl WindowUDFImpl for MassIndex {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn signature(&self) -> &Signature {
+        &self.signature
+    }
+
+    fn return_type(&self, _arg_types: &[DataType]) -> Result<DataType> {
+        Ok(DataType::Float64)
+    }
+
+    fn partition_evaluator(&self) -> Result<Box<dyn PartitionEvaluator>> {
+        Ok(Box::new(MassIndexPartitionEvaluator::new()))
+    }
+
+    fn simplify(&self) -> Option<WindowFunctionSimplification> {
+        period_simplification(&self.name, vec![PeriodArg::rows(2, "period")])
+    }
+}
+
+#[derive(Debug)]
+struct MassIndexPartitionEvaluator {
+    single_ema: Option<f64>,
+    double_ema: Option<f64>,
+    ratios: RollingSum,
+}
+
+impl MassIndexPartitionEvaluator {
+    fn new() -> Self {
+        Self {
+            single_ema: None,
+            double_ema: None,
+            ratios: RollingSum::default(),
+        }
+    }
+}
+
+impl PartitionEvaluator for MassIndexPartitionEvaluator {
+    fn evaluate_all(
+        &mut self,
+        values: &[ArrayRef],
+        num_rows: usize,
+    ) -> Result<ArrayRef> {
+        check_arg_count(values, 3, "Mass index function (high, low, period)")?;
+
+        let high_array = float64_arg(values, 0, "High")?;
+        let low_array = float64_arg(values, 1, "Low")?;
+        let period = period_arg(values, 2, "Period")?;
+        let alpha = 2.0 / (RANGE_EMA_PERIOD + 1.0);
+
+        let mut result = Vec::with_capacity(num_rows);
+        *self = Self::new();
+        self.ratios.reset(period);
+
+        for i in 0..num_rows {
+            let (Some(high), Some(low)) = (value_at(high_array, i), value_at(low_array, i)) else {
+                result.push(None);
+                continue;
+            };
+
+            let range = high - low;
+            let single = match self.single_ema {
+                None => range,
+                Some(prev) => alpha * range + (1.0 - alpha) * prev,
+            };
+            let double = match self.double_ema {
+                None => single,
+                Some(prev) => alpha * single + (1.0 - alpha) * prev,
+            };
+            self.single_ema = Some(single);
+            self.double_ema = Some(double);
+
+            // Mass Index = Σ EMA(range) / EMA(EMA(range)) over the period
+            let ratio = if double != 0.0 { single / double } else { 1.0 };
+            result.push(self.ratios.push(ratio));
+        }
+
+        Ok(Arc::new(Float64Array::from(result)))
+    }
+
+    fn uses_window_frame(&self) -> bool {
+        false
+    }
+
+    fn include_rank(&self) -> bool {
+        false
+    }
+}
+
+pub fn register_mass_index(ctx: &SessionContext) -> Result<()> {
+    let mass_index_udf = WindowUDF::from(OhlcvArgs::new(MassIndex::new(), HL));
+    ctx.register_udwf(mass_index_udf);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use datafusion::execution::context::SessionContext;
+
+    #[tokio::test]
+    async fn test_mass_index() -> Result<()> {
+        let ctx = SessionContext::new();
+        register_mass_index(&ctx)?;
+
+        // Test mass index with a 5 period sum using SQL
+        let result = ctx
+            .sql("SELECT high, low, mass_index(high, low, 5) OVER () AS mass_5 FROM (VALUES
+                (101.0, 99.0), (103.0, 100.0), (104.0, 101.0), (102.0, 98.0),
+                (106.0, 99.0), (108.0, 104.0), (107.0, 103.0), (110.0, 105.0)
+            ) AS t(high, low)")
+            .await?
+            .collect()
+            .await?;
+
+        println!("Mass Index Test Results:");
+        datafusion::arrow::util::pretty::print_batches(&result)?;
+
+        Ok(())
+    }
+}
@@ -2,3 +2,50 @@ pub mod sma;
 pub mod ema;
 pub mod rsi;
 pub mod macd;
+pub mod bollinger;
+pub mod atr;
+pub mod stochastic;
+pub mod vwap;
+pub mod obv;
+pub mod adx;
+pub mod cci;
+pub mod trix;
+pub mod ichimoku;
+pub mod psar;
+pub mod donchian;
+pub mod kama;
+pub mod chaikin;
+pub mod adl;
+pub mod force_index;
+pub mod rvi;
+pub mod coppock;
+pub mod kst;
+pub mod mass_index;
+pub mod ulcer;
+pub mod rolling_stats;
+pub mod volatility;
+pub mod zscore;
+pub mod returns;
+pub mod cum_return;
+pub mod drawdown;
+pub mod risk_ratios;
+pub mod correlation;
+pub mod beta;
+pub mod linreg;
+pub mod anchored_vwap;
+pub mod twap;
+pub mod pivot_points;
+pub mod price_transforms;
+pub mod heikin_ashi;
+pub mod gaps;
+pub mod fractal;
+pub mod volume_profile;
+pub mod ewma_vol;
+pub mod black_scholes;
+pub mod frames;
+pub mod last_indicators;
+pub mod ohlcv;
+pub(crate) mod kernels;
+pub mod sessions;
+
+pub(crate) mod utils;
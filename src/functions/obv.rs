@@ -0,0 +1,172 @@
+use std::any::Any;
+use std::ops::Range;
+use std::sync::Arc;
+
+use datafusion::arrow::array::{ArrayRef, Float64Array};
+use datafusion::arrow::datatypes::DataType;
+use datafusion::error::Result;
+use datafusion::execution::context::SessionContext;
+use datafusion::logical_expr::{Signature, TypeSignature, Volatility, WindowUDF, WindowUDFImpl, PartitionEvaluator};
+use datafusion::scalar::ScalarValue;
+
+use super::ohlcv::{CV, OhlcvArgs};
+use super::utils::{check_arg_count, float64_arg, value_at};
+
+/// On-Balance Volume: running total of volume signed by the close-to-close direction
+#[derive(Debug)]
+pub struct OnBalanceVolume {
+    name: String,
+    signature: Signature,
+}
+
+impl OnBalanceVolume {
+    pub fn new() -> Self {
+        Self {
+            name: "obv".to_string(),
+            signature: Signature::one_of(
+                vec![TypeSignature::Exact(vec![DataType::Float64, DataType::Float64])],
+                Volatility::Immutable,
+            ),
+        }
+    }
+}
+
+impl WindowUDFImpl for OnBalanceVolume {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn signature(&self) -> &Signature {
+        &self.signature
+    }
+
+    fn return_type(&self, _arg_types: &[DataType]) -> Result<DataType> {
+        Ok(DataType::Float64)
+    }
+
+    fn partition_evaluator(&self) -> Result<Box<dyn PartitionEvaluator>> {
+        Ok(Box::new(ObvPartitionEvaluator::new()))
+    }
+}
+
+#[derive(Debug)]
+struct ObvPartitionEvaluator {
+    prev_close: Option<f64>,
+    current_obv: f64,
+}
+
+impl ObvPartitionEvaluator {
+    fn new() -> Self {
+        Self {
+            prev_close: None,
+            current_obv: 0.0,
+        }
+    }
+
+    /// Add the next bar and return the running OBV
+    fn push(&mut self, close: f64, volume: f64) -> f64 {
+        // The first bar has no direction, so OBV starts at zero
+        if let Some(prev_close) = self.prev_close {
+            if close > prev_close {
+                self.current_obv += volume;
+            } else if close < prev_close {
+                self.current_obv -= volume;
+            }
+        }
+        self.prev_close = Some(close);
+        self.current_obv
+    }
+}
+
+impl PartitionEvaluator for ObvPartitionEvaluator {
+    fn evaluate_all(
+        &mut self,
+        values: &[ArrayRef],
+        num_rows: usize,
+    ) -> Result<ArrayRef> {
+        check_arg_count(values, 2, "OBV function (close, volume)")?;
+
+        let close_array = float64_arg(values, 0, "Close")?;
+        let volume_array = float64_arg(values, 1, "Volume")?;
+
+        let mut result = Vec::with_capacity(num_rows);
+        self.prev_close = None;
+        self.current_obv = 0.0;
+
+        for i in 0..num_rows {
+            let obv = match (value_at(close_array, i), value_at(volume_array, i)) {
+                (Some(close), Some(volume)) => Some(self.push(close, volume)),
+                _ => None,
+            };
+            result.push(obv);
+        }
+
+        Ok(Arc::new(Float64Array::from(result)))
+    }
+
+    /// Streaming evaluation: only the previous close and running total are kept
+    fn evaluate(&mut self, values: &[ArrayRef], range: &Range<usize>) -> Result<ScalarValue> {
+        check_arg_count(values, 2, "OBV function (close, volume)")?;
+
+        let close = value_at(float64_arg(values, 0, "Close")?, range.start);
+        let volume = value_at(float64_arg(values, 1, "Volume")?, range.start);
+        let obv = match (close, volume) {
+            (Some(close), Some(volume)) => Some(self.push(close, volume)),
+            _ => None,
+        };
+        Ok(ScalarValue::Float64(obv))
+    }
+
+    fn supports_bounded_execution(&self) -> bool {
+        true
+    }
+
+    fn is_causal(&self) -> bool {
+        true
+    }
+
+    fn uses_window_frame(&self) -> bool {
+        false
+    }
+
+    fn include_rank(&self) -> bool {
+        false
+    }
+}
+
+pub fn register_obv(ctx: &SessionContext) -> Result<()> {
+    let obv_udf = WindowUDF::from(OhlcvArgs::new(OnBalanceVolume::new(), CV));
+    ctx.register_udwf(obv_udf);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use datafusion::execution::context::SessionContext;
+
+    #[tokio::test]
+    async fn test_obv() -> Result<()> {
+        let ctx = SessionContext::new();
+        register_obv(&ctx)?;
+
+        // Test OBV using SQL
+        let result = ctx
+            .sql("SELECT close, volume, obv(close, volume) OVER () AS obv FROM (VALUES
+                (53.26, 8200.0), (53.30, 8100.0), (53.32, 8300.0), (53.72, 8900.0),
+                (54.19, 9200.0), (53.92, 13300.0), (54.65, 10300.0), (54.60, 9900.0)
+            ) AS t(close, volume)")
+            .await?
+            .collect()
+            .await?;
+
+        println!("OBV Test Results:");
+        datafusion::arrow::util::pretty::print_batches(&result)?;
+
+        Ok(())
+    }
+}
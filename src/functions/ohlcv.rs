@@ -0,0 +1,245 @@
+//! Struct argument support for the multi-input indicators
+//!
+//! Indicators that take several price columns also accept a single struct
+//! column in their place, with the columns looked up by field name
+//! (case-insensitively):
+//!
+//! ```sql
+//! SELECT atr(named_struct('high', high, 'low', low, 'close', close), 14) OVER (...)
+//! -- or, with an `ohlcv` struct column already in the table
+//! SELECT atr(ohlcv, 14) OVER (...)
+//! ```
+//!
+//! Extra struct fields are ignored, so one `ohlcv` column serves every
+//! indicator.
+
+use std::any::Any;
+use std::ops::Range;
+use std::sync::Arc;
+
+use datafusion::arrow::array::{Array, ArrayRef, AsArray, StructArray};
+use datafusion::arrow::compute::{cast, is_null, nullif};
+use datafusion::arrow::datatypes::{DataType, Fields};
+use datafusion::error::{DataFusionError, Result};
+use datafusion::logical_expr::function::WindowFunctionSimplification;
+use datafusion::logical_expr::{PartitionEvaluator, Signature, Volatility, WindowUDFImpl};
+use datafusion::scalar::ScalarValue;
+
+use super::utils::coerce_window_args;
+
+pub const HL: &[&str] = &["high", "low"];
+pub const HLC: &[&str] = &["high", "low", "close"];
+pub const OHLC: &[&str] = &["open", "high", "low", "close"];
+pub const HLCV: &[&str] = &["high", "low", "close", "volume"];
+pub const OC: &[&str] = &["open", "close"];
+pub const CV: &[&str] = &["close", "volume"];
+
+/// Wraps a window function so its leading price columns can be passed as one struct
+#[derive(Debug)]
+pub struct OhlcvArgs {
+    inner: Arc<dyn WindowUDFImpl>,
+    fields: &'static [&'static str],
+    signature: Signature,
+}
+
+impl OhlcvArgs {
+    /// `fields` names the struct fields standing in for the leading positional arguments
+    pub fn new(inner: impl WindowUDFImpl + 'static, fields: &'static [&'static str]) -> Self {
+        Self {
+            inner: Arc::new(inner),
+            fields,
+            signature: Signature::user_defined(Volatility::Immutable),
+        }
+    }
+
+    /// Positional argument types equivalent to `arg_types`
+    fn expand_types(&self, arg_types: &[DataType]) -> Vec<DataType> {
+        match arg_types.first() {
+            Some(DataType::Struct(_)) => {
+                let mut expanded = vec![DataType::Float64; self.fields.len()];
+                expanded.extend_from_slice(&arg_types[1..]);
+                expanded
+            }
+            _ => arg_types.to_vec(),
+        }
+    }
+}
+
+/// Index of the field called `name`, ignoring case
+fn field_index(fields: &Fields, name: &str) -> Option<usize> {
+    fields.iter().position(|field| field.name().eq_ignore_ascii_case(name))
+}
+
+impl WindowUDFImpl for OhlcvArgs {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn name(&self) -> &str {
+        self.inner.name()
+    }
+
+    fn signature(&self) -> &Signature {
+        &self.signature
+    }
+
+    fn coerce_types(&self, arg_types: &[DataType]) -> Result<Vec<DataType>> {
+        let Some(DataType::Struct(struct_fields)) = arg_types.first() else {
+            return coerce_window_args(self.inner.as_ref(), arg_types);
+        };
+
+        let missing = self
+            .fields
+            .iter()
+            .filter(|name| {
+                field_index(struct_fields, name)
+                    .map(|i| !struct_fields[i].data_type().is_numeric())
+                    .unwrap_or(true)
+            })
+            .copied()
+            .collect::<Vec<_>>();
+        if !missing.is_empty() {
+            return Err(DataFusionError::Plan(format!(
+                "{} struct argument needs numeric fields {}; missing or non-numeric: {}",
+                self.name(),
+                self.fields.join(", "),
+                missing.join(", ")
+            )));
+        }
+
+        let mut coerced = coerce_window_args(self.inner.as_ref(), &self.expand_types(arg_types))?;
+        coerced.splice(..self.fields.len(), [arg_types[0].clone()]);
+        Ok(coerced)
+    }
+
+    fn return_type(&self, arg_types: &[DataType]) -> Result<DataType> {
+        self.inner.return_type(&self.expand_types(arg_types))
+    }
+
+    fn partition_evaluator(&self) -> Result<Box<dyn PartitionEvaluator>> {
+        Ok(Box::new(OhlcvArgsPartitionEvaluator {
+            inner: self.inner.partition_evaluator()?,
+            fields: self.fields,
+        }))
+    }
+
+    /// The inner function's period checks recognise a leading struct
+    fn simplify(&self) -> Option<WindowFunctionSimplification> {
+        self.inner.simplify()
+    }
+}
+
+#[derive(Debug)]
+struct OhlcvArgsPartitionEvaluator {
+    inner: Box<dyn PartitionEvaluator>,
+    fields: &'static [&'static str],
+}
+
+impl OhlcvArgsPartitionEvaluator {
+    /// Replace a leading struct argument with its fields as Float64 columns
+    fn expand(&self, values: &[ArrayRef]) -> Result<Vec<ArrayRef>> {
+        let Some(ohlcv) = values.first().and_then(|array| array.as_struct_opt()) else {
+            return Ok(values.to_vec());
+        };
+
+        let mut expanded = self
+            .fields
+            .iter()
+            .map(|name| struct_column(ohlcv, name))
+            .collect::<Result<Vec<_>>>()?;
+        expanded.extend_from_slice(&values[1..]);
+        Ok(expanded)
+    }
+}
+
+/// Field `name` of `ohlcv` as Float64, null wherever the struct itself is null
+fn struct_column(ohlcv: &StructArray, name: &str) -> Result<ArrayRef> {
+    let index = field_index(ohlcv.fields(), name).ok_or_else(|| {
+        DataFusionError::Execution(format!("Struct argument has no '{}' field", name))
+    })?;
+
+    let column = cast(ohlcv.column(index), &DataType::Float64)?;
+    if ohlcv.null_count() == 0 {
+        return Ok(column);
+    }
+    Ok(nullif(&column, &is_null(ohlcv)?)?)
+}
+
+impl PartitionEvaluator for OhlcvArgsPartitionEvaluator {
+    fn evaluate_all(
+        &mut self,
+        values: &[ArrayRef],
+        num_rows: usize,
+    ) -> Result<ArrayRef> {
+        let expanded = self.expand(values)?;
+        self.inner.evaluate_all(&expanded, num_rows)
+    }
+
+    fn evaluate(&mut self, values: &[ArrayRef], range: &Range<usize>) -> Result<ScalarValue> {
+        // Expand only the current rows rather than the whole buffered partition
+        let rows = values
+            .iter()
+            .map(|array| array.slice(range.start, range.len()))
+            .collect::<Vec<_>>();
+        let expanded = self.expand(&rows)?;
+        self.inner.evaluate(&expanded, &(0..range.len()))
+    }
+
+    fn supports_bounded_execution(&self) -> bool {
+        self.inner.supports_bounded_execution()
+    }
+
+    fn is_causal(&self) -> bool {
+        self.inner.is_causal()
+    }
+
+    fn uses_window_frame(&self) -> bool {
+        false
+    }
+
+    fn include_rank(&self) -> bool {
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use datafusion::execution::context::SessionContext;
+
+    #[tokio::test]
+    async fn test_ohlcv_struct_args() -> Result<()> {
+        let ctx = SessionContext::new();
+        crate::register_financial_functions(&ctx)?;
+
+        // Test struct and positional calls side by side using SQL
+        let result = ctx
+            .sql("SELECT close,
+                    atr(high, low, close, 3) OVER () AS atr_positional,
+                    atr(ohlcv, 3) OVER () AS atr_struct,
+                    stochastic(ohlcv, 3, 2) OVER () AS stoch_struct,
+                    cmf(ohlcv, 3) OVER () AS cmf_struct
+                FROM (
+                    SELECT *, named_struct('Open', open, 'High', high, 'Low', low, 'Close', close, 'Volume', volume) AS ohlcv
+                    FROM (VALUES
+                        (10.0, 10.5, 9.5, 10.2, 1000.0), (10.2, 10.9, 10.0, 10.8, 1500.0),
+                        (10.8, 11.2, 10.4, 10.5, 1200.0), (10.5, 10.7, 9.9, 10.0, 1800.0),
+                        (10.0, 10.6, 9.8, 10.4, 1100.0), (10.4, 11.0, 10.3, 10.9, 1300.0)
+                    ) AS t(open, high, low, close, volume)
+                )")
+            .await?
+            .collect()
+            .await?;
+
+        println!("OHLCV Struct Argument Test Results:");
+        datafusion::arrow::util::pretty::print_batches(&result)?;
+
+        // A struct lacking a required field is rejected while planning
+        assert!(ctx
+            .sql("SELECT atr(named_struct('high', 2.0, 'low', 1.0), 3) OVER ()")
+            .await
+            .is_err());
+
+        Ok(())
+    }
+}
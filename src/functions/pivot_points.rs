@@ -0,0 +1,156 @@
+use std::any::Any;
+
+use datafusion::arrow::array::ArrayRef;
+use datafusion::arrow::datatypes::DataType;
+use datafusion::error::Result;
+use datafusion::execution::context::SessionContext;
+use datafusion::logical_expr::{Signature, TypeSignature, Volatility, WindowUDF, WindowUDFImpl, PartitionEvaluator};
+
+use super::ohlcv::{HLC, OhlcvArgs};
+use super::utils::{check_arg_count, float64_arg, float64_struct_array, float64_struct_type, value_at};
+
+const PIVOT_FIELDS: [&str; 7] = ["p", "r1", "r2", "r3", "s1", "s2", "s3"];
+
+/// Classic floor-trader pivot levels computed from the prior bar
+///
+/// Run over daily bars and join back to intraday data for session levels.
+#[derive(Debug)]
+pub struct PivotPoints {
+    name: String,
+    signature: Signature,
+}
+
+impl PivotPoints {
+    pub fn new() -> Self {
+        Self {
+            name: "pivot_points".to_string(),
+            signature: Signature::one_of(
+                vec![TypeSignature::Exact(vec![DataType::Float64, DataType::Float64, DataType::Float64])],
+                Volatility::Immutable,
+            ),
+        }
+    }
+}
+
+impl WindowUDFImpl for PivotPoints {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn signature(&self) -> &Signature {
+        &self.signature
+    }
+
+    fn return_type(&self, _arg_types: &[DataType]) -> Result<DataType> {
+        Ok(float64_struct_type(&PIVOT_FIELDS))
+    }
+
+    fn partition_evaluator(&self) -> Result<Box<dyn PartitionEvaluator>> {
+        Ok(Box::new(PivotPointsPartitionEvaluator::new()))
+    }
+}
+
+/// Pivot, resistance and support levels from one bar's high, low and close
+fn pivot_levels(high: f64, low: f64, close: f64) -> [f64; 7] {
+    let p = (high + low + close) / 3.0;
+    let range = high - low;
+    [
+        p,
+        2.0 * p - low,
+        p + range,
+        high + 2.0 * (p - low),
+        2.0 * p - high,
+        p - range,
+        low - 2.0 * (high - p),
+    ]
+}
+
+#[derive(Debug)]
+struct PivotPointsPartitionEvaluator {
+    prev_bar: Option<(f64, f64, f64)>,
+}
+
+impl PivotPointsPartitionEvaluator {
+    fn new() -> Self {
+        Self { prev_bar: None }
+    }
+}
+
+impl PartitionEvaluator for PivotPointsPartitionEvaluator {
+    fn evaluate_all(
+        &mut self,
+        values: &[ArrayRef],
+        num_rows: usize,
+    ) -> Result<ArrayRef> {
+        check_arg_count(values, 3, "Pivot points function (high, low, close)")?;
+
+        let high_array = float64_arg(values, 0, "High")?;
+        let low_array = float64_arg(values, 1, "Low")?;
+        let close_array = float64_arg(values, 2, "Close")?;
+
+        let mut columns = vec![Vec::with_capacity(num_rows); PIVOT_FIELDS.len()];
+        self.prev_bar = None;
+
+        for i in 0..num_rows {
+            // P = (H + L + C) / 3 of the prior bar; R/S levels are offsets from P
+            let levels = self.prev_bar.map(|(high, low, close)| pivot_levels(high, low, close));
+            for (idx, column) in columns.iter_mut().enumerate() {
+                column.push(levels.map(|l| l[idx]));
+            }
+
+            if let (Some(high), Some(low), Some(close)) =
+                (value_at(high_array, i), value_at(low_array, i), value_at(close_array, i))
+            {
+                self.prev_bar = Some((high, low, close));
+            }
+        }
+
+        Ok(float64_struct_array(&PIVOT_FIELDS, columns))
+    }
+
+    fn uses_window_frame(&self) -> bool {
+        false
+    }
+
+    fn include_rank(&self) -> bool {
+        false
+    }
+}
+
+pub fn register_pivot_points(ctx: &SessionContext) -> Result<()> {
+    let pivot_points_udf = WindowUDF::from(OhlcvArgs::new(PivotPoints::new(), HLC));
+    ctx.register_udwf(pivot_points_udf);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use datafusion::execution::context::SessionContext;
+
+    #[tokio::test]
+    async fn test_pivot_points() -> Result<()> {
+        let ctx = SessionContext::new();
+        register_pivot_points(&ctx)?;
+
+        // Test pivot levels from the prior bar using SQL
+        let result = ctx
+            .sql("SELECT high, low, close,
+                    pivot_points(high, low, close) OVER () AS pivots
+                FROM (VALUES
+                (102.0, 99.0, 101.0), (103.5, 100.5, 103.0), (104.0, 101.0, 101.5), (102.5, 98.5, 99.0)
+            ) AS t(high, low, close)")
+            .await?
+            .collect()
+            .await?;
+
+        println!("Pivot Points Test Results:");
+        datafusion::arrow::util::pretty::print_batches(&result)?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,246 @@
+use std::any::Any;
+use std::sync::Arc;
+
+use datafusion::arrow::array::{ArrayRef, Float64Array};
+use datafusion::arrow::datatypes::DataType;
+use datafusion::error::Result;
+use datafusion::execution::context::SessionContext;
+use datafusion::logical_expr::{
+    ColumnarValue, PartitionEvaluator, ScalarUDF, ScalarUDFImpl, Signature, TypeSignature, Volatility, WindowUDF,
+    WindowUDFImpl,
+};
+
+use super::atr::true_range;
+use super::ohlcv::{HLC, OhlcvArgs};
+use super::utils::{check_arg_count, float64_arg, value_at};
+
+/// Per-bar True Range, using the previous close in the partition
+#[derive(Debug)]
+pub struct TrueRange {
+    name: String,
+    signature: Signature,
+}
+
+impl TrueRange {
+    pub fn new() -> Self {
+        Self {
+            name: "true_range".to_string(),
+            signature: Signature::one_of(
+                vec![TypeSignature::Exact(vec![DataType::Float64, DataType::Float64, DataType::Float64])],
+                Volatility::Immutable,
+            ),
+        }
+    }
+}
+
+impl WindowUDFImpl for TrueRange {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn signature(&self) -> &Signature {
+        &self.signature
+    }
+
+    fn return_type(&self, _arg_types: &[DataType]) -> Result<DataType> {
+        Ok(DataType::Float64)
+    }
+
+    fn partition_evaluator(&self) -> Result<Box<dyn PartitionEvaluator>> {
+        Ok(Box::new(TrueRangePartitionEvaluator::new()))
+    }
+}
+
+#[derive(Debug)]
+struct TrueRangePartitionEvaluator {
+    prev_close: Option<f64>,
+}
+
+impl TrueRangePartitionEvaluator {
+    fn new() -> Self {
+        Self { prev_close: None }
+    }
+}
+
+impl PartitionEvaluator for TrueRangePartitionEvaluator {
+    fn evaluate_all(
+        &mut self,
+        values: &[ArrayRef],
+        num_rows: usize,
+    ) -> Result<ArrayRef> {
+        check_arg_count(values, 3, "True range function (high, low, close)")?;
+
+        let high_array = float64_arg(values, 0, "High")?;
+        let low_array = float64_arg(values, 1, "Low")?;
+        let close_array = float64_arg(values, 2, "Close")?;
+
+        let mut result = Vec::with_capacity(num_rows);
+        self.prev_close = None;
+
+        for i in 0..num_rows {
+            let (Some(high), Some(low), Some(close)) =
+                (value_at(high_array, i), value_at(low_array, i), value_at(close_array, i))
+            else {
+                result.push(None);
+                continue;
+            };
+
+            // TR = max(H - L, |H - prev C|, |L - prev C|); H - L on the first bar
+            result.push(Some(true_range(high, low, self.prev_close)));
+            self.prev_close = Some(close);
+        }
+
+        Ok(Arc::new(Float64Array::from(result)))
+    }
+
+    fn uses_window_frame(&self) -> bool {
+        false
+    }
+
+    fn include_rank(&self) -> bool {
+        false
+    }
+}
+
+/// Which single-bar price a scalar transform computes
+#[derive(Debug, Clone, Copy)]
+enum PriceKind {
+    Typical,
+    Median,
+    WeightedClose,
+}
+
+/// Row-wise price helpers shared by many indicators
+#[derive(Debug)]
+pub struct PriceTransform {
+    name: String,
+    signature: Signature,
+    kind: PriceKind,
+}
+
+impl PriceTransform {
+    /// `typical_price(high, low, close)` = (H + L + C) / 3
+    pub fn typical() -> Self {
+        Self::new("typical_price", PriceKind::Typical, 3)
+    }
+
+    /// `median_price(high, low)` = (H + L) / 2
+    pub fn median() -> Self {
+        Self::new("median_price", PriceKind::Median, 2)
+    }
+
+    /// `weighted_close(high, low, close)` = (H + L + 2C) / 4
+    pub fn weighted_close() -> Self {
+        Self::new("weighted_close", PriceKind::WeightedClose, 3)
+    }
+
+    fn new(name: &str, kind: PriceKind, arg_count: usize) -> Self {
+        Self {
+            name: name.to_string(),
+            signature: Signature::one_of(
+                vec![TypeSignature::Exact(vec![DataType::Float64; arg_count])],
+                Volatility::Immutable,
+            ),
+            kind,
+        }
+    }
+}
+
+impl ScalarUDFImpl for PriceTransform {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn signature(&self) -> &Signature {
+        &self.signature
+    }
+
+    fn return_type(&self, _arg_types: &[DataType]) -> Result<DataType> {
+        Ok(DataType::Float64)
+    }
+
+    fn invoke(&self, args: &[ColumnarValue]) -> Result<ColumnarValue> {
+        let values = ColumnarValue::values_to_arrays(args)?;
+
+        let result: Float64Array = match self.kind {
+            PriceKind::Median => {
+                check_arg_count(&values, 2, "median_price(high, low)")?;
+                let high = float64_arg(&values, 0, "High")?;
+                let low = float64_arg(&values, 1, "Low")?;
+                high.iter()
+                    .zip(low.iter())
+                    .map(|(h, l)| Some((h? + l?) / 2.0))
+                    .collect()
+            }
+            PriceKind::Typical | PriceKind::WeightedClose => {
+                check_arg_count(&values, 3, "Price transform function (high, low, close)")?;
+                let high = float64_arg(&values, 0, "High")?;
+                let low = float64_arg(&values, 1, "Low")?;
+                let close = float64_arg(&values, 2, "Close")?;
+                let weighted = matches!(self.kind, PriceKind::WeightedClose);
+                high.iter()
+                    .zip(low.iter())
+                    .zip(close.iter())
+                    .map(|((h, l), c)| {
+                        let (h, l, c) = (h?, l?, c?);
+                        Some(if weighted {
+                            (h + l + 2.0 * c) / 4.0
+                        } else {
+                            (h + l + c) / 3.0
+                        })
+                    })
+                    .collect()
+            }
+        };
+
+        Ok(ColumnarValue::Array(Arc::new(result)))
+    }
+}
+
+/// Register `true_range`, `typical_price`, `median_price` and `weighted_close`
+pub fn register_price_transforms(ctx: &SessionContext) -> Result<()> {
+    ctx.register_udwf(WindowUDF::from(OhlcvArgs::new(TrueRange::new(), HLC)));
+    ctx.register_udf(ScalarUDF::from(PriceTransform::typical()));
+    ctx.register_udf(ScalarUDF::from(PriceTransform::median()));
+    ctx.register_udf(ScalarUDF::from(PriceTransform::weighted_close()));
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use datafusion::execution::context::SessionContext;
+
+    #[tokio::test]
+    async fn test_price_transforms() -> Result<()> {
+        let ctx = SessionContext::new();
+        register_price_transforms(&ctx)?;
+
+        // Test true range and the scalar price helpers using SQL
+        let result = ctx
+            .sql("SELECT high, low, close,
+                    true_range(high, low, close) OVER () AS tr,
+                    typical_price(high, low, close) AS tp,
+                    median_price(high, low) AS mp,
+                    weighted_close(high, low, close) AS wc
+                FROM (VALUES
+                (102.0, 99.0, 101.0), (103.5, 100.5, 103.0), (104.0, 101.0, 101.5), (102.5, 98.5, 99.0)
+            ) AS t(high, low, close)")
+            .await?
+            .collect()
+            .await?;
+
+        println!("Price Transform Test Results:");
+        datafusion::arrow::util::pretty::print_batches(&result)?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,247 @@
+use std::any::Any;
+use std::sync::Arc;
+
+use datafusion::arrow::array::{ArrayRef, Float64Array};
+use datafusion::arrow::datatypes::DataType;
+use datafusion::error::{DataFusionError, Result};
+use datafusion::execution::context::SessionContext;
+use datafusion::logical_expr::{Signature, TypeSignature, Volatility, WindowUDF, WindowUDFImpl, PartitionEvaluator};
+
+use super::ohlcv::{HL, OhlcvArgs};
+use super::utils::{check_arg_count, f64_param_arg, float64_arg, value_at};
+
+/// Parabolic Stop and Reverse
+#[derive(Debug)]
+pub struct ParabolicSar {
+    name: String,
+    signature: Signature,
+}
+
+impl ParabolicSar {
+    pub fn new() -> Self {
+        Self {
+            name: "psar".to_string(),
+            signature: Signature::one_of(
+                vec![TypeSignature::Exact(vec![
+                    DataType::Float64,
+                    DataType::Float64,
+                    DataType::Float64,
+                    DataType::Float64,
+                ])],
+                Volatility::Immutable,
+            ),
+        }
+    }
+}
+
+impl WindowUDFImpl for ParabolicSar {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn signature(&self) -> &Signature {
+        &self.signature
+    }
+
+    fn return_type(&self, _arg_types: &[DataType]) -> Result<DataType> {
+        Ok(DataType::Float64)
+    }
+
+    fn partition_evaluator(&self) -> Result<Box<dyn PartitionEvaluator>> {
+        Ok(Box::new(PsarPartitionEvaluator::new()))
+    }
+}
+
+#[derive(Debug)]
+struct PsarPartitionEvaluator {
+    bars: Vec<(f64, f64)>,
+    uptrend: bool,
+    sar: f64,
+    extreme_point: f64,
+    acceleration: f64,
+}
+
+impl PsarPartitionEvaluator {
+    fn new() -> Self {
+        Self {
+            bars: Vec::new(),
+            uptrend: true,
+            sar: 0.0,
+            extreme_point: 0.0,
+            acceleration: 0.0,
+        }
+    }
+
+    fn step(&mut self, high: f64, low: f64, step: f64, max_step: f64) -> Option<f64> {
+        self.bars.push((high, low));
+        let n = self.bars.len();
+
+        if n == 1 {
+            return None;
+        }
+
+        if n == 2 {
+            // Seed the trend from the direction of the first two bars
+            let (prev_high, prev_low) = self.bars[0];
+            self.uptrend = high + low >= prev_high + prev_low;
+            self.acceleration = step;
+            if self.uptrend {
+                self.sar = prev_low;
+                self.extreme_point = high.max(prev_high);
+            } else {
+                self.sar = prev_high;
+                self.extreme_point = low.min(prev_low);
+            }
+            return Some(self.sar);
+        }
+
+        let (prev_high, prev_low) = self.bars[n - 2];
+        let (prev2_high, prev2_low) = self.bars[n - 3];
+
+        // SAR = previous SAR + AF × (EP - previous SAR)
+        let mut sar = self.sar + self.acceleration * (self.extreme_point - self.sar);
+
+        if self.uptrend {
+            // SAR may never move into the prior two bars' range
+            sar = sar.min(prev_low).min(prev2_low);
+            if low < sar {
+                self.uptrend = false;
+                sar = self.extreme_point;
+                self.extreme_point = low;
+                self.acceleration = step;
+            } else if high > self.extreme_point {
+                self.extreme_point = high;
+                self.acceleration = (self.acceleration + step).min(max_step);
+            }
+        } else {
+            sar = sar.max(prev_high).max(prev2_high);
+            if high > sar {
+                self.uptrend = true;
+                sar = self.extreme_point;
+                self.extreme_point = high;
+                self.acceleration = step;
+            } else if low < self.extreme_point {
+                self.extreme_point = low;
+                self.acceleration = (self.acceleration + step).min(max_step);
+            }
+        }
+
+        self.sar = sar;
+        Some(sar)
+    }
+}
+
+impl PartitionEvaluator for PsarPartitionEvaluator {
+    fn evaluate_all(
+        &mut self,
+        values: &[ArrayRef],
+        num_rows: usize,
+    ) -> Result<ArrayRef> {
+        check_arg_count(values, 4, "PSAR function (high, low, acceleration, max_acceleration)")?;
+
+        let high_array = float64_arg(values, 0, "High")?;
+        let low_array = float64_arg(values, 1, "Low")?;
+        let step = f64_param_arg(values, 2, "Acceleration")?;
+        let max_step = f64_param_arg(values, 3, "Maximum acceleration")?;
+
+        if step <= 0.0 || max_step < step {
+            return Err(DataFusionError::Execution(
+                "PSAR acceleration must be positive and not exceed the maximum acceleration".to_string(),
+            ));
+        }
+
+        let mut result = Vec::with_capacity(num_rows);
+        *self = Self::new();
+
+        for i in 0..num_rows {
+            match (value_at(high_array, i), value_at(low_array, i)) {
+                (Some(high), Some(low)) => result.push(self.step(high, low, step, max_step)),
+                _ => result.push(None),
+            }
+        }
+
+        Ok(Arc::new(Float64Array::from(result)))
+    }
+
+    fn uses_window_frame(&self) -> bool {
+        false
+    }
+
+    fn include_rank(&self) -> bool {
+        false
+    }
+}
+
+pub fn register_psar(ctx: &SessionContext) -> Result<()> {
+    let psar_udf = WindowUDF::from(OhlcvArgs::new(ParabolicSar::new(), HL));
+    ctx.register_udwf(psar_udf);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::functions::utils::{assert_close, float64_column};
+    use datafusion::execution::context::SessionContext;
+
+    #[tokio::test]
+    async fn test_psar() -> Result<()> {
+        let ctx = SessionContext::new();
+        register_psar(&ctx)?;
+
+        // Test PSAR with the standard 0.02 / 0.2 acceleration using SQL
+        let result = ctx
+            .sql("SELECT high, low, psar(high, low, 0.02, 0.2) OVER () AS psar FROM (VALUES
+                (45.05, 44.40), (45.30, 44.82), (45.60, 45.10), (45.96, 45.30), (46.20, 45.61),
+                (46.03, 45.40), (45.50, 44.90), (44.80, 44.10), (44.30, 43.60), (44.90, 44.00),
+                (45.40, 44.70), (46.10, 45.20)
+            ) AS t(high, low)")
+            .await?
+            .collect()
+            .await?;
+
+        println!("PSAR Test Results:");
+        datafusion::arrow::util::pretty::print_batches(&result)?;
+
+        // The uptrend's SAR trails the lows until the low of 44.10 crosses it;
+        // the SAR then flips to the extreme high of 46.20, and flips back to
+        // the extreme low of 43.60 when the high of 46.10 crosses it
+        assert_close(
+            &float64_column(&result, "psar"),
+            &[
+                None,
+                Some(44.4),
+                Some(44.4),
+                Some(44.448),
+                Some(44.53872),
+                Some(44.6716224),
+                Some(44.793892608),
+                Some(46.2),
+                Some(46.158),
+                Some(46.05568),
+                Some(45.9574528),
+                Some(43.6),
+            ],
+        );
+
+        // The acceleration steps 0.02, 0.04, 0.06 and then holds at the 0.06 cap:
+        // 9.3528 + 0.06 × (14 - 9.3528) = 9.631632
+        let result = ctx
+            .sql("SELECT psar(high, low, 0.02, 0.06) OVER () AS psar FROM (VALUES
+                (10.0, 9.0), (11.0, 10.0), (12.0, 11.0), (13.0, 12.0), (14.0, 13.0), (15.0, 14.0), (16.0, 15.0)
+            ) AS t(high, low)")
+            .await?
+            .collect()
+            .await?;
+        assert_close(
+            &float64_column(&result, "psar"),
+            &[None, Some(9.0), Some(9.0), Some(9.12), Some(9.3528), Some(9.631632), Some(9.95373408)],
+        );
+
+        Ok(())
+    }
+}
@@ -0,0 +1,183 @@
+use std::any::Any;
+use std::sync::Arc;
+
+use datafusion::arrow::array::{ArrayRef, Float64Array};
+use datafusion::arrow::datatypes::DataType;
+use datafusion::error::{DataFusionError, Result};
+use datafusion::execution::context::SessionContext;
+use datafusion::logical_expr::function::WindowFunctionSimplification;
+use datafusion::logical_expr::{Signature, TypeSignature, Volatility, WindowUDF, WindowUDFImpl, PartitionEvaluator};
+
+use super::utils::{float64_arg, period_arg, value_at};
+use crate::validation::{period_simplification, PeriodArg};
+
+/// How a period return is expressed
+#[derive(Debug, Clone, Copy)]
+enum ReturnKind {
+    Simple,
+    Log,
+}
+
+/// Period-over-period returns with an optional lag
+#[derive(Debug)]
+pub struct PeriodReturns {
+    name: String,
+    signature: Signature,
+    kind: ReturnKind,
+}
+
+impl PeriodReturns {
+    /// `returns(price [, lag])`: P_t / P_t-lag - 1
+    pub fn simple() -> Self {
+        Self::new("returns", ReturnKind::Simple)
+    }
+
+    /// `log_returns(price [, lag])`: ln(P_t / P_t-lag)
+    pub fn log() -> Self {
+        Self::new("log_returns", ReturnKind::Log)
+    }
+
+    fn new(name: &str, kind: ReturnKind) -> Self {
+        Self {
+            name: name.to_string(),
+            signature: Signature::one_of(
+                vec![
+                    TypeSignature::Exact(vec![DataType::Float64]),
+                    TypeSignature::Exact(vec![DataType::Float64, DataType::Int64]),
+                ],
+                Volatility::Immutable,
+            ),
+            kind,
+        }
+    }
+}
+
+impl WindowUDFImpl for PeriodReturns {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn signature(&self) -> &Signature {
+        &self.signature
+    }
+
+    fn return_type(&self, _arg_types: &[DataType]) -> Result<DataType> {
+        Ok(DataType::Float64)
+    }
+
+    fn partition_evaluator(&self) -> Result<Box<dyn PartitionEvaluator>> {
+        Ok(Box::new(ReturnsPartitionEvaluator::new(self.kind)))
+    }
+
+    fn simplify(&self) -> Option<WindowFunctionSimplification> {
+        period_simplification(&self.name, vec![PeriodArg::rows(1, "lag")])
+    }
+}
+
+#[derive(Debug)]
+struct ReturnsPartitionEvaluator {
+    kind: ReturnKind,
+    prices: Vec<f64>,
+}
+
+impl ReturnsPartitionEvaluator {
+    fn new(kind: ReturnKind) -> Self {
+        Self {
+            kind,
+            prices: Vec::new(),
+        }
+    }
+}
+
+impl PartitionEvaluator for ReturnsPartitionEvaluator {
+    fn evaluate_all(
+        &mut self,
+        values: &[ArrayRef],
+        num_rows: usize,
+    ) -> Result<ArrayRef> {
+        if values.is_empty() || values.len() > 2 {
+            return Err(DataFusionError::Execution(
+                "Returns function requires 1 or 2 arguments: price and optional lag".to_string(),
+            ));
+        }
+
+        let price_array = float64_arg(values, 0, "Price")?;
+        let lag = if values.len() == 2 { period_arg(values, 1, "Lag")? } else { 1 };
+
+        let mut result = Vec::with_capacity(num_rows);
+        self.prices.clear();
+
+        for i in 0..num_rows {
+            let Some(price) = value_at(price_array, i) else {
+                result.push(None);
+                continue;
+            };
+
+            self.prices.push(price);
+
+            if self.prices.len() <= lag {
+                result.push(None);
+                continue;
+            }
+
+            let base = self.prices[self.prices.len() - 1 - lag];
+            let period_return = match self.kind {
+                ReturnKind::Simple if base != 0.0 => Some(price / base - 1.0),
+                ReturnKind::Log if base > 0.0 && price > 0.0 => Some((price / base).ln()),
+                _ => None,
+            };
+            result.push(period_return);
+        }
+
+        Ok(Arc::new(Float64Array::from(result)))
+    }
+
+    fn uses_window_frame(&self) -> bool {
+        false
+    }
+
+    fn include_rank(&self) -> bool {
+        false
+    }
+}
+
+/// Register `returns` and `log_returns`
+pub fn register_returns(ctx: &SessionContext) -> Result<()> {
+    ctx.register_udwf(WindowUDF::from(PeriodReturns::simple()));
+    ctx.register_udwf(WindowUDF::from(PeriodReturns::log()));
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use datafusion::execution::context::SessionContext;
+
+    #[tokio::test]
+    async fn test_returns() -> Result<()> {
+        let ctx = SessionContext::new();
+        register_returns(&ctx)?;
+
+        // Test simple, lagged and log returns using SQL
+        let result = ctx
+            .sql("SELECT price,
+                    returns(price) OVER () AS ret_1,
+                    returns(price, 2) OVER () AS ret_2,
+                    log_returns(price) OVER () AS log_ret
+                FROM (VALUES
+                (100.0), (102.0), (99.0), (104.0), (105.0), (103.0)
+            ) AS t(price)")
+            .await?
+            .collect()
+            .await?;
+
+        println!("Returns Test Results:");
+        datafusion::arrow::util::pretty::print_batches(&result)?;
+
+        Ok(())
+    }
+}
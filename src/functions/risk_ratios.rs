@@ -0,0 +1,268 @@
+use std::any::Any;
+
+use datafusion::arrow::array::ArrayRef;
+use datafusion::arrow::datatypes::{DataType, Field};
+use datafusion::error::{DataFusionError, Result};
+use datafusion::execution::context::SessionContext;
+use datafusion::logical_expr::function::{AccumulatorArgs, StateFieldsArgs};
+use datafusion::logical_expr::utils::format_state_name;
+use datafusion::logical_expr::{Accumulator, AggregateUDF, AggregateUDFImpl, Signature, TypeSignature, Volatility};
+use datafusion::scalar::ScalarValue;
+
+use super::drawdown::max_drawdown;
+use super::rolling_stats::mean_and_variance;
+use super::utils::{f64_param_arg, float64_arg, OrderedSeries};
+
+/// Periods per year assumed by `calmar_ratio` when none is given (daily bars)
+const DEFAULT_PERIODS_PER_YEAR: f64 = 252.0;
+
+/// Which risk-adjusted performance ratio an aggregate computes
+#[derive(Debug, Clone, Copy)]
+enum RiskRatioKind {
+    Sharpe,
+    Sortino,
+    Calmar,
+}
+
+/// Risk-adjusted performance ratios over a group of periodic returns
+///
+/// Sharpe and Sortino are per-period ratios of excess return; Calmar uses the
+/// compounded equity curve and therefore needs returns in time order, e.g.
+/// `calmar_ratio(ret ORDER BY window_start)`. The keys are carried through
+/// partial aggregation so the order holds across partitions.
+#[derive(Debug)]
+pub struct RiskRatio {
+    name: String,
+    signature: Signature,
+    kind: RiskRatioKind,
+}
+
+impl RiskRatio {
+    /// `sharpe_ratio(returns [, risk_free])`
+    pub fn sharpe() -> Self {
+        Self::new("sharpe_ratio", RiskRatioKind::Sharpe)
+    }
+
+    /// `sortino_ratio(returns [, risk_free])`
+    pub fn sortino() -> Self {
+        Self::new("sortino_ratio", RiskRatioKind::Sortino)
+    }
+
+    /// `calmar_ratio(returns [, periods_per_year])`
+    pub fn calmar() -> Self {
+        Self::new("calmar_ratio", RiskRatioKind::Calmar)
+    }
+
+    fn new(name: &str, kind: RiskRatioKind) -> Self {
+        Self {
+            name: name.to_string(),
+            signature: Signature::one_of(
+                vec![
+                    TypeSignature::Exact(vec![DataType::Float64]),
+                    TypeSignature::Exact(vec![DataType::Float64, DataType::Float64]),
+                ],
+                Volatility::Immutable,
+            ),
+            kind,
+        }
+    }
+}
+
+impl AggregateUDFImpl for RiskRatio {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn signature(&self) -> &Signature {
+        &self.signature
+    }
+
+    fn return_type(&self, _arg_types: &[DataType]) -> Result<DataType> {
+        Ok(DataType::Float64)
+    }
+
+    fn accumulator(&self, acc_args: AccumulatorArgs) -> Result<Box<dyn Accumulator>> {
+        Ok(Box::new(RiskRatioAccumulator {
+            kind: self.kind,
+            args: acc_args.exprs.len(),
+            returns: OrderedSeries::try_new(1, &acc_args)?,
+            parameter: None,
+        }))
+    }
+
+    fn state_fields(&self, args: StateFieldsArgs) -> Result<Vec<Field>> {
+        let mut fields = OrderedSeries::state_fields(args.name, &["returns"]);
+        fields.push(Field::new(format_state_name(args.name, "parameter"), DataType::Float64, true));
+        Ok(fields)
+    }
+}
+
+#[derive(Debug)]
+struct RiskRatioAccumulator {
+    kind: RiskRatioKind,
+    /// Number of function arguments; any ORDER BY columns follow them
+    args: usize,
+    returns: OrderedSeries,
+    /// Risk-free rate per period for Sharpe/Sortino, periods per year for Calmar
+    parameter: Option<f64>,
+}
+
+impl Accumulator for RiskRatioAccumulator {
+    fn update_batch(&mut self, values: &[ArrayRef]) -> Result<()> {
+        if !(1..=2).contains(&self.args) || values.len() < self.args {
+            return Err(DataFusionError::Execution(
+                "Risk ratio functions require 1 or 2 arguments: returns and an optional parameter".to_string(),
+            ));
+        }
+
+        self.returns.update(&[float64_arg(values, 0, "Returns")?], &values[self.args..])?;
+        if self.args == 2 && !values[1].is_empty() {
+            self.parameter = Some(f64_param_arg(values, 1, "Second argument")?);
+        }
+
+        Ok(())
+    }
+
+    fn evaluate(&mut self) -> Result<ScalarValue> {
+        let returns = self.returns.sorted().swap_remove(0).into_iter().flatten().collect::<Vec<_>>();
+        let ratio = match self.kind {
+            RiskRatioKind::Sharpe => sharpe(&returns, self.parameter.unwrap_or(0.0)),
+            RiskRatioKind::Sortino => sortino(&returns, self.parameter.unwrap_or(0.0)),
+            RiskRatioKind::Calmar => calmar(&returns, self.parameter.unwrap_or(DEFAULT_PERIODS_PER_YEAR)),
+        };
+        Ok(ScalarValue::Float64(ratio))
+    }
+
+    fn size(&self) -> usize {
+        std::mem::size_of_val(self) + self.returns.heap_size()
+    }
+
+    fn state(&mut self) -> Result<Vec<ScalarValue>> {
+        let mut state = self.returns.state();
+        state.push(ScalarValue::Float64(self.parameter));
+        Ok(state)
+    }
+
+    fn merge_batch(&mut self, states: &[ArrayRef]) -> Result<()> {
+        self.returns.merge(states)?;
+        let parameter_idx = self.returns.state_len();
+        if let Some(parameter) = float64_arg(states, parameter_idx, "Parameter state")?.iter().flatten().last() {
+            self.parameter = Some(parameter);
+        }
+        Ok(())
+    }
+}
+
+/// Sharpe ratio of per-period returns
+fn sharpe(returns: &[f64], risk_free: f64) -> Option<f64> {
+    let excess = returns.iter().map(|r| r - risk_free).collect::<Vec<_>>();
+    if excess.is_empty() {
+        return None;
+    }
+    // Sharpe = mean(r - rf) / stddev(r - rf)
+    let (mean, variance) = mean_and_variance(&excess);
+    variance.filter(|v| *v > 0.0).map(|v| mean / v.sqrt())
+}
+
+/// Sortino ratio of per-period returns
+fn sortino(returns: &[f64], risk_free: f64) -> Option<f64> {
+    if returns.is_empty() {
+        return None;
+    }
+    let n = returns.len() as f64;
+    let mean = returns.iter().map(|r| r - risk_free).sum::<f64>() / n;
+    // Downside deviation only penalises returns below the risk-free rate
+    let downside = returns
+        .iter()
+        .map(|r| (r - risk_free).min(0.0).powi(2))
+        .sum::<f64>()
+        / n;
+    if downside > 0.0 {
+        Some(mean / downside.sqrt())
+    } else {
+        None
+    }
+}
+
+/// Calmar ratio of per-period returns taken in time order
+fn calmar(returns: &[f64], periods_per_year: f64) -> Option<f64> {
+    if returns.is_empty() {
+        return None;
+    }
+    let mut equity = Vec::with_capacity(returns.len() + 1);
+    equity.push(1.0);
+    for r in returns {
+        let last = *equity.last().unwrap_or(&1.0);
+        equity.push(last * (1.0 + r));
+    }
+    let final_equity = *equity.last()?;
+    if final_equity <= 0.0 {
+        return None;
+    }
+
+    // Calmar = annualized compound return / |max drawdown|
+    let years = returns.len() as f64 / periods_per_year;
+    let annual_return = final_equity.powf(1.0 / years) - 1.0;
+    max_drawdown(&equity)
+        .filter(|dd| *dd < 0.0)
+        .map(|dd| annual_return / dd.abs())
+}
+
+/// Register `sharpe_ratio`, `sortino_ratio` and `calmar_ratio`
+pub fn register_risk_ratios(ctx: &SessionContext) -> Result<()> {
+    ctx.register_udaf(AggregateUDF::from(RiskRatio::sharpe()));
+    ctx.register_udaf(AggregateUDF::from(RiskRatio::sortino()));
+    ctx.register_udaf(AggregateUDF::from(RiskRatio::calmar()));
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use datafusion::arrow::array::AsArray;
+    use datafusion::arrow::datatypes::Float64Type;
+    use datafusion::execution::context::SessionContext;
+
+    #[tokio::test]
+    async fn test_risk_ratios() -> Result<()> {
+        let ctx = SessionContext::new();
+        register_risk_ratios(&ctx)?;
+
+        // Test risk-adjusted ratios grouped by ticker using SQL
+        let result = ctx
+            .sql("SELECT ticker,
+                    sharpe_ratio(ret, 0.0001) AS sharpe,
+                    sortino_ratio(ret, 0.0001) AS sortino,
+                    calmar_ratio(ret) AS calmar
+                FROM (VALUES
+                ('AAPL', 0.012), ('AAPL', -0.004), ('AAPL', 0.007), ('AAPL', -0.011), ('AAPL', 0.015),
+                ('MSFT', 0.003), ('MSFT', 0.002), ('MSFT', -0.006), ('MSFT', 0.009), ('MSFT', -0.001)
+            ) AS t(ticker, ret)
+                GROUP BY ticker
+                ORDER BY ticker")
+            .await?
+            .collect()
+            .await?;
+
+        println!("Risk Ratio Test Results:");
+        datafusion::arrow::util::pretty::print_batches(&result)?;
+
+        // Test that calmar_ratio follows ORDER BY rather than row order
+        let ordered = ctx
+            .sql("SELECT calmar_ratio(ret ORDER BY day) AS calmar FROM (VALUES
+                (4, -0.011), (2, -0.004), (5, 0.015), (1, 0.012), (3, 0.007)
+            ) AS t(day, ret)")
+            .await?
+            .collect()
+            .await?;
+
+        let calmar = ordered[0].column(0).as_primitive::<Float64Type>().value(0);
+        assert_eq!(calmar, result[0].column(3).as_primitive::<Float64Type>().value(0));
+
+        Ok(())
+    }
+}
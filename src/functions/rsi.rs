@@ -1,11 +1,43 @@
 use std::any::Any;
+use std::ops::Range;
 use std::sync::Arc;
 
-use datafusion::arrow::array::{ArrayRef, Float64Array, Int64Array};
+use datafusion::arrow::array::{ArrayRef, AsArray, Float64Array};
 use datafusion::arrow::datatypes::DataType;
 use datafusion::error::{DataFusionError, Result};
 use datafusion::execution::context::SessionContext;
-use datafusion::logical_expr::{Signature, TypeSignature, Volatility, WindowUDF, WindowUDFImpl, PartitionEvaluator};
+use datafusion::logical_expr::function::WindowFunctionSimplification;
+use datafusion::logical_expr::{Signature, Volatility, WindowUDF, WindowUDFImpl, PartitionEvaluator};
+use datafusion::scalar::ScalarValue;
+
+use super::kernels::{self, map_valid};
+use super::utils::{coerce_numeric_args, period_arg, value_at, RollingSum};
+use crate::validation::{period_simplification, PeriodArg};
+
+/// How average gains and losses are smoothed
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum RsiMethod {
+    /// Wilder's smoothing (RMA), alpha = 1 / N, seeded with an SMA
+    Wilder,
+    /// Standard EMA, alpha = 2 / (N + 1), seeded with an SMA
+    Ema,
+    /// Simple rolling mean of the last N gains and losses (Cutler's RSI)
+    Sma,
+}
+
+impl RsiMethod {
+    fn parse(method: &str) -> Result<Self> {
+        match method.to_ascii_lowercase().as_str() {
+            "wilder" | "rma" => Ok(Self::Wilder),
+            "ema" => Ok(Self::Ema),
+            "sma" => Ok(Self::Sma),
+            other => Err(DataFusionError::Execution(format!(
+                "Unknown RSI method '{}', expected 'wilder', 'ema' or 'sma'",
+                other
+            ))),
+        }
+    }
+}
 
 #[derive(Debug)]
 pub struct RelativeStrengthIndex {
@@ -17,10 +49,7 @@ impl RelativeStrengthIndex {
     pub fn new() -> Self {
         Self {
             name: "rsi".to_string(),
-            signature: Signature::one_of(
-                vec![TypeSignature::Exact(vec![DataType::Float64, DataType::Int64])],
-                Volatility::Immutable,
-            ),
+            signature: Signature::user_defined(Volatility::Immutable),
         }
     }
 }
@@ -38,6 +67,17 @@ impl WindowUDFImpl for RelativeStrengthIndex {
         &self.signature
     }
 
+    fn coerce_types(&self, arg_types: &[DataType]) -> Result<Vec<DataType>> {
+        coerce_numeric_args(
+            &self.name,
+            arg_types,
+            &[
+                vec![DataType::Float64, DataType::Int64],
+                vec![DataType::Float64, DataType::Int64, DataType::Utf8],
+            ],
+        )
+    }
+
     fn return_type(&self, _arg_types: &[DataType]) -> Result<DataType> {
         Ok(DataType::Float64)
     }
@@ -45,37 +85,101 @@ impl WindowUDFImpl for RelativeStrengthIndex {
     fn partition_evaluator(&self) -> Result<Box<dyn PartitionEvaluator>> {
         Ok(Box::new(RsiPartitionEvaluator::new()))
     }
+
+    fn simplify(&self) -> Option<WindowFunctionSimplification> {
+        period_simplification(&self.name, vec![PeriodArg::rows(1, "window_size")])
+    }
 }
 
 #[derive(Debug)]
 struct RsiPartitionEvaluator {
     window_size: usize,
-    values: Vec<f64>,
-    gains: Vec<f64>,
-    losses: Vec<f64>,
-    avg_gain: f64,
-    avg_loss: f64,
+    method: RsiMethod,
+    // Running state for row-at-a-time evaluation
+    prev_value: Option<f64>,
+    gains: RollingSum,
+    losses: RollingSum,
+    averages: Option<(f64, f64)>,
 }
 
 impl RsiPartitionEvaluator {
     fn new() -> Self {
         Self {
             window_size: 0,
-            values: Vec::new(),
-            gains: Vec::new(),
-            losses: Vec::new(),
-            avg_gain: 0.0,
-            avg_loss: 0.0,
+            method: RsiMethod::Wilder,
+            prev_value: None,
+            gains: RollingSum::default(),
+            losses: RollingSum::default(),
+            averages: None,
         }
     }
 
-    fn calculate_rsi(&self, avg_gain: f64, avg_loss: f64) -> f64 {
-        if avg_loss == 0.0 {
-            return 100.0;
+    /// Read the constant arguments
+    fn configure(&mut self, values: &[ArrayRef]) -> Result<()> {
+        if values.len() != 2 && values.len() != 3 {
+            return Err(DataFusionError::Execution(
+                "RSI function requires 2 or 3 arguments: value, window_size and optional method".to_string(),
+            ));
         }
-        let rs = avg_gain / avg_loss;
-        100.0 - (100.0 / (1.0 + rs))
+
+        self.window_size = period_arg(values, 1, "Window size")?;
+
+        self.method = match values.get(2) {
+            Some(method_array) => method_array
+                .as_string_opt::<i32>()
+                .ok_or_else(|| DataFusionError::Execution("Method must be Utf8".to_string()))?
+                .iter()
+                .find_map(|x| x)
+                .map(RsiMethod::parse)
+                .transpose()?
+                .unwrap_or(RsiMethod::Wilder),
+            None => RsiMethod::Wilder,
+        };
+
+        self.gains.reset(self.window_size);
+        self.losses.reset(self.window_size);
+        Ok(())
     }
+
+    /// Add the next value and return the RSI once window_size changes are seen
+    fn push(&mut self, value: f64) -> Option<f64> {
+        let prev = self.prev_value.replace(value)?;
+        let change = value - prev;
+        let (gain, loss) = (change.max(0.0), (-change).max(0.0));
+
+        let period = self.window_size as f64;
+        let alpha = match self.method {
+            RsiMethod::Wilder => 1.0 / period,
+            RsiMethod::Ema => 2.0 / (period + 1.0),
+            RsiMethod::Sma => {
+                let (gain_sum, loss_sum) = (self.gains.push(gain), self.losses.push(loss));
+                return Some(kernels::rsi_value(gain_sum?, loss_sum?));
+            }
+        };
+
+        // Smoothed averages are seeded with the mean of the first window_size changes
+        let (avg_gain, avg_loss) = match self.averages {
+            Some((avg_gain, avg_loss)) => (
+                avg_gain * (1.0 - alpha) + gain * alpha,
+                avg_loss * (1.0 - alpha) + loss * alpha,
+            ),
+            None => {
+                let (gain_sum, loss_sum) = (self.gains.push(gain), self.losses.push(loss));
+                (gain_sum? / period, loss_sum? / period)
+            }
+        };
+        self.averages = Some((avg_gain, avg_loss));
+        Some(kernels::rsi_value(avg_gain, avg_loss))
+    }
+}
+
+fn value_array(values: &[ArrayRef]) -> Result<&Float64Array> {
+    values[0]
+        .as_any()
+        .downcast_ref::<Float64Array>()
+        .ok_or_else(|| {
+            DataFusionError::Execution("First argument must be Float64".to_string())
+        })
 }
 
 impl PartitionEvaluator for RsiPartitionEvaluator {
@@ -84,84 +188,37 @@ impl PartitionEvaluator for RsiPartitionEvaluator {
         values: &[ArrayRef],
         num_rows: usize,
     ) -> Result<ArrayRef> {
-        if values.len() != 2 {
-            return Err(DataFusionError::Execution(
-                "RSI function requires exactly 2 arguments: value and window_size".to_string(),
-            ));
-        }
+        self.configure(values)?;
+        let value_array = value_array(values)?;
 
-        let value_array = values[0]
-            .as_any()
-            .downcast_ref::<Float64Array>()
-            .ok_or_else(|| {
-                DataFusionError::Execution("First argument must be Float64".to_string())
-            })?;
-
-        let window_size_array = values[1]
-            .as_any()
-            .downcast_ref::<Int64Array>()
-            .ok_or_else(|| {
-                DataFusionError::Execution("Second argument must be Int64".to_string())
-            })?;
-
-        // Get window size from first non-null value
-        self.window_size = window_size_array
-            .iter()
-            .find_map(|x| x)
-            .ok_or_else(|| {
-                DataFusionError::Execution("Window size cannot be null".to_string())
-            })? as usize;
-
-        let mut result = Vec::with_capacity(num_rows);
-        self.values.clear();
-        self.gains.clear();
-        self.losses.clear();
-
-        for i in 0..num_rows {
-            if let Some(current_value) = value_array.value(i).into() {
-                self.values.push(current_value);
-                
-                if self.values.len() == 1 {
-                    // First value, no RSI calculation possible
-                    result.push(None);
-                    continue;
-                }
-
-                // Calculate price change
-                let prev_value = self.values[self.values.len() - 2];
-                let change = current_value - prev_value;
-                
-                let gain = if change > 0.0 { change } else { 0.0 };
-                let loss = if change < 0.0 { -change } else { 0.0 };
-                
-                self.gains.push(gain);
-                self.losses.push(loss);
-
-                if self.gains.len() < self.window_size {
-                    // Not enough data for RSI calculation
-                    result.push(None);
-                    continue;
-                }
+        // The first RSI needs window_size price changes, so window_size + 1 values
+        let period = self.window_size;
+        let result = map_valid(value_array, |values, out| match self.method {
+            RsiMethod::Wilder => kernels::rsi_smoothed(values, period, 1.0 / period as f64, out),
+            RsiMethod::Ema => kernels::rsi_smoothed(values, period, 2.0 / (period as f64 + 1.0), out),
+            // Cutler's RSI: plain mean of the last N gains and losses
+            RsiMethod::Sma => kernels::rsi_rolling(values, period, out),
+        });
+        debug_assert_eq!(result.len(), num_rows);
 
-                if self.gains.len() == self.window_size {
-                    // First RSI calculation - use simple average
-                    self.avg_gain = self.gains.iter().sum::<f64>() / self.window_size as f64;
-                    self.avg_loss = self.losses.iter().sum::<f64>() / self.window_size as f64;
-                } else {
-                    // Subsequent calculations - use Wilder's smoothing
-                    let alpha = 1.0 / self.window_size as f64;
-                    self.avg_gain = (self.avg_gain * (1.0 - alpha)) + (gain * alpha);
-                    self.avg_loss = (self.avg_loss * (1.0 - alpha)) + (loss * alpha);
-                }
+        Ok(Arc::new(result))
+    }
 
-                let rsi = self.calculate_rsi(self.avg_gain, self.avg_loss);
-                result.push(Some(rsi));
-            } else {
-                result.push(None);
-            }
+    /// Streaming evaluation: only the previous value and running averages are kept
+    fn evaluate(&mut self, values: &[ArrayRef], range: &Range<usize>) -> Result<ScalarValue> {
+        if self.window_size == 0 {
+            self.configure(values)?;
         }
+        let rsi = value_at(value_array(values)?, range.start).and_then(|value| self.push(value));
+        Ok(ScalarValue::Float64(rsi))
+    }
+
+    fn supports_bounded_execution(&self) -> bool {
+        true
+    }
 
-        Ok(Arc::new(Float64Array::from(result)))
+    fn is_causal(&self) -> bool {
+        true
     }
 
     fn uses_window_frame(&self) -> bool {
@@ -182,6 +239,7 @@ pub fn register_rsi(ctx: &SessionContext) -> Result<()> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use datafusion::arrow::array::Int64Array;
     use datafusion::execution::context::SessionContext;
 
     #[tokio::test]
@@ -203,6 +261,45 @@ mod tests {
         println!("RSI Test Results:");
         datafusion::arrow::util::pretty::print_batches(&result)?;
 
+        // Test RSI smoothing methods using SQL
+        let result = ctx
+            .sql("SELECT price,
+                    rsi(price, 5, 'wilder') OVER () AS rsi_wilder,
+                    rsi(price, 5, 'ema') OVER () AS rsi_ema,
+                    rsi(price, 5, 'sma') OVER () AS rsi_sma
+                FROM (VALUES
+                (44.34), (44.09), (44.15), (43.61), (44.33), (44.83), (45.85), (46.08),
+                (45.89), (46.03), (46.83), (47.69), (46.49), (46.26)
+            ) AS t(price)")
+            .await?
+            .collect()
+            .await?;
+
+        println!("RSI Method Test Results:");
+        datafusion::arrow::util::pretty::print_batches(&result)?;
+
+        // Row-at-a-time (bounded memory) evaluation matches evaluating the whole partition
+        let prices: ArrayRef = Arc::new(Float64Array::from(vec![
+            Some(44.34), Some(44.09), None, Some(44.15), Some(43.61), Some(44.33), Some(44.83),
+            Some(45.85), Some(46.08), Some(45.89), Some(46.03), Some(46.83), Some(47.69),
+        ]));
+        for method in ["wilder", "ema", "sma"] {
+            let values = vec![
+                Arc::clone(&prices),
+                Arc::new(Int64Array::from(vec![5; prices.len()])) as ArrayRef,
+                Arc::new(datafusion::arrow::array::StringArray::from(vec![method; prices.len()])),
+            ];
+            let batch = RsiPartitionEvaluator::new().evaluate_all(&values, prices.len())?;
+            let mut streaming = RsiPartitionEvaluator::new();
+            for i in 0..prices.len() {
+                let expected = ScalarValue::try_from_array(&batch, i)?;
+                match (streaming.evaluate(&values, &(i..i + 1))?, expected) {
+                    (ScalarValue::Float64(Some(a)), ScalarValue::Float64(Some(b))) => assert!((a - b).abs() < 1e-9),
+                    (a, b) => assert_eq!(a, b),
+                }
+            }
+        }
+
         Ok(())
     }
 }
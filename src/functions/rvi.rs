@@ -0,0 +1,203 @@
+use std::any::Any;
+
+use datafusion::arrow::array::ArrayRef;
+use datafusion::arrow::datatypes::DataType;
+use datafusion::error::Result;
+use datafusion::execution::context::SessionContext;
+use datafusion::logical_expr::function::WindowFunctionSimplification;
+use datafusion::logical_expr::{Signature, TypeSignature, Volatility, WindowUDF, WindowUDFImpl, PartitionEvaluator};
+
+use super::ohlcv::{OHLC, OhlcvArgs};
+use super::utils::{check_arg_count, float64_arg, float64_struct_array, float64_struct_type, period_arg, value_at, RollingSum};
+use crate::validation::{period_simplification, PeriodArg};
+
+const RVI_FIELDS: [&str; 2] = ["rvi", "signal"];
+
+/// Relative Vigor Index with its symmetrically weighted signal line
+#[derive(Debug)]
+pub struct RelativeVigorIndex {
+    name: String,
+    signature: Signature,
+}
+
+impl RelativeVigorIndex {
+    pub fn new() -> Self {
+        Self {
+            name: "rvi".to_string(),
+            signature: Signature::one_of(
+                vec![TypeSignature::Exact(vec![
+                    DataType::Float64,
+                    DataType::Float64,
+                    DataType::Float64,
+                    DataType::Float64,
+                    DataType::Int64,
+                ])],
+                Volatility::Immutable,
+            ),
+        }
+    }
+}
+
+impl WindowUDFImpl for RelativeVigorIndex {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn signature(&self) -> &Signature {
+        &self.signature
+    }
+
+    fn return_type(&self, _arg_types: &[DataType]) -> Result<DataType> {
+        Ok(float64_struct_type(&RVI_FIELDS))
+    }
+
+    fn partition_evaluator(&self) -> Result<Box<dyn PartitionEvaluator>> {
+        Ok(Box::new(RviPartitionEvaluator::new()))
+    }
+
+    fn simplify(&self) -> Option<WindowFunctionSimplification> {
+        period_simplification(&self.name, vec![PeriodArg::rows(4, "period")])
+    }
+}
+
+/// Symmetric 1-2-2-1 weighted average of the last four values
+fn swma(values: &[f64]) -> Option<f64> {
+    let n = values.len();
+    if n < 4 {
+        return None;
+    }
+    Some((values[n - 1] + 2.0 * values[n - 2] + 2.0 * values[n - 3] + values[n - 4]) / 6.0)
+}
+
+#[derive(Debug)]
+struct RviPartitionEvaluator {
+    close_open: Vec<f64>,
+    high_low: Vec<f64>,
+    numerators: RollingSum,
+    denominators: RollingSum,
+    rvi_values: Vec<f64>,
+}
+
+impl RviPartitionEvaluator {
+    fn new() -> Self {
+        Self {
+            close_open: Vec::new(),
+            high_low: Vec::new(),
+            numerators: RollingSum::default(),
+            denominators: RollingSum::default(),
+            rvi_values: Vec::new(),
+        }
+    }
+}
+
+impl PartitionEvaluator for RviPartitionEvaluator {
+    fn evaluate_all(
+        &mut self,
+        values: &[ArrayRef],
+        num_rows: usize,
+    ) -> Result<ArrayRef> {
+        check_arg_count(values, 5, "RVI function (open, high, low, close, period)")?;
+
+        let open_array = float64_arg(values, 0, "Open")?;
+        let high_array = float64_arg(values, 1, "High")?;
+        let low_array = float64_arg(values, 2, "Low")?;
+        let close_array = float64_arg(values, 3, "Close")?;
+        let period = period_arg(values, 4, "Period")?;
+
+        let mut rvi_result = Vec::with_capacity(num_rows);
+        let mut signal_result = Vec::with_capacity(num_rows);
+        *self = Self::new();
+        self.numerators.reset(period);
+        self.denominators.reset(period);
+
+        for i in 0..num_rows {
+            let (Some(open), Some(high), Some(low), Some(close)) = (
+                value_at(open_array, i),
+                value_at(high_array, i),
+                value_at(low_array, i),
+                value_at(close_array, i),
+            ) else {
+                rvi_result.push(None);
+                signal_result.push(None);
+                continue;
+            };
+
+            self.close_open.push(close - open);
+            self.high_low.push(high - low);
+
+            let (Some(numerator), Some(denominator)) = (swma(&self.close_open), swma(&self.high_low)) else {
+                rvi_result.push(None);
+                signal_result.push(None);
+                continue;
+            };
+            let numerator_sum = self.numerators.push(numerator);
+            let denominator_sum = self.denominators.push(denominator);
+
+            // RVI = SMA(numerator, N) / SMA(denominator, N)
+            let (Some(numerator_sum), Some(denominator_sum)) = (numerator_sum, denominator_sum) else {
+                rvi_result.push(None);
+                signal_result.push(None);
+                continue;
+            };
+            if denominator_sum == 0.0 {
+                rvi_result.push(None);
+                signal_result.push(None);
+                continue;
+            }
+
+            let rvi = numerator_sum / denominator_sum;
+            self.rvi_values.push(rvi);
+            rvi_result.push(Some(rvi));
+            signal_result.push(swma(&self.rvi_values));
+        }
+
+        Ok(float64_struct_array(&RVI_FIELDS, vec![rvi_result, signal_result]))
+    }
+
+    fn uses_window_frame(&self) -> bool {
+        false
+    }
+
+    fn include_rank(&self) -> bool {
+        false
+    }
+}
+
+pub fn register_rvi(ctx: &SessionContext) -> Result<()> {
+    let rvi_udf = WindowUDF::from(OhlcvArgs::new(RelativeVigorIndex::new(), OHLC));
+    ctx.register_udwf(rvi_udf);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use datafusion::execution::context::SessionContext;
+
+    #[tokio::test]
+    async fn test_rvi() -> Result<()> {
+        let ctx = SessionContext::new();
+        register_rvi(&ctx)?;
+
+        // Test RVI with a 3 period window using SQL
+        let result = ctx
+            .sql("SELECT close, rvi(open, high, low, close, 3) OVER () AS rvi_3 FROM (VALUES
+                (100.0, 102.0, 99.0, 101.5), (101.5, 103.0, 100.5, 102.8), (102.8, 104.0, 101.0, 101.2),
+                (101.2, 102.5, 99.5, 100.1), (100.1, 101.8, 99.8, 101.6), (101.6, 104.5, 101.0, 104.2),
+                (104.2, 105.0, 103.1, 103.5), (103.5, 106.2, 103.0, 105.9), (105.9, 107.0, 104.8, 106.5),
+                (106.5, 107.5, 105.0, 105.3)
+            ) AS t(open, high, low, close)")
+            .await?
+            .collect()
+            .await?;
+
+        println!("RVI Test Results:");
+        datafusion::arrow::util::pretty::print_batches(&result)?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,297 @@
+//! Session-aware variants of the financial window functions
+//!
+//! An intraday partition spanning several days otherwise carries indicator
+//! state (EMAs, cumulative VWAP sums, ...) from one day's close into the next
+//! day's open. [`session_reset`] wraps a window function as
+//! `session_<name>(timestamp, ...)`, which restarts the indicator whenever
+//! the trading day of `timestamp` changes.
+//!
+//! Trading days are cut at a fixed time of day in UTC, 06:00 by default. That
+//! is 01:00 or 02:00 in New York, while US equities are closed even for
+//! extended hours (04:00 to 20:00 ET), in both standard and daylight time.
+
+use std::any::Any;
+use std::ops::Range;
+use std::sync::Arc;
+use std::time::Duration;
+
+use datafusion::arrow::array::{Array, ArrayRef};
+use datafusion::arrow::compute::concat;
+use datafusion::arrow::datatypes::{DataType, TimeUnit};
+use datafusion::error::{DataFusionError, Result};
+use datafusion::execution::context::SessionContext;
+use datafusion::logical_expr::expr::WindowFunction;
+use datafusion::logical_expr::function::WindowFunctionSimplification;
+use datafusion::logical_expr::{Expr, PartitionEvaluator, Signature, Volatility, WindowUDF, WindowUDFImpl};
+use datafusion::scalar::ScalarValue;
+
+use super::utils::{coerce_window_args, timestamp_nanos_arg};
+
+/// Default start of the trading day, after UTC midnight
+pub const DEFAULT_SESSION_BOUNDARY: Duration = Duration::from_secs(6 * 60 * 60);
+
+const DAY_NANOS: i64 = 86_400_000_000_000;
+
+/// Wraps a window function so it restarts at every trading day boundary
+#[derive(Debug)]
+pub struct SessionReset {
+    name: String,
+    inner: Arc<dyn WindowUDFImpl>,
+    boundary_nanos: i64,
+    signature: Signature,
+}
+
+impl SessionReset {
+    pub fn new(udf: &WindowUDF) -> Self {
+        Self {
+            name: format!("session_{}", udf.name()),
+            inner: Arc::clone(udf.inner()),
+            boundary_nanos: DEFAULT_SESSION_BOUNDARY.as_nanos() as i64,
+            signature: Signature::user_defined(Volatility::Immutable),
+        }
+    }
+
+    /// Start each trading day `boundary` after UTC midnight
+    pub fn with_boundary(mut self, boundary: Duration) -> Self {
+        self.boundary_nanos = (boundary.as_nanos() % DAY_NANOS as u128) as i64;
+        self
+    }
+}
+
+impl WindowUDFImpl for SessionReset {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn signature(&self) -> &Signature {
+        &self.signature
+    }
+
+    fn coerce_types(&self, arg_types: &[DataType]) -> Result<Vec<DataType>> {
+        let timestamp = match arg_types.first() {
+            Some(DataType::Timestamp(_, tz)) => DataType::Timestamp(TimeUnit::Nanosecond, tz.clone()),
+            Some(t) if t.is_integer() || matches!(t, DataType::Null) => DataType::Int64,
+            _ => {
+                return Err(DataFusionError::Plan(format!(
+                    "{} requires a timestamp (or Int64 epoch nanoseconds) first argument, followed by the arguments of {}",
+                    self.name,
+                    self.inner.name()
+                )))
+            }
+        };
+
+        let mut coerced = vec![timestamp];
+        coerced.extend(coerce_window_args(self.inner.as_ref(), &arg_types[1..])?);
+        Ok(coerced)
+    }
+
+    fn return_type(&self, arg_types: &[DataType]) -> Result<DataType> {
+        self.inner.return_type(&arg_types[1..])
+    }
+
+    fn partition_evaluator(&self) -> Result<Box<dyn PartitionEvaluator>> {
+        Ok(Box::new(SessionResetPartitionEvaluator {
+            udf: Arc::clone(&self.inner),
+            inner: self.inner.partition_evaluator()?,
+            boundary_nanos: self.boundary_nanos,
+            session: None,
+        }))
+    }
+
+    /// Run the wrapped function's checks on the arguments after the timestamp
+    fn simplify(&self) -> Option<WindowFunctionSimplification> {
+        let inner = self.inner.simplify()?;
+        Some(Box::new(move |window_function, info| {
+            if let Some(args) = window_function.args.get(1..) {
+                let shifted = WindowFunction {
+                    args: args.to_vec(),
+                    ..window_function.clone()
+                };
+                inner(shifted, info)?;
+            }
+            Ok(Expr::WindowFunction(window_function))
+        }))
+    }
+}
+
+#[derive(Debug)]
+struct SessionResetPartitionEvaluator {
+    udf: Arc<dyn WindowUDFImpl>,
+    inner: Box<dyn PartitionEvaluator>,
+    boundary_nanos: i64,
+    // Trading day of the last row seen by `evaluate`
+    session: Option<i64>,
+}
+
+impl SessionResetPartitionEvaluator {
+    /// Trading day number of an epoch-nanosecond timestamp
+    fn session_of(&self, timestamp: i64) -> i64 {
+        (timestamp - self.boundary_nanos).div_euclid(DAY_NANOS)
+    }
+
+    /// Evaluate one session's rows with a fresh evaluator
+    fn evaluate_session(&self, args: &[ArrayRef], rows: Range<usize>) -> Result<ArrayRef> {
+        let session = args
+            .iter()
+            .map(|array| array.slice(rows.start, rows.len()))
+            .collect::<Vec<_>>();
+        self.udf.partition_evaluator()?.evaluate_all(&session, rows.len())
+    }
+}
+
+impl PartitionEvaluator for SessionResetPartitionEvaluator {
+    fn evaluate_all(
+        &mut self,
+        values: &[ArrayRef],
+        num_rows: usize,
+    ) -> Result<ArrayRef> {
+        if values.is_empty() {
+            return Err(DataFusionError::Execution(
+                "Session functions require a timestamp argument".to_string(),
+            ));
+        }
+
+        let timestamps = timestamp_nanos_arg(values, 0, "Timestamp")?;
+        let args = &values[1..];
+        if num_rows == 0 {
+            return self.inner.evaluate_all(args, 0);
+        }
+
+        // Split into runs of consecutive rows in one session; rows with a
+        // null timestamp stay in the current session
+        let mut results = Vec::new();
+        let (mut start, mut session) = (0, None);
+        for i in 0..num_rows {
+            if timestamps.is_null(i) {
+                continue;
+            }
+            let row_session = self.session_of(timestamps.value(i));
+            if session.is_some_and(|current| current != row_session) {
+                results.push(self.evaluate_session(args, start..i)?);
+                start = i;
+            }
+            session = Some(row_session);
+        }
+        results.push(self.evaluate_session(args, start..num_rows)?);
+
+        let results = results.iter().map(|array| array.as_ref()).collect::<Vec<_>>();
+        Ok(concat(&results)?)
+    }
+
+    fn evaluate(&mut self, values: &[ArrayRef], range: &Range<usize>) -> Result<ScalarValue> {
+        let rows = values
+            .iter()
+            .map(|array| array.slice(range.start, range.len()))
+            .collect::<Vec<_>>();
+
+        let timestamps = timestamp_nanos_arg(&rows, 0, "Timestamp")?;
+        if timestamps.is_valid(0) {
+            let row_session = self.session_of(timestamps.value(0));
+            if self.session.is_some_and(|current| current != row_session) {
+                self.inner = self.udf.partition_evaluator()?;
+            }
+            self.session = Some(row_session);
+        }
+
+        self.inner.evaluate(&rows[1..], &(0..range.len()))
+    }
+
+    fn supports_bounded_execution(&self) -> bool {
+        self.inner.supports_bounded_execution()
+    }
+
+    fn is_causal(&self) -> bool {
+        self.inner.is_causal()
+    }
+
+    fn uses_window_frame(&self) -> bool {
+        false
+    }
+
+    fn include_rank(&self) -> bool {
+        false
+    }
+}
+
+/// Wrap a window function as `session_<name>(timestamp, ...)`
+pub fn session_reset(udf: &WindowUDF) -> WindowUDF {
+    WindowUDF::from(SessionReset::new(udf))
+}
+
+/// Register a `session_<name>` variant of every financial window function
+///
+/// Only the session variants are registered. Use
+/// `FinancialFunctions::all().with_sessions()` for both, or to pick groups,
+/// a prefix or another boundary.
+pub fn register_session_functions(ctx: &SessionContext) -> Result<()> {
+    crate::FinancialFunctions::all().register_sessions(ctx)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_session_reset() -> Result<()> {
+        let ctx = SessionContext::new();
+        crate::register_financial_functions(&ctx)?;
+        register_session_functions(&ctx)?;
+
+        // Two trading days in one partition: 14:30 UTC on consecutive days
+        let day = DAY_NANOS;
+        let open = 14 * 3_600_000_000_000_i64 + 30 * 60_000_000_000;
+        let result = ctx
+            .sql(&format!("SELECT ts, price,
+                    ema(price, 3) OVER (ORDER BY ts) AS ema_3,
+                    session_ema(ts, price, 3) OVER (ORDER BY ts) AS session_ema_3,
+                    session_vwap(ts, price, volume) OVER (ORDER BY ts) AS session_vwap
+                FROM (SELECT CAST(d * {} + {} + m * 60000000000 AS BIGINT) AS ts, price, volume FROM (VALUES
+                    (0, 0, 100.0, 1000.0), (0, 1, 101.0, 1500.0), (0, 2, 102.0, 1200.0),
+                    (1, 0, 95.0, 2000.0), (1, 1, 96.0, 1800.0), (1, 2, 97.0, 1100.0)
+                ) AS t(d, m, price, volume))", day, open))
+            .await?
+            .collect()
+            .await?;
+
+        println!("Session Reset Test Results:");
+        datafusion::arrow::util::pretty::print_batches(&result)?;
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_session_registration() -> Result<()> {
+        // Session variants alone leave the plain functions unregistered
+        let ctx = SessionContext::new();
+        register_session_functions(&ctx)?;
+        assert!(ctx.udwf("session_ema").is_ok());
+        assert!(ctx.udwf("ema").is_err());
+
+        // Through the builder they follow the selection and prefix
+        let ctx = SessionContext::new();
+        crate::FinancialFunctions::new()
+            .with_ema()
+            .with_sessions()
+            .with_prefix("fin_")
+            .register(&ctx)?;
+        assert!(ctx.udwf("fin_ema").is_ok());
+        assert!(ctx.udwf("fin_session_ema").is_ok());
+        assert!(ctx.udwf("session_ema").is_err());
+        assert!(ctx.udwf("fin_session_sma").is_err());
+
+        // Periods are validated after the leading timestamp
+        let err = ctx
+            .sql("SELECT fin_session_ema(ts, price, 0) OVER (ORDER BY ts)
+                FROM (VALUES (1, 100.0), (2, 101.0)) AS t(ts, price)")
+            .await?
+            .into_optimized_plan()
+            .unwrap_err();
+        assert!(err.to_string().contains("window_size"), "{}", err);
+
+        Ok(())
+    }
+}
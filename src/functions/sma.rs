@@ -1,11 +1,17 @@
 use std::any::Any;
+use std::ops::Range;
 use std::sync::Arc;
 
-use datafusion::arrow::array::{ArrayRef, Float64Array, Int64Array};
+use datafusion::arrow::array::{ArrayRef, Float64Array};
 use datafusion::arrow::datatypes::DataType;
 use datafusion::error::{DataFusionError, Result};
 use datafusion::execution::context::SessionContext;
-use datafusion::logical_expr::{Signature, TypeSignature, Volatility, WindowUDF, WindowUDFImpl, PartitionEvaluator};
+use datafusion::logical_expr::function::WindowFunctionSimplification;
+use datafusion::logical_expr::{Signature, Volatility, WindowUDF, WindowUDFImpl, PartitionEvaluator};
+use datafusion::scalar::ScalarValue;
+
+use super::utils::{coerce_numeric_args, min_periods_arg, period_arg, value_at, RollingSum};
+use crate::validation::{period_simplification, PeriodArg};
 
 #[derive(Debug)]
 pub struct SimpleMovingAverage {
@@ -17,10 +23,7 @@ impl SimpleMovingAverage {
     pub fn new() -> Self {
         Self {
             name: "sma".to_string(),
-            signature: Signature::one_of(
-                vec![TypeSignature::Exact(vec![DataType::Float64, DataType::Int64])],
-                Volatility::Immutable,
-            ),
+            signature: Signature::user_defined(Volatility::Immutable),
         }
     }
 }
@@ -38,6 +41,17 @@ impl WindowUDFImpl for SimpleMovingAverage {
         &self.signature
     }
 
+    fn coerce_types(&self, arg_types: &[DataType]) -> Result<Vec<DataType>> {
+        coerce_numeric_args(
+            &self.name,
+            arg_types,
+            &[
+                vec![DataType::Float64, DataType::Int64],
+                vec![DataType::Float64, DataType::Int64, DataType::Int64],
+            ],
+        )
+    }
+
     fn return_type(&self, _arg_types: &[DataType]) -> Result<DataType> {
         Ok(DataType::Float64)
     }
@@ -45,80 +59,98 @@ impl WindowUDFImpl for SimpleMovingAverage {
     fn partition_evaluator(&self) -> Result<Box<dyn PartitionEvaluator>> {
         Ok(Box::new(SmaPartitionEvaluator::new()))
     }
+
+    fn simplify(&self) -> Option<WindowFunctionSimplification> {
+        period_simplification(
+            &self.name,
+            vec![PeriodArg::rows(1, "window_size"), PeriodArg::rows(2, "min_periods")],
+        )
+    }
 }
 
 #[derive(Debug)]
 struct SmaPartitionEvaluator {
-    values: Vec<f64>,
+    window: RollingSum,
     window_size: usize,
+    min_periods: usize,
 }
 
 impl SmaPartitionEvaluator {
     fn new() -> Self {
         Self {
-            values: Vec::new(),
+            window: RollingSum::default(),
             window_size: 0,
+            min_periods: 0,
+        }
+    }
+
+    /// Read the constant arguments and start a fresh window
+    fn configure(&mut self, values: &[ArrayRef]) -> Result<()> {
+        if values.len() != 2 && values.len() != 3 {
+            return Err(DataFusionError::Execution(
+                "SMA function requires 2 or 3 arguments: value, window_size and optional min_periods".to_string(),
+            ));
         }
+
+        self.window_size = period_arg(values, 1, "Window size")?;
+        self.min_periods = min_periods_arg(values, 2, self.window_size, self.window_size)?;
+        self.window.reset(self.window_size);
+        Ok(())
+    }
+
+    /// Add the next value and return the average once min_periods is met
+    fn push(&mut self, value: f64) -> Option<f64> {
+        // Running window sum keeps each row O(1) regardless of window size;
+        // partial windows average what they hold once min_periods is met
+        self.window.push(value);
+        (self.window.len() >= self.min_periods.max(1))
+            .then(|| self.window.sum() / self.window.len() as f64)
     }
 }
 
+fn value_array(values: &[ArrayRef]) -> Result<&Float64Array> {
+    values[0]
+        .as_any()
+        .downcast_ref::<Float64Array>()
+        .ok_or_else(|| {
+            DataFusionError::Execution("First argument must be Float64".to_string())
+        })
+}
+
 impl PartitionEvaluator for SmaPartitionEvaluator {
     fn evaluate_all(
         &mut self,
         values: &[ArrayRef],
         num_rows: usize,
     ) -> Result<ArrayRef> {
-        if values.len() != 2 {
-            return Err(DataFusionError::Execution(
-                "SMA function requires exactly 2 arguments: value and window_size".to_string(),
-            ));
-        }
-
-        let value_array = values[0]
-            .as_any()
-            .downcast_ref::<Float64Array>()
-            .ok_or_else(|| {
-                DataFusionError::Execution("First argument must be Float64".to_string())
-            })?;
-
-        let window_size_array = values[1]
-            .as_any()
-            .downcast_ref::<Int64Array>()
-            .ok_or_else(|| {
-                DataFusionError::Execution("Second argument must be Int64".to_string())
-            })?;
-
-        // Get window size from first non-null value
-        self.window_size = window_size_array
-            .iter()
-            .find_map(|x| x)
-            .ok_or_else(|| {
-                DataFusionError::Execution("Window size cannot be null".to_string())
-            })? as usize;
+        self.configure(values)?;
+        let value_array = value_array(values)?;
 
         let mut result = Vec::with_capacity(num_rows);
-        self.values.clear();
-
         for i in 0..num_rows {
-            if let Some(value) = value_array.value(i).into() {
-                self.values.push(value);
-                
-                if self.values.len() >= self.window_size {
-                    let start_idx = self.values.len().saturating_sub(self.window_size);
-                    let window_sum: f64 = self.values[start_idx..].iter().sum();
-                    let sma = window_sum / self.window_size as f64;
-                    result.push(Some(sma));
-                } else {
-                    result.push(None);
-                }
-            } else {
-                result.push(None);
-            }
+            result.push(value_at(value_array, i).and_then(|value| self.push(value)));
         }
 
         Ok(Arc::new(Float64Array::from(result)))
     }
 
+    /// Streaming evaluation: only the running window is kept between rows
+    fn evaluate(&mut self, values: &[ArrayRef], range: &Range<usize>) -> Result<ScalarValue> {
+        if self.window_size == 0 {
+            self.configure(values)?;
+        }
+        let value = value_at(value_array(values)?, range.start).and_then(|value| self.push(value));
+        Ok(ScalarValue::Float64(value))
+    }
+
+    fn supports_bounded_execution(&self) -> bool {
+        true
+    }
+
+    fn is_causal(&self) -> bool {
+        true
+    }
+
     fn uses_window_frame(&self) -> bool {
         false
     }
@@ -153,7 +185,7 @@ mod tests {
 
         // Test SMA with window size 3 using SQL
         let result = ctx
-            .sql("SELECT price, sma(price, 3) OVER () AS sma_3 FROM (VALUES 
+            .sql("SELECT price, sma(price, 3) OVER () AS sma_3, sma(price, 3, 1) OVER () AS sma_3_partial FROM (VALUES 
                 (1.0), (2.0), (3.0), (4.0), (5.0), (6.0), (7.0), (8.0), (9.0), (10.0)
             ) AS t(price)")
             .await?
@@ -163,6 +195,19 @@ mod tests {
         println!("SMA Test Results:");
         datafusion::arrow::util::pretty::print_batches(&result)?;
 
+        // Test SMA over Float32, Decimal128 and integer inputs without explicit casts
+        let result = ctx
+            .sql("SELECT sma(CAST(price AS FLOAT), 3) OVER () AS sma_f32,
+                    sma(CAST(price AS DECIMAL(10, 2)), 3) OVER () AS sma_decimal,
+                    sma(CAST(price AS INT), CAST(3 AS INT)) OVER () AS sma_int
+                FROM (VALUES (1.0), (2.0), (3.0), (4.0), (5.0)) AS t(price)")
+            .await?
+            .collect()
+            .await?;
+
+        println!("SMA Coercion Test Results:");
+        datafusion::arrow::util::pretty::print_batches(&result)?;
+
         Ok(())
     }
 }
@@ -0,0 +1,223 @@
+use std::any::Any;
+
+use datafusion::arrow::array::ArrayRef;
+use datafusion::arrow::datatypes::DataType;
+use datafusion::error::Result;
+use datafusion::execution::context::SessionContext;
+use datafusion::logical_expr::function::WindowFunctionSimplification;
+use datafusion::logical_expr::{Signature, TypeSignature, Volatility, WindowUDF, WindowUDFImpl, PartitionEvaluator};
+
+use super::ohlcv::{HLC, OhlcvArgs};
+use super::utils::{check_arg_count, float64_arg, float64_struct_array, float64_struct_type, period_arg, value_at, RollingSum};
+use crate::validation::{period_simplification, PeriodArg};
+
+const STOCHASTIC_FIELDS: [&str; 2] = ["k", "d"];
+
+/// Stochastic oscillator returning %K and its %D signal line
+#[derive(Debug)]
+pub struct StochasticOscillator {
+    name: String,
+    signature: Signature,
+}
+
+impl StochasticOscillator {
+    pub fn new() -> Self {
+        Self {
+            name: "stochastic".to_string(),
+            signature: Signature::one_of(
+                vec![TypeSignature::Exact(vec![
+                    DataType::Float64,
+                    DataType::Float64,
+                    DataType::Float64,
+                    DataType::Int64,
+                    DataType::Int64,
+                ])],
+                Volatility::Immutable,
+            ),
+        }
+    }
+}
+
+impl WindowUDFImpl for StochasticOscillator {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn signature(&self) -> &Signature {
+        &self.signature
+    }
+
+    fn return_type(&self, _arg_types: &[DataType]) -> Result<DataType> {
+        Ok(float64_struct_type(&STOCHASTIC_FIELDS))
+    }
+
+    fn partition_evaluator(&self) -> Result<Box<dyn PartitionEvaluator>> {
+        Ok(Box::new(StochasticPartitionEvaluator::new()))
+    }
+
+    fn simplify(&self) -> Option<WindowFunctionSimplification> {
+        period_simplification(
+            &self.name,
+            vec![PeriodArg::rows(3, "k_period"), PeriodArg::rows(4, "d_period")],
+        )
+    }
+}
+
+#[derive(Debug)]
+struct StochasticPartitionEvaluator {
+    highs: Vec<f64>,
+    lows: Vec<f64>,
+    k_values: RollingSum,
+}
+
+impl StochasticPartitionEvaluator {
+    fn new() -> Self {
+        Self {
+            highs: Vec::new(),
+            lows: Vec::new(),
+            k_values: RollingSum::default(),
+        }
+    }
+}
+
+impl PartitionEvaluator for StochasticPartitionEvaluator {
+    fn evaluate_all(
+        &mut self,
+        values: &[ArrayRef],
+        num_rows: usize,
+    ) -> Result<ArrayRef> {
+        check_arg_count(values, 5, "Stochastic function (high, low, close, k_period, d_period)")?;
+
+        let high_array = float64_arg(values, 0, "High")?;
+        let low_array = float64_arg(values, 1, "Low")?;
+        let close_array = float64_arg(values, 2, "Close")?;
+        let k_period = period_arg(values, 3, "%K period")?;
+        let d_period = period_arg(values, 4, "%D period")?;
+
+        let mut k_result = Vec::with_capacity(num_rows);
+        let mut d_result = Vec::with_capacity(num_rows);
+        self.highs.clear();
+        self.lows.clear();
+        self.k_values.reset(d_period);
+
+        for i in 0..num_rows {
+            let (Some(high), Some(low), Some(close)) = (
+                value_at(high_array, i),
+                value_at(low_array, i),
+                value_at(close_array, i),
+            ) else {
+                k_result.push(None);
+                d_result.push(None);
+                continue;
+            };
+
+            self.highs.push(high);
+            self.lows.push(low);
+
+            if self.highs.len() < k_period {
+                k_result.push(None);
+                d_result.push(None);
+                continue;
+            }
+
+            let start_idx = self.highs.len() - k_period;
+            let highest = self.highs[start_idx..].iter().cloned().fold(f64::MIN, f64::max);
+            let lowest = self.lows[start_idx..].iter().cloned().fold(f64::MAX, f64::min);
+
+            // %K = 100 × (close - lowest low) / (highest high - lowest low)
+            // A flat range has no position within it, so report the midpoint
+            let k = if highest > lowest {
+                100.0 * (close - lowest) / (highest - lowest)
+            } else {
+                50.0
+            };
+            k_result.push(Some(k));
+
+            // %D = SMA(%K, d_period)
+            d_result.push(self.k_values.push(k).map(|sum| sum / d_period as f64));
+        }
+
+        Ok(float64_struct_array(&STOCHASTIC_FIELDS, vec![k_result, d_result]))
+    }
+
+    fn uses_window_frame(&self) -> bool {
+        false
+    }
+
+    fn include_rank(&self) -> bool {
+        false
+    }
+}
+
+pub fn register_stochastic(ctx: &SessionContext) -> Result<()> {
+    let stochastic_udf = WindowUDF::from(OhlcvArgs::new(StochasticOscillator::new(), HLC));
+    ctx.register_udwf(stochastic_udf);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::functions::utils::{assert_close, float64_column};
+    use datafusion::execution::context::SessionContext;
+
+    #[tokio::test]
+    async fn test_stochastic() -> Result<()> {
+        let ctx = SessionContext::new();
+        register_stochastic(&ctx)?;
+
+        // Test stochastic with %K period 5 and %D period 3 using SQL
+        let result = ctx
+            .sql("SELECT close, stochastic(high, low, close, 5, 3) OVER () AS stoch FROM (VALUES
+                (127.01, 125.36, 126.00), (127.62, 126.16, 126.60), (126.59, 124.93, 125.60),
+                (127.35, 126.09, 127.00), (128.17, 126.82, 127.50), (128.43, 126.48, 128.00),
+                (127.37, 126.03, 126.90), (126.42, 124.83, 125.20), (126.90, 126.39, 126.70),
+                (126.85, 125.72, 126.50)
+            ) AS t(high, low, close)")
+            .await?
+            .collect()
+            .await?;
+
+        println!("Stochastic Test Results:");
+        datafusion::arrow::util::pretty::print_batches(&result)?;
+
+        // %K places each close in its five-bar range, e.g. the fifth bar's
+        // (127.50 - 124.93) / (128.17 - 124.93) = 79.32; %D averages three %K values
+        let result = ctx
+            .sql("SELECT stoch['k'] AS k, stoch['d'] AS d
+                FROM (SELECT stochastic(high, low, close, 5, 3) OVER () AS stoch FROM (VALUES
+                    (127.01, 125.36, 126.00), (127.62, 126.16, 126.60), (126.59, 124.93, 125.60),
+                    (127.35, 126.09, 127.00), (128.17, 126.82, 127.50), (128.43, 126.48, 128.00),
+                    (127.37, 126.03, 126.90), (126.42, 124.83, 125.20), (126.90, 126.39, 126.70),
+                    (126.85, 125.72, 126.50)
+                ) AS t(high, low, close))")
+            .await?
+            .collect()
+            .await?;
+        let k = [79.320987654321, 87.714285714286, 56.285714285714, 10.277777777778, 51.944444444444, 46.388888888889];
+        let d = [74.440329218107, 51.425925925926, 39.502645502646, 36.203703703704];
+        let expected = |warm_up: usize, values: &[f64]| {
+            std::iter::repeat(None).take(warm_up).chain(values.iter().copied().map(Some)).collect::<Vec<_>>()
+        };
+        assert_close(&float64_column(&result, "k"), &expected(4, &k));
+        assert_close(&float64_column(&result, "d"), &expected(6, &d));
+
+        // A range with no width puts the close at its midpoint
+        let result = ctx
+            .sql("SELECT stoch['k'] AS k, stoch['d'] AS d
+                FROM (SELECT stochastic(high, low, close, 3, 2) OVER () AS stoch FROM (VALUES
+                    (10.0, 10.0, 10.0), (10.0, 10.0, 10.0), (10.0, 10.0, 10.0), (10.0, 10.0, 10.0)
+                ) AS t(high, low, close))")
+            .await?
+            .collect()
+            .await?;
+        assert_close(&float64_column(&result, "k"), &[None, None, Some(50.0), Some(50.0)]);
+        assert_close(&float64_column(&result, "d"), &[None, None, None, Some(50.0)]);
+
+        Ok(())
+    }
+}
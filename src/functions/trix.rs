@@ -0,0 +1,168 @@
+use std::any::Any;
+use std::sync::Arc;
+
+use datafusion::arrow::array::{ArrayRef, Float64Array};
+use datafusion::arrow::datatypes::DataType;
+use datafusion::error::Result;
+use datafusion::execution::context::SessionContext;
+use datafusion::logical_expr::function::WindowFunctionSimplification;
+use datafusion::logical_expr::{Signature, TypeSignature, Volatility, WindowUDF, WindowUDFImpl, PartitionEvaluator};
+
+use super::utils::{check_arg_count, float64_arg, period_arg, value_at};
+use crate::validation::{period_simplification, PeriodArg};
+
+/// TRIX: one-period percent rate of change of a triple-smoothed EMA
+#[derive(Debug)]
+pub struct Trix {
+    name: String,
+    signature: Signature,
+}
+
+impl Trix {
+    pub fn new() -> Self {
+        Self {
+            name: "trix".to_string(),
+            signature: Signature::one_of(
+                vec![TypeSignature::Exact(vec![DataType::Float64, DataType::Int64])],
+                Volatility::Immutable,
+            ),
+        }
+    }
+}
+
+impl WindowUDFImpl for Trix {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn signature(&self) -> &Signature {
+        &self.signature
+    }
+
+    fn return_type(&self, _arg_types: &[DataType]) -> Result<DataType> {
+        Ok(DataType::Float64)
+    }
+
+    fn partition_evaluator(&self) -> Result<Box<dyn PartitionEvaluator>> {
+        Ok(Box::new(TrixPartitionEvaluator::new()))
+    }
+
+    fn simplify(&self) -> Option<WindowFunctionSimplification> {
+        period_simplification(&self.name, vec![PeriodArg::rows(1, "period")])
+    }
+}
+
+#[derive(Debug)]
+struct TrixPartitionEvaluator {
+    alpha: f64,
+    ema1: Option<f64>,
+    ema2: Option<f64>,
+    ema3: Option<f64>,
+}
+
+impl TrixPartitionEvaluator {
+    fn new() -> Self {
+        Self {
+            alpha: 0.0,
+            ema1: None,
+            ema2: None,
+            ema3: None,
+        }
+    }
+
+    fn smooth(alpha: f64, prev: Option<f64>, value: f64) -> f64 {
+        match prev {
+            None => value,
+            Some(prev_ema) => alpha * value + (1.0 - alpha) * prev_ema,
+        }
+    }
+}
+
+impl PartitionEvaluator for TrixPartitionEvaluator {
+    fn evaluate_all(
+        &mut self,
+        values: &[ArrayRef],
+        num_rows: usize,
+    ) -> Result<ArrayRef> {
+        check_arg_count(values, 2, "TRIX function (value, period)")?;
+
+        let value_array = float64_arg(values, 0, "First argument")?;
+        let period = period_arg(values, 1, "Period")?;
+
+        self.alpha = 2.0 / (period as f64 + 1.0);
+        self.ema1 = None;
+        self.ema2 = None;
+        self.ema3 = None;
+
+        let mut result = Vec::with_capacity(num_rows);
+
+        for i in 0..num_rows {
+            let Some(value) = value_at(value_array, i) else {
+                result.push(None);
+                continue;
+            };
+
+            let ema1 = Self::smooth(self.alpha, self.ema1, value);
+            let ema2 = Self::smooth(self.alpha, self.ema2, ema1);
+            let ema3 = Self::smooth(self.alpha, self.ema3, ema2);
+
+            // TRIX = 100 × (EMA3 - previous EMA3) / previous EMA3
+            let trix = match self.ema3 {
+                Some(prev_ema3) if prev_ema3 != 0.0 => Some(100.0 * (ema3 - prev_ema3) / prev_ema3),
+                _ => None,
+            };
+
+            self.ema1 = Some(ema1);
+            self.ema2 = Some(ema2);
+            self.ema3 = Some(ema3);
+            result.push(trix);
+        }
+
+        Ok(Arc::new(Float64Array::from(result)))
+    }
+
+    fn uses_window_frame(&self) -> bool {
+        false
+    }
+
+    fn include_rank(&self) -> bool {
+        false
+    }
+}
+
+pub fn register_trix(ctx: &SessionContext) -> Result<()> {
+    let trix_udf = WindowUDF::from(Trix::new());
+    ctx.register_udwf(trix_udf);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use datafusion::execution::context::SessionContext;
+
+    #[tokio::test]
+    async fn test_trix() -> Result<()> {
+        let ctx = SessionContext::new();
+        register_trix(&ctx)?;
+
+        // Test TRIX with a 5 period window using SQL
+        let result = ctx
+            .sql("SELECT price, trix(price, 5) OVER () AS trix_5 FROM (VALUES
+                (100.0), (102.0), (98.0), (105.0), (107.0), (103.0), (110.0), (108.0),
+                (112.0), (115.0), (113.0), (118.0)
+            ) AS t(price)")
+            .await?
+            .collect()
+            .await?;
+
+        println!("TRIX Test Results:");
+        datafusion::arrow::util::pretty::print_batches(&result)?;
+
+        Ok(())
+    }
+}
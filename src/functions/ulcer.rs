@@ -0,0 +1,314 @@
+use std::any::Any;
+use std::sync::Arc;
+
+use datafusion::arrow::array::{ArrayRef, Float64Array};
+use datafusion::arrow::datatypes::{DataType, Field};
+use datafusion::error::{DataFusionError, Result};
+use datafusion::execution::context::SessionContext;
+use datafusion::logical_expr::function::{AccumulatorArgs, StateFieldsArgs};
+use datafusion::logical_expr::utils::format_state_name;
+use datafusion::logical_expr::function::WindowFunctionSimplification;
+use datafusion::logical_expr::{
+    Accumulator, AggregateUDF, AggregateUDFImpl, PartitionEvaluator, Signature, TypeSignature, Volatility,
+    WindowUDF, WindowUDFImpl,
+};
+use datafusion::scalar::ScalarValue;
+
+use super::utils::{check_arg_count, f64_param_arg, float64_arg, period_arg, value_at, OrderedSeries, RollingSum};
+use crate::validation::{period_simplification, PeriodArg};
+
+/// Ulcer Index: root mean square of percent drawdowns from the rolling high
+#[derive(Debug)]
+pub struct UlcerIndex {
+    name: String,
+    signature: Signature,
+}
+
+impl UlcerIndex {
+    pub fn new() -> Self {
+        Self {
+            name: "ulcer_index".to_string(),
+            signature: Signature::one_of(
+                vec![TypeSignature::Exact(vec![DataType::Float64, DataType::Int64])],
+                Volatility::Immutable,
+            ),
+        }
+    }
+}
+
+impl WindowUDFImpl for UlcerIndex {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn signature(&self) -> &Signature {
+        &self.signature
+    }
+
+    fn return_type(&self, _arg_types: &[DataType]) -> Result<DataType> {
+        Ok(DataType::Float64)
+    }
+
+    fn partition_evaluator(&self) -> Result<Box<dyn PartitionEvaluator>> {
+        Ok(Box::new(UlcerIndexPartitionEvaluator::new()))
+    }
+
+    fn simplify(&self) -> Option<WindowFunctionSimplification> {
+        period_simplification(&self.name, vec![PeriodArg::rows(1, "period")])
+    }
+}
+
+#[derive(Debug)]
+struct UlcerIndexPartitionEvaluator {
+    values: Vec<f64>,
+    squared_drawdowns: RollingSum,
+}
+
+impl UlcerIndexPartitionEvaluator {
+    fn new() -> Self {
+        Self {
+            values: Vec::new(),
+            squared_drawdowns: RollingSum::default(),
+        }
+    }
+}
+
+impl PartitionEvaluator for UlcerIndexPartitionEvaluator {
+    fn evaluate_all(
+        &mut self,
+        values: &[ArrayRef],
+        num_rows: usize,
+    ) -> Result<ArrayRef> {
+        check_arg_count(values, 2, "Ulcer index function (value, period)")?;
+
+        let value_array = float64_arg(values, 0, "First argument")?;
+        let period = period_arg(values, 1, "Period")?;
+
+        let mut result = Vec::with_capacity(num_rows);
+        self.values.clear();
+        self.squared_drawdowns.reset(period);
+
+        for i in 0..num_rows {
+            let Some(value) = value_at(value_array, i) else {
+                result.push(None);
+                continue;
+            };
+
+            self.values.push(value);
+
+            // Percent drawdown from the highest value of the lookback period
+            let start_idx = self.values.len().saturating_sub(period);
+            let highest = self.values[start_idx..].iter().cloned().fold(f64::MIN, f64::max);
+            let drawdown = if highest > 0.0 { 100.0 * (value - highest) / highest } else { 0.0 };
+
+            // UI = sqrt(Σ drawdown² / N)
+            let squared_sum = self.squared_drawdowns.push(drawdown * drawdown);
+            result.push(squared_sum.map(|sum| (sum / period as f64).sqrt()));
+        }
+
+        Ok(Arc::new(Float64Array::from(result)))
+    }
+
+    fn uses_window_frame(&self) -> bool {
+        false
+    }
+
+    fn include_rank(&self) -> bool {
+        false
+    }
+}
+
+/// Ulcer Performance Index (Martin ratio) over all prices in a group
+///
+/// Give the price order inside the call, e.g.
+/// `ulcer_performance_index(close ORDER BY window_start)`; the keys are
+/// carried through partial aggregation so the drawdowns hold across
+/// partitions. Without `ORDER BY` prices are taken in arrival order.
+#[derive(Debug)]
+pub struct UlcerPerformanceIndex {
+    name: String,
+    signature: Signature,
+}
+
+impl UlcerPerformanceIndex {
+    pub fn new() -> Self {
+        Self {
+            name: "ulcer_performance_index".to_string(),
+            signature: Signature::one_of(
+                vec![
+                    TypeSignature::Exact(vec![DataType::Float64]),
+                    TypeSignature::Exact(vec![DataType::Float64, DataType::Float64]),
+                ],
+                Volatility::Immutable,
+            ),
+        }
+    }
+}
+
+impl AggregateUDFImpl for UlcerPerformanceIndex {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn signature(&self) -> &Signature {
+        &self.signature
+    }
+
+    fn return_type(&self, _arg_types: &[DataType]) -> Result<DataType> {
+        Ok(DataType::Float64)
+    }
+
+    fn accumulator(&self, acc_args: AccumulatorArgs) -> Result<Box<dyn Accumulator>> {
+        Ok(Box::new(UlcerPerformanceAccumulator {
+            args: acc_args.exprs.len(),
+            prices: OrderedSeries::try_new(1, &acc_args)?,
+            risk_free: 0.0,
+        }))
+    }
+
+    fn state_fields(&self, args: StateFieldsArgs) -> Result<Vec<Field>> {
+        let mut fields = OrderedSeries::state_fields(args.name, &["prices"]);
+        fields.push(Field::new(format_state_name(args.name, "risk_free"), DataType::Float64, true));
+        Ok(fields)
+    }
+}
+
+#[derive(Debug)]
+struct UlcerPerformanceAccumulator {
+    /// Number of function arguments; any ORDER BY columns follow them
+    args: usize,
+    prices: OrderedSeries,
+    risk_free: f64,
+}
+
+impl Accumulator for UlcerPerformanceAccumulator {
+    fn update_batch(&mut self, values: &[ArrayRef]) -> Result<()> {
+        if !(1..=2).contains(&self.args) || values.len() < self.args {
+            return Err(DataFusionError::Execution(
+                "Ulcer performance index requires 1 or 2 arguments: value and optional risk_free".to_string(),
+            ));
+        }
+
+        let value_array = float64_arg(values, 0, "First argument")?;
+        self.prices.update(&[value_array], &values[self.args..])?;
+
+        if self.args == 2 && !values[1].is_empty() {
+            self.risk_free = f64_param_arg(values, 1, "Risk-free rate")?;
+        }
+
+        Ok(())
+    }
+
+    fn evaluate(&mut self) -> Result<ScalarValue> {
+        let prices = self.prices.sorted().swap_remove(0).into_iter().flatten().collect::<Vec<_>>();
+        let (Some(first), Some(last)) = (prices.first(), prices.last()) else {
+            return Ok(ScalarValue::Float64(None));
+        };
+        if *first == 0.0 {
+            return Ok(ScalarValue::Float64(None));
+        }
+
+        // Ulcer index over the whole series, drawdowns measured from the running high
+        let mut running_high = f64::MIN;
+        let mut sum_squares = 0.0;
+        for price in &prices {
+            running_high = running_high.max(*price);
+            let drawdown = if running_high > 0.0 { 100.0 * (price - running_high) / running_high } else { 0.0 };
+            sum_squares += drawdown * drawdown;
+        }
+        let ulcer_index = (sum_squares / prices.len() as f64).sqrt();
+        if ulcer_index == 0.0 {
+            return Ok(ScalarValue::Float64(None));
+        }
+
+        // UPI = (percent total return - risk-free rate) / ulcer index
+        let total_return = 100.0 * (last - first) / first;
+        Ok(ScalarValue::Float64(Some((total_return - self.risk_free) / ulcer_index)))
+    }
+
+    fn size(&self) -> usize {
+        std::mem::size_of_val(self) + self.prices.heap_size()
+    }
+
+    fn state(&mut self) -> Result<Vec<ScalarValue>> {
+        let mut state = self.prices.state();
+        state.push(ScalarValue::Float64(Some(self.risk_free)));
+        Ok(state)
+    }
+
+    fn merge_batch(&mut self, states: &[ArrayRef]) -> Result<()> {
+        self.prices.merge(states)?;
+        let risk_free_idx = self.prices.state_len();
+        if let Some(risk_free) = float64_arg(states, risk_free_idx, "Risk-free state")?.iter().flatten().last() {
+            self.risk_free = risk_free;
+        }
+        Ok(())
+    }
+}
+
+/// Register `ulcer_index` and `ulcer_performance_index`
+pub fn register_ulcer(ctx: &SessionContext) -> Result<()> {
+    ctx.register_udwf(WindowUDF::from(UlcerIndex::new()));
+    ctx.register_udaf(AggregateUDF::from(UlcerPerformanceIndex::new()));
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use datafusion::arrow::array::{AsArray, RecordBatch};
+    use datafusion::arrow::datatypes::Float64Type;
+    use datafusion::execution::context::SessionContext;
+
+    #[tokio::test]
+    async fn test_ulcer() -> Result<()> {
+        let ctx = SessionContext::new();
+        register_ulcer(&ctx)?;
+
+        // Test the rolling ulcer index with a 3 period window using SQL
+        let result = ctx
+            .sql("SELECT price, ulcer_index(price, 3) OVER () AS ui_3 FROM (VALUES
+                (100.0), (102.0), (98.0), (95.0), (99.0), (103.0), (101.0), (97.0)
+            ) AS t(price)")
+            .await?
+            .collect()
+            .await?;
+
+        println!("Ulcer Index Test Results:");
+        datafusion::arrow::util::pretty::print_batches(&result)?;
+
+        // Test the ulcer performance index aggregate using SQL
+        let result = ctx
+            .sql("SELECT ulcer_performance_index(price, 1.0) AS upi FROM (VALUES
+                (100.0), (102.0), (98.0), (95.0), (99.0), (103.0), (101.0), (107.0)
+            ) AS t(price)")
+            .await?
+            .collect()
+            .await?;
+
+        println!("Ulcer Performance Index Test Results:");
+        datafusion::arrow::util::pretty::print_batches(&result)?;
+
+        // Test that ORDER BY inside the call restores the series from shuffled rows
+        let ordered = ctx
+            .sql("SELECT ulcer_performance_index(price, 1.0 ORDER BY day) AS upi FROM (VALUES
+                (5, 99.0), (2, 102.0), (8, 107.0), (1, 100.0), (4, 95.0), (7, 101.0), (3, 98.0), (6, 103.0)
+            ) AS t(day, price)")
+            .await?
+            .collect()
+            .await?;
+
+        let upi = |batches: &[RecordBatch]| batches[0].column(0).as_primitive::<Float64Type>().value(0);
+        assert_eq!(upi(&ordered), upi(&result));
+
+        Ok(())
+    }
+}
@@ -0,0 +1,557 @@
+//! Shared argument handling and output helpers for the financial window functions
+
+use std::collections::VecDeque;
+use std::sync::Arc;
+
+use datafusion::arrow::array::{
+    Array, ArrayRef, AsArray, BinaryBuilder, Float64Array, Int64Array, ListArray, ListBuilder, StructArray,
+};
+use datafusion::arrow::compute::cast;
+use datafusion::arrow::datatypes::{DataType, Field, Fields, Float64Type, Int64Type, TimeUnit};
+use datafusion::arrow::row::{RowConverter, SortField};
+use datafusion::error::{DataFusionError, Result};
+use datafusion::logical_expr::function::AccumulatorArgs;
+use datafusion::logical_expr::utils::format_state_name;
+use datafusion::logical_expr::{TypeSignature, WindowUDFImpl};
+use datafusion::scalar::ScalarValue;
+
+/// Ensure the evaluator received the expected number of arguments
+pub(crate) fn check_arg_count(values: &[ArrayRef], expected: usize, usage: &str) -> Result<()> {
+    if values.len() != expected {
+        return Err(DataFusionError::Execution(format!(
+            "{} requires exactly {} arguments",
+            usage, expected
+        )));
+    }
+    Ok(())
+}
+
+/// Coerce argument types onto the first accepted layout with matching arity
+///
+/// Any numeric type (Float32, Decimal128, integers, ...) coerces to a Float64
+/// slot and any integer type to an Int64 slot; other slots must match exactly.
+pub(crate) fn coerce_numeric_args(name: &str, arg_types: &[DataType], accepted: &[Vec<DataType>]) -> Result<Vec<DataType>> {
+    for layout in accepted.iter().filter(|layout| layout.len() == arg_types.len()) {
+        let coerced = arg_types
+            .iter()
+            .zip(layout)
+            .map(|(actual, target)| match target {
+                _ if actual == target || matches!(actual, DataType::Null) => Some(target.clone()),
+                DataType::Float64 if actual.is_numeric() => Some(DataType::Float64),
+                DataType::Int64 if actual.is_integer() => Some(DataType::Int64),
+                _ => None,
+            })
+            .collect::<Option<Vec<_>>>();
+
+        if let Some(coerced) = coerced {
+            return Ok(coerced);
+        }
+    }
+
+    let usage = crate::docs::documentation(name)
+        .map(|doc| format!(". Expected {}", doc.syntax))
+        .unwrap_or_default();
+    Err(DataFusionError::Plan(format!(
+        "{} does not support argument types ({}){}",
+        name,
+        arg_types.iter().map(|t| t.to_string()).collect::<Vec<_>>().join(", "),
+        usage
+    )))
+}
+
+/// Coerce arguments for a wrapped window function, through its `Exact`
+/// layouts if it has them and its own `coerce_types` otherwise
+pub(crate) fn coerce_window_args(udf: &dyn WindowUDFImpl, arg_types: &[DataType]) -> Result<Vec<DataType>> {
+    match exact_layouts(&udf.signature().type_signature) {
+        Some(layouts) => coerce_numeric_args(udf.name(), arg_types, &layouts),
+        None => udf.coerce_types(arg_types),
+    }
+}
+
+/// The accepted layouts of an `Exact` / `OneOf(Exact..)` signature
+fn exact_layouts(signature: &TypeSignature) -> Option<Vec<Vec<DataType>>> {
+    match signature {
+        TypeSignature::Exact(types) => Some(vec![types.clone()]),
+        TypeSignature::OneOf(signatures) => signatures
+            .iter()
+            .map(|signature| match signature {
+                TypeSignature::Exact(types) => Some(types.clone()),
+                _ => None,
+            })
+            .collect(),
+        _ => None,
+    }
+}
+
+/// Downcast the argument at `idx` to a Float64 array
+pub(crate) fn float64_arg<'a>(values: &'a [ArrayRef], idx: usize, name: &str) -> Result<&'a Float64Array> {
+    values[idx]
+        .as_any()
+        .downcast_ref::<Float64Array>()
+        .ok_or_else(|| DataFusionError::Execution(format!("{} must be Float64", name)))
+}
+
+/// Read a positive period from the first non-null value of an Int64 argument
+pub(crate) fn period_arg(values: &[ArrayRef], idx: usize, name: &str) -> Result<usize> {
+    let period = values[idx]
+        .as_any()
+        .downcast_ref::<Int64Array>()
+        .ok_or_else(|| DataFusionError::Execution(format!("{} must be Int64", name)))?
+        .iter()
+        .find_map(|x| x)
+        .ok_or_else(|| DataFusionError::Execution(format!("{} cannot be null", name)))?;
+
+    if period <= 0 {
+        return Err(DataFusionError::Execution(format!(
+            "{} must be positive, got {}",
+            name, period
+        )));
+    }
+
+    Ok(period as usize)
+}
+
+/// Read an optional `min_periods` argument at `idx`, falling back to `default`
+///
+/// Lets partial windows at the start of a series emit values once at least
+/// `min_periods` observations are available, like pandas' `min_periods`.
+pub(crate) fn min_periods_arg(values: &[ArrayRef], idx: usize, period: usize, default: usize) -> Result<usize> {
+    if values.len() <= idx {
+        return Ok(default);
+    }
+
+    let min_periods = period_arg(values, idx, "min_periods")?;
+    if min_periods > period {
+        return Err(DataFusionError::Execution(format!(
+            "min_periods ({}) cannot exceed the period ({})",
+            min_periods, period
+        )));
+    }
+    Ok(min_periods)
+}
+
+/// Read a constant Float64 parameter from the first non-null value of an argument
+pub(crate) fn f64_param_arg(values: &[ArrayRef], idx: usize, name: &str) -> Result<f64> {
+    float64_arg(values, idx, name)?
+        .iter()
+        .find_map(|x| x)
+        .ok_or_else(|| DataFusionError::Execution(format!("{} cannot be null", name)))
+}
+
+/// Timestamp argument types accepted by the time-aware functions: raw epoch
+/// nanoseconds (as in the Polygon flat files) or a nanosecond timestamp
+pub(crate) fn timestamp_arg_types() -> Vec<DataType> {
+    vec![DataType::Int64, DataType::Timestamp(TimeUnit::Nanosecond, None)]
+}
+
+/// Read a timestamp argument as epoch nanoseconds
+pub(crate) fn timestamp_nanos_arg(values: &[ArrayRef], idx: usize, name: &str) -> Result<Int64Array> {
+    match values[idx].data_type() {
+        DataType::Int64 | DataType::Timestamp(TimeUnit::Nanosecond, _) => {
+            Ok(cast(&values[idx], &DataType::Int64)?.as_primitive::<Int64Type>().clone())
+        }
+        other => Err(DataFusionError::Execution(format!(
+            "{} must be Int64 nanoseconds or Timestamp(Nanosecond), got {}",
+            name, other
+        ))),
+    }
+}
+
+/// Read the value at row `i`, treating nulls as missing
+pub(crate) fn value_at(array: &Float64Array, i: usize) -> Option<f64> {
+    if array.is_valid(i) {
+        Some(array.value(i))
+    } else {
+        None
+    }
+}
+
+/// Struct type with one nullable Float64 field per name
+pub(crate) fn float64_struct_type(names: &[&str]) -> DataType {
+    DataType::Struct(Fields::from(
+        names
+            .iter()
+            .map(|name| Field::new(*name, DataType::Float64, true))
+            .collect::<Vec<_>>(),
+    ))
+}
+
+/// Build a struct array from named Float64 columns of equal length
+pub(crate) fn float64_struct_array(names: &[&str], columns: Vec<Vec<Option<f64>>>) -> ArrayRef {
+    let children = names
+        .iter()
+        .zip(columns)
+        .map(|(name, column)| {
+            (
+                Arc::new(Field::new(*name, DataType::Float64, true)),
+                Arc::new(Float64Array::from(column)) as ArrayRef,
+            )
+        })
+        .collect::<Vec<_>>();
+
+    Arc::new(StructArray::from(children))
+}
+
+/// Percent rate of change between the latest value and the one `period` values earlier
+pub(crate) fn rate_of_change(values: &[f64], period: usize) -> Option<f64> {
+    let n = values.len();
+    if n <= period {
+        return None;
+    }
+    let base = values[n - 1 - period];
+    if base == 0.0 {
+        return None;
+    }
+    Some(100.0 * (values[n - 1] - base) / base)
+}
+
+/// Accumulator state field holding a list of Float64 values
+pub(crate) fn float64_list_field(name: String) -> Field {
+    Field::new_list(name, Field::new("item", DataType::Float64, true), true)
+}
+
+/// Pack buffered values into a single-row list scalar for accumulator state
+pub(crate) fn float64_list_scalar(values: &[f64]) -> ScalarValue {
+    let list = ListArray::from_iter_primitive::<Float64Type, _, _>(vec![Some(
+        values.iter().map(|v| Some(*v)).collect::<Vec<_>>(),
+    )]);
+    ScalarValue::List(Arc::new(list))
+}
+
+/// Append every non-null value of a list-typed state column to `out`
+pub(crate) fn extend_from_list_state(state: &ArrayRef, out: &mut Vec<f64>) -> Result<()> {
+    let lists = state.as_list_opt::<i32>().ok_or_else(|| {
+        DataFusionError::Internal("Accumulator state must be a list of Float64".to_string())
+    })?;
+
+    for list in lists.iter().flatten() {
+        let values = list
+            .as_any()
+            .downcast_ref::<Float64Array>()
+            .ok_or_else(|| DataFusionError::Internal("Accumulator state must be a list of Float64".to_string()))?;
+        out.extend(values.iter().flatten());
+    }
+
+    Ok(())
+}
+
+/// Rows of one or more Float64 series buffered by an order-sensitive
+/// aggregate, together with their `ORDER BY` keys
+///
+/// Each partial aggregate receives its own rows in order, but partial states
+/// reach the final aggregate in whatever order their partitions finish. The
+/// keys are encoded in arrow's row format, whose bytes compare like the
+/// `ORDER BY` itself, and travel in the state so merged rows can be put back
+/// in order. Without an `ORDER BY` no keys are kept and rows stay in arrival
+/// order. Nulls are kept so the series line up with the input rows.
+#[derive(Debug)]
+pub(crate) struct OrderedSeries {
+    series: Vec<Vec<Option<f64>>>,
+    keys: Vec<Vec<u8>>,
+    converter: Option<RowConverter>,
+}
+
+impl OrderedSeries {
+    /// Buffer `inputs` series, keyed by the aggregate's `ORDER BY` if it has one
+    pub(crate) fn try_new(inputs: usize, acc_args: &AccumulatorArgs) -> Result<Self> {
+        let converter = if acc_args.ordering_req.is_empty() {
+            None
+        } else {
+            let fields = acc_args
+                .ordering_req
+                .iter()
+                .map(|sort| Ok(SortField::new_with_options(sort.expr.data_type(acc_args.schema)?, sort.options)))
+                .collect::<Result<Vec<_>>>()?;
+            Some(RowConverter::new(fields)?)
+        };
+
+        Ok(Self {
+            series: vec![Vec::new(); inputs.max(1)],
+            keys: Vec::new(),
+            converter,
+        })
+    }
+
+    /// State fields: a Float64 list per named series, then the list of keys
+    pub(crate) fn state_fields<S: AsRef<str>>(name: &str, series: &[S]) -> Vec<Field> {
+        let mut fields = series
+            .iter()
+            .map(|series| float64_list_field(format_state_name(name, series.as_ref())))
+            .collect::<Vec<_>>();
+        fields.push(Field::new_list(
+            format_state_name(name, "order_keys"),
+            Field::new("item", DataType::Binary, true),
+            true,
+        ));
+        fields
+    }
+
+    /// Number of state columns `state` produces and `merge` consumes
+    pub(crate) fn state_len(&self) -> usize {
+        self.series.len() + 1
+    }
+
+    /// Append a batch of rows; `order` holds the `ORDER BY` columns that
+    /// DataFusion passes after the function's own arguments
+    pub(crate) fn update(&mut self, series: &[&Float64Array], order: &[ArrayRef]) -> Result<()> {
+        for (buffer, array) in self.series.iter_mut().zip(series) {
+            buffer.extend(array.iter());
+        }
+        if let Some(converter) = &self.converter {
+            let rows = converter.convert_columns(order)?;
+            self.keys.extend(rows.iter().map(|row| row.as_ref().to_vec()));
+        }
+        Ok(())
+    }
+
+    /// Append the rows of partial states, read from the first `state_len` columns
+    pub(crate) fn merge(&mut self, states: &[ArrayRef]) -> Result<()> {
+        let invalid = || DataFusionError::Internal("Ordered series state has an unexpected layout".to_string());
+        let lists = states
+            .get(..self.state_len())
+            .ok_or_else(invalid)?
+            .iter()
+            .map(|state| state.as_list_opt::<i32>().ok_or_else(invalid))
+            .collect::<Result<Vec<_>>>()?;
+        let (key_lists, series_lists) = lists.split_last().ok_or_else(invalid)?;
+
+        for row in (0..key_lists.len()).filter(|&row| key_lists.is_valid(row)) {
+            for (buffer, lists) in self.series.iter_mut().zip(series_lists) {
+                let values = lists.value(row);
+                buffer.extend(values.as_primitive_opt::<Float64Type>().ok_or_else(invalid)?.iter());
+            }
+            let keys = key_lists.value(row);
+            self.keys
+                .extend(keys.as_binary_opt::<i32>().ok_or_else(invalid)?.iter().flatten().map(<[u8]>::to_vec));
+        }
+        Ok(())
+    }
+
+    /// Pack the buffered rows and keys into single-row list scalars
+    pub(crate) fn state(&self) -> Vec<ScalarValue> {
+        let mut state = self
+            .series
+            .iter()
+            .map(|series| {
+                let list = ListArray::from_iter_primitive::<Float64Type, _, _>(vec![Some(series.clone())]);
+                ScalarValue::List(Arc::new(list))
+            })
+            .collect::<Vec<_>>();
+
+        let mut keys = ListBuilder::new(BinaryBuilder::new());
+        for key in &self.keys {
+            keys.values().append_value(key);
+        }
+        keys.append(true);
+        state.push(ScalarValue::List(Arc::new(keys.finish())));
+        state
+    }
+
+    /// The buffered series, sorted by key when there is an `ORDER BY`
+    ///
+    /// The sort is stable, so rows with equal keys keep their arrival order.
+    pub(crate) fn sorted(&self) -> Vec<Vec<Option<f64>>> {
+        let len = self.series[0].len();
+        if self.keys.len() != len {
+            return self.series.clone();
+        }
+
+        let mut order = (0..len).collect::<Vec<_>>();
+        order.sort_by(|&a, &b| self.keys[a].cmp(&self.keys[b]));
+        self.series
+            .iter()
+            .map(|series| order.iter().map(|&i| series[i]).collect())
+            .collect()
+    }
+
+    /// Bytes held on the heap by the buffered rows and keys
+    pub(crate) fn heap_size(&self) -> usize {
+        self.series
+            .iter()
+            .map(|series| series.capacity() * std::mem::size_of::<Option<f64>>())
+            .sum::<usize>()
+            + self.keys.iter().map(|key| key.capacity()).sum::<usize>()
+            + self.keys.capacity() * std::mem::size_of::<Vec<u8>>()
+    }
+}
+
+/// Fixed-length window with an O(1) running sum
+///
+/// The sum is recomputed from the window once every `period` evictions so
+/// floating-point drift from repeated add/subtract stays bounded.
+#[derive(Debug, Default)]
+pub(crate) struct RollingSum {
+    window: VecDeque<f64>,
+    period: usize,
+    sum: f64,
+    evictions: usize,
+}
+
+impl RollingSum {
+    pub(crate) fn new(period: usize) -> Self {
+        Self {
+            window: VecDeque::new(),
+            period,
+            sum: 0.0,
+            evictions: 0,
+        }
+    }
+
+    /// Clear the window and start over with a new period
+    pub(crate) fn reset(&mut self, period: usize) {
+        *self = Self::new(period);
+    }
+
+    /// Add a value, evicting the oldest once the window is full, and return
+    /// the window sum if the window holds `period` values
+    pub(crate) fn push(&mut self, value: f64) -> Option<f64> {
+        self.window.push_back(value);
+        self.sum += value;
+
+        if self.window.len() > self.period {
+            if let Some(evicted) = self.window.pop_front() {
+                self.sum -= evicted;
+                self.evictions += 1;
+            }
+            if self.evictions >= self.period {
+                self.sum = self.window.iter().sum();
+                self.evictions = 0;
+            }
+        }
+
+        self.is_full().then_some(self.sum)
+    }
+
+    pub(crate) fn is_full(&self) -> bool {
+        self.window.len() >= self.period
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.window.len()
+    }
+
+    pub(crate) fn sum(&self) -> f64 {
+        self.sum
+    }
+
+    /// The window, oldest first
+    pub(crate) fn values(&self) -> impl Iterator<Item = f64> + '_ {
+        self.window.iter().copied()
+    }
+}
+
+/// Fixed-length window with an O(1) running mean and sum of squared
+/// deviations
+///
+/// Welford's update, extended so a full window replaces its oldest value in
+/// one step. As with [`RollingSum`], both are recomputed from the window once
+/// every `period` evictions to bound drift.
+#[derive(Debug, Default)]
+pub(crate) struct RollingMoments {
+    window: VecDeque<f64>,
+    period: usize,
+    mean: f64,
+    m2: f64,
+    evictions: usize,
+}
+
+impl RollingMoments {
+    pub(crate) fn new(period: usize) -> Self {
+        Self {
+            window: VecDeque::new(),
+            period,
+            mean: 0.0,
+            m2: 0.0,
+            evictions: 0,
+        }
+    }
+
+    /// Clear the window and start over with a new period
+    pub(crate) fn reset(&mut self, period: usize) {
+        *self = Self::new(period);
+    }
+
+    /// Add a value, returning the oldest one if the window was already full
+    pub(crate) fn push(&mut self, value: f64) -> Option<f64> {
+        self.window.push_back(value);
+
+        if self.window.len() <= self.period {
+            let delta = value - self.mean;
+            self.mean += delta / self.window.len() as f64;
+            self.m2 += delta * (value - self.mean);
+            return None;
+        }
+
+        let evicted = self.window.pop_front()?;
+        let previous_mean = self.mean;
+        self.mean += (value - evicted) / self.period as f64;
+        self.m2 += (value - evicted) * (value - self.mean + evicted - previous_mean);
+
+        self.evictions += 1;
+        if self.evictions >= self.period {
+            let n = self.window.len() as f64;
+            self.mean = self.window.iter().sum::<f64>() / n;
+            self.m2 = self.window.iter().map(|v| (v - self.mean).powi(2)).sum();
+            self.evictions = 0;
+        }
+        Some(evicted)
+    }
+
+    pub(crate) fn is_full(&self) -> bool {
+        self.window.len() >= self.period
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.window.len()
+    }
+
+    pub(crate) fn mean(&self) -> f64 {
+        self.mean
+    }
+
+    /// Sum of squared deviations from the mean
+    pub(crate) fn sum_squares(&self) -> f64 {
+        self.m2.max(0.0)
+    }
+
+    /// Variance with an N denominator
+    pub(crate) fn population_variance(&self) -> f64 {
+        self.sum_squares() / self.window.len().max(1) as f64
+    }
+
+    /// Variance with an N - 1 denominator, matching [`mean_and_variance`]
+    ///
+    /// [`mean_and_variance`]: super::rolling_stats::mean_and_variance
+    pub(crate) fn sample_variance(&self) -> Option<f64> {
+        (self.window.len() >= 2).then(|| self.sum_squares() / (self.window.len() - 1) as f64)
+    }
+
+    /// The window, oldest first
+    pub(crate) fn values(&self) -> impl Iterator<Item = f64> + '_ {
+        self.window.iter().copied()
+    }
+}
+
+/// Float64 values of the column `name` across `batches`
+#[cfg(test)]
+pub(crate) fn float64_column(batches: &[datafusion::arrow::array::RecordBatch], name: &str) -> Vec<Option<f64>> {
+    batches
+        .iter()
+        .flat_map(|batch| {
+            let column = batch.column_by_name(name).unwrap_or_else(|| panic!("no column {}", name));
+            column.as_primitive::<Float64Type>().iter().collect::<Vec<_>>()
+        })
+        .collect()
+}
+
+/// Assert that `actual` is `expected` to within 1e-6, with NULLs in the same rows
+#[cfg(test)]
+pub(crate) fn assert_close(actual: &[Option<f64>], expected: &[Option<f64>]) {
+    assert_eq!(actual.len(), expected.len(), "{:?} vs {:?}", actual, expected);
+    for (row, (actual, expected)) in actual.iter().zip(expected).enumerate() {
+        match (actual, expected) {
+            (Some(a), Some(e)) => assert!((a - e).abs() < 1e-6, "row {}: {} vs {}", row, a, e),
+            _ => assert_eq!(actual, expected, "row {}", row),
+        }
+    }
+}
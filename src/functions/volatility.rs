@@ -0,0 +1,161 @@
+use std::any::Any;
+use std::sync::Arc;
+
+use datafusion::arrow::array::{ArrayRef, Float64Array};
+use datafusion::arrow::datatypes::DataType;
+use datafusion::error::Result;
+use datafusion::execution::context::SessionContext;
+use datafusion::logical_expr::function::WindowFunctionSimplification;
+use datafusion::logical_expr::{Signature, TypeSignature, Volatility, WindowUDF, WindowUDFImpl, PartitionEvaluator};
+
+use super::rolling_stats::mean_and_variance;
+use super::utils::{check_arg_count, f64_param_arg, float64_arg, period_arg, value_at};
+use crate::validation::{period_simplification, PeriodArg};
+
+/// Annualized realized volatility from the rolling standard deviation of log returns
+#[derive(Debug)]
+pub struct RealizedVolatility {
+    name: String,
+    signature: Signature,
+}
+
+impl RealizedVolatility {
+    pub fn new() -> Self {
+        Self {
+            name: "volatility".to_string(),
+            signature: Signature::one_of(
+                vec![TypeSignature::Exact(vec![DataType::Float64, DataType::Int64, DataType::Float64])],
+                Volatility::Immutable,
+            ),
+        }
+    }
+}
+
+impl WindowUDFImpl for RealizedVolatility {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn signature(&self) -> &Signature {
+        &self.signature
+    }
+
+    fn return_type(&self, _arg_types: &[DataType]) -> Result<DataType> {
+        Ok(DataType::Float64)
+    }
+
+    fn partition_evaluator(&self) -> Result<Box<dyn PartitionEvaluator>> {
+        Ok(Box::new(VolatilityPartitionEvaluator::new()))
+    }
+
+    fn simplify(&self) -> Option<WindowFunctionSimplification> {
+        period_simplification(&self.name, vec![PeriodArg::rows(1, "period")])
+    }
+}
+
+#[derive(Debug)]
+struct VolatilityPartitionEvaluator {
+    prev_price: Option<f64>,
+    log_returns: Vec<f64>,
+}
+
+impl VolatilityPartitionEvaluator {
+    fn new() -> Self {
+        Self {
+            prev_price: None,
+            log_returns: Vec::new(),
+        }
+    }
+}
+
+impl PartitionEvaluator for VolatilityPartitionEvaluator {
+    fn evaluate_all(
+        &mut self,
+        values: &[ArrayRef],
+        num_rows: usize,
+    ) -> Result<ArrayRef> {
+        check_arg_count(values, 3, "Volatility function (price, period, annualization_factor)")?;
+
+        let price_array = float64_arg(values, 0, "Price")?;
+        let period = period_arg(values, 1, "Period")?;
+        let annualization = f64_param_arg(values, 2, "Annualization factor")?.sqrt();
+
+        let mut result = Vec::with_capacity(num_rows);
+        self.prev_price = None;
+        self.log_returns.clear();
+
+        for i in 0..num_rows {
+            let Some(price) = value_at(price_array, i) else {
+                result.push(None);
+                continue;
+            };
+
+            let Some(prev_price) = self.prev_price.replace(price) else {
+                result.push(None);
+                continue;
+            };
+
+            if prev_price <= 0.0 || price <= 0.0 {
+                result.push(None);
+                continue;
+            }
+            self.log_returns.push((price / prev_price).ln());
+
+            if self.log_returns.len() < period {
+                result.push(None);
+                continue;
+            }
+
+            // σ_annual = stddev(ln(P_t / P_t-1)) × sqrt(annualization factor)
+            let (_, variance) = mean_and_variance(&self.log_returns[self.log_returns.len() - period..]);
+            result.push(variance.map(|v| v.sqrt() * annualization));
+        }
+
+        Ok(Arc::new(Float64Array::from(result)))
+    }
+
+    fn uses_window_frame(&self) -> bool {
+        false
+    }
+
+    fn include_rank(&self) -> bool {
+        false
+    }
+}
+
+pub fn register_volatility(ctx: &SessionContext) -> Result<()> {
+    let volatility_udf = WindowUDF::from(RealizedVolatility::new());
+    ctx.register_udwf(volatility_udf);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use datafusion::execution::context::SessionContext;
+
+    #[tokio::test]
+    async fn test_volatility() -> Result<()> {
+        let ctx = SessionContext::new();
+        register_volatility(&ctx)?;
+
+        // Test 5 period volatility annualized with 252 trading days using SQL
+        let result = ctx
+            .sql("SELECT price, volatility(price, 5, 252.0) OVER () AS vol_5 FROM (VALUES
+                (100.0), (101.5), (99.8), (102.3), (103.1), (101.9), (104.6), (105.2),
+                (103.7), (106.0)
+            ) AS t(price)")
+            .await?
+            .collect()
+            .await?;
+
+        println!("Volatility Test Results:");
+        datafusion::arrow::util::pretty::print_batches(&result)?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,228 @@
+use std::any::Any;
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use datafusion::arrow::array::{Array, ArrayRef, Float64Array, ListArray, StructArray};
+use datafusion::arrow::datatypes::{DataType, Field, Fields, Float64Type};
+use datafusion::error::{DataFusionError, Result};
+use datafusion::execution::context::SessionContext;
+use datafusion::logical_expr::function::{AccumulatorArgs, StateFieldsArgs};
+use datafusion::logical_expr::utils::format_state_name;
+use datafusion::logical_expr::{Accumulator, AggregateUDF, AggregateUDFImpl, Signature, TypeSignature, Volatility};
+use datafusion::scalar::ScalarValue;
+
+use super::utils::{
+    check_arg_count, extend_from_list_state, f64_param_arg, float64_arg, float64_list_field, float64_list_scalar,
+    value_at,
+};
+
+/// Volume traded at each price level, plus the point of control
+///
+/// Prices are bucketed to the lower edge of `bin_size` wide bins. The result
+/// is a struct of `poc` (the level with the most volume) and parallel
+/// `prices` / `volumes` lists in ascending price order.
+#[derive(Debug)]
+pub struct VolumeProfile {
+    name: String,
+    signature: Signature,
+}
+
+impl VolumeProfile {
+    pub fn new() -> Self {
+        Self {
+            name: "volume_profile".to_string(),
+            signature: Signature::one_of(
+                vec![TypeSignature::Exact(vec![DataType::Float64, DataType::Float64, DataType::Float64])],
+                Volatility::Immutable,
+            ),
+        }
+    }
+}
+
+fn volume_profile_fields() -> Fields {
+    Fields::from(vec![
+        Field::new("poc", DataType::Float64, true),
+        float64_list_field("prices".to_string()),
+        float64_list_field("volumes".to_string()),
+    ])
+}
+
+impl AggregateUDFImpl for VolumeProfile {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn signature(&self) -> &Signature {
+        &self.signature
+    }
+
+    fn return_type(&self, _arg_types: &[DataType]) -> Result<DataType> {
+        Ok(DataType::Struct(volume_profile_fields()))
+    }
+
+    fn accumulator(&self, _acc_args: AccumulatorArgs) -> Result<Box<dyn Accumulator>> {
+        Ok(Box::new(VolumeProfileAccumulator::new()))
+    }
+
+    fn state_fields(&self, args: StateFieldsArgs) -> Result<Vec<Field>> {
+        Ok(vec![
+            float64_list_field(format_state_name(args.name, "prices")),
+            float64_list_field(format_state_name(args.name, "volumes")),
+            Field::new(format_state_name(args.name, "bin_size"), DataType::Float64, true),
+        ])
+    }
+}
+
+#[derive(Debug)]
+struct VolumeProfileAccumulator {
+    /// Volume keyed by bin index (lower edge = index × bin size)
+    bins: BTreeMap<i64, f64>,
+    bin_size: Option<f64>,
+}
+
+impl VolumeProfileAccumulator {
+    fn new() -> Self {
+        Self {
+            bins: BTreeMap::new(),
+            bin_size: None,
+        }
+    }
+
+    fn add(&mut self, price: f64, volume: f64, bin_size: f64) {
+        let bin = (price / bin_size).floor() as i64;
+        *self.bins.entry(bin).or_insert(0.0) += volume;
+    }
+
+    fn levels(&self) -> (Vec<f64>, Vec<f64>) {
+        let bin_size = self.bin_size.unwrap_or(1.0);
+        self.bins
+            .iter()
+            .map(|(bin, volume)| (*bin as f64 * bin_size, *volume))
+            .unzip()
+    }
+}
+
+impl Accumulator for VolumeProfileAccumulator {
+    fn update_batch(&mut self, values: &[ArrayRef]) -> Result<()> {
+        check_arg_count(values, 3, "volume_profile(price, volume, bin_size)")?;
+
+        let price_array = float64_arg(values, 0, "Price")?;
+        let volume_array = float64_arg(values, 1, "Volume")?;
+        if price_array.is_empty() {
+            return Ok(());
+        }
+
+        let bin_size = f64_param_arg(values, 2, "Bin size")?;
+        if bin_size <= 0.0 {
+            return Err(DataFusionError::Execution(format!(
+                "Bin size must be positive, got {}",
+                bin_size
+            )));
+        }
+        self.bin_size = Some(bin_size);
+
+        for i in 0..price_array.len() {
+            if let (Some(price), Some(volume)) = (value_at(price_array, i), value_at(volume_array, i)) {
+                self.add(price, volume, bin_size);
+            }
+        }
+        Ok(())
+    }
+
+    fn evaluate(&mut self) -> Result<ScalarValue> {
+        let (prices, volumes) = self.levels();
+        // Point of control = price level with the highest traded volume
+        let poc = prices
+            .iter()
+            .zip(&volumes)
+            .max_by(|a, b| a.1.total_cmp(b.1))
+            .map(|(price, _)| *price);
+
+        let to_list = |values: &[f64]| -> ArrayRef {
+            Arc::new(ListArray::from_iter_primitive::<Float64Type, _, _>(vec![Some(
+                values.iter().map(|v| Some(*v)).collect::<Vec<_>>(),
+            )]))
+        };
+
+        let profile = StructArray::new(
+            volume_profile_fields(),
+            vec![
+                Arc::new(Float64Array::from(vec![poc])) as ArrayRef,
+                to_list(&prices),
+                to_list(&volumes),
+            ],
+            None,
+        );
+        Ok(ScalarValue::Struct(Arc::new(profile)))
+    }
+
+    fn size(&self) -> usize {
+        std::mem::size_of_val(self) + self.bins.len() * (std::mem::size_of::<i64>() + std::mem::size_of::<f64>())
+    }
+
+    fn state(&mut self) -> Result<Vec<ScalarValue>> {
+        let (prices, volumes) = self.levels();
+        Ok(vec![
+            float64_list_scalar(&prices),
+            float64_list_scalar(&volumes),
+            ScalarValue::Float64(self.bin_size),
+        ])
+    }
+
+    fn merge_batch(&mut self, states: &[ArrayRef]) -> Result<()> {
+        let Some(bin_size) = float64_arg(states, 2, "Bin size state")?.iter().flatten().last() else {
+            return Ok(());
+        };
+        self.bin_size = Some(bin_size);
+
+        let mut prices = Vec::new();
+        let mut volumes = Vec::new();
+        extend_from_list_state(&states[0], &mut prices)?;
+        extend_from_list_state(&states[1], &mut volumes)?;
+        // Prices in state are bin lower edges, so re-binning is exact up to rounding
+        for (price, volume) in prices.into_iter().zip(volumes) {
+            self.add(price + bin_size / 2.0, volume, bin_size);
+        }
+        Ok(())
+    }
+}
+
+pub fn register_volume_profile(ctx: &SessionContext) -> Result<()> {
+    let volume_profile_udaf = AggregateUDF::from(VolumeProfile::new());
+    ctx.register_udaf(volume_profile_udaf);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use datafusion::execution::context::SessionContext;
+
+    #[tokio::test]
+    async fn test_volume_profile() -> Result<()> {
+        let ctx = SessionContext::new();
+        register_volume_profile(&ctx)?;
+
+        // Test $1 volume profile per ticker using SQL
+        let result = ctx
+            .sql("SELECT ticker, volume_profile(price, volume, 1.0) AS profile
+                FROM (VALUES
+                ('AAPL', 100.2, 500.0), ('AAPL', 100.8, 700.0), ('AAPL', 101.4, 300.0), ('AAPL', 102.1, 900.0),
+                ('MSFT', 300.5, 200.0), ('MSFT', 301.2, 400.0), ('MSFT', 300.9, 100.0)
+            ) AS t(ticker, price, volume)
+                GROUP BY ticker
+                ORDER BY ticker")
+            .await?
+            .collect()
+            .await?;
+
+        println!("Volume Profile Test Results:");
+        datafusion::arrow::util::pretty::print_batches(&result)?;
+
+        Ok(())
+    }
+}
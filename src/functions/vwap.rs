@@ -0,0 +1,216 @@
+use std::any::Any;
+use std::collections::VecDeque;
+use std::ops::Range;
+use std::sync::Arc;
+
+use datafusion::arrow::array::{ArrayRef, Float64Array};
+use datafusion::arrow::datatypes::DataType;
+use datafusion::error::{DataFusionError, Result};
+use datafusion::execution::context::SessionContext;
+use datafusion::logical_expr::function::WindowFunctionSimplification;
+use datafusion::logical_expr::{Signature, TypeSignature, Volatility, WindowUDF, WindowUDFImpl, PartitionEvaluator};
+use datafusion::scalar::ScalarValue;
+
+use super::utils::{float64_arg, period_arg, value_at};
+use crate::validation::{period_simplification, PeriodArg};
+
+/// Volume Weighted Average Price, cumulative over the partition or rolling over a period
+#[derive(Debug)]
+pub struct VolumeWeightedAveragePrice {
+    name: String,
+    signature: Signature,
+}
+
+impl VolumeWeightedAveragePrice {
+    pub fn new() -> Self {
+        Self {
+            name: "vwap".to_string(),
+            signature: Signature::one_of(
+                vec![
+                    TypeSignature::Exact(vec![DataType::Float64, DataType::Float64]),
+                    TypeSignature::Exact(vec![DataType::Float64, DataType::Float64, DataType::Int64]),
+                ],
+                Volatility::Immutable,
+            ),
+        }
+    }
+}
+
+impl WindowUDFImpl for VolumeWeightedAveragePrice {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn signature(&self) -> &Signature {
+        &self.signature
+    }
+
+    fn return_type(&self, _arg_types: &[DataType]) -> Result<DataType> {
+        Ok(DataType::Float64)
+    }
+
+    fn partition_evaluator(&self) -> Result<Box<dyn PartitionEvaluator>> {
+        Ok(Box::new(VwapPartitionEvaluator::new()))
+    }
+
+    fn simplify(&self) -> Option<WindowFunctionSimplification> {
+        period_simplification(&self.name, vec![PeriodArg::rows(2, "period")])
+    }
+}
+
+#[derive(Debug)]
+struct VwapPartitionEvaluator {
+    window: VecDeque<(f64, f64)>,
+    price_volume_sum: f64,
+    volume_sum: f64,
+    period: Option<usize>,
+    configured: bool,
+}
+
+impl VwapPartitionEvaluator {
+    fn new() -> Self {
+        Self {
+            window: VecDeque::new(),
+            price_volume_sum: 0.0,
+            volume_sum: 0.0,
+            period: None,
+            configured: false,
+        }
+    }
+
+    /// Read the optional period and clear the running sums
+    fn configure(&mut self, values: &[ArrayRef]) -> Result<()> {
+        if values.len() != 2 && values.len() != 3 {
+            return Err(DataFusionError::Execution(
+                "VWAP function requires 2 or 3 arguments: price, volume and optional period".to_string(),
+            ));
+        }
+
+        self.period = if values.len() == 3 {
+            Some(period_arg(values, 2, "Period")?)
+        } else {
+            None
+        };
+        self.window.clear();
+        self.price_volume_sum = 0.0;
+        self.volume_sum = 0.0;
+        self.configured = true;
+        Ok(())
+    }
+
+    /// Add the next trade and return the VWAP so far
+    fn push(&mut self, price: f64, volume: f64) -> Option<f64> {
+        self.price_volume_sum += price * volume;
+        self.volume_sum += volume;
+
+        if let Some(period) = self.period {
+            self.window.push_back((price, volume));
+            if self.window.len() > period {
+                if let Some((old_price, old_volume)) = self.window.pop_front() {
+                    self.price_volume_sum -= old_price * old_volume;
+                    self.volume_sum -= old_volume;
+                }
+            }
+            if self.window.len() < period {
+                return None;
+            }
+        }
+
+        // VWAP = Σ(price × volume) / Σ(volume)
+        (self.volume_sum > 0.0).then(|| self.price_volume_sum / self.volume_sum)
+    }
+}
+
+impl PartitionEvaluator for VwapPartitionEvaluator {
+    fn evaluate_all(
+        &mut self,
+        values: &[ArrayRef],
+        num_rows: usize,
+    ) -> Result<ArrayRef> {
+        self.configure(values)?;
+        let price_array = float64_arg(values, 0, "Price")?;
+        let volume_array = float64_arg(values, 1, "Volume")?;
+
+        let mut result = Vec::with_capacity(num_rows);
+        for i in 0..num_rows {
+            let vwap = match (value_at(price_array, i), value_at(volume_array, i)) {
+                (Some(price), Some(volume)) => self.push(price, volume),
+                _ => None,
+            };
+            result.push(vwap);
+        }
+
+        Ok(Arc::new(Float64Array::from(result)))
+    }
+
+    /// Streaming evaluation: only the running sums (and the rolling window, if
+    /// a period is given) are kept between rows
+    fn evaluate(&mut self, values: &[ArrayRef], range: &Range<usize>) -> Result<ScalarValue> {
+        if !self.configured {
+            self.configure(values)?;
+        }
+        let price = value_at(float64_arg(values, 0, "Price")?, range.start);
+        let volume = value_at(float64_arg(values, 1, "Volume")?, range.start);
+        let vwap = match (price, volume) {
+            (Some(price), Some(volume)) => self.push(price, volume),
+            _ => None,
+        };
+        Ok(ScalarValue::Float64(vwap))
+    }
+
+    fn supports_bounded_execution(&self) -> bool {
+        true
+    }
+
+    fn is_causal(&self) -> bool {
+        true
+    }
+
+    fn uses_window_frame(&self) -> bool {
+        false
+    }
+
+    fn include_rank(&self) -> bool {
+        false
+    }
+}
+
+pub fn register_vwap(ctx: &SessionContext) -> Result<()> {
+    let vwap_udf = WindowUDF::from(VolumeWeightedAveragePrice::new());
+    ctx.register_udwf(vwap_udf);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use datafusion::execution::context::SessionContext;
+
+    #[tokio::test]
+    async fn test_vwap() -> Result<()> {
+        let ctx = SessionContext::new();
+        register_vwap(&ctx)?;
+
+        // Test cumulative and 3 period rolling VWAP using SQL
+        let result = ctx
+            .sql("SELECT price, volume,
+                    vwap(price, volume) OVER () AS vwap_cum,
+                    vwap(price, volume, 3) OVER () AS vwap_3
+                FROM (VALUES
+                (100.0, 1000.0), (101.0, 1500.0), (102.0, 800.0), (101.5, 1200.0),
+                (103.0, 2000.0), (102.5, 900.0), (104.0, 1700.0), (103.5, 1100.0)
+            ) AS t(price, volume)")
+            .await?
+            .collect()
+            .await?;
+
+        println!("VWAP Test Results:");
+        datafusion::arrow::util::pretty::print_batches(&result)?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,150 @@
+use std::any::Any;
+use std::sync::Arc;
+
+use datafusion::arrow::array::{ArrayRef, Float64Array};
+use datafusion::arrow::datatypes::DataType;
+use datafusion::error::Result;
+use datafusion::execution::context::SessionContext;
+use datafusion::logical_expr::function::WindowFunctionSimplification;
+use datafusion::logical_expr::{Signature, TypeSignature, Volatility, WindowUDF, WindowUDFImpl, PartitionEvaluator};
+
+use super::utils::{check_arg_count, float64_arg, period_arg, value_at, RollingMoments};
+use crate::validation::{period_simplification, PeriodArg};
+
+/// Rolling z-score of the current value against its trailing window
+#[derive(Debug)]
+pub struct RollingZScore {
+    name: String,
+    signature: Signature,
+}
+
+impl RollingZScore {
+    pub fn new() -> Self {
+        Self {
+            name: "zscore".to_string(),
+            signature: Signature::one_of(
+                vec![TypeSignature::Exact(vec![DataType::Float64, DataType::Int64])],
+                Volatility::Immutable,
+            ),
+        }
+    }
+}
+
+impl WindowUDFImpl for RollingZScore {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn signature(&self) -> &Signature {
+        &self.signature
+    }
+
+    fn return_type(&self, _arg_types: &[DataType]) -> Result<DataType> {
+        Ok(DataType::Float64)
+    }
+
+    fn partition_evaluator(&self) -> Result<Box<dyn PartitionEvaluator>> {
+        Ok(Box::new(ZScorePartitionEvaluator::new()))
+    }
+
+    fn simplify(&self) -> Option<WindowFunctionSimplification> {
+        period_simplification(&self.name, vec![PeriodArg::rows(1, "period")])
+    }
+}
+
+#[derive(Debug)]
+struct ZScorePartitionEvaluator {
+    window: RollingMoments,
+}
+
+impl ZScorePartitionEvaluator {
+    fn new() -> Self {
+        Self {
+            window: RollingMoments::default(),
+        }
+    }
+}
+
+impl PartitionEvaluator for ZScorePartitionEvaluator {
+    fn evaluate_all(
+        &mut self,
+        values: &[ArrayRef],
+        num_rows: usize,
+    ) -> Result<ArrayRef> {
+        check_arg_count(values, 2, "Z-score function (value, period)")?;
+
+        let value_array = float64_arg(values, 0, "First argument")?;
+        let period = period_arg(values, 1, "Period")?;
+
+        let mut result = Vec::with_capacity(num_rows);
+        self.window.reset(period);
+
+        for i in 0..num_rows {
+            let Some(value) = value_at(value_array, i) else {
+                result.push(None);
+                continue;
+            };
+
+            self.window.push(value);
+
+            if !self.window.is_full() {
+                result.push(None);
+                continue;
+            }
+
+            // z = (x - mean) / stddev over the trailing window
+            let mean = self.window.mean();
+            result.push(match self.window.sample_variance() {
+                Some(variance) if variance > 0.0 => Some((value - mean) / variance.sqrt()),
+                Some(_) => Some(0.0),
+                None => None,
+            });
+        }
+
+        Ok(Arc::new(Float64Array::from(result)))
+    }
+
+    fn uses_window_frame(&self) -> bool {
+        false
+    }
+
+    fn include_rank(&self) -> bool {
+        false
+    }
+}
+
+pub fn register_zscore(ctx: &SessionContext) -> Result<()> {
+    let zscore_udf = WindowUDF::from(RollingZScore::new());
+    ctx.register_udwf(zscore_udf);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use datafusion::execution::context::SessionContext;
+
+    #[tokio::test]
+    async fn test_zscore() -> Result<()> {
+        let ctx = SessionContext::new();
+        register_zscore(&ctx)?;
+
+        // Test z-score with a 5 period window using SQL
+        let result = ctx
+            .sql("SELECT price, zscore(price, 5) OVER () AS z_5 FROM (VALUES
+                (100.0), (101.0), (100.5), (99.5), (100.2), (100.8), (108.0), (100.4)
+            ) AS t(price)")
+            .await?
+            .collect()
+            .await?;
+
+        println!("Z-Score Test Results:");
+        datafusion::arrow::util::pretty::print_batches(&result)?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,321 @@
+//! Plugin interface for third-party indicators
+//!
+//! Downstream crates implement [`Indicator`] once and add it with
+//! [`register_indicator`]. Registered indicators are then exposed as SQL
+//! window functions by `register_financial_functions` (and
+//! `FinancialFunctions::all()`), and can be attached to the streaming
+//! pipeline with `StreamingIndicators::add_indicator`.
+//!
+//! ```ignore
+//! #[derive(Debug)]
+//! struct Momentum { signature: Signature }
+//!
+//! impl Indicator for Momentum {
+//!     fn name(&self) -> &str { "momentum" }
+//!     fn signature(&self) -> &Signature { &self.signature }
+//!     fn evaluator(&self, params: &[ScalarValue]) -> Result<Box<dyn IndicatorEvaluator>> { ... }
+//! }
+//!
+//! register_indicator(Momentum::new());
+//! register_financial_functions(&ctx)?; // momentum(close, 10) OVER (...) now resolves
+//! ```
+
+use std::any::Any;
+use std::fmt::Debug;
+use std::sync::{Arc, OnceLock, RwLock};
+
+use datafusion::arrow::array::{Array, ArrayRef, AsArray, Float64Array};
+use datafusion::arrow::compute::cast;
+use datafusion::arrow::datatypes::{DataType, Float64Type};
+use datafusion::error::{DataFusionError, Result};
+use datafusion::execution::context::SessionContext;
+use datafusion::logical_expr::{PartitionEvaluator, Signature, WindowUDF, WindowUDFImpl};
+use datafusion::scalar::ScalarValue;
+
+/// A custom indicator usable from SQL and from the streaming pipeline
+///
+/// SQL arguments are the indicator's input series (Float64, one value per
+/// row) followed by constant parameters such as periods. The streaming
+/// pipeline feeds the tick price, then the tick volume, as the inputs.
+pub trait Indicator: Debug + Send + Sync {
+    /// Function name used in SQL and as the streaming value key
+    fn name(&self) -> &str;
+
+    /// Accepted SQL argument types, inputs first and then parameters
+    fn signature(&self) -> &Signature;
+
+    /// Number of leading arguments that are per-row input series
+    fn inputs(&self) -> usize {
+        1
+    }
+
+    /// Create a fresh evaluator for one partition or stream
+    fn evaluator(&self, params: &[ScalarValue]) -> Result<Box<dyn IndicatorEvaluator>>;
+}
+
+/// Incremental state of an [`Indicator`]
+pub trait IndicatorEvaluator: Debug + Send {
+    /// Consume the next row's inputs and return the indicator value, if any
+    fn update(&mut self, inputs: &[f64]) -> Option<f64>;
+}
+
+// A panic inside a user's `name()` while the lock is held poisons it, but the
+// list is never left half-updated, so callers recover the guard instead of
+// propagating that panic
+fn registry() -> &'static RwLock<Vec<Arc<dyn Indicator>>> {
+    static REGISTRY: OnceLock<RwLock<Vec<Arc<dyn Indicator>>>> = OnceLock::new();
+    REGISTRY.get_or_init(|| RwLock::new(Vec::new()))
+}
+
+/// Add an indicator to the global registry, replacing one with the same name
+pub fn register_indicator(indicator: impl Indicator + 'static) {
+    let mut indicators = registry().write().unwrap_or_else(|e| e.into_inner());
+    indicators.retain(|existing| existing.name() != indicator.name());
+    indicators.push(Arc::new(indicator));
+}
+
+/// Remove an indicator from the global registry, returning it if it was there
+///
+/// Window functions already registered with a context from it stay usable.
+pub fn unregister_indicator(name: &str) -> Option<Arc<dyn Indicator>> {
+    let mut indicators = registry().write().unwrap_or_else(|e| e.into_inner());
+    let position = indicators.iter().position(|indicator| indicator.name() == name)?;
+    Some(indicators.remove(position))
+}
+
+/// Look up a registered indicator by name
+pub fn registered_indicator(name: &str) -> Option<Arc<dyn Indicator>> {
+    registry()
+        .read()
+        .unwrap_or_else(|e| e.into_inner())
+        .iter()
+        .find(|indicator| indicator.name() == name)
+        .cloned()
+}
+
+/// All registered indicators, in registration order
+pub fn registered_indicators() -> Vec<Arc<dyn Indicator>> {
+    registry().read().unwrap_or_else(|e| e.into_inner()).clone()
+}
+
+/// Register every indicator in the registry as a window function
+pub fn register_indicators(ctx: &SessionContext) -> Result<()> {
+    for indicator in registered_indicators() {
+        ctx.register_udwf(WindowUDF::from(IndicatorUdf::new(indicator)));
+    }
+    Ok(())
+}
+
+/// Window function adapter for an [`Indicator`]
+#[derive(Debug)]
+pub struct IndicatorUdf {
+    indicator: Arc<dyn Indicator>,
+}
+
+impl IndicatorUdf {
+    pub fn new(indicator: Arc<dyn Indicator>) -> Self {
+        Self { indicator }
+    }
+}
+
+impl WindowUDFImpl for IndicatorUdf {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn name(&self) -> &str {
+        self.indicator.name()
+    }
+
+    fn signature(&self) -> &Signature {
+        self.indicator.signature()
+    }
+
+    fn return_type(&self, _arg_types: &[DataType]) -> Result<DataType> {
+        Ok(DataType::Float64)
+    }
+
+    fn partition_evaluator(&self) -> Result<Box<dyn PartitionEvaluator>> {
+        Ok(Box::new(IndicatorPartitionEvaluator {
+            indicator: Arc::clone(&self.indicator),
+        }))
+    }
+}
+
+#[derive(Debug)]
+struct IndicatorPartitionEvaluator {
+    indicator: Arc<dyn Indicator>,
+}
+
+impl PartitionEvaluator for IndicatorPartitionEvaluator {
+    fn evaluate_all(
+        &mut self,
+        values: &[ArrayRef],
+        num_rows: usize,
+    ) -> Result<ArrayRef> {
+        let name = self.indicator.name();
+        let inputs = self.indicator.inputs();
+        if values.len() < inputs {
+            return Err(DataFusionError::Execution(format!(
+                "{} function requires at least {} input arguments",
+                name, inputs
+            )));
+        }
+
+        if num_rows == 0 {
+            return Ok(Arc::new(Float64Array::from(Vec::<Option<f64>>::new())));
+        }
+
+        let series = values[..inputs]
+            .iter()
+            .map(|array| Ok(cast(array, &DataType::Float64)?.as_primitive::<Float64Type>().clone()))
+            .collect::<Result<Vec<_>>>()?;
+
+        // Parameters are constant, so read them from the first row
+        let params = values[inputs..]
+            .iter()
+            .map(|array| ScalarValue::try_from_array(array, 0))
+            .collect::<Result<Vec<_>>>()?;
+        let mut evaluator = self.indicator.evaluator(&params)?;
+
+        let mut result = Vec::with_capacity(num_rows);
+        let mut row = vec![0.0; inputs];
+        for i in 0..num_rows {
+            let mut complete = true;
+            for (slot, array) in row.iter_mut().zip(&series) {
+                if array.is_null(i) {
+                    complete = false;
+                    break;
+                }
+                *slot = array.value(i);
+            }
+            result.push(if complete { evaluator.update(&row) } else { None });
+        }
+
+        Ok(Arc::new(Float64Array::from(result)))
+    }
+
+    fn uses_window_frame(&self) -> bool {
+        false
+    }
+
+    fn include_rank(&self) -> bool {
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use datafusion::logical_expr::{TypeSignature, Volatility};
+    use std::collections::VecDeque;
+
+    /// Price change over the last `period` rows
+    #[derive(Debug)]
+    struct Momentum {
+        signature: Signature,
+    }
+
+    #[derive(Debug)]
+    struct MomentumEvaluator {
+        period: usize,
+        prices: VecDeque<f64>,
+    }
+
+    impl Indicator for Momentum {
+        fn name(&self) -> &str {
+            "test_momentum"
+        }
+
+        fn signature(&self) -> &Signature {
+            &self.signature
+        }
+
+        fn evaluator(&self, params: &[ScalarValue]) -> Result<Box<dyn IndicatorEvaluator>> {
+            let period = match params.first() {
+                Some(ScalarValue::Int64(Some(period))) if *period > 0 => *period as usize,
+                _ => return Err(DataFusionError::Execution("Period must be a positive integer".to_string())),
+            };
+            Ok(Box::new(MomentumEvaluator {
+                period,
+                prices: VecDeque::new(),
+            }))
+        }
+    }
+
+    impl IndicatorEvaluator for MomentumEvaluator {
+        fn update(&mut self, inputs: &[f64]) -> Option<f64> {
+            self.prices.push_back(inputs[0]);
+            if self.prices.len() > self.period + 1 {
+                self.prices.pop_front();
+            }
+            (self.prices.len() > self.period).then(|| inputs[0] - self.prices[0])
+        }
+    }
+
+    /// Unregisters the test indicator when dropped, even if the test panics
+    struct Registered(&'static str);
+
+    impl Drop for Registered {
+        fn drop(&mut self) {
+            unregister_indicator(self.0);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_custom_indicator() -> Result<()> {
+        register_indicator(Momentum {
+            signature: Signature::one_of(
+                vec![TypeSignature::Exact(vec![DataType::Float64, DataType::Int64])],
+                Volatility::Immutable,
+            ),
+        });
+        let _registered = Registered("test_momentum");
+
+        let ctx = SessionContext::new();
+        crate::register_financial_functions(&ctx)?;
+
+        // Test the plugin indicator alongside a built-in one using SQL
+        let result = ctx
+            .sql("SELECT price,
+                    test_momentum(price, 2) OVER () AS momentum_2,
+                    sma(price, 2) OVER () AS sma_2
+                FROM (VALUES (10.0), (11.0), (10.5), (12.0), (12.5), (11.5)) AS t(price)")
+            .await?
+            .collect()
+            .await?;
+
+        println!("Custom Indicator Test Results:");
+        datafusion::arrow::util::pretty::print_batches(&result)?;
+
+        // The same indicator feeds the streaming pipeline
+        let mut streaming = crate::StreamingIndicators::new("AAPL".to_string(), 5);
+        streaming
+            .add_indicator("momentum_2", "test_momentum", &[ScalarValue::Int64(Some(2))])
+            .unwrap();
+        let mut last = None;
+        for price in [10.0, 11.0, 10.5, 12.0] {
+            let tick = crate::MarketTick {
+                symbol: "AAPL".to_string(),
+                timestamp: chrono::Utc::now(),
+                price,
+                volume: 1000,
+                fractional_volume: None,
+                bid: None,
+                ask: None,
+            };
+            last = streaming.update(&tick).custom["momentum_2"];
+        }
+        assert_eq!(last, Some(1.5));
+
+        // Once unregistered it is gone from new contexts and streams
+        assert!(unregister_indicator("test_momentum").is_some());
+        assert!(registered_indicator("test_momentum").is_none());
+        assert!(unregister_indicator("test_momentum").is_none());
+        let ctx = SessionContext::new();
+        crate::register_financial_functions(&ctx)?;
+        assert!(ctx.sql("SELECT test_momentum(1.0, 2) OVER ()").await.is_err());
+
+        Ok(())
+    }
+}
@@ -1,19 +1,43 @@
 use datafusion::execution::context::SessionContext;
 use datafusion::error::Result;
 
+pub mod adjust;
+#[cfg(feature = "alpaca")]
+pub mod alpaca;
+pub mod bars;
+#[cfg(feature = "binance")]
+pub mod binance;
+pub mod calendar;
+pub mod continuous;
+pub mod docs;
+pub mod expr;
+pub mod factory;
+#[cfg(feature = "flight")]
+pub mod flight;
+pub mod forex;
 pub mod functions;
+pub mod indicator;
+pub mod nbbo;
+pub mod options;
 pub mod polygon;
+pub mod registration;
+pub mod source;
 pub mod streaming;
+pub mod validation;
+#[cfg(feature = "yahoo")]
+pub mod yahoo;
 
+pub use factory::FinancialFunctionFactory;
 pub use functions::*;
+pub use indicator::{register_indicator, unregister_indicator, Indicator, IndicatorEvaluator};
 pub use polygon::*;
+pub use registration::FinancialFunctions;
+pub use source::{BarTimeframe, MarketDataSource};
 pub use streaming::{MarketTick, StreamingIndicators, StreamingProcessor};
 
 /// Register all financial functions with the given SessionContext
+///
+/// Use [`FinancialFunctions`] to register a subset or add a name prefix.
 pub fn register_financial_functions(ctx: &SessionContext) -> Result<()> {
-    functions::sma::register_sma(ctx)?;
-    functions::ema::register_ema(ctx)?;
-    functions::rsi::register_rsi(ctx)?;
-    functions::macd::register_macd(ctx)?;
-    Ok(())
+    FinancialFunctions::all().register(ctx)
 }
@@ -0,0 +1,283 @@
+//! National best bid and offer from exchange quotes
+//!
+//! `quotes_v1` rows are quote updates from individual exchanges: each sets
+//! the bid of `bid_exchange` and the ask of `ask_exchange`. The NBBO is the
+//! highest bid and lowest ask across every exchange's latest quote, so it
+//! depends on all earlier rows and is built by replaying them in order.
+
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+
+use datafusion::arrow::array::{Array, ArrayRef, AsArray, Float64Array, Int32Array, Int64Array, RecordBatch, StringArray};
+use datafusion::arrow::compute::cast;
+use datafusion::arrow::datatypes::{DataType, Field, Float64Type, Int32Type, Int64Type, Schema};
+use datafusion::dataframe::DataFrame;
+use datafusion::error::{DataFusionError, Result};
+use datafusion::execution::context::SessionContext;
+use datafusion::prelude::col;
+
+/// Column names used to read quote rows
+#[derive(Debug, Clone)]
+pub struct QuoteColumns {
+    /// Optional grouping column; each ticker has its own NBBO
+    pub ticker: Option<String>,
+    /// Epoch nanoseconds column used for ordering
+    pub timestamp: String,
+    /// Optional tie-breaker for quotes with the same timestamp
+    pub sequence: Option<String>,
+    pub bid_exchange: String,
+    pub bid_price: String,
+    pub bid_size: String,
+    pub ask_exchange: String,
+    pub ask_price: String,
+    pub ask_size: String,
+}
+
+impl Default for QuoteColumns {
+    /// Polygon flat file quote column names
+    fn default() -> Self {
+        Self {
+            ticker: Some("ticker".to_string()),
+            timestamp: "sip_timestamp".to_string(),
+            sequence: Some("sequence_number".to_string()),
+            bid_exchange: "bid_exchange".to_string(),
+            bid_price: "bid_price".to_string(),
+            bid_size: "bid_size".to_string(),
+            ask_exchange: "ask_exchange".to_string(),
+            ask_price: "ask_price".to_string(),
+            ask_size: "ask_size".to_string(),
+        }
+    }
+}
+
+/// One side of the NBBO: price, size summed over the exchanges at that
+/// price, and the lowest-numbered of those exchanges
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Best {
+    price: f64,
+    size: i64,
+    exchange: i32,
+}
+
+/// One NBBO row
+#[derive(Debug)]
+struct Nbbo {
+    ticker: Option<String>,
+    timestamp: i64,
+    bid: Option<Best>,
+    ask: Option<Best>,
+}
+
+/// Latest quote of each exchange for one ticker
+#[derive(Debug, Default)]
+struct Book {
+    bids: BTreeMap<i32, (f64, i64)>,
+    asks: BTreeMap<i32, (f64, i64)>,
+}
+
+impl Book {
+    /// Replace an exchange's side; a missing or zero price withdraws it
+    fn update(side: &mut BTreeMap<i32, (f64, i64)>, exchange: i32, price: Option<f64>, size: i64) {
+        match price.filter(|p| *p > 0.0) {
+            Some(price) => side.insert(exchange, (price, size)),
+            None => side.remove(&exchange),
+        };
+    }
+
+    fn best_bid(&self) -> Option<Best> {
+        best(&self.bids, |a, b| a > b)
+    }
+
+    fn best_ask(&self) -> Option<Best> {
+        best(&self.asks, |a, b| a < b)
+    }
+}
+
+fn best(side: &BTreeMap<i32, (f64, i64)>, better: impl Fn(f64, f64) -> bool) -> Option<Best> {
+    let mut result: Option<Best> = None;
+    // Exchanges iterate in ascending order, so ties keep the lowest one
+    for (&exchange, &(price, size)) in side {
+        match &mut result {
+            Some(best) if best.price == price => best.size += size,
+            Some(best) if !better(price, best.price) => {}
+            _ => result = Some(Best { price, size, exchange }),
+        }
+    }
+    result
+}
+
+/// Build the NBBO series of quote rows, using Polygon column names
+pub async fn build_nbbo(ctx: &SessionContext, quotes: DataFrame) -> Result<DataFrame> {
+    build_nbbo_with_columns(ctx, quotes, &QuoteColumns::default()).await
+}
+
+/// Build the NBBO series of quote rows
+///
+/// Quotes are replayed in timestamp order per ticker, and a row is emitted
+/// whenever the best bid or ask changes, once all quotes sharing a timestamp
+/// are applied. Output columns are `ticker` (when grouped), `timestamp`,
+/// `bid_price`, `bid_size`, `bid_exchange`, `ask_price`, `ask_size`,
+/// `ask_exchange`, `mid`, `spread` and `spread_bps` (spread over mid, in
+/// basis points). A side with no exchange quoting is null, as are the
+/// derived columns; locked and crossed markets are kept as quoted.
+pub async fn build_nbbo_with_columns(ctx: &SessionContext, quotes: DataFrame, columns: &QuoteColumns) -> Result<DataFrame> {
+    let mut sort = Vec::new();
+    if let Some(ticker) = &columns.ticker {
+        sort.push(col(ticker.as_str()).sort(true, false));
+    }
+    sort.push(col(columns.timestamp.as_str()).sort(true, false));
+    if let Some(sequence) = &columns.sequence {
+        if quotes.schema().has_column_with_unqualified_name(sequence) {
+            sort.push(col(sequence.as_str()).sort(true, false));
+        }
+    }
+    let batches = quotes.sort(sort)?.collect().await?;
+
+    let mut books: HashMap<Option<String>, Book> = HashMap::new();
+    let mut rows: Vec<Nbbo> = Vec::new();
+    let mut last: HashMap<Option<String>, (Option<Best>, Option<Best>)> = HashMap::new();
+    // Quote applied last, whose timestamp may still have more updates
+    let mut pending: Option<(Option<String>, i64)> = None;
+
+    let mut emit = |books: &HashMap<Option<String>, Book>, (ticker, timestamp): (Option<String>, i64)| {
+        let book = &books[&ticker];
+        let current = (book.best_bid(), book.best_ask());
+        if last.get(&ticker) != Some(&current) {
+            last.insert(ticker.clone(), current);
+            rows.push(Nbbo { ticker, timestamp, bid: current.0, ask: current.1 });
+        }
+    };
+
+    for batch in &batches {
+        let tickers = match &columns.ticker {
+            Some(name) => Some(cast(column(batch, name)?, &DataType::Utf8)?),
+            None => None,
+        };
+        let timestamps = cast(column(batch, &columns.timestamp)?, &DataType::Int64)?;
+        let timestamps = timestamps.as_primitive::<Int64Type>();
+        let int32 = |name: &str| cast(column(batch, name)?, &DataType::Int32);
+        let int64 = |name: &str| cast(column(batch, name)?, &DataType::Int64);
+        let float64 = |name: &str| cast(column(batch, name)?, &DataType::Float64);
+        let (bid_exchanges, ask_exchanges) = (int32(&columns.bid_exchange)?, int32(&columns.ask_exchange)?);
+        let (bid_prices, ask_prices) = (float64(&columns.bid_price)?, float64(&columns.ask_price)?);
+        let (bid_sizes, ask_sizes) = (int64(&columns.bid_size)?, int64(&columns.ask_size)?);
+        let (bid_exchanges, ask_exchanges) = (bid_exchanges.as_primitive::<Int32Type>(), ask_exchanges.as_primitive::<Int32Type>());
+        let (bid_prices, ask_prices) = (bid_prices.as_primitive::<Float64Type>(), ask_prices.as_primitive::<Float64Type>());
+        let (bid_sizes, ask_sizes) = (bid_sizes.as_primitive::<Int64Type>(), ask_sizes.as_primitive::<Int64Type>());
+
+        for row in 0..batch.num_rows() {
+            if timestamps.is_null(row) {
+                continue;
+            }
+            let ticker = tickers
+                .as_ref()
+                .map(|t| t.as_string::<i32>())
+                .filter(|t| t.is_valid(row))
+                .map(|t| t.value(row).to_string());
+            let timestamp = timestamps.value(row);
+
+            if let Some(previous) = pending.take() {
+                if previous.0 != ticker || previous.1 != timestamp {
+                    emit(&books, previous);
+                }
+            }
+
+            let book = books.entry(ticker.clone()).or_default();
+            let size = |sizes: &Int64Array| if sizes.is_valid(row) { sizes.value(row) } else { 0 };
+            if bid_exchanges.is_valid(row) {
+                let price = bid_prices.is_valid(row).then(|| bid_prices.value(row));
+                Book::update(&mut book.bids, bid_exchanges.value(row), price, size(bid_sizes));
+            }
+            if ask_exchanges.is_valid(row) {
+                let price = ask_prices.is_valid(row).then(|| ask_prices.value(row));
+                Book::update(&mut book.asks, ask_exchanges.value(row), price, size(ask_sizes));
+            }
+            pending = Some((ticker, timestamp));
+        }
+    }
+    if let Some(previous) = pending {
+        emit(&books, previous);
+    }
+
+    nbbo_to_dataframe(ctx, columns, rows)
+}
+
+fn column<'a>(batch: &'a RecordBatch, name: &str) -> Result<&'a ArrayRef> {
+    batch
+        .column_by_name(name)
+        .ok_or_else(|| DataFusionError::Plan(format!("Column '{}' not found", name)))
+}
+
+fn nbbo_to_dataframe(ctx: &SessionContext, columns: &QuoteColumns, rows: Vec<Nbbo>) -> Result<DataFrame> {
+    let mut fields = Vec::new();
+    let mut arrays: Vec<ArrayRef> = Vec::new();
+
+    if let Some(ticker) = &columns.ticker {
+        fields.push(Field::new(ticker, DataType::Utf8, true));
+        arrays.push(Arc::new(rows.iter().map(|r| r.ticker.clone()).collect::<StringArray>()));
+    }
+    fields.push(Field::new("timestamp", DataType::Int64, false));
+    arrays.push(Arc::new(rows.iter().map(|r| r.timestamp).collect::<Int64Array>()));
+
+    for (side, best_of) in [("bid", (|r: &Nbbo| r.bid) as fn(&Nbbo) -> Option<Best>), ("ask", |r: &Nbbo| r.ask)] {
+        fields.push(Field::new(format!("{}_price", side), DataType::Float64, true));
+        fields.push(Field::new(format!("{}_size", side), DataType::Int64, true));
+        fields.push(Field::new(format!("{}_exchange", side), DataType::Int32, true));
+        arrays.push(Arc::new(rows.iter().map(|r| best_of(r).map(|b| b.price)).collect::<Float64Array>()));
+        arrays.push(Arc::new(rows.iter().map(|r| best_of(r).map(|b| b.size)).collect::<Int64Array>()));
+        arrays.push(Arc::new(rows.iter().map(|r| best_of(r).map(|b| b.exchange)).collect::<Int32Array>()));
+    }
+
+    let quotes = |r: &Nbbo| r.bid.zip(r.ask).map(|(bid, ask)| (bid.price, ask.price));
+    for name in ["mid", "spread", "spread_bps"] {
+        fields.push(Field::new(name, DataType::Float64, true));
+    }
+    arrays.push(Arc::new(rows.iter().map(|r| quotes(r).map(|(bid, ask)| (bid + ask) / 2.0)).collect::<Float64Array>()));
+    arrays.push(Arc::new(rows.iter().map(|r| quotes(r).map(|(bid, ask)| ask - bid)).collect::<Float64Array>()));
+    arrays.push(Arc::new(
+        rows.iter()
+            .map(|r| quotes(r).map(|(bid, ask)| (ask - bid) / ((bid + ask) / 2.0) * 10_000.0))
+            .collect::<Float64Array>(),
+    ));
+
+    let batch = RecordBatch::try_new(Arc::new(Schema::new(fields)), arrays)?;
+    ctx.read_batch(batch)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_build_nbbo() -> Result<()> {
+        let ctx = SessionContext::new();
+        // Exchanges 11 and 12 quote AAPL; 12 withdraws its bid at t=4
+        let df = ctx
+            .sql("SELECT * FROM (VALUES
+                ('AAPL', 1, 1, 11, 100.00, 200, 11, 100.05, 300),
+                ('AAPL', 2, 2, 12, 100.01, 100, 12, 100.05, 100),
+                ('AAPL', 2, 3, 11, 100.01, 400, 11, 100.04, 100),
+                ('AAPL', 3, 4, 11, 100.01, 400, 11, 100.04, 100),
+                ('AAPL', 4, 5, 12, 0.0, 0, 12, 100.05, 100),
+                ('MSFT', 1, 1, 11, 300.00, 100, 11, 300.10, 100)
+            ) AS t(ticker, sip_timestamp, sequence_number, bid_exchange, bid_price, bid_size,
+                   ask_exchange, ask_price, ask_size)")
+            .await?;
+
+        let result = build_nbbo(&ctx, df)
+            .await?
+            .sort(vec![col("ticker").sort(true, false), col("timestamp").sort(true, false)])?
+            .collect()
+            .await?;
+
+        println!("NBBO Test Results:");
+        datafusion::arrow::util::pretty::print_batches(&result)?;
+
+        // t=3 repeats the NBBO of t=2, so AAPL has rows at t=1, 2 and 4
+        assert_eq!(result.iter().map(|b| b.num_rows()).sum::<usize>(), 4);
+        let bid_sizes = result[0].column_by_name("bid_size").unwrap().as_primitive::<Int64Type>();
+        assert_eq!(bid_sizes.value(1), 500);
+
+        Ok(())
+    }
+}
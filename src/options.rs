@@ -0,0 +1,280 @@
+//! Option contracts identified by OCC (OSI) symbols
+//!
+//! Options flat files key every row by a symbol such as
+//! `O:AAPL240119C00150000`: the underlying root, the expiration as `YYMMDD`,
+//! `C` or `P`, and the strike times 1000 in eight digits. [`parse_occ_symbol`]
+//! splits one symbol, and [`with_contract_columns`] adds the parts as
+//! `underlying`, `expiration`, `strike` and `option_type` columns.
+
+use std::any::Any;
+use std::sync::Arc;
+
+use chrono::{Datelike, NaiveDate};
+use datafusion::arrow::array::{ArrayRef, AsArray, Date32Array, Float64Array, StringArray};
+use datafusion::arrow::compute::cast;
+use datafusion::arrow::datatypes::DataType;
+use datafusion::dataframe::DataFrame;
+use datafusion::error::Result;
+use datafusion::execution::context::SessionContext;
+use datafusion::logical_expr::{ColumnarValue, ScalarUDF, ScalarUDFImpl, Signature, Volatility};
+use datafusion::prelude::col;
+
+/// Days from 0001-01-01 to 1970-01-01
+const UNIX_EPOCH_DAYS_FROM_CE: i32 = 719_163;
+
+/// Polygon's prefix for option tickers
+pub const POLYGON_OPTION_PREFIX: &str = "O:";
+
+/// Call or put
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum OptionType {
+    Call,
+    Put,
+}
+
+impl OptionType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            OptionType::Call => "call",
+            OptionType::Put => "put",
+        }
+    }
+
+    /// OCC symbol letter, `C` or `P`
+    pub fn code(&self) -> char {
+        match self {
+            OptionType::Call => 'C',
+            OptionType::Put => 'P',
+        }
+    }
+}
+
+/// Terms of one listed option contract
+#[derive(Debug, Clone, PartialEq)]
+pub struct OptionContract {
+    pub underlying: String,
+    pub expiration: NaiveDate,
+    pub option_type: OptionType,
+    pub strike: f64,
+}
+
+impl OptionContract {
+    /// Polygon ticker of the contract, e.g. `O:AAPL240119C00150000`
+    pub fn ticker(&self) -> String {
+        format!(
+            "{}{}{}{}{:08}",
+            POLYGON_OPTION_PREFIX,
+            self.underlying,
+            self.expiration.format("%y%m%d"),
+            self.option_type.code(),
+            (self.strike * 1000.0).round() as u64
+        )
+    }
+}
+
+/// Parse an OCC option symbol, with or without Polygon's `O:` prefix
+///
+/// The space padding of the 21-character OSI form (`AAPL  240119C00150000`)
+/// is accepted too. Returns `None` for anything that isn't an option symbol.
+pub fn parse_occ_symbol(symbol: &str) -> Option<OptionContract> {
+    let symbol = symbol.strip_prefix(POLYGON_OPTION_PREFIX).unwrap_or(symbol);
+    if !symbol.is_ascii() || symbol.len() < 16 {
+        return None;
+    }
+
+    let (root, terms) = symbol.split_at(symbol.len() - 15);
+    let underlying = root.trim_end();
+    if underlying.is_empty() || underlying.contains(' ') {
+        return None;
+    }
+
+    let expiration = NaiveDate::parse_from_str(&terms[..6], "%y%m%d").ok()?;
+    let option_type = match &terms[6..7] {
+        "C" => OptionType::Call,
+        "P" => OptionType::Put,
+        _ => return None,
+    };
+    let strike = &terms[7..];
+    if !strike.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+
+    Some(OptionContract {
+        underlying: underlying.to_string(),
+        expiration,
+        option_type,
+        strike: strike.parse::<u64>().ok()? as f64 / 1000.0,
+    })
+}
+
+/// Which part of the symbol a scalar function extracts
+#[derive(Debug, Clone, Copy)]
+enum ContractField {
+    Underlying,
+    Expiration,
+    Strike,
+    OptionType,
+}
+
+/// Scalar functions extracting one term of an OCC symbol; null for
+/// symbols that don't parse
+#[derive(Debug)]
+pub struct OptionSymbolFunction {
+    name: String,
+    signature: Signature,
+    field: ContractField,
+}
+
+impl OptionSymbolFunction {
+    /// `option_underlying(ticker)`: underlying root, e.g. `AAPL`
+    pub fn underlying() -> Self {
+        Self::new("option_underlying", ContractField::Underlying)
+    }
+
+    /// `option_expiration(ticker)`: expiration date
+    pub fn expiration() -> Self {
+        Self::new("option_expiration", ContractField::Expiration)
+    }
+
+    /// `option_strike(ticker)`: strike price
+    pub fn strike() -> Self {
+        Self::new("option_strike", ContractField::Strike)
+    }
+
+    /// `option_type(ticker)`: `'call'` or `'put'`
+    pub fn option_type() -> Self {
+        Self::new("option_type", ContractField::OptionType)
+    }
+
+    fn new(name: &str, field: ContractField) -> Self {
+        Self {
+            name: name.to_string(),
+            signature: Signature::uniform(1, vec![DataType::Utf8], Volatility::Immutable),
+            field,
+        }
+    }
+}
+
+impl ScalarUDFImpl for OptionSymbolFunction {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn signature(&self) -> &Signature {
+        &self.signature
+    }
+
+    fn return_type(&self, _arg_types: &[DataType]) -> Result<DataType> {
+        Ok(match self.field {
+            ContractField::Underlying | ContractField::OptionType => DataType::Utf8,
+            ContractField::Expiration => DataType::Date32,
+            ContractField::Strike => DataType::Float64,
+        })
+    }
+
+    fn invoke(&self, args: &[ColumnarValue]) -> Result<ColumnarValue> {
+        let values = ColumnarValue::values_to_arrays(args)?;
+        let symbols = cast(&values[0], &DataType::Utf8)?;
+        let contracts = symbols
+            .as_string::<i32>()
+            .iter()
+            .map(|symbol| symbol.and_then(parse_occ_symbol))
+            .collect::<Vec<_>>();
+
+        let result: ArrayRef = match self.field {
+            ContractField::Underlying => Arc::new(
+                contracts
+                    .iter()
+                    .map(|c| c.as_ref().map(|c| c.underlying.as_str()))
+                    .collect::<StringArray>(),
+            ),
+            ContractField::Expiration => Arc::new(
+                contracts
+                    .iter()
+                    .map(|c| c.as_ref().map(|c| c.expiration.num_days_from_ce() - UNIX_EPOCH_DAYS_FROM_CE))
+                    .collect::<Date32Array>(),
+            ),
+            ContractField::Strike => Arc::new(
+                contracts
+                    .iter()
+                    .map(|c| c.as_ref().map(|c| c.strike))
+                    .collect::<Float64Array>(),
+            ),
+            ContractField::OptionType => Arc::new(
+                contracts
+                    .iter()
+                    .map(|c| c.as_ref().map(|c| c.option_type.as_str()))
+                    .collect::<StringArray>(),
+            ),
+        };
+
+        Ok(ColumnarValue::Array(result))
+    }
+}
+
+/// Add `underlying`, `expiration`, `strike` and `option_type` columns
+/// parsed from the OCC symbols in `ticker`
+pub fn with_contract_columns(df: DataFrame, ticker: &str) -> Result<DataFrame> {
+    let symbol = || vec![col(ticker)];
+    df.with_column("underlying", ScalarUDF::from(OptionSymbolFunction::underlying()).call(symbol()))?
+        .with_column("expiration", ScalarUDF::from(OptionSymbolFunction::expiration()).call(symbol()))?
+        .with_column("strike", ScalarUDF::from(OptionSymbolFunction::strike()).call(symbol()))?
+        .with_column("option_type", ScalarUDF::from(OptionSymbolFunction::option_type()).call(symbol()))
+}
+
+/// Register `option_underlying`, `option_expiration`, `option_strike` and `option_type`
+pub fn register_options(ctx: &SessionContext) -> Result<()> {
+    ctx.register_udf(ScalarUDF::from(OptionSymbolFunction::underlying()));
+    ctx.register_udf(ScalarUDF::from(OptionSymbolFunction::expiration()));
+    ctx.register_udf(ScalarUDF::from(OptionSymbolFunction::strike()));
+    ctx.register_udf(ScalarUDF::from(OptionSymbolFunction::option_type()));
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_option_symbols() -> Result<()> {
+        let contract = parse_occ_symbol("O:AAPL240119C00150000").unwrap();
+        assert_eq!(contract.underlying, "AAPL");
+        assert_eq!(contract.expiration, NaiveDate::from_ymd_opt(2024, 1, 19).unwrap());
+        assert_eq!(contract.option_type, OptionType::Call);
+        assert_eq!(contract.strike, 150.0);
+        assert_eq!(contract.ticker(), "O:AAPL240119C00150000");
+        assert_eq!(parse_occ_symbol("SPY   241220P00450500").unwrap().strike, 450.5);
+        assert!(parse_occ_symbol("AAPL").is_none());
+
+        let ctx = SessionContext::new();
+        let df = ctx
+            .sql("SELECT ticker, close FROM (VALUES
+                ('O:AAPL240119C00150000', 42.1), ('O:SPY241220P00450500', 3.75),
+                ('O:BRKB250620C00400000', 12.0), ('AAPL', 185.0)
+            ) AS t(ticker, close)")
+            .await?;
+
+        // Test the contract columns and the SQL functions
+        let result = with_contract_columns(df, "ticker")?.collect().await?;
+
+        println!("Option Symbol Test Results:");
+        datafusion::arrow::util::pretty::print_batches(&result)?;
+
+        register_options(&ctx)?;
+        let result = ctx
+            .sql("SELECT option_underlying('O:TSLA240621P00180000') AS underlying,
+                    option_expiration('O:TSLA240621P00180000') AS expiration,
+                    option_strike('O:TSLA240621P00180000') AS strike,
+                    option_type('O:TSLA240621P00180000') AS option_type")
+            .await?
+            .collect()
+            .await?;
+        datafusion::arrow::util::pretty::print_batches(&result)?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,191 @@
+//! Polygon datasets as a DataFusion catalog
+//!
+//! [`register_polygon_catalog`] adds a `polygon` catalog with one schema per
+//! asset class, named by its S3 prefix, and one [`PolygonTableProvider`] per
+//! data type, so every dataset can be queried from SQL as e.g.
+//! `polygon.us_stocks_sip.minute_aggs`.
+
+use std::any::Any;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use datafusion::catalog::{CatalogProvider, SchemaProvider};
+use datafusion::datasource::TableProvider;
+use datafusion::error::Result;
+use datafusion::execution::context::SessionContext;
+
+use super::{flat_file_schema, AssetClass, PolygonClient, PolygonDataType, PolygonTableProvider};
+
+/// Catalog name used by [`register_polygon_catalog`]
+pub const POLYGON_CATALOG: &str = "polygon";
+
+/// Data types exposed as tables, when the asset class has them
+const TABLE_DATA_TYPES: [PolygonDataType; 4] = [
+    PolygonDataType::Trades,
+    PolygonDataType::Quotes,
+    PolygonDataType::MinuteAggs,
+    PolygonDataType::DayAggs,
+];
+
+/// Table name of a data type: its S3 directory without the version suffix
+fn table_name(data_type: &PolygonDataType) -> &'static str {
+    let prefix = data_type.s3_prefix();
+    prefix.strip_suffix("_v1").unwrap_or(prefix)
+}
+
+/// Catalog of every asset class with fixed-schema flat files
+pub struct PolygonCatalogProvider {
+    client: Arc<PolygonClient>,
+}
+
+impl PolygonCatalogProvider {
+    pub fn new(client: Arc<PolygonClient>) -> Self {
+        Self { client }
+    }
+}
+
+impl std::fmt::Debug for PolygonCatalogProvider {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PolygonCatalogProvider").finish_non_exhaustive()
+    }
+}
+
+impl CatalogProvider for PolygonCatalogProvider {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn schema_names(&self) -> Vec<String> {
+        AssetClass::ALL
+            .iter()
+            .filter(|asset_class| !PolygonSchemaProvider::data_types(asset_class).is_empty())
+            .map(|asset_class| asset_class.s3_prefix().to_string())
+            .collect()
+    }
+
+    fn schema(&self, name: &str) -> Option<Arc<dyn SchemaProvider>> {
+        let asset_class = AssetClass::ALL.into_iter().find(|a| a.s3_prefix() == name)?;
+        if PolygonSchemaProvider::data_types(&asset_class).is_empty() {
+            return None;
+        }
+        Some(Arc::new(PolygonSchemaProvider {
+            client: self.client.clone(),
+            asset_class,
+        }))
+    }
+}
+
+/// The datasets of one asset class
+pub struct PolygonSchemaProvider {
+    client: Arc<PolygonClient>,
+    asset_class: AssetClass,
+}
+
+impl PolygonSchemaProvider {
+    /// Data types of `asset_class` with a fixed schema
+    fn data_types(asset_class: &AssetClass) -> Vec<PolygonDataType> {
+        TABLE_DATA_TYPES
+            .into_iter()
+            .filter(|data_type| flat_file_schema(asset_class, data_type).is_some())
+            .collect()
+    }
+}
+
+impl std::fmt::Debug for PolygonSchemaProvider {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PolygonSchemaProvider")
+            .field("asset_class", &self.asset_class)
+            .finish_non_exhaustive()
+    }
+}
+
+#[async_trait]
+impl SchemaProvider for PolygonSchemaProvider {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn table_names(&self) -> Vec<String> {
+        Self::data_types(&self.asset_class)
+            .iter()
+            .map(|data_type| table_name(data_type).to_string())
+            .collect()
+    }
+
+    async fn table(&self, name: &str) -> Result<Option<Arc<dyn TableProvider>>> {
+        let Some(data_type) = Self::data_types(&self.asset_class)
+            .into_iter()
+            .find(|data_type| table_name(data_type) == name)
+        else {
+            return Ok(None);
+        };
+        let table = PolygonTableProvider::try_new(self.client.clone(), self.asset_class.clone(), data_type)?;
+        Ok(Some(Arc::new(table)))
+    }
+
+    fn table_exist(&self, name: &str) -> bool {
+        self.table_names().iter().any(|table| table == name)
+    }
+}
+
+/// Register the `polygon` catalog of every dataset in `ctx`
+///
+/// Also registers the client's object store with `ctx`, so scans can read
+/// the files.
+pub fn register_polygon_catalog(ctx: &SessionContext, client: Arc<PolygonClient>) -> Result<()> {
+    client.register_object_store(ctx)?;
+    ctx.register_catalog(POLYGON_CATALOG, Arc::new(PolygonCatalogProvider::new(client)));
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use datafusion::arrow::array::AsArray;
+
+    #[tokio::test]
+    async fn test_polygon_catalog() -> Result<()> {
+        let root = std::env::temp_dir().join(format!("polygon-catalog-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&root);
+        let dir = root.join("us_stocks_sip/minute_aggs_v1/2024");
+        std::fs::create_dir_all(&dir)?;
+        std::fs::write(
+            dir.join("2024-01-02.csv"),
+            "ticker,volume,open,close,high,low,window_start,transactions\n\
+             AAPL,100,1.0,2.0,3.0,0.5,1704205800000000000,10\n\
+             MSFT,200,1.0,2.0,3.0,0.5,1704205800000000000,20\n",
+        )?;
+
+        let ctx = SessionContext::new();
+        register_polygon_catalog(&ctx, Arc::new(PolygonClient::from_local(&root)?))?;
+
+        // Futures have no fixed schema, so no schema of their own
+        let catalog = ctx.catalog(POLYGON_CATALOG).unwrap();
+        assert_eq!(
+            catalog.schema_names(),
+            ["us_stocks_sip", "us_options_opra", "indices", "forex", "global_crypto"]
+        );
+        assert!(catalog.schema("futures").is_none());
+
+        let stocks = catalog.schema("us_stocks_sip").unwrap();
+        assert_eq!(stocks.table_names(), ["trades", "quotes", "minute_aggs", "day_aggs"]);
+        assert!(stocks.table_exist("minute_aggs") && !stocks.table_exist("minute_aggs_v1"));
+        assert_eq!(catalog.schema("indices").unwrap().table_names(), ["minute_aggs", "day_aggs"]);
+        assert_eq!(catalog.schema("forex").unwrap().table_names(), ["quotes", "minute_aggs", "day_aggs"]);
+
+        let batches = ctx
+            .sql("SELECT ticker, volume FROM polygon.us_stocks_sip.minute_aggs
+                  WHERE date = '2024-01-02' AND ticker = 'MSFT'")
+            .await?
+            .collect()
+            .await?;
+        let volumes = batches
+            .iter()
+            .flat_map(|batch| batch.column(1).as_primitive::<datafusion::arrow::datatypes::Int64Type>().values().to_vec())
+            .collect::<Vec<_>>();
+        assert_eq!(volumes, [200]);
+
+        std::fs::remove_dir_all(&root)?;
+        Ok(())
+    }
+}
@@ -1,70 +1,345 @@
 //! Polygon.io data client for flat files and APIs
 
-use super::{DataSource, PolygonConfig, AssetClass, PolygonDataType};
+use crate::calendar::ExchangeCalendar;
+use crate::continuous::{build_continuous, BackAdjust, RollRule};
+use crate::options::{with_contract_columns, OptionType, POLYGON_OPTION_PREFIX};
+use super::conditions::with_condition_columns;
+use super::timestamps::{with_exchange_time_auto, DATE_COLUMN, LOCAL_TIME_COLUMN, SESSION_COLUMN};
+#[cfg(feature = "azure")]
+use super::AzureConfig;
+#[cfg(feature = "gcs")]
+use super::GcsConfig;
+use super::paths::year_prefix;
+use super::{flat_file_schema, PathResolver, PathTemplate, PolygonLayout, DataNotAvailable, PolygonError, CacheConfig, DataSource, PolygonConfig, AssetClass, PolygonDataType, RetryConfig};
+use datafusion::arrow::array::RecordBatch;
+use datafusion::arrow::csv::reader::Format;
+use datafusion::arrow::csv::ReaderBuilder;
+use datafusion::arrow::datatypes::SchemaRef;
+use datafusion::datasource::MemTable;
 use datafusion::execution::context::SessionContext;
 use datafusion::error::Result;
-use datafusion::prelude::CsvReadOptions;
+use datafusion::prelude::{CsvReadOptions, ParquetReadOptions};
+use datafusion::dataframe::DataFrameWriteOptions;
 use datafusion::datasource::file_format::file_compression_type::FileCompressionType;
-use std::sync::Arc;
+use std::collections::HashMap;
+use std::io::{BufRead, Cursor};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 use chrono::{NaiveDate, Datelike};
 use object_store::{ObjectStore, path::Path as ObjectPath};
-use futures::stream::StreamExt;
+use object_store::aws::{AmazonS3, AmazonS3Builder};
+use futures::stream::{StreamExt, TryStreamExt};
+use tokio_util::io::{StreamReader, SyncIoBridge};
+
+/// Year of the earliest Polygon flat files
+const FIRST_FLAT_FILE_YEAR: i32 = 2003;
+
+/// Local CSV copies looked for, in order of preference
+const LOCAL_CSV_EXTENSIONS: [&str; 4] = [".csv", ".csv.gz", ".csv.zst", ".csv.bz2"];
+
+/// Keys returned by `list_available_files`
+pub const DEFAULT_LIST_LIMIT: usize = 1000;
+
+/// Limits for concurrent multi-day downloads
+#[derive(Debug, Clone)]
+pub struct DownloadOptions {
+    /// Files fetched at once
+    pub concurrency: usize,
+    /// Most file downloads started per second; `None` for no limit
+    pub requests_per_second: Option<f64>,
+}
+
+impl Default for DownloadOptions {
+    fn default() -> Self {
+        Self {
+            concurrency: 8,
+            requests_per_second: Some(10.0),
+        }
+    }
+}
+
+/// Where [`PolygonClient::prefetch`] puts downloaded files
+#[derive(Debug, Clone)]
+pub enum PrefetchTarget {
+    /// Convert each file into the client's Parquet cache (see `with_cache`)
+    Cache,
+    /// Copy the `.csv.gz` files unchanged under this directory, in the
+    /// bucket's layout, for use with `PolygonClient::from_local`
+    Mirror(PathBuf),
+}
+
+/// Outcome of prefetching one day
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PrefetchStatus {
+    Downloaded,
+    /// Already in the target
+    Present,
+    /// No file in the source for that day
+    Missing,
+}
+
+/// Progress reported after each day of a prefetch
+#[derive(Debug, Clone)]
+pub struct PrefetchProgress {
+    pub date: NaiveDate,
+    pub status: PrefetchStatus,
+    /// Days finished so far, including this one
+    pub completed: usize,
+    pub total: usize,
+}
+
+/// Days of a prefetch by outcome, each in date order
+#[derive(Debug, Clone, Default)]
+pub struct PrefetchSummary {
+    pub downloaded: Vec<NaiveDate>,
+    pub present: Vec<NaiveDate>,
+    pub missing: Vec<NaiveDate>,
+}
+
+/// Spaces out request starts to stay under a maximum rate
+struct RateLimiter {
+    interval: Option<Duration>,
+    next: Mutex<Instant>,
+}
+
+impl RateLimiter {
+    fn new(requests_per_second: Option<f64>) -> Self {
+        Self {
+            interval: requests_per_second
+                .filter(|rate| *rate > 0.0)
+                .map(|rate| Duration::from_secs_f64(1.0 / rate)),
+            next: Mutex::new(Instant::now()),
+        }
+    }
+
+    /// Wait for the next free request slot
+    async fn acquire(&self) {
+        let Some(interval) = self.interval else {
+            return;
+        };
+        let wait = {
+            let mut next = self.next.lock().unwrap_or_else(|e| e.into_inner());
+            let now = Instant::now();
+            let slot = (*next).max(now);
+            *next = slot + interval;
+            slot - now
+        };
+        if !wait.is_zero() {
+            tokio::time::sleep(wait).await;
+        }
+    }
+}
 
 /// Polygon.io data client for flat files
 pub struct PolygonClient {
     source: DataSource,
     ctx: SessionContext,
+    cache: Option<CacheConfig>,
+    api_key: Option<String>,
+    exchange_time: Option<ExchangeCalendar>,
+    paths: Arc<dyn PathResolver>,
 }
 
 impl PolygonClient {
     /// Create a new Polygon.io client with S3 data source
     pub fn from_s3(config: PolygonConfig) -> Result<Self> {
-        let source = DataSource::S3(config.clone());
-        let ctx = SessionContext::new();
-        
-        // Register S3 object store for direct flat file access
-        Self::register_s3_store(&ctx, &config)?;
-        
-        Ok(Self { source, ctx })
+        Self::new(DataSource::S3(config))
+    }
+
+    /// Create a client reading a Google Cloud Storage mirror of the flat files
+    #[cfg(feature = "gcs")]
+    pub fn from_gcs(config: GcsConfig) -> Result<Self> {
+        Self::new(DataSource::Gcs(config))
+    }
+
+    /// Create a client reading an Azure Blob Storage mirror of the flat files
+    #[cfg(feature = "azure")]
+    pub fn from_azure(config: AzureConfig) -> Result<Self> {
+        Self::new(DataSource::Azure(config))
     }
     
     /// Create a new Polygon.io client with local file system data source
     pub fn from_local<P: Into<std::path::PathBuf>>(root: P) -> Result<Self> {
-        let source = DataSource::Local { root: root.into() };
-        let ctx = SessionContext::new();
-        
-        Ok(Self { source, ctx })
+        Self::new(DataSource::Local { root: root.into() })
     }
     
     /// Create a new client from data source (preferred constructor)
     pub fn new(source: DataSource) -> Result<Self> {
-        match source {
-            DataSource::S3(config) => Self::from_s3(config),
-            DataSource::Local { root } => Self::from_local(root),
+        Self::with_context(SessionContext::new(), source)
+    }
+
+    /// Create a client that reads into a caller-owned `ctx`
+    ///
+    /// The source's object store is registered with `ctx`, and every
+    /// DataFrame the client returns belongs to it, so loaded data can be
+    /// registered as tables and queried alongside the application's own
+    /// tables and functions. Tables such as
+    /// [`register_polygon_table`](super::register_polygon_table) can be
+    /// registered into the same context.
+    pub fn with_context(ctx: SessionContext, source: DataSource) -> Result<Self> {
+        let api_key = match &source {
+            DataSource::S3(config) => config.api_key.clone(),
+            _ => None,
         }
+        .or_else(|| std::env::var("POLYGON_API_KEY").ok());
+        let client = Self {
+            source,
+            ctx,
+            cache: None,
+            api_key,
+            exchange_time: None,
+            paths: Arc::new(PolygonLayout),
+        };
+
+        // Register the object store for direct flat file access
+        client.register_object_store(&client.ctx)?;
+
+        Ok(client)
+    }
+    
+    /// Cache S3 flat files locally as Parquet, serving repeat loads from disk
+    pub fn with_cache(mut self, cache: CacheConfig) -> Self {
+        self.cache = Some(cache);
+        self
     }
     
-    /// Register Polygon.io S3 object store with DataFusion
-    fn register_s3_store(ctx: &SessionContext, config: &PolygonConfig) -> Result<()> {
-        use object_store::aws::AmazonS3Builder;
+    /// Add exchange-local `local_time`, `date` and `session` columns to
+    /// everything `load_data` returns (see [`with_exchange_time`](super::with_exchange_time))
+    pub fn with_exchange_time(mut self, calendar: ExchangeCalendar) -> Self {
+        self.exchange_time = Some(calendar);
+        self
+    }
+    
+    /// Find flat files with `resolver` instead of Polygon's layout, for
+    /// mirrors that organize the files differently
+    pub fn with_path_resolver(mut self, resolver: impl PathResolver + 'static) -> Self {
+        self.paths = Arc::new(resolver);
+        self
+    }
+
+    /// Find flat files by a [`PathTemplate`], e.g.
+    /// `{asset_class}/{data_type}/year={year}/month={month}/{date}.csv.gz`
+    pub fn with_path_template(self, template: &str) -> Result<Self> {
+        Ok(self.with_path_resolver(PathTemplate::new(template)?))
+    }
+
+    /// Set the REST API key used for reference data
+    pub fn with_api_key(mut self, api_key: impl Into<String>) -> Self {
+        self.api_key = Some(api_key.into());
+        self
+    }
+
+    /// REST API key, from the config or `POLYGON_API_KEY`
+    pub(super) fn api_key(&self) -> Result<&str> {
+        self.api_key.as_deref().ok_or_else(|| {
+            PolygonError::MissingCredentials {
+                credential: "REST API key",
+                env_var: "POLYGON_API_KEY",
+            }
+            .into()
+        })
+    }
+    
+    /// Make the client's data source readable from plans run in `ctx`
+    ///
+    /// Needed before executing plans built by this client, such as a
+    /// [`PolygonTableProvider`](super::PolygonTableProvider) scan, in another context.
+    pub fn register_object_store(&self, ctx: &SessionContext) -> Result<()> {
         use url::Url;
-        
-        let s3 = AmazonS3Builder::new()
+
+        let Some(base_url) = self.source.base_url() else {
+            return Ok(());
+        };
+        let url = Url::parse(&format!("{}/", base_url))
+            .map_err(|e| datafusion::error::DataFusionError::External(Box::new(e)))?;
+
+        ctx.runtime_env()
+            .register_object_store(&url, self.object_store()?);
+
+        Ok(())
+    }
+
+    /// Object store of a remote source
+    fn object_store(&self) -> Result<Arc<dyn ObjectStore>> {
+        match &self.source {
+            DataSource::S3(config) => Ok(Arc::new(Self::s3_store(config)?)),
+            #[cfg(feature = "gcs")]
+            DataSource::Gcs(config) => {
+                let mut builder = object_store::gcp::GoogleCloudStorageBuilder::from_env()
+                    .with_bucket_name(&config.bucket)
+                    .with_retry(config.retry.object_store_config());
+                if let Some(path) = &config.service_account_path {
+                    builder = builder.with_service_account_path(path);
+                }
+                Ok(Arc::new(builder.build().map_err(|e| datafusion::error::DataFusionError::External(Box::new(e)))?))
+            }
+            #[cfg(feature = "azure")]
+            DataSource::Azure(config) => {
+                let mut builder = object_store::azure::MicrosoftAzureBuilder::from_env()
+                    .with_account(&config.account)
+                    .with_container_name(&config.container)
+                    .with_retry(config.retry.object_store_config());
+                if let Some(access_key) = &config.access_key {
+                    builder = builder.with_access_key(access_key);
+                }
+                Ok(Arc::new(builder.build().map_err(|e| datafusion::error::DataFusionError::External(Box::new(e)))?))
+            }
+            DataSource::Local { .. } => Err(datafusion::error::DataFusionError::Configuration(
+                "A local data source has no object store".to_string(),
+            )),
+        }
+    }
+
+    /// Where a flat file path was looked for: the full URL of a remote
+    /// file, or the local `.csv.gz` path under a mirror's root
+    fn attempted_path(&self, path: &str) -> String {
+        match &self.source {
+            DataSource::Local { root } => local_path(root, path, ".csv.gz").display().to_string(),
+            _ => path.to_string(),
+        }
+    }
+
+    /// Object store key of a flat file path
+    fn object_key(&self, path: &str) -> ObjectPath {
+        let key = self
+            .source
+            .base_url()
+            .and_then(|base_url| path.strip_prefix(&format!("{}/", base_url)).map(str::to_string))
+            .unwrap_or_else(|| path.to_string());
+        ObjectPath::from(key)
+    }
+
+    /// Build the Polygon.io S3 store, retrying each request per `config.retry`
+    fn s3_store(config: &PolygonConfig) -> Result<AmazonS3> {
+        if config.access_key.is_empty() {
+            return Err(PolygonError::MissingCredentials {
+                credential: "S3 access key",
+                env_var: "POLYGON_ACCESS_KEY_ID",
+            }
+            .into());
+        }
+        if config.secret_key.is_empty() {
+            return Err(PolygonError::MissingCredentials {
+                credential: "S3 secret key",
+                env_var: "POLYGON_SECRET_ACCESS_KEY",
+            }
+            .into());
+        }
+        AmazonS3Builder::new()
             .with_endpoint(&config.endpoint)
             .with_access_key_id(&config.access_key)
             .with_secret_access_key(&config.secret_key)
             .with_bucket_name(&config.bucket)
             .with_region("us-east-1") // Polygon.io region
+            .with_retry(config.retry.object_store_config())
             .build()
-            .map_err(|e| datafusion::error::DataFusionError::External(Box::new(e)))?;
-        
-        let url = Url::parse(&format!("s3://{}/", &config.bucket))
-            .map_err(|e| datafusion::error::DataFusionError::External(Box::new(e)))?;
-            
-        ctx.runtime_env()
-            .register_object_store(&url, Arc::new(s3));
-            
-        Ok(())
+            .map_err(|e| datafusion::error::DataFusionError::External(Box::new(e)))
+    }
+
+    /// Retry policy for REST requests, which go over the network for either source
+    pub(super) fn rest_retry_config(&self) -> RetryConfig {
+        self.source.retry().cloned().unwrap_or_default()
     }
 
     /// Load minute aggregates from Polygon.io flat files  
@@ -73,7 +348,7 @@ impl PolygonClient {
         symbol: &str,
         date: NaiveDate,
     ) -> Result<datafusion::dataframe::DataFrame> {
-        self.load_data(AssetClass::Stocks, PolygonDataType::MinuteAggs, date, Some(symbol)).await
+        self.load_data(AssetClass::Stocks, PolygonDataType::MinuteAggs, date, &[symbol]).await
     }
 
     /// Load day aggregates from Polygon.io flat files
@@ -82,7 +357,7 @@ impl PolygonClient {
         symbol: &str,
         date: NaiveDate,
     ) -> Result<datafusion::dataframe::DataFrame> {
-        self.load_data(AssetClass::Stocks, PolygonDataType::DayAggs, date, Some(symbol)).await
+        self.load_data(AssetClass::Stocks, PolygonDataType::DayAggs, date, &[symbol]).await
     }
 
     /// Load trades data from Polygon.io flat files
@@ -91,45 +366,202 @@ impl PolygonClient {
         symbol: &str,
         date: NaiveDate,
     ) -> Result<datafusion::dataframe::DataFrame> {
-        self.load_data(AssetClass::Stocks, PolygonDataType::Trades, date, Some(symbol)).await
+        self.load_data(AssetClass::Stocks, PolygonDataType::Trades, date, &[symbol]).await
     }
 
     /// Load CSV data from appropriate source with decompression
+    ///
+    /// S3 flat files are read with `schema` when there is one; local files
+    /// always have their schema inferred, since they needn't follow
+    /// Polygon's layout. When `symbols` are given, lines of other tickers
+    /// are dropped before they are parsed (see [`read_csv_symbols`]).
     async fn load_csv_from_source(
         &self,
         path: &str,
-        symbol: &str,
+        schema: Option<&SchemaRef>,
+        symbols: &[&str],
     ) -> Result<datafusion::dataframe::DataFrame> {
+        if !symbols.is_empty() {
+            if let Some(df) = self.load_csv_symbols(path, schema, symbols).await? {
+                return Ok(df);
+            }
+        }
+
         let df = match &self.source {
-            DataSource::S3(_) => {
-                // Read compressed CSV from S3
+            DataSource::Local { root } => {
+                // Mirrors may keep files plain or recompressed; the format
+                // is read from the file itself
+                let local_path = local_csv_path(root, path).unwrap_or_else(|| local_path(root, path, ".csv"));
+                let extension = file_extension(&local_path);
                 let csv_options = CsvReadOptions::new()
+                    .has_header(true)
+                    .file_extension(&extension)
+                    .file_compression_type(detect_compression(&local_path)?);
+                let shown = local_path.to_string_lossy();
+                self.ctx
+                    .read_csv(shown.as_ref(), csv_options)
+                    .await
+                    .map_err(|e| PolygonError::from_read(e, &shown))?
+            }
+            _ => {
+                // Read compressed CSV from the bucket; the store retries
+                // transient failures of each request
+                let mut csv_options = CsvReadOptions::new()
                     .has_header(true)
                     .file_compression_type(FileCompressionType::GZIP);
-                self.ctx.read_csv(path, csv_options).await?
+                if let Some(schema) = schema {
+                    csv_options = csv_options.schema(schema);
+                }
+                self.ctx.read_csv(path, csv_options).await.map_err(|e| PolygonError::from_read(e, path))?
             }
+        };
+        
+        filter_symbols(df, symbols)
+    }
+
+    /// Read only the lines of `symbols` from a CSV file, or `None` if its
+    /// first column isn't `ticker`
+    ///
+    /// Whole-market files are mostly other tickers; matching the leading
+    /// ticker of each line is far cheaper than parsing every row and
+    /// filtering afterwards.
+    async fn load_csv_symbols(
+        &self,
+        path: &str,
+        schema: Option<&SchemaRef>,
+        symbols: &[&str],
+    ) -> Result<Option<datafusion::dataframe::DataFrame>> {
+        let symbols = symbols.iter().map(|symbol| symbol.to_string()).collect::<Vec<_>>();
+        let rows = match &self.source {
             DataSource::Local { root } => {
-                // Convert to local file path and use uncompressed CSV
-                let local_path = if path.starts_with("s3://") {
-                    // Convert S3 path to local path, change .gz to regular .csv
-                    let path_part = path.strip_prefix("s3://flatfiles/").unwrap_or(path);
-                    let uncompressed_path = path_part.replace(".csv.gz", ".csv");
-                    root.join(uncompressed_path)
-                } else {
-                    let uncompressed_path = path.replace(".csv.gz", ".csv");
-                    root.join(uncompressed_path.strip_prefix("file://").unwrap_or(&uncompressed_path))
-                };
-                
-                let csv_options = CsvReadOptions::new().has_header(true);
-                self.ctx.read_csv(local_path.to_string_lossy().as_ref(), csv_options).await?
+                let local_path = local_csv_path(root, path).unwrap_or_else(|| local_path(root, path, ".csv"));
+                let shown = local_path.display().to_string();
+                let file_name = shown.clone();
+                spawn_blocking(move || {
+                    let symbols = symbols.iter().map(String::as_str).collect::<Vec<_>>();
+                    // zstd and bzip2 files are left to the DataFusion reader
+                    let compression = detect_compression(&local_path)?;
+                    let file = std::fs::File::open(&local_path)?;
+                    match compression {
+                        FileCompressionType::UNCOMPRESSED => {
+                            read_csv_symbols(std::io::BufReader::new(file), &file_name, None, &symbols)
+                        }
+                        FileCompressionType::GZIP => {
+                            let reader = std::io::BufReader::new(flate2::read::MultiGzDecoder::new(file));
+                            read_csv_symbols(reader, &file_name, None, &symbols)
+                        }
+                        _ => Ok(None),
+                    }
+                })
+                .await
+                .map_err(|e| PolygonError::from_read(e, &shown))?
+            }
+            _ => {
+                let store = self.object_store()?;
+                let location = self.object_key(path);
+                let result = store.get(&location).await.map_err(|e| PolygonError::from_object_store(e, path))?;
+                // Decompress the object as it arrives rather than buffering
+                // it; the bridge blocks, so the decoding runs off the workers
+                let body = SyncIoBridge::new(StreamReader::new(result.into_stream()));
+                let path = path.to_string();
+                let schema = schema.cloned();
+                spawn_blocking(move || {
+                    let symbols = symbols.iter().map(String::as_str).collect::<Vec<_>>();
+                    let reader = std::io::BufReader::new(flate2::read::MultiGzDecoder::new(body));
+                    read_csv_symbols(reader, &path, schema.as_ref(), &symbols)
+                })
+                .await?
             }
         };
-        
-        // Filter by symbol if provided
-        if !symbol.is_empty() {
-            Ok(df.filter(datafusion::prelude::col("ticker").eq(datafusion::prelude::lit(symbol)))?)
-        } else {
-            Ok(df)
+
+        let Some((schema, batches)) = rows else {
+            return Ok(None);
+        };
+        let table = MemTable::try_new(schema, vec![batches])?;
+        Ok(Some(self.ctx.read_table(Arc::new(table))?))
+    }
+
+    /// Load Parquet data from appropriate source
+    async fn load_parquet_from_source(
+        &self,
+        path: &str,
+        symbols: &[&str],
+    ) -> Result<datafusion::dataframe::DataFrame> {
+        let parquet_path = match &self.source {
+            DataSource::Local { root } => local_path(root, path, ".parquet").to_string_lossy().to_string(),
+            _ => path.replace(".csv.gz", ".parquet"),
+        };
+        self.read_parquet(&parquet_path, symbols).await
+    }
+
+    /// Read a Parquet file, keeping only `symbols`
+    async fn read_parquet(&self, path: &str, symbols: &[&str]) -> Result<datafusion::dataframe::DataFrame> {
+        // Only the columns a query uses are decoded, and the symbol filter is
+        // pushed down to skip row groups by their statistics
+        let df = self
+            .ctx
+            .read_parquet(path, ParquetReadOptions::default())
+            .await
+            .map_err(|e| PolygonError::from_read(e, path))?;
+        filter_symbols(df, symbols)
+    }
+
+    /// Cached Parquet copy of a remote flat file, downloading it on a miss
+    ///
+    /// Returns `None` when caching is off or the source is local.
+    async fn cached_parquet(&self, path: &str, schema: Option<&SchemaRef>) -> Result<Option<PathBuf>> {
+        let Some(cache) = self.cache.as_ref().filter(|_| !self.source.is_local()) else {
+            return Ok(None);
+        };
+
+        let cached = cache_path(cache, path);
+        if is_fresh(&cached, cache.ttl) {
+            return Ok(Some(cached));
+        }
+
+        // Cache the whole file, not just the requested symbols, sorted by
+        // ticker so symbol filters can skip most row groups
+        let mut df = self.load_csv_from_source(path, schema, &[]).await?;
+        if df.schema().has_column_with_unqualified_name("ticker") {
+            df = df.sort(vec![datafusion::prelude::col("ticker").sort(true, false)])?;
+        }
+
+        if let Some(dir) = cached.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        // Write beside the final path and rename, so readers never see a partial file
+        let partial = cached.with_extension("parquet.partial");
+        df.write_parquet(
+            partial.to_string_lossy().as_ref(),
+            DataFrameWriteOptions::new().with_single_file_output(true),
+            None,
+        )
+        .await?;
+        std::fs::rename(&partial, &cached)?;
+
+        Ok(Some(cached))
+    }
+
+    /// Drop the cached copy of one day's file, if any
+    pub fn invalidate_cache(&self, asset_class: AssetClass, data_type: PolygonDataType, date: NaiveDate) -> Result<()> {
+        let Some(cache) = &self.cache else {
+            return Ok(());
+        };
+        let cached = cache_path(cache, &self.flat_file_path(&asset_class, &data_type, date));
+        match std::fs::remove_file(cached) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e.into()),
+            _ => Ok(()),
+        }
+    }
+
+    /// Drop every cached file
+    pub fn clear_cache(&self) -> Result<()> {
+        let Some(cache) = &self.cache else {
+            return Ok(());
+        };
+        match std::fs::remove_dir_all(&cache.dir) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e.into()),
+            _ => Ok(()),
         }
     }
 
@@ -149,86 +581,143 @@ impl PolygonClient {
     }
 
     /// List available files in data source for discovery
+    ///
+    /// Returns up to [`DEFAULT_LIST_LIMIT`] keys under `prefix`, at any
+    /// depth; use [`list_files`](Self::list_files) for another limit.
     pub async fn list_available_files(&self, prefix: &str) -> Result<Vec<String>> {
+        self.list_files(prefix, Some(DEFAULT_LIST_LIMIT)).await
+    }
+
+    /// List the keys under `prefix`, at any depth, stopping after `limit`
+    ///
+    /// S3 listings are fetched page by page, so a limit also bounds the
+    /// number of requests. Pass `None` to list everything.
+    pub async fn list_files(&self, prefix: &str, limit: Option<usize>) -> Result<Vec<String>> {
+        let limit = limit.unwrap_or(usize::MAX);
         match &self.source {
-            DataSource::S3(config) => {
-                use object_store::aws::AmazonS3Builder;
-                
-                let s3 = AmazonS3Builder::new()
-                    .with_endpoint(&config.endpoint)
-                    .with_access_key_id(&config.access_key)
-                    .with_secret_access_key(&config.secret_key)
-                    .with_bucket_name(&config.bucket)
-                    .with_region("us-east-1")
-                    .build()
-                    .map_err(|e| datafusion::error::DataFusionError::External(Box::new(e)))?;
-                
-                let prefix_path = ObjectPath::from(prefix);
-                let mut files = Vec::new();
-                
-                let mut stream = s3.list(Some(&prefix_path));
-                while let Some(result) = stream.next().await {
-                    match result {
-                        Ok(meta) => {
-                            files.push(meta.location.to_string());
-                            if files.len() >= 20 { // Limit results
-                                break;
-                            }
-                        }
-                        Err(e) => {
-                            return Err(datafusion::error::DataFusionError::External(Box::new(e)));
-                        }
-                    }
-                }
-                
-                Ok(files)
-            }
             DataSource::Local { root } => {
-                // List local files
-                let search_path = root.join(prefix);
                 let mut files = Vec::new();
-                
-                if let Ok(entries) = std::fs::read_dir(&search_path) {
-                    for entry in entries.flatten().take(20) {
-                        if let Ok(path) = entry.path().strip_prefix(root) {
-                            files.push(path.to_string_lossy().to_string());
+                let mut pending = vec![root.join(prefix)];
+                while let Some(dir) = pending.pop() {
+                    let Ok(entries) = std::fs::read_dir(&dir) else {
+                        continue;
+                    };
+                    for entry in entries.flatten() {
+                        let path = entry.path();
+                        if path.is_dir() {
+                            pending.push(path);
+                        } else if let Ok(relative) = path.strip_prefix(root) {
+                            files.push(relative.to_string_lossy().to_string());
                         }
                     }
                 }
-                
+                files.sort();
+                files.truncate(limit);
                 Ok(files)
             }
+            _ => {
+                let store = self.object_store()?;
+                let prefix_path = ObjectPath::from(prefix);
+                list_store_files(store.as_ref(), &prefix_path, limit).await
+            }
         }
     }
-    
+
+    /// Names of the directories directly under `prefix`, sorted
+    ///
+    /// Uses a delimiter listing on S3, so only the directory level is read,
+    /// however many files are below it.
+    pub async fn list_directories(&self, prefix: &str) -> Result<Vec<String>> {
+        let mut directories = match &self.source {
+            DataSource::Local { root } => match std::fs::read_dir(root.join(prefix)) {
+                Ok(entries) => entries
+                    .flatten()
+                    .filter(|entry| entry.path().is_dir())
+                    .map(|entry| entry.file_name().to_string_lossy().to_string())
+                    .collect(),
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => Vec::new(),
+                Err(e) => return Err(e.into()),
+            },
+            _ => {
+                let store = self.object_store()?;
+                let prefix_path = (!prefix.trim_matches('/').is_empty()).then(|| ObjectPath::from(prefix));
+                let listing = store.list_with_delimiter(prefix_path.as_ref()).await?;
+                listing
+                    .common_prefixes
+                    .iter()
+                    .filter_map(|path| path.filename().map(str::to_string))
+                    .collect::<Vec<_>>()
+            }
+        };
+        directories.sort();
+        Ok(directories)
+    }
+
     /// Discover available asset classes in the data source
     pub async fn discover_asset_classes(&self) -> Result<Vec<String>> {
-        let files = self.list_available_files("").await?;
-        let mut asset_classes = std::collections::HashSet::new();
-        
-        for file in files {
-            let parts: Vec<&str> = file.split('/').collect();
-            if !parts.is_empty() {
-                asset_classes.insert(parts[0].to_string());
-            }
-        }
-        
-        Ok(asset_classes.into_iter().collect())
+        self.list_directories("").await
     }
-    
+
     /// Discover available data types for a specific asset class
     pub async fn discover_data_types(&self, asset_class: &str) -> Result<Vec<String>> {
-        let files = self.list_available_files(&format!("{}/", asset_class)).await?;
-        let mut data_types = std::collections::HashSet::new();
-        
-        for file in files {
-            let parts: Vec<&str> = file.split('/').collect();
-            if parts.len() >= 2 {
-                data_types.insert(parts[1].to_string());
+        self.list_directories(&format!("{}/", asset_class)).await
+    }
+
+    /// Dates in `year` that have a file for the dataset, in order
+    ///
+    /// Lists the year's directory rather than probing each day, so backfills
+    /// can skip holidays and missing days up front. Each day's path comes
+    /// from the client's [`PathResolver`]; compressed, plain CSV and
+    /// `.parquet` copies all count.
+    pub async fn discover_available_dates(
+        &self,
+        asset_class: AssetClass,
+        data_type: PolygonDataType,
+        year: i32,
+    ) -> Result<Vec<NaiveDate>> {
+        let prefix = year_prefix(self.paths.as_ref(), &asset_class, &data_type, year);
+        let names = self
+            .list_files(&prefix, None)
+            .await?
+            .into_iter()
+            .map(|name| name.replace('\\', "/"))
+            .collect::<std::collections::HashSet<_>>();
+
+        let (Some(first), Some(last)) = (NaiveDate::from_ymd_opt(year, 1, 1), NaiveDate::from_ymd_opt(year, 12, 31)) else {
+            return Ok(Vec::new());
+        };
+        let dates = first
+            .iter_days()
+            .take_while(|date| *date <= last)
+            .filter(|date| {
+                let key = self.paths.resolve(&asset_class, &data_type, *date);
+                LOCAL_CSV_EXTENSIONS
+                    .iter()
+                    .chain(&[".parquet"])
+                    .any(|extension| names.contains(&key.replace(".csv.gz", extension)))
+            })
+            .collect();
+        Ok(dates)
+    }
+
+    /// Most recent day with a file for the dataset, or `None` if it has none
+    ///
+    /// Lists this year's directory and, while that has no files, each earlier
+    /// year back to the first flat files, so a daily job can load the latest
+    /// day without knowing when Polygon last published one.
+    pub async fn latest_available_date(
+        &self,
+        asset_class: AssetClass,
+        data_type: PolygonDataType,
+    ) -> Result<Option<NaiveDate>> {
+        let this_year = chrono::Utc::now().year();
+        for year in (FIRST_FLAT_FILE_YEAR..=this_year).rev() {
+            let dates = self.discover_available_dates(asset_class.clone(), data_type.clone(), year).await?;
+            if let Some(latest) = dates.last() {
+                return Ok(Some(*latest));
             }
         }
-        
-        Ok(data_types.into_iter().collect())
+        Ok(None)
     }
 
     /// Load crypto day aggregates from Polygon.io flat files
@@ -236,52 +725,637 @@ impl PolygonClient {
         &self,
         date: NaiveDate,
     ) -> Result<datafusion::dataframe::DataFrame> {
-        self.load_data(AssetClass::Crypto, PolygonDataType::DayAggs, date, None).await
+        self.load_data(AssetClass::Crypto, PolygonDataType::DayAggs, date, &[]).await
     }
 
     /// Load data for any asset class and data type
+    ///
+    /// Only rows whose ticker is in `symbols` are kept; pass `&[]` for every ticker.
+    /// Days without a file fail with [`DataNotAvailable`]; see [`file_exists`](Self::file_exists).
+    /// Options data gets `underlying`, `expiration`, `strike` and
+    /// `option_type` columns parsed from the OCC ticker, and stock trades get
+    /// the condition flags of [`with_condition_columns`], including
+    /// `is_eligible`.
     pub async fn load_data(
         &self,
         asset_class: AssetClass,
         data_type: PolygonDataType,
         date: NaiveDate,
-        symbol: Option<&str>,
+        symbols: &[&str],
+    ) -> Result<datafusion::dataframe::DataFrame> {
+        let stock_trades = matches!((&asset_class, &data_type), (AssetClass::Stocks, PolygonDataType::Trades));
+        let mut df = self.load_file(asset_class.clone(), data_type, date, symbols).await?;
+        if let AssetClass::Options = asset_class {
+            df = with_contract_columns(df, "ticker")?;
+        }
+        if stock_trades && df.schema().has_column_with_unqualified_name("conditions") {
+            df = with_condition_columns(df, "conditions")?;
+        }
+        match &self.exchange_time {
+            Some(calendar) => with_exchange_time_auto(df, calendar),
+            None => Ok(df),
+        }
+    }
+
+    /// Whether the source has a file for the dataset on `date`
+    ///
+    /// A HEAD request on S3; locally, a `.csv` or `.parquet` copy counts.
+    pub async fn file_exists(&self, asset_class: AssetClass, data_type: PolygonDataType, date: NaiveDate) -> Result<bool> {
+        let file_path = self.flat_file_path(&asset_class, &data_type, date);
+        match &self.source {
+            DataSource::Local { root } => Ok(local_csv_path(root, &file_path).is_some()
+                || local_path(root, &file_path, ".parquet").exists()),
+            _ => {
+                let store = self.object_store()?;
+                let location = self.object_key(&file_path);
+                match store.head(&location).await {
+                    Ok(_) => Ok(true),
+                    Err(object_store::Error::NotFound { .. }) => Ok(false),
+                    Err(e) => Err(PolygonError::from_object_store(e, &file_path)),
+                }
+            }
+        }
+    }
+
+    /// Whether a fresh cached copy of a remote flat file exists
+    fn is_cached(&self, path: &str) -> bool {
+        match &self.cache {
+            Some(cache) if !self.source.is_local() => is_fresh(&cache_path(cache, path), cache.ttl),
+            _ => false,
+        }
+    }
+
+    /// Read one day's file, from the cache, a local Parquet copy or the CSV
+    ///
+    /// Days without a file fail with [`DataNotAvailable`].
+    pub(super) async fn load_file(
+        &self,
+        asset_class: AssetClass,
+        data_type: PolygonDataType,
+        date: NaiveDate,
+        symbols: &[&str],
+    ) -> Result<datafusion::dataframe::DataFrame> {
+        let file_path = self.flat_file_path(&asset_class, &data_type, date);
+        let schema = flat_file_schema(&asset_class, &data_type);
+
+        // Fail up front with a typed error; a lazy CSV scan would only hit
+        // the missing file once collected
+        if !self.is_cached(&file_path) && !self.file_exists(asset_class.clone(), data_type.clone(), date).await? {
+            let path = self.attempted_path(&file_path);
+            return Err(DataNotAvailable { asset_class, data_type, date, path }.into());
+        }
+
+        if let Some(cached) = self.cached_parquet(&file_path, schema.as_ref()).await? {
+            return self.read_parquet(cached.to_string_lossy().as_ref(), symbols).await;
+        }
+
+        // Prefer a local Parquet copy over the CSV when there is one
+        if let DataSource::Local { root } = &self.source {
+            if local_path(root, &file_path, ".parquet").exists() {
+                return self.load_parquet_from_source(&file_path, symbols).await;
+            }
+        }
+        self.load_csv_from_source(&file_path, schema.as_ref(), symbols).await
+    }
+
+    /// Load the Parquet version of a dataset
+    ///
+    /// Reads `YYYY-MM-DD.parquet` in place of `YYYY-MM-DD.csv.gz`, from the
+    /// same S3 prefix or local directory. `load_data` already picks up local
+    /// Parquet files on its own; use this to require Parquet.
+    pub async fn load_parquet(
+        &self,
+        asset_class: AssetClass,
+        data_type: PolygonDataType,
+        date: NaiveDate,
+        symbols: &[&str],
     ) -> Result<datafusion::dataframe::DataFrame> {
-        let data_type_str = match data_type {
-            PolygonDataType::MinuteAggs => "minute_aggs_v1",
-            PolygonDataType::DayAggs => "day_aggs_v1", 
-            PolygonDataType::Trades => "trades_v1",
-            PolygonDataType::Quotes => "quotes_v1",
-            PolygonDataType::GroupedDaily => "grouped_daily_v1",
+        let file_path = self.flat_file_path(&asset_class, &data_type, date);
+        self.load_parquet_from_source(&file_path, symbols).await
+    }
+
+    /// Load several symbols from one file, returning a DataFrame per symbol
+    ///
+    /// The file is read once, filtered to `symbols`, and held in memory; each
+    /// symbol's DataFrame is then a filter over that in-memory copy rather
+    /// than another scan of the whole-market file. Symbols without rows map
+    /// to empty DataFrames.
+    pub async fn load_symbols(
+        &self,
+        asset_class: AssetClass,
+        data_type: PolygonDataType,
+        date: NaiveDate,
+        symbols: &[&str],
+    ) -> Result<HashMap<String, datafusion::dataframe::DataFrame>> {
+        let batches = self.load_data(asset_class, data_type, date, symbols).await?.collect().await?;
+        let loaded = self.ctx.read_batches(batches)?;
+
+        let mut frames = HashMap::with_capacity(symbols.len());
+        for symbol in symbols {
+            let df = loaded
+                .clone()
+                .filter(datafusion::prelude::col("ticker").eq(datafusion::prelude::lit(*symbol)))?;
+            frames.insert(symbol.to_string(), df);
+        }
+        Ok(frames)
+    }
+
+    /// Option chain of `underlying` from the `as_of` options day aggregates
+    ///
+    /// One row per expiration and strike, ordered by both, with the call and
+    /// put side by side: `call_ticker`, `call_close`, `call_volume` and the
+    /// same for `put_*`. `days_to_expiry` counts calendar days from `as_of`,
+    /// and `underlying_price` is the underlying's `as_of` close (null if its
+    /// stocks file or row is missing). Contracts that didn't trade that day
+    /// have no row in the flat files, so they are absent from the chain.
+    pub async fn build_option_chain(&self, underlying: &str, as_of: NaiveDate) -> Result<datafusion::dataframe::DataFrame> {
+        use datafusion::arrow::datatypes::DataType;
+        use datafusion::functions::expr_fn::starts_with;
+        use datafusion::functions_aggregate::expr_fn::max;
+        use datafusion::logical_expr::{cast, when};
+        use datafusion::prelude::{col, lit};
+
+        let contracts = self
+            .load_data(AssetClass::Options, PolygonDataType::DayAggs, as_of, &[])
+            .await?
+            .filter(
+                starts_with(col("ticker"), lit(format!("{}{}", POLYGON_OPTION_PREFIX, underlying)))
+                    .and(col("underlying").eq(lit(underlying))),
+            )?;
+
+        let mut sides = Vec::new();
+        for option_type in [OptionType::Call, OptionType::Put] {
+            let is_side = || col("option_type").eq(lit(option_type.as_str()));
+            for column in ["ticker", "close", "volume"] {
+                let value = when(is_side(), col(column)).end()?;
+                sides.push(max(value).alias(format!("{}_{}", option_type.as_str(), column)));
+            }
+        }
+
+        // NaiveDate's default is the Unix epoch
+        let as_of_days = as_of.signed_duration_since(NaiveDate::default()).num_days();
+        let days_to_expiry = cast(cast(col("expiration"), DataType::Int32), DataType::Int64) - lit(as_of_days);
+        let underlying_price = self.underlying_close(underlying, as_of).await;
+
+        let mut columns = vec![
+            lit(underlying).alias("underlying"),
+            col("expiration"),
+            days_to_expiry.alias("days_to_expiry"),
+            col("strike"),
+        ];
+        for option_type in [OptionType::Call, OptionType::Put] {
+            for column in ["ticker", "close", "volume"] {
+                columns.push(col(format!("{}_{}", option_type.as_str(), column)));
+            }
+        }
+        columns.push(lit(underlying_price).alias("underlying_price"));
+
+        contracts
+            .aggregate(vec![col("expiration"), col("strike")], sides)?
+            .select(columns)?
+            .sort(vec![col("expiration").sort(true, false), col("strike").sort(true, false)])
+    }
+
+    /// Load futures day aggregates from `start` to `end` (inclusive) as one
+    /// continuous `root` series (see [`build_continuous`])
+    pub async fn load_continuous(
+        &self,
+        root: &str,
+        start: NaiveDate,
+        end: NaiveDate,
+        roll: RollRule,
+        adjust: BackAdjust,
+        options: &DownloadOptions,
+    ) -> Result<datafusion::dataframe::DataFrame> {
+        let bars = self
+            .load_date_range(AssetClass::Futures, PolygonDataType::DayAggs, start, end, &[], options)
+            .await?;
+        build_continuous(&self.ctx, bars, root, roll, adjust).await
+    }
+
+    /// Stock close of `symbol` on `date`, if its day aggregates have one
+    async fn underlying_close(&self, symbol: &str, date: NaiveDate) -> Option<f64> {
+        use datafusion::arrow::array::AsArray;
+        use datafusion::arrow::datatypes::{DataType, Float64Type};
+
+        let df = self.load_file(AssetClass::Stocks, PolygonDataType::DayAggs, date, &[symbol]).await.ok()?;
+        let closes = df
+            .select(vec![datafusion::logical_expr::cast(datafusion::prelude::col("close"), DataType::Float64)])
+            .ok()?
+            .collect()
+            .await
+            .ok()?;
+        let closes = closes.iter().find(|batch| batch.num_rows() > 0)?.column(0).as_primitive::<Float64Type>();
+        closes.iter().flatten().next()
+    }
+
+    /// Load one day's bar of every stock: Polygon's grouped daily data
+    ///
+    /// Columns of the file are cast to the [`flat_file_schema`] types, so
+    /// they match whether the file came from S3 or from a local copy whose
+    /// schema was inferred.
+    pub async fn load_grouped_daily(&self, date: NaiveDate) -> Result<datafusion::dataframe::DataFrame> {
+        use datafusion::logical_expr::cast;
+        use datafusion::prelude::col;
+
+        let df = self.load_data(AssetClass::Stocks, PolygonDataType::GroupedDaily, date, &[]).await?;
+        let Some(schema) = flat_file_schema(&AssetClass::Stocks, &PolygonDataType::GroupedDaily) else {
+            return Ok(df);
         };
-        
-        let file_path = match &self.source {
-            DataSource::S3(config) => {
-                format!(
-                    "s3://{}/{}/{}/{}/{}-{:02}-{:02}.csv.gz",
-                    &config.bucket,
-                    asset_class.s3_prefix(),
-                    data_type_str,
-                    date.format("%Y"),
-                    date.format("%Y"),
-                    date.month(),
-                    date.day()
-                )
+        let columns = df
+            .schema()
+            .fields()
+            .iter()
+            .map(|field| match schema.field_with_name(field.name()) {
+                Ok(typed) => cast(col(field.name()), typed.data_type().clone()).alias(field.name()),
+                Err(_) => col(field.name()),
+            })
+            .collect::<Vec<_>>();
+        df.select(columns)
+    }
+
+    /// Whole-market screener table for `date`, one row per stock
+    ///
+    /// Combines the grouped daily bars of `date` with those of up to
+    /// `lookback` earlier trading days, downloaded as in
+    /// [`load_date_range`](Self::load_date_range). Besides `ticker`, `date`,
+    /// `open`, `high`, `low`, `close`, `volume` and `transactions` of `date`,
+    /// each row has `previous_close` (the last earlier close), `change` and
+    /// `change_percent` against it, `avg_volume` (mean daily volume over the
+    /// earlier days) and `relative_volume` (volume over `avg_volume`).
+    /// Stocks with no earlier bar have nulls in those columns. Fails with
+    /// [`DataNotAvailable`] when `date` has no file.
+    pub async fn market_snapshot(
+        &self,
+        date: NaiveDate,
+        lookback: usize,
+        options: &DownloadOptions,
+    ) -> Result<datafusion::dataframe::DataFrame> {
+        use datafusion::arrow::datatypes::DataType;
+        use datafusion::common::JoinType;
+        use datafusion::functions_aggregate::expr_fn::avg;
+        use datafusion::functions_aggregate::first_last::last_value_udaf;
+        use datafusion::logical_expr::{cast, ExprFunctionExt};
+        use datafusion::prelude::{col, lit};
+        use datafusion::scalar::ScalarValue;
+
+        // Trading days fill at least 5 in every 7 calendar days, plus holidays
+        let start = date - chrono::Duration::days(lookback as i64 * 7 / 5 + 10);
+        let mut days = candidate_days(&AssetClass::Stocks, start, date);
+        if days.last() != Some(&date) {
+            days.push(date);
+        }
+        let days = days.split_off(days.len().saturating_sub(lookback + 1));
+
+        let limiter = RateLimiter::new(options.requests_per_second);
+        let loaded = futures::stream::iter(days)
+            .map(|day| {
+                let limiter = &limiter;
+                async move {
+                    limiter.acquire().await;
+                    match self.load_grouped_daily(day).await {
+                        Ok(df) => {
+                            // NaiveDate's default is the Unix epoch
+                            let epoch_days = day.signed_duration_since(NaiveDate::default()).num_days() as i32;
+                            Ok(Some(df.with_column("date", lit(ScalarValue::Date32(Some(epoch_days))))?))
+                        }
+                        Err(e) if day != date && is_missing_file(&e) => Ok(None),
+                        Err(e) => Err(e),
+                    }
+                }
+            })
+            .buffered(options.concurrency.max(1))
+            .try_collect::<Vec<_>>()
+            .await?;
+
+        let mut loaded = loaded.into_iter().flatten().collect::<Vec<_>>();
+        // The last day is `date`, which loads or fails above
+        let latest = loaded.pop().ok_or_else(|| {
+            datafusion::error::DataFusionError::Internal(format!("Market snapshot loaded no data for {}", date))
+        })?;
+
+        let today = latest.clone().select(vec![
+            col("ticker"),
+            col("date"),
+            col("open"),
+            col("high"),
+            col("low"),
+            col("close"),
+            col("volume"),
+            col("transactions"),
+        ])?;
+        let output = vec![
+            col("ticker"),
+            col("date"),
+            col("open"),
+            col("high"),
+            col("low"),
+            col("close"),
+            col("volume"),
+            col("transactions"),
+            col("previous_close"),
+            (col("close") - col("previous_close")).alias("change"),
+            ((col("close") - col("previous_close")) / col("previous_close") * lit(100.0)).alias("change_percent"),
+            col("avg_volume"),
+            (cast(col("volume"), DataType::Float64) / col("avg_volume")).alias("relative_volume"),
+        ];
+
+        // Without an earlier day, an empty history leaves the comparisons null
+        let mut earlier = latest.clone().filter(lit(false))?;
+        for day in loaded {
+            earlier = earlier.union(day)?;
+        }
+
+        let previous_close = last_value_udaf()
+            .call(vec![cast(col("close"), DataType::Float64)])
+            .order_by(vec![col("date").sort(true, false)])
+            .build()?
+            .alias("previous_close");
+        let stats = earlier.aggregate(
+            vec![col("ticker").alias("__ticker")],
+            vec![previous_close, avg(cast(col("volume"), DataType::Float64)).alias("avg_volume")],
+        )?;
+
+        today
+            .join(stats, JoinType::Left, &["ticker"], &["__ticker"], None)?
+            .select(output)?
+            .sort(vec![col("ticker").sort(true, false)])
+    }
+
+    /// Load every daily file from `start` to `end` (inclusive) into one DataFrame
+    ///
+    /// Days are downloaded in parallel, at most `options.concurrency` at a
+    /// time and no faster than `options.requests_per_second`, and returned in
+    /// date order. Stock, option and index downloads only request NYSE
+    /// trading days; other days without a file are skipped.
+    pub async fn load_date_range(
+        &self,
+        asset_class: AssetClass,
+        data_type: PolygonDataType,
+        start: NaiveDate,
+        end: NaiveDate,
+        symbols: &[&str],
+        options: &DownloadOptions,
+    ) -> Result<datafusion::dataframe::DataFrame> {
+        let limiter = RateLimiter::new(options.requests_per_second);
+        let days = candidate_days(&asset_class, start, end);
+
+        let downloads = futures::stream::iter(days)
+            .map(|date| {
+                let (limiter, asset_class, data_type) = (&limiter, asset_class.clone(), data_type.clone());
+                async move {
+                    limiter.acquire().await;
+                    match self.load_data(asset_class, data_type, date, symbols).await {
+                        Ok(df) => df.collect().await.map(Some),
+                        Err(e) if is_missing_file(&e) => Ok(None),
+                        Err(e) => Err(e),
+                    }
+                }
+            })
+            .buffered(options.concurrency.max(1))
+            .try_collect::<Vec<_>>()
+            .await?;
+
+        let mut combined: Option<datafusion::dataframe::DataFrame> = None;
+        for batches in downloads.into_iter().flatten().filter(|batches| !batches.is_empty()) {
+            let day = self.ctx.read_batches(batches)?;
+            combined = Some(match combined {
+                Some(df) => df.union(day)?,
+                None => day,
+            });
+        }
+
+        combined.ok_or_else(|| {
+            datafusion::error::DataFusionError::Execution(format!(
+                "No {:?} {:?} files found between {} and {}",
+                asset_class, data_type, start, end
+            ))
+        })
+    }
+
+    /// Download every daily file from `start` to `end` (inclusive) ahead of use
+    ///
+    /// Files are fetched with the same concurrency, rate limit and trading
+    /// day selection as [`load_date_range`](Self::load_date_range), and
+    /// `progress` is called as each day finishes. Days already in `target`
+    /// are skipped, so an interrupted backfill can simply be rerun.
+    pub async fn prefetch(
+        &self,
+        asset_class: AssetClass,
+        data_type: PolygonDataType,
+        start: NaiveDate,
+        end: NaiveDate,
+        target: &PrefetchTarget,
+        options: &DownloadOptions,
+        progress: impl Fn(&PrefetchProgress),
+    ) -> Result<PrefetchSummary> {
+        if self.source.is_local() {
+            return Err(datafusion::error::DataFusionError::Configuration(
+                "Prefetching needs a remote data source".to_string(),
+            ));
+        }
+        if matches!(target, PrefetchTarget::Cache) && self.cache.is_none() {
+            return Err(datafusion::error::DataFusionError::Configuration(
+                "Prefetching into the cache needs with_cache".to_string(),
+            ));
+        }
+
+        let limiter = RateLimiter::new(options.requests_per_second);
+        let days = candidate_days(&asset_class, start, end);
+        let total = days.len();
+        let schema = flat_file_schema(&asset_class, &data_type);
+
+        let mut downloads = futures::stream::iter(days)
+            .map(|date| {
+                let (limiter, schema) = (&limiter, schema.as_ref());
+                let file_path = self.flat_file_path(&asset_class, &data_type, date);
+                async move {
+                    let status = self.prefetch_file(&file_path, schema, target, limiter).await?;
+                    Ok::<_, datafusion::error::DataFusionError>((date, status))
+                }
+            })
+            .buffer_unordered(options.concurrency.max(1));
+
+        let mut summary = PrefetchSummary::default();
+        while let Some((date, status)) = downloads.try_next().await? {
+            match status {
+                PrefetchStatus::Downloaded => summary.downloaded.push(date),
+                PrefetchStatus::Present => summary.present.push(date),
+                PrefetchStatus::Missing => summary.missing.push(date),
             }
-            DataSource::Local { .. } => {
-                format!(
-                    "{}/{}/{}/{}-{:02}-{:02}.csv.gz",
-                    asset_class.s3_prefix(),
-                    data_type_str,
-                    date.format("%Y"),
-                    date.format("%Y"),
-                    date.month(),
-                    date.day()
-                )
+            progress(&PrefetchProgress {
+                date,
+                status,
+                completed: summary.downloaded.len() + summary.present.len() + summary.missing.len(),
+                total,
+            });
+        }
+
+        summary.downloaded.sort();
+        summary.present.sort();
+        summary.missing.sort();
+        Ok(summary)
+    }
+
+    /// Fetch one file into `target`, unless it is already there
+    async fn prefetch_file(
+        &self,
+        file_path: &str,
+        schema: Option<&SchemaRef>,
+        target: &PrefetchTarget,
+        limiter: &RateLimiter,
+    ) -> Result<PrefetchStatus> {
+        let store = self.object_store()?;
+        let location = self.object_key(file_path);
+
+        let dir = match target {
+            PrefetchTarget::Cache if self.is_cached(file_path) => return Ok(PrefetchStatus::Present),
+            PrefetchTarget::Cache => {
+                limiter.acquire().await;
+                let exists = match store.head(&location).await {
+                    Ok(_) => true,
+                    Err(object_store::Error::NotFound { .. }) => false,
+                    Err(e) => return Err(PolygonError::from_object_store(e, file_path)),
+                };
+                if !exists {
+                    return Ok(PrefetchStatus::Missing);
+                }
+                self.cached_parquet(file_path, schema).await?;
+                return Ok(PrefetchStatus::Downloaded);
+            }
+            PrefetchTarget::Mirror(dir) if local_csv_path(dir, location.as_ref()).is_some() => {
+                return Ok(PrefetchStatus::Present)
             }
+            PrefetchTarget::Mirror(dir) => dir,
         };
-        
-        self.load_csv_from_source(&file_path, symbol.unwrap_or("")).await
+
+        limiter.acquire().await;
+        let bytes = match store.get(&location).await {
+            Ok(result) => result.bytes().await.map_err(|e| PolygonError::from_object_store(e, file_path))?,
+            Err(object_store::Error::NotFound { .. }) => return Ok(PrefetchStatus::Missing),
+            Err(e) => return Err(PolygonError::from_object_store(e, file_path)),
+        };
+
+        // Write beside the final path and rename, so an interrupted download
+        // never looks like a complete file
+        let destination = dir.join(location.as_ref());
+        if let Some(parent) = destination.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let partial = destination.with_extension("gz.partial");
+        std::fs::write(&partial, &bytes)?;
+        std::fs::rename(&partial, &destination)?;
+        Ok(PrefetchStatus::Downloaded)
+    }
+
+    /// Write `df` to the local directory `path` as Hive-partitioned Parquet,
+    /// e.g. `path/date=2024-01-02/ticker=AAPL/*.parquet`
+    ///
+    /// Partition columns are kept in the directory names rather than the
+    /// files. A `date` partition `df` doesn't have is derived from its
+    /// `window_start` or `sip_timestamp` as the exchange date (see
+    /// `with_exchange_time`; NYSE unless the client has a calendar).
+    ///
+    /// The output replaces anything already at `path`. It is written to
+    /// `path.partial` first, so a failed write leaves the old output as it
+    /// was. Swapping it in takes two renames, not one atomic step: while the
+    /// old output moves to `path.previous` there is briefly nothing at
+    /// `path`, and a crash in between leaves the old output there (the next
+    /// call clears it). Readers that must never see that gap should write
+    /// to a new directory each time and switch to it themselves.
+    pub async fn write_partitioned(
+        &self,
+        df: datafusion::dataframe::DataFrame,
+        path: impl AsRef<Path>,
+        partition_cols: &[&str],
+    ) -> Result<()> {
+        use datafusion::arrow::datatypes::DataType;
+        use datafusion::prelude::{cast, col};
+
+        let path = path.as_ref();
+        let mut df = df;
+        if partition_cols.contains(&DATE_COLUMN) && !df.schema().has_column_with_unqualified_name(DATE_COLUMN) {
+            let calendar = self.exchange_time.clone().unwrap_or_default();
+            let added = [LOCAL_TIME_COLUMN, SESSION_COLUMN]
+                .into_iter()
+                .filter(|name| !df.schema().has_column_with_unqualified_name(name))
+                .collect::<Vec<_>>();
+            df = with_exchange_time_auto(df, &calendar)?;
+            if !df.schema().has_column_with_unqualified_name(DATE_COLUMN) {
+                return Err(datafusion::error::DataFusionError::Plan(format!(
+                    "Cannot partition by '{}': no such column and no timestamp column to derive it from",
+                    DATE_COLUMN
+                )));
+            }
+            df = df.drop_columns(&added)?;
+        }
+        // Hive partition values are written as text
+        for name in partition_cols {
+            if df.schema().field_with_unqualified_name(name)?.data_type() != &DataType::Utf8 {
+                df = df.with_column(name, cast(col(*name), DataType::Utf8))?;
+            }
+        }
+
+        let file_name = path.file_name().ok_or_else(|| {
+            datafusion::error::DataFusionError::Configuration(format!("Invalid output directory: {}", path.display()))
+        })?;
+        let sibling = |suffix: &str| {
+            let mut name = file_name.to_os_string();
+            name.push(suffix);
+            path.with_file_name(name)
+        };
+        let (partial, previous) = (sibling(".partial"), sibling(".previous"));
+        if partial.exists() {
+            std::fs::remove_dir_all(&partial)?;
+        }
+        std::fs::create_dir_all(&partial)?;
+        df.write_parquet(
+            &format!("{}/", partial.to_string_lossy()),
+            DataFrameWriteOptions::new()
+                .with_partition_by(partition_cols.iter().map(|name| name.to_string()).collect()),
+            None,
+        )
+        .await?;
+
+        // A directory can't be renamed over a non-empty one, so move the old
+        // output aside first and delete it once the new one is in place
+        if path.exists() {
+            if previous.exists() {
+                std::fs::remove_dir_all(&previous)?;
+            }
+            std::fs::rename(path, &previous)?;
+            if let Err(e) = std::fs::rename(&partial, path) {
+                // Put the old output back rather than leave nothing at `path`
+                std::fs::rename(&previous, path)?;
+                return Err(e.into());
+            }
+            std::fs::remove_dir_all(&previous)?;
+        } else {
+            std::fs::rename(&partial, path)?;
+        }
+        Ok(())
+    }
+
+    /// Flat file path of a dataset for one day, as `.csv.gz`
+    fn flat_file_path(&self, asset_class: &AssetClass, data_type: &PolygonDataType, date: NaiveDate) -> String {
+        let key = self.paths.resolve(asset_class, data_type, date);
+        match self.source.base_url() {
+            Some(base_url) => format!("{}/{}", base_url, key),
+            None => key,
+        }
+    }
+
+    /// The same client reading into `ctx`, which must already have the
+    /// source's object store registered
+    pub(super) fn in_context(&self, ctx: SessionContext) -> Self {
+        Self {
+            source: self.source.clone(),
+            ctx,
+            cache: self.cache.clone(),
+            api_key: self.api_key.clone(),
+            exchange_time: self.exchange_time.clone(),
+            paths: self.paths.clone(),
+        }
     }
 
     /// Get the session context for custom queries
@@ -289,3 +1363,347 @@ impl PolygonClient {
         &self.ctx
     }
 }
+
+/// Whether an error means the requested file doesn't exist
+pub(super) fn is_missing_file(error: &datafusion::error::DataFusionError) -> bool {
+    DataNotAvailable::from_error(error).is_some()
+        || matches!(PolygonError::from_error(error), Some(PolygonError::ObjectNotFound { .. }))
+}
+
+/// Days from `start` to `end` (inclusive) that may have a file
+///
+/// Stock, option and index days the exchange was closed are skipped; crypto
+/// and forex trade through them.
+pub(super) fn candidate_days(asset_class: &AssetClass, start: NaiveDate, end: NaiveDate) -> Vec<NaiveDate> {
+    let calendar = matches!(asset_class, AssetClass::Stocks | AssetClass::Options | AssetClass::Indices)
+        .then(ExchangeCalendar::nyse);
+    start
+        .iter_days()
+        .take_while(|day| *day <= end)
+        .filter(|day| calendar.as_ref().map_or(true, |calendar| calendar.is_trading_day(*day)))
+        .collect()
+}
+
+/// Up to `limit` keys under `prefix` in the bucket
+async fn list_store_files(store: &dyn ObjectStore, prefix: &ObjectPath, limit: usize) -> Result<Vec<String>> {
+    store.list(Some(prefix))
+        .take(limit)
+        .map_ok(|meta| meta.location.to_string())
+        .try_collect()
+        .await
+        .map_err(Into::into)
+}
+
+/// First existing local CSV copy of a flat file, plain or compressed
+fn local_csv_path(root: &Path, path: &str) -> Option<PathBuf> {
+    LOCAL_CSV_EXTENSIONS
+        .iter()
+        .map(|extension| local_path(root, path, extension))
+        .find(|candidate| candidate.exists())
+}
+
+/// Compression of a file, from its leading magic bytes
+fn detect_compression(path: &Path) -> Result<FileCompressionType> {
+    use std::io::Read;
+
+    let mut magic = [0u8; 4];
+    let read = std::fs::File::open(path)?.read(&mut magic)?;
+    Ok(match &magic[..read] {
+        [0x1f, 0x8b, ..] => FileCompressionType::GZIP,
+        [0x28, 0xb5, 0x2f, 0xfd] => FileCompressionType::ZSTD,
+        [b'B', b'Z', b'h', ..] => FileCompressionType::BZIP2,
+        _ => FileCompressionType::UNCOMPRESSED,
+    })
+}
+
+/// Run blocking file work on tokio's blocking pool
+async fn spawn_blocking<T: Send + 'static>(work: impl FnOnce() -> Result<T> + Send + 'static) -> Result<T> {
+    tokio::task::spawn_blocking(work)
+        .await
+        .map_err(|e| datafusion::error::DataFusionError::External(Box::new(e)))?
+}
+
+/// Everything from the first `.` of the file name, e.g. `.csv.zst`
+fn file_extension(path: &Path) -> String {
+    let name = path.file_name().map(|name| name.to_string_lossy()).unwrap_or_default();
+    name.find('.').map(|dot| name[dot..].to_string()).unwrap_or_default()
+}
+
+/// Local path of a flat file, with `extension` in place of `.csv.gz`
+fn local_path(root: &Path, path: &str, extension: &str) -> PathBuf {
+    let path_part = path
+        .strip_prefix("s3://flatfiles/")
+        .or_else(|| path.strip_prefix("file://"))
+        .unwrap_or(path);
+    root.join(path_part.replace(".csv.gz", extension))
+}
+
+/// Cache location of an S3 flat file: its key under the cache directory, as Parquet
+fn cache_path(cache: &CacheConfig, path: &str) -> PathBuf {
+    // Drop the `scheme://bucket/` prefix
+    let key = path
+        .split_once("://")
+        .and_then(|(_, rest)| rest.split_once('/'))
+        .map(|(_, key)| key)
+        .unwrap_or(path);
+    cache.dir.join(key.replace(".csv.gz", ".parquet"))
+}
+
+/// Whether a cached file exists and is younger than `ttl`
+fn is_fresh(path: &Path, ttl: Option<Duration>) -> bool {
+    let Ok(modified) = std::fs::metadata(path).and_then(|meta| meta.modified()) else {
+        return false;
+    };
+    match ttl {
+        Some(ttl) => modified.elapsed().map(|age| age <= ttl).unwrap_or(true),
+        None => true,
+    }
+}
+
+/// Parse the header and the lines of `symbols` from CSV text led by a
+/// `ticker` column, or `None` if the first column is something else
+fn read_csv_symbols(
+    reader: impl BufRead,
+    path: &str,
+    schema: Option<&SchemaRef>,
+    symbols: &[&str],
+) -> Result<Option<(SchemaRef, Vec<RecordBatch>)>> {
+    let unquote = |field: &str| field.trim().trim_matches('"').to_string();
+    let leading = |line: &str| unquote(line.split(',').next().unwrap_or_default());
+    // A corrupt or truncated archive surfaces as an invalid-data read error
+    let read_error = |e: std::io::Error| -> datafusion::error::DataFusionError {
+        match e.kind() {
+            std::io::ErrorKind::InvalidData | std::io::ErrorKind::InvalidInput | std::io::ErrorKind::UnexpectedEof => {
+                PolygonError::Decompression { path: path.to_string(), message: e.to_string() }.into()
+            }
+            _ => e.into(),
+        }
+    };
+    let schema_error = |message: String| -> datafusion::error::DataFusionError {
+        PolygonError::SchemaMismatch { path: path.to_string(), message }.into()
+    };
+
+    let mut lines = reader.lines();
+    let Some(header) = lines.next().transpose().map_err(read_error)? else {
+        return Ok(None);
+    };
+    if leading(&header) != "ticker" {
+        return Ok(None);
+    }
+    if let Some(schema) = schema {
+        let columns = header.split(',').map(unquote).collect::<Vec<_>>();
+        let expected = schema.fields().iter().map(|f| f.name().to_string()).collect::<Vec<_>>();
+        if columns != expected {
+            return Err(schema_error(format!(
+                "expected columns {} but the header is {}",
+                expected.join(","),
+                columns.join(",")
+            )));
+        }
+    }
+
+    let mut kept = header;
+    kept.push('\n');
+    for line in lines {
+        let line = line.map_err(read_error)?;
+        if symbols.contains(&leading(&line).as_str()) {
+            kept.push_str(&line);
+            kept.push('\n');
+        }
+    }
+
+    let schema = match schema {
+        Some(schema) => schema.clone(),
+        None => {
+            let (inferred, _) = Format::default()
+                .with_header(true)
+                .infer_schema(Cursor::new(kept.as_bytes()), None)
+                .map_err(|e| schema_error(e.to_string()))?;
+            Arc::new(inferred)
+        }
+    };
+    let batches = ReaderBuilder::new(schema.clone())
+        .with_header(true)
+        .build(Cursor::new(kept.into_bytes()))?
+        .collect::<std::result::Result<Vec<_>, _>>()
+        .map_err(|e| schema_error(e.to_string()))?;
+    Ok(Some((schema, batches)))
+}
+
+/// Keep only rows whose ticker is in `symbols`, as one IN list so the file is scanned once
+fn filter_symbols(df: datafusion::dataframe::DataFrame, symbols: &[&str]) -> Result<datafusion::dataframe::DataFrame> {
+    if symbols.is_empty() {
+        return Ok(df);
+    }
+    let symbols = symbols.iter().map(|s| datafusion::prelude::lit(*s)).collect();
+    df.filter(datafusion::prelude::col("ticker").in_list(symbols, false))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// An empty directory under the system temp dir, unique to this process
+    fn scratch_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("polygon-client-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).expect("create scratch directory");
+        dir
+    }
+
+    const DAY_AGGS_CSV: &str = "ticker,volume,close\nAAPL,100,185.5\nMSFT,200,370.1\n\"AAPL\",150,186.0\nAAPLX,50,1.0\n";
+
+    fn row_count(batches: &[RecordBatch]) -> usize {
+        batches.iter().map(|batch| batch.num_rows()).sum()
+    }
+
+    #[test]
+    fn test_read_csv_symbols() -> Result<()> {
+        // Quoted tickers match; other tickers, including longer ones, do not
+        let (schema, batches) = read_csv_symbols(Cursor::new(DAY_AGGS_CSV), "day.csv", None, &["AAPL"])?.unwrap();
+        let names = schema.fields().iter().map(|f| f.name().as_str()).collect::<Vec<_>>();
+        assert_eq!(names, ["ticker", "volume", "close"]);
+        assert_eq!(row_count(&batches), 2);
+
+        // No matching lines still yields the header's columns
+        let (schema, batches) = read_csv_symbols(Cursor::new(DAY_AGGS_CSV), "day.csv", None, &["TSLA"])?.unwrap();
+        assert_eq!(schema.fields().len(), 3);
+        assert_eq!(row_count(&batches), 0);
+
+        // Files not led by a ticker column, and empty files, are left to the full reader
+        assert!(read_csv_symbols(Cursor::new("date,value\n2024-01-02,1\n"), "x.csv", None, &["AAPL"])?.is_none());
+        assert!(read_csv_symbols(Cursor::new(""), "x.csv", None, &["AAPL"])?.is_none());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_read_csv_symbols_with_schema() -> Result<()> {
+        use datafusion::arrow::datatypes::{DataType, Field, Schema};
+
+        // A fixed schema is used as given rather than inferred
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("ticker", DataType::Utf8, false),
+            Field::new("volume", DataType::Float64, true),
+            Field::new("close", DataType::Float64, true),
+        ]));
+        let (read, batches) = read_csv_symbols(Cursor::new(DAY_AGGS_CSV), "day.csv", Some(&schema), &["MSFT"])?.unwrap();
+        assert_eq!(read, schema);
+        assert_eq!(batches[0].column(1).data_type(), &DataType::Float64);
+        assert_eq!(row_count(&batches), 1);
+
+        // A header that disagrees with it is a schema mismatch
+        let renamed = "ticker,shares,close\nMSFT,200,370.1\n";
+        let err = read_csv_symbols(Cursor::new(renamed), "day.csv", Some(&schema), &["MSFT"]).unwrap_err();
+        assert!(matches!(PolygonError::from_error(&err), Some(PolygonError::SchemaMismatch { .. })), "{}", err);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_load_csv_symbols() -> Result<()> {
+        use std::io::Write;
+
+        let root = scratch_dir("load_csv_symbols");
+        let client = PolygonClient::new(DataSource::Local { root: root.clone() })?;
+        std::fs::create_dir_all(root.join("day_aggs"))?;
+
+        // Plain and gzipped copies are both read line by line
+        std::fs::write(root.join("day_aggs/2024-01-02.csv"), DAY_AGGS_CSV)?;
+        let mut gz = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+        gz.write_all(DAY_AGGS_CSV.as_bytes())?;
+        std::fs::write(root.join("day_aggs/2024-01-03.csv.gz"), gz.finish()?)?;
+
+        for path in ["day_aggs/2024-01-02.csv.gz", "day_aggs/2024-01-03.csv.gz"] {
+            let df = client.load_csv_symbols(path, None, &["AAPL", "MSFT"]).await?.unwrap();
+            assert_eq!(df.count().await?, 3, "{}", path);
+        }
+
+        std::fs::remove_dir_all(&root)?;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_is_missing_file() -> Result<()> {
+        use datafusion::error::DataFusionError;
+
+        // Only typed errors count; messages that merely mention "not found"
+        // or a 404 are real failures
+        let missing = DataFusionError::from(PolygonError::ObjectNotFound { path: "x".to_string() });
+        assert!(is_missing_file(&missing));
+        assert!(is_missing_file(&DataFusionError::Context("loading".to_string(), Box::new(missing))));
+        assert!(!is_missing_file(&DataFusionError::Plan("column \"price\" not found".to_string())));
+        assert!(!is_missing_file(&DataFusionError::Execution("GET https://example.com/404 failed".to_string())));
+
+        // A local file that isn't there is typed where it is opened
+        let root = scratch_dir("is_missing_file");
+        let client = PolygonClient::new(DataSource::Local { root: root.clone() })?;
+        let error = client.load_csv_symbols("day_aggs/2024-01-02.csv.gz", None, &["AAPL"]).await.unwrap_err();
+        assert!(is_missing_file(&error), "{}", error);
+
+        std::fs::remove_dir_all(&root)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_candidate_days() {
+        let day = |d: u32| NaiveDate::from_ymd_opt(2024, 1, d).unwrap();
+
+        // Stocks skip the weekend and New Year's Day; crypto trades through them
+        assert_eq!(candidate_days(&AssetClass::Stocks, day(1), day(8)), [day(2), day(3), day(4), day(5), day(8)]);
+        assert_eq!(candidate_days(&AssetClass::Crypto, day(1), day(8)).len(), 8);
+        assert!(candidate_days(&AssetClass::Crypto, day(8), day(1)).is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_rate_limiter() {
+        // Without a rate, or with a non-positive one, requests are not delayed
+        for rate in [None, Some(0.0), Some(-1.0)] {
+            let limiter = RateLimiter::new(rate);
+            let start = Instant::now();
+            for _ in 0..100 {
+                limiter.acquire().await;
+            }
+            assert!(start.elapsed() < Duration::from_millis(50), "{:?}", rate);
+        }
+
+        // At 50 per second the first request goes at once and the next four
+        // are spaced 20ms apart, counted from when the limiter was made
+        let limiter = RateLimiter::new(Some(50.0));
+        let start = Instant::now();
+        for _ in 0..5 {
+            limiter.acquire().await;
+        }
+        assert!(start.elapsed() >= Duration::from_millis(75), "{:?}", start.elapsed());
+    }
+
+    #[tokio::test]
+    async fn test_write_partitioned_replaces_output() -> Result<()> {
+        let root = scratch_dir("write_partitioned");
+        let client = PolygonClient::new(DataSource::Local { root: root.clone() })?;
+        let out = root.join("bars");
+
+        let first = client
+            .session_context()
+            .sql("SELECT * FROM (VALUES ('AAPL', '2024-01-02', 1.0), ('MSFT', '2024-01-02', 2.0)) AS t(ticker, date, close)")
+            .await?;
+        client.write_partitioned(first, &out, &["date", "ticker"]).await?;
+        assert!(out.join("date=2024-01-02/ticker=AAPL").is_dir());
+        assert!(out.join("date=2024-01-02/ticker=MSFT").is_dir());
+
+        // A partial write left by a crash is cleared, and the new output replaces the old
+        std::fs::create_dir_all(root.join("bars.partial/stale"))?;
+        let second = client
+            .session_context()
+            .sql("SELECT * FROM (VALUES ('AAPL', '2024-01-03', 3.0)) AS t(ticker, date, close)")
+            .await?;
+        client.write_partitioned(second, &out, &["date", "ticker"]).await?;
+        assert!(out.join("date=2024-01-03/ticker=AAPL").is_dir());
+        assert!(!out.join("date=2024-01-02").exists());
+        assert!(!root.join("bars.partial").exists());
+        assert!(!root.join("bars.previous").exists());
+
+        std::fs::remove_dir_all(&root)?;
+        Ok(())
+    }
+}
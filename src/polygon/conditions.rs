@@ -0,0 +1,292 @@
+//! Decoding of stock trade condition codes
+//!
+//! Stock `trades_v1` rows list their sale conditions as Polygon condition
+//! IDs, e.g. `"12,37"` for an odd lot traded in extended hours. Whether a
+//! trade sets the consolidated last sale or counts toward consolidated
+//! volume depends on those conditions, following the CTA and UTP
+//! eligibility rules. [`with_condition_columns`] adds readable flags and the
+//! eligibility of each trade.
+
+use std::any::Any;
+use std::sync::Arc;
+
+use datafusion::arrow::array::{ArrayRef, AsArray, BooleanArray};
+use datafusion::arrow::compute::cast;
+use datafusion::arrow::datatypes::DataType;
+use datafusion::dataframe::DataFrame;
+use datafusion::error::Result;
+use datafusion::logical_expr::{ColumnarValue, ScalarUDF, ScalarUDFImpl, Signature, Volatility};
+use datafusion::prelude::col;
+
+/// A stock sale condition and its effect on consolidated data
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TradeCondition {
+    pub id: u16,
+    pub name: &'static str,
+    /// Can set the consolidated last sale price
+    pub updates_last: bool,
+    /// Counts toward consolidated volume
+    pub updates_volume: bool,
+}
+
+const fn condition(id: u16, name: &'static str, updates_last: bool, updates_volume: bool) -> TradeCondition {
+    TradeCondition { id, name, updates_last, updates_volume }
+}
+
+/// Polygon's stock trade conditions
+pub const TRADE_CONDITIONS: &[TradeCondition] = &[
+    condition(0, "Regular Trade", true, true),
+    condition(1, "Acquisition", true, true),
+    condition(2, "Average Price Trade", false, true),
+    condition(3, "Automatic Execution", true, true),
+    condition(4, "Bunched Trade", true, true),
+    condition(5, "Bunched Sold Trade", false, true),
+    condition(6, "CAP Election", true, true),
+    condition(7, "Cash Sale", false, true),
+    condition(8, "Closing Prints", true, true),
+    condition(9, "Cross Trade", true, true),
+    condition(10, "Derivatively Priced", false, true),
+    condition(11, "Distribution", true, true),
+    condition(12, "Form T", false, true),
+    condition(13, "Extended Trading Hours (Sold Out of Sequence)", false, true),
+    condition(14, "Intermarket Sweep", true, true),
+    condition(15, "Market Center Official Close", false, false),
+    condition(16, "Market Center Official Open", false, false),
+    condition(17, "Market Center Opening Trade", true, true),
+    condition(18, "Market Center Reopening Trade", true, true),
+    condition(19, "Market Center Closing Trade", true, true),
+    condition(20, "Next Day", false, true),
+    condition(21, "Price Variation Trade", false, true),
+    condition(22, "Prior Reference Price", false, true),
+    condition(23, "Rule 155 Trade (AMEX)", true, true),
+    condition(24, "Rule 127 NYSE", true, true),
+    condition(25, "Opening Prints", true, true),
+    condition(27, "Stopped Stock (Regular Trade)", true, true),
+    condition(28, "Re-Opening Prints", true, true),
+    condition(29, "Seller", false, true),
+    condition(30, "Sold Last", true, true),
+    condition(32, "Sold (Out of Sequence)", false, true),
+    condition(33, "Sold (Out of Sequence) and Stopped Stock", false, true),
+    condition(34, "Split Trade", true, true),
+    condition(35, "Stock Option", true, true),
+    condition(36, "Yellow Flag Regular Trade", true, true),
+    condition(37, "Odd Lot Trade", false, true),
+    condition(38, "Corrected Consolidated Close", true, false),
+    condition(41, "Trade Thru Exempt", true, true),
+    condition(52, "Contingent Trade", false, true),
+    condition(53, "Qualified Contingent Trade", false, true),
+];
+
+/// Look up a condition by Polygon ID
+pub fn trade_condition(id: u16) -> Option<&'static TradeCondition> {
+    TRADE_CONDITIONS.iter().find(|c| c.id == id)
+}
+
+/// Parse a flat file `conditions` value such as `"12,37"`, skipping
+/// anything that isn't a condition ID
+pub fn parse_conditions(conditions: &str) -> Vec<u16> {
+    conditions
+        .split(|c: char| c == ',' || c == ' ' || c == '[' || c == ']')
+        .filter_map(|code| code.parse().ok())
+        .collect()
+}
+
+/// Readable flags of one trade's conditions
+///
+/// Unknown condition IDs don't affect eligibility. A trade without
+/// conditions is a regular, eligible trade.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TradeFlags {
+    /// Regular-way trade with no special handling
+    pub regular: bool,
+    pub odd_lot: bool,
+    pub derivatively_priced: bool,
+    pub extended_hours: bool,
+    pub intermarket_sweep: bool,
+    pub out_of_sequence: bool,
+    /// Eligible to set the consolidated last sale and counted in volume
+    pub eligible: bool,
+    /// Counted in consolidated volume
+    pub volume_eligible: bool,
+}
+
+impl TradeFlags {
+    pub fn from_codes(codes: &[u16]) -> Self {
+        let has = |ids: &[u16]| codes.iter().any(|code| ids.contains(code));
+        let known = || codes.iter().filter_map(|code| trade_condition(*code));
+        let updates_last = known().all(|c| c.updates_last);
+        let volume_eligible = known().all(|c| c.updates_volume);
+
+        Self {
+            regular: codes.iter().all(|code| matches!(code, 0 | 3 | 14 | 41)),
+            odd_lot: has(&[37]),
+            derivatively_priced: has(&[10]),
+            extended_hours: has(&[12, 13]),
+            intermarket_sweep: has(&[14]),
+            out_of_sequence: has(&[13, 32, 33]),
+            eligible: updates_last && volume_eligible,
+            volume_eligible,
+        }
+    }
+}
+
+/// Flag columns added by [`with_condition_columns`], in order
+pub const CONDITION_COLUMNS: &[&str] = &[
+    "is_regular",
+    "is_odd_lot",
+    "is_derivatively_priced",
+    "is_extended_hours",
+    "is_intermarket_sweep",
+    "is_out_of_sequence",
+    "is_eligible",
+    "is_volume_eligible",
+];
+
+/// Boolean flag of a `conditions` value, null-safe: null conditions are a
+/// regular trade
+#[derive(Debug)]
+struct ConditionFlagFunction {
+    name: String,
+    signature: Signature,
+    flag: fn(&TradeFlags) -> bool,
+}
+
+impl ConditionFlagFunction {
+    fn new(column: &str, flag: fn(&TradeFlags) -> bool) -> Self {
+        Self {
+            name: format!("trade_condition_{}", column),
+            signature: Signature::uniform(1, vec![DataType::Utf8], Volatility::Immutable),
+            flag,
+        }
+    }
+}
+
+impl ScalarUDFImpl for ConditionFlagFunction {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn signature(&self) -> &Signature {
+        &self.signature
+    }
+
+    fn return_type(&self, _arg_types: &[DataType]) -> Result<DataType> {
+        Ok(DataType::Boolean)
+    }
+
+    fn invoke(&self, args: &[ColumnarValue]) -> Result<ColumnarValue> {
+        let values = ColumnarValue::values_to_arrays(args)?;
+        let conditions = cast(&values[0], &DataType::Utf8)?;
+        let result: ArrayRef = Arc::new(
+            conditions
+                .as_string::<i32>()
+                .iter()
+                .map(|conditions| {
+                    let codes = conditions.map(parse_conditions).unwrap_or_default();
+                    Some((self.flag)(&TradeFlags::from_codes(&codes)))
+                })
+                .collect::<BooleanArray>(),
+        );
+        Ok(ColumnarValue::Array(result))
+    }
+}
+
+/// Add the [`CONDITION_COLUMNS`] flags decoded from the condition IDs in
+/// `conditions`
+pub fn with_condition_columns(df: DataFrame, conditions: &str) -> Result<DataFrame> {
+    let flags: [fn(&TradeFlags) -> bool; 8] = [
+        |f| f.regular,
+        |f| f.odd_lot,
+        |f| f.derivatively_priced,
+        |f| f.extended_hours,
+        |f| f.intermarket_sweep,
+        |f| f.out_of_sequence,
+        |f| f.eligible,
+        |f| f.volume_eligible,
+    ];
+
+    let mut df = df;
+    for (column, flag) in CONDITION_COLUMNS.iter().zip(flags) {
+        let udf = ScalarUDF::from(ConditionFlagFunction::new(column, flag));
+        df = df.with_column(column, udf.call(vec![col(conditions)]))?;
+    }
+    Ok(df)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use datafusion::prelude::SessionContext;
+
+    #[test]
+    fn test_trade_conditions() {
+        let odd_lot = trade_condition(37).unwrap();
+        assert_eq!(odd_lot.name, "Odd Lot Trade");
+        assert!(!odd_lot.updates_last && odd_lot.updates_volume);
+        assert!(trade_condition(26).is_none());
+
+        // IDs are listed once each, in order
+        assert!(TRADE_CONDITIONS.windows(2).all(|pair| pair[0].id < pair[1].id));
+    }
+
+    #[test]
+    fn test_parse_conditions() {
+        assert_eq!(parse_conditions("12,37"), [12, 37]);
+        assert_eq!(parse_conditions("[14, 41]"), [14, 41]);
+        assert_eq!(parse_conditions("x,3,"), [3]);
+        assert!(parse_conditions("").is_empty());
+    }
+
+    #[test]
+    fn test_trade_flags() {
+        // No conditions, and the regular-way codes, are regular eligible trades
+        for codes in [&[][..], &[0], &[3], &[14, 41]] {
+            let flags = TradeFlags::from_codes(codes);
+            assert!(flags.regular && flags.eligible && flags.volume_eligible, "{:?}", codes);
+        }
+        assert!(TradeFlags::from_codes(&[14]).intermarket_sweep);
+
+        // An odd lot in extended hours counts toward volume only
+        let flags = TradeFlags::from_codes(&[12, 37]);
+        assert!(!flags.regular && flags.odd_lot && flags.extended_hours);
+        assert!(!flags.eligible && flags.volume_eligible);
+
+        let flags = TradeFlags::from_codes(&[13]);
+        assert!(flags.out_of_sequence && flags.extended_hours && !flags.eligible);
+        assert!(TradeFlags::from_codes(&[10]).derivatively_priced);
+
+        // An official close is neither a last sale nor volume
+        let flags = TradeFlags::from_codes(&[15]);
+        assert!(!flags.eligible && !flags.volume_eligible);
+
+        // Unknown codes don't affect eligibility but aren't regular
+        let flags = TradeFlags::from_codes(&[999]);
+        assert!(!flags.regular && flags.eligible && flags.volume_eligible);
+    }
+
+    #[tokio::test]
+    async fn test_with_condition_columns() -> Result<()> {
+        let ctx = SessionContext::new();
+        let df = ctx
+            .sql("SELECT * FROM (VALUES ('12,37'), (NULL), (''), ('999'), ('14,41')) AS t(conditions)")
+            .await?;
+        let batches = with_condition_columns(df, "conditions")?.collect().await?;
+
+        let flag = |name: &str| -> Vec<Option<bool>> {
+            batches
+                .iter()
+                .flat_map(|batch| batch.column_by_name(name).unwrap().as_boolean().iter().collect::<Vec<_>>())
+                .collect()
+        };
+        assert_eq!(flag("is_regular"), [Some(false), Some(true), Some(true), Some(false), Some(true)]);
+        assert_eq!(flag("is_odd_lot"), [Some(true), Some(false), Some(false), Some(false), Some(false)]);
+        assert_eq!(flag("is_eligible"), [Some(false), Some(true), Some(true), Some(true), Some(true)]);
+        assert_eq!(flag("is_intermarket_sweep"), [Some(false), Some(false), Some(false), Some(false), Some(true)]);
+        assert_eq!(flag("is_volume_eligible"), [Some(true); 5]);
+        Ok(())
+    }
+}
@@ -3,6 +3,9 @@
 use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
 
+use super::RetryConfig;
+use std::time::Duration;
+
 /// Configuration for Polygon.io S3 flat files access
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PolygonConfig {
@@ -10,6 +13,12 @@ pub struct PolygonConfig {
     pub secret_key: String,
     pub endpoint: String,
     pub bucket: String,
+    /// Retry policy for transient S3 failures
+    #[serde(default)]
+    pub retry: RetryConfig,
+    /// REST API key, needed for reference data such as ticker details
+    #[serde(default)]
+    pub api_key: Option<String>,
 }
 
 impl Default for PolygonConfig {
@@ -31,15 +40,24 @@ impl PolygonConfig {
             .unwrap_or_else(|_| "https://files.polygon.io".to_string());
         let bucket = std::env::var("POLYGON_S3_BUCKET")
             .unwrap_or_else(|_| "flatfiles".to_string());
+        let api_key = std::env::var("POLYGON_API_KEY").ok();
             
         Ok(Self {
             access_key,
             secret_key,
             endpoint,
             bucket,
+            retry: RetryConfig::default(),
+            api_key,
         })
     }
     
+    /// Use `retry` for S3 requests instead of the default policy
+    pub fn with_retry(mut self, retry: RetryConfig) -> Self {
+        self.retry = retry;
+        self
+    }
+    
     /// Demo configuration with placeholder values
     pub fn demo() -> Self {
         Self {
@@ -47,6 +65,64 @@ impl PolygonConfig {
             secret_key: "your_secret_key_here".to_string(),
             endpoint: "https://files.polygon.io".to_string(),
             bucket: "flatfiles".to_string(),
+            retry: RetryConfig::default(),
+            api_key: None,
+        }
+    }
+}
+
+/// Google Cloud Storage bucket mirroring the flat file layout
+///
+/// Credentials not set here are read by `object_store` from the usual
+/// `GOOGLE_*` environment variables.
+#[cfg(feature = "gcs")]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GcsConfig {
+    pub bucket: String,
+    /// Path of a service account JSON key
+    #[serde(default)]
+    pub service_account_path: Option<String>,
+    /// Retry policy for transient failures
+    #[serde(default)]
+    pub retry: RetryConfig,
+}
+
+#[cfg(feature = "gcs")]
+impl GcsConfig {
+    pub fn new(bucket: impl Into<String>) -> Self {
+        Self {
+            bucket: bucket.into(),
+            service_account_path: None,
+            retry: RetryConfig::default(),
+        }
+    }
+}
+
+/// Azure Blob Storage container mirroring the flat file layout
+///
+/// Credentials not set here are read by `object_store` from the usual
+/// `AZURE_*` environment variables.
+#[cfg(feature = "azure")]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AzureConfig {
+    pub account: String,
+    pub container: String,
+    /// Storage account access key
+    #[serde(default)]
+    pub access_key: Option<String>,
+    /// Retry policy for transient failures
+    #[serde(default)]
+    pub retry: RetryConfig,
+}
+
+#[cfg(feature = "azure")]
+impl AzureConfig {
+    pub fn new(account: impl Into<String>, container: impl Into<String>) -> Self {
+        Self {
+            account: account.into(),
+            container: container.into(),
+            access_key: None,
+            retry: RetryConfig::default(),
         }
     }
 }
@@ -56,11 +132,46 @@ impl PolygonConfig {
 pub enum DataSource {
     /// S3-based data source with Polygon.io credentials
     S3(PolygonConfig),
+    /// Google Cloud Storage mirror
+    #[cfg(feature = "gcs")]
+    Gcs(GcsConfig),
+    /// Azure Blob Storage mirror
+    #[cfg(feature = "azure")]
+    Azure(AzureConfig),
     /// Local file system data source
     Local { root: PathBuf },
 }
 
 impl DataSource {
+    /// URL of the bucket or container, e.g. `s3://flatfiles`; `None` for local sources
+    pub fn base_url(&self) -> Option<String> {
+        match self {
+            DataSource::S3(config) => Some(format!("s3://{}", config.bucket)),
+            #[cfg(feature = "gcs")]
+            DataSource::Gcs(config) => Some(format!("gs://{}", config.bucket)),
+            #[cfg(feature = "azure")]
+            DataSource::Azure(config) => Some(format!("az://{}", config.container)),
+            DataSource::Local { .. } => None,
+        }
+    }
+
+    /// Retry policy of a remote source
+    pub fn retry(&self) -> Option<&RetryConfig> {
+        match self {
+            DataSource::S3(config) => Some(&config.retry),
+            #[cfg(feature = "gcs")]
+            DataSource::Gcs(config) => Some(&config.retry),
+            #[cfg(feature = "azure")]
+            DataSource::Azure(config) => Some(&config.retry),
+            DataSource::Local { .. } => None,
+        }
+    }
+
+    pub fn is_local(&self) -> bool {
+        matches!(self, DataSource::Local { .. })
+    }
+
+
     /// Create S3 data source from configuration
     pub fn s3(config: PolygonConfig) -> Self {
         Self::S3(config)
@@ -76,3 +187,31 @@ impl DataSource {
         Ok(Self::S3(PolygonConfig::from_env()?))
     }
 }
+
+/// Local Parquet cache for flat files read from S3
+///
+/// Each file is decompressed once, on first read, and stored as Parquet under
+/// `dir` with the same `asset_class/data_type/year/` layout as the bucket.
+#[derive(Debug, Clone)]
+pub struct CacheConfig {
+    /// Directory holding the cached Parquet files
+    pub dir: PathBuf,
+    /// Re-download files older than this; `None` keeps them until invalidated
+    pub ttl: Option<Duration>,
+}
+
+impl CacheConfig {
+    /// Cache under `dir`, never expiring
+    pub fn new<P: Into<PathBuf>>(dir: P) -> Self {
+        Self {
+            dir: dir.into(),
+            ttl: None,
+        }
+    }
+
+    /// Expire cached files after `ttl`
+    pub fn with_ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = Some(ttl);
+        self
+    }
+}
@@ -0,0 +1,131 @@
+//! Typed errors of Polygon data loading
+//!
+//! Loading returns DataFusion's `Result`, so these travel inside
+//! `DataFusionError::External`; [`PolygonError::from_error`] gets them back
+//! out. Errors about a file name the path the client built for it, which is
+//! usually the quickest way to tell a missing day from a wrong bucket,
+//! prefix or mirror layout.
+
+use datafusion::error::DataFusionError;
+
+/// A failure loading Polygon data
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PolygonError {
+    /// A credential needed for the request isn't configured
+    MissingCredentials {
+        credential: &'static str,
+        /// Environment variable it is read from
+        env_var: &'static str,
+    },
+    /// No object or file at `path`
+    ObjectNotFound { path: String },
+    /// The file at `path` doesn't have the columns expected of it
+    SchemaMismatch { path: String, message: String },
+    /// The file at `path` isn't valid for its compression
+    Decompression { path: String, message: String },
+    /// The request for `path` was throttled (HTTP 429 or S3 `SlowDown`)
+    RateLimited { path: String },
+}
+
+impl std::fmt::Display for PolygonError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PolygonError::MissingCredentials { credential, env_var } => {
+                write!(f, "No Polygon {} configured (set {})", credential, env_var)
+            }
+            PolygonError::ObjectNotFound { path } => write!(f, "Object not found: {}", path),
+            PolygonError::SchemaMismatch { path, message } => write!(f, "Unexpected schema in {}: {}", path, message),
+            PolygonError::Decompression { path, message } => write!(f, "Failed to decompress {}: {}", path, message),
+            PolygonError::RateLimited { path } => write!(f, "Rate limited requesting {}", path),
+        }
+    }
+}
+
+impl std::error::Error for PolygonError {}
+
+impl From<PolygonError> for DataFusionError {
+    fn from(error: PolygonError) -> Self {
+        DataFusionError::External(Box::new(error))
+    }
+}
+
+impl PolygonError {
+    /// The Polygon error inside `error`, if it is one
+    pub fn from_error(error: &DataFusionError) -> Option<&PolygonError> {
+        match error {
+            DataFusionError::External(e) => e.downcast_ref::<PolygonError>(),
+            DataFusionError::Context(_, e) => Self::from_error(e),
+            _ => None,
+        }
+    }
+
+    /// Classify an object store failure on `path`: not found and throttling
+    /// become typed errors, anything else is kept as is
+    ///
+    /// Throttling is a 429, or the 503 S3 sends as `SlowDown`, on the
+    /// `reqwest` response error among the failure's sources.
+    pub(crate) fn from_object_store(error: object_store::Error, path: &str) -> DataFusionError {
+        if let object_store::Error::NotFound { .. } = error {
+            return PolygonError::ObjectNotFound { path: path.to_string() }.into();
+        }
+        if matches!(
+            response_status(&error),
+            Some(reqwest::StatusCode::TOO_MANY_REQUESTS | reqwest::StatusCode::SERVICE_UNAVAILABLE)
+        ) {
+            return PolygonError::RateLimited { path: path.to_string() }.into();
+        }
+        DataFusionError::ObjectStore(error)
+    }
+
+    /// Type the missing-file errors of a read of `path`: object store
+    /// failures as in [`from_object_store`](Self::from_object_store), and a
+    /// local file that isn't there as [`ObjectNotFound`](Self::ObjectNotFound)
+    pub(crate) fn from_read(error: DataFusionError, path: &str) -> DataFusionError {
+        match error {
+            DataFusionError::ObjectStore(error) => Self::from_object_store(error, path),
+            DataFusionError::IoError(error) if error.kind() == std::io::ErrorKind::NotFound => {
+                PolygonError::ObjectNotFound { path: path.to_string() }.into()
+            }
+            error => error,
+        }
+    }
+}
+
+/// Status of the first `reqwest` error with one among `error` and its sources
+fn response_status(error: &(dyn std::error::Error + 'static)) -> Option<reqwest::StatusCode> {
+    let mut cause = Some(error);
+    while let Some(error) = cause {
+        if let Some(status) = error.downcast_ref::<reqwest::Error>().and_then(reqwest::Error::status) {
+            return Some(status);
+        }
+        cause = error.source();
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_object_store() {
+        let not_found = object_store::Error::NotFound {
+            path: "a/b.csv.gz".to_string(),
+            source: "missing".into(),
+        };
+        let error = PolygonError::from_object_store(not_found, "a/b.csv.gz");
+        assert_eq!(
+            PolygonError::from_error(&error),
+            Some(&PolygonError::ObjectNotFound { path: "a/b.csv.gz".to_string() })
+        );
+
+        // Throttling is read from a typed status, never from the message
+        let generic = object_store::Error::Generic {
+            store: "S3",
+            source: "429 Too Many Requests: SlowDown".into(),
+        };
+        let error = PolygonError::from_object_store(generic, "a/b.csv.gz");
+        assert!(PolygonError::from_error(&error).is_none());
+        assert!(matches!(error, DataFusionError::ObjectStore(_)));
+    }
+}
@@ -1,12 +1,32 @@
 // Re-export public API from submodules
+pub mod catalog;
 pub mod config;
 pub mod types;
 pub mod client;
+pub mod conditions;
+pub mod error;
+pub mod paths;
+pub mod reference;
+pub mod retry;
+pub mod schemas;
 pub mod validator;
 pub mod signals;
+pub mod table;
+pub mod timestamps;
+#[cfg(feature = "ws")]
+pub mod ws;
 
+pub use catalog::*;
 pub use config::*;
 pub use types::*;
 pub use client::*;
+pub use conditions::*;
+pub use error::*;
+pub use paths::*;
+pub use reference::*;
+pub use retry::*;
+pub use schemas::*;
 pub use validator::*;
 pub use signals::*;
+pub use table::*;
+pub use timestamps::*;
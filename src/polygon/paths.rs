@@ -0,0 +1,176 @@
+//! Layout of flat files within a data source
+//!
+//! Polygon's bucket keeps one file per dataset and day at
+//! `{asset_class}/{data_type}/{year}/{date}.csv.gz`, e.g.
+//! `us_stocks_sip/day_aggs_v1/2024/2024-01-02.csv.gz`. Mirrors that
+//! reorganize the files, such as into a Hive-style `year=2024/month=01/`
+//! layout, give the client their own [`PathResolver`], usually a
+//! [`PathTemplate`].
+
+use chrono::{Datelike, NaiveDate};
+use datafusion::error::{DataFusionError, Result};
+
+use super::{AssetClass, PolygonDataType};
+
+/// Placeholders a [`PathTemplate`] may use
+const PLACEHOLDERS: [&str; 6] = ["asset_class", "data_type", "year", "month", "day", "date"];
+
+/// Where the file of one dataset and day is kept
+pub trait PathResolver: Send + Sync + std::fmt::Debug {
+    /// Key of the file relative to the source's bucket or root directory,
+    /// ending in `.csv.gz`
+    ///
+    /// Local mirrors also look for the key with `.csv`, `.csv.zst`,
+    /// `.csv.bz2` and `.parquet` in place of `.csv.gz`.
+    fn resolve(&self, asset_class: &AssetClass, data_type: &PolygonDataType, date: NaiveDate) -> String;
+}
+
+/// Polygon's own layout, used by default
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PolygonLayout;
+
+impl PathResolver for PolygonLayout {
+    fn resolve(&self, asset_class: &AssetClass, data_type: &PolygonDataType, date: NaiveDate) -> String {
+        format!(
+            "{}/{}/{}/{}.csv.gz",
+            asset_class.s3_prefix(),
+            data_type.s3_prefix(),
+            date.format("%Y"),
+            date.format("%Y-%m-%d")
+        )
+    }
+}
+
+/// A layout described by a template such as
+/// `{asset_class}/{data_type}/year={year}/month={month}/{date}.csv.gz`
+///
+/// `{asset_class}` and `{data_type}` are Polygon's directory names
+/// (`us_stocks_sip`, `minute_aggs_v1`), `{month}` and `{day}` are zero
+/// padded, and `{date}` is `YYYY-MM-DD`.
+#[derive(Debug, Clone)]
+pub struct PathTemplate {
+    template: String,
+}
+
+impl PathTemplate {
+    /// Fails unless the template ends in `.csv.gz`, only uses the known
+    /// placeholders, and identifies the day by `{date}` or by all of
+    /// `{year}`, `{month}` and `{day}`
+    pub fn new(template: impl Into<String>) -> Result<Self> {
+        let template = template.into();
+        let invalid = |reason: &str| {
+            DataFusionError::Configuration(format!("Invalid flat file path template '{}': {}", template, reason))
+        };
+
+        if !template.ends_with(".csv.gz") {
+            return Err(invalid("it must end in .csv.gz"));
+        }
+        let mut used = Vec::new();
+        let mut rest = template.as_str();
+        while let Some(start) = rest.find('{') {
+            let end = rest[start..].find('}').ok_or_else(|| invalid("unclosed '{'"))?;
+            let name = &rest[start + 1..start + end];
+            if !PLACEHOLDERS.contains(&name) {
+                return Err(invalid(&format!("unknown placeholder {{{}}}", name)));
+            }
+            used.push(name);
+            rest = &rest[start + end + 1..];
+        }
+        let has_day = used.contains(&"date") || ["year", "month", "day"].iter().all(|name| used.contains(name));
+        if !has_day {
+            return Err(invalid("it must contain {date}, or {year}, {month} and {day}"));
+        }
+
+        Ok(Self { template })
+    }
+}
+
+impl PathResolver for PathTemplate {
+    fn resolve(&self, asset_class: &AssetClass, data_type: &PolygonDataType, date: NaiveDate) -> String {
+        self.template
+            .replace("{asset_class}", asset_class.s3_prefix())
+            .replace("{data_type}", data_type.s3_prefix())
+            .replace("{year}", &date.year().to_string())
+            .replace("{month}", &format!("{:02}", date.month()))
+            .replace("{day}", &format!("{:02}", date.day()))
+            .replace("{date}", &date.format("%Y-%m-%d").to_string())
+    }
+}
+
+/// Longest directory prefix shared by the files of `year`, for listing them
+pub(super) fn year_prefix(
+    resolver: &dyn PathResolver,
+    asset_class: &AssetClass,
+    data_type: &PolygonDataType,
+    year: i32,
+) -> String {
+    let path = |month, day| {
+        let date = NaiveDate::from_ymd_opt(year, month, day).expect("valid date");
+        resolver.resolve(asset_class, data_type, date)
+    };
+    let (first, last) = (path(1, 1), path(12, 31));
+    let common = first
+        .char_indices()
+        .zip(last.chars())
+        .find(|((_, a), b)| a != b)
+        .map_or(first.len(), |((index, _), _)| index);
+    match first[..common].rfind('/') {
+        Some(slash) => first[..=slash].to_string(),
+        None => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_path_template_new() {
+        for valid in [
+            "{asset_class}/{data_type}/{year}/{date}.csv.gz",
+            "{data_type}/year={year}/month={month}/day={day}/data.csv.gz",
+            "flat/{date}.csv.gz",
+        ] {
+            assert!(PathTemplate::new(valid).is_ok(), "{}", valid);
+        }
+
+        for (invalid, reason) in [
+            ("{asset_class}/{data_type}/{date}.parquet", ".csv.gz"),
+            ("{asset_class}/{ticker}/{date}.csv.gz", "unknown placeholder {ticker}"),
+            ("{asset_class}/{date.csv.gz", "unclosed"),
+            ("{data_type}/{year}/{month}/data.csv.gz", "{date}"),
+        ] {
+            let err = PathTemplate::new(invalid).unwrap_err().to_string();
+            assert!(err.contains(reason), "{}: {}", invalid, err);
+        }
+    }
+
+    #[test]
+    fn test_path_template_resolve() {
+        let template = PathTemplate::new("{asset_class}/{data_type}/year={year}/month={month}/{day}-{date}.csv.gz").unwrap();
+        let date = NaiveDate::from_ymd_opt(2024, 3, 5).unwrap();
+        assert_eq!(
+            template.resolve(&AssetClass::Stocks, &PolygonDataType::MinuteAggs, date),
+            "us_stocks_sip/minute_aggs_v1/year=2024/month=03/05-2024-03-05.csv.gz"
+        );
+    }
+
+    #[test]
+    fn test_year_prefix() {
+        let prefix = |resolver: &dyn PathResolver| year_prefix(resolver, &AssetClass::Stocks, &PolygonDataType::DayAggs, 2024);
+
+        assert_eq!(prefix(&PolygonLayout), "us_stocks_sip/day_aggs_v1/2024/");
+
+        // Hive-style months share only the year directory
+        let hive = PathTemplate::new("{asset_class}/{data_type}/year={year}/month={month}/{date}.csv.gz").unwrap();
+        assert_eq!(prefix(&hive), "us_stocks_sip/day_aggs_v1/year=2024/");
+
+        // A year that isn't a directory of its own falls back to the shared parent
+        let flat = PathTemplate::new("{asset_class}/{data_type}/{date}.csv.gz").unwrap();
+        assert_eq!(prefix(&flat), "us_stocks_sip/day_aggs_v1/");
+
+        // No shared directory at all lists from the root
+        let dated = PathTemplate::new("{date}/{asset_class}.csv.gz").unwrap();
+        assert_eq!(prefix(&dated), "");
+    }
+}
@@ -0,0 +1,209 @@
+//! Polygon.io reference data: ticker names, exchanges, types, market caps and
+//! CIKs, plus splits and dividends
+//!
+//! Reference data comes from the REST API rather than the flat files, so it
+//! needs an API key. It loads into DataFrames on the client's
+//! [`SessionContext`](datafusion::execution::context::SessionContext), where
+//! it can be registered as a table and joined with price data.
+
+use std::sync::Arc;
+
+use datafusion::arrow::array::{ArrayRef, BooleanArray, Float64Array, StringArray};
+use datafusion::arrow::datatypes::{DataType, Field, Schema};
+use datafusion::arrow::record_batch::RecordBatch;
+use datafusion::dataframe::DataFrame;
+use datafusion::error::{DataFusionError, Result};
+use futures::stream::{StreamExt, TryStreamExt};
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+
+use super::{with_retries, AssetClass, PolygonClient, PolygonError, TickerDetails};
+use crate::adjust::{CorporateActions, Dividend, Split};
+
+/// Base URL of the Polygon.io REST API
+pub const REST_API_URL: &str = "https://api.polygon.io";
+
+/// Largest page the reference endpoints return
+const PAGE_LIMIT: usize = 1000;
+
+/// Ticker detail requests in flight at once
+const DETAIL_CONCURRENCY: usize = 4;
+
+#[derive(Debug, Deserialize)]
+struct Page<T> {
+    #[serde(default = "Vec::new")]
+    results: Vec<T>,
+    next_url: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct TickerDetailsResponse {
+    results: TickerDetails,
+}
+
+impl PolygonClient {
+    /// Load every active ticker of `asset_class` as a DataFrame
+    ///
+    /// Market caps, share counts and industries are only filled in by
+    /// [`load_ticker_details`](Self::load_ticker_details); they are null here.
+    /// Options and futures aren't covered by the reference tickers endpoint.
+    pub async fn load_tickers(&self, asset_class: AssetClass) -> Result<DataFrame> {
+        let market = asset_class.reference_market().ok_or_else(|| {
+            DataFusionError::Plan(format!("No reference tickers for {:?}", asset_class))
+        })?;
+
+        let tickers = self
+            .rest_get_all(
+                &format!("{}/v3/reference/tickers", REST_API_URL),
+                &[("market", market), ("active", "true")],
+            )
+            .await?;
+
+        self.session_context().read_batch(tickers_batch(&tickers)?)
+    }
+
+    /// Full reference data for one ticker, including market cap and CIK
+    pub async fn ticker_details(&self, symbol: &str) -> Result<TickerDetails> {
+        let url = format!("{}/v3/reference/tickers/{}", REST_API_URL, symbol);
+        let response: TickerDetailsResponse = self.rest_get(&url, &[]).await?;
+        Ok(response.results)
+    }
+
+    /// Full reference data for `symbols` as a DataFrame, one row per symbol
+    pub async fn load_ticker_details(&self, symbols: &[&str]) -> Result<DataFrame> {
+        let details = futures::stream::iter(symbols)
+            .map(|symbol| self.ticker_details(symbol))
+            .buffered(DETAIL_CONCURRENCY)
+            .try_collect::<Vec<_>>()
+            .await?;
+
+        self.session_context().read_batch(tickers_batch(&details)?)
+    }
+
+    /// Every split of `symbols`, oldest first
+    pub async fn load_splits(&self, symbols: &[&str]) -> Result<Vec<Split>> {
+        let url = format!("{}/v3/reference/splits", REST_API_URL);
+        let mut splits = Vec::new();
+        for symbol in symbols {
+            splits.extend(self.rest_get_all::<Split>(&url, &[("ticker", *symbol)]).await?);
+        }
+        splits.sort_by_key(|split| split.execution_date);
+        Ok(splits)
+    }
+
+    /// Every cash dividend of `symbols`, oldest first
+    pub async fn load_dividends(&self, symbols: &[&str]) -> Result<Vec<Dividend>> {
+        let url = format!("{}/v3/reference/dividends", REST_API_URL);
+        let mut dividends = Vec::new();
+        for symbol in symbols {
+            dividends.extend(self.rest_get_all::<Dividend>(&url, &[("ticker", *symbol)]).await?);
+        }
+        dividends.sort_by_key(|dividend| dividend.ex_dividend_date);
+        Ok(dividends)
+    }
+
+    /// Splits and dividends of `symbols`, ready for [`adjust_prices`](crate::adjust::adjust_prices)
+    pub async fn load_corporate_actions(&self, symbols: &[&str]) -> Result<CorporateActions> {
+        Ok(CorporateActions {
+            splits: self.load_splits(symbols).await?,
+            dividends: self.load_dividends(symbols).await?,
+        })
+    }
+
+    /// GET every page of a paginated REST endpoint
+    async fn rest_get_all<T: DeserializeOwned>(&self, url: &str, query: &[(&str, &str)]) -> Result<Vec<T>> {
+        let limit = PAGE_LIMIT.to_string();
+        let mut query = query.to_vec();
+        query.push(("limit", limit.as_str()));
+
+        let mut results = Vec::new();
+        let mut page: Page<T> = self.rest_get(url, &query).await?;
+        loop {
+            results.append(&mut page.results);
+            // `next_url` already carries the query, except for the API key
+            let Some(next_url) = page.next_url.take() else {
+                break;
+            };
+            page = self.rest_get(&next_url, &[]).await?;
+        }
+        Ok(results)
+    }
+
+    /// GET a REST endpoint and decode its JSON, retrying transient failures
+    async fn rest_get<T: DeserializeOwned>(&self, url: &str, query: &[(&str, &str)]) -> Result<T> {
+        let api_key = self.api_key()?;
+        let http = &reqwest::Client::new();
+
+        with_retries(&self.rest_retry_config(), || async move {
+            http.get(url)
+                .query(query)
+                .query(&[("apiKey", api_key)])
+                .send()
+                .await
+                .and_then(|response| response.error_for_status())
+                .map_err(|e| rest_error(e, url))?
+                .json::<T>()
+                .await
+                .map_err(|e| rest_error(e, url))
+        })
+        .await
+    }
+}
+
+/// Schema of the ticker reference table
+pub fn tickers_schema() -> Schema {
+    let utf8 = |name: &str| Field::new(name, DataType::Utf8, true);
+    Schema::new(vec![
+        Field::new("ticker", DataType::Utf8, false),
+        utf8("name"),
+        utf8("market"),
+        utf8("locale"),
+        utf8("primary_exchange"),
+        utf8("type"),
+        Field::new("active", DataType::Boolean, true),
+        utf8("currency_name"),
+        utf8("cik"),
+        Field::new("market_cap", DataType::Float64, true),
+        Field::new("share_class_shares_outstanding", DataType::Float64, true),
+        utf8("sic_description"),
+        utf8("list_date"),
+    ])
+}
+
+/// Build the ticker reference table from `details`
+pub fn tickers_batch(details: &[TickerDetails]) -> Result<RecordBatch> {
+    let strings = |field: fn(&TickerDetails) -> Option<&str>| -> ArrayRef {
+        Arc::new(details.iter().map(field).collect::<StringArray>())
+    };
+    let floats = |field: fn(&TickerDetails) -> Option<f64>| -> ArrayRef {
+        Arc::new(details.iter().map(field).collect::<Float64Array>())
+    };
+
+    let columns = vec![
+        Arc::new(details.iter().map(|d| Some(d.ticker.as_str())).collect::<StringArray>()) as ArrayRef,
+        strings(|d| d.name.as_deref()),
+        strings(|d| d.market.as_deref()),
+        strings(|d| d.locale.as_deref()),
+        strings(|d| d.primary_exchange.as_deref()),
+        strings(|d| d.ticker_type.as_deref()),
+        Arc::new(details.iter().map(|d| d.active).collect::<BooleanArray>()),
+        strings(|d| d.currency_name.as_deref()),
+        strings(|d| d.cik.as_deref()),
+        floats(|d| d.market_cap),
+        floats(|d| d.share_class_shares_outstanding),
+        strings(|d| d.sic_description.as_deref()),
+        strings(|d| d.list_date.as_deref()),
+    ];
+
+    Ok(RecordBatch::try_new(Arc::new(tickers_schema()), columns)?)
+}
+
+/// A REST failure on `url`, with throttling as [`PolygonError::RateLimited`]
+///
+/// `url` is the endpoint without its query, which holds the API key.
+fn rest_error(error: reqwest::Error, url: &str) -> DataFusionError {
+    if error.status() == Some(reqwest::StatusCode::TOO_MANY_REQUESTS) {
+        return PolygonError::RateLimited { path: url.to_string() }.into();
+    }
+    DataFusionError::External(Box::new(error.without_url()))
+}
@@ -0,0 +1,175 @@
+//! Retries with exponential backoff for transient failures
+//!
+//! Object store requests are retried by the store itself, configured from
+//! [`RetryConfig::object_store_config`], so every read is covered, including
+//! the ones DataFusion makes while scanning. [`with_retries`] wraps the REST
+//! requests, which have no retry layer of their own.
+
+use std::collections::hash_map::RandomState;
+use std::future::Future;
+use std::hash::{BuildHasher, Hasher};
+use std::io::ErrorKind;
+use std::time::Duration;
+
+use datafusion::error::{DataFusionError, Result};
+use serde::{Deserialize, Serialize};
+
+use super::PolygonError;
+
+/// Retry policy for object store and REST requests
+///
+/// Attempt `n` waits a random time between half and all of
+/// `initial_backoff * multiplier^n`, capped at `max_backoff`, so clients
+/// throttled together don't retry in lockstep.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RetryConfig {
+    /// Retries after the first attempt; 0 disables retrying
+    pub max_retries: usize,
+    pub initial_backoff: Duration,
+    pub max_backoff: Duration,
+    pub multiplier: f64,
+}
+
+impl Default for RetryConfig {
+    fn default() -> Self {
+        Self {
+            max_retries: 5,
+            initial_backoff: Duration::from_millis(500),
+            max_backoff: Duration::from_secs(30),
+            multiplier: 2.0,
+        }
+    }
+}
+
+impl RetryConfig {
+    /// Fail on the first error
+    pub fn none() -> Self {
+        Self {
+            max_retries: 0,
+            ..Self::default()
+        }
+    }
+
+    /// Wait before retry number `attempt` (0-based), with jitter
+    pub fn backoff(&self, attempt: usize) -> Duration {
+        let exponential = self.initial_backoff.as_secs_f64() * self.multiplier.powi(attempt as i32);
+        let capped = exponential.min(self.max_backoff.as_secs_f64());
+        Duration::from_secs_f64(capped * (0.5 + 0.5 * unit_random()))
+    }
+
+    /// The equivalent `object_store` policy, applied to each S3 request
+    pub(crate) fn object_store_config(&self) -> object_store::RetryConfig {
+        object_store::RetryConfig {
+            backoff: object_store::BackoffConfig {
+                init_backoff: self.initial_backoff,
+                max_backoff: self.max_backoff,
+                base: self.multiplier,
+            },
+            max_retries: self.max_retries,
+            ..Default::default()
+        }
+    }
+}
+
+/// Uniform random number in [0, 1) from the std hasher's random keys
+fn unit_random() -> f64 {
+    let bits = RandomState::new().build_hasher().finish();
+    (bits >> 11) as f64 / (1u64 << 53) as f64
+}
+
+/// Whether an error is throttling, a server error or a dropped connection
+///
+/// Looks through the error's sources for a typed cause: Polygon rate
+/// limiting, a 429 or 5xx response or a connect or timeout failure from
+/// `reqwest`, or an I/O error from a dropped connection.
+pub fn is_transient(error: &DataFusionError) -> bool {
+    if let Some(PolygonError::RateLimited { .. }) = PolygonError::from_error(error) {
+        return true;
+    }
+
+    let mut cause: Option<&(dyn std::error::Error + 'static)> = Some(error);
+    while let Some(error) = cause {
+        if let Some(error) = error.downcast_ref::<reqwest::Error>() {
+            if error.is_timeout() || error.is_connect() {
+                return true;
+            }
+            if let Some(status) = error.status() {
+                return status == reqwest::StatusCode::TOO_MANY_REQUESTS || status.is_server_error();
+            }
+        }
+        if let Some(error) = error.downcast_ref::<std::io::Error>() {
+            if matches!(
+                error.kind(),
+                ErrorKind::TimedOut
+                    | ErrorKind::ConnectionReset
+                    | ErrorKind::ConnectionAborted
+                    | ErrorKind::BrokenPipe
+                    | ErrorKind::UnexpectedEof
+            ) {
+                return true;
+            }
+        }
+        cause = error.source();
+    }
+    false
+}
+
+/// Run `operation`, retrying transient failures according to `config`
+pub async fn with_retries<T, F, Fut>(config: &RetryConfig, mut operation: F) -> Result<T>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T>>,
+{
+    let mut attempt = 0;
+    loop {
+        match operation().await {
+            Err(e) if attempt < config.max_retries && is_transient(&e) => {
+                tokio::time::sleep(config.backoff(attempt)).await;
+                attempt += 1;
+            }
+            result => return result,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_backoff() {
+        let config = RetryConfig {
+            max_retries: 5,
+            initial_backoff: Duration::from_millis(100),
+            max_backoff: Duration::from_secs(1),
+            multiplier: 2.0,
+        };
+
+        // Jitter keeps each wait between half and all of the exponential step
+        for (attempt, full) in [(0, 0.1), (1, 0.2), (2, 0.4), (3, 0.8), (4, 1.0), (10, 1.0)] {
+            let wait = config.backoff(attempt).as_secs_f64();
+            assert!(wait >= full / 2.0 - 1e-9 && wait <= full + 1e-9, "attempt {}: {}", attempt, wait);
+        }
+    }
+
+    #[test]
+    fn test_is_transient() {
+        let io = |kind: ErrorKind| DataFusionError::IoError(std::io::Error::new(kind, "io"));
+        assert!(is_transient(&io(ErrorKind::ConnectionReset)));
+        assert!(is_transient(&io(ErrorKind::TimedOut)));
+        assert!(!is_transient(&io(ErrorKind::NotFound)));
+        assert!(!is_transient(&io(ErrorKind::PermissionDenied)));
+
+        let rate_limited = DataFusionError::from(PolygonError::RateLimited { path: "x".to_string() });
+        assert!(is_transient(&rate_limited));
+        assert!(is_transient(&rate_limited.context("loading AAPL")));
+
+        // Causes are found through wrapping errors
+        let wrapped = DataFusionError::External(Box::new(std::io::Error::new(ErrorKind::BrokenPipe, "pipe")));
+        assert!(is_transient(&wrapped.context("loading AAPL")));
+
+        // Numbers in a message are not statuses
+        assert!(!is_transient(&DataFusionError::Execution("row 500 of 504 is invalid".to_string())));
+        assert!(!is_transient(&DataFusionError::Plan("timeout column not found".to_string())));
+    }
+}
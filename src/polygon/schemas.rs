@@ -0,0 +1,130 @@
+//! Arrow schemas of Polygon flat files
+//!
+//! Reading a flat file with an explicit schema skips inference, which reads
+//! the first rows of every file and can settle on a different type from one
+//! day to the next, e.g. Int64 for a day of whole-number crypto volumes and
+//! Float64 for the next. Timestamps stay Int64 nanoseconds since the epoch,
+//! as in the files.
+
+use std::sync::Arc;
+
+use datafusion::arrow::datatypes::{DataType, Field, Schema, SchemaRef};
+
+use super::{AssetClass, PolygonDataType};
+
+/// Schema of one flat file dataset, or `None` where Polygon doesn't publish
+/// the file or its layout isn't fixed (futures), in which case it is inferred
+pub fn flat_file_schema(asset_class: &AssetClass, data_type: &PolygonDataType) -> Option<SchemaRef> {
+    use DataType::{Float64, Int32, Int64, Utf8};
+
+    // Crypto and forex trade fractional units
+    let quantity = match asset_class {
+        AssetClass::Crypto | AssetClass::Forex => Float64,
+        _ => Int64,
+    };
+
+    let columns: Vec<(&str, DataType)> = match (asset_class, data_type) {
+        (AssetClass::Futures, _) => return None,
+
+        (AssetClass::Indices, PolygonDataType::MinuteAggs | PolygonDataType::DayAggs | PolygonDataType::GroupedDaily) => vec![
+            ("ticker", Utf8),
+            ("open", Float64),
+            ("close", Float64),
+            ("high", Float64),
+            ("low", Float64),
+            ("window_start", Int64),
+        ],
+        (_, PolygonDataType::MinuteAggs | PolygonDataType::DayAggs | PolygonDataType::GroupedDaily) => vec![
+            ("ticker", Utf8),
+            ("volume", quantity),
+            ("open", Float64),
+            ("close", Float64),
+            ("high", Float64),
+            ("low", Float64),
+            ("window_start", Int64),
+            ("transactions", Int64),
+        ],
+
+        (AssetClass::Stocks, PolygonDataType::Trades) => vec![
+            ("ticker", Utf8),
+            ("conditions", Utf8),
+            ("correction", Int32),
+            ("exchange", Int32),
+            ("id", Utf8),
+            ("participant_timestamp", Int64),
+            ("price", Float64),
+            ("sequence_number", Int64),
+            ("sip_timestamp", Int64),
+            ("size", Int64),
+            ("tape", Int32),
+            ("trf_id", Int64),
+            ("trf_timestamp", Int64),
+        ],
+        (AssetClass::Options, PolygonDataType::Trades) => vec![
+            ("ticker", Utf8),
+            ("conditions", Utf8),
+            ("correction", Int32),
+            ("exchange", Int32),
+            ("participant_timestamp", Int64),
+            ("price", Float64),
+            ("sip_timestamp", Int64),
+            ("size", Int64),
+        ],
+        (AssetClass::Crypto, PolygonDataType::Trades) => vec![
+            ("ticker", Utf8),
+            ("conditions", Utf8),
+            ("correction", Int32),
+            ("exchange", Int32),
+            ("id", Utf8),
+            ("participant_timestamp", Int64),
+            ("price", Float64),
+            ("size", Float64),
+        ],
+
+        (AssetClass::Stocks, PolygonDataType::Quotes) => vec![
+            ("ticker", Utf8),
+            ("ask_exchange", Int32),
+            ("ask_price", Float64),
+            ("ask_size", Int64),
+            ("bid_exchange", Int32),
+            ("bid_price", Float64),
+            ("bid_size", Int64),
+            ("conditions", Utf8),
+            ("indicators", Utf8),
+            ("participant_timestamp", Int64),
+            ("sequence_number", Int64),
+            ("sip_timestamp", Int64),
+            ("tape", Int32),
+            ("trf_timestamp", Int64),
+        ],
+        (AssetClass::Options, PolygonDataType::Quotes) => vec![
+            ("ticker", Utf8),
+            ("ask_exchange", Int32),
+            ("ask_price", Float64),
+            ("ask_size", Int64),
+            ("bid_exchange", Int32),
+            ("bid_price", Float64),
+            ("bid_size", Int64),
+            ("sequence_number", Int64),
+            ("sip_timestamp", Int64),
+        ],
+        (AssetClass::Forex, PolygonDataType::Quotes) => vec![
+            ("ticker", Utf8),
+            ("ask_exchange", Int32),
+            ("ask_price", Float64),
+            ("bid_exchange", Int32),
+            ("bid_price", Float64),
+            ("participant_timestamp", Int64),
+        ],
+
+        // Not published: forex and index trades, crypto and index quotes
+        (AssetClass::Forex | AssetClass::Indices, PolygonDataType::Trades)
+        | (AssetClass::Crypto | AssetClass::Indices, PolygonDataType::Quotes) => return None,
+    };
+
+    let fields = columns
+        .into_iter()
+        .map(|(name, data_type)| Field::new(name, data_type, true))
+        .collect::<Vec<_>>();
+    Some(Arc::new(Schema::new(fields)))
+}
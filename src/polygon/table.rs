@@ -0,0 +1,591 @@
+//! Polygon flat files as one queryable table
+//!
+//! [`PolygonTableProvider`] exposes every daily file of one dataset as a
+//! single table with a `date` partition column. Filters on `date` and
+//! `ticker` are pushed into the scan, so `WHERE date BETWEEN ...` only opens
+//! the files of those days and a ticker filter is applied as each file is
+//! read. Files are opened as the scan is executed, not while it is planned,
+//! and a `LIMIT` stops the scan once enough rows have been read. The files
+//! are read with the executing session's configuration, memory pool and
+//! object stores.
+
+use std::any::Any;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use chrono::{Duration, NaiveDate, Utc};
+use datafusion::arrow::array::{RecordBatch, RecordBatchOptions};
+use datafusion::arrow::datatypes::{DataType, Field, Schema, SchemaRef};
+use datafusion::catalog::Session;
+use datafusion::dataframe::DataFrame;
+use datafusion::datasource::TableProvider;
+use datafusion::error::{DataFusionError, Result};
+use datafusion::execution::context::SessionContext;
+use datafusion::execution::session_state::SessionStateBuilder;
+use datafusion::execution::TaskContext;
+use datafusion::logical_expr::expr::InList;
+use datafusion::logical_expr::{
+    cast as cast_expr, Between, BinaryExpr, Cast, Expr, Operator, TableProviderFilterPushDown, TableType,
+};
+use datafusion::physical_expr::EquivalenceProperties;
+use datafusion::physical_plan::empty::EmptyExec;
+use datafusion::physical_plan::stream::RecordBatchStreamAdapter;
+use datafusion::physical_plan::{
+    DisplayAs, DisplayFormatType, ExecutionMode, ExecutionPlan, Partitioning, PlanProperties,
+    SendableRecordBatchStream,
+};
+use datafusion::prelude::{col, lit};
+use datafusion::scalar::ScalarValue;
+use futures::future;
+use futures::stream::{StreamExt, TryStreamExt};
+
+use super::client::{candidate_days, is_missing_file};
+use super::{flat_file_schema, AssetClass, PolygonClient, PolygonDataType};
+
+/// Partition column holding each file's date
+pub const DATE_PARTITION: &str = "date";
+
+/// First day scanned when neither the query nor the table bounds the date
+const DEFAULT_START: (i32, u32, u32) = (2003, 9, 10);
+
+/// Files opened ahead of the one being read during a scan
+const SCAN_CONCURRENCY: usize = 8;
+
+/// One Polygon dataset, e.g. stock minute aggregates, as a table
+///
+/// The schema is the dataset's [`flat_file_schema`] plus a `date` column.
+/// Rows are the raw files: the options contract and exchange-time columns
+/// that `load_data` adds are not included. Without a `date` filter every
+/// day in the table's range is scanned.
+#[derive(Clone)]
+pub struct PolygonTableProvider {
+    client: Arc<PolygonClient>,
+    asset_class: AssetClass,
+    data_type: PolygonDataType,
+    schema: SchemaRef,
+    start: NaiveDate,
+    end: Option<NaiveDate>,
+}
+
+impl PolygonTableProvider {
+    /// Fails for datasets without a fixed schema (see [`flat_file_schema`])
+    pub fn try_new(client: Arc<PolygonClient>, asset_class: AssetClass, data_type: PolygonDataType) -> Result<Self> {
+        let file_schema = flat_file_schema(&asset_class, &data_type).ok_or_else(|| {
+            DataFusionError::NotImplemented(format!(
+                "No fixed schema for {:?} {:?} flat files",
+                asset_class, data_type
+            ))
+        })?;
+
+        let mut fields = file_schema.fields().iter().cloned().collect::<Vec<_>>();
+        fields.push(Arc::new(Field::new(DATE_PARTITION, DataType::Date32, false)));
+
+        let (year, month, day) = DEFAULT_START;
+        Ok(Self {
+            client,
+            asset_class,
+            data_type,
+            schema: Arc::new(Schema::new(fields)),
+            start: NaiveDate::from_ymd_opt(year, month, day).expect("valid default start"),
+            end: None,
+        })
+    }
+
+    /// Limit the table to files from `start` to `end` (inclusive); by default
+    /// it spans 2003-09-10 to today
+    pub fn with_date_range(mut self, start: NaiveDate, end: NaiveDate) -> Self {
+        self.start = start;
+        self.end = Some(end);
+        self
+    }
+
+    /// Load one day's rows with the table's column types and `date` column,
+    /// reading through `client`
+    async fn load_day(
+        &self,
+        client: &PolygonClient,
+        date: NaiveDate,
+        tickers: &[String],
+        columns: &[&str],
+    ) -> Result<Option<DataFrame>> {
+        let symbols = tickers.iter().map(String::as_str).collect::<Vec<_>>();
+        let df = match client
+            .load_file(self.asset_class.clone(), self.data_type.clone(), date, &symbols)
+            .await
+        {
+            Ok(df) => df,
+            Err(e) if is_missing_file(&e) => return Ok(None),
+            Err(e) => return Err(e),
+        };
+
+        let exprs = columns
+            .iter()
+            .map(|name| {
+                if *name == DATE_PARTITION {
+                    return Ok(lit(ScalarValue::Date32(Some(epoch_days(date)))).alias(DATE_PARTITION));
+                }
+                let field = self.schema.field_with_name(name)?;
+                Ok(cast_expr(col(*name), field.data_type().clone()).alias(*name))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        df.select(exprs).map(Some)
+    }
+}
+
+impl std::fmt::Debug for PolygonTableProvider {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PolygonTableProvider")
+            .field("asset_class", &self.asset_class)
+            .field("data_type", &self.data_type)
+            .field("start", &self.start)
+            .field("end", &self.end)
+            .finish()
+    }
+}
+
+#[async_trait]
+impl TableProvider for PolygonTableProvider {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn schema(&self) -> SchemaRef {
+        self.schema.clone()
+    }
+
+    fn table_type(&self) -> TableType {
+        TableType::Base
+    }
+
+    fn supports_filters_pushdown(&self, filters: &[&Expr]) -> Result<Vec<TableProviderFilterPushDown>> {
+        // Filters only prune files; DataFusion still applies them to the rows
+        Ok(filters
+            .iter()
+            .map(|filter| {
+                if date_bounds(filter).is_some() || ticker_values(filter).is_some() {
+                    TableProviderFilterPushDown::Inexact
+                } else {
+                    TableProviderFilterPushDown::Unsupported
+                }
+            })
+            .collect())
+    }
+
+    async fn scan(
+        &self,
+        _state: &dyn Session,
+        projection: Option<&Vec<usize>>,
+        filters: &[Expr],
+        limit: Option<usize>,
+    ) -> Result<Arc<dyn ExecutionPlan>> {
+        let mut start = self.start;
+        let mut end = self.end.unwrap_or_else(|| Utc::now().date_naive());
+        let mut tickers: Option<Vec<String>> = None;
+        for filter in filters {
+            if let Some((low, high)) = date_bounds(filter) {
+                start = start.max(low.unwrap_or(start));
+                end = end.min(high.unwrap_or(end));
+            }
+            if let Some(values) = ticker_values(filter) {
+                tickers = Some(match tickers {
+                    Some(current) => current.into_iter().filter(|t| values.contains(t)).collect(),
+                    None => values,
+                });
+            }
+        }
+
+        let projected = match projection {
+            Some(indices) => Arc::new(self.schema.project(indices)?),
+            None => self.schema.clone(),
+        };
+        // An empty ticker list means no ticker matches, not every ticker
+        if tickers.as_ref().is_some_and(|t| t.is_empty()) || start > end {
+            return Ok(Arc::new(EmptyExec::new(projected)));
+        }
+        let tickers = tickers.unwrap_or_default();
+
+        // Counting rows projects no columns; read the date and drop it after
+        let mut columns = projected.fields().iter().map(|f| f.name().clone()).collect::<Vec<_>>();
+        if columns.is_empty() {
+            columns.push(DATE_PARTITION.to_string());
+        }
+
+        Ok(Arc::new(PolygonScanExec::new(
+            self.clone(),
+            candidate_days(&self.asset_class, start, end),
+            tickers,
+            columns,
+            projected,
+            limit,
+        )))
+    }
+}
+
+/// Scan of a [`PolygonTableProvider`], reading its days in order as the
+/// stream is polled
+///
+/// Opens up to [`SCAN_CONCURRENCY`] files ahead of the one being read, and
+/// stops opening files once `limit` rows have been produced.
+#[derive(Debug)]
+pub struct PolygonScanExec {
+    table: PolygonTableProvider,
+    days: Vec<NaiveDate>,
+    tickers: Vec<String>,
+    columns: Vec<String>,
+    schema: SchemaRef,
+    limit: Option<usize>,
+    properties: PlanProperties,
+}
+
+impl PolygonScanExec {
+    fn new(
+        table: PolygonTableProvider,
+        days: Vec<NaiveDate>,
+        tickers: Vec<String>,
+        columns: Vec<String>,
+        schema: SchemaRef,
+        limit: Option<usize>,
+    ) -> Self {
+        let properties = PlanProperties::new(
+            EquivalenceProperties::new(schema.clone()),
+            Partitioning::UnknownPartitioning(1),
+            ExecutionMode::Bounded,
+        );
+        Self {
+            table,
+            days,
+            tickers,
+            columns,
+            schema,
+            limit,
+            properties,
+        }
+    }
+}
+
+impl DisplayAs for PolygonScanExec {
+    fn fmt_as(&self, _t: DisplayFormatType, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "PolygonScanExec: {:?} {:?}, days={}",
+            self.table.asset_class,
+            self.table.data_type,
+            self.days.len()
+        )?;
+        if !self.tickers.is_empty() {
+            write!(f, ", tickers=[{}]", self.tickers.join(", "))?;
+        }
+        if let Some(limit) = self.limit {
+            write!(f, ", limit={}", limit)?;
+        }
+        Ok(())
+    }
+}
+
+impl ExecutionPlan for PolygonScanExec {
+    fn name(&self) -> &str {
+        "PolygonScanExec"
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn properties(&self) -> &PlanProperties {
+        &self.properties
+    }
+
+    fn children(&self) -> Vec<&Arc<dyn ExecutionPlan>> {
+        vec![]
+    }
+
+    fn with_new_children(self: Arc<Self>, _children: Vec<Arc<dyn ExecutionPlan>>) -> Result<Arc<dyn ExecutionPlan>> {
+        Ok(self)
+    }
+
+    fn execute(&self, partition: usize, context: Arc<TaskContext>) -> Result<SendableRecordBatchStream> {
+        if partition != 0 {
+            return Err(DataFusionError::Internal(format!(
+                "PolygonScanExec has one partition, got {}",
+                partition
+            )));
+        }
+
+        // Read the files in the executing session rather than the client's own
+        let state = SessionStateBuilder::new()
+            .with_config(context.session_config().clone())
+            .with_runtime_env(context.runtime_env())
+            .with_default_features()
+            .build();
+        let client = Arc::new(self.table.client.in_context(SessionContext::new_with_state(state)));
+
+        let table = Arc::new(self.table.clone());
+        let tickers = Arc::new(self.tickers.clone());
+        let columns = Arc::new(self.columns.clone());
+        let days = futures::stream::iter(self.days.clone())
+            .map(move |date| {
+                let (table, client) = (table.clone(), client.clone());
+                let (tickers, columns) = (tickers.clone(), columns.clone());
+                async move {
+                    let columns = columns.iter().map(String::as_str).collect::<Vec<_>>();
+                    match table.load_day(&client, date, &tickers, &columns).await? {
+                        Some(df) => df.execute_stream().await.map(Some),
+                        None => Ok(None),
+                    }
+                }
+            })
+            .buffered(SCAN_CONCURRENCY)
+            .try_filter_map(|day| future::ready(Ok(day)))
+            .try_flatten();
+
+        // Rebuild each batch on the scan's schema; this also drops the date
+        // column read for a projection of no columns
+        let schema = self.schema.clone();
+        let batches = days.map(move |batch| {
+            let batch = batch?;
+            let columns = if schema.fields().is_empty() {
+                vec![]
+            } else {
+                batch.columns().to_vec()
+            };
+            let options = RecordBatchOptions::new().with_row_count(Some(batch.num_rows()));
+            Ok(RecordBatch::try_new_with_options(schema.clone(), columns, &options)?)
+        });
+
+        // Ending the stream at the limit drops the files still being opened
+        let limited = batches.scan(self.limit, |remaining, batch| {
+            let batch = match (batch, *remaining) {
+                (Ok(_), Some(0)) => return future::ready(None),
+                (Ok(batch), Some(n)) => {
+                    let batch = batch.slice(0, n.min(batch.num_rows()));
+                    *remaining = Some(n - batch.num_rows());
+                    Ok(batch)
+                }
+                (batch, _) => batch,
+            };
+            future::ready(Some(batch))
+        });
+
+        Ok(Box::pin(RecordBatchStreamAdapter::new(self.schema.clone(), limited)))
+    }
+}
+
+/// Register one Polygon dataset as the table `name` in `ctx`
+///
+/// Also registers the client's object store with `ctx`, so scans can read
+/// the files.
+pub fn register_polygon_table(
+    ctx: &SessionContext,
+    name: &str,
+    client: Arc<PolygonClient>,
+    asset_class: AssetClass,
+    data_type: PolygonDataType,
+) -> Result<()> {
+    client.register_object_store(ctx)?;
+    let table = PolygonTableProvider::try_new(client, asset_class, data_type)?;
+    ctx.register_table(name, Arc::new(table))?;
+    Ok(())
+}
+
+/// Days since the Unix epoch of `date`
+fn epoch_days(date: NaiveDate) -> i32 {
+    // NaiveDate's default is the Unix epoch
+    date.signed_duration_since(NaiveDate::default()).num_days() as i32
+}
+
+/// Inclusive `(low, high)` bounds a filter places on the `date` column
+///
+/// An `IN` list is bounded by its earliest and latest dates.
+fn date_bounds(filter: &Expr) -> Option<(Option<NaiveDate>, Option<NaiveDate>)> {
+    match filter {
+        Expr::InList(InList { expr, list, negated: false }) if is_column(expr, DATE_PARTITION) => {
+            let dates = list.iter().map(date_literal).collect::<Option<Vec<_>>>()?;
+            Some((Some(*dates.iter().min()?), Some(*dates.iter().max()?)))
+        }
+        Expr::Between(Between { expr, negated: false, low, high }) if is_column(expr, DATE_PARTITION) => {
+            Some((Some(date_literal(low)?), Some(date_literal(high)?)))
+        }
+        Expr::BinaryExpr(BinaryExpr { left, op, right }) => {
+            let (op, date) = if is_column(left, DATE_PARTITION) {
+                (*op, date_literal(right)?)
+            } else if is_column(right, DATE_PARTITION) {
+                (op.swap()?, date_literal(left)?)
+            } else {
+                return None;
+            };
+            let day = Duration::days(1);
+            match op {
+                Operator::Eq => Some((Some(date), Some(date))),
+                Operator::Gt => Some((Some(date + day), None)),
+                Operator::GtEq => Some((Some(date), None)),
+                Operator::Lt => Some((None, Some(date - day))),
+                Operator::LtEq => Some((None, Some(date))),
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+/// Tickers a filter restricts the `ticker` column to
+fn ticker_values(filter: &Expr) -> Option<Vec<String>> {
+    match filter {
+        Expr::BinaryExpr(BinaryExpr { left, op: Operator::Eq, right }) => {
+            let value = if is_column(left, "ticker") {
+                string_literal(right)?
+            } else if is_column(right, "ticker") {
+                string_literal(left)?
+            } else {
+                return None;
+            };
+            Some(vec![value])
+        }
+        Expr::InList(InList { expr, list, negated: false }) if is_column(expr, "ticker") => {
+            list.iter().map(string_literal).collect()
+        }
+        _ => None,
+    }
+}
+
+fn is_column(expr: &Expr, name: &str) -> bool {
+    matches!(expr, Expr::Column(column) if column.name == name)
+}
+
+fn date_literal(expr: &Expr) -> Option<NaiveDate> {
+    match expr {
+        Expr::Literal(ScalarValue::Date32(Some(days))) => {
+            NaiveDate::default().checked_add_signed(Duration::days(*days as i64))
+        }
+        Expr::Cast(Cast { expr, .. }) => date_literal(expr),
+        expr => string_literal(expr).and_then(|s| NaiveDate::parse_from_str(&s, "%Y-%m-%d").ok()),
+    }
+}
+
+fn string_literal(expr: &Expr) -> Option<String> {
+    match expr {
+        Expr::Literal(ScalarValue::Utf8(Some(s)) | ScalarValue::LargeUtf8(Some(s)) | ScalarValue::Utf8View(Some(s))) => {
+            Some(s.clone())
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+
+    fn day(d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2024, 1, d).unwrap()
+    }
+
+    fn date_lit(d: u32) -> Expr {
+        lit(ScalarValue::Date32(Some(epoch_days(day(d)))))
+    }
+
+    /// Stock day aggregates of January 2024 from a local mirror at `root`
+    fn day_aggs(root: &Path) -> Result<PolygonTableProvider> {
+        let client = Arc::new(PolygonClient::from_local(root)?);
+        Ok(PolygonTableProvider::try_new(client, AssetClass::Stocks, PolygonDataType::DayAggs)?
+            .with_date_range(day(2), day(31)))
+    }
+
+    #[test]
+    fn test_date_bounds() {
+        let date = || col(DATE_PARTITION);
+        assert_eq!(date_bounds(&date().eq(date_lit(5))), Some((Some(day(5)), Some(day(5)))));
+        assert_eq!(
+            date_bounds(&date().between(lit("2024-01-02"), lit("2024-01-09"))),
+            Some((Some(day(2)), Some(day(9))))
+        );
+        assert_eq!(date_bounds(&date().gt(date_lit(5))), Some((Some(day(6)), None)));
+        assert_eq!(date_bounds(&date().gt_eq(date_lit(5))), Some((Some(day(5)), None)));
+        assert_eq!(date_bounds(&date().lt(date_lit(5))), Some((None, Some(day(4)))));
+        assert_eq!(date_bounds(&date().lt_eq(date_lit(5))), Some((None, Some(day(5)))));
+        // A literal on the left flips the comparison
+        assert_eq!(date_bounds(&date_lit(5).gt(date())), Some((None, Some(day(4)))));
+        assert_eq!(
+            date_bounds(&date().in_list(vec![date_lit(9), date_lit(3), date_lit(5)], false)),
+            Some((Some(day(3)), Some(day(9))))
+        );
+
+        // Filters that don't bound the date aren't pushed down
+        assert_eq!(date_bounds(&date().not_eq(date_lit(5))), None);
+        assert_eq!(date_bounds(&date().in_list(vec![date_lit(3)], true)), None);
+        assert_eq!(date_bounds(&date().eq(lit("not a date"))), None);
+        assert_eq!(date_bounds(&col("ticker").eq(lit("AAPL"))), None);
+    }
+
+    #[test]
+    fn test_ticker_values() {
+        let tickers = |values: &[&str]| Some(values.iter().map(|t| t.to_string()).collect::<Vec<_>>());
+        assert_eq!(ticker_values(&col("ticker").eq(lit("AAPL"))), tickers(&["AAPL"]));
+        assert_eq!(ticker_values(&lit("MSFT").eq(col("ticker"))), tickers(&["MSFT"]));
+        assert_eq!(
+            ticker_values(&col("ticker").in_list(vec![lit("AAPL"), lit("MSFT")], false)),
+            tickers(&["AAPL", "MSFT"])
+        );
+
+        assert_eq!(ticker_values(&col("ticker").in_list(vec![lit("AAPL"), lit(1)], false)), None);
+        assert_eq!(ticker_values(&col("ticker").in_list(vec![lit("AAPL")], true)), None);
+        assert_eq!(ticker_values(&col("ticker").not_eq(lit("AAPL"))), None);
+    }
+
+    #[tokio::test]
+    async fn test_scan_filters() -> Result<()> {
+        let ctx = SessionContext::new();
+        let table = day_aggs(&std::env::temp_dir())?;
+        let aapl_msft = col("ticker").in_list(vec![lit("AAPL"), lit("MSFT")], false);
+
+        // Ticker filters intersect, and date filters narrow the days
+        let filters = [
+            aapl_msft.clone(),
+            col("ticker").eq(lit("MSFT")),
+            col(DATE_PARTITION).between(date_lit(3), date_lit(8)),
+        ];
+        let plan = table.scan(&ctx.state(), None, &filters, None).await?;
+        let scan = plan.as_any().downcast_ref::<PolygonScanExec>().expect("a Polygon scan");
+        assert_eq!(scan.tickers, ["MSFT"]);
+        assert_eq!(scan.days, [day(3), day(4), day(5), day(8)]);
+
+        // Disjoint ticker filters, or dates outside the table, leave nothing to read
+        for filters in [vec![aapl_msft, col("ticker").eq(lit("TSLA"))], vec![col(DATE_PARTITION).gt(date_lit(31))]] {
+            let plan = table.scan(&ctx.state(), None, &filters, None).await?;
+            assert!(plan.as_any().is::<EmptyExec>(), "{:?}", filters);
+        }
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_scan_limit() -> Result<()> {
+        let root = std::env::temp_dir().join(format!("polygon-table-limit-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&root);
+        let dir = root.join("us_stocks_sip/day_aggs_v1/2024");
+        std::fs::create_dir_all(&dir)?;
+
+        // One readable day, then days whose files aren't day aggregates
+        std::fs::write(
+            dir.join("2024-01-02.csv"),
+            "ticker,volume,open,close,high,low,window_start,transactions\n\
+             AAPL,100,1.0,2.0,3.0,0.5,1704171600000000000,10\n\
+             MSFT,200,1.0,2.0,3.0,0.5,1704171600000000000,20\n\
+             TSLA,300,1.0,2.0,3.0,0.5,1704171600000000000,30\n",
+        )?;
+        for date in candidate_days(&AssetClass::Stocks, day(3), day(31)) {
+            std::fs::write(dir.join(format!("{}.csv", date)), "garbage\n1\n")?;
+        }
+
+        let ctx = SessionContext::new();
+        ctx.register_table("day_aggs", Arc::new(day_aggs(&root)?))?;
+
+        // The first file meets the limit, so the scan never reads the others
+        let batches = ctx.sql("SELECT ticker, date FROM day_aggs LIMIT 2").await?.collect().await?;
+        assert_eq!(batches.iter().map(|batch| batch.num_rows()).sum::<usize>(), 2);
+
+        // Without a limit it goes on to them
+        assert!(ctx.sql("SELECT ticker FROM day_aggs").await?.collect().await.is_err());
+
+        std::fs::remove_dir_all(&root)?;
+        Ok(())
+    }
+}
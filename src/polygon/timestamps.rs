@@ -0,0 +1,163 @@
+//! Exchange-local views of Polygon's UTC nanosecond timestamps
+//!
+//! Flat files stamp rows with Int64 nanoseconds since the epoch in UTC
+//! (`window_start` for aggregates, `sip_timestamp` for trades and quotes).
+//! [`with_exchange_time`] keeps that column and adds the same instant in the
+//! exchange's time zone, its trading date and its market session.
+
+use datafusion::arrow::datatypes::{DataType, TimeUnit};
+use datafusion::dataframe::DataFrame;
+use datafusion::error::Result;
+use datafusion::logical_expr::{cast as cast_expr, Expr, ScalarUDF};
+use datafusion::prelude::col;
+
+use crate::calendar::{CalendarFunction, ExchangeCalendar};
+
+/// Timestamp columns converted automatically, in order of preference
+pub const TIMESTAMP_COLUMNS: &[&str] = &["window_start", "sip_timestamp"];
+
+/// Exchange-local `Timestamp` column added by [`with_exchange_time`]
+pub const LOCAL_TIME_COLUMN: &str = "local_time";
+/// Exchange-local `Date32` column added by [`with_exchange_time`]
+pub const DATE_COLUMN: &str = "date";
+/// Market session column (`pre`, `regular`, `post`, `closed`) added by [`with_exchange_time`]
+pub const SESSION_COLUMN: &str = "session";
+
+/// Add `local_time`, `date` and `session` columns derived from `timestamp`
+///
+/// `timestamp` may be Int64 epoch nanoseconds or any `Timestamp` column.
+/// `local_time` is a nanosecond `Timestamp` in the calendar's time zone, so
+/// it displays as exchange time while comparing as the same instant.
+pub fn with_exchange_time(df: DataFrame, timestamp: &str, calendar: &ExchangeCalendar) -> Result<DataFrame> {
+    let data_type = df.schema().field_with_unqualified_name(timestamp)?.data_type().clone();
+    let nanos = utc_nanos(col(timestamp), &data_type);
+    let timezone = calendar.timezone().name();
+
+    let trading_date = ScalarUDF::from(CalendarFunction::trading_date().with_calendar(calendar.clone()));
+    let session = ScalarUDF::from(CalendarFunction::market_session().with_calendar(calendar.clone()));
+
+    df.with_column(
+        LOCAL_TIME_COLUMN,
+        cast_expr(nanos.clone(), DataType::Timestamp(TimeUnit::Nanosecond, Some(timezone.into()))),
+    )?
+    .with_column(DATE_COLUMN, trading_date.call(vec![nanos.clone()]))?
+    .with_column(SESSION_COLUMN, session.call(vec![nanos]))
+}
+
+/// Apply [`with_exchange_time`] to the first of [`TIMESTAMP_COLUMNS`] in
+/// `df`, or return `df` unchanged if it has none
+pub fn with_exchange_time_auto(df: DataFrame, calendar: &ExchangeCalendar) -> Result<DataFrame> {
+    let column = TIMESTAMP_COLUMNS
+        .iter()
+        .find(|name| df.schema().field_with_unqualified_name(name).is_ok());
+    match column {
+        Some(column) => with_exchange_time(df, column, calendar),
+        None => Ok(df),
+    }
+}
+
+/// Int64 UTC epoch nanoseconds of a timestamp or epoch-nanosecond column
+fn utc_nanos(expr: Expr, data_type: &DataType) -> Expr {
+    match data_type {
+        DataType::Int64 => expr,
+        // Normalize the unit first; the stored value is UTC whatever the zone
+        DataType::Timestamp(_, tz) => cast_expr(
+            cast_expr(expr, DataType::Timestamp(TimeUnit::Nanosecond, tz.clone())),
+            DataType::Int64,
+        ),
+        _ => cast_expr(expr, DataType::Int64),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    use chrono::{NaiveDate, TimeZone, Utc};
+    use datafusion::arrow::array::{AsArray, Int64Array, RecordBatch, StringArray};
+    use datafusion::arrow::datatypes::{Date32Type, Field, Int64Type, Schema, TimestampNanosecondType};
+    use datafusion::prelude::SessionContext;
+
+    fn nanos(month: u32, day: u32, hour: u32, minute: u32) -> i64 {
+        Utc.with_ymd_and_hms(2024, month, day, hour, minute, 0).unwrap().timestamp_nanos_opt().unwrap()
+    }
+
+    fn epoch_days(month: u32, day: u32) -> i32 {
+        let date = NaiveDate::from_ymd_opt(2024, month, day).unwrap();
+        date.signed_duration_since(NaiveDate::default()).num_days() as i32
+    }
+
+    fn frame(ctx: &SessionContext, column: &str, values: Vec<i64>) -> Result<DataFrame> {
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("ticker", DataType::Utf8, false),
+            Field::new(column, DataType::Int64, false),
+        ]));
+        let tickers = StringArray::from(vec!["X:BTCUSD"; values.len()]);
+        let batch = RecordBatch::try_new(schema, vec![Arc::new(tickers), Arc::new(Int64Array::from(values))])?;
+        ctx.read_batch(batch)
+    }
+
+    #[tokio::test]
+    async fn test_with_exchange_time_across_dst() -> Result<()> {
+        let ctx = SessionContext::new();
+        let window_start = vec![
+            nanos(3, 8, 13, 30),  // 08:30 EST, before the spring change
+            nanos(3, 8, 14, 30),  // 09:30 EST
+            nanos(3, 11, 13, 30), // 09:30 EDT, after it
+            nanos(3, 12, 3, 0),   // 23:00 EDT on March 11
+            nanos(11, 1, 13, 30), // 09:30 EDT, before the fall change
+            nanos(11, 4, 13, 30), // 08:30 EST, after it
+            nanos(11, 4, 14, 30), // 09:30 EST
+        ];
+        let df = frame(&ctx, "window_start", window_start.clone())?;
+        let df = with_exchange_time(df, "window_start", &ExchangeCalendar::nyse())?;
+        let schema = df.schema().inner().clone();
+        let batch = datafusion::arrow::compute::concat_batches(&schema, &df.collect().await?)?;
+
+        // The original column is kept, and local_time is the same instant in New York time
+        let kept = batch.column_by_name("window_start").unwrap().as_primitive::<Int64Type>();
+        assert_eq!(kept.values().to_vec(), window_start);
+        let local_time = batch.column_by_name(LOCAL_TIME_COLUMN).unwrap();
+        assert_eq!(
+            local_time.data_type(),
+            &DataType::Timestamp(TimeUnit::Nanosecond, Some("America/New_York".into()))
+        );
+        assert_eq!(local_time.as_primitive::<TimestampNanosecondType>().values().to_vec(), window_start);
+
+        let dates = batch.column_by_name(DATE_COLUMN).unwrap().as_primitive::<Date32Type>();
+        let expected = [(3, 8), (3, 8), (3, 11), (3, 11), (11, 1), (11, 4), (11, 4)].map(|(m, d)| epoch_days(m, d));
+        assert_eq!(dates.values().to_vec(), expected);
+
+        let sessions = batch.column_by_name(SESSION_COLUMN).unwrap().as_string::<i32>();
+        let sessions = sessions.iter().map(Option::unwrap).collect::<Vec<_>>();
+        assert_eq!(sessions, ["pre", "regular", "regular", "closed", "regular", "pre", "regular"]);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_with_exchange_time_auto() -> Result<()> {
+        let ctx = SessionContext::new();
+        let calendar = ExchangeCalendar::nyse();
+
+        // Crypto aggregates keep their UTC window_start untouched beside the added columns
+        let window_start = vec![nanos(3, 9, 23, 59), nanos(3, 10, 0, 0), nanos(3, 10, 7, 30)];
+        let df = with_exchange_time_auto(frame(&ctx, "window_start", window_start.clone())?, &calendar)?;
+        let names = df.schema().fields().iter().map(|f| f.name().as_str()).collect::<Vec<_>>();
+        assert_eq!(names, ["ticker", "window_start", LOCAL_TIME_COLUMN, DATE_COLUMN, SESSION_COLUMN]);
+        let batches = df.collect().await?;
+        let kept = batches.iter().flat_map(|batch| {
+            batch.column_by_name("window_start").unwrap().as_primitive::<Int64Type>().values().to_vec()
+        });
+        assert_eq!(kept.collect::<Vec<_>>(), window_start);
+
+        // Trades are converted by sip_timestamp
+        let df = with_exchange_time_auto(frame(&ctx, "sip_timestamp", vec![nanos(3, 11, 13, 30)])?, &calendar)?;
+        assert!(df.schema().has_column_with_unqualified_name(SESSION_COLUMN));
+
+        // Without a known timestamp column the frame is returned as is
+        let df = with_exchange_time_auto(frame(&ctx, "timestamp", vec![nanos(3, 11, 13, 30)])?, &calendar)?;
+        assert_eq!(df.schema().fields().len(), 2);
+        Ok(())
+    }
+}
@@ -1,5 +1,7 @@
 //! Data types for Polygon.io integration
 
+use chrono::NaiveDate;
+use datafusion::error::DataFusionError;
 use serde::{Deserialize, Serialize};
 
 /// Supported Polygon.io data types
@@ -12,6 +14,21 @@ pub enum PolygonDataType {
     GroupedDaily,
 }
 
+impl PolygonDataType {
+    /// Get the S3 directory name of this data type
+    ///
+    /// Polygon publishes the grouped daily bars, one row per ticker for a
+    /// whole market, as the day aggregates file.
+    pub fn s3_prefix(&self) -> &'static str {
+        match self {
+            PolygonDataType::MinuteAggs => "minute_aggs_v1",
+            PolygonDataType::DayAggs | PolygonDataType::GroupedDaily => "day_aggs_v1",
+            PolygonDataType::Trades => "trades_v1",
+            PolygonDataType::Quotes => "quotes_v1",
+        }
+    }
+}
+
 /// Supported asset classes
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum AssetClass {
@@ -24,6 +41,16 @@ pub enum AssetClass {
 }
 
 impl AssetClass {
+    /// Every asset class
+    pub const ALL: [AssetClass; 6] = [
+        AssetClass::Stocks,
+        AssetClass::Options,
+        AssetClass::Futures,
+        AssetClass::Indices,
+        AssetClass::Forex,
+        AssetClass::Crypto,
+    ];
+
     /// Get the S3 directory prefix for this asset class
     pub fn s3_prefix(&self) -> &'static str {
         match self {
@@ -35,4 +62,76 @@ impl AssetClass {
             AssetClass::Crypto => "global_crypto",
         }
     }
+
+    /// The `market` filter of the reference tickers API, if it covers this asset class
+    pub fn reference_market(&self) -> Option<&'static str> {
+        match self {
+            AssetClass::Stocks => Some("stocks"),
+            AssetClass::Indices => Some("indices"),
+            AssetClass::Forex => Some("fx"),
+            AssetClass::Crypto => Some("crypto"),
+            AssetClass::Options | AssetClass::Futures => None,
+        }
+    }
+}
+
+/// Reference data for one ticker
+///
+/// The ticker list only fills the basic fields; `market_cap`, share counts
+/// and the industry come from the per-ticker details endpoint.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TickerDetails {
+    pub ticker: String,
+    pub name: Option<String>,
+    pub market: Option<String>,
+    pub locale: Option<String>,
+    pub primary_exchange: Option<String>,
+    #[serde(rename = "type")]
+    pub ticker_type: Option<String>,
+    pub active: Option<bool>,
+    pub currency_name: Option<String>,
+    pub cik: Option<String>,
+    pub market_cap: Option<f64>,
+    pub share_class_shares_outstanding: Option<f64>,
+    pub sic_description: Option<String>,
+    pub list_date: Option<String>,
+}
+
+/// Error for a day with no flat file, e.g. a holiday or a day not yet published
+#[derive(Debug, Clone)]
+pub struct DataNotAvailable {
+    pub asset_class: AssetClass,
+    pub data_type: PolygonDataType,
+    pub date: NaiveDate,
+    /// Path of the flat file that was looked for
+    pub path: String,
+}
+
+impl std::fmt::Display for DataNotAvailable {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "No {:?} {:?} data available for {} (no file at {})",
+            self.asset_class, self.data_type, self.date, self.path
+        )
+    }
+}
+
+impl std::error::Error for DataNotAvailable {}
+
+impl From<DataNotAvailable> for DataFusionError {
+    fn from(error: DataNotAvailable) -> Self {
+        DataFusionError::External(Box::new(error))
+    }
+}
+
+impl DataNotAvailable {
+    /// The missing day `error` reports, if it is a [`DataNotAvailable`] error
+    pub fn from_error(error: &DataFusionError) -> Option<&DataNotAvailable> {
+        match error {
+            DataFusionError::External(e) => e.downcast_ref::<DataNotAvailable>(),
+            DataFusionError::Context(_, e) => Self::from_error(e),
+            _ => None,
+        }
+    }
 }
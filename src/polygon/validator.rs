@@ -1,46 +1,526 @@
 //! Data validation utilities for Polygon.io datasets
 
+use async_trait::async_trait;
+use datafusion::arrow::array::{Array, AsArray};
+use datafusion::arrow::datatypes::{DataType, Date32Type, Float64Type, Int64Type};
+use datafusion::dataframe::DataFrame;
 use datafusion::execution::context::SessionContext;
 use datafusion::error::Result;
+use datafusion::logical_expr::ScalarUDF;
+use datafusion::prelude::{cast, col, lit};
 
-use std::collections::HashMap;
+use crate::adjust::CorporateActions;
+use crate::calendar::{CalendarFunction, ExchangeCalendar};
 
-/// Data quality validation report
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+
+use super::{AssetClass, PolygonDataType};
+
+/// Default [`ValidationConfig::max_spread`]
+pub const EXCESSIVE_SPREAD: f64 = 0.10;
+
+/// How the validator treats data on days the exchange is closed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WeekendPolicy {
+    /// Count rows on weekends and holidays, and trades outside exchange
+    /// hours, as failures
+    Flag,
+    /// Accept them, for markets that trade around the clock
+    Allow,
+}
+
+/// Thresholds of the validator's checks
+#[derive(Debug, Clone)]
+pub struct ValidationConfig {
+    /// Longest gap between consecutive minute bars before it counts as a time gap
+    pub max_gap: Duration,
+    /// Spread, as a fraction of the midpoint, above which a quote is excessively wide
+    pub max_spread: f64,
+    /// Count bar-to-bar close returns more than this many standard deviations
+    /// from the ticker's mean as price spikes; `None` skips the check
+    pub spike_z_score: Option<f64>,
+    pub weekend_policy: WeekendPolicy,
+    /// Custom checks run after the built-in ones
+    pub rules: Vec<Arc<dyn ValidationRule>>,
+    /// Fraction of rows an error check may fail and only warn, e.g. 0.001
+    /// to fail at 0.1% or more; 0 fails on any bad row
+    pub error_threshold: f64,
+    /// Asset class recorded in reports
+    pub asset_class: Option<AssetClass>,
+    /// Date of the validated data recorded in reports
+    pub date: Option<NaiveDate>,
+    /// Rolling price outlier check of bars and trades; `None` skips it
+    pub outliers: Option<OutlierConfig>,
+}
+
+impl Default for ValidationConfig {
+    fn default() -> Self {
+        Self {
+            max_gap: Duration::from_secs(60),
+            max_spread: EXCESSIVE_SPREAD,
+            spike_z_score: None,
+            weekend_policy: WeekendPolicy::Flag,
+            rules: Vec::new(),
+            error_threshold: 0.0,
+            asset_class: None,
+            date: None,
+            outliers: None,
+        }
+    }
+}
+
+impl ValidationConfig {
+    /// Defaults for `asset_class`: crypto and forex trade through weekends
+    /// and US holidays, so their data there is allowed
+    pub fn for_asset_class(asset_class: &AssetClass) -> Self {
+        let weekend_policy = match asset_class {
+            AssetClass::Crypto | AssetClass::Forex => WeekendPolicy::Allow,
+            _ => WeekendPolicy::Flag,
+        };
+        Self {
+            weekend_policy,
+            asset_class: Some(asset_class.clone()),
+            ..Self::default()
+        }
+    }
+
+    pub fn with_max_gap(mut self, max_gap: Duration) -> Self {
+        self.max_gap = max_gap;
+        self
+    }
+
+    pub fn with_max_spread(mut self, max_spread: f64) -> Self {
+        self.max_spread = max_spread;
+        self
+    }
+
+    pub fn with_spike_z_score(mut self, z_score: f64) -> Self {
+        self.spike_z_score = Some(z_score);
+        self
+    }
+
+    pub fn with_weekend_policy(mut self, policy: WeekendPolicy) -> Self {
+        self.weekend_policy = policy;
+        self
+    }
+
+    pub fn with_error_threshold(mut self, fraction: f64) -> Self {
+        self.error_threshold = fraction;
+        self
+    }
+
+    /// Record `date` as the date of the validated data
+    pub fn with_date(mut self, date: NaiveDate) -> Self {
+        self.date = Some(date);
+        self
+    }
+
+    /// Count prices far outside their rolling window as "Price Outliers"
+    pub fn with_outlier_check(mut self, outliers: OutlierConfig) -> Self {
+        self.outliers = Some(outliers);
+        self
+    }
+
+    /// Also run `rule` on every validated table
+    pub fn with_rule(mut self, rule: impl ValidationRule + 'static) -> Self {
+        self.rules.push(Arc::new(rule));
+        self
+    }
+}
+
+/// How far a price is from its rolling window
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutlierMethod {
+    /// Standard deviations from the window's mean
+    ZScore,
+    /// Median absolute deviations from the window's median, scaled to match
+    /// standard deviations for normal data; earlier outliers barely move it
+    Mad,
+}
+
+/// Settings of the rolling price outlier check
+///
+/// Each print (a bar's close or a trade's price) is compared with the
+/// `window` prints of its ticker before it, and counts as an outlier when
+/// it is more than `threshold` deviations away. Windows with no spread at
+/// all flag nothing.
 #[derive(Debug, Clone)]
+pub struct OutlierConfig {
+    pub method: OutlierMethod,
+    /// Earlier prints each price is compared with, at least 2 (see [`OutlierConfig::window`])
+    window: usize,
+    pub threshold: f64,
+    /// Tickers and exchange dates whose prints are never outliers, such as
+    /// split and ex-dividend dates
+    pub allowed_dates: HashSet<(String, NaiveDate)>,
+}
+
+impl Default for OutlierConfig {
+    fn default() -> Self {
+        Self::new(OutlierMethod::Mad, 50, 10.0)
+    }
+}
+
+impl OutlierConfig {
+    pub fn new(method: OutlierMethod, window: usize, threshold: f64) -> Self {
+        Self {
+            method,
+            window: window.max(2),
+            threshold,
+            allowed_dates: HashSet::new(),
+        }
+    }
+
+    /// Earlier prints each price is compared with
+    pub fn window(&self) -> usize {
+        self.window
+    }
+
+    /// Never flag `ticker`'s prints on the exchange date `date`
+    pub fn allow_date(mut self, ticker: impl Into<String>, date: NaiveDate) -> Self {
+        self.allowed_dates.insert((ticker.into(), date));
+        self
+    }
+
+    /// Never flag prints on split execution and ex-dividend dates
+    pub fn allow_corporate_actions(mut self, actions: &CorporateActions) -> Self {
+        let splits = actions.splits.iter().map(|split| (split.ticker.clone(), split.execution_date));
+        let dividends = actions
+            .dividends
+            .iter()
+            .map(|dividend| (dividend.ticker.clone(), dividend.ex_dividend_date));
+        self.allowed_dates.extend(splits.chain(dividends));
+        self
+    }
+
+    /// Whether `ticker`'s `price` on `date` is an outlier against the full
+    /// `window` of prints before it
+    fn is_outlier(&self, window: &VecDeque<f64>, ticker: &str, date: NaiveDate, price: f64) -> bool {
+        window.len() == self.window
+            && self.deviations(window, price) > self.threshold
+            && !self.allowed_dates.contains(&(ticker.to_string(), date))
+    }
+
+    /// Deviations of `price` from `window`, or 0 when the window has no
+    /// spread or fewer than two prints
+    fn deviations(&self, window: &VecDeque<f64>, price: f64) -> f64 {
+        if window.len() < 2 {
+            return 0.0;
+        }
+        let (center, scale) = match self.method {
+            OutlierMethod::ZScore => {
+                let n = window.len() as f64;
+                let mean = window.iter().sum::<f64>() / n;
+                let variance = window.iter().map(|x| (x - mean).powi(2)).sum::<f64>() / (n - 1.0);
+                (mean, variance.sqrt())
+            }
+            OutlierMethod::Mad => {
+                let center = median(window.iter().copied().collect()).unwrap_or(0.0);
+                let mad = median(window.iter().map(|x| (x - center).abs()).collect()).unwrap_or(0.0);
+                // 1.4826 * MAD estimates the standard deviation of normal data
+                (center, 1.4826 * mad)
+            }
+        };
+        if scale > 0.0 {
+            (price - center).abs() / scale
+        } else {
+            0.0
+        }
+    }
+}
+
+/// Median of `values`, or `None` when there are none
+fn median(mut values: Vec<f64>) -> Option<f64> {
+    if values.is_empty() {
+        return None;
+    }
+    values.sort_by(f64::total_cmp);
+    let mid = values.len() / 2;
+    match values.len() % 2 {
+        0 => Some((values[mid - 1] + values[mid]) / 2.0),
+        _ => Some(values[mid]),
+    }
+}
+
+/// How much a failed check matters
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum Severity {
+    /// Reported for information only
+    Info,
+    /// Makes the validation warn
+    Warning,
+    /// Fails the validation, unless few enough rows fail (see
+    /// [`ValidationConfig::error_threshold`])
+    Error,
+}
+
+/// Outcome of one check or of a whole validation
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum ValidationStatus {
+    Pass,
+    Warn,
+    Fail,
+}
+
+/// A check run by the validator, reported under its name
+#[async_trait]
+pub trait ValidationRule: Send + Sync + std::fmt::Debug {
+    fn name(&self) -> &str;
+
+    fn severity(&self) -> Severity {
+        Severity::Error
+    }
+
+    /// Number of rows of `table_name` failing the check
+    async fn failed_rows(&self, ctx: &SessionContext, table_name: &str) -> Result<usize>;
+}
+
+/// A rule written in SQL
+#[derive(Debug, Clone)]
+pub struct SqlRule {
+    name: String,
+    query: String,
+    severity: Severity,
+}
+
+impl SqlRule {
+    /// Rows matching the `WHERE` condition `failing` fail,
+    /// e.g. `exchange = 4 AND size > 10000`
+    pub fn new(name: impl Into<String>, failing: &str) -> Self {
+        Self::from_query(name, &format!("SELECT COUNT(*) FROM {{table}} WHERE {}", failing))
+    }
+
+    /// `query` returns the failing row count as a single Int64, with
+    /// `{table}` standing for the validated table
+    pub fn from_query(name: impl Into<String>, query: &str) -> Self {
+        Self {
+            name: name.into(),
+            query: query.to_string(),
+            severity: Severity::Error,
+        }
+    }
+
+    pub fn with_severity(mut self, severity: Severity) -> Self {
+        self.severity = severity;
+        self
+    }
+}
+
+#[async_trait]
+impl ValidationRule for SqlRule {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn severity(&self) -> Severity {
+        self.severity
+    }
+
+    async fn failed_rows(&self, ctx: &SessionContext, table_name: &str) -> Result<usize> {
+        count(ctx, &self.query.replace("{table}", table_name)).await
+    }
+}
+
+/// A rule given as a closure from the table to its failing rows, e.g.
+/// `|df| df.filter(col("high").gt(col("low") * lit(1.5)))`
+pub struct FnRule<F> {
+    name: String,
+    failing: F,
+    severity: Severity,
+}
+
+impl<F> FnRule<F>
+where
+    F: Fn(DataFrame) -> Result<DataFrame> + Send + Sync,
+{
+    pub fn new(name: impl Into<String>, failing: F) -> Self {
+        Self {
+            name: name.into(),
+            failing,
+            severity: Severity::Error,
+        }
+    }
+
+    pub fn with_severity(mut self, severity: Severity) -> Self {
+        self.severity = severity;
+        self
+    }
+}
+
+impl<F> std::fmt::Debug for FnRule<F> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("FnRule")
+            .field("name", &self.name)
+            .field("severity", &self.severity)
+            .finish_non_exhaustive()
+    }
+}
+
+#[async_trait]
+impl<F> ValidationRule for FnRule<F>
+where
+    F: Fn(DataFrame) -> Result<DataFrame> + Send + Sync,
+{
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn severity(&self) -> Severity {
+        self.severity
+    }
+
+    async fn failed_rows(&self, ctx: &SessionContext, table_name: &str) -> Result<usize> {
+        (self.failing)(ctx.table(table_name).await?)?.count().await
+    }
+}
+
+/// Data quality validation report
+///
+/// Serializes to JSON with [`to_json`](Self::to_json), so reports can be
+/// stored and tracked over time.
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ValidationReport {
+    /// Table the checks ran on
+    pub table_name: Option<String>,
+    pub asset_class: Option<AssetClass>,
+    pub data_type: Option<PolygonDataType>,
+    /// Date of the validated data
+    pub date: Option<NaiveDate>,
+    /// When the validation ran
+    pub run_at: DateTime<Utc>,
     pub checks: HashMap<String, usize>,
+    /// Severity of each check; checks added by `add_check` are errors
+    pub severities: HashMap<String, Severity>,
+    /// Failed rows of each check as a percentage of `total_rows`
+    pub failed_percent: HashMap<String, f64>,
     pub total_rows: usize,
+    /// See [`ValidationConfig::error_threshold`]
+    pub error_threshold: f64,
+    /// Whether no check failed, warnings aside
     pub passed: bool,
 }
 
 impl ValidationReport {
     pub fn new() -> Self {
         Self {
+            table_name: None,
+            asset_class: None,
+            data_type: None,
+            date: None,
+            run_at: Utc::now(),
             checks: HashMap::new(),
+            severities: HashMap::new(),
+            failed_percent: HashMap::new(),
             total_rows: 0,
+            error_threshold: 0.0,
             passed: true,
         }
     }
+
+    /// Report on `table_name` with `config`'s threshold and dataset details
+    fn for_table(table_name: &str, data_type: Option<PolygonDataType>, config: &ValidationConfig) -> Self {
+        Self {
+            table_name: Some(table_name.to_string()),
+            asset_class: config.asset_class.clone(),
+            data_type,
+            date: config.date,
+            ..Self::new()
+        }
+        .with_error_threshold(config.error_threshold)
+    }
     
     pub fn add_check(&mut self, name: &str, failed_rows: usize) {
+        self.add_check_with_severity(name, failed_rows, Severity::Error);
+    }
+
+    /// Record a check; only `Severity::Error` failures can fail the report
+    pub fn add_check_with_severity(&mut self, name: &str, failed_rows: usize, severity: Severity) {
         self.checks.insert(name.to_string(), failed_rows);
-        if failed_rows > 0 {
-            self.passed = false;
-        }
+        self.severities.insert(name.to_string(), severity);
+        self.refresh();
     }
     
     pub fn set_total_rows(&mut self, count: usize) {
         self.total_rows = count;
+        self.refresh();
+    }
+
+    pub fn with_error_threshold(mut self, fraction: f64) -> Self {
+        self.error_threshold = fraction;
+        self.refresh();
+        self
+    }
+
+    pub fn to_json(&self) -> serde_json::Result<String> {
+        serde_json::to_string_pretty(self)
+    }
+
+    pub fn from_json(json: &str) -> serde_json::Result<Self> {
+        serde_json::from_str(json)
+    }
+
+    /// Recompute the fields derived from the checks and row count
+    fn refresh(&mut self) {
+        let total_rows = self.total_rows;
+        self.failed_percent = self
+            .checks
+            .iter()
+            .map(|(name, failed_rows)| {
+                let percent = match total_rows {
+                    0 => 0.0,
+                    _ => *failed_rows as f64 / total_rows as f64 * 100.0,
+                };
+                (name.clone(), percent)
+            })
+            .collect();
+        self.passed = self.status() != ValidationStatus::Fail;
+    }
+
+    /// Outcome of the check `name`, if it ran
+    pub fn check_status(&self, name: &str) -> Option<ValidationStatus> {
+        let failed_rows = *self.checks.get(name)?;
+        let severity = self.severities.get(name).copied().unwrap_or(Severity::Error);
+        let within_threshold =
+            self.total_rows > 0 && (failed_rows as f64 / self.total_rows as f64) <= self.error_threshold;
+        Some(match severity {
+            _ if failed_rows == 0 => ValidationStatus::Pass,
+            Severity::Info => ValidationStatus::Pass,
+            Severity::Warning => ValidationStatus::Warn,
+            Severity::Error if within_threshold => ValidationStatus::Warn,
+            Severity::Error => ValidationStatus::Fail,
+        })
+    }
+
+    /// Worst outcome of any check
+    pub fn status(&self) -> ValidationStatus {
+        self.checks
+            .keys()
+            .filter_map(|name| self.check_status(name))
+            .max()
+            .unwrap_or(ValidationStatus::Pass)
     }
     
     pub fn summary(&self) -> String {
         let mut report = format!("Validation Report:\n");
         report.push_str(&format!("Total rows: {}\n", self.total_rows));
-        report.push_str(&format!("Overall status: {}\n\n", 
-            if self.passed { "✅ PASSED" } else { "❌ FAILED" }));
+        report.push_str(&format!("Overall status: {}\n\n", match self.status() {
+            ValidationStatus::Pass => "✅ PASSED",
+            ValidationStatus::Warn => "⚠️ PASSED WITH WARNINGS",
+            ValidationStatus::Fail => "❌ FAILED",
+        }));
         
         for (check, failed_count) in &self.checks {
-            let status = if *failed_count == 0 { "✅" } else { "❌" };
+            let status = match self.check_status(check) {
+                Some(ValidationStatus::Fail) => "❌",
+                Some(ValidationStatus::Warn) => "⚠️",
+                _ if *failed_count > 0 => "ℹ️",
+                _ => "✅",
+            };
             report.push_str(&format!("{} {}: {} failed rows\n", status, check, failed_count));
         }
         
@@ -60,12 +540,33 @@ pub struct PolygonValidator;
 impl PolygonValidator {
 
     
-    /// Validate minute aggregates data quality
+    /// Run only `config`'s custom rules, on a table of any kind
+    pub async fn validate_rules(
+        ctx: &SessionContext,
+        table_name: &str,
+        config: &ValidationConfig,
+    ) -> Result<ValidationReport> {
+        let mut report = ValidationReport::for_table(table_name, None, config);
+        report.set_total_rows(count(ctx, &format!("SELECT COUNT(*) FROM {}", table_name)).await?);
+        run_rules(ctx, table_name, config, &mut report).await?;
+        Ok(report)
+    }
+
+    /// Validate minute aggregates data quality with the default [`ValidationConfig`]
     pub async fn validate_minute_aggs(
         ctx: &SessionContext,
         table_name: &str,
     ) -> Result<ValidationReport> {
-        let mut report = ValidationReport::new();
+        Self::validate_minute_aggs_with_config(ctx, table_name, &ValidationConfig::default()).await
+    }
+
+    /// Validate minute aggregates data quality
+    pub async fn validate_minute_aggs_with_config(
+        ctx: &SessionContext,
+        table_name: &str,
+        config: &ValidationConfig,
+    ) -> Result<ValidationReport> {
+        let mut report = ValidationReport::for_table(table_name, Some(PolygonDataType::MinuteAggs), config);
 
         // Get total row count
         let total_count = ctx
@@ -93,8 +594,9 @@ impl PolygonValidator {
                 )
                 SELECT COUNT(*) as gap_count
                 FROM time_gaps 
-                WHERE gap_ns > 60000000000", // More than 1 minute gap
-                table_name
+                WHERE gap_ns > {}",
+                table_name,
+                config.max_gap.as_nanos()
             ))
             .await?
             .collect()
@@ -158,16 +660,34 @@ impl PolygonValidator {
 
         report.add_check("Time Gaps", gap_rows);
         report.add_check("Logic Errors", logic_rows);
+        if let Some(z_score) = config.spike_z_score {
+            report.add_check("Price Spikes", price_spikes(ctx, table_name, z_score).await?);
+        }
+        if let Some(outliers) = &config.outliers {
+            let outlier_rows = price_outliers(ctx, table_name, "window_start", "close", outliers).await?;
+            report.add_check("Price Outliers", outlier_rows);
+        }
+
+        run_rules(ctx, table_name, config, &mut report).await?;
 
         Ok(report)
     }
 
-    /// Validate day aggregates data quality
+    /// Validate day aggregates data quality with the default [`ValidationConfig`]
     pub async fn validate_day_aggs(
         ctx: &SessionContext,
         table_name: &str,
     ) -> Result<ValidationReport> {
-        let mut report = ValidationReport::new();
+        Self::validate_day_aggs_with_config(ctx, table_name, &ValidationConfig::default()).await
+    }
+
+    /// Validate day aggregates data quality
+    pub async fn validate_day_aggs_with_config(
+        ctx: &SessionContext,
+        table_name: &str,
+        config: &ValidationConfig,
+    ) -> Result<ValidationReport> {
+        let mut report = ValidationReport::for_table(table_name, Some(PolygonDataType::DayAggs), config);
 
         // Get total row count
         let total_count = ctx
@@ -184,26 +704,351 @@ impl PolygonValidator {
             }
         }
 
-        // Check for missing weekend filtering (should not have Saturday/Sunday data)
-        let weekend_check = ctx
-            .sql(&format!(
-                "SELECT COUNT(*) as weekend_count
-                FROM {}
-                WHERE EXTRACT(DOW FROM date) IN (0, 6)", // Sunday = 0, Saturday = 6
-                table_name
-            ))
-            .await?
-            .collect()
+        if config.weekend_policy == WeekendPolicy::Flag {
+            // Rows dated on weekends or exchange holidays, when the market was closed
+            let day_counts = ctx
+                .sql(&format!(
+                    "SELECT CAST(date AS DATE) AS day, COUNT(*) AS row_count
+                    FROM {}
+                    GROUP BY CAST(date AS DATE)",
+                    table_name
+                ))
+                .await?
+                .collect()
+                .await?;
+
+            let calendar = ExchangeCalendar::nyse();
+            let mut closed_rows = 0;
+            for batch in &day_counts {
+                let days = batch.column(0).as_primitive::<Date32Type>();
+                let counts = batch.column(1).as_primitive::<Int64Type>();
+                for row in 0..batch.num_rows() {
+                    let closed = days.is_valid(row)
+                        && days.value_as_date(row).is_some_and(|day| !calendar.is_trading_day(day));
+                    if closed {
+                        closed_rows += counts.value(row) as usize;
+                    }
+                }
+            }
+
+            report.add_check("Non-Trading Day Data", closed_rows);
+        }
+        if let Some(z_score) = config.spike_z_score {
+            report.add_check("Price Spikes", price_spikes(ctx, table_name, z_score).await?);
+        }
+        if let Some(outliers) = &config.outliers {
+            let outlier_rows = price_outliers(ctx, table_name, "window_start", "close", outliers).await?;
+            report.add_check("Price Outliers", outlier_rows);
+        }
+
+        run_rules(ctx, table_name, config, &mut report).await?;
+
+        Ok(report)
+    }
+
+    /// Validate trades data quality
+    ///
+    /// Checks for non-positive prices and negative sizes, SIP timestamps
+    /// that go backwards in a ticker's `sequence_number` order (when the
+    /// table has one), trade IDs repeated for the same ticker and exchange,
+    /// and trades outside NYSE pre-market to post-market hours (unless
+    /// the config's [`WeekendPolicy`] allows them).
+    pub async fn validate_trades(
+        ctx: &SessionContext,
+        table_name: &str,
+    ) -> Result<ValidationReport> {
+        Self::validate_trades_with_config(ctx, table_name, &ValidationConfig::default()).await
+    }
+
+    /// Validate trades data quality with `config`'s thresholds (see [`validate_trades`](Self::validate_trades))
+    pub async fn validate_trades_with_config(
+        ctx: &SessionContext,
+        table_name: &str,
+        config: &ValidationConfig,
+    ) -> Result<ValidationReport> {
+        let mut report = ValidationReport::for_table(table_name, Some(PolygonDataType::Trades), config);
+        report.set_total_rows(count(ctx, &format!("SELECT COUNT(*) FROM {}", table_name)).await?);
+
+        let invalid_values = count(
+            ctx,
+            &format!("SELECT COUNT(*) FROM {} WHERE price <= 0 OR size < 0", table_name),
+        )
+        .await?;
+        report.add_check("Negative Prices or Sizes", invalid_values);
+
+        let trades = ctx.table(table_name).await?;
+        if trades.schema().has_column_with_unqualified_name("sequence_number") {
+            let out_of_sequence = count(
+                ctx,
+                &format!(
+                    "WITH ordered AS (
+                        SELECT sip_timestamp,
+                               LAG(sip_timestamp) OVER (PARTITION BY ticker ORDER BY sequence_number) AS prev_time
+                        FROM {}
+                    )
+                    SELECT COUNT(*) FROM ordered WHERE sip_timestamp < prev_time",
+                    table_name
+                ),
+            )
             .await?;
+            report.add_check("Out-of-Sequence Timestamps", out_of_sequence);
+        }
 
-        let weekend_rows = if let Some(batch) = weekend_check.first() {
-            if let Some(array) = batch.column(0).as_any().downcast_ref::<datafusion::arrow::array::Int64Array>() {
-                array.value(0) as usize
-            } else { 0 }
-        } else { 0 };
+        // Every copy after the first of an ID counts as a duplicate
+        let duplicates = count(
+            ctx,
+            &format!(
+                "SELECT COALESCE(SUM(copies - 1), 0) FROM (
+                    SELECT COUNT(*) AS copies FROM {}
+                    GROUP BY ticker, exchange, id
+                    HAVING COUNT(*) > 1
+                )",
+                table_name
+            ),
+        )
+        .await?;
+        report.add_check("Duplicate Trade IDs", duplicates);
+
+        if config.weekend_policy == WeekendPolicy::Flag {
+            let session = ScalarUDF::from(CalendarFunction::market_session());
+            let outside_hours = trades
+                .filter(session.call(vec![col("sip_timestamp")]).eq(lit("closed")))?
+                .count()
+                .await?;
+            report.add_check("Outside Exchange Hours", outside_hours);
+        }
+        if let Some(outliers) = &config.outliers {
+            let outlier_rows = price_outliers(ctx, table_name, "sip_timestamp", "price", outliers).await?;
+            report.add_check("Price Outliers", outlier_rows);
+        }
 
-        report.add_check("Weekend Data", weekend_rows);
+        run_rules(ctx, table_name, config, &mut report).await?;
 
         Ok(report)
     }
+
+    /// Validate quotes data quality
+    ///
+    /// Checks for crossed (bid above ask) and locked (bid equal to ask)
+    /// markets, zero or negative sizes on a quoted side, and two-sided
+    /// quotes whose spread is over [`EXCESSIVE_SPREAD`] of the midpoint. A
+    /// side with a zero price is an absent side, not an error.
+    pub async fn validate_quotes(
+        ctx: &SessionContext,
+        table_name: &str,
+    ) -> Result<ValidationReport> {
+        Self::validate_quotes_with_config(ctx, table_name, &ValidationConfig::default()).await
+    }
+
+    /// Validate quotes data quality, with `config.max_spread` as the spread limit
+    pub async fn validate_quotes_with_config(
+        ctx: &SessionContext,
+        table_name: &str,
+        config: &ValidationConfig,
+    ) -> Result<ValidationReport> {
+        let mut report = ValidationReport::for_table(table_name, Some(PolygonDataType::Quotes), config);
+        report.set_total_rows(count(ctx, &format!("SELECT COUNT(*) FROM {}", table_name)).await?);
+
+        let two_sided = "bid_price > 0 AND ask_price > 0";
+        let crossed = count(
+            ctx,
+            &format!("SELECT COUNT(*) FROM {} WHERE {} AND bid_price > ask_price", table_name, two_sided),
+        )
+        .await?;
+        let locked = count(
+            ctx,
+            &format!("SELECT COUNT(*) FROM {} WHERE {} AND bid_price = ask_price", table_name, two_sided),
+        )
+        .await?;
+        let invalid_sizes = count(
+            ctx,
+            &format!(
+                "SELECT COUNT(*) FROM {}
+                WHERE (bid_price > 0 AND bid_size <= 0) OR (ask_price > 0 AND ask_size <= 0)
+                   OR bid_size < 0 OR ask_size < 0",
+                table_name
+            ),
+        )
+        .await?;
+        let wide = count(
+            ctx,
+            &format!(
+                "SELECT COUNT(*) FROM {}
+                WHERE {} AND ask_price > bid_price
+                  AND (ask_price - bid_price) / ((ask_price + bid_price) / 2) > {}",
+                table_name, two_sided, config.max_spread
+            ),
+        )
+        .await?;
+
+        report.add_check("Crossed Markets", crossed);
+        report.add_check("Locked Markets", locked);
+        report.add_check("Zero or Negative Sizes", invalid_sizes);
+        report.add_check("Excessive Spreads", wide);
+
+        run_rules(ctx, table_name, config, &mut report).await?;
+
+        Ok(report)
+    }
+}
+
+/// Add the results of `config`'s custom rules to `report`
+async fn run_rules(
+    ctx: &SessionContext,
+    table_name: &str,
+    config: &ValidationConfig,
+    report: &mut ValidationReport,
+) -> Result<()> {
+    for rule in &config.rules {
+        let failed_rows = rule.failed_rows(ctx, table_name).await?;
+        report.add_check_with_severity(rule.name(), failed_rows, rule.severity());
+    }
+    Ok(())
+}
+
+/// Bars whose close-to-close return is more than `z_score` standard
+/// deviations from their ticker's mean return
+async fn price_spikes(ctx: &SessionContext, table_name: &str, z_score: f64) -> Result<usize> {
+    count(
+        ctx,
+        &format!(
+            "WITH returns AS (
+                SELECT ticker,
+                       close / LAG(close) OVER (PARTITION BY ticker ORDER BY window_start) - 1 AS ret
+                FROM {}
+            ),
+            scored AS (
+                SELECT ret,
+                       AVG(ret) OVER (PARTITION BY ticker) AS mean_ret,
+                       STDDEV(ret) OVER (PARTITION BY ticker) AS sd_ret
+                FROM returns
+            )
+            SELECT COUNT(*) FROM scored WHERE sd_ret > 0 AND ABS(ret - mean_ret) / sd_ret > {}",
+            table_name, z_score
+        ),
+    )
+    .await
+}
+
+/// Prints of `price_column` outside their rolling window (see [`OutlierConfig`])
+async fn price_outliers(
+    ctx: &SessionContext,
+    table_name: &str,
+    time_column: &str,
+    price_column: &str,
+    outliers: &OutlierConfig,
+) -> Result<usize> {
+    let batches = ctx
+        .table(table_name)
+        .await?
+        .select(vec![
+            cast(col("ticker"), DataType::Utf8).alias("ticker"),
+            cast(col(time_column), DataType::Int64).alias("time"),
+            cast(col(price_column), DataType::Float64).alias("price"),
+        ])?
+        .sort(vec![col("ticker").sort(true, false), col("time").sort(true, false)])?
+        .collect()
+        .await?;
+
+    let calendar = ExchangeCalendar::nyse();
+    let mut window = VecDeque::with_capacity(outliers.window + 1);
+    let mut current_ticker: Option<String> = None;
+    let mut flagged = 0;
+    for batch in &batches {
+        let tickers = batch.column(0).as_string::<i32>();
+        let times = batch.column(1).as_primitive::<Int64Type>();
+        let prices = batch.column(2).as_primitive::<Float64Type>();
+        for row in 0..batch.num_rows() {
+            if tickers.is_null(row) || times.is_null(row) || prices.is_null(row) {
+                continue;
+            }
+            let ticker = tickers.value(row);
+            if current_ticker.as_deref() != Some(ticker) {
+                window.clear();
+                current_ticker = Some(ticker.to_string());
+            }
+
+            let price = prices.value(row);
+            let date = calendar.local_date(DateTime::from_timestamp_nanos(times.value(row)));
+            if outliers.is_outlier(&window, ticker, date, price) {
+                flagged += 1;
+            }
+            window.push_back(price);
+            if window.len() > outliers.window {
+                window.pop_front();
+            }
+        }
+    }
+    Ok(flagged)
+}
+
+/// The single Int64 count a query returns
+async fn count(ctx: &SessionContext, sql: &str) -> Result<usize> {
+    let batches = ctx.sql(sql).await?.collect().await?;
+    Ok(batches
+        .first()
+        .filter(|batch| batch.num_rows() > 0)
+        .map(|batch| {
+            let column = batch.column(0).as_primitive::<Int64Type>();
+            if column.is_valid(0) { column.value(0) as usize } else { 0 }
+        })
+        .unwrap_or(0))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn window(prices: &[f64]) -> VecDeque<f64> {
+        prices.iter().copied().collect()
+    }
+
+    #[test]
+    fn test_median() {
+        assert_eq!(median(vec![]), None);
+        assert_eq!(median(vec![3.0]), Some(3.0));
+        assert_eq!(median(vec![5.0, 1.0, 3.0]), Some(3.0));
+        assert_eq!(median(vec![4.0, 1.0, 3.0, 2.0]), Some(2.5));
+    }
+
+    #[test]
+    fn test_deviations() {
+        let zscore = OutlierConfig::new(OutlierMethod::ZScore, 4, 3.0);
+        // Mean 10, sample standard deviation sqrt(4 / 3)
+        let prices = window(&[9.0, 11.0, 9.0, 11.0]);
+        let sd = (4.0f64 / 3.0).sqrt();
+        assert!((zscore.deviations(&prices, 13.0) - 3.0 / sd).abs() < 1e-12);
+
+        let mad = OutlierConfig::new(OutlierMethod::Mad, 4, 3.0);
+        // Median 10, MAD 1
+        assert!((mad.deviations(&prices, 13.0) - 3.0 / 1.4826).abs() < 1e-12);
+
+        // No spread, or too few prints, flags nothing
+        assert_eq!(zscore.deviations(&window(&[10.0, 10.0, 10.0, 10.0]), 50.0), 0.0);
+        assert_eq!(mad.deviations(&window(&[10.0, 10.0, 10.0, 10.0]), 50.0), 0.0);
+        assert_eq!(zscore.deviations(&window(&[]), 50.0), 0.0);
+        assert_eq!(mad.deviations(&window(&[10.0]), 50.0), 0.0);
+    }
+
+    #[test]
+    fn test_outlier_window_is_clamped() {
+        let config = OutlierConfig::new(OutlierMethod::Mad, 0, 3.0);
+        assert_eq!(config.window(), 2);
+        assert!(!config.is_outlier(&window(&[]), "AAPL", NaiveDate::default(), 1000.0));
+    }
+
+    #[test]
+    fn test_outlier_allowlist() {
+        let split_day = NaiveDate::from_ymd_opt(2020, 8, 31).unwrap();
+        let next_day = NaiveDate::from_ymd_opt(2020, 9, 1).unwrap();
+        let config = OutlierConfig::new(OutlierMethod::Mad, 4, 3.0).allow_date("AAPL", split_day);
+        let prices = window(&[499.0, 501.0, 499.0, 501.0]);
+
+        assert!(!config.is_outlier(&prices, "AAPL", split_day, 125.0));
+        assert!(config.is_outlier(&prices, "AAPL", next_day, 125.0));
+        assert!(config.is_outlier(&prices, "MSFT", split_day, 125.0));
+        assert!(!config.is_outlier(&prices, "AAPL", next_day, 500.5));
+        // A partial window never flags
+        assert!(!config.is_outlier(&window(&[499.0, 501.0, 499.0]), "AAPL", next_day, 125.0));
+    }
 }
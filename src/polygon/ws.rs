@@ -0,0 +1,392 @@
+//! Polygon.io WebSocket ingestion into the streaming indicators
+//!
+//! Connects to a Polygon WebSocket cluster, authenticates, subscribes to
+//! channels (`T.AAPL`, `Q.*`, `XT.BTC-USD`, `C.EUR/USD`, `AM.MSFT`, ...) and
+//! converts trades and aggregates into [`MarketTick`]s:
+//!
+//! - trades: trade price and size
+//! - aggregates: bar close and volume, at the bar's end
+//!
+//! `volume` holds sizes rounded down; a crypto or forex size with a fraction
+//! is also kept exactly in `fractional_volume`.
+//! Quotes are dropped unless [`PolygonWebSocket::with_quotes`] asks for
+//! them, since their bid/ask midpoints and zero volumes would otherwise mix
+//! into the trade prices the indicators see. Requires the `ws` feature.
+
+use std::collections::HashMap;
+
+use chrono::{DateTime, Utc};
+use datafusion::error::{DataFusionError, Result};
+use futures::{SinkExt, StreamExt};
+use serde::Deserialize;
+use tokio::net::TcpStream;
+use tokio_tungstenite::tungstenite::Message;
+use tokio_tungstenite::{connect_async, MaybeTlsStream, WebSocketStream};
+
+use crate::streaming::{MarketTick, StreamingProcessor};
+
+/// Polygon WebSocket clusters
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WsCluster {
+    Stocks,
+    Crypto,
+    Forex,
+}
+
+impl WsCluster {
+    fn path(&self) -> &'static str {
+        match self {
+            WsCluster::Stocks => "stocks",
+            WsCluster::Crypto => "crypto",
+            WsCluster::Forex => "forex",
+        }
+    }
+}
+
+/// Connection settings for a Polygon WebSocket feed
+#[derive(Debug, Clone)]
+pub struct PolygonWebSocket {
+    cluster: WsCluster,
+    api_key: String,
+    channels: Vec<String>,
+    delayed: bool,
+    quotes: bool,
+}
+
+impl PolygonWebSocket {
+    pub fn new(cluster: WsCluster, api_key: impl Into<String>) -> Self {
+        Self {
+            cluster,
+            api_key: api_key.into(),
+            channels: Vec::new(),
+            delayed: false,
+            quotes: false,
+        }
+    }
+
+    /// Create from the `POLYGON_API_KEY` environment variable
+    pub fn from_env(cluster: WsCluster) -> std::result::Result<Self, Box<dyn std::error::Error>> {
+        dotenv::dotenv().ok();
+        let api_key = std::env::var("POLYGON_API_KEY").map_err(|_| "POLYGON_API_KEY not found in environment")?;
+        Ok(Self::new(cluster, api_key))
+    }
+
+    /// Subscribe to channels such as `T.AAPL` or `XQ.*` once connected
+    pub fn subscribe<I, S>(mut self, channels: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.channels.extend(channels.into_iter().map(Into::into));
+        self
+    }
+
+    /// Use the 15-minute delayed feed
+    pub fn with_delayed(mut self, delayed: bool) -> Self {
+        self.delayed = delayed;
+        self
+    }
+
+    /// Also emit quotes, as ticks priced at the bid/ask midpoint with zero
+    /// volume and the bid and ask set
+    ///
+    /// Use it on a connection subscribed to quotes only, or check
+    /// `MarketTick::bid` to tell quotes from trades.
+    pub fn with_quotes(mut self, quotes: bool) -> Self {
+        self.quotes = quotes;
+        self
+    }
+
+    fn url(&self) -> String {
+        let host = if self.delayed { "delayed.polygon.io" } else { "socket.polygon.io" };
+        format!("wss://{}/{}", host, self.cluster.path())
+    }
+
+    /// Connect, authenticate and subscribe
+    pub async fn connect(&self) -> Result<WsConnection> {
+        let (socket, _) = connect_async(self.url()).await.map_err(external)?;
+        let mut connection = WsConnection {
+            socket,
+            quotes: self.quotes,
+        };
+
+        let auth = serde_json::json!({ "action": "auth", "params": self.api_key });
+        connection.send(auth).await?;
+        connection.await_auth().await?;
+
+        if !self.channels.is_empty() {
+            let subscribe = serde_json::json!({ "action": "subscribe", "params": self.channels.join(",") });
+            connection.send(subscribe).await?;
+        }
+        Ok(connection)
+    }
+
+    /// Stream ticks into `processors`, keyed by symbol, until the connection closes
+    ///
+    /// Ticks for symbols without a processor are dropped. Signals are
+    /// delivered through each processor's signal handlers.
+    pub async fn run(&self, processors: &HashMap<String, StreamingProcessor>) -> Result<()> {
+        let mut connection = self.connect().await?;
+        while let Some(ticks) = connection.next_ticks().await? {
+            for tick in ticks {
+                if let Some(processor) = processors.get(&tick.symbol) {
+                    processor.process_tick(tick).map_err(DataFusionError::External)?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// An authenticated WebSocket connection
+pub struct WsConnection {
+    socket: WebSocketStream<MaybeTlsStream<TcpStream>>,
+    quotes: bool,
+}
+
+impl WsConnection {
+    async fn send(&mut self, message: serde_json::Value) -> Result<()> {
+        self.socket.send(Message::Text(message.to_string())).await.map_err(external)
+    }
+
+    /// Wait for the server to accept or reject the API key
+    async fn await_auth(&mut self) -> Result<()> {
+        while let Some(events) = self.next_events().await? {
+            for event in events {
+                if let WsEvent::Status { status, message } = event {
+                    match status.as_str() {
+                        "auth_success" => return Ok(()),
+                        "auth_failed" => {
+                            return Err(DataFusionError::Execution(format!(
+                                "Polygon WebSocket authentication failed: {}",
+                                message
+                            )))
+                        }
+                        _ => {}
+                    }
+                }
+            }
+        }
+        Err(DataFusionError::Execution(
+            "Polygon WebSocket closed before authenticating".to_string(),
+        ))
+    }
+
+    /// Next batch of ticks; `None` once the connection closes
+    ///
+    /// Status messages, quotes unless enabled, and events that don't map to
+    /// a tick yield an empty batch.
+    pub async fn next_ticks(&mut self) -> Result<Option<Vec<MarketTick>>> {
+        let Some(events) = self.next_events().await? else {
+            return Ok(None);
+        };
+        let quotes = self.quotes;
+        Ok(Some(
+            events
+                .into_iter()
+                .filter(|event| quotes || !event.is_quote())
+                .filter_map(WsEvent::into_tick)
+                .collect(),
+        ))
+    }
+
+    async fn next_events(&mut self) -> Result<Option<Vec<WsEvent>>> {
+        loop {
+            let Some(message) = self.socket.next().await else {
+                return Ok(None);
+            };
+            match message.map_err(external)? {
+                Message::Text(text) => return parse_events(&text).map(Some),
+                Message::Ping(payload) => self.socket.send(Message::Pong(payload)).await.map_err(external)?,
+                Message::Close(_) => return Ok(None),
+                _ => {}
+            }
+        }
+    }
+}
+
+/// One element of a Polygon WebSocket message array, tagged by `ev`
+#[derive(Debug, Deserialize)]
+#[serde(tag = "ev")]
+enum WsEvent {
+    #[serde(rename = "status")]
+    Status {
+        status: String,
+        #[serde(default)]
+        message: String,
+    },
+    #[serde(rename = "T")]
+    Trade {
+        sym: String,
+        p: f64,
+        s: f64,
+        t: i64,
+    },
+    #[serde(rename = "Q")]
+    Quote {
+        sym: String,
+        bp: f64,
+        ap: f64,
+        t: i64,
+    },
+    #[serde(rename = "AM", alias = "A")]
+    Aggregate {
+        sym: String,
+        c: f64,
+        v: f64,
+        e: i64,
+    },
+    #[serde(rename = "XT")]
+    CryptoTrade {
+        pair: String,
+        p: f64,
+        s: f64,
+        t: i64,
+    },
+    #[serde(rename = "XQ")]
+    CryptoQuote {
+        pair: String,
+        bp: f64,
+        ap: f64,
+        t: i64,
+    },
+    #[serde(rename = "XA", alias = "XAS")]
+    CryptoAggregate {
+        pair: String,
+        c: f64,
+        v: f64,
+        e: i64,
+    },
+    #[serde(rename = "C")]
+    ForexQuote {
+        p: String,
+        b: f64,
+        a: f64,
+        t: i64,
+    },
+    #[serde(rename = "CA", alias = "CAS")]
+    ForexAggregate {
+        pair: String,
+        c: f64,
+        v: f64,
+        e: i64,
+    },
+    #[serde(other)]
+    Other,
+}
+
+impl WsEvent {
+    fn is_quote(&self) -> bool {
+        matches!(
+            self,
+            WsEvent::Quote { .. } | WsEvent::CryptoQuote { .. } | WsEvent::ForexQuote { .. }
+        )
+    }
+
+    fn into_tick(self) -> Option<MarketTick> {
+        let (symbol, timestamp_ms, price, volume, quote) = match self {
+            WsEvent::Trade { sym, p, s, t } => (sym, t, p, s, None),
+            WsEvent::CryptoTrade { pair, p, s, t } => (pair, t, p, s, None),
+            WsEvent::Quote { sym, bp, ap, t } => (sym, t, (bp + ap) / 2.0, 0.0, Some((bp, ap))),
+            WsEvent::CryptoQuote { pair, bp, ap, t } => (pair, t, (bp + ap) / 2.0, 0.0, Some((bp, ap))),
+            WsEvent::ForexQuote { p, b, a, t } => (p, t, (b + a) / 2.0, 0.0, Some((b, a))),
+            WsEvent::Aggregate { sym, c, v, e } => (sym, e, c, v, None),
+            WsEvent::CryptoAggregate { pair, c, v, e } => (pair, e, c, v, None),
+            WsEvent::ForexAggregate { pair, c, v, e } => (pair, e, c, v, None),
+            WsEvent::Status { .. } | WsEvent::Other => return None,
+        };
+
+        Some(MarketTick {
+            symbol,
+            timestamp: DateTime::<Utc>::from_timestamp_millis(timestamp_ms)?,
+            price,
+            volume: volume as u64,
+            fractional_volume: (volume.fract() != 0.0).then_some(volume),
+            bid: quote.map(|(bid, _)| bid),
+            ask: quote.map(|(_, ask)| ask),
+        })
+    }
+}
+
+/// Parse a message array, skipping elements that don't match their event's layout
+fn parse_events(text: &str) -> Result<Vec<WsEvent>> {
+    let values: Vec<serde_json::Value> =
+        serde_json::from_str(text).map_err(|e| DataFusionError::External(Box::new(e)))?;
+    Ok(values
+        .into_iter()
+        .filter_map(|value| serde_json::from_value(value).ok())
+        .collect())
+}
+
+fn external(error: tokio_tungstenite::tungstenite::Error) -> DataFusionError {
+    DataFusionError::External(Box::new(error))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_events() -> Result<()> {
+        let events = parse_events(
+            r#"[
+                {"ev":"status","status":"auth_success","message":"authenticated"},
+                {"ev":"T","sym":"AAPL","p":189.5,"s":100,"t":1704205800000},
+                {"ev":"T","sym":"AAPL","p":"bad"},
+                {"ev":"XQ","pair":"BTC-USD","bp":42000.0,"ap":42001.0,"t":1704205800000},
+                {"ev":"LULD","T":"AAPL"}
+            ]"#,
+        )?;
+
+        // The malformed trade is skipped and unknown events are kept as Other
+        assert_eq!(events.len(), 4);
+        assert!(matches!(&events[0], WsEvent::Status { status, .. } if status == "auth_success"));
+        assert!(matches!(&events[1], WsEvent::Trade { sym, .. } if sym == "AAPL"));
+        assert!(events[2].is_quote());
+        assert!(matches!(events[3], WsEvent::Other));
+
+        assert!(parse_events("not json").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_into_tick() -> Result<()> {
+        let mut events = parse_events(
+            r#"[
+                {"ev":"XT","pair":"BTC-USD","p":42000.5,"s":0.0025,"t":1704205800000},
+                {"ev":"AM","sym":"MSFT","c":375.2,"v":12034,"e":1704205860000},
+                {"ev":"CA","pair":"EUR/USD","c":1.0951,"v":42,"s":1704205800000,"e":1704205860000},
+                {"ev":"C","p":"EUR/USD","b":1.0950,"a":1.0952,"t":1704205800000}
+            ]"#,
+        )?
+        .into_iter();
+
+        // Fractional crypto sizes are kept beside the rounded-down volume
+        let trade = events.next().and_then(WsEvent::into_tick).expect("crypto trade tick");
+        assert_eq!(trade.symbol, "BTC-USD");
+        assert_eq!((trade.volume, trade.fractional_volume), (0, Some(0.0025)));
+        assert_eq!(trade.exact_volume(), 0.0025);
+        assert_eq!(trade.bid, None);
+        assert_eq!(trade.timestamp.timestamp_millis(), 1704205800000);
+
+        // Aggregates of every cluster are stamped at the bar's end
+        let bar = events.next().and_then(WsEvent::into_tick).expect("aggregate tick");
+        assert_eq!((bar.price, bar.volume, bar.fractional_volume), (375.2, 12034, None));
+        assert_eq!(bar.timestamp.timestamp_millis(), 1704205860000);
+
+        let forex_bar = events.next().and_then(WsEvent::into_tick).expect("forex aggregate tick");
+        assert_eq!(forex_bar.timestamp.timestamp_millis(), 1704205860000);
+
+        let quote = events.next().and_then(WsEvent::into_tick).expect("forex quote tick");
+        assert_eq!(quote.symbol, "EUR/USD");
+        assert!((quote.price - 1.0951).abs() < 1e-12);
+        assert_eq!((quote.volume, quote.bid, quote.ask), (0, Some(1.0950), Some(1.0952)));
+
+        let status = WsEvent::Status {
+            status: "connected".to_string(),
+            message: String::new(),
+        };
+        assert!(status.into_tick().is_none());
+        Ok(())
+    }
+}
@@ -0,0 +1,585 @@
+//! Selective, optionally prefixed registration of the financial functions
+
+use std::any::Any;
+use std::collections::HashSet;
+use std::sync::Arc;
+use std::time::Duration;
+
+use datafusion::arrow::compute::SortOptions;
+use datafusion::arrow::datatypes::{DataType, Field};
+use datafusion::common::ScalarValue;
+use datafusion::error::{DataFusionError, Result};
+use datafusion::execution::context::SessionContext;
+use datafusion::logical_expr::function::{
+    AccumulatorArgs, AggregateFunctionSimplification, StateFieldsArgs, WindowFunctionSimplification,
+};
+use datafusion::logical_expr::simplify::{ExprSimplifyResult, SimplifyInfo};
+use datafusion::logical_expr::sort_properties::{ExprProperties, SortProperties};
+use datafusion::logical_expr::utils::AggregateOrderSensitivity;
+use datafusion::logical_expr::{
+    Accumulator, AggregateUDF, AggregateUDFImpl, ColumnarValue, Expr, GroupsAccumulator, PartitionEvaluator,
+    ReversedUDAF, ScalarUDF, ScalarUDFImpl, Signature, WindowUDF, WindowUDFImpl,
+};
+
+use crate::calendar;
+use crate::options;
+use crate::functions::*;
+use crate::functions::frames::RejectIgnoredFrames;
+use crate::functions::sessions::{SessionReset, DEFAULT_SESSION_BOUNDARY};
+use crate::indicator::{register_indicators, registered_indicators};
+
+/// A module's worth of related functions and the function that registers them
+struct FunctionGroup {
+    name: &'static str,
+    register: fn(&SessionContext) -> Result<()>,
+}
+
+/// Builder selecting which financial functions to register, and under what names
+///
+/// ```ignore
+/// FinancialFunctions::new()
+///     .with_sma()
+///     .with_rsi()
+///     .with_prefix("fin_")
+///     .register(&ctx)?; // registers fin_sma and fin_rsi
+///
+/// FinancialFunctions::new()
+///     .with_ema()
+///     .with_sessions()
+///     .register(&ctx)?; // registers ema and session_ema
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct FinancialFunctions {
+    groups: Vec<&'static str>,
+    custom: bool,
+    prefix: Option<String>,
+    sessions: Option<Duration>,
+}
+
+/// Indicators added by downstream crates through `indicator::register_indicator`
+const CUSTOM_INDICATORS: FunctionGroup = FunctionGroup {
+    name: "custom",
+    register: register_indicators,
+};
+
+macro_rules! function_groups {
+    ($($with:ident => $module:ident::$register:ident),* $(,)?) => {
+        const FUNCTION_GROUPS: &[FunctionGroup] = &[
+            $(FunctionGroup { name: stringify!($module), register: $module::$register }),*
+        ];
+
+        impl FinancialFunctions {
+            $(
+                #[doc = concat!("Include the functions registered by `", stringify!($module), "::", stringify!($register), "`")]
+                pub fn $with(self) -> Self {
+                    self.with_group(stringify!($module))
+                }
+            )*
+        }
+    };
+}
+
+function_groups! {
+    with_sma => sma::register_sma,
+    with_ema => ema::register_ema,
+    with_rsi => rsi::register_rsi,
+    with_macd => macd::register_macd,
+    with_bollinger => bollinger::register_bollinger,
+    with_atr => atr::register_atr,
+    with_stochastic => stochastic::register_stochastic,
+    with_vwap => vwap::register_vwap,
+    with_obv => obv::register_obv,
+    with_adx => adx::register_adx,
+    with_cci => cci::register_cci,
+    with_trix => trix::register_trix,
+    with_ichimoku => ichimoku::register_ichimoku,
+    with_psar => psar::register_psar,
+    with_donchian => donchian::register_donchian,
+    with_kama => kama::register_kama,
+    with_chaikin => chaikin::register_chaikin,
+    with_adl => adl::register_adl,
+    with_force_index => force_index::register_force_index,
+    with_rvi => rvi::register_rvi,
+    with_coppock => coppock::register_coppock,
+    with_kst => kst::register_kst,
+    with_mass_index => mass_index::register_mass_index,
+    with_ulcer => ulcer::register_ulcer,
+    with_rolling_stats => rolling_stats::register_rolling_stats,
+    with_volatility => volatility::register_volatility,
+    with_zscore => zscore::register_zscore,
+    with_returns => returns::register_returns,
+    with_cum_return => cum_return::register_cum_return,
+    with_drawdown => drawdown::register_drawdown,
+    with_risk_ratios => risk_ratios::register_risk_ratios,
+    with_correlation => correlation::register_correlation,
+    with_beta => beta::register_beta,
+    with_linreg => linreg::register_linreg,
+    with_anchored_vwap => anchored_vwap::register_anchored_vwap,
+    with_twap => twap::register_twap,
+    with_pivot_points => pivot_points::register_pivot_points,
+    with_price_transforms => price_transforms::register_price_transforms,
+    with_heikin_ashi => heikin_ashi::register_heikin_ashi,
+    with_gaps => gaps::register_gaps,
+    with_fractal => fractal::register_fractal,
+    with_volume_profile => volume_profile::register_volume_profile,
+    with_ewma_vol => ewma_vol::register_ewma_vol,
+    with_black_scholes => black_scholes::register_black_scholes,
+    with_last_indicators => last_indicators::register_last_indicators,
+    with_calendar => calendar::register_calendar,
+    with_options => options::register_options,
+}
+
+impl FinancialFunctions {
+    /// Start with no functions selected
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Select every financial function, including registered custom indicators
+    pub fn all() -> Self {
+        Self {
+            groups: FUNCTION_GROUPS.iter().map(|group| group.name).collect(),
+            custom: true,
+            prefix: None,
+            sessions: None,
+        }
+    }
+
+    /// Include the indicators added with `indicator::register_indicator`
+    pub fn with_custom_indicators(mut self) -> Self {
+        self.custom = true;
+        self
+    }
+
+    /// Leave out the indicators added with `indicator::register_indicator`
+    pub fn without_custom_indicators(mut self) -> Self {
+        self.custom = false;
+        self
+    }
+
+    /// Register every function under `prefix` + its usual name
+    pub fn with_prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.prefix = Some(prefix.into());
+        self
+    }
+
+    /// Also register a `session_<name>` variant of each selected window
+    /// function, restarting at every trading day (see `sessions`)
+    pub fn with_sessions(self) -> Self {
+        self.with_session_boundary(DEFAULT_SESSION_BOUNDARY)
+    }
+
+    /// Like [`with_sessions`](Self::with_sessions), with trading days
+    /// starting `boundary` after UTC midnight
+    pub fn with_session_boundary(mut self, boundary: Duration) -> Self {
+        self.sessions = Some(boundary);
+        self
+    }
+
+    fn with_group(mut self, name: &'static str) -> Self {
+        if !self.groups.contains(&name) {
+            self.groups.push(name);
+        }
+        self
+    }
+
+    /// Names of the selected function groups (module names), in registration order
+    pub fn groups(&self) -> Vec<&'static str> {
+        FUNCTION_GROUPS
+            .iter()
+            .map(|group| group.name)
+            .filter(|name| self.groups.contains(name))
+            .collect()
+    }
+
+    /// Register the selected functions with `ctx`
+    ///
+    /// The window functions registered get an analyzer rule rejecting frames
+    /// they would ignore; see [`frames`](crate::functions::frames).
+    pub fn register(&self, ctx: &SessionContext) -> Result<()> {
+        let existing = window_function_names(ctx);
+        for group in self.selected() {
+            match self.prefix() {
+                "" => (group.register)(ctx)?,
+                prefix => register_prefixed(ctx, group, prefix)?,
+            }
+        }
+        if self.sessions.is_some() {
+            self.register_sessions(ctx)?;
+        }
+
+        let added = window_function_names(ctx).difference(&existing).cloned().collect::<HashSet<_>>();
+        if !added.is_empty() {
+            ctx.add_analyzer_rule(Arc::new(RejectIgnoredFrames::new(added)));
+        }
+        Ok(())
+    }
+
+    /// Register only the `session_<name>` variants of the selected window
+    /// functions, leaving the plain functions out
+    pub fn register_sessions(&self, ctx: &SessionContext) -> Result<()> {
+        let boundary = self.sessions.unwrap_or(DEFAULT_SESSION_BOUNDARY);
+        for group in self.selected() {
+            let scratch = SessionContext::new();
+            let builtin = scratch.state().window_functions().keys().cloned().collect::<HashSet<_>>();
+            (group.register)(&scratch)?;
+
+            for (name, udf) in scratch.state().window_functions() {
+                if builtin.contains(name) {
+                    continue;
+                }
+                let session = SessionReset::new(udf).with_boundary(boundary);
+                let session = match self.prefix() {
+                    "" => WindowUDF::from(session),
+                    prefix => WindowUDF::from(Renamed::new(prefix, Arc::new(session) as Arc<dyn WindowUDFImpl>)),
+                };
+                ctx.register_udwf(session);
+            }
+        }
+        Ok(())
+    }
+
+    /// The selected groups, then the custom indicators if requested and any exist
+    fn selected(&self) -> impl Iterator<Item = &'static FunctionGroup> + '_ {
+        let custom = (self.custom && !registered_indicators().is_empty()).then_some(&CUSTOM_INDICATORS);
+        FUNCTION_GROUPS
+            .iter()
+            .filter(|group| self.groups.contains(&group.name))
+            .chain(custom)
+    }
+
+    fn prefix(&self) -> &str {
+        self.prefix.as_deref().unwrap_or("")
+    }
+}
+
+/// Names of the window functions registered with `ctx`
+fn window_function_names(ctx: &SessionContext) -> HashSet<String> {
+    ctx.state().window_functions().keys().cloned().collect()
+}
+
+/// Register a group into a scratch context, then copy its functions into
+/// `ctx` under prefixed names
+fn register_prefixed(ctx: &SessionContext, group: &FunctionGroup, prefix: &str) -> Result<()> {
+    let scratch = SessionContext::new();
+    let before = scratch.state();
+    let builtin_windows = before.window_functions().keys().cloned().collect::<HashSet<_>>();
+    let builtin_aggregates = before.aggregate_functions().keys().cloned().collect::<HashSet<_>>();
+    let builtin_scalars = before.scalar_functions().keys().cloned().collect::<HashSet<_>>();
+
+    (group.register)(&scratch)?;
+
+    let after = scratch.state();
+    let mut registered = 0;
+    for (name, udf) in after.window_functions() {
+        if !builtin_windows.contains(name) {
+            ctx.register_udwf(WindowUDF::from(Renamed::new(prefix, Arc::clone(udf.inner()))));
+            registered += 1;
+        }
+    }
+    for (name, udf) in after.aggregate_functions() {
+        if !builtin_aggregates.contains(name) {
+            ctx.register_udaf(AggregateUDF::from(Renamed::new(prefix, Arc::clone(udf.inner()))));
+            registered += 1;
+        }
+    }
+    for (name, udf) in after.scalar_functions() {
+        if !builtin_scalars.contains(name) {
+            ctx.register_udf(ScalarUDF::from(Renamed::new(prefix, Arc::clone(udf.inner()))));
+            registered += 1;
+        }
+    }
+
+    if registered == 0 {
+        return Err(DataFusionError::Internal(format!(
+            "Function group '{}' registered no functions",
+            group.name
+        )));
+    }
+    Ok(())
+}
+
+/// A UDF exposed under a different name, delegating everything else
+#[derive(Debug)]
+pub(crate) struct Renamed<T: ?Sized> {
+    name: String,
+    aliases: Vec<String>,
+    inner: Arc<T>,
+}
+
+impl<T: ?Sized> Renamed<T> {
+    fn new(prefix: &str, inner: Arc<T>) -> Self
+    where
+        T: NamedUdf,
+    {
+        Self {
+            name: format!("{}{}", prefix, inner.udf_name()),
+            aliases: inner.udf_aliases().iter().map(|alias| format!("{}{}", prefix, alias)).collect(),
+            inner,
+        }
+    }
+
+    /// Expose `inner` as `name`, without its aliases
+    pub(crate) fn named(name: impl Into<String>, inner: Arc<T>) -> Self {
+        Self {
+            name: name.into(),
+            aliases: Vec::new(),
+            inner,
+        }
+    }
+}
+
+/// Name lookup shared by the three UDF traits
+trait NamedUdf {
+    fn udf_name(&self) -> &str;
+    fn udf_aliases(&self) -> &[String];
+}
+
+impl NamedUdf for dyn WindowUDFImpl {
+    fn udf_name(&self) -> &str {
+        self.name()
+    }
+
+    fn udf_aliases(&self) -> &[String] {
+        self.aliases()
+    }
+}
+
+impl NamedUdf for dyn AggregateUDFImpl {
+    fn udf_name(&self) -> &str {
+        self.name()
+    }
+
+    fn udf_aliases(&self) -> &[String] {
+        self.aliases()
+    }
+}
+
+impl NamedUdf for dyn ScalarUDFImpl {
+    fn udf_name(&self) -> &str {
+        self.name()
+    }
+
+    fn udf_aliases(&self) -> &[String] {
+        self.aliases()
+    }
+}
+
+impl WindowUDFImpl for Renamed<dyn WindowUDFImpl> {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn signature(&self) -> &Signature {
+        self.inner.signature()
+    }
+
+    fn coerce_types(&self, arg_types: &[DataType]) -> Result<Vec<DataType>> {
+        self.inner.coerce_types(arg_types)
+    }
+
+    fn return_type(&self, arg_types: &[DataType]) -> Result<DataType> {
+        self.inner.return_type(arg_types)
+    }
+
+    fn partition_evaluator(&self) -> Result<Box<dyn PartitionEvaluator>> {
+        self.inner.partition_evaluator()
+    }
+
+    fn aliases(&self) -> &[String] {
+        &self.aliases
+    }
+
+    fn simplify(&self) -> Option<WindowFunctionSimplification> {
+        self.inner.simplify()
+    }
+
+    fn sort_options(&self) -> Option<SortOptions> {
+        self.inner.sort_options()
+    }
+}
+
+impl AggregateUDFImpl for Renamed<dyn AggregateUDFImpl> {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn signature(&self) -> &Signature {
+        self.inner.signature()
+    }
+
+    fn coerce_types(&self, arg_types: &[DataType]) -> Result<Vec<DataType>> {
+        self.inner.coerce_types(arg_types)
+    }
+
+    fn return_type(&self, arg_types: &[DataType]) -> Result<DataType> {
+        self.inner.return_type(arg_types)
+    }
+
+    fn accumulator(&self, acc_args: AccumulatorArgs) -> Result<Box<dyn Accumulator>> {
+        self.inner.accumulator(acc_args)
+    }
+
+    fn state_fields(&self, args: StateFieldsArgs) -> Result<Vec<Field>> {
+        self.inner.state_fields(args)
+    }
+
+    fn aliases(&self) -> &[String] {
+        &self.aliases
+    }
+
+    fn is_nullable(&self) -> bool {
+        self.inner.is_nullable()
+    }
+
+    fn groups_accumulator_supported(&self, args: AccumulatorArgs) -> bool {
+        self.inner.groups_accumulator_supported(args)
+    }
+
+    fn create_groups_accumulator(&self, args: AccumulatorArgs) -> Result<Box<dyn GroupsAccumulator>> {
+        self.inner.create_groups_accumulator(args)
+    }
+
+    fn create_sliding_accumulator(&self, args: AccumulatorArgs) -> Result<Box<dyn Accumulator>> {
+        self.inner.create_sliding_accumulator(args)
+    }
+
+    fn order_sensitivity(&self) -> AggregateOrderSensitivity {
+        self.inner.order_sensitivity()
+    }
+
+    fn with_beneficial_ordering(
+        self: Arc<Self>,
+        beneficial_ordering: bool,
+    ) -> Result<Option<Arc<dyn AggregateUDFImpl>>> {
+        let reordered = Arc::clone(&self.inner).with_beneficial_ordering(beneficial_ordering)?;
+        Ok(reordered.map(|inner| {
+            Arc::new(Self {
+                name: self.name.clone(),
+                aliases: self.aliases.clone(),
+                inner,
+            }) as Arc<dyn AggregateUDFImpl>
+        }))
+    }
+
+    fn simplify(&self) -> Option<AggregateFunctionSimplification> {
+        self.inner.simplify()
+    }
+
+    fn reverse_expr(&self) -> ReversedUDAF {
+        self.inner.reverse_expr()
+    }
+
+    fn is_descending(&self) -> Option<bool> {
+        self.inner.is_descending()
+    }
+
+    fn default_value(&self, data_type: &DataType) -> Result<ScalarValue> {
+        self.inner.default_value(data_type)
+    }
+}
+
+impl ScalarUDFImpl for Renamed<dyn ScalarUDFImpl> {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn signature(&self) -> &Signature {
+        self.inner.signature()
+    }
+
+    fn coerce_types(&self, arg_types: &[DataType]) -> Result<Vec<DataType>> {
+        self.inner.coerce_types(arg_types)
+    }
+
+    fn return_type(&self, arg_types: &[DataType]) -> Result<DataType> {
+        self.inner.return_type(arg_types)
+    }
+
+    fn invoke(&self, args: &[ColumnarValue]) -> Result<ColumnarValue> {
+        self.inner.invoke(args)
+    }
+
+    fn invoke_no_args(&self, number_rows: usize) -> Result<ColumnarValue> {
+        self.inner.invoke_no_args(number_rows)
+    }
+
+    fn aliases(&self) -> &[String] {
+        &self.aliases
+    }
+
+    fn simplify(&self, args: Vec<Expr>, info: &dyn SimplifyInfo) -> Result<ExprSimplifyResult> {
+        self.inner.simplify(args, info)
+    }
+
+    fn short_circuits(&self) -> bool {
+        self.inner.short_circuits()
+    }
+
+    fn output_ordering(&self, input: &[ExprProperties]) -> Result<SortProperties> {
+        self.inner.output_ordering(input)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_selective_prefixed_registration() -> Result<()> {
+        let ctx = SessionContext::new();
+        FinancialFunctions::new()
+            .with_sma()
+            .with_rsi()
+            .with_price_transforms()
+            .with_prefix("fin_")
+            .register(&ctx)?;
+
+        // Prefixed names resolve, unselected and unprefixed names do not
+        let result = ctx
+            .sql("SELECT price,
+                    fin_sma(price, 3) OVER () AS sma_3,
+                    fin_rsi(price, 3) OVER () AS rsi_3,
+                    fin_typical_price(price + 1.0, price - 1.0, price) AS tp
+                FROM (VALUES (1.0), (2.0), (3.0), (2.5), (4.0), (5.0)) AS t(price)")
+            .await?
+            .collect()
+            .await?;
+
+        println!("Prefixed Registration Test Results:");
+        datafusion::arrow::util::pretty::print_batches(&result)?;
+
+        assert!(ctx.sql("SELECT sma(1.0, 3) OVER ()").await.is_err());
+        assert!(ctx.sql("SELECT fin_ema(1.0, 3) OVER ()").await.is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_renamed_forwards_optional_methods() {
+        let inner = Arc::clone(datafusion::functions_aggregate::stddev::stddev_udaf().inner());
+        let renamed = Renamed::new("fin_", Arc::clone(&inner));
+
+        assert_eq!(renamed.name(), "fin_stddev");
+        let expected: Vec<String> = inner.aliases().iter().map(|alias| format!("fin_{}", alias)).collect();
+        assert_eq!(renamed.aliases(), expected.as_slice());
+        assert_eq!(renamed.is_nullable(), inner.is_nullable());
+        assert_eq!(renamed.is_descending(), inner.is_descending());
+
+        // Names passed through the factory carry no aliases of their own
+        let named = Renamed::named("my_stddev", inner);
+        assert!(named.aliases().is_empty());
+    }
+}
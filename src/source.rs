@@ -0,0 +1,82 @@
+//! Provider-independent market data loading
+//!
+//! [`MarketDataSource`] is the loader interface shared by data providers:
+//! bars, trades and quotes for a set of symbols over a date range, as
+//! DataFrames with Polygon's flat file column names. Indicators, signals and
+//! DataFrame transforms default to those names, so they work the same
+//! whichever provider loaded the data. [`PolygonClient`] implements it from
+//! flat files; with the `alpaca` feature,
+//! [`AlpacaClient`](crate::alpaca::AlpacaClient) implements it from the
+//! Alpaca REST API.
+
+use async_trait::async_trait;
+use chrono::NaiveDate;
+use datafusion::dataframe::DataFrame;
+use datafusion::error::Result;
+
+use crate::polygon::{AssetClass, DownloadOptions, PolygonClient, PolygonDataType};
+
+/// Length of each bar
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BarTimeframe {
+    Minute,
+    Day,
+}
+
+/// Loads stock market data for symbols and dates
+///
+/// Ranges are inclusive. Every method returns rows with Polygon's column
+/// names and epoch nanosecond timestamps:
+///
+/// - bars: `ticker`, `volume`, `open`, `close`, `high`, `low`,
+///   `window_start`, `transactions`
+/// - trades: `ticker`, `sip_timestamp`, `price`, `size`, `exchange`,
+///   `conditions`
+/// - quotes: `ticker`, `sip_timestamp`, `bid_price`, `bid_size`,
+///   `bid_exchange`, `ask_price`, `ask_size`, `ask_exchange`
+///
+/// Exchange identifiers and condition codes are the provider's own, and
+/// providers may add columns of their own.
+#[async_trait]
+pub trait MarketDataSource: Send + Sync {
+    async fn load_bars(
+        &self,
+        symbols: &[&str],
+        timeframe: BarTimeframe,
+        start: NaiveDate,
+        end: NaiveDate,
+    ) -> Result<DataFrame>;
+
+    async fn load_trades(&self, symbols: &[&str], start: NaiveDate, end: NaiveDate) -> Result<DataFrame>;
+
+    async fn load_quotes(&self, symbols: &[&str], start: NaiveDate, end: NaiveDate) -> Result<DataFrame>;
+}
+
+/// Stock flat files, downloaded with the default [`DownloadOptions`]
+#[async_trait]
+impl MarketDataSource for PolygonClient {
+    async fn load_bars(
+        &self,
+        symbols: &[&str],
+        timeframe: BarTimeframe,
+        start: NaiveDate,
+        end: NaiveDate,
+    ) -> Result<DataFrame> {
+        let data_type = match timeframe {
+            BarTimeframe::Minute => PolygonDataType::MinuteAggs,
+            BarTimeframe::Day => PolygonDataType::DayAggs,
+        };
+        self.load_date_range(AssetClass::Stocks, data_type, start, end, symbols, &DownloadOptions::default())
+            .await
+    }
+
+    async fn load_trades(&self, symbols: &[&str], start: NaiveDate, end: NaiveDate) -> Result<DataFrame> {
+        self.load_date_range(AssetClass::Stocks, PolygonDataType::Trades, start, end, symbols, &DownloadOptions::default())
+            .await
+    }
+
+    async fn load_quotes(&self, symbols: &[&str], start: NaiveDate, end: NaiveDate) -> Result<DataFrame> {
+        self.load_date_range(AssetClass::Stocks, PolygonDataType::Quotes, start, end, symbols, &DownloadOptions::default())
+            .await
+    }
+}
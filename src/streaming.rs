@@ -3,11 +3,14 @@
 //! Provides capabilities for processing streaming financial data with
 //! real-time technical indicators and signal detection.
 
-use std::collections::VecDeque;
+use std::collections::{BTreeMap, VecDeque};
 use std::sync::{Arc, Mutex};
 use chrono::{DateTime, Utc};
+use datafusion::scalar::ScalarValue;
 use serde::{Deserialize, Serialize};
 
+use crate::indicator::{registered_indicator, IndicatorEvaluator};
+
 type Result<T> = std::result::Result<T, Box<dyn std::error::Error + Send + Sync>>;
 
 /// Real-time market data point
@@ -16,23 +19,35 @@ pub struct MarketTick {
     pub symbol: String,
     pub timestamp: DateTime<Utc>,
     pub price: f64,
+    /// Shares or units traded, rounded down when fractional
     pub volume: u64,
+    /// The exact volume, when it has a fraction (crypto and forex sizes)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fractional_volume: Option<f64>,
     pub bid: Option<f64>,
     pub ask: Option<f64>,
 }
 
+impl MarketTick {
+    /// The volume with its fraction, if any
+    pub fn exact_volume(&self) -> f64 {
+        self.fractional_volume.unwrap_or(self.volume as f64)
+    }
+}
+
 /// Streaming financial indicators calculator
 pub struct StreamingIndicators {
     _symbol: String,
     window_size: usize,
     prices: VecDeque<f64>,
-    volumes: VecDeque<u64>,
+    volumes: VecDeque<f64>,
     _sma_buffer: VecDeque<f64>,
     ema_value: Option<f64>,
     rsi_gains: VecDeque<f64>,
     rsi_losses: VecDeque<f64>,
     rsi_avg_gain: f64,
     rsi_avg_loss: f64,
+    custom: Vec<(String, Box<dyn IndicatorEvaluator>)>,
 }
 
 impl StreamingIndicators {
@@ -49,14 +64,33 @@ impl StreamingIndicators {
             rsi_losses: VecDeque::new(),
             rsi_avg_gain: 0.0,
             rsi_avg_loss: 0.0,
+            custom: Vec::new(),
         }
     }
 
+    /// Attach a registered custom indicator, reported under `alias`
+    ///
+    /// The indicator receives the tick price, then the tick volume, as inputs.
+    pub fn add_indicator(&mut self, alias: impl Into<String>, name: &str, params: &[ScalarValue]) -> Result<()> {
+        let indicator = registered_indicator(name)
+            .ok_or_else(|| format!("No indicator named '{}' is registered", name))?;
+        if indicator.inputs() > 2 {
+            return Err(format!(
+                "Indicator '{}' takes {} inputs; streaming provides price and volume",
+                name,
+                indicator.inputs()
+            )
+            .into());
+        }
+        self.custom.push((alias.into(), indicator.evaluator(params)?));
+        Ok(())
+    }
+
     /// Process new market tick and update indicators
     pub fn update(&mut self, tick: &MarketTick) -> StreamingIndicatorValues {
         // Add new price and volume
         self.prices.push_back(tick.price);
-        self.volumes.push_back(tick.volume);
+        self.volumes.push_back(tick.exact_volume());
 
         // Maintain window size
         if self.prices.len() > self.window_size {
@@ -69,17 +103,25 @@ impl StreamingIndicators {
         let ema = self.calculate_ema(tick.price);
         let rsi = self.calculate_rsi(tick.price);
         let volume_sma = self.calculate_volume_sma();
+        let inputs = [tick.price, tick.exact_volume()];
+        let custom = self
+            .custom
+            .iter_mut()
+            .map(|(alias, evaluator)| (alias.clone(), evaluator.update(&inputs)))
+            .collect();
 
         StreamingIndicatorValues {
             symbol: tick.symbol.clone(),
             timestamp: tick.timestamp,
             price: tick.price,
             volume: tick.volume,
+            fractional_volume: tick.fractional_volume,
             sma,
             ema,
             rsi,
             volume_sma,
-            volume_ratio: volume_sma.map(|vs| tick.volume as f64 / vs),
+            volume_ratio: volume_sma.map(|vs| tick.exact_volume() / vs),
+            custom,
         }
     }
 
@@ -155,8 +197,8 @@ impl StreamingIndicators {
             return None;
         }
 
-        let sum: u64 = self.volumes.iter().sum();
-        Some(sum as f64 / self.volumes.len() as f64)
+        let sum: f64 = self.volumes.iter().sum();
+        Some(sum / self.volumes.len() as f64)
     }
 }
 
@@ -167,11 +209,17 @@ pub struct StreamingIndicatorValues {
     pub timestamp: DateTime<Utc>,
     pub price: f64,
     pub volume: u64,
+    /// The tick's exact volume, when it has a fraction
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fractional_volume: Option<f64>,
     pub sma: Option<f64>,
     pub ema: Option<f64>,
     pub rsi: Option<f64>,
     pub volume_sma: Option<f64>,
     pub volume_ratio: Option<f64>,
+    /// Values of attached custom indicators, keyed by alias
+    #[serde(default)]
+    pub custom: BTreeMap<String, Option<f64>>,
 }
 
 /// Real-time signal detector
@@ -291,6 +339,11 @@ impl StreamingProcessor {
         }
     }
 
+    /// Attach a registered custom indicator, see [`StreamingIndicators::add_indicator`]
+    pub fn add_indicator(&mut self, alias: impl Into<String>, name: &str, params: &[ScalarValue]) -> Result<()> {
+        self.indicators.lock().unwrap().add_indicator(alias, name, params)
+    }
+
     /// Add signal handler callback
     pub fn add_signal_handler<F>(&mut self, handler: F)
     where
@@ -334,6 +387,7 @@ mod tests {
             timestamp: Utc::now(),
             price: 150.0,
             volume: 1000,
+            fractional_volume: None,
             bid: Some(149.5),
             ask: Some(150.5),
         };
@@ -350,11 +404,13 @@ mod tests {
             timestamp: Utc::now(),
             price: 150.0,
             volume: 1000,
+            fractional_volume: None,
             sma: Some(149.0),
             ema: Some(150.5),
             rsi: Some(25.0), // Oversold
             volume_sma: Some(500.0),
             volume_ratio: Some(2.5), // Volume spike
+            custom: BTreeMap::new(),
         };
 
         let detector = StreamingSignalDetector::new(indicators);
@@ -379,6 +435,7 @@ mod tests {
             timestamp: Utc::now(),
             price: 150.0,
             volume: 1000,
+            fractional_volume: None,
             bid: Some(149.5),
             ask: Some(150.5),
         };
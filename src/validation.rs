@@ -0,0 +1,184 @@
+//! Plan-time validation of period arguments
+//!
+//! The evaluators only see their arguments once execution starts, so a bad
+//! `sma(price, -5)` used to fail (or misbehave) deep inside `evaluate_all`.
+//! Each window function with period arguments declares them from its
+//! `simplify` method with [`period_simplification`]. DataFusion calls it
+//! while optimizing the plan, after constant folding, so periods that are
+//! literals or constant expressions such as `0 - 5` are rejected before any
+//! data is read, with the offending argument and the function's expected
+//! syntax. The wrappers (prefixed names, OHLCV structs, session variants,
+//! `last_*` aggregates) forward the hook, so their calls are checked too.
+//!
+//! Periods read from columns are only known at run time, where the
+//! evaluators' own checks remain authoritative.
+
+use datafusion::arrow::datatypes::DataType;
+use datafusion::error::{DataFusionError, Result};
+use datafusion::logical_expr::function::WindowFunctionSimplification;
+use datafusion::logical_expr::simplify::SimplifyInfo;
+use datafusion::logical_expr::Expr;
+use datafusion::scalar::ScalarValue;
+
+/// Largest accepted row-count period
+pub const MAX_PERIOD: i64 = 1_000_000;
+
+/// A period argument of a window function
+#[derive(Debug, Clone, Copy)]
+pub struct PeriodArg {
+    /// Position among the function's positional (non-struct) arguments
+    pub index: usize,
+    /// Name used in errors, as in the function's documented syntax
+    pub name: &'static str,
+    /// Upper bound, `None` when the period is not a row count (e.g. nanoseconds)
+    pub max: Option<i64>,
+}
+
+impl PeriodArg {
+    /// A row count, at most [`MAX_PERIOD`]
+    pub const fn rows(index: usize, name: &'static str) -> Self {
+        Self {
+            index,
+            name,
+            max: Some(MAX_PERIOD),
+        }
+    }
+
+    /// A period in other units, with no upper bound
+    pub const fn unbounded(index: usize, name: &'static str) -> Self {
+        Self { index, name, max: None }
+    }
+}
+
+/// `simplify` hook rejecting null, non-positive or oversized constant
+/// periods in calls to `function`
+///
+/// The call is returned unchanged; the hook only checks it.
+pub fn period_simplification(function: &str, periods: Vec<PeriodArg>) -> Option<WindowFunctionSimplification> {
+    let function = function.to_string();
+    Some(Box::new(move |window_function, info| {
+        let called = window_function.fun.to_string();
+        check_periods(&called, &function, &periods, &window_function.args, info)?;
+        Ok(Expr::WindowFunction(window_function))
+    }))
+}
+
+/// Check the constant periods among `args` of a call to `called`, a name
+/// under which `function` is registered
+fn check_periods(called: &str, function: &str, periods: &[PeriodArg], args: &[Expr], info: &dyn SimplifyInfo) -> Result<()> {
+    // A leading OHLCV struct stands in for the price columns before the first period
+    let struct_arg = args
+        .first()
+        .and_then(|arg| info.get_data_type(arg).ok())
+        .is_some_and(|arg_type| matches!(arg_type, DataType::Struct(_)));
+    let shift = match (struct_arg, periods.first()) {
+        (true, Some(first)) => first.index.saturating_sub(1),
+        _ => 0,
+    };
+
+    for period_arg in periods {
+        let Some(value) = args.get(period_arg.index - shift).and_then(literal_value) else {
+            continue;
+        };
+
+        let period = match value.cast_to(&DataType::Int64) {
+            Ok(ScalarValue::Int64(period)) => period,
+            _ => continue,
+        };
+        let problem = match period {
+            None => "must not be null".to_string(),
+            Some(p) if p <= 0 => format!("must be a positive integer, got {}", p),
+            Some(p) => match period_arg.max {
+                Some(max) if p > max => format!("must be at most {}, got {}", max, p),
+                _ => continue,
+            },
+        };
+
+        return Err(DataFusionError::Plan(format!(
+            "{}: {} {}{}",
+            called,
+            period_arg.name,
+            problem,
+            usage(called, function)
+        )));
+    }
+    Ok(())
+}
+
+/// `function`'s documented syntax, spelled as called
+fn usage(called: &str, function: &str) -> String {
+    let Some(doc) = crate::docs::documentation(function) else {
+        return String::new();
+    };
+    let params = doc.syntax.strip_prefix(function).unwrap_or(doc.syntax);
+    // Session variants take the timestamp ahead of the function's own arguments
+    let params = match called.ends_with(&format!("session_{}", function)) {
+        true => params.replacen('(', "(timestamp, ", 1),
+        false => params.to_string(),
+    };
+    format!(". Expected {}{}", called, params)
+}
+
+/// The constant value of a literal argument, looking through casts and negation
+fn literal_value(expr: &Expr) -> Option<ScalarValue> {
+    match expr {
+        Expr::Literal(value) => Some(value.clone()),
+        Expr::Cast(cast) => literal_value(&cast.expr),
+        Expr::TryCast(cast) => literal_value(&cast.expr),
+        Expr::Negative(inner) => literal_value(inner)?.arithmetic_negate().ok(),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use datafusion::execution::context::SessionContext;
+
+    #[tokio::test]
+    async fn test_argument_validation() -> Result<()> {
+        let ctx = SessionContext::new();
+        crate::FinancialFunctions::all().with_sessions().register(&ctx)?;
+
+        let values = "(VALUES (1.0, 2.0, 0.5), (2.0, 3.0, 1.5), (3.0, 4.0, 2.5)) AS t(price, high, low)";
+
+        // Valid periods plan and run as before
+        let result = ctx
+            .sql(&format!("SELECT price, sma(price, 2) OVER () AS sma_2 FROM {}", values))
+            .await?
+            .collect()
+            .await?;
+
+        println!("Argument Validation Test Results:");
+        datafusion::arrow::util::pretty::print_batches(&result)?;
+
+        // Invalid literal periods are rejected while planning, before execution
+        for query in [
+            "SELECT sma(price, -5) OVER () FROM",
+            "SELECT sma(price, 0) OVER () FROM",
+            "SELECT rsi(price, CAST(NULL AS BIGINT)) OVER () FROM",
+            "SELECT donchian(high, low, 5000000) OVER () FROM",
+            "SELECT last_sma(price, -1) FROM",
+            "SELECT atr(named_struct('high', high, 'low', low, 'close', price), 0) OVER () FROM",
+            "SELECT sma(price, 0 - 5) OVER () FROM",
+            "SELECT session_sma(1, price, 0) OVER () FROM",
+        ] {
+            let err = ctx
+                .sql(&format!("{} {}", query, values))
+                .await?
+                .into_optimized_plan()
+                .unwrap_err();
+            println!("{}", err);
+            assert!(err.to_string().contains("Expected"), "{}", err);
+        }
+
+        // Periods read from columns are left to the evaluators at run time
+        let df = ctx
+            .sql(&format!("SELECT sma(price, CAST(price AS BIGINT) - 5) OVER () FROM {}", values))
+            .await?;
+        assert!(df.clone().into_optimized_plan().is_ok());
+        assert!(df.collect().await.is_err());
+
+        Ok(())
+    }
+}
@@ -0,0 +1,364 @@
+//! Yahoo Finance daily bars
+//!
+//! [`YahooClient`] loads free daily OHLCV bars from Yahoo Finance's chart
+//! API with the schema of [`PolygonClient::load_day_aggs`]: Polygon's stock
+//! day aggregates columns, with `window_start` at midnight of the trading
+//! day in the exchange's time zone. Validation, signals and bar transforms
+//! therefore take them unchanged, which makes this handy for examples,
+//! tests and anyone without a Polygon subscription. Yahoo doesn't publish
+//! trade counts, so `transactions` is null, and prices are unadjusted.
+//!
+//! The chart API is unofficial and may change or throttle without notice;
+//! don't rely on it for anything that matters.
+//!
+//! [`PolygonClient::load_day_aggs`]: crate::polygon::PolygonClient::load_day_aggs
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use chrono::{DateTime, Days, NaiveDate};
+use chrono_tz::Tz;
+use datafusion::arrow::array::{ArrayRef, Float64Array, Int64Array, RecordBatch, StringArray};
+use datafusion::arrow::datatypes::{DataType, Field, Schema};
+use datafusion::dataframe::DataFrame;
+use datafusion::error::{DataFusionError, Result};
+use datafusion::execution::context::SessionContext;
+use serde::Deserialize;
+
+use crate::polygon::{with_retries, RetryConfig};
+use crate::source::{BarTimeframe, MarketDataSource};
+
+/// Base URL of Yahoo Finance's chart API
+pub const YAHOO_CHART_URL: &str = "https://query1.finance.yahoo.com/v8/finance/chart";
+
+/// Yahoo rejects requests without a browser-like user agent
+const USER_AGENT: &str = "Mozilla/5.0 (compatible; datafusion-functions-financial)";
+
+#[derive(Debug, Deserialize)]
+struct ChartResponse {
+    chart: Chart,
+}
+
+#[derive(Debug, Deserialize)]
+struct Chart {
+    result: Option<Vec<ChartResult>>,
+    error: Option<ChartError>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ChartError {
+    code: String,
+    description: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct ChartResult {
+    meta: ChartMeta,
+    #[serde(default)]
+    timestamp: Vec<i64>,
+    indicators: Indicators,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ChartMeta {
+    exchange_timezone_name: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct Indicators {
+    quote: Vec<ChartQuote>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct ChartQuote {
+    #[serde(default)]
+    open: Vec<Option<f64>>,
+    #[serde(default)]
+    high: Vec<Option<f64>>,
+    #[serde(default)]
+    low: Vec<Option<f64>>,
+    #[serde(default)]
+    close: Vec<Option<f64>>,
+    #[serde(default)]
+    volume: Vec<Option<i64>>,
+}
+
+/// One daily bar
+#[derive(Debug)]
+struct DayBar {
+    window_start: i64,
+    open: f64,
+    high: f64,
+    low: f64,
+    close: f64,
+    volume: i64,
+}
+
+/// Yahoo Finance daily bar client
+#[derive(Clone)]
+pub struct YahooClient {
+    base_url: String,
+    retry: RetryConfig,
+    ctx: SessionContext,
+    http: reqwest::Client,
+}
+
+impl Default for YahooClient {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl YahooClient {
+    pub fn new() -> Self {
+        Self::with_context(SessionContext::new())
+    }
+
+    /// Create a client that loads into a caller-owned `ctx`
+    pub fn with_context(ctx: SessionContext) -> Self {
+        Self {
+            base_url: YAHOO_CHART_URL.to_string(),
+            retry: RetryConfig::default(),
+            ctx,
+            http: reqwest::Client::new(),
+        }
+    }
+
+    pub fn with_retry(mut self, retry: RetryConfig) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    /// Get the session context for custom queries
+    pub fn session_context(&self) -> &SessionContext {
+        &self.ctx
+    }
+
+    /// Daily bars of `symbols` for the trading days `start` to `end`
+    ///
+    /// Symbols are Yahoo's, e.g. `AAPL`, `BRK-B` or `^GSPC`. Days Yahoo
+    /// returns without a close, such as halts, are skipped.
+    pub async fn load_day_aggs(&self, symbols: &[&str], start: NaiveDate, end: NaiveDate) -> Result<DataFrame> {
+        let mut rows = Vec::new();
+        for symbol in symbols {
+            for bar in self.fetch_day_bars(symbol, start, end).await? {
+                rows.push((*symbol, bar));
+            }
+        }
+
+        self.ctx.read_batch(day_aggs_batch(&rows)?)
+    }
+
+    async fn fetch_day_bars(&self, symbol: &str, start: NaiveDate, end: NaiveDate) -> Result<Vec<DayBar>> {
+        // period2 is exclusive; a day past `end` covers it in any time zone
+        let seconds = |date: NaiveDate| date.and_hms_opt(0, 0, 0).expect("midnight is valid").and_utc().timestamp();
+        let after_end = end
+            .checked_add_days(Days::new(2))
+            .ok_or_else(|| DataFusionError::Execution(format!("Date out of range: {}", end)))?;
+        let url = format!("{}/{}", self.base_url, symbol);
+        let query = [
+            ("period1", seconds(start - Days::new(1)).to_string()),
+            ("period2", seconds(after_end).to_string()),
+            ("interval", "1d".to_string()),
+        ];
+
+        let (url, query) = (&url, &query);
+        let response: ChartResponse = with_retries(&self.retry, || async move {
+            self.http
+                .get(url)
+                .header(reqwest::header::USER_AGENT, USER_AGENT)
+                .query(query)
+                .send()
+                .await
+                .and_then(|response| response.error_for_status())
+                .map_err(external)?
+                .json::<ChartResponse>()
+                .await
+                .map_err(external)
+        })
+        .await?;
+
+        day_bars(response, symbol, start, end)
+    }
+}
+
+/// Daily bars only; minute bars, trades and quotes return `NotImplemented`
+#[async_trait]
+impl MarketDataSource for YahooClient {
+    async fn load_bars(
+        &self,
+        symbols: &[&str],
+        timeframe: BarTimeframe,
+        start: NaiveDate,
+        end: NaiveDate,
+    ) -> Result<DataFrame> {
+        match timeframe {
+            BarTimeframe::Day => self.load_day_aggs(symbols, start, end).await,
+            BarTimeframe::Minute => {
+                Err(DataFusionError::NotImplemented("Yahoo Finance loader only provides daily bars".to_string()))
+            }
+        }
+    }
+
+    async fn load_trades(&self, _symbols: &[&str], _start: NaiveDate, _end: NaiveDate) -> Result<DataFrame> {
+        Err(DataFusionError::NotImplemented("Yahoo Finance has no trades".to_string()))
+    }
+
+    async fn load_quotes(&self, _symbols: &[&str], _start: NaiveDate, _end: NaiveDate) -> Result<DataFrame> {
+        Err(DataFusionError::NotImplemented("Yahoo Finance has no quotes".to_string()))
+    }
+}
+
+/// Day aggregates of each symbol's bars, with a null `transactions` column
+fn day_aggs_batch(rows: &[(&str, DayBar)]) -> Result<RecordBatch> {
+    let columns = vec![
+        Arc::new(rows.iter().map(|(symbol, _)| Some(*symbol)).collect::<StringArray>()) as ArrayRef,
+        Arc::new(rows.iter().map(|(_, b)| b.volume).collect::<Int64Array>()),
+        Arc::new(rows.iter().map(|(_, b)| b.open).collect::<Float64Array>()),
+        Arc::new(rows.iter().map(|(_, b)| b.close).collect::<Float64Array>()),
+        Arc::new(rows.iter().map(|(_, b)| b.high).collect::<Float64Array>()),
+        Arc::new(rows.iter().map(|(_, b)| b.low).collect::<Float64Array>()),
+        Arc::new(rows.iter().map(|(_, b)| b.window_start).collect::<Int64Array>()),
+        Arc::new(Int64Array::new_null(rows.len())),
+    ];
+    let schema = Schema::new(vec![
+        Field::new("ticker", DataType::Utf8, false),
+        Field::new("volume", DataType::Int64, false),
+        Field::new("open", DataType::Float64, false),
+        Field::new("close", DataType::Float64, false),
+        Field::new("high", DataType::Float64, false),
+        Field::new("low", DataType::Float64, false),
+        Field::new("window_start", DataType::Int64, false),
+        Field::new("transactions", DataType::Int64, true),
+    ]);
+    Ok(RecordBatch::try_new(Arc::new(schema), columns)?)
+}
+
+/// The bars of a chart response for `symbol` on the days `start` to `end`,
+/// skipping days without a full set of prices
+fn day_bars(response: ChartResponse, symbol: &str, start: NaiveDate, end: NaiveDate) -> Result<Vec<DayBar>> {
+    if let Some(error) = response.chart.error {
+        return Err(DataFusionError::Execution(format!(
+            "Yahoo Finance error for {}: {} ({})",
+            symbol, error.description, error.code
+        )));
+    }
+    let Some(result) = response.chart.result.and_then(|results| results.into_iter().next()) else {
+        return Ok(Vec::new());
+    };
+    let timezone: Tz = result.meta.exchange_timezone_name.parse().map_err(|e| {
+        DataFusionError::Execution(format!("Unknown exchange time zone for {}: {}", symbol, e))
+    })?;
+    let quote = result.indicators.quote.into_iter().next().unwrap_or_default();
+
+    let mut bars = Vec::new();
+    for (i, &timestamp) in result.timestamp.iter().enumerate() {
+        let at = |values: &[Option<f64>]| values.get(i).copied().flatten();
+        let (Some(open), Some(high), Some(low), Some(close)) =
+            (at(&quote.open), at(&quote.high), at(&quote.low), at(&quote.close))
+        else {
+            continue;
+        };
+        let Some(opened) = DateTime::from_timestamp(timestamp, 0) else {
+            continue;
+        };
+        let day = opened.with_timezone(&timezone).date_naive();
+        if day < start || day > end {
+            continue;
+        }
+        let Some(window_start) = day
+            .and_hms_opt(0, 0, 0)
+            .and_then(|midnight| midnight.and_local_timezone(timezone).earliest())
+            .and_then(|midnight| midnight.timestamp_nanos_opt())
+        else {
+            continue;
+        };
+        bars.push(DayBar {
+            window_start,
+            open,
+            high,
+            low,
+            close,
+            volume: quote.volume.get(i).copied().flatten().unwrap_or(0),
+        });
+    }
+    Ok(bars)
+}
+
+fn external(error: reqwest::Error) -> DataFusionError {
+    DataFusionError::External(Box::new(error))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use datafusion::arrow::array::{Array, AsArray};
+    use datafusion::arrow::datatypes::{Float64Type, Int64Type};
+
+    /// AAPL around New Year 2024, trimmed from a chart response: a bar
+    /// before the range, a halted day without prices, and a 4:1 adjusted close
+    const CHART: &str = r#"{"chart":{"result":[{
+        "meta":{"symbol":"AAPL","exchangeTimezoneName":"America/New_York"},
+        "timestamp":[1703860200,1704205800,1704292200,1704378600,1704465000],
+        "indicators":{
+            "quote":[{
+                "open":[193.9,187.15,184.22,null,181.99],
+                "high":[194.4,188.44,185.88,null,182.76],
+                "low":[191.73,183.89,183.43,null,180.17],
+                "close":[192.53,185.64,184.25,null,181.18],
+                "volume":[42628800,82488700,null,null,62303300]
+            }],
+            "adjclose":[{"adjclose":[48.13,46.41,46.06,null,45.3]}]
+        }
+    }],"error":null}}"#;
+
+    fn day(d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2024, 1, d).unwrap()
+    }
+
+    fn parse(json: &str) -> ChartResponse {
+        serde_json::from_str(json).unwrap()
+    }
+
+    #[test]
+    fn test_day_bars() -> Result<()> {
+        let bars = day_bars(parse(CHART), "AAPL", day(1), day(5))?;
+
+        // The bar before the range and the day without prices are skipped
+        assert_eq!(bars.len(), 3);
+        // Closes are raw, not the adjusted closes
+        let closes = bars.iter().map(|bar| bar.close).collect::<Vec<_>>();
+        assert_eq!(closes, [185.64, 184.25, 181.18]);
+        // A missing volume reads as zero
+        assert_eq!(bars.iter().map(|bar| bar.volume).collect::<Vec<_>>(), [82488700, 0, 62303300]);
+
+        // window_start is midnight New York time: 05:00 UTC in January
+        assert_eq!(bars[0].window_start, 1_704_171_600_000_000_000);
+        assert_eq!(bars[2].window_start - bars[0].window_start, 3 * 86_400_000_000_000);
+
+        let rows = bars.into_iter().map(|bar| ("AAPL", bar)).collect::<Vec<_>>();
+        let batch = day_aggs_batch(&rows)?;
+        let names = batch.schema().fields().iter().map(|f| f.name().clone()).collect::<Vec<_>>();
+        assert_eq!(names, ["ticker", "volume", "open", "close", "high", "low", "window_start", "transactions"]);
+        assert_eq!(batch.column(2).as_primitive::<Float64Type>().values().to_vec(), [187.15, 184.22, 181.99]);
+        assert_eq!(batch.column(6).as_primitive::<Int64Type>().value(0), 1_704_171_600_000_000_000);
+        assert_eq!(batch.column(7).null_count(), 3);
+        Ok(())
+    }
+
+    #[test]
+    fn test_chart_errors() {
+        let not_found = parse(
+            r#"{"chart":{"result":null,"error":{"code":"Not Found","description":"No data found, symbol may be delisted"}}}"#,
+        );
+        let error = day_bars(not_found, "NOPE", day(1), day(5)).unwrap_err();
+        assert!(error.to_string().contains("No data found"), "{}", error);
+
+        let empty = parse(r#"{"chart":{"result":[],"error":null}}"#);
+        assert!(day_bars(empty, "AAPL", day(1), day(5)).unwrap().is_empty());
+
+        let bad_zone = parse(&CHART.replace("America/New_York", "Nowhere/Special"));
+        assert!(day_bars(bad_zone, "AAPL", day(1), day(5)).is_err());
+    }
+}